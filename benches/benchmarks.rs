@@ -1,6 +1,7 @@
 use criterion::*;
 use itertools::*;
 
+use legion::pod::{Pod, PodRegistry};
 use legion::prelude::*;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -30,6 +31,12 @@ struct Position(f32);
 #[derive(Copy, Clone, Debug, PartialEq)]
 struct Rotation(f32);
 
+unsafe impl Pod for Position {}
+unsafe impl Pod for Rotation {}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Marker<const N: usize>(f32);
+
 fn create_entities(
     world: &mut World,
     variants: &mut [Box<dyn FnMut(Entity, &mut World)>],
@@ -113,6 +120,26 @@ fn bench_iter_simple(c: &mut Criterion) {
     });
 }
 
+fn bench_iter_large(c: &mut Criterion) {
+    c.bench_function("iter-large", |b| {
+        let mut world = setup(0);
+        for i in 0..64 {
+            world.insert(
+                (Tag(i as f32),),
+                (0..16_000).map(|_| (Position(0.), Rotation(0.))),
+            );
+        }
+
+        let query = <(Read<Position>, Write<Rotation>)>::query();
+
+        b.iter(|| {
+            for (pos, mut rot) in query.iter(&mut world) {
+                rot.0 = pos.0;
+            }
+        });
+    });
+}
+
 fn bench_iter_complex(c: &mut Criterion) {
     c.bench_function("iter-complex", |b| {
         let mut world = setup(0);
@@ -184,12 +211,175 @@ fn bench_iter_chunks_complex(c: &mut Criterion) {
     });
 }
 
+fn bench_iter_batched(c: &mut Criterion) {
+    c.bench(
+        "iter-batched",
+        Benchmark::new("scalar", |b| {
+            let mut world = setup(10000);
+            let query = <(Read<Position>, Write<Rotation>)>::query();
+
+            b.iter(|| {
+                for (pos, mut rot) in query.iter(&mut world) {
+                    rot.0 += pos.0;
+                }
+            });
+        })
+        .with_function("batched-8", |b| {
+            let mut world = setup(10000);
+            let query = <(Read<Position>, Write<Rotation>)>::query();
+
+            b.iter(|| {
+                query.for_each_batched::<8, _, _>(
+                    &mut world,
+                    |(pos, rot): (&[Position; 8], &mut [Rotation; 8])| {
+                        for i in 0..8 {
+                            rot[i].0 += pos[i].0;
+                        }
+                    },
+                    |(pos, rot)| rot.0 += pos.0,
+                );
+            });
+        }),
+    );
+}
+
+fn bench_iter_internal(c: &mut Criterion) {
+    c.bench(
+        "iter-1m",
+        Benchmark::new("external", |b| {
+            let mut world = setup(1_000_000);
+            let query = <(Read<Position>, Write<Rotation>)>::query();
+
+            b.iter(|| {
+                for (pos, mut rot) in query.iter(&mut world) {
+                    rot.0 += pos.0;
+                }
+            });
+        })
+        .with_function("internal", |b| {
+            let mut world = setup(1_000_000);
+            let query = <(Read<Position>, Write<Rotation>)>::query();
+
+            b.iter(|| {
+                query.for_each(&mut world, |(pos, mut rot)| rot.0 += pos.0);
+            });
+        })
+        .sample_size(20),
+    );
+}
+
+fn bench_insert_from_fn(c: &mut Criterion) {
+    c.bench(
+        "insert-1m",
+        Benchmark::new("iterator", |b| {
+            b.iter(|| {
+                let mut world = setup(0);
+                world.insert(
+                    (),
+                    (0..1_000_000).map(|i| (Position(i as f32), Rotation(0.))),
+                );
+                world
+            });
+        })
+        .with_function("from-fn", |b| {
+            b.iter(|| {
+                let mut world = setup(0);
+                world.insert_from_fn((), 1_000_000, |i| (Position(i as f32), Rotation(0.)));
+                world
+            });
+        })
+        .with_function("pod-bytes", |b| {
+            let mut registry = PodRegistry::new();
+            registry
+                .register::<Position>("position")
+                .register::<Rotation>("rotation");
+            let mut source = setup(0);
+            source.insert_from_fn((), 1_000_000, |i| (Position(i as f32), Rotation(0.)));
+            let bytes = source.write_chunks_to_bytes(&registry).unwrap();
+
+            b.iter(|| {
+                let mut world = setup(0);
+                world.load_chunks_from_bytes(&bytes, &registry).unwrap();
+                world
+            });
+        })
+        .sample_size(10),
+    );
+}
+
+fn bench_iter_changed(c: &mut Criterion) {
+    c.bench_function("iter-changed-many-chunks", |b| {
+        let mut world = setup(0);
+        for i in 0..2000 {
+            world.insert(
+                (Tag(i as f32),),
+                (0..8).map(|_| (Position(0.), Rotation(0.))),
+            );
+        }
+
+        let query = Read::<Position>::query().filter(changed::<Position>());
+        let write = Write::<Position>::query().filter(tag_value(&Tag(0.)));
+
+        b.iter(|| {
+            for mut pos in write.iter(&mut world) {
+                pos.0 += 1.;
+            }
+            query.iter(&mut world).count()
+        });
+    });
+}
+
+fn bench_iter_many_archetypes(c: &mut Criterion) {
+    c.bench_function("iter-narrow-many-archetypes", |b| {
+        let mut world = setup(0);
+
+        // one archetype for each of the 4096 subsets of 12 marker components
+        let mut markers: Vec<Box<dyn FnMut(Entity, &mut World)>> = vec![
+            Box::new(|e, w| w.add_component(e, Marker::<0>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<1>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<2>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<3>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<4>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<5>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<6>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<7>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<8>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<9>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<10>(0.0))),
+            Box::new(|e, w| w.add_component(e, Marker::<11>(0.0))),
+        ];
+        for mask in 0..(1 << markers.len()) {
+            let entity = world.insert((), Some((A(0.0),)))[0];
+            for (i, marker) in markers.iter_mut().enumerate() {
+                if mask & (1 << i) != 0 {
+                    marker(entity, &mut world);
+                }
+            }
+        }
+        world.insert((), (0..1000).map(|_| (Position(0.), Rotation(0.))));
+
+        let query = <(Read<Position>, Write<Rotation>)>::query();
+
+        b.iter(|| {
+            for (pos, mut rot) in query.iter(&mut world) {
+                rot.0 = pos.0;
+            }
+        });
+    });
+}
+
 criterion_group!(
     basic,
     bench_create_delete,
     bench_iter_simple,
+    bench_iter_large,
     bench_iter_complex,
     bench_iter_chunks_simple,
-    bench_iter_chunks_complex
+    bench_iter_chunks_complex,
+    bench_iter_batched,
+    bench_iter_internal,
+    bench_insert_from_fn,
+    bench_iter_changed,
+    bench_iter_many_archetypes
 );
 criterion_main!(basic);
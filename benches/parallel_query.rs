@@ -143,6 +143,35 @@ fn par_for_each(world: &mut World) {
     );
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Group(u8);
+
+fn setup_tagged(data: &[Variants]) -> World {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    for (i, chunk) in data.chunks(256).enumerate() {
+        world.insert(
+            (Group((i % 4) as u8),),
+            chunk.iter().map(|_| (A(0.0), B(0.0))),
+        );
+    }
+
+    world
+}
+
+fn par_for_each_untagged(world: &mut World) {
+    <(Write<B>, Read<A>)>::query().par_for_each(world, |(mut b, a)| {
+        b.0 = a.0;
+    });
+}
+
+fn par_for_each_tagged(world: &mut World) {
+    <(Write<B>, Read<A>, Tagged<Group>)>::query().par_for_each(world, |(mut b, a, group)| {
+        b.0 = a.0 + group.0 as f32;
+    });
+}
+
 fn bench_ordered(c: &mut Criterion) {
     c.bench(
         "concurrent queries",
@@ -169,6 +198,16 @@ fn bench_ordered(c: &mut Criterion) {
             let data = data(*n);
             let mut world = setup(&data);
             join(|| {}, || b.iter(|| par_for_each(&mut world)));
+        })
+        .with_function("par_for_each untagged", |b, n| {
+            let data = data(*n);
+            let mut world = setup_tagged(&data);
+            join(|| {}, || b.iter(|| par_for_each_untagged(&mut world)));
+        })
+        .with_function("par_for_each tagged", |b, n| {
+            let data = data(*n);
+            let mut world = setup_tagged(&data);
+            join(|| {}, || b.iter(|| par_for_each_tagged(&mut world)));
         }),
     );
 }
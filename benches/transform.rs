@@ -83,6 +83,18 @@ fn par_for_each(world: &mut World) {
     });
 }
 
+fn par_for_each_batched(world: &mut World) {
+    <(
+        Read<Position>,
+        Read<Orientation>,
+        Read<Scale>,
+        Write<Transform>,
+    )>::query()
+    .par_for_each_batched(world, 256, |(pos, orient, scale, trans)| {
+        trans.0 = process(&pos.0, &orient.0, &scale.0);
+    });
+}
+
 fn bench_transform(c: &mut Criterion) {
     c.bench(
         "update transform (experimental)",
@@ -103,6 +115,11 @@ fn bench_transform(c: &mut Criterion) {
             let data = data(*n);
             let mut world = setup(data);
             join(|| {}, || b.iter(|| par_for_each(&mut world)));
+        })
+        .with_function("par_for_each_batched", |b, n| {
+            let data = data(*n);
+            let mut world = setup(data);
+            join(|| {}, || b.iter(|| par_for_each_batched(&mut world)));
         }),
     );
 }
@@ -7,7 +7,7 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 use std::sync::atomic::AtomicIsize;
 
-#[cfg(not(debug_assertions))]
+#[cfg(not(any(debug_assertions, feature = "borrow-checks")))]
 use std::marker::PhantomData;
 
 /// A `RefCell` implementation which is thread safe. This type performs all the standard runtime
@@ -17,8 +17,9 @@ use std::marker::PhantomData;
 /// garunteeing safe access across threads.
 ///
 /// # Safety
-/// Runtime borrow checking is only conducted in builds with `debug_assertions` enabled. Release
-/// builds assume proper resource access and will cause undefined behavior with improper use.
+/// Runtime borrow checking is only conducted in builds with `debug_assertions` or the
+/// `borrow-checks` feature enabled. Other builds assume proper resource access and will cause
+/// undefined behavior with improper use.
 pub struct AtomicRefCell<T> {
     value: UnsafeCell<T>,
     borrow_state: AtomicIsize,
@@ -49,14 +50,26 @@ impl<T> AtomicRefCell<T> {
     /// This method panics if this value is already mutably borrowed.
     ///
     /// # Safety
-    /// Runtime borrow checking is only conducted in builds with `debug_assertions` enabled. Release
-    /// builds assume proper resource access and will cause undefined behavior with improper use.
+    /// Runtime borrow checking is only conducted in builds with `debug_assertions` or the
+    /// `borrow-checks` feature enabled. Other builds assume proper resource access and will cause
+    /// undefined behavior with improper use.
     #[inline(always)]
     pub fn get(&self) -> Ref<T> { self.try_get().unwrap() }
 
     /// Unwrap the value from the RefCell and kill it, returning the value.
     pub fn into_inner(self) -> T { self.value.into_inner() }
 
+    /// Releases any borrows which are still recorded against this cell.
+    ///
+    /// As this requires exclusive access to the cell, any recorded borrows must have been leaked
+    /// (e.g. with `mem::forget`). Returns `true` if the cell was recorded as borrowed.
+    pub fn reset_borrows(&mut self) -> bool {
+        let state = self.borrow_state.get_mut();
+        let borrowed = *state != 0;
+        *state = 0;
+        borrowed
+    }
+
     /// Retrieve an immutable `Ref` wrapped reference of `&T`. This is the safe version of `get`
     /// providing an error result on failure.
     ///
@@ -64,7 +77,7 @@ impl<T> AtomicRefCell<T> {
     ///
     /// `Some(T)` if the value can be retrieved.
     /// `Err` if the value is already mutably borrowed.
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "borrow-checks"))]
     pub fn try_get(&self) -> Result<Ref<T>, &'static str> {
         loop {
             let read = self.borrow_state.load(std::sync::atomic::Ordering::SeqCst);
@@ -100,7 +113,7 @@ impl<T> AtomicRefCell<T> {
     /// This release version of this function does not perform runtime borrow checking and will
     /// cause undefined behavior if borrow rules are violated. This means they should be enforced
     /// on the use of this type.
-    #[cfg(not(debug_assertions))]
+    #[cfg(not(any(debug_assertions, feature = "borrow-checks")))]
     #[inline(always)]
     pub fn try_get(&self) -> Result<Ref<T>, &'static str> {
         Ok(Ref::new(Shared::new(&self.borrow_state), unsafe {
@@ -115,8 +128,9 @@ impl<T> AtomicRefCell<T> {
     /// This method panics if this value is already mutably borrowed.
     ///
     /// # Safety
-    /// Runtime borrow checking is only conducted in builds with `debug_assertions` enabled. Release
-    /// builds assume proper resource access and will cause undefined behavior with improper use.
+    /// Runtime borrow checking is only conducted in builds with `debug_assertions` or the
+    /// `borrow-checks` feature enabled. Other builds assume proper resource access and will cause
+    /// undefined behavior with improper use.
     #[inline(always)]
     pub fn get_mut(&self) -> RefMut<T> { self.try_get_mut().unwrap() }
 
@@ -133,7 +147,7 @@ impl<T> AtomicRefCell<T> {
     /// This release version of this function does not perform runtime borrow checking and will
     /// cause undefined behavior if borrow rules are violated. This means they should be enforced
     /// on the use of this type.
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "borrow-checks"))]
     pub fn try_get_mut(&self) -> Result<RefMut<T>, &'static str> {
         let borrowed =
             self.borrow_state
@@ -160,7 +174,7 @@ impl<T> AtomicRefCell<T> {
     /// This release version of this function does not perform runtime borrow checking and will
     /// cause undefined behavior if borrow rules are violated. This means they should be enforced
     /// on the use of this type.
-    #[cfg(not(debug_assertions))]
+    #[cfg(not(any(debug_assertions, feature = "borrow-checks")))]
     #[inline(always)]
     pub fn try_get_mut(&self) -> Result<RefMut<T>, &'static str> {
         Ok(RefMut::new(Exclusive::new(&self.borrow_state), unsafe {
@@ -169,6 +183,67 @@ impl<T> AtomicRefCell<T> {
     }
 }
 
+/// Determines what happens when component data cannot be borrowed because it is already
+/// borrowed elsewhere. See `World::set_borrow_conflict_policy`.
+///
+/// Runtime borrow checks are only performed in builds with `debug_assertions` or the
+/// `borrow-checks` feature enabled; in other builds conflicts are not detected under any policy.
+///
+/// Query iteration checks each chunk for conflicts before borrowing its components. A
+/// conflicting borrow taken by another thread between that check and the borrow itself is not
+/// skipped, and panics as it would under `Panic`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum BorrowConflictPolicy {
+    /// Panic on conflict. This is the default.
+    #[default]
+    Panic,
+    /// Return a `BorrowConflict` error from fallible accessors, return `None` from
+    /// `World::get_component` and friends, and skip the conflicting chunks during query
+    /// iteration. Every conflict is logged as a warning.
+    ReturnError,
+    /// Behave as `Panic` in builds with `debug_assertions` enabled, and as `ReturnError` in
+    /// other builds. Release builds only detect conflicts with the `borrow-checks` feature.
+    PanicInDebugSkipInRelease,
+}
+
+impl BorrowConflictPolicy {
+    /// Determines if conflicts are reported and skipped over, rather than panicking.
+    pub fn skips(self) -> bool {
+        match self {
+            BorrowConflictPolicy::Panic => false,
+            BorrowConflictPolicy::ReturnError => true,
+            BorrowConflictPolicy::PanicInDebugSkipInRelease => !cfg!(debug_assertions),
+        }
+    }
+}
+
+/// An error returned when component data cannot be borrowed because it is already borrowed
+/// elsewhere.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct BorrowConflict {
+    /// The name of the component type which could not be borrowed.
+    pub component: &'static str,
+    /// Whether the component was to be borrowed mutably.
+    pub mutable: bool,
+    /// Why the borrow failed.
+    pub reason: &'static str,
+}
+
+impl std::fmt::Display for BorrowConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot borrow {} {}: {}",
+            self.component,
+            if self.mutable { "mutably" } else { "immutably" },
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for BorrowConflict {}
+
 unsafe impl<T: Send> Send for AtomicRefCell<T> {}
 
 unsafe impl<T: Sync> Sync for AtomicRefCell<T> {}
@@ -188,21 +263,21 @@ impl<A: UnsafeClone, B: UnsafeClone> UnsafeClone for (A, B) {
 
 #[derive(Debug)]
 pub struct Shared<'a> {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "borrow-checks"))]
     state: &'a AtomicIsize,
-    #[cfg(not(debug_assertions))]
+    #[cfg(not(any(debug_assertions, feature = "borrow-checks")))]
     state: PhantomData<&'a ()>,
 }
 
 impl<'a> Shared<'a> {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "borrow-checks"))]
     fn new(state: &'a AtomicIsize) -> Self { Self { state } }
-    #[cfg(not(debug_assertions))]
+    #[cfg(not(any(debug_assertions, feature = "borrow-checks")))]
     #[inline(always)]
     fn new(_: &'a AtomicIsize) -> Self { Self { state: PhantomData } }
 }
 
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "borrow-checks"))]
 impl<'a> Drop for Shared<'a> {
     fn drop(&mut self) { self.state.fetch_sub(1, std::sync::atomic::Ordering::SeqCst); }
 }
@@ -210,7 +285,7 @@ impl<'a> Drop for Shared<'a> {
 impl<'a> Clone for Shared<'a> {
     #[inline(always)]
     fn clone(&self) -> Self {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "borrow-checks"))]
         self.state.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
         Shared { state: self.state }
     }
@@ -222,21 +297,21 @@ impl<'a> UnsafeClone for Shared<'a> {
 
 #[derive(Debug)]
 pub struct Exclusive<'a> {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "borrow-checks"))]
     state: &'a AtomicIsize,
-    #[cfg(not(debug_assertions))]
+    #[cfg(not(any(debug_assertions, feature = "borrow-checks")))]
     state: PhantomData<&'a ()>,
 }
 
 impl<'a> Exclusive<'a> {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "borrow-checks"))]
     fn new(state: &'a AtomicIsize) -> Self { Self { state } }
-    #[cfg(not(debug_assertions))]
+    #[cfg(not(any(debug_assertions, feature = "borrow-checks")))]
     #[inline(always)]
     fn new(_: &'a AtomicIsize) -> Self { Self { state: PhantomData } }
 }
 
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "borrow-checks"))]
 impl<'a> Drop for Exclusive<'a> {
     fn drop(&mut self) { self.state.fetch_add(1, std::sync::atomic::Ordering::SeqCst); }
 }
@@ -244,7 +319,7 @@ impl<'a> Drop for Exclusive<'a> {
 impl<'a> UnsafeClone for Exclusive<'a> {
     #[inline(always)]
     unsafe fn clone(&self) -> Self {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "borrow-checks"))]
         self.state.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
         Exclusive { state: self.state }
     }
@@ -543,6 +618,12 @@ impl<'a, T: 'a, I: Iterator<Item = &'a T>> Iterator for RefIter<'a, T, I> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) { self.iter.size_hint() }
+
+    #[inline(always)]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.iter.nth(n)?;
+        Some(Ref::new(Clone::clone(&self.borrow), item))
+    }
 }
 
 impl<'a, T: 'a, I: Iterator<Item = &'a T> + ExactSizeIterator> ExactSizeIterator
@@ -550,6 +631,16 @@ impl<'a, T: 'a, I: Iterator<Item = &'a T> + ExactSizeIterator> ExactSizeIterator
 {
 }
 
+impl<'a, T: 'a, I: Iterator<Item = &'a T> + DoubleEndedIterator> DoubleEndedIterator
+    for RefIter<'a, T, I>
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next_back()?;
+        Some(Ref::new(Clone::clone(&self.borrow), item))
+    }
+}
+
 #[derive(Debug)]
 enum TryIter<State, T> {
     Found { borrow: State, iter: T },
@@ -601,6 +692,22 @@ impl<'a, T: 'a, I: Iterator<Item = &'a T>> Iterator for TryRefIter<'a, T, I> {
             TryIter::Missing(n) => (n, Some(n)),
         }
     }
+
+    #[inline(always)]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        Some(match self.inner {
+            TryIter::Found {
+                ref borrow,
+                ref mut iter,
+                ..
+            } => Some(Ref::new(Clone::clone(borrow), iter.nth(n)?)),
+            TryIter::Missing(ref mut count) => {
+                *count = count.saturating_sub(n);
+                *count = count.checked_sub(1)?;
+                None
+            }
+        })
+    }
 }
 
 impl<'a, T: 'a, I: Iterator<Item = &'a T> + ExactSizeIterator> ExactSizeIterator
@@ -608,6 +715,25 @@ impl<'a, T: 'a, I: Iterator<Item = &'a T> + ExactSizeIterator> ExactSizeIterator
 {
 }
 
+impl<'a, T: 'a, I: Iterator<Item = &'a T> + DoubleEndedIterator> DoubleEndedIterator
+    for TryRefIter<'a, T, I>
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(match self.inner {
+            TryIter::Found {
+                ref borrow,
+                ref mut iter,
+                ..
+            } => Some(Ref::new(Clone::clone(borrow), iter.next_back()?)),
+            TryIter::Missing(ref mut n) => {
+                *n = n.checked_sub(1)?;
+                None
+            }
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct RefIterMut<'a, T: 'a, I: Iterator<Item = &'a mut T>> {
     #[allow(dead_code)]
@@ -634,6 +760,12 @@ impl<'a, T: 'a, I: Iterator<Item = &'a mut T>> Iterator for RefIterMut<'a, T, I>
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) { self.iter.size_hint() }
+
+    #[inline(always)]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.iter.nth(n)?;
+        Some(RefMut::new(unsafe { self.borrow.clone() }, item))
+    }
 }
 
 impl<'a, T: 'a, I: Iterator<Item = &'a mut T> + ExactSizeIterator> ExactSizeIterator
@@ -641,6 +773,16 @@ impl<'a, T: 'a, I: Iterator<Item = &'a mut T> + ExactSizeIterator> ExactSizeIter
 {
 }
 
+impl<'a, T: 'a, I: Iterator<Item = &'a mut T> + DoubleEndedIterator> DoubleEndedIterator
+    for RefIterMut<'a, T, I>
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next_back()?;
+        Some(RefMut::new(unsafe { self.borrow.clone() }, item))
+    }
+}
+
 #[derive(Debug)]
 pub struct TryRefIterMut<'a, T: 'a, I: Iterator<Item = &'a mut T>> {
     inner: TryIter<Exclusive<'a>, I>,
@@ -686,9 +828,44 @@ impl<'a, T: 'a, I: Iterator<Item = &'a mut T>> Iterator for TryRefIterMut<'a, T,
             TryIter::Missing(n) => (n, Some(n)),
         }
     }
+
+    #[inline(always)]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        Some(match self.inner {
+            TryIter::Found {
+                ref borrow,
+                ref mut iter,
+                ..
+            } => Some(RefMut::new(unsafe { borrow.clone() }, iter.nth(n)?)),
+            TryIter::Missing(ref mut count) => {
+                *count = count.saturating_sub(n);
+                *count = count.checked_sub(1)?;
+                None
+            }
+        })
+    }
 }
 
 impl<'a, T: 'a, I: Iterator<Item = &'a mut T> + ExactSizeIterator> ExactSizeIterator
     for TryRefIterMut<'a, T, I>
 {
 }
+
+impl<'a, T: 'a, I: Iterator<Item = &'a mut T> + DoubleEndedIterator> DoubleEndedIterator
+    for TryRefIterMut<'a, T, I>
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(match self.inner {
+            TryIter::Found {
+                ref borrow,
+                ref mut iter,
+                ..
+            } => Some(RefMut::new(unsafe { borrow.clone() }, iter.next_back()?)),
+            TryIter::Missing(ref mut n) => {
+                *n = n.checked_sub(1)?;
+                None
+            }
+        })
+    }
+}
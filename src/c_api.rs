@@ -1,8 +1,8 @@
+use crate::borrow::{Ref, RefMut};
+use crate::storage::Component;
+use crate::storage::ComponentResourceSet;
 use crate::storage::ComponentTypeId;
 use std::ffi::c_void;
-use std::any::TypeId;
-use std::cell::RefMut;
-use std::ops::Deref;
 
 #[repr(C)]
 pub struct Universe {
@@ -13,15 +13,17 @@ pub struct World {
     _private: [u8; 0],
 }
 
+// the opaque handle types are zero-sized, so references to them must never be created; a
+// reference to a zero-sized type grants no access to the world behind it
 impl From<*mut World> for &mut crate::prelude::World {
     fn from(world: *mut World) -> Self {
-        unsafe { std::mem::transmute::<&mut World, &mut crate::prelude::World>(world.as_mut().unwrap()) }
+        unsafe { (world as *mut crate::prelude::World).as_mut() }.expect("world null ptr")
     }
 }
 
 impl From<&mut crate::prelude::World> for *mut World {
     fn from(world: &mut crate::prelude::World) -> Self {
-        unsafe { std::mem::transmute::<&mut crate::prelude::World, &mut World>(world) }
+        world as *mut crate::prelude::World as *mut World
     }
 }
 
@@ -31,20 +33,28 @@ pub struct Entity {
     version: u32
 }
 
-// @TODO not the best, since it could theoretically be laid-out differently
-
 impl From<crate::prelude::Entity> for Entity {
     fn from(entity: crate::prelude::Entity) -> Self {
-        unsafe { std::mem::transmute::<crate::prelude::Entity, Entity>(entity) }
+        let bits = entity.to_bits();
+        Entity {
+            index: bits as u32,
+            version: (bits >> 32) as u32,
+        }
     }
 }
 
 impl From<Entity> for crate::prelude::Entity {
     fn from(entity: Entity) -> Self {
-        unsafe { std::mem::transmute::<Entity, crate::prelude::Entity>(entity) }
+        crate::prelude::Entity::from_bits(
+            (u64::from(entity.version) << 32) | u64::from(entity.index),
+        )
     }
 }
 
+/// Gets the identifier used to refer to the Rust component type `T` in
+/// `lgn_world_get_rust_component`.
+pub fn lgn_rust_component_id<T: Component>() -> u64 { ComponentTypeId::of::<T>().ffi_id() }
+
 //pub struct ExternalComponent {}
 
 //#[repr(C)]
@@ -98,27 +108,139 @@ impl From<Entity> for crate::prelude::Entity {
 //    unsafe { slice.offset((size * location.component()) as isize) as *mut c_void }
 //}
 
-pub fn lgn_world_get_rust_component(ptr: *mut World, ty: u64, entity: Entity) -> *mut c_void {
-    let world = unsafe { (ptr as *mut crate::prelude::World).as_mut().expect("universe null ptr") }; // @TODO better error perhaps
+/// An opaque handle to a single component of an entity, returned by
+/// `lgn_world_get_rust_component` and `lgn_world_get_rust_component_mut`.
+///
+/// The handle holds the runtime borrow on the component's chunk slice until it is passed to
+/// `lgn_component_release`. While a write handle is outstanding, any other access to the
+/// same components will panic; while a read handle is outstanding, writes will panic.
+/// All handles must be released before the world is modified structurally or freed.
+///
+/// Runtime borrow checks are only performed in builds with `debug_assertions` enabled. Release
+/// builds do not detect conflicting accesses, which are then undefined behaviour.
+#[repr(C)]
+pub struct LgnComponentRef {
+    _private: [u8; 0],
+}
+
+enum ComponentBorrow {
+    Read(Ref<'static, *mut u8>),
+    Write(RefMut<'static, *mut u8>),
+}
+
+struct ComponentRef {
+    borrow: ComponentBorrow,
+    ptr: *mut c_void,
+}
+
+fn find_rust_component(
+    world: &crate::prelude::World,
+    ty: u64,
+    entity: Entity,
+) -> Option<(&ComponentResourceSet, usize)> {
     let entity: crate::prelude::Entity = entity.into();
+    let location = world.entity_location(entity)?;
+    let archetype = world.storage().archetype(location.archetype())?;
+    let chunk = archetype
+        .chunkset(location.set())?
+        .chunk(location.chunk())?;
+    let type_id = archetype
+        .description()
+        .components()
+        .iter()
+        .map(|(type_id, _)| *type_id)
+        .find(|type_id| type_id.ffi_id() == ty)?;
+    Some((chunk.components(type_id)?, location.component().as_usize()))
+}
+
+fn into_handle(borrow: ComponentBorrow, size: usize, index: usize) -> *mut LgnComponentRef {
+    let base = match &borrow {
+        ComponentBorrow::Read(ptr) => **ptr,
+        ComponentBorrow::Write(ptr) => **ptr,
+    };
+    let ptr = unsafe { base.add(size * index) as *mut c_void };
+    Box::into_raw(Box::new(ComponentRef { borrow, ptr })) as *mut LgnComponentRef
+}
 
-    if !world.is_alive(entity) {
-        panic!("AA")
+/// Borrows a Rust component of an entity for reading.
+///
+/// Returns null if the entity is not alive or does not have the component. The component
+/// remains borrowed until the handle is passed to `lgn_component_release`.
+///
+/// # Panics
+///
+/// Panics if the component is currently borrowed for writing. Conflicts are only detected in
+/// builds with `debug_assertions` enabled.
+pub fn lgn_world_get_rust_component(
+    ptr: *mut World,
+    ty: u64,
+    entity: Entity,
+) -> *mut LgnComponentRef {
+    let world: &mut crate::prelude::World = ptr.into();
+    match find_rust_component(world, ty, entity) {
+        Some((components, index)) => {
+            let (slice, size, _) = components.data_raw();
+            // the world outlives the handle, as required by `lgn_component_release`
+            let slice =
+                unsafe { std::mem::transmute::<Ref<*mut u8>, Ref<'static, *mut u8>>(slice) };
+            into_handle(ComponentBorrow::Read(slice), size, index)
+        }
+        None => std::ptr::null_mut(),
     }
+}
 
-    let location = world.entity_allocator.get_location(entity.index()).unwrap();  // @TODO better error
-    let archetype = world.storage().archetypes().get(location.archetype()).unwrap(); // @TODO better error
-    let chunk = archetype
-        .chunksets()
-        .get(location.set()).unwrap()  // @TODO better error
-        .get(location.chunk()).unwrap();  // @TODO better error
-    let (slice, size, count) =
-        chunk
-            .components(unsafe { std::mem::transmute::<(u64, u32), ComponentTypeId>((ty, 0)) }).unwrap()
-            .data_raw();
-    let (slice_borrow, slice) = unsafe { slice.deconstruct() };
+/// Borrows a Rust component of an entity for writing.
+///
+/// Returns null if the entity is not alive or does not have the component. The component
+/// remains borrowed until the handle is passed to `lgn_component_release`.
+///
+/// # Panics
+///
+/// Panics if the component is currently borrowed. Conflicts are only detected in builds with
+/// `debug_assertions` enabled.
+pub fn lgn_world_get_rust_component_mut(
+    ptr: *mut World,
+    ty: u64,
+    entity: Entity,
+) -> *mut LgnComponentRef {
+    let world: &mut crate::prelude::World = ptr.into();
+    match find_rust_component(world, ty, entity) {
+        Some((components, index)) => {
+            let (slice, size, _) = components.data_raw_mut();
+            // the world outlives the handle, as required by `lgn_component_release`
+            let slice =
+                unsafe { std::mem::transmute::<RefMut<*mut u8>, RefMut<'static, *mut u8>>(slice) };
+            into_handle(ComponentBorrow::Write(slice), size, index)
+        }
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Gets a pointer to the component data referred to by a handle.
+pub fn lgn_component_ptr(handle: *const LgnComponentRef) -> *const c_void {
+    let handle = unsafe { (handle as *const ComponentRef).as_ref() }.expect("handle null ptr");
+    handle.ptr
+}
+
+/// Gets a mutable pointer to the component data referred to by a handle.
+///
+/// Returns null if the handle was not created by `lgn_world_get_rust_component_mut`.
+pub fn lgn_component_ptr_mut(handle: *mut LgnComponentRef) -> *mut c_void {
+    let handle = unsafe { (handle as *mut ComponentRef).as_mut() }.expect("handle null ptr");
+    match handle.borrow {
+        ComponentBorrow::Write(_) => handle.ptr,
+        ComponentBorrow::Read(_) => std::ptr::null_mut(),
+    }
+}
 
-    unsafe { slice.offset((size * location.component()) as isize) as *mut c_void }
+/// Releases a component handle, and the borrow it holds. Null handles are ignored.
+pub fn lgn_component_release(handle: *mut LgnComponentRef) {
+    if !handle.is_null() {
+        unsafe {
+            let _handle = Box::from_raw(handle as *mut ComponentRef);
+            // let handle be dropped
+        }
+    }
 }
 
 pub fn lgn_universe_new() -> *mut Universe {
@@ -154,35 +276,87 @@ pub fn lgn_world_free(ptr: *mut World) -> () {
 
 #[cfg(test)]
 mod test {
-    use crate::c_api::{lgn_world_get_rust_component, World };
-    use crate::storage::ComponentTypeId;
-    use std::os::raw::c_void;
+    use crate::c_api::*;
+    use crate::prelude::{IntoQuery, Read, Write};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
 
+    #[derive(Debug, PartialEq)]
     struct Pos(f32, f32, f32);
     struct Vel(f32, f32, f32);
 
-    #[test]
-    fn get_rust_component() {
+    fn create_world() -> (crate::prelude::World, crate::prelude::Entity) {
         let universe = crate::prelude::Universe::new();
         let mut world = universe.create_world();
 
-        let entity = world.insert(
-            (),
-            vec![
-                (Pos(1., 2., 3.), Vel(1., 2., 3.)),
-            ],
-        )[0].clone();
+        let entity = world.insert((), vec![(Pos(1., 2., 3.), Vel(1., 2., 3.))])[0];
+        (world, entity)
+    }
 
-        let pos_id = unsafe { std::mem::transmute::<std::any::TypeId, u64>(std::any::TypeId::of::<Pos>()) };
-        assert_eq!(unsafe { std::mem::transmute::<(u64, u32), ComponentTypeId>((pos_id, 0))}, ComponentTypeId::of::<Pos>());
+    #[test]
+    fn get_rust_component() {
+        let (mut world, entity) = create_world();
 
+        let pos_id = lgn_rust_component_id::<Pos>();
+        assert_ne!(pos_id, lgn_rust_component_id::<Vel>());
 
-        let ffi_pos = lgn_world_get_rust_component((&mut world).into(), pos_id, entity.into());
+        let handle = lgn_world_get_rust_component((&mut world).into(), pos_id, entity.into());
+        assert!(!handle.is_null());
+        assert!(lgn_component_ptr_mut(handle).is_null());
 
-        let pos = unsafe { std::mem::transmute::<*mut c_void, &mut Pos>(ffi_pos) };
+        let pos = unsafe { &*(lgn_component_ptr(handle) as *const Pos) };
+        assert_eq!(*pos, Pos(1., 2., 3.));
+        lgn_component_release(handle);
 
-        assert_eq!(pos.0, 1.);
-        assert_eq!(pos.1, 2.);
-        assert_eq!(pos.2, 3.);
+        world.delete(entity);
+        let handle = lgn_world_get_rust_component((&mut world).into(), pos_id, entity.into());
+        assert!(handle.is_null());
     }
-}
\ No newline at end of file
+
+    // borrows are only checked with debug assertions enabled
+    #[test]
+    #[cfg(debug_assertions)]
+    fn write_handle_blocks_queries() {
+        let (mut world, entity) = create_world();
+        let pos_id = lgn_rust_component_id::<Pos>();
+
+        let handle = lgn_world_get_rust_component_mut((&mut world).into(), pos_id, entity.into());
+        assert!(!handle.is_null());
+        unsafe { (*(lgn_component_ptr_mut(handle) as *mut Pos)).0 = 5. };
+
+        let conflict = catch_unwind(AssertUnwindSafe(|| {
+            Write::<Pos>::query().iter(&mut world).count()
+        }));
+        assert!(conflict.is_err());
+        let conflict = catch_unwind(AssertUnwindSafe(|| {
+            Read::<Pos>::query().iter(&mut world).count()
+        }));
+        assert!(conflict.is_err());
+
+        lgn_component_release(handle);
+        for mut pos in Write::<Pos>::query().iter(&mut world) {
+            assert_eq!(*pos, Pos(5., 2., 3.));
+            pos.1 = 6.;
+        }
+        assert_eq!(
+            *world.get_component::<Pos>(entity).unwrap(),
+            Pos(5., 6., 3.)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn read_handle_blocks_writes() {
+        let (mut world, entity) = create_world();
+        let pos_id = lgn_rust_component_id::<Pos>();
+
+        let handle = lgn_world_get_rust_component((&mut world).into(), pos_id, entity.into());
+        assert_eq!(1, Read::<Pos>::query().iter(&mut world).count());
+        let conflict = catch_unwind(AssertUnwindSafe(|| {
+            Write::<Pos>::query().iter(&mut world).count()
+        }));
+        assert!(conflict.is_err());
+
+        lgn_component_release(handle);
+        assert_eq!(1, Write::<Pos>::query().iter(&mut world).count());
+    }
+}
@@ -0,0 +1,583 @@
+//! Maps which associate state with chunks, keyed by each chunk's dense `ChunkIndex`.
+//!
+//! Stateful filters need to remember something about each chunk they visit (e.g. the last
+//! component version they observed). Storing this in a `Vec` indexed by the chunk's dense index
+//! avoids the hashing and allocation costs of a hash map keyed by `ChunkId`. Each slot records
+//! the generation of the chunk index it was written for, so state belonging to a released chunk
+//! is never observed through a new chunk which reuses its index.
+//!
+//! `ChunkStateMap` stores any state for use from a single thread, and `ShardedChunkStateMap`
+//! stores it behind sharded locks for use from many. Component versions, which the `changed`
+//! filter records from every thread iterating a query, are stored without locking in a
+//! `ChunkVersionMap`.
+use crate::storage::ChunkIndex;
+use crate::storage::ComponentTypeId;
+use crate::storage::Version;
+use parking_lot::Mutex;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+
+/// Stores a value of type `T` for each chunk.
+#[derive(Debug, Clone)]
+pub struct ChunkStateMap<T> {
+    slots: Vec<(u32, Option<T>)>,
+}
+
+impl<T> Default for ChunkStateMap<T> {
+    fn default() -> Self { Self { slots: Vec::new() } }
+}
+
+impl<T> ChunkStateMap<T> {
+    /// Creates a new empty map.
+    pub fn new() -> Self { Self::default() }
+
+    /// Gets the value stored for the given chunk.
+    pub fn get(&self, chunk: ChunkIndex) -> Option<&T> {
+        match self.slots.get(chunk.index()) {
+            Some((generation, value)) if *generation == chunk.generation() => value.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to the value stored for the given chunk.
+    pub fn get_mut(&mut self, chunk: ChunkIndex) -> Option<&mut T> { self.slot(chunk).as_mut() }
+
+    /// Stores a value for the given chunk, returning the previous value.
+    pub fn insert(&mut self, chunk: ChunkIndex, value: T) -> Option<T> {
+        self.slot(chunk).replace(value)
+    }
+
+    /// Removes the value stored for the given chunk.
+    pub fn remove(&mut self, chunk: ChunkIndex) -> Option<T> { self.slot(chunk).take() }
+
+    /// Gets the slot for the given chunk, which is `None` if no value is stored.
+    ///
+    /// Any value stored for a previous generation of the chunk's index is discarded.
+    pub fn slot(&mut self, chunk: ChunkIndex) -> &mut Option<T> {
+        let index = chunk.index();
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || (0, None));
+        }
+
+        let (generation, value) = &mut self.slots[index];
+        if *generation != chunk.generation() {
+            *generation = chunk.generation();
+            *value = None;
+        }
+
+        value
+    }
+
+    /// Removes all values from the map.
+    pub fn clear(&mut self) { self.slots.clear(); }
+}
+
+/// A `ChunkStateMap` which can be accessed concurrently.
+///
+/// Chunks are distributed across a fixed number of independently locked shards, so that
+/// threads processing different chunks rarely contend.
+#[derive(Debug)]
+pub struct ShardedChunkStateMap<T> {
+    shards: Box<[Mutex<ChunkStateMap<T>>]>,
+}
+
+impl<T> ShardedChunkStateMap<T> {
+    const SHARDS: usize = 16;
+
+    /// Creates a new empty map.
+    pub fn new() -> Self {
+        Self {
+            shards: (0..Self::SHARDS)
+                .map(|_| Mutex::new(ChunkStateMap::new()))
+                .collect(),
+        }
+    }
+
+    /// Calls `f` with the slot for the given chunk, while holding a lock on its shard.
+    pub fn with_slot<R, F: FnOnce(&mut Option<T>) -> R>(&self, chunk: ChunkIndex, f: F) -> R {
+        let shard = chunk.index() % Self::SHARDS;
+        let local = ChunkIndex::new((chunk.index() / Self::SHARDS) as u32, chunk.generation());
+        f(self.shards[shard].lock().slot(local))
+    }
+
+    /// Removes all values from the map.
+    pub fn clear(&mut self) {
+        for shard in self.shards.iter_mut() {
+            shard.get_mut().clear();
+        }
+    }
+}
+
+impl<T> Default for ShardedChunkStateMap<T> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T: Clone> Clone for ShardedChunkStateMap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: self
+                .shards
+                .iter()
+                .map(|shard| Mutex::new(shard.lock().clone()))
+                .collect(),
+        }
+    }
+}
+
+const SEGMENT_LEN: usize = 256;
+
+type Segment = [AtomicU64; SEGMENT_LEN];
+
+struct SegmentTable {
+    segments: Box<[AtomicPtr<Segment>]>,
+}
+
+impl SegmentTable {
+    fn new(len: usize) -> Self {
+        Self {
+            segments: (0..len).map(|_| AtomicPtr::new(ptr::null_mut())).collect(),
+        }
+    }
+
+    /// Gets a pointer to a table which has already been moved into its final `Box`. Tables are
+    /// only ever modified through their atomics, so the pointer is derived from a shared
+    /// reference; taking it before the box is moved would be invalidated by the move.
+    fn as_ptr(table: &SegmentTable) -> *mut SegmentTable {
+        table as *const SegmentTable as *mut SegmentTable
+    }
+}
+
+/// Records the last observed component version for each chunk, and can be updated
+/// concurrently without locking.
+///
+/// Component versions are allocated from a single monotonically increasing counter, so a chunk
+/// which reuses the dense index of a released chunk always has newer versions than any recorded
+/// for its predecessor. This allows the map to ignore index generations and store a single
+/// `AtomicU64` per index.
+pub struct ChunkVersionMap {
+    current: AtomicPtr<SegmentTable>,
+    // boxed so that `current` remains valid as tables are added
+    #[allow(clippy::vec_box)]
+    tables: Mutex<Vec<Box<SegmentTable>>>,
+}
+
+impl ChunkVersionMap {
+    /// Creates a new empty map.
+    pub fn new() -> Self {
+        let tables = vec![Box::new(SegmentTable::new(0))];
+        Self {
+            current: AtomicPtr::new(SegmentTable::as_ptr(&tables[0])),
+            tables: Mutex::new(tables),
+        }
+    }
+
+    fn slot(&self, index: usize) -> &AtomicU64 {
+        let (segment, offset) = (index / SEGMENT_LEN, index % SEGMENT_LEN);
+
+        // fast path: the segment has already been allocated
+        let table = unsafe { &*self.current.load(Ordering::Acquire) };
+        if let Some(ptr) = table.segments.get(segment) {
+            let ptr = ptr.load(Ordering::Acquire);
+            if !ptr.is_null() {
+                return unsafe { &(*ptr)[offset] };
+            }
+        }
+
+        self.slot_slow(segment, offset)
+    }
+
+    #[cold]
+    fn slot_slow(&self, segment: usize, offset: usize) -> &AtomicU64 {
+        let mut tables = self.tables.lock();
+
+        // grow the segment table, keeping the previous tables alive for concurrent readers
+        let len = tables.last().unwrap().segments.len();
+        if segment >= len {
+            let table = Box::new(SegmentTable::new((segment + 1).max(len * 2)));
+            for (new, old) in table
+                .segments
+                .iter()
+                .zip(tables.last().unwrap().segments.iter())
+            {
+                new.store(old.load(Ordering::Acquire), Ordering::Relaxed);
+            }
+            tables.push(table);
+            let current = SegmentTable::as_ptr(tables.last().unwrap());
+            self.current.store(current, Ordering::Release);
+        }
+
+        let ptr = &tables.last().unwrap().segments[segment];
+        if ptr.load(Ordering::Acquire).is_null() {
+            let new: Box<Segment> = Box::new(unsafe { std::mem::zeroed() });
+            ptr.store(Box::into_raw(new), Ordering::Release);
+        }
+
+        unsafe { &(*ptr.load(Ordering::Acquire))[offset] }
+    }
+
+    /// Gets the number of chunks for which a version has been recorded.
+    pub fn len(&self) -> usize {
+        let table = unsafe { &*self.current.load(Ordering::Acquire) };
+        table
+            .segments
+            .iter()
+            .map(|segment| segment.load(Ordering::Acquire))
+            .filter(|segment| !segment.is_null())
+            .map(|segment| {
+                unsafe { &*segment }
+                    .iter()
+                    .filter(|version| version.load(Ordering::Relaxed) > 0)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Determines if no versions have been recorded.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Gets the last version recorded for the given chunk, or `Version::NONE` if none has been
+    /// recorded.
+    pub fn get(&self, chunk: ChunkIndex) -> Version {
+        Version::from_bits(self.slot(chunk.index()).load(Ordering::Relaxed))
+    }
+
+    /// Records `version` for the given chunk if it is newer than the version already recorded,
+    /// and returns the previously recorded version.
+    pub fn update(&self, chunk: ChunkIndex, version: Version) -> Version {
+        let previous = self.slot(chunk.index()).fetch_update(
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |previous| {
+                if version.is_newer_than(Version::from_bits(previous)) {
+                    Some(version.to_bits())
+                } else {
+                    None
+                }
+            },
+        );
+        Version::from_bits(previous.unwrap_or_else(|previous| previous))
+    }
+}
+
+impl Default for ChunkVersionMap {
+    fn default() -> Self { Self::new() }
+}
+
+impl Clone for ChunkVersionMap {
+    fn clone(&self) -> Self {
+        let clone = Self::new();
+        let table = unsafe { &*self.current.load(Ordering::Acquire) };
+        for (i, segment) in table.segments.iter().enumerate() {
+            let segment = segment.load(Ordering::Acquire);
+            if segment.is_null() {
+                continue;
+            }
+
+            for (offset, version) in unsafe { &*segment }.iter().enumerate() {
+                let version = version.load(Ordering::Relaxed);
+                if version > 0 {
+                    clone
+                        .slot(i * SEGMENT_LEN + offset)
+                        .store(version, Ordering::Relaxed);
+                }
+            }
+        }
+        clone
+    }
+}
+
+impl std::fmt::Debug for ChunkVersionMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let table = unsafe { &*self.current.load(Ordering::Acquire) };
+        let segments = table
+            .segments
+            .iter()
+            .filter(|s| !s.load(Ordering::Acquire).is_null())
+            .count();
+        f.debug_struct("ChunkVersionMap")
+            .field("segments", &segments)
+            .finish()
+    }
+}
+
+impl Drop for ChunkVersionMap {
+    fn drop(&mut self) {
+        // the latest table holds every allocated segment
+        for segment in self.tables.get_mut().last().unwrap().segments.iter() {
+            let segment = segment.load(Ordering::Relaxed);
+            if !segment.is_null() {
+                unsafe { drop(Box::from_raw(segment)) };
+            }
+        }
+    }
+}
+
+/// Records, for each component type, the version of each chunk's components when they were
+/// last fetched by a query. Backs the `Changed<T>` view element.
+///
+/// Updated from every thread iterating the query without locking. Each tracked component type
+/// has a `ChunkVersionMap`, stored in an append-only list which is searched linearly; views
+/// track only a handful of component types, and entries are never removed until the tracker is
+/// dropped.
+#[derive(Default)]
+pub struct ChangeTracker {
+    head: AtomicPtr<TrackedType>,
+}
+
+struct TrackedType {
+    type_id: ComponentTypeId,
+    versions: ChunkVersionMap,
+    next: *mut TrackedType,
+}
+
+// entries are only shared through `&ChangeTracker`, and are only mutated through atomics
+unsafe impl Send for ChangeTracker {}
+unsafe impl Sync for ChangeTracker {}
+
+impl ChangeTracker {
+    /// Creates a new empty tracker.
+    pub fn new() -> Self { Self::default() }
+
+    fn iter(&self) -> impl Iterator<Item = &TrackedType> {
+        let mut next = self.head.load(Ordering::Acquire);
+        std::iter::from_fn(move || {
+            // entries live until the tracker is dropped
+            let entry = unsafe { next.as_ref()? };
+            next = entry.next;
+            Some(entry)
+        })
+    }
+
+    fn versions(&self, type_id: ComponentTypeId) -> &ChunkVersionMap {
+        if let Some(entry) = self.iter().find(|entry| entry.type_id == type_id) {
+            return &entry.versions;
+        }
+
+        let entry = Box::into_raw(Box::new(TrackedType {
+            type_id,
+            versions: ChunkVersionMap::new(),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            // another thread may have added the type since the list was last searched
+            let mut next = head;
+            while let Some(existing) = unsafe { next.as_ref() } {
+                if existing.type_id == type_id {
+                    unsafe { drop(Box::from_raw(entry)) };
+                    return &existing.versions;
+                }
+                next = existing.next;
+            }
+
+            unsafe { (*entry).next = head };
+            match self
+                .head
+                .compare_exchange(head, entry, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return unsafe { &(*entry).versions },
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Records `version` for the `type_id` components of the given chunk, and returns the
+    /// previously recorded version.
+    pub fn update(&self, type_id: ComponentTypeId, chunk: ChunkIndex, version: Version) -> Version {
+        self.versions(type_id).update(chunk, version)
+    }
+}
+
+impl Clone for ChangeTracker {
+    fn clone(&self) -> Self {
+        let clone = Self::new();
+        for entry in self.iter() {
+            let copy = Box::into_raw(Box::new(TrackedType {
+                type_id: entry.type_id,
+                versions: entry.versions.clone(),
+                next: clone.head.load(Ordering::Relaxed),
+            }));
+            clone.head.store(copy, Ordering::Relaxed);
+        }
+        clone
+    }
+}
+
+impl std::fmt::Debug for ChangeTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|entry| (entry.type_id, &entry.versions)))
+            .finish()
+    }
+}
+
+impl Drop for ChangeTracker {
+    fn drop(&mut self) {
+        let mut next = *self.head.get_mut();
+        while !next.is_null() {
+            let entry = unsafe { Box::from_raw(next) };
+            next = entry.next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk_state::*;
+
+    #[test]
+    fn get_insert_remove() {
+        let mut map = ChunkStateMap::new();
+        let a = ChunkIndex::new(3, 0);
+        let b = ChunkIndex::new(0, 0);
+
+        assert_eq!(None, map.insert(a, 1));
+        assert_eq!(None, map.insert(b, 2));
+        assert_eq!(Some(&1), map.get(a));
+        assert_eq!(Some(1), map.insert(a, 3));
+        assert_eq!(Some(3), map.remove(a));
+        assert_eq!(None, map.get(a));
+        assert_eq!(Some(&2), map.get(b));
+    }
+
+    #[test]
+    fn generation_mismatch() {
+        let mut map = ChunkStateMap::new();
+        let old = ChunkIndex::new(2, 0);
+        let new = ChunkIndex::new(2, 1);
+
+        map.insert(old, 1);
+        assert_eq!(None, map.get(new));
+        assert_eq!(None, map.get_mut(new));
+
+        // writing the new generation discards the old value
+        map.insert(new, 2);
+        assert_eq!(None, map.get(old));
+        assert_eq!(Some(&2), map.get(new));
+    }
+
+    #[test]
+    fn sharded_generation_mismatch() {
+        let map = ShardedChunkStateMap::new();
+        let old = ChunkIndex::new(17, 0);
+        let new = ChunkIndex::new(17, 1);
+
+        map.with_slot(old, |slot| *slot = Some(1));
+        assert_eq!(Some(1), map.with_slot(old, |slot| *slot));
+        assert_eq!(None, map.with_slot(ChunkIndex::new(1, 0), |slot| *slot));
+        assert_eq!(None, map.with_slot(new, |slot| *slot));
+        assert_eq!(None, map.with_slot(old, |slot| *slot));
+    }
+
+    #[test]
+    fn version_map_update() {
+        let map = ChunkVersionMap::new();
+        let a = ChunkIndex::new(3, 0);
+        let b = ChunkIndex::new(1000, 0);
+
+        let v = Version::from_bits;
+
+        assert_eq!(Version::NONE, map.get(a));
+        assert_eq!(Version::NONE, map.update(a, v(5)));
+        assert_eq!(v(5), map.update(a, v(4)));
+        assert_eq!(v(5), map.get(a));
+        assert_eq!(Version::NONE, map.update(b, v(7)));
+        assert_eq!(v(5), map.get(a));
+
+        let clone = map.clone();
+        assert_eq!(v(5), clone.get(a));
+        assert_eq!(v(7), clone.get(b));
+    }
+
+    #[test]
+    fn version_map_update_across_overflow() {
+        let map = ChunkVersionMap::new();
+        let a = ChunkIndex::new(0, 0);
+        let before = Version::from_bits(u64::MAX - 1);
+        let after = Version::from_bits(2);
+
+        map.update(a, before);
+        assert_eq!(before, map.update(a, after));
+        assert_eq!(after, map.update(a, before));
+        assert_eq!(after, map.get(a));
+    }
+
+    #[test]
+    fn version_map_concurrent_update() {
+        let map = ChunkVersionMap::new();
+        let changed = AtomicU64::new(0);
+
+        // each chunk is seen as changed by exactly one thread
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for i in 0..2000 {
+                        let version = Version::from_bits(1);
+                        if map.update(ChunkIndex::new(i, 0), version).is_none() {
+                            changed.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(2000, changed.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn change_tracker_concurrent_update() {
+        let tracker = ChangeTracker::new();
+        let types = [
+            ComponentTypeId::of::<u8>(),
+            ComponentTypeId::of::<u16>(),
+            ComponentTypeId::of::<u32>(),
+        ];
+        let changed = AtomicU64::new(0);
+
+        // each type's chunks are seen as changed by exactly one thread
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for i in 0..500 {
+                        for &type_id in &types {
+                            let version = Version::from_bits(1);
+                            if tracker
+                                .update(type_id, ChunkIndex::new(i, 0), version)
+                                .is_none()
+                            {
+                                changed.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(1500, changed.load(Ordering::Relaxed));
+        assert_eq!(3, tracker.iter().count());
+
+        let clone = tracker.clone();
+        let a = ChunkIndex::new(0, 0);
+        assert_eq!(
+            Version::from_bits(1),
+            clone.update(types[1], a, Version::from_bits(2))
+        );
+        assert_eq!(
+            Version::from_bits(1),
+            tracker.update(types[1], a, Version::from_bits(3))
+        );
+    }
+
+    #[test]
+    fn recycled_index_has_new_generation() {
+        let a = ChunkIndex::alloc();
+        let mut map = ChunkStateMap::new();
+        map.insert(a, ());
+        a.free();
+
+        let b = ChunkIndex::alloc();
+        assert_ne!(a, b);
+        assert_eq!(None, map.get(b));
+        b.free();
+    }
+}
@@ -0,0 +1,143 @@
+//! Cloning entities and their component data.
+//!
+//! Components are stored type-erased, so a `CloneImpl` records how to clone each component
+//! type. See `World::clone_entity`.
+
+use crate::entity::Entity;
+use crate::storage::ArchetypeDescription;
+use crate::storage::Component;
+use crate::storage::ComponentTypeId;
+use fxhash::FxHashMap;
+
+/// Rewrites the entity references held by a component after it has been cloned.
+///
+/// When entities are cloned, `remap` maps each cloned entity to its clone, and every other
+/// entity to itself, so that e.g. a reference from an entity to itself points at the clone.
+pub trait EntityRemap {
+    /// Replaces each entity referenced by the component with `remap(entity)`.
+    fn remap_entities(&mut self, remap: &dyn Fn(Entity) -> Entity);
+}
+
+/// Clones the component at `src` into the uninitialized memory at `dst`.
+type CloneFn = unsafe fn(*const u8, *mut u8, &dyn Fn(Entity) -> Entity);
+
+/// A registry of the component types which can be cloned, and how to clone them.
+///
+/// # Examples
+///
+/// ```
+/// # use legion::prelude::*;
+/// # use legion::clone::CloneImpl;
+/// # #[derive(Clone, Copy, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let mut cloner = CloneImpl::new();
+/// cloner.register::<Position>();
+///
+/// let entity = world.insert((), vec![(Position(1.0),)])[0];
+/// let clone = world.clone_entity(entity, &cloner).unwrap();
+/// assert_ne!(entity, clone);
+/// assert_eq!(Position(1.0), *world.get_component::<Position>(clone).unwrap());
+/// ```
+#[derive(Default, Clone)]
+pub struct CloneImpl {
+    clone_fns: FxHashMap<ComponentTypeId, CloneFn>,
+}
+
+impl CloneImpl {
+    /// Creates an empty registry.
+    pub fn new() -> Self { Self::default() }
+
+    /// Registers a component type which is cloned with `Clone`.
+    pub fn register<T: Component + Clone>(&mut self) -> &mut Self {
+        unsafe fn clone<T: Component + Clone>(
+            src: *const u8,
+            dst: *mut u8,
+            _: &dyn Fn(Entity) -> Entity,
+        ) {
+            std::ptr::write(dst as *mut T, (*(src as *const T)).clone());
+        }
+
+        self.clone_fns
+            .insert(ComponentTypeId::of::<T>(), clone::<T>);
+        self
+    }
+
+    /// Registers a component type which is cloned with `Clone`, and then has its entity
+    /// references rewritten with `EntityRemap`.
+    pub fn register_remap<T: Component + Clone + EntityRemap>(&mut self) -> &mut Self {
+        unsafe fn clone<T: Component + Clone + EntityRemap>(
+            src: *const u8,
+            dst: *mut u8,
+            remap: &dyn Fn(Entity) -> Entity,
+        ) {
+            let mut component = (*(src as *const T)).clone();
+            component.remap_entities(remap);
+            std::ptr::write(dst as *mut T, component);
+        }
+
+        self.clone_fns
+            .insert(ComponentTypeId::of::<T>(), clone::<T>);
+        self
+    }
+
+    /// Determines if the given component type has been registered.
+    pub fn contains(&self, type_id: ComponentTypeId) -> bool {
+        self.clone_fns.contains_key(&type_id)
+    }
+
+    /// Checks that every component type in an archetype has been registered.
+    pub(crate) fn validate(&self, desc: &ArchetypeDescription) -> Result<(), CloneError> {
+        for ((type_id, _), name) in desc.components().iter().zip(desc.component_names()) {
+            if !self.contains(*type_id) {
+                return Err(CloneError::NotRegistered(name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Clones a component of the given type from `src` into the uninitialized memory at `dst`.
+    ///
+    /// # Safety
+    ///
+    /// `src` must point to a valid component of type `type_id`, and `dst` must be valid for
+    /// writing one such component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component type has not been registered.
+    pub(crate) unsafe fn clone(
+        &self,
+        type_id: ComponentTypeId,
+        src: *const u8,
+        dst: *mut u8,
+        remap: &dyn Fn(Entity) -> Entity,
+    ) {
+        (self.clone_fns[&type_id])(src, dst, remap);
+    }
+}
+
+/// An error returned when entities could not be cloned.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CloneError {
+    /// The entity is not alive.
+    EntityNotFound(Entity),
+    /// The entity has a component of the named type, which has not been registered with the
+    /// `CloneImpl`.
+    NotRegistered(&'static str),
+}
+
+impl std::fmt::Display for CloneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CloneError::EntityNotFound(entity) => write!(f, "entity {} not found", entity),
+            CloneError::NotRegistered(name) => {
+                write!(f, "no clone implementation registered for {}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CloneError {}
@@ -0,0 +1,257 @@
+//! Collections which associate external data with entities and chunks.
+//!
+//! Integrations often need to attach data to entities or chunks which does not belong in a
+//! component, such as render handles or physics body IDs. A `SecondaryMap` stores such data in a
+//! `Vec` indexed by the key's index, and records the generation of the key each value was stored
+//! for, so that a stale key (a deleted entity, or a released chunk) never observes data belonging
+//! to a newer key which reuses its index.
+//!
+//! Chunks are keyed by their dense `ChunkIndex` (see `Chunk::dense_index`). Unlike `ChunkId`, it
+//! is dense and distinguishes each reuse of a chunk's storage.
+//!
+//! Entries for dead keys are not removed automatically; call `SecondaryMap::auto_gc` to prune
+//! them, or create an entity map with `SecondaryMap::with_events` and call
+//! `SecondaryMap::maintain` to prune the entities deleted since the last call.
+//!
+//! ```rust
+//! # use legion::prelude::*;
+//! # use legion::collections::SecondaryMap;
+//! # #[derive(Clone, Copy, Debug, PartialEq)]
+//! # struct Position(f32);
+//! let universe = Universe::new();
+//! let mut world = universe.create_world();
+//! let mut handles = SecondaryMap::with_events(&mut world);
+//!
+//! let entities = world.insert((), vec![(Position(0.0),), (Position(1.0),)]).to_vec();
+//! handles.insert(entities[0], "first");
+//! handles.insert(entities[1], "second");
+//!
+//! // drops the value stored for the deleted entity
+//! world.delete(entities[0]);
+//! assert_eq!(1, handles.maintain(&world));
+//! assert_eq!(None, handles.get(entities[0]));
+//! assert_eq!(1, handles.len());
+//! ```
+use crate::entity::Entity;
+use crate::event::Event;
+use crate::filter::filter_fns::any;
+use crate::storage::ChunkIndex;
+use crate::world::World;
+use crossbeam_channel::Receiver;
+use std::marker::PhantomData;
+use std::num::Wrapping;
+
+/// A key which can index a `SecondaryMap`.
+pub trait SecondaryKey: Copy {
+    /// Gets the index of the key's slot.
+    fn slot(self) -> usize;
+
+    /// Gets the generation of the key, which distinguishes it from other keys with the same slot.
+    fn generation(self) -> u32;
+
+    /// Reconstructs a key from its slot and generation.
+    fn from_parts(slot: usize, generation: u32) -> Self;
+
+    /// Determines if the key is still alive.
+    fn is_alive(self, world: &World) -> bool;
+}
+
+impl SecondaryKey for Entity {
+    fn slot(self) -> usize { self.index() as usize }
+
+    fn generation(self) -> u32 { self.version().0 }
+
+    fn from_parts(slot: usize, generation: u32) -> Self {
+        Entity::new(slot as u32, Wrapping(generation))
+    }
+
+    fn is_alive(self, world: &World) -> bool { world.is_alive(self) }
+}
+
+impl SecondaryKey for ChunkIndex {
+    fn slot(self) -> usize { self.index() }
+
+    fn generation(self) -> u32 { ChunkIndex::generation(&self) }
+
+    fn from_parts(slot: usize, generation: u32) -> Self { ChunkIndex::new(slot as u32, generation) }
+
+    /// Chunk indices are unique across all worlds, and remain alive until their chunk is
+    /// released.
+    fn is_alive(self, _: &World) -> bool { ChunkIndex::is_alive(self) }
+}
+
+/// Stores a value for each of a set of entities or chunks.
+#[derive(Debug)]
+pub struct SecondaryMap<K: SecondaryKey, V> {
+    slots: Vec<Option<(u32, V)>>,
+    len: usize,
+    events: Option<Receiver<Event>>,
+    _key: PhantomData<K>,
+}
+
+impl<K: SecondaryKey, V> Default for SecondaryMap<K, V> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            events: None,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<K: SecondaryKey, V> SecondaryMap<K, V> {
+    /// Creates a new empty map.
+    pub fn new() -> Self { Self::default() }
+
+    /// Gets the number of values stored in the map, including any stored for dead keys which
+    /// have not yet been pruned.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Determines if the map is empty.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Stores a value for the given key, returning the previous value.
+    ///
+    /// Any value stored for a stale key with the same index is dropped. If the key is itself
+    /// stale, because a value is already stored for a newer key with the same index, the map
+    /// is left unchanged and `value` is dropped.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let index = key.slot();
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+
+        // generations only move forwards, wrapping around for chunk indices
+        if let Some((generation, _)) = &self.slots[index] {
+            if (key.generation().wrapping_sub(*generation) as i32) < 0 {
+                return None;
+            }
+        }
+
+        match self.slots[index].replace((key.generation(), value)) {
+            Some((generation, previous)) if generation == key.generation() => Some(previous),
+            Some(_) => None,
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// Gets the value stored for the given key.
+    pub fn get(&self, key: K) -> Option<&V> {
+        match self.slots.get(key.slot()) {
+            Some(Some((generation, value))) if *generation == key.generation() => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to the value stored for the given key.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        match self.slots.get_mut(key.slot()) {
+            Some(Some((generation, value))) if *generation == key.generation() => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Determines if a value is stored for the given key.
+    pub fn contains_key(&self, key: K) -> bool { self.get(key).is_some() }
+
+    /// Removes the value stored for the given key.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let slot = self.slots.get_mut(key.slot())?;
+        match slot {
+            Some((generation, _)) if *generation == key.generation() => {
+                self.len -= 1;
+                slot.take().map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes all values from the map.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    /// Iterates through all keys and values stored in the map, in order of their index.
+    pub fn iter(&self) -> impl Iterator<Item = (K, &V)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let (generation, value) = slot.as_ref()?;
+            Some((K::from_parts(index, *generation), value))
+        })
+    }
+
+    /// Iterates through all keys and values stored in the map, in order of their index.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (K, &mut V)> + '_ {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let (generation, value) = slot.as_mut()?;
+                Some((K::from_parts(index, *generation), value))
+            })
+    }
+
+    /// Removes all values for which `f` returns `false`, returning the number removed.
+    pub fn retain<F: FnMut(K, &mut V) -> bool>(&mut self, mut f: F) -> usize {
+        let mut removed = 0;
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if let Some((generation, value)) = slot {
+                if !f(K::from_parts(index, *generation), value) {
+                    *slot = None;
+                    removed += 1;
+                }
+            }
+        }
+        self.len -= removed;
+        removed
+    }
+
+    /// Removes the values stored for all keys which are no longer alive, returning the number
+    /// removed.
+    ///
+    /// This visits every value in the map. Entity maps created with `with_events` can instead
+    /// call `maintain`, which only visits deleted entities.
+    pub fn auto_gc(&mut self, world: &World) -> usize { self.retain(|key, _| key.is_alive(world)) }
+}
+
+impl<V> SecondaryMap<Entity, V> {
+    /// Creates a new empty map and subscribes it to the given world's removal events, so that
+    /// `maintain` can prune the values of deleted entities.
+    pub fn with_events(world: &mut World) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        world.subscribe(sender, any());
+        Self {
+            events: Some(receiver),
+            ..Self::default()
+        }
+    }
+
+    /// Removes the values stored for entities which have been deleted from the subscribed
+    /// world since the last call, returning the number removed.
+    ///
+    /// Does nothing if the map was not created with `with_events`.
+    pub fn maintain(&mut self, world: &World) -> usize {
+        let events = match &self.events {
+            Some(events) => events,
+            None => return 0,
+        };
+
+        // entities moved between chunks are also removed from their previous chunk
+        let deleted = events
+            .try_iter()
+            .filter_map(|event| match event {
+                Event::EntityRemoved(entity, _) if !world.is_alive(entity) => Some(entity),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        deleted
+            .into_iter()
+            .filter(|entity| self.remove(*entity).is_some())
+            .count()
+    }
+}
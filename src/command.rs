@@ -10,7 +10,7 @@ use bit_set::BitSet;
 use derivative::Derivative;
 use std::{marker::PhantomData, sync::Arc};
 
-use crate::borrow::{AtomicRefCell, RefMut};
+use parking_lot::{Mutex, MutexGuard};
 
 pub trait WorldWritable {
     fn write(self: Arc<Self>, world: &mut World);
@@ -189,7 +189,7 @@ where
 
 #[derive(Default)]
 pub struct CommandBuffer {
-    commands: AtomicRefCell<Vec<EntityCommand>>,
+    commands: Mutex<Vec<EntityCommand>>,
     block: Option<EntityBlock>,
     used_entities: BitSet,
 }
@@ -198,18 +198,30 @@ pub struct CommandBuffer {
 unsafe impl Send for CommandBuffer {}
 unsafe impl Sync for CommandBuffer {}
 
+/// An error returned when a command buffer could not record a command.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum CommandError {
+    /// The command buffer has no reserved entity IDs left with which to create an entity.
     EntityBlockFull,
 }
 
 impl CommandBuffer {
+    // commands may be recorded concurrently, such as from within a parallel query
     #[inline]
-    fn get_commands(&self) -> RefMut<Vec<EntityCommand>> { self.commands.get_mut() }
-
+    fn get_commands(&self) -> MutexGuard<'_, Vec<EntityCommand>> { self.commands.lock() }
+
+    /// Applies the recorded commands to the world, in the order in which they were recorded.
+    ///
+    /// Entities inserted by the buffer are allocated IDs when it is written, rather than when
+    /// the inserts are recorded, in the order of the inserts. A `Schedule` writes the buffers
+    /// of its systems in the order in which the systems were added, even if they ran in
+    /// parallel.
     pub fn write(&self, world: &mut World) {
         tracing::trace!("Draining command buffer");
 
-        while let Some(command) = self.get_commands().pop() {
+        let commands = std::mem::take(&mut *self.get_commands());
+        for command in commands {
             match command {
                 EntityCommand::WriteWorld(ptr) => ptr.write(world),
                 EntityCommand::ExecMutWorld(closure) => closure(world),
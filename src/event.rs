@@ -9,6 +9,7 @@ use std::sync::Arc;
 
 /// Events emitted by a world to subscribers. See `World.subscribe(Sender, EntityFilter)`.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Event {
     /// A new archetype has been created.
     ArchetypeCreated(ArchetypeId),
@@ -18,6 +19,11 @@ pub enum Event {
     EntityInserted(Entity, ChunkId),
     /// An entity has been removed from a chunk.
     EntityRemoved(Entity, ChunkId),
+    /// An archetype's entity count has exceeded its entity threshold, and is now the given
+    /// count. Sent to the subscribers of the chunk whose insertion crossed the threshold, once
+    /// per crossing: the event is sent again only after the count has dropped back to or below
+    /// the threshold, or after `World::reset_high_water_marks` is called.
+    EntityThresholdExceeded(ArchetypeId, usize),
 }
 
 pub(crate) trait EventFilter: Send + Sync + 'static {
@@ -1,3 +1,4 @@
+use crate::chunk_state::ChunkVersionMap;
 use crate::iterator::FissileZip;
 use crate::iterator::SliceVecIter;
 use crate::storage::ArchetypeData;
@@ -10,13 +11,15 @@ use crate::storage::Storage;
 use crate::storage::Tag;
 use crate::storage::TagTypeId;
 use crate::storage::TagTypes;
+use crate::summary::MinMaxSummary;
 use std::iter::Enumerate;
 use std::iter::Repeat;
 use std::iter::Take;
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 use std::slice::Iter;
-use std::sync::atomic::AtomicU64;
-use std::sync::atomic::Ordering;
+#[cfg(feature = "testing")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub mod filter_fns {
     ///! Contains functions for constructing filters.
@@ -41,6 +44,38 @@ pub mod filter_fns {
         EntityFilterTuple::new(TagFilter::new(), Passthrough, Passthrough)
     }
 
+    /// Creates an entity data filter which includes chunks that do not contain
+    /// entity data components of type `T`.
+    ///
+    /// This is equivalent to `!component::<T>()`, and composes with other filters in the same way:
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Clone, Copy, Debug, PartialEq)]
+    /// # struct Health(f32);
+    /// # #[derive(Clone, Copy, Debug, PartialEq)]
+    /// # struct Dead;
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Health(10.),), (Health(5.),)]);
+    /// world.insert((), vec![(Health(0.), Dead)]);
+    ///
+    /// let query = Read::<Health>::query().filter(without::<Dead>());
+    /// assert_eq!(2, query.iter(&mut world).count());
+    /// ```
+    pub fn without<T: Component>(
+    ) -> EntityFilterTuple<Not<ComponentFilter<T>>, Passthrough, Passthrough> {
+        EntityFilterTuple::new(!ComponentFilter::new(), Passthrough, Passthrough)
+    }
+
+    /// Creates a shared data filter which includes chunks that do not contain
+    /// shared data components of type `T`.
+    ///
+    /// This is equivalent to `!tag::<T>()`.
+    pub fn without_tag<T: Tag>() -> EntityFilterTuple<Not<TagFilter<T>>, Passthrough, Passthrough> {
+        EntityFilterTuple::new(!TagFilter::new(), Passthrough, Passthrough)
+    }
+
     /// Creates a shared data filter which includes chunks that contain
     /// specific shared data values.
     pub fn tag_value<'a, T: Tag>(
@@ -59,6 +94,134 @@ pub mod filter_fns {
             ComponentChangedFilter::new(),
         )
     }
+
+    /// Creates a filter which includes chunks containing entities which were spawned before
+    /// the given world tick.
+    ///
+    /// Chunks are skipped as a whole when all of their entities were spawned at or after
+    /// `tick`, but a matching chunk may also contain newer entities; use `Chunk::spawn_ticks`
+    /// to check each entity. No chunks match unless the world was created with
+    /// `WorldOptions::record_spawn_tick`.
+    pub fn spawned_before(
+        tick: u64,
+    ) -> EntityFilterTuple<Passthrough, Passthrough, SpawnedBeforeFilter> {
+        EntityFilterTuple::new(Passthrough, Passthrough, SpawnedBeforeFilter::new(tick))
+    }
+
+    /// Creates a filter which includes chunks containing at least `n` entities.
+    ///
+    /// Together with `max_entities(n - 1)`, this splits the chunks matched by a query into two
+    /// groups with no chunk in both. Empty chunks never match `min_entities` for any `n > 0`.
+    pub fn min_entities(
+        n: usize,
+    ) -> EntityFilterTuple<Passthrough, Passthrough, EntityCountFilter> {
+        EntityFilterTuple::new(
+            Passthrough,
+            Passthrough,
+            EntityCountFilter::new(n, usize::MAX),
+        )
+    }
+
+    /// Creates a filter which includes chunks containing at most `n` entities.
+    ///
+    /// Empty chunks, which may remain in the world after entities are deleted, are skipped by
+    /// queries before this filter is evaluated, unless they are iterated with
+    /// `Query::iter_chunks_including_empty`, in which case they always match `max_entities`.
+    pub fn max_entities(
+        n: usize,
+    ) -> EntityFilterTuple<Passthrough, Passthrough, EntityCountFilter> {
+        EntityFilterTuple::new(Passthrough, Passthrough, EntityCountFilter::new(0, n))
+    }
+
+    /// Creates a filter which includes chunks containing entity data components of type `T`
+    /// which may lie within `range`.
+    ///
+    /// Chunks are skipped as a whole when their `MinMaxSummary<T>` proves that none of their
+    /// components lie within the range, but a matching chunk may also contain components
+    /// outside of it, so each component must still be checked. Every chunk containing `T`
+    /// matches unless `World::enable_summary::<T, MinMaxSummary<T>>` has been called.
+    ///
+    /// Summarizing a chunk reads its `T` components, so the query's view should also read `T`.
+    pub fn component_range<T, R>(
+        range: R,
+    ) -> EntityFilterTuple<ComponentFilter<T>, Passthrough, ComponentRangeFilter<T>>
+    where
+        T: Component + PartialOrd + Clone,
+        R: RangeBounds<T>,
+    {
+        EntityFilterTuple::new(
+            ComponentFilter::new(),
+            Passthrough,
+            ComponentRangeFilter::new(range),
+        )
+    }
+
+    /// Creates a filter which includes chunks whose archetype has exactly the given entity
+    /// data component types, and no others.
+    ///
+    /// Tags are not considered, so this composes with tag filters as usual. Combined with
+    /// `!`, it can find entities which deviate from an expected layout:
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Clone, Copy, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Clone, Copy, Debug, PartialEq)]
+    /// # struct Velocity(f32);
+    /// # #[derive(Clone, Copy, Debug, PartialEq)]
+    /// # struct Health(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Position(0.), Velocity(0.))]);
+    /// let stray = world.insert((), vec![(Position(0.), Velocity(0.), Health(0.))])[0];
+    /// let missing = world.insert((), vec![(Position(0.),)])[0];
+    ///
+    /// // every entity with a position should have exactly a position and a velocity
+    /// let lint = Read::<Position>::query().filter(!exact::<(Position, Velocity)>());
+    /// let deviating = lint.collect_entities(&world);
+    /// assert_eq!(2, deviating.len());
+    /// assert!(deviating.contains(&stray) && deviating.contains(&missing));
+    /// ```
+    pub fn exact_components(
+        types: &[ComponentTypeId],
+    ) -> EntityFilterTuple<ComponentSetFilter, Passthrough, Passthrough> {
+        EntityFilterTuple::new(
+            ComponentSetFilter::new(types, ComponentSetMatch::Exact),
+            Passthrough,
+            Passthrough,
+        )
+    }
+
+    /// Creates a filter which includes chunks whose archetype has exactly the entity data
+    /// component types in the tuple `T`. See `exact_components`.
+    pub fn exact<T: ComponentTypeSet>(
+    ) -> EntityFilterTuple<ComponentSetFilter, Passthrough, Passthrough> {
+        exact_components(&T::component_types())
+    }
+
+    /// Creates a filter which includes chunks whose archetype has all of the given entity data
+    /// component types, and possibly others.
+    pub fn superset_of(
+        types: &[ComponentTypeId],
+    ) -> EntityFilterTuple<ComponentSetFilter, Passthrough, Passthrough> {
+        EntityFilterTuple::new(
+            ComponentSetFilter::new(types, ComponentSetMatch::Superset),
+            Passthrough,
+            Passthrough,
+        )
+    }
+
+    /// Creates a filter which includes chunks whose archetype has no entity data component
+    /// types other than the given types, but which may lack some of them.
+    pub fn subset_of(
+        types: &[ComponentTypeId],
+    ) -> EntityFilterTuple<ComponentSetFilter, Passthrough, Passthrough> {
+        EntityFilterTuple::new(
+            ComponentSetFilter::new(types, ComponentSetMatch::Subset),
+            Passthrough,
+            Passthrough,
+        )
+    }
 }
 
 pub(crate) trait FilterResult {
@@ -98,6 +261,18 @@ pub trait Filter<T: Copy>: Send + Sync + Sized {
     /// Determines if an element of `Self::Iter` matches the filter conditions.
     fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool>;
 
+    /// Determines if an element of `Self::Iter` matches the filter conditions, without
+    /// updating any state held by the filter, such as the versions last seen by `changed`.
+    #[inline]
+    fn peek_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        self.is_match(item)
+    }
+
+    /// Describes the structure of the filter.
+    fn describe(&self) -> FilterDescription {
+        FilterDescription::Other(std::any::type_name::<Self>())
+    }
+
     /// Creates an iterator which yields bools for each element in the source
     /// which indicate if the element matches the filter.
     fn matches(&mut self, source: T) -> FilterIter<Self, T> {
@@ -109,6 +284,26 @@ pub trait Filter<T: Copy>: Send + Sync + Sized {
     }
 }
 
+// allows a filter to be combined with others without giving up ownership of its state
+impl<T: Copy, F: Filter<T>> Filter<T> for &F {
+    type Iter = F::Iter;
+
+    #[inline]
+    fn collect(&self, source: T) -> Self::Iter { (*self).collect(source) }
+
+    #[inline]
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        (*self).is_match(item)
+    }
+
+    #[inline]
+    fn peek_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        (*self).peek_match(item)
+    }
+
+    fn describe(&self) -> FilterDescription { (*self).describe() }
+}
+
 /// An iterator over the elements matching a filter.
 pub struct FilterIter<'a, F: Filter<T>, T: Copy> {
     elements: <F as Filter<T>>::Iter,
@@ -200,6 +395,99 @@ pub trait EntityFilter: Send + Clone {
         &'a self,
         storage: &'b Storage,
     ) -> FilterEntityIter<'b, 'a, Self::ArchetypeFilter, Self::ChunksetFilter>;
+
+    /// Describes the structure of the filter, combining its archetype, chunkset and chunk
+    /// filters.
+    fn describe(&self) -> FilterDescription {
+        let (arch, chunkset, chunk) = self.filters();
+        let mut parts = vec![arch.describe(), chunkset.describe(), chunk.describe()];
+        parts.retain(|part| *part != FilterDescription::Passthrough);
+        match parts.len() {
+            0 => FilterDescription::Passthrough,
+            1 => parts.pop().unwrap(),
+            _ => FilterDescription::And(parts),
+        }
+    }
+}
+
+/// Describes the structure of a filter expression, e.g. for logging or tooling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize))]
+pub enum FilterDescription {
+    /// A filter which has no opinion on which entities match.
+    Passthrough,
+    /// A filter which matches all entities.
+    Any,
+    /// Negates the inner filter.
+    Not(Box<FilterDescription>),
+    /// Requires that all inner filters match.
+    And(Vec<FilterDescription>),
+    /// Requires that any inner filter match.
+    Or(Vec<FilterDescription>),
+    /// Requires the named component type.
+    Component(&'static str),
+    /// Requires the named tag type.
+    Tag(&'static str),
+    /// Requires a specific value of the named tag type.
+    TagValue(&'static str),
+    /// Requires that the named component type has changed.
+    Changed(&'static str),
+    /// Requires entities spawned before the given world tick.
+    SpawnedBefore(u64),
+    /// Requires that the named component type may lie within a range.
+    Range(&'static str),
+    /// Requires chunks containing at least the given number of entities.
+    MinEntities(usize),
+    /// Requires chunks containing at most the given number of entities.
+    MaxEntities(usize),
+    /// Compares each archetype's component types with a set of the given size.
+    ComponentSet(ComponentSetMatch, usize),
+    /// A filter which does not describe itself, identified by its type name.
+    Other(&'static str),
+}
+
+impl std::fmt::Display for FilterDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn join(
+            f: &mut std::fmt::Formatter,
+            parts: &[FilterDescription],
+            op: &str,
+        ) -> std::fmt::Result {
+            write!(f, "(")?;
+            for (i, part) in parts.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                write!(f, "{}", part)?;
+            }
+            write!(f, ")")
+        }
+
+        match self {
+            FilterDescription::Passthrough => write!(f, "passthrough"),
+            FilterDescription::Any => write!(f, "any"),
+            FilterDescription::Not(inner) => write!(f, "!{}", inner),
+            FilterDescription::And(parts) => join(f, parts, "&"),
+            FilterDescription::Or(parts) => join(f, parts, "|"),
+            FilterDescription::Component(name) => write!(f, "component<{}>", name),
+            FilterDescription::Tag(name) => write!(f, "tag<{}>", name),
+            FilterDescription::TagValue(name) => write!(f, "tag_value<{}>", name),
+            FilterDescription::Changed(name) => write!(f, "changed<{}>", name),
+            FilterDescription::SpawnedBefore(tick) => write!(f, "spawned_before({})", tick),
+            FilterDescription::Range(name) => write!(f, "component_range<{}>", name),
+            FilterDescription::MinEntities(n) => write!(f, "min_entities({})", n),
+            FilterDescription::MaxEntities(n) => write!(f, "max_entities({})", n),
+            FilterDescription::ComponentSet(mode, len) => {
+                let name = match mode {
+                    ComponentSetMatch::Exact => "exact_components",
+                    ComponentSetMatch::Superset => "superset_of",
+                    ComponentSetMatch::Subset => "subset_of",
+                };
+                write!(f, "{}({})", name, len)
+            }
+            FilterDescription::Other(name) => write!(f, "{}", name),
+        }
+    }
 }
 
 /// An EntityFilter which combined both an archetype filter and a chunk filter.
@@ -301,6 +589,37 @@ where
     }
 }
 
+/// An object-safe form of `EntityFilter`.
+///
+/// `Filter` and `EntityFilter` describe their iteration state with associated types, and so
+/// cannot be used as trait objects. `DynFilter` is implemented for every entity filter, allowing
+/// filters which are only known at runtime (such as those constructed by an editor) to be boxed
+/// and passed to APIs such as `World::delete_all` and `World::entities_matching`.
+///
+/// `World::entities_matching` is the query entry point for runtime-built filters and
+/// `World::delete_all` is the clearing entry point; typed `Query` iteration still requires a
+/// static `EntityFilter`, as its chunk views are resolved at compile time.
+pub trait DynFilter: Send + Sync {
+    /// Calls `f` with every chunk in the storage which matches the filter.
+    fn for_each_chunk(&self, storage: &Storage, f: &mut dyn FnMut(&ComponentStorage));
+}
+
+impl<F: EntityFilter + Sync> DynFilter for F {
+    fn for_each_chunk(&self, storage: &Storage, f: &mut dyn FnMut(&ComponentStorage)) {
+        let (_, _, chunk_filter) = self.filters();
+        for (archetype_id, set_index) in self.iter(storage) {
+            let archetype = &storage.archetypes()[archetype_id.index()];
+            let chunks = archetype.chunksets()[set_index].occupied();
+            let matches = chunk_filter.collect(ChunkFilterData { chunks });
+            for (chunk, data) in chunks.iter().zip(matches) {
+                if chunk_filter.is_match(&data).is_pass() {
+                    f(chunk);
+                }
+            }
+        }
+    }
+}
+
 impl<A, S, C> std::ops::Not for EntityFilterTuple<A, S, C>
 where
     A: std::ops::Not,
@@ -462,6 +781,8 @@ impl<'a> Filter<ArchetypeFilterData<'a>> for Passthrough {
 
     #[inline]
     fn is_match(&self, _: &<Self::Iter as Iterator>::Item) -> Option<bool> { None }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Passthrough }
 }
 
 impl<'a> Filter<ChunksetFilterData<'a>> for Passthrough {
@@ -474,6 +795,8 @@ impl<'a> Filter<ChunksetFilterData<'a>> for Passthrough {
 
     #[inline]
     fn is_match(&self, _: &<Self::Iter as Iterator>::Item) -> Option<bool> { None }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Passthrough }
 }
 
 impl<'a> Filter<ChunkFilterData<'a>> for Passthrough {
@@ -486,6 +809,8 @@ impl<'a> Filter<ChunkFilterData<'a>> for Passthrough {
 
     #[inline]
     fn is_match(&self, _: &<Self::Iter as Iterator>::Item) -> Option<bool> { None }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Passthrough }
 }
 
 impl std::ops::Not for Passthrough {
@@ -524,6 +849,8 @@ impl<'a> Filter<ArchetypeFilterData<'a>> for Any {
 
     #[inline]
     fn is_match(&self, _: &<Self::Iter as Iterator>::Item) -> Option<bool> { Some(true) }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Any }
 }
 
 impl<'a> Filter<ChunksetFilterData<'a>> for Any {
@@ -536,6 +863,8 @@ impl<'a> Filter<ChunksetFilterData<'a>> for Any {
 
     #[inline]
     fn is_match(&self, _: &<Self::Iter as Iterator>::Item) -> Option<bool> { Some(true) }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Any }
 }
 
 impl<'a> Filter<ChunkFilterData<'a>> for Any {
@@ -548,6 +877,8 @@ impl<'a> Filter<ChunkFilterData<'a>> for Any {
 
     #[inline]
     fn is_match(&self, _: &<Self::Iter as Iterator>::Item) -> Option<bool> { Some(true) }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Any }
 }
 
 impl<Rhs: ActiveFilter> std::ops::BitAnd<Rhs> for Any {
@@ -596,6 +927,22 @@ impl<'a, T: Copy, F: Filter<T>> Filter<T> for Not<F> {
     fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
         self.filter.is_match(item).map(|x| !x)
     }
+
+    #[inline]
+    fn peek_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        self.filter.peek_match(item).map(|x| !x)
+    }
+
+    fn describe(&self) -> FilterDescription {
+        FilterDescription::Not(Box::new(self.filter.describe()))
+    }
+}
+
+impl<F> std::ops::Not for Not<F> {
+    type Output = F;
+
+    #[inline]
+    fn not(self) -> Self::Output { self.filter }
 }
 
 impl<'a, F, Rhs: ActiveFilter> std::ops::BitAnd<Rhs> for Not<F> {
@@ -652,6 +999,15 @@ impl<'a, T: Copy, F: Filter<T>> Filter<T> for And<(F,)> {
     fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
         self.filters.0.is_match(item)
     }
+
+    #[inline]
+    fn peek_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        self.filters.0.peek_match(item)
+    }
+
+    fn describe(&self) -> FilterDescription {
+        FilterDescription::And(vec![self.filters.0.describe()])
+    }
 }
 
 impl<T> std::ops::Not for And<(T,)> {
@@ -734,6 +1090,22 @@ macro_rules! impl_and_filter {
                 $( result = result.coalesce_and($ty.is_match($ty2)); )*
                 result
             }
+
+            #[inline]
+            fn peek_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+                #![allow(non_snake_case)]
+                let ($( $ty, )*) = &self.filters;
+                let recursive_zip!(@unzip $($ty2),*) = item;
+                let mut result: Option<bool> = None;
+                $( result = result.coalesce_and($ty.peek_match($ty2)); )*
+                result
+            }
+
+            fn describe(&self) -> FilterDescription {
+                #![allow(non_snake_case)]
+                let ($( $ty, )*) = &self.filters;
+                FilterDescription::And(vec![$( $ty.describe() ),*])
+            }
         }
 
         impl<$( $ty ),*> std::ops::Not for And<($( $ty, )*)> {
@@ -794,6 +1166,32 @@ impl_and_filter!(A => a, B => b, C => c);
 impl_and_filter!(A => a, B => b, C => c, D => d);
 impl_and_filter!(A => a, B => b, C => c, D => d, E => e);
 impl_and_filter!(A => a, B => b, C => c, D => d, E => e, F => f);
+impl_and_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g);
+impl_and_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h);
+impl_and_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i);
+impl_and_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j);
+impl_and_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k
+);
+impl_and_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l
+);
+impl_and_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m
+);
+impl_and_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m, N => n
+);
+impl_and_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m, N => n, O => o
+);
+impl_and_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m, N => n, O => o, P => p
+);
 
 /// A filter which requires that any filter within `T` match.
 #[derive(Debug, Clone)]
@@ -829,6 +1227,22 @@ macro_rules! impl_or_filter {
                 $( result = result.coalesce_or($ty.is_match($ty2)); )*
                 result
             }
+
+            #[inline]
+            fn peek_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+                #![allow(non_snake_case)]
+                let ($( $ty, )*) = &self.filters;
+                let recursive_zip!(@unzip $($ty2),*) = item;
+                let mut result: Option<bool> = None;
+                $( result = result.coalesce_or($ty.peek_match($ty2)); )*
+                result
+            }
+
+            fn describe(&self) -> FilterDescription {
+                #![allow(non_snake_case)]
+                let ($( $ty, )*) = &self.filters;
+                FilterDescription::Or(vec![$( $ty.describe() ),*])
+            }
         }
 
         impl<$( $ty ),*> std::ops::Not for Or<($( $ty, )*)> {
@@ -889,11 +1303,42 @@ impl_or_filter!(A => a, B => b, C => c);
 impl_or_filter!(A => a, B => b, C => c, D => d);
 impl_or_filter!(A => a, B => b, C => c, D => d, E => e);
 impl_or_filter!(A => a, B => b, C => c, D => d, E => e, F => f);
+impl_or_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g);
+impl_or_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h);
+impl_or_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i);
+impl_or_filter!(A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j);
+impl_or_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k
+);
+impl_or_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l
+);
+impl_or_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m
+);
+impl_or_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m, N => n
+);
+impl_or_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m, N => n, O => o
+);
+impl_or_filter!(
+    A => a, B => b, C => c, D => d, E => e, F => f, G => g, H => h, I => i, J => j, K => k, L => l,
+    M => m, N => n, O => o, P => p
+);
 
 /// A filter qhich requires that all chunks contain entity data components of type `T`.
-#[derive(Debug)]
 pub struct ComponentFilter<T>(PhantomData<T>);
 
+impl<T> std::fmt::Debug for ComponentFilter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ComponentFilter<{}>", std::any::type_name::<T>())
+    }
+}
+
 impl<T: Component> ComponentFilter<T> {
     fn new() -> Self { ComponentFilter(PhantomData) }
 }
@@ -917,6 +1362,10 @@ impl<'a, T: Component> Filter<ArchetypeFilterData<'a>> for ComponentFilter<T> {
     fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
         Some(item.contains(&ComponentTypeId::of::<T>()))
     }
+
+    fn describe(&self) -> FilterDescription {
+        FilterDescription::Component(std::any::type_name::<T>())
+    }
 }
 
 impl<T> std::ops::Not for ComponentFilter<T> {
@@ -962,10 +1411,148 @@ impl<'a, T> std::ops::BitOr<Passthrough> for ComponentFilter<T> {
     fn bitor(self, _: Passthrough) -> Self::Output { self }
 }
 
+/// How a `ComponentSetFilter` compares each archetype's component types with its own.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize))]
+pub enum ComponentSetMatch {
+    /// The archetype has exactly the filter's types.
+    Exact,
+    /// The archetype has all of the filter's types.
+    Superset,
+    /// The archetype has only types which are in the filter.
+    Subset,
+}
+
+/// A filter which compares the full set of entity data component types in each archetype with
+/// a set of types.
+#[derive(Debug, Clone)]
+pub struct ComponentSetFilter {
+    types: Vec<ComponentTypeId>,
+    mode: ComponentSetMatch,
+}
+
+impl ComponentSetFilter {
+    fn new(types: &[ComponentTypeId], mode: ComponentSetMatch) -> Self {
+        let mut types = types.to_vec();
+        types.sort();
+        types.dedup();
+        ComponentSetFilter { types, mode }
+    }
+}
+
+impl ActiveFilter for ComponentSetFilter {}
+
+impl<'a> Filter<ArchetypeFilterData<'a>> for ComponentSetFilter {
+    type Iter = SliceVecIter<'a, ComponentTypeId>;
+
+    #[inline]
+    fn collect(&self, source: ArchetypeFilterData<'a>) -> Self::Iter {
+        source.component_types.iter()
+    }
+
+    #[inline]
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        // an archetype's component types are unique
+        let contains_all = || self.types.iter().all(|t| item.contains(t));
+        let contained = || item.iter().all(|t| self.types.binary_search(t).is_ok());
+        Some(match self.mode {
+            ComponentSetMatch::Exact => item.len() == self.types.len() && contains_all(),
+            ComponentSetMatch::Superset => contains_all(),
+            ComponentSetMatch::Subset => contained(),
+        })
+    }
+
+    fn describe(&self) -> FilterDescription {
+        FilterDescription::ComponentSet(self.mode, self.types.len())
+    }
+}
+
+impl std::ops::Not for ComponentSetFilter {
+    type Output = Not<Self>;
+
+    #[inline]
+    fn not(self) -> Self::Output { Not { filter: self } }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitAnd<Rhs> for ComponentSetFilter {
+    type Output = And<(Self, Rhs)>;
+
+    #[inline]
+    fn bitand(self, rhs: Rhs) -> Self::Output {
+        And {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitAnd<Passthrough> for ComponentSetFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, _: Passthrough) -> Self::Output { self }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitOr<Rhs> for ComponentSetFilter {
+    type Output = Or<(Self, Rhs)>;
+
+    #[inline]
+    fn bitor(self, rhs: Rhs) -> Self::Output {
+        Or {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitOr<Passthrough> for ComponentSetFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, _: Passthrough) -> Self::Output { self }
+}
+
+/// A tuple of entity data component types, e.g. `(Position, Velocity)`.
+pub trait ComponentTypeSet {
+    /// Gets the ID of each component type in the tuple.
+    fn component_types() -> Vec<ComponentTypeId>;
+}
+
+macro_rules! impl_component_type_set {
+    ( $( $ty: ident ),* ) => {
+        impl<$( $ty: Component ),*> ComponentTypeSet for ($( $ty, )*) {
+            fn component_types() -> Vec<ComponentTypeId> {
+                vec![$( ComponentTypeId::of::<$ty>() ),*]
+            }
+        }
+    };
+}
+
+impl_component_type_set!();
+impl_component_type_set!(A);
+impl_component_type_set!(A, B);
+impl_component_type_set!(A, B, C);
+impl_component_type_set!(A, B, C, D);
+impl_component_type_set!(A, B, C, D, E);
+impl_component_type_set!(A, B, C, D, E, F);
+impl_component_type_set!(A, B, C, D, E, F, G);
+impl_component_type_set!(A, B, C, D, E, F, G, H);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I, J);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I, J, K);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_component_type_set!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
 /// A filter which requires that all chunks contain shared tag data of type `T`.
-#[derive(Debug)]
 pub struct TagFilter<T>(PhantomData<T>);
 
+impl<T> std::fmt::Debug for TagFilter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "TagFilter<{}>", std::any::type_name::<T>())
+    }
+}
+
 impl<T: Tag> TagFilter<T> {
     fn new() -> Self { TagFilter(PhantomData) }
 }
@@ -987,6 +1574,8 @@ impl<'a, T: Tag> Filter<ArchetypeFilterData<'a>> for TagFilter<T> {
     fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
         Some(item.contains(&TagTypeId::of::<T>()))
     }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Tag(std::any::type_name::<T>()) }
 }
 
 impl<T> std::ops::Not for TagFilter<T> {
@@ -1033,11 +1622,16 @@ impl<'a, T> std::ops::BitOr<Passthrough> for TagFilter<T> {
 }
 
 /// A filter which requires that all chunks contain a specific tag value.
-#[derive(Debug)]
 pub struct TagValueFilter<'a, T> {
     value: &'a T,
 }
 
+impl<'a, T> std::fmt::Debug for TagValueFilter<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "TagValueFilter<{}>", std::any::type_name::<T>())
+    }
+}
+
 impl<'a, T: Tag> TagValueFilter<'a, T> {
     fn new(value: &'a T) -> Self { TagValueFilter { value } }
 }
@@ -1068,6 +1662,10 @@ impl<'a, 'b, T: Tag> Filter<ChunksetFilterData<'a>> for TagValueFilter<'b, T> {
     fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
         Some(**item == *self.value)
     }
+
+    fn describe(&self) -> FilterDescription {
+        FilterDescription::TagValue(std::any::type_name::<T>())
+    }
 }
 
 impl<'a, T> std::ops::Not for TagValueFilter<'a, T> {
@@ -1115,32 +1713,76 @@ impl<'a, T> std::ops::BitOr<Passthrough> for TagValueFilter<'a, T> {
 
 /// A filter which requires that entity data of type `T` has changed within the
 /// chunk since the last time the filter was executed.
-#[derive(Debug)]
+///
+/// Clones of this filter start without any recorded versions, so each clone tracks changes
+/// independently and matches every chunk the first time it is executed.
 pub struct ComponentChangedFilter<T: Component> {
-    last_read_version: AtomicU64,
+    last_read_versions: ChunkVersionMap,
+    #[cfg(feature = "testing")]
+    checked: AtomicUsize,
+    #[cfg(feature = "testing")]
+    matched: AtomicUsize,
     phantom: PhantomData<T>,
 }
 
+/// Counts the chunks checked and matched by a `ComponentChangedFilter`.
+#[cfg(feature = "testing")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChangedFilterStats {
+    /// The number of chunks whose versions the filter has checked and recorded.
+    pub checked: usize,
+    /// The number of checked chunks which had changed.
+    pub matched: usize,
+}
+
 impl<T: Component> ComponentChangedFilter<T> {
     fn new() -> ComponentChangedFilter<T> {
         ComponentChangedFilter {
-            last_read_version: AtomicU64::new(0),
+            last_read_versions: ChunkVersionMap::new(),
+            #[cfg(feature = "testing")]
+            checked: AtomicUsize::new(0),
+            #[cfg(feature = "testing")]
+            matched: AtomicUsize::new(0),
             phantom: PhantomData,
         }
     }
+
+    /// Gets the number of chunks the filter has checked and matched since it was created or
+    /// since `reset_stats` was last called.
+    ///
+    /// Chunks which are only peeked at, such as by `Query::count`, are not counted.
+    #[cfg(feature = "testing")]
+    pub fn stats(&self) -> ChangedFilterStats {
+        ChangedFilterStats {
+            checked: self.checked.load(Ordering::Relaxed),
+            matched: self.matched.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets the filter's counters to zero, without forgetting the versions it has recorded.
+    #[cfg(feature = "testing")]
+    pub fn reset_stats(&self) {
+        self.checked.store(0, Ordering::Relaxed);
+        self.matched.store(0, Ordering::Relaxed);
+    }
 }
 
 impl<T: Component> ActiveFilter for ComponentChangedFilter<T> {}
 
-impl<T: Component> Clone for ComponentChangedFilter<T> {
-    fn clone(&self) -> Self {
-        Self {
-            last_read_version: AtomicU64::new(self.last_read_version.load(Ordering::Relaxed)),
-            phantom: PhantomData,
-        }
+impl<T: Component> std::fmt::Debug for ComponentChangedFilter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ComponentChangedFilter")
+            .field("type", &std::any::type_name::<T>())
+            .field("entries", &self.last_read_versions.len())
+            .finish()
     }
 }
 
+impl<T: Component> Clone for ComponentChangedFilter<T> {
+    fn clone(&self) -> Self { Self::new() }
+}
+
 impl<'a, T: Component> Filter<ChunkFilterData<'a>> for ComponentChangedFilter<T> {
     type Iter = Iter<'a, ComponentStorage>;
 
@@ -1154,29 +1796,30 @@ impl<'a, T: Component> Filter<ChunkFilterData<'a>> for ComponentChangedFilter<T>
         }
 
         let version = components.unwrap().version();
-        let mut last_read = self.last_read_version.load(Ordering::Relaxed);
-        if last_read < version {
-            loop {
-                match self.last_read_version.compare_exchange_weak(
-                    last_read,
-                    version,
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                ) {
-                    Ok(_) => break,
-                    Err(stored_last_read) => {
-                        last_read = stored_last_read;
-                        if last_read < version {
-                            // matched version is already considered visited, update no longer needed
-                            break;
-                        }
-                    }
-                }
+        let last_read = self.last_read_versions.update(item.dense_index(), version);
+        let changed = version.is_newer_than(last_read);
+        #[cfg(feature = "testing")]
+        {
+            self.checked.fetch_add(1, Ordering::Relaxed);
+            if changed {
+                self.matched.fetch_add(1, Ordering::Relaxed);
             }
-            Some(true)
-        } else {
-            Some(false)
         }
+        Some(changed)
+    }
+
+    #[inline]
+    fn peek_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        let version = match item.components(ComponentTypeId::of::<T>()) {
+            Some(components) => components.version(),
+            None => return Some(false),
+        };
+        let last_read = self.last_read_versions.get(item.dense_index());
+        Some(version.is_newer_than(last_read))
+    }
+
+    fn describe(&self) -> FilterDescription {
+        FilterDescription::Changed(std::any::type_name::<T>())
     }
 }
 
@@ -1223,6 +1866,247 @@ impl<'a, T: Component> std::ops::BitOr<Passthrough> for ComponentChangedFilter<T
     fn bitor(self, _: Passthrough) -> Self::Output { self }
 }
 
+/// A filter which requires that a chunk contains entities spawned before a given world tick.
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnedBeforeFilter {
+    tick: u64,
+}
+
+impl SpawnedBeforeFilter {
+    fn new(tick: u64) -> Self { Self { tick } }
+}
+
+impl ActiveFilter for SpawnedBeforeFilter {}
+
+impl<'a> Filter<ChunkFilterData<'a>> for SpawnedBeforeFilter {
+    type Iter = Iter<'a, ComponentStorage>;
+
+    fn collect(&self, source: ChunkFilterData<'a>) -> Self::Iter { source.chunks.iter() }
+
+    #[inline]
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        match item.spawn_tick_range() {
+            Some((min, _)) => Some(min < self.tick),
+            None => Some(false),
+        }
+    }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::SpawnedBefore(self.tick) }
+}
+
+impl std::ops::Not for SpawnedBeforeFilter {
+    type Output = Not<Self>;
+
+    #[inline]
+    fn not(self) -> Self::Output { Not { filter: self } }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitAnd<Rhs> for SpawnedBeforeFilter {
+    type Output = And<(Self, Rhs)>;
+
+    #[inline]
+    fn bitand(self, rhs: Rhs) -> Self::Output {
+        And {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitAnd<Passthrough> for SpawnedBeforeFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, _: Passthrough) -> Self::Output { self }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitOr<Rhs> for SpawnedBeforeFilter {
+    type Output = Or<(Self, Rhs)>;
+
+    #[inline]
+    fn bitor(self, rhs: Rhs) -> Self::Output {
+        Or {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitOr<Passthrough> for SpawnedBeforeFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, _: Passthrough) -> Self::Output { self }
+}
+
+/// A filter which requires that the number of entities in a chunk lies within a range.
+#[derive(Debug, Clone, Copy)]
+pub struct EntityCountFilter {
+    min: usize,
+    max: usize,
+}
+
+impl EntityCountFilter {
+    fn new(min: usize, max: usize) -> Self { Self { min, max } }
+}
+
+impl ActiveFilter for EntityCountFilter {}
+
+impl<'a> Filter<ChunkFilterData<'a>> for EntityCountFilter {
+    type Iter = Iter<'a, ComponentStorage>;
+
+    fn collect(&self, source: ChunkFilterData<'a>) -> Self::Iter { source.chunks.iter() }
+
+    #[inline]
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        Some(item.len() >= self.min && item.len() <= self.max)
+    }
+
+    fn describe(&self) -> FilterDescription {
+        match (self.min, self.max) {
+            (min, usize::MAX) => FilterDescription::MinEntities(min),
+            (0, max) => FilterDescription::MaxEntities(max),
+            (min, max) => FilterDescription::And(vec![
+                FilterDescription::MinEntities(min),
+                FilterDescription::MaxEntities(max),
+            ]),
+        }
+    }
+}
+
+impl std::ops::Not for EntityCountFilter {
+    type Output = Not<Self>;
+
+    #[inline]
+    fn not(self) -> Self::Output { Not { filter: self } }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitAnd<Rhs> for EntityCountFilter {
+    type Output = And<(Self, Rhs)>;
+
+    #[inline]
+    fn bitand(self, rhs: Rhs) -> Self::Output {
+        And {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitAnd<Passthrough> for EntityCountFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, _: Passthrough) -> Self::Output { self }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitOr<Rhs> for EntityCountFilter {
+    type Output = Or<(Self, Rhs)>;
+
+    #[inline]
+    fn bitor(self, rhs: Rhs) -> Self::Output {
+        Or {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitOr<Passthrough> for EntityCountFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, _: Passthrough) -> Self::Output { self }
+}
+
+/// A filter which requires that a chunk may contain entity data of type `T` within a range,
+/// according to the chunk's `MinMaxSummary<T>`.
+#[derive(Debug, Clone)]
+pub struct ComponentRangeFilter<T> {
+    start: Bound<T>,
+    end: Bound<T>,
+}
+
+impl<T: Component + PartialOrd + Clone> ComponentRangeFilter<T> {
+    fn new<R: RangeBounds<T>>(range: R) -> Self {
+        fn cloned<T: Clone>(bound: Bound<&T>) -> Bound<T> {
+            match bound {
+                Bound::Included(x) => Bound::Included(x.clone()),
+                Bound::Excluded(x) => Bound::Excluded(x.clone()),
+                Bound::Unbounded => Bound::Unbounded,
+            }
+        }
+
+        Self {
+            start: cloned(range.start_bound()),
+            end: cloned(range.end_bound()),
+        }
+    }
+}
+
+impl<T: Component> ActiveFilter for ComponentRangeFilter<T> {}
+
+impl<'a, T: Component + PartialOrd + Clone> Filter<ChunkFilterData<'a>>
+    for ComponentRangeFilter<T>
+{
+    type Iter = Iter<'a, ComponentStorage>;
+
+    fn collect(&self, source: ChunkFilterData<'a>) -> Self::Iter { source.chunks.iter() }
+
+    #[inline]
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        if item.is_empty() {
+            return Some(false);
+        }
+
+        match item.summary::<T, MinMaxSummary<T>>() {
+            Some(summary) => Some(summary.overlaps(&(self.start.clone(), self.end.clone()))),
+            None => Some(true),
+        }
+    }
+
+    fn describe(&self) -> FilterDescription { FilterDescription::Range(std::any::type_name::<T>()) }
+}
+
+impl<T: Component> std::ops::Not for ComponentRangeFilter<T> {
+    type Output = Not<Self>;
+
+    #[inline]
+    fn not(self) -> Self::Output { Not { filter: self } }
+}
+
+impl<T: Component, Rhs: ActiveFilter> std::ops::BitAnd<Rhs> for ComponentRangeFilter<T> {
+    type Output = And<(Self, Rhs)>;
+
+    #[inline]
+    fn bitand(self, rhs: Rhs) -> Self::Output {
+        And {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl<T: Component> std::ops::BitAnd<Passthrough> for ComponentRangeFilter<T> {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, _: Passthrough) -> Self::Output { self }
+}
+
+impl<T: Component, Rhs: ActiveFilter> std::ops::BitOr<Rhs> for ComponentRangeFilter<T> {
+    type Output = Or<(Self, Rhs)>;
+
+    #[inline]
+    fn bitor(self, rhs: Rhs) -> Self::Output {
+        Or {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl<T: Component> std::ops::BitOr<Passthrough> for ComponentRangeFilter<T> {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, _: Passthrough) -> Self::Output { self }
+}
+
 #[cfg(test)]
 mod test {
     use super::filter_fns::*;
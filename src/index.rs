@@ -0,0 +1,289 @@
+//! Keeps an external index of entity data in sync with a `World`.
+//!
+//! `SyncedIndex` subscribes to the world's insertion and removal events for a component type and
+//! uses chunk versions to find chunks whose data may have been written since the last update.
+//! Changed chunks are read in bulk and their values handed to an `IndexBackend`.
+//!
+//! ```rust
+//! # use legion::prelude::*;
+//! # use legion::index::*;
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! struct Position(f32, f32);
+//!
+//! impl GridPosition for Position {
+//!     fn grid_position(&self) -> (f32, f32) { (self.0, self.1) }
+//! }
+//!
+//! let universe = Universe::new();
+//! let mut world = universe.create_world();
+//! let mut index = SyncedIndex::<Position, _>::new(&mut world, UniformGrid::new(10.0));
+//!
+//! let entity = world.insert((), vec![(Position(1.0, 1.0),)])[0];
+//! index.update(&world);
+//!
+//! let found = index.query_region(&Rect::new((0.0, 0.0), (5.0, 5.0)));
+//! assert_eq!(vec![entity], found);
+//! ```
+use crate::entity::Entity;
+use crate::event::Event;
+use crate::filter::filter_fns::component;
+use crate::storage::ChunkId;
+use crate::storage::Component;
+use crate::storage::ComponentTypeId;
+use crate::storage::Version;
+use crate::world::World;
+use crossbeam_channel::Receiver;
+use fxhash::FxHashMap;
+use fxhash::FxHashSet;
+use std::marker::PhantomData;
+
+/// A spatial (or otherwise keyed) lookup structure which can be kept in sync by a `SyncedIndex`.
+pub trait IndexBackend<T>: Send + Sync {
+    /// Describes an area to search within the index.
+    type Region;
+
+    /// Inserts or updates the entry for the given entity.
+    fn update(&mut self, entity: Entity, value: &T);
+
+    /// Removes the entry for the given entity, if one exists.
+    fn remove(&mut self, entity: Entity);
+
+    /// Finds all entities within the given region.
+    fn query_region(&self, region: &Self::Region) -> Vec<Entity>;
+}
+
+/// Maintains an `IndexBackend` for the values of component `T` stored in a world.
+pub struct SyncedIndex<T: Component, I: IndexBackend<T>> {
+    backend: I,
+    receiver: Receiver<Event>,
+    locations: FxHashMap<Entity, ChunkId>,
+    versions: FxHashMap<ChunkId, Version>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Component, I: IndexBackend<T>> SyncedIndex<T, I> {
+    /// Creates a new index and subscribes it to events from the given world.
+    ///
+    /// Entities already present in the world are added on the first call to `update`.
+    pub fn new(world: &mut World, backend: I) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        world.subscribe(sender, component::<T>());
+
+        Self {
+            backend,
+            receiver,
+            locations: FxHashMap::default(),
+            versions: FxHashMap::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Gets the index backend.
+    pub fn backend(&self) -> &I { &self.backend }
+
+    /// Finds all entities within the given region.
+    pub fn query_region(&self, region: &I::Region) -> Vec<Entity> {
+        self.backend.query_region(region)
+    }
+
+    /// Brings the index up to date with the world.
+    ///
+    /// Entities removed from the world are removed from the backend. The values of `T` are then
+    /// re-read from every chunk which has had entities inserted, or whose `T` components may
+    /// have been written, since the last update. Each chunk's `T` components are borrowed while
+    /// they are read; a chunk whose components are already borrowed mutably is left to be
+    /// re-read by the next update.
+    ///
+    /// Returns the number of entities whose values were re-read from the world.
+    pub fn update(&mut self, world: &World) -> usize {
+        let mut dirty = FxHashSet::<ChunkId>::default();
+        let mut removed = Vec::new();
+
+        // replay structural changes in order; an entity moved between chunks is
+        // seen as a removal followed by an insertion
+        for event in self.receiver.try_iter() {
+            match event {
+                Event::EntityInserted(entity, chunk) => {
+                    self.locations.insert(entity, chunk);
+                    dirty.insert(chunk);
+                }
+                Event::EntityRemoved(entity, chunk)
+                    if self.locations.get(&entity) == Some(&chunk) =>
+                {
+                    self.locations.remove(&entity);
+                    removed.push(entity);
+                }
+                _ => {}
+            }
+        }
+
+        for entity in removed {
+            if !self.locations.contains_key(&entity) {
+                self.backend.remove(entity);
+            }
+        }
+
+        // bulk read every chunk which has been written or had entities inserted; chunks which
+        // have since been freed are not carried over into the new version map
+        let mut updated = 0;
+        let mut versions =
+            FxHashMap::with_capacity_and_hasher(self.versions.len(), Default::default());
+        let type_id = ComponentTypeId::of::<T>();
+        for archetype in world.storage().archetypes() {
+            if !archetype
+                .description()
+                .components()
+                .iter()
+                .any(|(t, _)| *t == type_id)
+            {
+                continue;
+            }
+
+            for chunkset in archetype.chunksets() {
+                for chunk in chunkset.occupied() {
+                    let components = chunk.components(type_id).unwrap();
+                    let version = components.version();
+                    let last_version = self.versions.get(&chunk.id()).copied();
+                    if last_version == Some(version) && !dirty.contains(&chunk.id()) {
+                        versions.insert(chunk.id(), version);
+                        continue;
+                    }
+
+                    // without a recorded version, the chunk is re-read by the next update
+                    let values = match unsafe { components.try_data_slice::<T>() } {
+                        Ok(values) => values,
+                        Err(_) => continue,
+                    };
+                    versions.insert(chunk.id(), version);
+                    for (entity, value) in chunk.entities().iter().zip(values.iter()) {
+                        self.locations.insert(*entity, chunk.id());
+                        self.backend.update(*entity, value);
+                        updated += 1;
+                    }
+                }
+            }
+        }
+
+        self.versions = versions;
+        updated
+    }
+}
+
+/// A component which can be stored in a `UniformGrid`.
+pub trait GridPosition {
+    /// Gets the 2D position of the component.
+    fn grid_position(&self) -> (f32, f32);
+}
+
+/// An axis-aligned rectangle, used to query a `UniformGrid`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rect {
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+impl Rect {
+    /// Creates a new rectangle from its minimum and maximum corners.
+    pub fn new(min: (f32, f32), max: (f32, f32)) -> Self { Self { min, max } }
+
+    /// Determines if the rectangle contains the given point.
+    pub fn contains(&self, point: (f32, f32)) -> bool {
+        point.0 >= self.min.0
+            && point.0 <= self.max.0
+            && point.1 >= self.min.1
+            && point.1 <= self.max.1
+    }
+}
+
+/// A simple index backend which buckets entities into square cells of a fixed size.
+#[derive(Debug, Default)]
+pub struct UniformGrid {
+    cell_size: f32,
+    cells: FxHashMap<(i32, i32), Vec<Entity>>,
+    positions: FxHashMap<Entity, (f32, f32)>,
+    cell_moves: usize,
+}
+
+impl UniformGrid {
+    /// Creates a new grid with the given cell size.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            ..Self::default()
+        }
+    }
+
+    /// Gets the number of times an entity has been moved between (or into) cells.
+    pub fn cell_moves(&self) -> usize { self.cell_moves }
+
+    /// Gets the number of entities stored in the grid.
+    pub fn len(&self) -> usize { self.positions.len() }
+
+    /// Determines if the grid is empty.
+    pub fn is_empty(&self) -> bool { self.len() < 1 }
+
+    fn cell(&self, position: (f32, f32)) -> (i32, i32) {
+        (
+            (position.0 / self.cell_size).floor() as i32,
+            (position.1 / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn remove_from_cell(&mut self, entity: Entity, cell: (i32, i32)) {
+        if let Some(entities) = self.cells.get_mut(&cell) {
+            entities.retain(|e| *e != entity);
+            if entities.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+}
+
+impl<T: GridPosition> IndexBackend<T> for UniformGrid {
+    type Region = Rect;
+
+    fn update(&mut self, entity: Entity, value: &T) {
+        let position = value.grid_position();
+        let cell = self.cell(position);
+
+        if let Some(previous) = self.positions.insert(entity, position) {
+            let previous = self.cell(previous);
+            if previous == cell {
+                return;
+            }
+
+            self.remove_from_cell(entity, previous);
+        }
+
+        self.cells.entry(cell).or_default().push(entity);
+        self.cell_moves += 1;
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(position) = self.positions.remove(&entity) {
+            let cell = self.cell(position);
+            self.remove_from_cell(entity, cell);
+        }
+    }
+
+    fn query_region(&self, region: &Rect) -> Vec<Entity> {
+        let min = self.cell(region.min);
+        let max = self.cell(region.max);
+
+        let mut found = Vec::new();
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(entities) = self.cells.get(&(x, y)) {
+                    found.extend(
+                        entities
+                            .iter()
+                            .filter(|e| region.contains(self.positions[e]))
+                            .copied(),
+                    );
+                }
+            }
+        }
+
+        found
+    }
+}
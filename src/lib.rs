@@ -226,6 +226,15 @@
 //!  * `log`: Configures `tracing` to redirect events to the `log` crate. This is a convenience feature for applications
 //!  that use `log` and do not wish to interact with `tracing`.
 //!  * `events`: Enables eventing APIs on worlds (enabled by default).
+//!  * `index`: Enables `SyncedIndex`, which keeps external lookup structures in sync with a world.
+//!  * `testing`: Enables utilities for comparing worlds in tests, such as `assert_worlds_equal!`, and the chunk counters of `changed` filters.
+//!  * `ffi`: Enables the experimental C API in the `c_api` module.
+//!  * `prefetch`: Prefetches the components of upcoming chunks while iterating queries. See `query::set_prefetch_distance`.
+//!  * `serde-1`: Implements `Serialize` and `Deserialize` for `Entity` and allocator statistics.
+//!  * `rand`: Enables random sampling of query results with `Query::sample`.
+//!  * `numa`: Enables `World::set_chunk_allocator`, which allocates chunks from memory matching their placement hints. See the `placement` module.
+//!  * `debug-labels`: Records entity labels set with `World::set_debug_label` in release builds, as they are in debug builds.
+//!  * `borrow-checks`: Performs runtime borrow checks in release builds, as they are in debug builds. See `borrow::BorrowConflictPolicy`.
 #![allow(dead_code)]
 
 #[macro_use]
@@ -233,16 +242,30 @@
 extern crate easy_ffi;
 
 pub mod borrow;
+pub mod chunk_state;
+pub mod clone;
+pub mod collections;
 pub mod command;
 pub mod entity;
 pub mod event;
 pub mod filter;
+#[cfg(feature = "index")]
+pub mod index;
 pub mod iterator;
+pub mod mirror;
+pub mod partition;
+#[cfg(feature = "par-iter")]
+pub mod placement;
+pub mod pod;
 pub mod query;
 pub mod resource;
 pub mod schedule;
+pub mod stability;
 pub mod storage;
+pub mod summary;
 pub mod system;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod world;
 #[cfg(feature = "ffi")]
 pub mod c_api;
@@ -258,9 +281,11 @@ pub mod prelude {
     pub use crate::entity::Entity;
     pub use crate::event::Event;
     pub use crate::filter::filter_fns::*;
-    pub use crate::query::{IntoQuery, Query, Read, Tagged, TryRead, TryWrite, Write};
+    pub use crate::query::{
+        Changed, Entities, Exclude, Has, IntoQuery, Query, Read, Tagged, TryRead, TryWrite, Write,
+    };
     pub use crate::resource::{ResourceSet, Resources};
-    pub use crate::schedule::{Executor, Runnable, Schedulable, Schedule};
+    pub use crate::schedule::{Executor, Runnable, Schedulable, Schedule, ShouldRun};
     pub use crate::system::{System, SystemBuilder};
     pub use crate::world::{Universe, World};
     pub use bit_set::BitSet;
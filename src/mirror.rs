@@ -0,0 +1,235 @@
+//! Mirroring component data into external buffers, such as GPU staging buffers.
+//!
+//! A `ComponentMirror` copies the components of each chunk matched by a query into a
+//! `MirrorTarget`, giving each chunk a stable region of the target. Chunks whose components
+//! have not changed since the mirror was last updated are not copied again.
+
+use crate::filter::EntityFilter;
+use crate::pod::Pod;
+use crate::query::{Query, ReadOnly, View};
+use crate::storage::{ChunkId, ComponentTypeId, Version};
+use crate::world::World;
+use fxhash::FxHashMap;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// A byte buffer which component data can be mirrored into, e.g. a GPU staging buffer.
+pub trait MirrorTarget {
+    /// Grows the buffer to hold at least `bytes` bytes, preserving its contents.
+    fn ensure_capacity(&mut self, bytes: usize);
+
+    /// Copies `bytes` into the buffer at `offset`.
+    fn write(&mut self, offset: usize, bytes: &[u8]);
+
+    /// Marks a range of the buffer which has been written since the last update, e.g. so that
+    /// only that range is uploaded.
+    fn mark_range_dirty(&mut self, range: Range<usize>);
+}
+
+/// The region of a `MirrorTarget` which holds the components of a chunk.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct MirroredChunk {
+    /// The offset of the region, in bytes.
+    pub offset: usize,
+    /// The number of components in the region.
+    pub len: usize,
+    /// The size of the region, in bytes. This is large enough to hold a full chunk.
+    pub capacity: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Slot {
+    chunk: MirroredChunk,
+    version: Version,
+    seen: u64,
+}
+
+/// Mirrors the `T` components of the chunks matched by a query into a `MirrorTarget`.
+///
+/// Each chunk is assigned a region of the target which is large enough to hold a full chunk,
+/// and which does not move for as long as the chunk is matched by the query. Regions of chunks
+/// which are no longer matched are reused for new chunks of the same capacity.
+///
+/// Components are copied byte for byte, so `T` must be a `Pod` type, and should have the layout
+/// expected by the consumer of the target.
+///
+/// ```
+/// # use legion::prelude::*;
+/// # use legion::mirror::{ComponentMirror, MirrorTarget};
+/// # use legion::pod::Pod;
+/// # use std::ops::Range;
+/// #[derive(Copy, Clone, Debug, PartialEq)]
+/// #[repr(C)]
+/// struct InstanceTransform([f32; 4]);
+///
+/// unsafe impl Pod for InstanceTransform {}
+///
+/// #[derive(Default)]
+/// struct Staging(Vec<u8>);
+///
+/// impl MirrorTarget for Staging {
+///     fn ensure_capacity(&mut self, bytes: usize) {
+///         if self.0.len() < bytes {
+///             self.0.resize(bytes, 0);
+///         }
+///     }
+///
+///     fn write(&mut self, offset: usize, bytes: &[u8]) {
+///         self.0[offset..offset + bytes.len()].copy_from_slice(bytes);
+///     }
+///
+///     fn mark_range_dirty(&mut self, _: Range<usize>) {}
+/// }
+///
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// world.insert((), (0..10).map(|_| (InstanceTransform([1.0; 4]),)));
+///
+/// let query = Read::<InstanceTransform>::query();
+/// let mut mirror = ComponentMirror::<InstanceTransform, _>::new(Staging::default());
+/// mirror.update(&world, &query);
+/// for (_, chunk) in mirror.chunks() {
+///     // draw `chunk.len` instances starting at `chunk.offset`
+///     assert_eq!(10, chunk.len);
+/// }
+/// ```
+pub struct ComponentMirror<T: Pod, M: MirrorTarget> {
+    target: M,
+    slots: FxHashMap<ChunkId, Slot>,
+    free: FxHashMap<usize, Vec<usize>>,
+    len: usize,
+    pump: u64,
+    component: PhantomData<T>,
+}
+
+impl<T: Pod, M: MirrorTarget> ComponentMirror<T, M> {
+    /// Creates a mirror which writes into `target`.
+    pub fn new(target: M) -> Self {
+        Self {
+            target,
+            slots: FxHashMap::default(),
+            free: FxHashMap::default(),
+            len: 0,
+            pump: 0,
+            component: PhantomData,
+        }
+    }
+
+    /// Gets the target.
+    pub fn target(&self) -> &M { &self.target }
+
+    /// Gets the target mutably.
+    pub fn target_mut(&mut self) -> &mut M { &mut self.target }
+
+    /// Consumes the mirror, returning its target.
+    pub fn into_target(self) -> M { self.target }
+
+    /// Gets the number of bytes of the target which are in use, including regions which are
+    /// free to be reused.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Determines if no regions of the target are in use.
+    pub fn is_empty(&self) -> bool { self.len() < 1 }
+
+    /// Gets the region of the target which holds the components of a chunk.
+    pub fn chunk(&self, id: ChunkId) -> Option<MirroredChunk> {
+        self.slots.get(&id).map(|slot| slot.chunk)
+    }
+
+    /// Gets the regions of the target which hold the components of each mirrored chunk.
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkId, MirroredChunk)> + '_ {
+        self.slots.iter().map(|(id, slot)| (*id, slot.chunk))
+    }
+
+    /// Copies the components of each chunk matched by `query` whose `T` components have changed
+    /// since the last update into the target.
+    ///
+    /// Regions of chunks which are no longer matched are released. The query should not use
+    /// stateful filters such as `changed`, as chunks which they skip are treated as unmatched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query's view does not read `T`.
+    pub fn update<V, F>(&mut self, world: &World, query: &Query<V, F>)
+    where
+        V: for<'v> View<'v> + ReadOnly,
+        F: EntityFilter,
+    {
+        if !V::reads::<T>() {
+            panic!("data type not readable via this query");
+        }
+
+        self.pump += 1;
+        let element_size = std::mem::size_of::<T>();
+        let type_id = ComponentTypeId::of::<T>();
+
+        for chunk in query.iter_chunks_immutable(world) {
+            let storage = chunk.storage();
+            let components = match storage.components(type_id) {
+                Some(components) => components,
+                None => continue,
+            };
+            let version = components.version();
+            let len = components.len();
+
+            let id = storage.id();
+            let slot = match self.slots.get_mut(&id) {
+                Some(slot) => {
+                    slot.seen = self.pump;
+                    if slot.chunk.len == len && !version.is_newer_than(slot.version) {
+                        continue;
+                    }
+                    slot
+                }
+                None => {
+                    let capacity = storage.capacity() * element_size;
+                    let offset = self.allocate(capacity);
+                    let chunk = MirroredChunk {
+                        offset,
+                        len,
+                        capacity,
+                    };
+                    self.slots.entry(id).or_insert(Slot {
+                        chunk,
+                        version,
+                        seen: self.pump,
+                    })
+                }
+            };
+            slot.chunk.len = len;
+            slot.version = version;
+
+            let data = chunk.components::<T>().unwrap();
+            // `T: Pod` guarantees that the components contain no uninitialised padding
+            let bytes = unsafe {
+                std::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * element_size)
+            };
+            let offset = slot.chunk.offset;
+            self.target.write(offset, bytes);
+            self.target.mark_range_dirty(offset..offset + bytes.len());
+        }
+
+        // release the regions of chunks which were not matched
+        let pump = self.pump;
+        let free = &mut self.free;
+        self.slots.retain(|_, slot| {
+            if slot.seen != pump {
+                let chunk = slot.chunk;
+                free.entry(chunk.capacity).or_default().push(chunk.offset);
+            }
+            slot.seen == pump
+        });
+    }
+
+    fn allocate(&mut self, capacity: usize) -> usize {
+        if let Some(offset) = self.free.get_mut(&capacity).and_then(|free| free.pop()) {
+            return offset;
+        }
+
+        let offset = self.len;
+        self.len += capacity;
+        self.target.ensure_capacity(self.len);
+        offset
+    }
+}
@@ -0,0 +1,220 @@
+//! Views of a world which are restricted to the entities with one value of a tag.
+//!
+//! A `WorldPartition` applies a `tag_value` filter to every query executed through it, and
+//! attaches its tag to every entity inserted through it, so code which is handed a partition
+//! instead of the world cannot see or create entities outside of it.
+
+use crate::entity::Entity;
+use crate::filter::filter_fns::{tag, tag_value};
+use crate::filter::{And, EntityFilter, EntityFilterTuple, TagFilter, TagValueFilter};
+use crate::query::{Chunk, ChunkDataIter, ChunkEntityIter, ChunkViewIter, Query, View};
+use crate::storage::{ArchetypeData, Tag, TagTypeId};
+use crate::world::{IntoComponentSource, World};
+
+/// The filter of a query executed through a `WorldPartition`. The query's own filters are
+/// borrowed, so stateful filters such as `changed` keep their state between executions.
+pub type PartitionFilter<'p, 'q, F, T> = EntityFilterTuple<
+    And<(&'q <F as EntityFilter>::ArchetypeFilter, TagFilter<T>)>,
+    And<(
+        &'q <F as EntityFilter>::ChunksetFilter,
+        TagValueFilter<'p, T>,
+    )>,
+    &'q <F as EntityFilter>::ChunkFilter,
+>;
+
+/// An iterator over the chunks matched by a query executed through a `WorldPartition`.
+pub type PartitionChunkIter<'a, 'p, 'q, V, F, T> = ChunkViewIter<
+    'a,
+    'a,
+    V,
+    And<(&'q <F as EntityFilter>::ArchetypeFilter, TagFilter<T>)>,
+    And<(
+        &'q <F as EntityFilter>::ChunksetFilter,
+        TagValueFilter<'p, T>,
+    )>,
+    &'q <F as EntityFilter>::ChunkFilter,
+>;
+
+/// A view of a world which only contains the entities with the tag value `T`.
+///
+/// ```
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Pos(f32);
+/// #[derive(Copy, Clone, Debug, PartialEq)]
+/// struct MatchId(u32);
+///
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// world.insert((MatchId(1),), vec![(Pos(1.0),)]);
+///
+/// let query = Read::<Pos>::query();
+/// let mut partition = world.partition(MatchId(2));
+/// partition.insert(vec![(Pos(2.0),), (Pos(3.0),)]);
+///
+/// // only the entities inserted into the partition are visible
+/// assert_eq!(2, partition.len());
+/// assert_eq!(2, partition.query(&query).count());
+/// for pos in partition.query(&query).iter() {
+///     assert!(pos.0 > 1.0);
+/// }
+/// ```
+pub struct WorldPartition<'p, T: Tag> {
+    world: &'p mut World,
+    value: T,
+}
+
+impl<'p, T: Tag> WorldPartition<'p, T> {
+    pub(crate) fn new(world: &'p mut World, value: T) -> Self { Self { world, value } }
+
+    /// Gets the tag value of the entities in the partition.
+    pub fn value(&self) -> &T { &self.value }
+
+    /// Inserts new entities into the partition, tagged with the partition's tag value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the components include the same type more than once.
+    pub fn insert<C: IntoComponentSource>(&mut self, components: C) -> &[Entity] {
+        self.world.insert((self.value.clone(),), components)
+    }
+
+    /// Determines if an entity is alive and belongs to the partition.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.world.get_tag::<T>(entity) == Some(&self.value)
+    }
+
+    /// Gets the number of entities in the partition.
+    pub fn len(&self) -> usize {
+        self.chunks()
+            .flat_map(|(arch, set)| arch.chunksets()[set].occupied())
+            .map(|chunk| chunk.len())
+            .sum()
+    }
+
+    /// Determines if the partition contains no entities.
+    pub fn is_empty(&self) -> bool { self.len() < 1 }
+
+    /// Gets an iterator over the entities in the partition.
+    pub fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.chunks().flat_map(|(arch, set)| {
+            arch.chunksets()[set]
+                .occupied()
+                .iter()
+                .flat_map(|chunk| chunk.entities().iter().copied())
+        })
+    }
+
+    /// Restricts a query to the partition.
+    pub fn query<'a, 'q, V, F>(
+        &'a mut self,
+        query: &'q Query<V, F>,
+    ) -> PartitionQuery<'a, 'q, V, F, T>
+    where
+        V: for<'v> View<'v>,
+        F: EntityFilter,
+    {
+        let (arch_filter, chunkset_filter, chunk_filter) = query.filter_ref().filters();
+        let tag = tag::<T>().arch_filter;
+        let value = tag_value(&self.value).chunkset_filter;
+        let filter = EntityFilterTuple::new(
+            And {
+                filters: (arch_filter, tag),
+            },
+            And {
+                filters: (chunkset_filter, value),
+            },
+            chunk_filter,
+        );
+        PartitionQuery {
+            world: &mut *self.world,
+            query: query.with_filter(filter),
+        }
+    }
+
+    // the archetypes and chunkset indices holding the partition's entities
+    fn chunks(&self) -> impl Iterator<Item = (&ArchetypeData, usize)> + '_ {
+        let value = &self.value;
+        self.world
+            .storage()
+            .archetypes()
+            .iter()
+            .filter_map(|arch| {
+                let tags = arch.tags().get(TagTypeId::of::<T>())?;
+                Some((arch, unsafe { tags.data_slice::<T>() }))
+            })
+            .flat_map(move |(arch, values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .take(arch.len())
+                    .filter(move |(_, v)| *v == value)
+                    .map(move |(set, _)| (arch, set))
+            })
+    }
+}
+
+/// A query restricted to the entities of a `WorldPartition`.
+pub struct PartitionQuery<'p, 'q, V, F, T>
+where
+    V: for<'v> View<'v>,
+    F: EntityFilter,
+    T: Tag,
+{
+    world: &'p mut World,
+    query: Query<V, PartitionFilter<'p, 'q, F, T>>,
+}
+
+impl<'p, 'q, V, F, T> PartitionQuery<'p, 'q, V, F, T>
+where
+    V: for<'v> View<'v>,
+    F: EntityFilter,
+    T: Tag,
+{
+    /// Gets an iterator which iterates through all chunks in the partition that match the query.
+    pub fn iter_chunks<'a>(&'a mut self) -> PartitionChunkIter<'a, 'p, 'q, V, F, T> {
+        self.query.iter_chunks(self.world)
+    }
+
+    /// Gets an iterator which iterates through all entity data in the partition that matches
+    /// the query.
+    pub fn iter<'a>(&'a mut self) -> ChunkDataIter<'a, V, PartitionChunkIter<'a, 'p, 'q, V, F, T>> {
+        self.query.iter(self.world)
+    }
+
+    /// Gets an iterator which iterates through all entity data in the partition that matches
+    /// the query, and also yields the `Entity` IDs.
+    pub fn iter_entities<'a>(
+        &'a mut self,
+    ) -> ChunkEntityIter<'a, V, PartitionChunkIter<'a, 'p, 'q, V, F, T>> {
+        self.query.iter_entities(self.world)
+    }
+
+    /// Iterates through all entity data in the partition that matches the query.
+    pub fn for_each<'a, G>(&'a mut self, f: G)
+    where
+        G: FnMut(<<V as View<'a>>::Iter as Iterator>::Item),
+    {
+        self.query.for_each(self.world, f)
+    }
+
+    /// Iterates through all entity data in the partition that matches the query, and also
+    /// yields the `Entity` IDs.
+    pub fn for_each_entities<'a, G>(&'a mut self, f: G)
+    where
+        G: FnMut((Entity, <<V as View<'a>>::Iter as Iterator>::Item)),
+    {
+        self.query.for_each_entities(self.world, f)
+    }
+
+    /// Iterates through all chunks in the partition that match the query.
+    pub fn for_each_chunk<'a, G>(&'a mut self, f: G)
+    where
+        G: FnMut(Chunk<'a, V>),
+    {
+        self.query.for_each_chunk(self.world, f)
+    }
+
+    /// Counts the entities in the partition that match the query.
+    pub fn count(&self) -> usize { self.query.count(self.world) }
+}
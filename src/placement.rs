@@ -0,0 +1,114 @@
+//! Routes parallel work on each chunk to a group of threads matching the chunk's placement hint.
+//!
+//! On machines with several NUMA nodes, iterating chunks on threads of a node other than the one
+//! holding their memory wastes memory bandwidth. A world configured with several
+//! `WorldOptions::placement_groups` gives each chunk a placement hint, and a `PlacementPool`
+//! holds one thread pool per group, so that `Query::par_for_each_placed` can process each chunk
+//! on the threads of its group.
+//!
+//! Legion does not pin threads or allocate node-local memory itself. Pin the threads of each
+//! group with `rayon::ThreadPoolBuilder::start_handler`, and provide node-local chunk memory
+//! with `World::set_chunk_allocator` (requires the `numa` feature).
+//!
+//! # Examples
+//!
+//! ```
+//! # use legion::prelude::*;
+//! # use legion::placement::PlacementPool;
+//! # use legion::world::WorldOptions;
+//! # #[derive(Copy, Clone, Debug, PartialEq)]
+//! # struct Position(f32);
+//! let options = WorldOptions::default().with_placement_groups(2);
+//! let mut world = Universe::new().create_world_with_options(options);
+//! world.insert((), (0..10000).map(|i| (Position(i as f32),)));
+//!
+//! // two groups of two threads, e.g. one group per socket
+//! let pool = PlacementPool::new(2, 2).unwrap();
+//!
+//! let query = Write::<Position>::query();
+//! query.par_for_each_placed(&mut world, &pool, |mut pos| {
+//!     pos.0 += 1.0;
+//! });
+//! ```
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+/// A set of thread pools, one for each placement group.
+///
+/// Chunks whose placement hint is beyond the last group are processed by the group given by
+/// the hint modulo the number of groups.
+pub struct PlacementPool {
+    groups: Vec<ThreadPool>,
+}
+
+impl PlacementPool {
+    /// Creates `groups` thread pools of `threads` threads each.
+    ///
+    /// Threads are named `legion-placement-<group>-<index>`.
+    pub fn new(groups: usize, threads: usize) -> Result<Self, ThreadPoolBuildError> {
+        let groups = (0..std::cmp::max(1, groups))
+            .map(|group| {
+                ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(move |index| format!("legion-placement-{}-{}", group, index))
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { groups })
+    }
+
+    /// Creates a placement pool from existing thread pools, one for each placement group in
+    /// order, such as pools whose threads have been pinned to the cores of a NUMA node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `groups` is empty.
+    pub fn from_pools(groups: Vec<ThreadPool>) -> Self {
+        assert!(
+            !groups.is_empty(),
+            "a placement pool needs at least one group"
+        );
+        Self { groups }
+    }
+
+    /// Gets the number of placement groups.
+    pub fn groups(&self) -> usize { self.groups.len() }
+
+    /// Gets the thread pool of a placement group.
+    pub fn pool(&self, group: usize) -> Option<&ThreadPool> { self.groups.get(group) }
+
+    /// Gets the group which processes chunks with the given placement hint.
+    pub fn group_of(&self, placement: u8) -> usize { placement as usize % self.groups.len() }
+
+    /// Calls `f` with each item on the threads of the group matching its placement hint.
+    ///
+    /// Items are processed by all groups at once, and this returns when all have completed.
+    pub(crate) fn run<T, F>(&self, items: impl Iterator<Item = (u8, T)>, f: F)
+    where
+        T: Send,
+        F: Fn(T) + Send + Sync,
+    {
+        let mut groups = self.groups.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        for (placement, item) in items {
+            groups[self.group_of(placement)].push(item);
+        }
+
+        // each group runs on its own pool while the calling thread waits for all of them
+        let f = &f;
+        rayon::scope(|scope| {
+            for (pool, items) in self.groups.iter().zip(groups) {
+                if !items.is_empty() {
+                    scope.spawn(move |_| pool.install(|| items.into_par_iter().for_each(f)));
+                }
+            }
+        });
+    }
+}
+
+impl std::fmt::Debug for PlacementPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PlacementPool")
+            .field("groups", &self.groups.len())
+            .finish()
+    }
+}
@@ -0,0 +1,528 @@
+//! A binary format which stores chunks of plain-old-data components as raw arrays.
+//!
+//! Loading entities through `World::insert` moves each component individually. When component
+//! data is stored as raw arrays, such as in a memory-mapped level file, it can instead be copied
+//! into chunks with a single copy per array. `World::write_chunks_to_bytes` writes the chunks of
+//! a world in this format, and `World::load_chunks_from_bytes` validates a buffer against a
+//! `PodRegistry` before copying its arrays into newly allocated chunks.
+//!
+//! Only component types which implement the `Pod` marker trait and are registered under a stable
+//! name can be stored. Archetypes with tags cannot be stored, and entity IDs are not preserved;
+//! loaded entities are allocated new IDs.
+//!
+//! # Format
+//!
+//! All header integers are little-endian. Component arrays are stored in the byte order of the
+//! machine which wrote them, which is declared in the header; a buffer declaring a different byte
+//! order than that of the loading machine is rejected.
+//!
+//! ```text
+//! header:
+//!     magic           [u8; 8]     b"LGNCHUNK"
+//!     version         u32         1
+//!     byte order      u8          0 = little-endian, 1 = big-endian
+//!     reserved        [u8; 3]     zeros
+//!     type count      u32
+//!     chunk count     u32
+//! for each component type:
+//!     size            u32
+//!     align           u32
+//!     fingerprint     u64         see `ComponentMeta::with_fingerprint`
+//!     name length     u32
+//!     name            [u8]        UTF-8, the name the type is registered under
+//! for each chunk:
+//!     entity count    u32
+//!     type count      u32
+//!     types           [u32]       indices into the component types
+//!     for each type:
+//!         padding     [u8]        zeros, up to a multiple of the type's alignment from the
+//!                                 start of the buffer
+//!         components  [u8]        entity count * size bytes
+//! ```
+//!
+//! Arrays are aligned relative to the start of the buffer, so that arrays in a buffer with at
+//! least the alignment of each type (such as a memory-mapped file) are themselves aligned.
+//!
+//! # Examples
+//!
+//! ```
+//! # use legion::prelude::*;
+//! # use legion::pod::{Pod, PodRegistry};
+//! #[derive(Copy, Clone, Debug, PartialEq)]
+//! #[repr(C)]
+//! struct Position(f32, f32);
+//!
+//! unsafe impl Pod for Position {}
+//!
+//! let mut registry = PodRegistry::new();
+//! registry.register::<Position>("position");
+//!
+//! let universe = Universe::new();
+//! let mut world = universe.create_world();
+//! world.insert((), (0..100).map(|i| (Position(i as f32, 0.0),)));
+//! let bytes = world.write_chunks_to_bytes(&registry).unwrap();
+//!
+//! let mut loaded = universe.create_world();
+//! let entities = loaded.load_chunks_from_bytes(&bytes, &registry).unwrap();
+//! assert_eq!(100, entities.len());
+//! ```
+use crate::entity::EntityAllocator;
+use crate::filter::{ArchetypeFilterData, Filter};
+use crate::iterator::SliceVecIter;
+use crate::storage::ArchetypeDescription;
+use crate::storage::Component;
+use crate::storage::ComponentMeta;
+use crate::storage::ComponentStorage;
+use crate::storage::ComponentTypeId;
+use crate::storage::LayoutFingerprint;
+use crate::storage::Storage;
+use crate::world::{ComponentLayout, ComponentSource, IntoComponentSource};
+use std::ptr::NonNull;
+
+const MAGIC: &[u8; 8] = b"LGNCHUNK";
+const VERSION: u32 = 1;
+
+#[cfg(target_endian = "little")]
+const BYTE_ORDER: u8 = 0;
+#[cfg(target_endian = "big")]
+const BYTE_ORDER: u8 = 1;
+
+/// A component type which can be copied to and from raw bytes.
+///
+/// # Safety
+///
+/// The type must not contain padding bytes, pointers or references, and every bit pattern of
+/// its size must be a valid value of the type.
+pub unsafe trait Pod: Component + Copy {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => { $(unsafe impl Pod for $ty {})* };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+#[derive(Clone)]
+struct PodType {
+    name: String,
+    type_id: ComponentTypeId,
+    type_name: &'static str,
+    meta: ComponentMeta,
+}
+
+/// The set of component types which can be stored in the binary chunk format, each registered
+/// under a name which identifies it in stored buffers.
+#[derive(Clone, Default)]
+pub struct PodRegistry {
+    types: Vec<PodType>,
+}
+
+impl PodRegistry {
+    /// Creates a new empty registry.
+    pub fn new() -> Self { Self::default() }
+
+    /// Registers component type `T` under the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type or the name has already been registered.
+    pub fn register<T: Pod>(&mut self, name: &str) -> &mut Self {
+        self.register_with_fingerprint::<T>(name, 0)
+    }
+
+    /// Registers component type `T` under the given name, with a fingerprint of its internal
+    /// layout which buffers must match. See `ComponentMeta::with_fingerprint`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type or the name has already been registered.
+    pub fn register_with_fingerprint<T: Pod>(&mut self, name: &str, fingerprint: u64) -> &mut Self {
+        let type_id = ComponentTypeId::of::<T>();
+        assert!(
+            self.types
+                .iter()
+                .all(|t| t.type_id != type_id && t.name != name),
+            "{} or the name {:?} has already been registered",
+            std::any::type_name::<T>(),
+            name
+        );
+        self.types.push(PodType {
+            name: name.to_owned(),
+            type_id,
+            type_name: std::any::type_name::<T>(),
+            meta: ComponentMeta::of::<T>().with_fingerprint(fingerprint),
+        });
+        self
+    }
+
+    /// Gets the number of registered types.
+    pub fn len(&self) -> usize { self.types.len() }
+
+    /// Determines if no types have been registered.
+    pub fn is_empty(&self) -> bool { self.types.is_empty() }
+
+    fn index_of(&self, type_id: ComponentTypeId) -> Option<usize> {
+        self.types.iter().position(|t| t.type_id == type_id)
+    }
+}
+
+impl std::fmt::Debug for PodRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.types.iter().map(|t| (&t.name, t.type_name)))
+            .finish()
+    }
+}
+
+/// An error returned when writing or loading chunks in the binary chunk format.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ChunkFormatError {
+    /// The buffer does not start with the format's magic bytes.
+    BadMagic,
+    /// The buffer was written with an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The buffer's component arrays are not stored in this machine's byte order.
+    ByteOrderMismatch,
+    /// The buffer ended part way through a header or array.
+    Truncated,
+    /// The buffer names a component type which is not registered.
+    UnknownComponent(String),
+    /// The buffer's layout of a component type differs from that of the registered type.
+    LayoutMismatch {
+        /// The name the type is registered under.
+        name: String,
+        /// The layout of the registered type.
+        expected: LayoutFingerprint,
+        /// The layout recorded in the buffer.
+        found: LayoutFingerprint,
+    },
+    /// A chunk refers to a component type index beyond the end of the buffer's types, or refers
+    /// to a type more than once.
+    InvalidTypeIndex(u32),
+    /// The buffer lists the named component type more than once.
+    DuplicateComponent(String),
+    /// The world contains the named component type, which is not registered.
+    UnregisteredComponent(&'static str),
+    /// The world contains entities with the named tag type; tags cannot be stored.
+    UnsupportedTag(&'static str),
+}
+
+impl std::fmt::Display for ChunkFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ChunkFormatError::BadMagic => write!(f, "not a chunk buffer"),
+            ChunkFormatError::UnsupportedVersion(version) => {
+                write!(f, "unsupported chunk format version {}", version)
+            }
+            ChunkFormatError::ByteOrderMismatch => {
+                write!(f, "chunk buffer was written with a different byte order")
+            }
+            ChunkFormatError::Truncated => write!(f, "chunk buffer is truncated"),
+            ChunkFormatError::UnknownComponent(name) => {
+                write!(f, "unknown component type {:?}", name)
+            }
+            ChunkFormatError::LayoutMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "component layout mismatch for {:?}: expected {:?}, found {:?}",
+                name, expected, found
+            ),
+            ChunkFormatError::InvalidTypeIndex(index) => {
+                write!(f, "invalid component type index {}", index)
+            }
+            ChunkFormatError::DuplicateComponent(name) => {
+                write!(f, "duplicate component type {:?}", name)
+            }
+            ChunkFormatError::UnregisteredComponent(name) => {
+                write!(f, "component type {} is not registered", name)
+            }
+            ChunkFormatError::UnsupportedTag(name) => {
+                write!(f, "cannot store entities with tag type {}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChunkFormatError {}
+
+fn pad_to(bytes: &mut Vec<u8>, align: usize) {
+    let padding = (align - bytes.len() % align) % align;
+    bytes.resize(bytes.len() + padding, 0);
+}
+
+/// Writes every chunk in the storage.
+pub(crate) fn write_chunks(
+    storage: &Storage,
+    registry: &PodRegistry,
+) -> Result<Vec<u8>, ChunkFormatError> {
+    let mut chunks = Vec::new();
+    for archetype in storage.archetypes() {
+        let description = archetype.description();
+        let occupied = archetype
+            .chunksets()
+            .iter()
+            .flat_map(|set| set.occupied())
+            .filter(|chunk| !chunk.is_empty())
+            .collect::<Vec<_>>();
+        if occupied.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = description.tag_names().first() {
+            return Err(ChunkFormatError::UnsupportedTag(name));
+        }
+
+        let types = description
+            .components()
+            .iter()
+            .zip(description.component_names())
+            .map(|((type_id, _), name)| {
+                registry
+                    .index_of(*type_id)
+                    .ok_or(ChunkFormatError::UnregisteredComponent(name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        chunks.extend(occupied.into_iter().map(|chunk| (chunk, types.clone())));
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&[BYTE_ORDER, 0, 0, 0]);
+    bytes.extend_from_slice(&(registry.types.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+
+    for t in &registry.types {
+        let layout = t.meta.layout_fingerprint();
+        bytes.extend_from_slice(&(layout.size as u32).to_le_bytes());
+        bytes.extend_from_slice(&(layout.align as u32).to_le_bytes());
+        bytes.extend_from_slice(&layout.fingerprint.to_le_bytes());
+        bytes.extend_from_slice(&(t.name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(t.name.as_bytes());
+    }
+
+    for (chunk, types) in chunks {
+        bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(types.len() as u32).to_le_bytes());
+        for index in &types {
+            bytes.extend_from_slice(&(*index as u32).to_le_bytes());
+        }
+
+        for index in types {
+            let t = &registry.types[index];
+            pad_to(&mut bytes, t.meta.align());
+            let (ptr, size, count) = chunk.components(t.type_id).unwrap().data_raw();
+            // safe because `Pod` types have no padding, so every byte is initialized
+            let data = unsafe { std::slice::from_raw_parts(*ptr as *const u8, size * count) };
+            bytes.extend_from_slice(data);
+        }
+    }
+
+    Ok(bytes)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ChunkFormatError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(ChunkFormatError::Truncated)?;
+        let taken = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, ChunkFormatError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, ChunkFormatError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn align(&mut self, align: usize) -> Result<(), ChunkFormatError> {
+        let padding = (align - self.offset % align) % align;
+        self.take(padding).map(|_| ())
+    }
+}
+
+/// The component arrays of a chunk read from a buffer, which can be inserted into a world.
+pub(crate) struct PodChunk<'a> {
+    len: usize,
+    written: usize,
+    components: Vec<(&'a PodType, &'a [u8])>,
+}
+
+impl<'a> PodChunk<'a> {
+    pub(crate) fn len(&self) -> usize { self.len }
+}
+
+/// Reads and validates every chunk in the buffer.
+pub(crate) fn read_chunks<'a>(
+    bytes: &'a [u8],
+    registry: &'a PodRegistry,
+) -> Result<Vec<PodChunk<'a>>, ChunkFormatError> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+        return Err(ChunkFormatError::BadMagic);
+    }
+
+    let version = reader.u32()?;
+    if version != VERSION {
+        return Err(ChunkFormatError::UnsupportedVersion(version));
+    }
+    if reader.take(4)?[0] != BYTE_ORDER {
+        return Err(ChunkFormatError::ByteOrderMismatch);
+    }
+
+    let type_count = reader.u32()?;
+    let chunk_count = reader.u32()?;
+
+    let mut types = Vec::new();
+    for _ in 0..type_count {
+        let found = LayoutFingerprint {
+            size: reader.u32()? as usize,
+            align: reader.u32()? as usize,
+            fingerprint: reader.u64()?,
+        };
+        let len = reader.u32()? as usize;
+        let name = String::from_utf8_lossy(reader.take(len)?);
+        let t = registry
+            .types
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| ChunkFormatError::UnknownComponent(name.clone().into_owned()))?;
+        if types
+            .iter()
+            .any(|other: &&PodType| other.type_id == t.type_id)
+        {
+            return Err(ChunkFormatError::DuplicateComponent(name.into_owned()));
+        }
+
+        let expected = t.meta.layout_fingerprint();
+        if found != expected {
+            return Err(ChunkFormatError::LayoutMismatch {
+                name: t.name.clone(),
+                expected,
+                found,
+            });
+        }
+
+        types.push(t);
+    }
+
+    let mut chunks = Vec::new();
+    for _ in 0..chunk_count {
+        let len = reader.u32()? as usize;
+        let count = reader.u32()?;
+        let mut indices = Vec::new();
+        for _ in 0..count {
+            let index = reader.u32()?;
+            if index >= type_count || indices.contains(&index) {
+                return Err(ChunkFormatError::InvalidTypeIndex(index));
+            }
+            indices.push(index);
+        }
+
+        let mut components = Vec::with_capacity(indices.len());
+        for index in indices {
+            let t = types[index as usize];
+            reader.align(t.meta.align())?;
+            let size = t
+                .meta
+                .size()
+                .checked_mul(len)
+                .ok_or(ChunkFormatError::Truncated)?;
+            components.push((t, reader.take(size)?));
+        }
+
+        if len > 0 {
+            chunks.push(PodChunk {
+                len,
+                written: 0,
+                components,
+            });
+        }
+    }
+
+    Ok(chunks)
+}
+
+impl<'a> ComponentLayout for PodChunk<'a> {
+    type Filter = Self;
+
+    fn get_filter(&mut self) -> &mut Self::Filter { self }
+
+    fn tailor_archetype(&self, archetype: &mut ArchetypeDescription) {
+        for (t, _) in &self.components {
+            archetype.register_component_named(t.type_id, t.meta, t.type_name);
+        }
+    }
+}
+
+impl<'a, 'b> Filter<ArchetypeFilterData<'b>> for PodChunk<'a> {
+    type Iter = SliceVecIter<'b, ComponentTypeId>;
+
+    fn collect(&self, source: ArchetypeFilterData<'b>) -> Self::Iter {
+        source.component_types.iter()
+    }
+
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        Some(
+            item.len() == self.components.len()
+                && self
+                    .components
+                    .iter()
+                    .all(|(t, _)| item.contains(&t.type_id)),
+        )
+    }
+}
+
+impl<'a> ComponentSource for PodChunk<'a> {
+    fn is_empty(&mut self) -> bool { self.written == self.len }
+
+    fn write(&mut self, allocator: &mut EntityAllocator, chunk: &mut ComponentStorage) -> usize {
+        let count = std::cmp::min(chunk.capacity() - chunk.len(), self.len - self.written);
+        let mut writer = chunk.writer();
+        let (entities, components) = writer.get();
+        for _ in 0..count {
+            entities.push(allocator.create_entity());
+        }
+
+        for (t, data) in &self.components {
+            let start = self.written * t.meta.size();
+            unsafe {
+                let mut writer = (&mut *components.get())
+                    .get_mut(t.type_id)
+                    .unwrap()
+                    .writer();
+                // the slice was validated to hold `len` components when it was read
+                let ptr = NonNull::new_unchecked(data.as_ptr().add(start) as *mut u8);
+                writer.push_raw(ptr, count);
+            }
+        }
+
+        self.written += count;
+        count
+    }
+}
+
+impl<'a> IntoComponentSource for PodChunk<'a> {
+    type Source = Self;
+
+    fn into(self) -> Self::Source { self }
+}
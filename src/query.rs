@@ -1,9 +1,14 @@
+use crate::borrow::BorrowConflict;
+use crate::borrow::Exclusive;
 use crate::borrow::RefIter;
 use crate::borrow::RefIterMut;
 use crate::borrow::RefMap;
 use crate::borrow::RefMapMut;
+use crate::borrow::Shared;
 use crate::borrow::TryRefIter;
 use crate::borrow::TryRefIterMut;
+use crate::chunk_state::ChangeTracker;
+use crate::command::CommandBuffer;
 use crate::entity::Entity;
 use crate::filter::And;
 use crate::filter::ArchetypeFilterData;
@@ -14,27 +19,50 @@ use crate::filter::EntityFilter;
 use crate::filter::EntityFilterTuple;
 use crate::filter::Filter;
 use crate::filter::FilterResult;
+use crate::filter::Not;
 use crate::filter::Passthrough;
 use crate::filter::TagFilter;
-#[cfg(feature = "par-iter")]
 use crate::iterator::{FissileEnumerate, FissileIterator};
+#[cfg(feature = "par-iter")]
+use crate::placement::PlacementPool;
 use crate::storage::ArchetypeData;
+use crate::storage::ArchetypeDescription;
+use crate::storage::ChunkId;
+use crate::storage::ChunkIndex;
 use crate::storage::Component;
 use crate::storage::ComponentStorage;
 use crate::storage::ComponentTypeId;
 use crate::storage::Storage;
 use crate::storage::Tag;
 use crate::storage::TagTypeId;
+use crate::summary::ComponentSummary;
 use crate::world::World;
 use derivative::Derivative;
+use parking_lot::Mutex;
 use std::any::TypeId;
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::iter::Copied;
 use std::iter::Enumerate;
+use std::iter::FusedIterator;
 use std::iter::Repeat;
+use std::iter::RepeatN;
 use std::iter::Take;
 use std::marker::PhantomData;
+#[cfg(feature = "par-iter")]
+use std::ops::Deref;
+#[cfg(feature = "par-iter")]
+use std::ops::DerefMut;
+use std::ops::{Bound, RangeBounds};
+use std::slice::ChunksExact;
+use std::slice::ChunksExactMut;
 use std::slice::Iter;
 use std::slice::IterMut;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
+#[cfg(feature = "rand")]
+use rand::Rng;
 #[cfg(feature = "par-iter")]
 use rayon::{
     iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer},
@@ -54,6 +82,24 @@ pub trait View<'a>: Sized + Send + Sync + 'static {
         chunk_index: usize,
     ) -> Self::Iter;
 
+    /// Pulls data out of a chunk on behalf of a query, which records in `changes` what it has
+    /// previously fetched. Only views which report changes (such as `Changed<T>`) need this.
+    #[doc(hidden)]
+    #[inline]
+    fn fetch_tracked(
+        archetype: &'a ArchetypeData,
+        chunk: &'a ComponentStorage,
+        chunk_index: usize,
+        _changes: &ChangeTracker,
+    ) -> Self::Iter {
+        Self::fetch(archetype, chunk, chunk_index)
+    }
+
+    /// Determines if `fetch_tracked` makes use of the change tracker passed to it.
+    #[doc(hidden)]
+    #[inline]
+    fn tracks_changes() -> bool { false }
+
     /// Validates that the view does not break any component borrowing rules.
     fn validate() -> bool;
 
@@ -84,7 +130,10 @@ pub trait ReadOnly {}
 
 #[doc(hidden)]
 pub trait ViewElement {
-    type Component;
+    type Component: 'static;
+
+    /// The element types of the view, which must be unique within a view.
+    fn element_types() -> Vec<TypeId> { vec![TypeId::of::<Self::Component>()] }
 }
 
 /// Converts a `View` into a `Query`.
@@ -102,6 +151,9 @@ impl<T: DefaultFilter + for<'a> View<'a>> IntoQuery for T {
         Query {
             view: PhantomData,
             filter: Self::filter(),
+            changes: Arc::new(ChangeTracker::new()),
+            archetypes: ArchetypeCache::default(),
+            pool: ExecutionPool::default(),
         }
     }
 }
@@ -338,7 +390,7 @@ impl<'a, T: Tag> View<'a> for Tagged<T> {
         chunk_index: usize,
     ) -> Self::Iter {
         let data = unsafe {
-            archetype
+            let tags = archetype
                 .tags()
                 .get(TagTypeId::of::<T>())
                 .unwrap_or_else(|| {
@@ -347,8 +399,9 @@ impl<'a, T: Tag> View<'a> for Tagged<T> {
                         std::any::type_name::<T>()
                     )
                 })
-                .data_slice::<T>()
-                .get_unchecked(chunk_index)
+                .data_slice::<T>();
+            debug_assert!(chunk_index < tags.len(), "chunkset index out of bounds");
+            tags.get_unchecked(chunk_index)
         };
         std::iter::repeat(data).take(chunk.len())
     }
@@ -373,574 +426,4687 @@ impl<T: Tag> ViewElement for Tagged<T> {
     type Component = Tagged<T>;
 }
 
-macro_rules! impl_view_tuple {
-    ( $( $ty: ident ),* ) => {
-        impl<$( $ty: ViewElement + DefaultFilter ),*> DefaultFilter for ($( $ty, )*) {
-            type Filter = EntityFilterTuple<
-                And<($( <$ty::Filter as EntityFilter>::ArchetypeFilter, )*)>,
-                And<($( <$ty::Filter as EntityFilter>::ChunksetFilter, )*)>,
-                And<($( <$ty::Filter as EntityFilter>::ChunkFilter, )*)>,
-            >;
+/// Requires that entities do not have a component type, without accessing any data.
+///
+/// `Exclude<T>` yields `()` for each entity, so it can be placed in a view tuple to restrict
+/// the query without a separate filter, e.g. `<(Write<Position>, Exclude<Frozen>)>::query()`.
+/// A view which both accesses and excludes `T` is invalid.
+#[derive(Derivative, Debug)]
+#[derivative(Default(bound = ""))]
+pub struct Exclude<T: Component>(PhantomData<T>);
 
-            fn filter() -> Self::Filter {
-                #![allow(non_snake_case)]
-                $( let $ty = $ty::filter().into_filters(); )*
-                EntityFilterTuple::new(
-                    And { filters: ($( $ty.0, )*) },
-                    And { filters: ($( $ty.1, )*) },
-                    And { filters: ($( $ty.2, )*) },
-                )
-            }
-        }
+impl<T: Component> ReadOnly for Exclude<T> {}
 
-        impl<$( $ty: ReadOnly ),* > ReadOnly for ($( $ty, )*) {}
+impl<T: Component> Copy for Exclude<T> {}
+impl<T: Component> Clone for Exclude<T> {
+    fn clone(&self) -> Self { *self }
+}
 
-        impl<'a, $( $ty: ViewElement + View<'a> ),* > View<'a> for ($( $ty, )*) {
-            type Iter = crate::zip::Zip<($( $ty::Iter, )*)>;
+impl<T: Component> DefaultFilter for Exclude<T> {
+    type Filter = EntityFilterTuple<Not<ComponentFilter<T>>, Passthrough, Passthrough>;
 
-            #[inline]
-            fn fetch(
-                archetype: &'a ArchetypeData,
-                chunk: &'a ComponentStorage,
-                chunk_index: usize,
-            ) -> Self::Iter {
-                crate::zip::multizip(($( $ty::fetch(archetype.clone(), chunk.clone(), chunk_index), )*))
-            }
+    fn filter() -> Self::Filter { !super::filter::filter_fns::component() }
+}
 
-            fn validate() -> bool {
-                let types = &[$( TypeId::of::<$ty::Component>() ),*];
-                for i in 0..types.len() {
-                    for j in (i + 1)..types.len() {
-                        if unsafe { types.get_unchecked(i) == types.get_unchecked(j) } {
-                            return false;
-                        }
-                    }
-                }
+impl<'a, T: Component> View<'a> for Exclude<T> {
+    type Iter = RepeatN<()>;
 
-                true
-            }
+    #[inline]
+    fn fetch(_: &'a ArchetypeData, chunk: &'a ComponentStorage, _: usize) -> Self::Iter {
+        std::iter::repeat_n((), chunk.len())
+    }
 
-            fn reads<Data: Component>() -> bool {
-                $( $ty::reads::<Data>() )||*
-            }
+    #[inline]
+    fn validate() -> bool { true }
 
-            fn writes<Data: Component>() -> bool {
-                $( $ty::writes::<Data>() )||*
-            }
+    #[inline]
+    fn reads<D: Component>() -> bool { false }
 
-            fn read_types() -> Vec<ComponentTypeId> {
-                let mut vec = vec![];
-                $( vec.extend($ty::read_types()); )*
-                vec
-            }
+    #[inline]
+    fn writes<D: Component>() -> bool { false }
 
-            fn write_types() -> Vec<ComponentTypeId> {
-                let mut vec = vec![];
-                $( vec.extend($ty::write_types()); )*
-                vec
-            }
-        }
-    };
+    #[inline]
+    fn read_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
+
+    #[inline]
+    fn write_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
 }
 
-impl_view_tuple!(A);
-impl_view_tuple!(A, B);
-impl_view_tuple!(A, B, C);
-impl_view_tuple!(A, B, C, D);
-impl_view_tuple!(A, B, C, D, E);
-impl_view_tuple!(A, B, C, D, E, F);
+impl<T: Component> ViewElement for Exclude<T> {
+    // shares the element type of `Read<T>` and `Write<T>`, so that a view tuple which also
+    // accesses `T` fails validation
+    type Component = T;
+}
 
-/// A type-safe view of a chunk of entities all of the same data layout.
-pub struct Chunk<'a, V: for<'b> View<'b>> {
-    archetype: &'a ArchetypeData,
-    components: &'a ComponentStorage,
-    index: usize,
-    view: PhantomData<V>,
+/// Yields the ID of each entity, so that it can be placed anywhere within a view tuple, e.g.
+/// `<(Entities, Read<Position>, Write<Health>)>::query()`.
+///
+/// `Entities` does not restrict which chunks are matched and does not access any component
+/// data, so it never conflicts with other access.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Entities;
+
+impl ReadOnly for Entities {}
+
+impl DefaultFilter for Entities {
+    type Filter = EntityFilterTuple<Passthrough, Passthrough, Passthrough>;
+
+    fn filter() -> Self::Filter { super::filter::filter_fns::passthrough() }
 }
 
-impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
-    pub fn new(archetype: &'a ArchetypeData, set: usize, index: usize) -> Self {
-        Self {
-            components: unsafe {
-                archetype
-                    .chunksets()
-                    .get_unchecked(set)
-                    .get_unchecked(index)
-            },
-            archetype,
-            index: set,
-            view: PhantomData,
-        }
+impl<'a> View<'a> for Entities {
+    type Iter = Copied<Iter<'a, Entity>>;
+
+    #[inline]
+    fn fetch(_: &'a ArchetypeData, chunk: &'a ComponentStorage, _: usize) -> Self::Iter {
+        chunk.entities().iter().copied()
     }
 
-    /// Get a slice of all entities contained within the chunk.
     #[inline]
-    pub fn entities(&self) -> &'a [Entity] { self.components.entities() }
+    fn validate() -> bool { true }
 
-    /// Get an iterator of all data contained within the chunk.
     #[inline]
-    pub fn iter(&mut self) -> <V as View<'a>>::Iter {
-        V::fetch(self.archetype, self.components, self.index)
-    }
+    fn reads<D: Component>() -> bool { false }
 
-    /// Get an iterator of all data and entity IDs contained within the chunk.
     #[inline]
-    pub fn iter_entities(&mut self) -> ZipEntities<'a, V> {
-        ZipEntities {
-            entities: self.entities(),
-            data: V::fetch(self.archetype, self.components, self.index),
-            index: 0,
-            view: PhantomData,
-        }
-    }
+    fn writes<D: Component>() -> bool { false }
 
-    /// Get a tag value.
-    pub fn tag<T: Tag>(&self) -> Option<&T> {
-        self.archetype
-            .tags()
-            .get(TagTypeId::of::<T>())
-            .map(|tags| unsafe { tags.data_slice::<T>() })
-            .map(|slice| unsafe { slice.get_unchecked(self.index) })
-    }
+    #[inline]
+    fn read_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
 
-    /// Get a slice of component data.
-    ///
-    /// # Panics
-    ///
-    /// This method performs runtime borrow checking. It will panic if
-    /// any other code is concurrently writing to the data slice.
-    pub fn components<T: Component>(&self) -> Option<RefMap<'a, &[T]>> {
-        if !V::reads::<T>() {
-            panic!("data type not readable via this query");
-        }
-        self.components
-            .components(ComponentTypeId::of::<T>())
-            .map(|c| unsafe { c.data_slice::<T>() })
-    }
+    #[inline]
+    fn write_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
+}
 
-    /// Get a mutable slice of component data.
-    ///
-    /// # Panics
-    ///
-    /// This method performs runtime borrow checking. It will panic if
-    /// any other code is concurrently accessing the data slice.
-    pub fn components_mut<T: Component>(&self) -> Option<RefMapMut<'a, &mut [T]>> {
-        if !V::writes::<T>() {
-            panic!("data type not writable via this query");
-        }
-        self.components
-            .components(ComponentTypeId::of::<T>())
-            .map(|c| unsafe { c.data_slice_mut::<T>() })
-    }
+impl ViewElement for Entities {
+    type Component = Entities;
 }
 
-/// An iterator which yields view data tuples and entity IDs from a `Chunk`.
-pub struct ZipEntities<'data, V: View<'data>> {
-    entities: &'data [Entity],
-    data: <V as View<'data>>::Iter,
-    index: usize,
-    view: PhantomData<V>,
+// The unit view yields nothing for each entity, and so can be used to find the entities which
+// match a filter, e.g. `<()>::query().filter(tag::<Enemy>()).iter_entities(&mut world)`. Use
+// `Entities` instead to yield just the entity IDs.
+impl ReadOnly for () {}
+
+impl DefaultFilter for () {
+    type Filter = EntityFilterTuple<Passthrough, Passthrough, Passthrough>;
+
+    fn filter() -> Self::Filter { super::filter::filter_fns::passthrough() }
 }
 
-impl<'data, V: View<'data>> Iterator for ZipEntities<'data, V> {
-    type Item = (Entity, <V::Iter as Iterator>::Item);
+impl<'a> View<'a> for () {
+    type Iter = RepeatN<()>;
 
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(data) = self.data.next() {
-            let i = self.index;
-            self.index += 1;
-            unsafe { Some((*self.entities.get_unchecked(i), data)) }
-        } else {
-            None
-        }
+    fn fetch(_: &'a ArchetypeData, chunk: &'a ComponentStorage, _: usize) -> Self::Iter {
+        std::iter::repeat_n((), chunk.len())
     }
 
     #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.entities.len() - self.index;
-        (len, Some(len))
-    }
+    fn validate() -> bool { true }
+
+    #[inline]
+    fn reads<D: Component>() -> bool { false }
+
+    #[inline]
+    fn writes<D: Component>() -> bool { false }
+
+    #[inline]
+    fn read_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
+
+    #[inline]
+    fn write_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
 }
 
-/// An iterator over all chunks that match a given query.
-pub struct ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
-where
-    V: for<'a> View<'a>,
-    FArch: Filter<ArchetypeFilterData<'data>>,
-    FChunkset: Filter<ChunksetFilterData<'data>>,
-    FChunk: Filter<ChunkFilterData<'data>>,
-{
-    _view: PhantomData<V>,
-    storage: &'data Storage,
-    arch_filter: &'filter FArch,
-    chunkset_filter: &'filter FChunkset,
-    chunk_filter: &'filter FChunk,
-    archetypes: Enumerate<FArch::Iter>,
-    set_frontier: Option<(&'data ArchetypeData, Take<Enumerate<FChunkset::Iter>>)>,
-    chunk_frontier: Option<(&'data ArchetypeData, usize, Take<Enumerate<FChunk::Iter>>)>,
+/// Yields whether each entity has a component type, without accessing any data.
+///
+/// `Has<T>` matches chunks both with and without `T`, and does not borrow `T`, so it may be
+/// combined with `Read<T>` or `Write<T>` in the same view, e.g.
+/// `<(Read<Transform>, Has<Selected>)>::query()` yields `(&Transform, bool)`.
+#[derive(Derivative, Debug)]
+#[derivative(Default(bound = ""))]
+pub struct Has<T: Component>(PhantomData<T>);
+
+impl<T: Component> ReadOnly for Has<T> {}
+
+impl<T: Component> Copy for Has<T> {}
+impl<T: Component> Clone for Has<T> {
+    fn clone(&self) -> Self { *self }
 }
 
-impl<'data, 'filter, V, FArch, FChunkset, FChunk>
-    ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
-where
-    V: for<'a> View<'a>,
-    FArch: Filter<ArchetypeFilterData<'data>>,
-    FChunkset: Filter<ChunksetFilterData<'data>>,
-    FChunk: Filter<ChunkFilterData<'data>>,
-{
-    fn next_set(&mut self) -> Option<(&'data ArchetypeData, usize)> {
-        loop {
-            // if we are looping through an archetype, find the next set
-            if let Some((ref arch, ref mut chunks)) = self.set_frontier {
-                for (set_index, filter_data) in chunks {
-                    if self.chunkset_filter.is_match(&filter_data).is_pass() {
-                        return Some((arch, set_index));
-                    }
-                }
-            }
+impl<T: Component> DefaultFilter for Has<T> {
+    type Filter = EntityFilterTuple<Passthrough, Passthrough, Passthrough>;
 
-            // we have completed the current set, find the next one
-            loop {
-                match self.archetypes.next() {
-                    Some((arch_index, arch_data)) => {
-                        if self.arch_filter.is_match(&arch_data).is_pass() {
-                            // we have found another set
-                            self.set_frontier = {
-                                let chunks =
-                                    unsafe { self.storage.archetypes().get_unchecked(arch_index) };
-                                let data = ChunksetFilterData {
-                                    archetype_data: chunks,
-                                };
+    fn filter() -> Self::Filter { super::filter::filter_fns::passthrough() }
+}
 
-                                Some((
-                                    chunks,
-                                    self.chunkset_filter
-                                        .collect(data)
-                                        .enumerate()
-                                        .take(chunks.len()),
-                                ))
-                            };
-                            break;
-                        }
-                    }
-                    // there are no more sets
-                    None => return None,
-                }
-            }
-        }
+impl<'a, T: Component> View<'a> for Has<T> {
+    type Iter = RepeatN<bool>;
+
+    #[inline]
+    fn fetch(_: &'a ArchetypeData, chunk: &'a ComponentStorage, _: usize) -> Self::Iter {
+        let has = chunk.components(ComponentTypeId::of::<T>()).is_some();
+        std::iter::repeat_n(has, chunk.len())
     }
-}
 
-impl<'data, 'filter, V, FArch, FChunkset, FChunk> Iterator
-    for ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
-where
-    V: for<'a> View<'a>,
-    FArch: Filter<ArchetypeFilterData<'data>>,
-    FChunkset: Filter<ChunksetFilterData<'data>>,
-    FChunk: Filter<ChunkFilterData<'data>>,
-{
-    type Item = Chunk<'data, V>;
+    #[inline]
+    fn validate() -> bool { true }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            // if we are looping through a set, then yield the next chunk
-            if let Some((ref arch, set_index, ref mut set)) = self.chunk_frontier {
-                for (chunk_index, filter_data) in set {
-                    if self.chunk_filter.is_match(&filter_data).is_pass() {
-                        return Some(Chunk::new(arch, set_index, chunk_index));
-                    }
-                }
-            }
+    #[inline]
+    fn reads<D: Component>() -> bool { false }
 
-            // we have completed the set, find the next
-            if let Some((ref arch, set_index)) = self.next_set() {
-                let chunks = unsafe { arch.chunksets().get_unchecked(set_index) }.occupied();
-                self.chunk_frontier = Some((
-                    arch,
-                    set_index,
-                    self.chunk_filter
-                        .collect(ChunkFilterData { chunks })
-                        .enumerate()
-                        .take(chunks.len()),
-                ))
-            } else {
-                return None;
-            }
-        }
-    }
+    #[inline]
+    fn writes<D: Component>() -> bool { false }
+
+    #[inline]
+    fn read_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
+
+    #[inline]
+    fn write_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
 }
 
-// An iterator which iterates through all entity data in all chunks.
-pub struct ChunkDataIter<'data, V, I>
-where
-    V: for<'a> View<'a>,
-    I: Iterator<Item = Chunk<'data, V>>,
-{
-    iter: I,
-    frontier: Option<<V as View<'data>>::Iter>,
-    _view: PhantomData<V>,
+impl<T: Component> ViewElement for Has<T> {
+    // does not alias `Read<T>` or `Write<T>`
+    type Component = Has<T>;
 }
 
-impl<'data, V, I> Iterator for ChunkDataIter<'data, V, I>
-where
-    V: for<'a> View<'a>,
-    I: Iterator<Item = Chunk<'data, V>>,
-{
-    type Item = <<V as View<'data>>::Iter as Iterator>::Item;
+/// Yields, for each entity, whether its `T` component may have been modified since the query
+/// last visited the entity's chunk.
+///
+/// Like the `changed` filter, changes are tracked per chunk, and each query remembers which
+/// component versions it has seen. Unlike the filter, unchanged chunks are still visited, so
+/// both changed and unchanged entities can be handled in a single pass. Chunks are always
+/// reported as changed the first time a query visits them.
+///
+/// Only entities with a `T` component are matched. The flag is computed when the chunk is
+/// fetched, so writes made through a `Write<T>` in the same query are reported on the next
+/// iteration.
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// world.insert((), vec![(Position(0.),)]);
+///
+/// let query = <(Read<Position>, Changed<Position>)>::query();
+/// assert!(query.iter(&mut world).all(|(_, changed)| changed));
+/// assert!(query.iter(&mut world).all(|(_, changed)| !changed));
+/// ```
+#[derive(Debug)]
+pub struct Changed<T: Component>(PhantomData<T>);
 
-    #[inline(always)]
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(ref mut inner) = self.frontier {
-                if let elt @ Some(_) = inner.next() {
-                    return elt;
-                }
-            }
-            match self.iter.next() {
-                Some(mut inner) => self.frontier = Some(inner.iter()),
-                None => return None,
-            }
-        }
-    }
+impl<T: Component> ReadOnly for Changed<T> {}
+
+impl<T: Component> Copy for Changed<T> {}
+impl<T: Component> Clone for Changed<T> {
+    fn clone(&self) -> Self { *self }
 }
 
-/// An iterator which iterates through all entity data in all chunks, zipped with entity ID.
-pub struct ChunkEntityIter<'data, V, I>
-where
-    V: for<'a> View<'a>,
-    I: Iterator<Item = Chunk<'data, V>>,
-{
-    iter: I,
-    frontier: Option<ZipEntities<'data, V>>,
-    _view: PhantomData<V>,
+impl<T: Component> DefaultFilter for Changed<T> {
+    type Filter = EntityFilterTuple<ComponentFilter<T>, Passthrough, Passthrough>;
+
+    fn filter() -> Self::Filter { super::filter::filter_fns::component() }
 }
 
-impl<'data, 'query, V, I> Iterator for ChunkEntityIter<'data, V, I>
-where
-    V: for<'a> View<'a>,
-    I: Iterator<Item = Chunk<'data, V>>,
-{
-    type Item = (Entity, <<V as View<'data>>::Iter as Iterator>::Item);
+impl<'a, T: Component> View<'a> for Changed<T> {
+    type Iter = RepeatN<bool>;
 
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(ref mut inner) = self.frontier {
-                if let elt @ Some(_) = inner.next() {
-                    return elt;
-                }
-            }
-            match self.iter.next() {
-                Some(mut inner) => self.frontier = Some(inner.iter_entities()),
-                None => return None,
+    fn fetch(_: &'a ArchetypeData, chunk: &'a ComponentStorage, _: usize) -> Self::Iter {
+        // without a query to remember previous versions, everything is new
+        let has = chunk.components(ComponentTypeId::of::<T>()).is_some();
+        std::iter::repeat_n(has, chunk.len())
+    }
+
+    #[inline]
+    fn fetch_tracked(
+        _: &'a ArchetypeData,
+        chunk: &'a ComponentStorage,
+        _: usize,
+        changes: &ChangeTracker,
+    ) -> Self::Iter {
+        let type_id = ComponentTypeId::of::<T>();
+        let changed = match chunk.components(type_id) {
+            Some(components) => {
+                let version = components.version();
+                let last_read = changes.update(type_id, chunk.dense_index(), version);
+                version.is_newer_than(last_read)
             }
-        }
+            None => false,
+        };
+        std::iter::repeat_n(changed, chunk.len())
     }
+
+    #[inline]
+    fn tracks_changes() -> bool { true }
+
+    #[inline]
+    fn validate() -> bool { true }
+
+    #[inline]
+    fn reads<D: Component>() -> bool { false }
+
+    #[inline]
+    fn writes<D: Component>() -> bool { false }
+
+    #[inline]
+    fn read_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
+
+    #[inline]
+    fn write_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
 }
 
-/// Queries for entities within a `World`.
+impl<T: Component> ViewElement for Changed<T> {
+    // does not alias `Read<T>` or `Write<T>`
+    type Component = Changed<T>;
+}
+
+/// Yields, for each entity, the version of the `T` components in the entity's chunk.
 ///
-/// # Examples
+/// This is the version consumed by the `changed` filter; it advances whenever the chunk's `T`
+/// components are borrowed mutably, added or moved. Versions should be compared with
+/// `storage::Version::is_newer_than`. With the `serde-1` feature they serialize as a `u64`.
 ///
-/// Queries can be constructed from any `View` type, including tuples of `View`s.
+/// `ChunkVersion<T>` is reported as a read of `T`, so it cannot be combined with `Write<T>` in
+/// the same view.
 ///
 /// ```rust
 /// # use legion::prelude::*;
+/// # use legion::query::ChunkVersion;
 /// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Position;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Velocity;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Model;
-/// // A query which matches any entity with a `Position` component
-/// let mut query = Read::<Position>::query();
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// world.insert((), vec![(Position(0.),)]);
 ///
-/// // A query which matches any entity with both a `Position` and a `Velocity` component
-/// let mut query = <(Read<Position>, Read<Velocity>)>::query();
+/// let query = ChunkVersion::<Position>::query();
+/// let before = query.iter(&mut world).next().unwrap();
+/// for mut pos in Write::<Position>::query().iter(&mut world) {
+///     pos.0 += 1.;
+/// }
+/// let after = query.iter(&mut world).next().unwrap();
+/// assert!(after.is_newer_than(before));
 /// ```
+#[derive(Derivative, Debug)]
+#[derivative(Default(bound = ""))]
+pub struct ChunkVersion<T: Component>(PhantomData<T>);
+
+impl<T: Component> ReadOnly for ChunkVersion<T> {}
+
+impl<T: Component> Copy for ChunkVersion<T> {}
+impl<T: Component> Clone for ChunkVersion<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T: Component> DefaultFilter for ChunkVersion<T> {
+    type Filter = EntityFilterTuple<ComponentFilter<T>, Passthrough, Passthrough>;
+
+    fn filter() -> Self::Filter { super::filter::filter_fns::component() }
+}
+
+impl<'a, T: Component> View<'a> for ChunkVersion<T> {
+    type Iter = RepeatN<crate::storage::Version>;
+
+    #[inline]
+    fn fetch(_: &'a ArchetypeData, chunk: &'a ComponentStorage, _: usize) -> Self::Iter {
+        let version = chunk
+            .components(ComponentTypeId::of::<T>())
+            .map(|components| components.version())
+            .unwrap_or(crate::storage::Version::NONE);
+        std::iter::repeat_n(version, chunk.len())
+    }
+
+    #[inline]
+    fn validate() -> bool { true }
+
+    #[inline]
+    fn reads<D: Component>() -> bool { TypeId::of::<T>() == TypeId::of::<D>() }
+
+    #[inline]
+    fn writes<D: Component>() -> bool { false }
+
+    #[inline]
+    fn read_types() -> Vec<ComponentTypeId> { vec![ComponentTypeId::of::<T>()] }
+
+    #[inline]
+    fn write_types() -> Vec<ComponentTypeId> { Vec::with_capacity(0) }
+}
+
+impl<T: Component> ViewElement for ChunkVersion<T> {
+    // does not alias `Read<T>`
+    type Component = ChunkVersion<T>;
+}
+
+/// A value which can be computed for each entity from the data in another view, without being
+/// stored in the world. See `Computed`.
+pub trait Compute: Send + Sync + Sized + 'static {
+    /// The view which the value is computed from.
+    type Source: for<'a> View<'a> + DefaultFilter;
+
+    /// Computes the value for a single entity.
+    fn compute<'a>(source: <<Self::Source as View<'a>>::Iter as Iterator>::Item) -> Self;
+}
+
+/// Computes a value of type `T` for each entity from other components, as if `T` were a
+/// stored component.
 ///
-/// The view determines what data is accessed, and whether it is accessed mutably or not.
+/// The computed value is yielded by value and computed lazily as the view is iterated. The
+/// view's default filter and borrows are those of `T::Source`.
 ///
 /// ```rust
 /// # use legion::prelude::*;
+/// # use legion::query::{Compute, Computed};
 /// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Position;
+/// # struct Position(f32);
 /// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Velocity;
+/// # struct Scale(f32);
 /// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Model;
-/// // A query which writes `Position`, reads `Velocity` and reads `Model`
-/// // Tags are read-only, and is distinguished from entity data reads with `Tagged<T>`.
-/// let mut query = <(Write<Position>, Read<Velocity>, Tagged<Model>)>::query();
-/// ```
+/// # struct Velocity(f32);
+/// #[derive(Debug, PartialEq)]
+/// struct Bounds(f32, f32);
 ///
-/// By default, a query will filter its results to include only entities with the data
-/// types accessed by the view. However, additional filters can be specified if needed:
+/// impl Compute for Bounds {
+///     type Source = (Read<Position>, Read<Scale>);
 ///
-/// ```rust
-/// # use legion::prelude::*;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Position;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Velocity;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Model;
-/// #[derive(Copy, Clone, Debug, PartialEq)]
-/// struct Static;
+///     fn compute<'a>((pos, scale): (Ref<'a, Position>, Ref<'a, Scale>)) -> Self {
+///         Bounds(pos.0 - scale.0, pos.0 + scale.0)
+///     }
+/// }
+/// # use legion::borrow::Ref;
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// world.insert((), vec![(Position(1.0), Scale(0.5), Velocity(0.0))]);
 ///
-/// // A query which also requires that entities have the `Static` tag
-/// let mut query = <(Read<Position>, Tagged<Model>)>::query().filter(tag::<Static>());
+/// let query = <(Read<Velocity>, Computed<Bounds>)>::query();
+/// for (vel, bounds) in query.iter(&mut world) {
+///     assert_eq!(Bounds(0.5, 1.5), bounds);
+/// }
 /// ```
-///
-/// Filters can be combined with bitwise operators:
-///
-/// ```rust
-/// # use legion::prelude::*;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Position;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Velocity;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Model;
-/// #[derive(Copy, Clone, Debug, PartialEq)]
-/// struct Static;
-///
-/// // This query matches entities with positions and a model
-/// // But it also requires that the entity is not static, or has moved (even if static)
-/// let mut query = <(Read<Position>, Tagged<Model>)>::query()
-///     .filter(!tag::<Static>() | changed::<Position>());
-/// ```
-///
-/// Filters can be iterated through to pull data out of a `World`:
-///
-/// ```rust
-/// # use legion::prelude::*;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Position;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Velocity;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Model;
-/// # let universe = Universe::new();
-/// # let mut world = universe.create_world();
-/// // A query which writes `Position`, reads `Velocity` and reads `Model`
-/// // Tags are read-only, and is distinguished from entity data reads with `Tagged<T>`.
-/// let mut query = <(Write<Position>, Read<Velocity>, Tagged<Model>)>::query();
-///
-/// for (mut pos, vel, model) in query.iter(&mut world) {
-///     // `.iter` yields tuples of references to a single entity's data:
-///     // pos: &mut Position
-///     // vel: &Velocity
-///     // model: &Model
-/// }
-/// ```
-///
-/// The lower level `iter_chunks` function allows access to each underlying chunk of entity data.
-/// This allows you to run code for each tag value, or to retrieve a contiguous data slice.
-///
-/// ```rust
-/// # use legion::prelude::*;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Position;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Velocity;
-/// # #[derive(Copy, Clone, Debug, PartialEq)]
-/// # struct Model;
-/// # let universe = Universe::new();
-/// # let mut world = universe.create_world();
-/// let mut query = <(Write<Position>, Read<Velocity>, Tagged<Model>)>::query();
-///
-/// for chunk in query.iter_chunks(&mut world) {
-///     let model = chunk.tag::<Model>();
-///     let positions = chunk.components_mut::<Position>();
-///     let velocities = chunk.components::<Velocity>();
-/// }
-/// ```
-///
-/// The `ChunkView` yielded from `iter_chunks` allows access to all shared data in the chunk (queried for or not),
-/// but entity data slices can only be accessed if they were requested in the query's view. Attempting to access
-/// other data types, or attempting to write to components that were only requested via a `Read` will panic.
-#[derive(Derivative)]
-#[derivative(Clone(bound = "F: Clone"))]
-pub struct Query<V: for<'a> View<'a>, F: EntityFilter> {
-    view: PhantomData<V>,
-    pub(crate) filter: F,
+#[derive(Debug)]
+pub struct Computed<T: Compute>(PhantomData<T>);
+
+impl<T: Compute> ReadOnly for Computed<T> where T::Source: ReadOnly {}
+
+impl<T: Compute> Copy for Computed<T> {}
+impl<T: Compute> Clone for Computed<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T: Compute> DefaultFilter for Computed<T> {
+    type Filter = <T::Source as DefaultFilter>::Filter;
+
+    fn filter() -> Self::Filter { T::Source::filter() }
+}
+
+impl<'a, T: Compute> View<'a> for Computed<T> {
+    #[allow(clippy::type_complexity)]
+    type Iter = std::iter::Map<
+        <T::Source as View<'a>>::Iter,
+        fn(<<T::Source as View<'a>>::Iter as Iterator>::Item) -> T,
+    >;
+
+    #[inline]
+    fn fetch(
+        archetype: &'a ArchetypeData,
+        chunk: &'a ComponentStorage,
+        chunk_index: usize,
+    ) -> Self::Iter {
+        T::Source::fetch(archetype, chunk, chunk_index).map(T::compute)
+    }
+
+    #[inline]
+    fn fetch_tracked(
+        archetype: &'a ArchetypeData,
+        chunk: &'a ComponentStorage,
+        chunk_index: usize,
+        changes: &ChangeTracker,
+    ) -> Self::Iter {
+        T::Source::fetch_tracked(archetype, chunk, chunk_index, changes).map(T::compute)
+    }
+
+    #[inline]
+    fn tracks_changes() -> bool { T::Source::tracks_changes() }
+
+    #[inline]
+    fn validate() -> bool { T::Source::validate() }
+
+    #[inline]
+    fn reads<D: Component>() -> bool { T::Source::reads::<D>() }
+
+    #[inline]
+    fn writes<D: Component>() -> bool { T::Source::writes::<D>() }
+
+    #[inline]
+    fn read_types() -> Vec<ComponentTypeId> { T::Source::read_types() }
+
+    #[inline]
+    fn write_types() -> Vec<ComponentTypeId> { T::Source::write_types() }
+}
+
+impl<T: Compute> ViewElement for Computed<T> {
+    type Component = Computed<T>;
+}
+
+macro_rules! impl_view_tuple {
+    ( $( $ty: ident ),* ) => {
+        impl<$( $ty: ViewElement + DefaultFilter ),*> DefaultFilter for ($( $ty, )*) {
+            type Filter = EntityFilterTuple<
+                And<($( <$ty::Filter as EntityFilter>::ArchetypeFilter, )*)>,
+                And<($( <$ty::Filter as EntityFilter>::ChunksetFilter, )*)>,
+                And<($( <$ty::Filter as EntityFilter>::ChunkFilter, )*)>,
+            >;
+
+            fn filter() -> Self::Filter {
+                #![allow(non_snake_case)]
+                $( let $ty = $ty::filter().into_filters(); )*
+                EntityFilterTuple::new(
+                    And { filters: ($( $ty.0, )*) },
+                    And { filters: ($( $ty.1, )*) },
+                    And { filters: ($( $ty.2, )*) },
+                )
+            }
+        }
+
+        impl<$( $ty: ReadOnly ),* > ReadOnly for ($( $ty, )*) {}
+
+        // nested tuples contribute each of their elements, so that duplicates are detected
+        // across nesting levels
+        impl<$( $ty: ViewElement ),* > ViewElement for ($( $ty, )*) {
+            type Component = ($( $ty::Component, )*);
+
+            fn element_types() -> Vec<TypeId> {
+                let mut vec = vec![];
+                $( vec.extend($ty::element_types()); )*
+                vec
+            }
+        }
+
+        impl<'a, $( $ty: ViewElement + View<'a> ),* > View<'a> for ($( $ty, )*) {
+            type Iter = crate::zip::Zip<($( $ty::Iter, )*)>;
+
+            #[inline]
+            fn fetch(
+                archetype: &'a ArchetypeData,
+                chunk: &'a ComponentStorage,
+                chunk_index: usize,
+            ) -> Self::Iter {
+                crate::zip::multizip(($( $ty::fetch(archetype.clone(), chunk.clone(), chunk_index), )*))
+            }
+
+            #[inline]
+            fn fetch_tracked(
+                archetype: &'a ArchetypeData,
+                chunk: &'a ComponentStorage,
+                chunk_index: usize,
+                changes: &ChangeTracker,
+            ) -> Self::Iter {
+                crate::zip::multizip((
+                    $( $ty::fetch_tracked(archetype.clone(), chunk.clone(), chunk_index, changes), )*
+                ))
+            }
+
+            #[inline]
+            fn tracks_changes() -> bool { false $( || $ty::tracks_changes() )* }
+
+            fn validate() -> bool {
+                let types = <Self as ViewElement>::element_types();
+                for i in 0..types.len() {
+                    for j in (i + 1)..types.len() {
+                        if unsafe { types.get_unchecked(i) == types.get_unchecked(j) } {
+                            return false;
+                        }
+                    }
+                }
+
+                // elements which access several components (such as `Computed`) may overlap
+                // with another element without sharing its element type
+                let reads = [$( $ty::read_types() ),*];
+                let writes = [$( $ty::write_types() ),*];
+                for i in 0..writes.len() {
+                    for j in 0..reads.len() {
+                        if i != j && writes[i].iter().any(|t| reads[j].contains(t)) {
+                            return false;
+                        }
+                    }
+                }
+
+                $( $ty::validate() )&&*
+            }
+
+            fn reads<Data: Component>() -> bool {
+                $( $ty::reads::<Data>() )||*
+            }
+
+            fn writes<Data: Component>() -> bool {
+                $( $ty::writes::<Data>() )||*
+            }
+
+            fn read_types() -> Vec<ComponentTypeId> {
+                let mut vec = vec![];
+                $( vec.extend($ty::read_types()); )*
+                vec
+            }
+
+            fn write_types() -> Vec<ComponentTypeId> {
+                let mut vec = vec![];
+                $( vec.extend($ty::write_types()); )*
+                vec
+            }
+        }
+    };
+}
+
+impl_view_tuple!(A);
+impl_view_tuple!(A, B);
+impl_view_tuple!(A, B, C);
+impl_view_tuple!(A, B, C, D);
+impl_view_tuple!(A, B, C, D, E);
+impl_view_tuple!(A, B, C, D, E, F);
+impl_view_tuple!(A, B, C, D, E, F, G);
+impl_view_tuple!(A, B, C, D, E, F, G, H);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+/// A view which can split the data in a chunk into batches of exactly `N` consecutive entities.
+pub trait BatchView<'a, const N: usize> {
+    /// The runtime borrows held while the batches are accessed.
+    type Borrow;
+
+    /// An iterator over `N`-sized batches of chunk data.
+    type Batches: Iterator + 'a;
+
+    /// An iterator over the data left over after the last full batch.
+    type Remainder: Iterator + 'a;
+
+    /// Splits the data in a chunk into full batches and a remainder.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `N` is 0.
+    fn fetch_batches(chunk: &'a ComponentStorage)
+        -> (Self::Borrow, Self::Batches, Self::Remainder);
+}
+
+#[inline]
+fn as_array<T, const N: usize>(slice: &[T]) -> &[T; N] { slice.try_into().unwrap() }
+
+#[inline]
+fn as_array_mut<T, const N: usize>(slice: &mut [T]) -> &mut [T; N] { slice.try_into().unwrap() }
+
+impl<'a, T: Component, const N: usize> BatchView<'a, N> for Read<T> {
+    type Borrow = Shared<'a>;
+    type Batches = std::iter::Map<ChunksExact<'a, T>, fn(&'a [T]) -> &'a [T; N]>;
+    type Remainder = Iter<'a, T>;
+
+    fn fetch_batches(
+        chunk: &'a ComponentStorage,
+    ) -> (Self::Borrow, Self::Batches, Self::Remainder) {
+        let (slice_borrow, slice) = unsafe {
+            chunk
+                .components(ComponentTypeId::of::<T>())
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Component of type {:?} not found in chunk when fetching Read view",
+                        std::any::type_name::<T>()
+                    )
+                })
+                .data_slice::<T>()
+                .deconstruct()
+        };
+        let batches = slice.chunks_exact(N);
+        let remainder = batches.remainder().iter();
+        (
+            slice_borrow,
+            batches.map(as_array as fn(&'a [T]) -> &'a [T; N]),
+            remainder,
+        )
+    }
+}
+
+impl<'a, T: Component, const N: usize> BatchView<'a, N> for Write<T> {
+    type Borrow = Exclusive<'a>;
+    type Batches = std::iter::Map<ChunksExactMut<'a, T>, fn(&'a mut [T]) -> &'a mut [T; N]>;
+    type Remainder = IterMut<'a, T>;
+
+    fn fetch_batches(
+        chunk: &'a ComponentStorage,
+    ) -> (Self::Borrow, Self::Batches, Self::Remainder) {
+        let (slice_borrow, slice) = unsafe {
+            chunk
+                .components(ComponentTypeId::of::<T>())
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Component of type {:?} not found in chunk when fetching Write view",
+                        std::any::type_name::<T>()
+                    )
+                })
+                .data_slice_mut::<T>()
+                .deconstruct()
+        };
+        let split = slice.len() - slice.len() % N;
+        let (batches, remainder) = slice.split_at_mut(split);
+        (
+            slice_borrow,
+            batches
+                .chunks_exact_mut(N)
+                .map(as_array_mut as fn(&'a mut [T]) -> &'a mut [T; N]),
+            remainder.iter_mut(),
+        )
+    }
+}
+
+macro_rules! impl_batch_view_tuple {
+    ( $( $ty: ident ),* ) => {
+        impl<'a, $( $ty: BatchView<'a, SIZE> ),*, const SIZE: usize> BatchView<'a, SIZE> for ($( $ty, )*) {
+            type Borrow = ($( $ty::Borrow, )*);
+            type Batches = crate::zip::Zip<($( $ty::Batches, )*)>;
+            type Remainder = crate::zip::Zip<($( $ty::Remainder, )*)>;
+
+            fn fetch_batches(
+                chunk: &'a ComponentStorage,
+            ) -> (Self::Borrow, Self::Batches, Self::Remainder) {
+                #![allow(non_snake_case)]
+                $( let $ty = $ty::fetch_batches(chunk); )*
+                (
+                    ($( $ty.0, )*),
+                    crate::zip::multizip(($( $ty.1, )*)),
+                    crate::zip::multizip(($( $ty.2, )*)),
+                )
+            }
+        }
+    };
+}
+
+impl_batch_view_tuple!(A);
+impl_batch_view_tuple!(A, B);
+impl_batch_view_tuple!(A, B, C);
+impl_batch_view_tuple!(A, B, C, D);
+impl_batch_view_tuple!(A, B, C, D, E);
+impl_batch_view_tuple!(A, B, C, D, E, F);
+impl_batch_view_tuple!(A, B, C, D, E, F, G);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_batch_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+/// A view whose data in a chunk can be split into disjoint ranges of entities, which can then be
+/// iterated by different tasks. See `Query::par_for_each_batched`.
+pub trait SplitView<'a> {
+    /// The runtime borrows held while the parts are accessed.
+    type Borrow;
+
+    /// The chunk data lent by the borrow, which can be split at an entity index.
+    type Parts: Send;
+
+    /// An iterator over the data in a range of entities.
+    type Iter: Iterator + 'a;
+
+    /// Borrows the data in a chunk.
+    fn fetch_parts(chunk: &'a ComponentStorage) -> (Self::Borrow, Self::Parts);
+
+    /// Splits parts into the entities before `mid` and the entities from `mid` onwards.
+    fn split_parts(parts: Self::Parts, mid: usize) -> (Self::Parts, Self::Parts);
+
+    /// Iterates through the data in parts.
+    fn iter_parts(parts: Self::Parts) -> Self::Iter;
 }
 
-impl<V, F> Query<V, F>
-where
-    V: for<'a> View<'a>,
-    F: EntityFilter,
-{
-    /// Adds an additional filter to the query.
-    pub fn filter<T: EntityFilter>(self, filter: T) -> Query<V, <F as std::ops::BitAnd<T>>::Output>
+impl<'a, T: Component> SplitView<'a> for Read<T> {
+    type Borrow = Shared<'a>;
+    type Parts = &'a [T];
+    type Iter = Iter<'a, T>;
+
+    fn fetch_parts(chunk: &'a ComponentStorage) -> (Self::Borrow, Self::Parts) {
+        unsafe {
+            chunk
+                .components(ComponentTypeId::of::<T>())
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Component of type {:?} not found in chunk when fetching Read view",
+                        std::any::type_name::<T>()
+                    )
+                })
+                .data_slice::<T>()
+                .deconstruct()
+        }
+    }
+
+    fn split_parts(parts: Self::Parts, mid: usize) -> (Self::Parts, Self::Parts) {
+        parts.split_at(mid)
+    }
+
+    fn iter_parts(parts: Self::Parts) -> Self::Iter { parts.iter() }
+}
+
+impl<'a, T: Component> SplitView<'a> for Write<T> {
+    type Borrow = Exclusive<'a>;
+    type Parts = &'a mut [T];
+    type Iter = IterMut<'a, T>;
+
+    fn fetch_parts(chunk: &'a ComponentStorage) -> (Self::Borrow, Self::Parts) {
+        unsafe {
+            chunk
+                .components(ComponentTypeId::of::<T>())
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Component of type {:?} not found in chunk when fetching Write view",
+                        std::any::type_name::<T>()
+                    )
+                })
+                .data_slice_mut::<T>()
+                .deconstruct()
+        }
+    }
+
+    fn split_parts(parts: Self::Parts, mid: usize) -> (Self::Parts, Self::Parts) {
+        parts.split_at_mut(mid)
+    }
+
+    fn iter_parts(parts: Self::Parts) -> Self::Iter { parts.iter_mut() }
+}
+
+macro_rules! impl_split_view_tuple {
+    ( $( $ty: ident ),* ) => {
+        impl<'a, $( $ty: SplitView<'a> ),*> SplitView<'a> for ($( $ty, )*) {
+            type Borrow = ($( $ty::Borrow, )*);
+            type Parts = ($( $ty::Parts, )*);
+            type Iter = crate::zip::Zip<($( $ty::Iter, )*)>;
+
+            fn fetch_parts(chunk: &'a ComponentStorage) -> (Self::Borrow, Self::Parts) {
+                #![allow(non_snake_case)]
+                $( let $ty = $ty::fetch_parts(chunk); )*
+                (($( $ty.0, )*), ($( $ty.1, )*))
+            }
+
+            fn split_parts(parts: Self::Parts, mid: usize) -> (Self::Parts, Self::Parts) {
+                #![allow(non_snake_case)]
+                let ($( $ty, )*) = parts;
+                $( let $ty = $ty::split_parts($ty, mid); )*
+                (($( $ty.0, )*), ($( $ty.1, )*))
+            }
+
+            fn iter_parts(parts: Self::Parts) -> Self::Iter {
+                #![allow(non_snake_case)]
+                let ($( $ty, )*) = parts;
+                crate::zip::multizip(($( $ty::iter_parts($ty), )*))
+            }
+        }
+    };
+}
+
+impl_split_view_tuple!(A);
+impl_split_view_tuple!(A, B);
+impl_split_view_tuple!(A, B, C);
+impl_split_view_tuple!(A, B, C, D);
+impl_split_view_tuple!(A, B, C, D, E);
+impl_split_view_tuple!(A, B, C, D, E, F);
+impl_split_view_tuple!(A, B, C, D, E, F, G);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_split_view_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+/// A view made up only of `Tagged` elements, which reads no entity data and so can be fetched
+/// once per chunk rather than once per entity. See `Query::iter_per_chunk`.
+pub trait TagView<'a>: View<'a> {
+    /// The tag values shared by all entities in a chunk.
+    type Tags: 'a;
+
+    /// Pulls the tag values of a chunkset out of an archetype.
+    fn fetch_tags(archetype: &'a ArchetypeData, set_index: usize) -> Self::Tags;
+}
+
+impl<'a, T: Tag> TagView<'a> for Tagged<T> {
+    type Tags = &'a T;
+
+    #[inline]
+    fn fetch_tags(archetype: &'a ArchetypeData, set_index: usize) -> Self::Tags {
+        let tags = archetype
+            .tags()
+            .get(TagTypeId::of::<T>())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Component of type {:?} not found in archetype when fetching Tagged view",
+                    std::any::type_name::<T>()
+                )
+            });
+        let tags = unsafe { tags.data_slice::<T>() };
+        debug_assert!(set_index < tags.len(), "chunkset index out of bounds");
+        unsafe { tags.get_unchecked(set_index) }
+    }
+}
+
+macro_rules! impl_tag_view_tuple {
+    ( $( $ty: ident ),* ) => {
+        impl<'a, $( $ty: ViewElement + TagView<'a> ),*> TagView<'a> for ($( $ty, )*) {
+            type Tags = ($( $ty::Tags, )*);
+
+            #[inline]
+            fn fetch_tags(archetype: &'a ArchetypeData, set_index: usize) -> Self::Tags {
+                ($( $ty::fetch_tags(archetype, set_index), )*)
+            }
+        }
+    };
+}
+
+impl_tag_view_tuple!(A);
+impl_tag_view_tuple!(A, B);
+impl_tag_view_tuple!(A, B, C);
+impl_tag_view_tuple!(A, B, C, D);
+impl_tag_view_tuple!(A, B, C, D, E);
+impl_tag_view_tuple!(A, B, C, D, E, F);
+impl_tag_view_tuple!(A, B, C, D, E, F, G);
+impl_tag_view_tuple!(A, B, C, D, E, F, G, H);
+
+/// A type-safe view of a chunk of entities all of the same data layout.
+pub struct Chunk<'a, V: for<'b> View<'b>> {
+    archetype: &'a ArchetypeData,
+    components: &'a ComponentStorage,
+    index: usize,
+    changes: Option<Arc<ChangeTracker>>,
+    view: PhantomData<V>,
+}
+
+impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
+    pub fn new(archetype: &'a ArchetypeData, set: usize, index: usize) -> Self {
+        debug_assert!(
+            set < archetype.chunksets().len(),
+            "chunkset index out of bounds"
+        );
+        debug_assert!(
+            index < archetype.chunksets()[set].len(),
+            "chunk index out of bounds"
+        );
+        Self {
+            components: unsafe {
+                archetype
+                    .chunksets()
+                    .get_unchecked(set)
+                    .get_unchecked(index)
+            },
+            archetype,
+            index: set,
+            changes: None,
+            view: PhantomData,
+        }
+    }
+
+    pub(crate) fn tracked(
+        archetype: &'a ArchetypeData,
+        set: usize,
+        index: usize,
+        changes: &Arc<ChangeTracker>,
+    ) -> Self {
+        // only views such as `Changed<T>` consult the tracker, so other views avoid the
+        // reference count traffic of holding onto it
+        Self {
+            changes: if V::tracks_changes() {
+                Some(changes.clone())
+            } else {
+                None
+            },
+            ..Self::new(archetype, set, index)
+        }
+    }
+
+    #[inline]
+    fn fetch(&self) -> <V as View<'a>>::Iter {
+        match &self.changes {
+            Some(changes) => V::fetch_tracked(self.archetype, self.components, self.index, changes),
+            None => V::fetch(self.archetype, self.components, self.index),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn storage(&self) -> &'a ComponentStorage { self.components }
+
+    /// Gets the unique ID of the chunk.
+    ///
+    /// The ID does not change while entities are added to or removed from the chunk, so it can
+    /// be used to key per-chunk caches. See `ChunkId`.
+    #[inline]
+    pub fn id(&self) -> ChunkId { self.components.id() }
+
+    /// Gets the dense index of the chunk, which distinguishes it from any chunk which later
+    /// reuses its storage. See `ChunkIndex`.
+    #[inline]
+    pub fn dense_index(&self) -> ChunkIndex { self.components.dense_index() }
+
+    /// Gets the placement hint of the chunk. See `ComponentStorage::placement`.
+    #[inline]
+    pub fn placement(&self) -> u8 { self.components.placement() }
+
+    /// Gets the description of the component and tag types stored in the chunk's archetype.
+    #[inline]
+    pub fn archetype_description(&self) -> &'a ArchetypeDescription { self.archetype.description() }
+
+    /// Get a slice of all entities contained within the chunk.
+    #[inline]
+    pub fn entities(&self) -> &'a [Entity] { self.components.entities() }
+
+    /// Gets the number of entities in the chunk.
+    #[inline]
+    pub fn len(&self) -> usize { self.components.len() }
+
+    /// Determines if the chunk contains no entities.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.components.is_empty() }
+
+    /// Get the world tick at which each entity in the chunk was spawned, in the same order as
+    /// `entities`.
+    ///
+    /// The slice is empty if the world was not created with `WorldOptions::record_spawn_tick`.
+    #[inline]
+    pub fn spawn_ticks(&self) -> &'a [u64] { self.components.spawn_ticks().unwrap_or(&[]) }
+
+    /// Gets the summary of the chunk's `T` components, if `T` is summarized by `S`.
+    ///
+    /// See `World::enable_summary`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the summary needs to be recomputed while `T` is borrowed mutably, such as by
+    /// a `Write<T>` iterator over this chunk.
+    #[inline]
+    pub fn summary<T: Component, S: ComponentSummary<T>>(&self) -> Option<Arc<S>> {
+        self.components.summary::<T, S>()
+    }
+
+    /// Get an iterator of all data contained within the chunk.
+    #[inline]
+    pub fn iter(&mut self) -> <V as View<'a>>::Iter { self.fetch() }
+
+    /// Get an iterator of all data and entity IDs contained within the chunk.
+    #[inline]
+    pub fn iter_entities(&mut self) -> ZipEntities<'a, V> {
+        let entities = self.entities();
+        ZipEntities::new(entities, self.fetch())
+    }
+
+    /// Get an iterator of the data and entity IDs of a range of the entities within the
+    /// chunk, such as `128..256`. The range is clamped to the number of entities in the chunk.
+    #[inline]
+    pub fn iter_entities_range<R: RangeBounds<usize>>(&mut self, range: R) -> ZipEntities<'a, V> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1),
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => usize::MAX,
+        };
+
+        let mut iter = self.iter_entities();
+        iter.end = iter.end.min(end);
+        if start > 0 {
+            iter.nth(start - 1);
+        }
+        iter
+    }
+
+    /// Gets a description of the chunk which can be held while its data is being iterated.
+    #[inline]
+    pub fn boundary(&self) -> ChunkBoundary<'a> {
+        ChunkBoundary {
+            archetype: self.archetype,
+            components: self.components,
+            set: self.index,
+        }
+    }
+
+    /// Get a tag value.
+    pub fn tag<T: Tag>(&self) -> Option<&T> {
+        self.archetype
+            .tags()
+            .get(TagTypeId::of::<T>())
+            .map(|tags| unsafe { tags.data_slice::<T>() })
+            .map(|slice| {
+                debug_assert!(self.index < slice.len(), "chunkset index out of bounds");
+                unsafe { slice.get_unchecked(self.index) }
+            })
+    }
+
+    /// Gets the values of all tags in a view made up only of `Tagged` elements.
+    #[inline]
+    pub fn tags(&self) -> <V as TagView<'a>>::Tags
+    where
+        V: for<'b> TagView<'b>,
+    {
+        V::fetch_tags(self.archetype, self.index)
+    }
+
+    /// Get a slice of component data.
+    ///
+    /// # Panics
+    ///
+    /// This method performs runtime borrow checking. It will panic if
+    /// any other code is concurrently writing to the data slice.
+    pub fn components<T: Component>(&self) -> Option<RefMap<'a, &[T]>> {
+        if !V::reads::<T>() {
+            panic!("data type not readable via this query");
+        }
+        self.components
+            .components(ComponentTypeId::of::<T>())
+            .map(|c| unsafe { c.data_slice::<T>() })
+    }
+
+    /// Get a mutable slice of component data.
+    ///
+    /// # Panics
+    ///
+    /// This method performs runtime borrow checking. It will panic if
+    /// any other code is concurrently accessing the data slice.
+    pub fn components_mut<T: Component>(&self) -> Option<RefMapMut<'a, &mut [T]>> {
+        if !V::writes::<T>() {
+            panic!("data type not writable via this query");
+        }
+        self.components
+            .components(ComponentTypeId::of::<T>())
+            .map(|c| unsafe { c.data_slice_mut::<T>() })
+    }
+}
+
+/// A `Chunk` handed to a parallel chunk callback, which cannot escape the callback invocation.
+///
+/// The `'task` lifetime is chosen independently for each invocation of the callback, so neither
+/// the chunk nor any data borrowed through it can be stored anywhere which outlives the call.
+/// The type is also `!Send`, so it cannot be handed to another task. This prevents two tasks from
+/// ever holding the borrows of the same chunk at once.
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let query = Write::<Position>::query();
+/// query.par_for_each_chunk(&mut world, |mut chunk| {
+///     for mut pos in chunk.iter() {
+///         pos.0 += 1.0;
+///     }
+/// });
+/// ```
+///
+/// Attempting to collect chunks out of the callback is rejected at compile time. The chunks
+/// yielded by `Query::par_iter_chunks` and `Query::par_iter_chunks_indexed` are not scoped, as
+/// rayon requires the items of a parallel iterator to be `Send`.
+///
+/// ```compile_fail
+/// # use legion::prelude::*;
+/// # use std::sync::Mutex;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let query = Write::<Position>::query();
+/// let chunks = Mutex::new(Vec::new());
+/// query.par_for_each_chunk(&mut world, |chunk| {
+///     chunks.lock().unwrap().push(chunk);
+/// });
+/// ```
+#[cfg(feature = "par-iter")]
+pub struct ChunkViewScoped<'task, V: for<'b> View<'b>> {
+    chunk: Chunk<'task, V>,
+    _not_send: PhantomData<*const ()>,
+}
+
+#[cfg(feature = "par-iter")]
+impl<'task, V: for<'b> View<'b>> ChunkViewScoped<'task, V> {
+    fn new(chunk: Chunk<'task, V>) -> Self {
+        Self {
+            chunk,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "par-iter")]
+impl<'task, V: for<'b> View<'b>> Deref for ChunkViewScoped<'task, V> {
+    type Target = Chunk<'task, V>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.chunk }
+}
+
+#[cfg(feature = "par-iter")]
+impl<'task, V: for<'b> View<'b>> DerefMut for ChunkViewScoped<'task, V> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.chunk }
+}
+
+/// An iterator which yields view data tuples and entity IDs from a `Chunk`.
+pub struct ZipEntities<'data, V: View<'data>> {
+    entities: &'data [Entity],
+    data: <V as View<'data>>::Iter,
+    index: usize,
+    end: usize,
+    view: PhantomData<V>,
+}
+
+impl<'data, V: View<'data>> ZipEntities<'data, V> {
+    fn new(entities: &'data [Entity], data: <V as View<'data>>::Iter) -> Self {
+        // the entity IDs are authoritative, but a chunk whose component arrays are shorter
+        // than its entity array must not be read past the end of either
+        let (lower, upper) = data.size_hint();
+        let end = upper.map_or(entities.len(), |upper| upper.min(entities.len()));
+
+        #[cfg(debug_assertions)]
+        {
+            if lower > entities.len() || end < entities.len() {
+                tracing::warn!(
+                    entities = entities.len(),
+                    components = lower,
+                    "Chunk entity and component counts disagree"
+                );
+            }
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = lower;
+
+        Self {
+            entities,
+            data,
+            index: 0,
+            end,
+            view: PhantomData,
+        }
+    }
+}
+
+impl<'data, V: View<'data>> Iterator for ZipEntities<'data, V> {
+    type Item = (Entity, <V::Iter as Iterator>::Item);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // the view's iterator is not polled again once every entity has been yielded
+        if self.index == self.end {
+            return None;
+        }
+
+        if let Some(data) = self.data.next() {
+            let i = self.index;
+            self.index += 1;
+            Some((self.entities[i], data))
+        } else {
+            self.index = self.end;
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.index;
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // never step past the last entity, even if the view's iterator is longer
+        if n >= self.end - self.index {
+            self.index = self.end;
+            return None;
+        }
+
+        if let Some(data) = self.data.nth(n) {
+            let i = self.index + n;
+            self.index = i + 1;
+            Some((self.entities[i], data))
+        } else {
+            self.index = self.end;
+            None
+        }
+    }
+}
+
+impl<'data, V: View<'data>> DoubleEndedIterator for ZipEntities<'data, V>
+where
+    V::Iter: DoubleEndedIterator,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index == self.end {
+            return None;
+        }
+
+        // skip any data beyond the last entity, so that data is paired with the same entity
+        // from either end
+        while self.data.size_hint().0 > self.end - self.index {
+            self.data.next_back();
+        }
+
+        if let Some(data) = self.data.next_back() {
+            self.end -= 1;
+            Some((self.entities[self.end], data))
+        } else {
+            self.end = self.index;
+            None
+        }
+    }
+}
+
+impl<'data, V: View<'data>> ExactSizeIterator for ZipEntities<'data, V> {}
+
+impl<'data, V: View<'data>> FusedIterator for ZipEntities<'data, V> {}
+
+/// Finds a component which view `V` cannot currently borrow from a chunk, if any.
+fn find_borrow_conflict<V: for<'a> View<'a>>(
+    archetype: &ArchetypeData,
+    chunk: &ComponentStorage,
+) -> Option<BorrowConflict> {
+    let writes = V::write_types();
+    let reads = V::read_types();
+    let accesses = writes.iter().map(|ty| (*ty, true)).chain(
+        reads
+            .iter()
+            .filter(|ty| !writes.contains(ty))
+            .map(|ty| (*ty, false)),
+    );
+
+    for (type_id, mutable) in accesses {
+        if let Some(reason) = chunk
+            .components(type_id)
+            .and_then(|components| components.borrow_conflict(mutable))
+        {
+            let desc = archetype.description();
+            let component = desc
+                .components()
+                .iter()
+                .position(|(ty, _)| *ty == type_id)
+                .map_or("<unknown>", |i| desc.component_names()[i]);
+            return Some(BorrowConflict {
+                component,
+                mutable,
+                reason,
+            });
+        }
+    }
+
+    None
+}
+
+/// Determines if view `V` cannot currently borrow a chunk's components, logging the conflict
+/// if so. Only called when the world's `BorrowConflictPolicy` skips conflicts.
+///
+/// The check is not atomic with the borrows taken when the chunk is fetched, so a conflicting
+/// borrow taken by another thread in between still panics.
+fn skip_borrow_conflict<V: for<'a> View<'a>>(
+    archetype: &ArchetypeData,
+    chunk: &ComponentStorage,
+) -> bool {
+    match find_borrow_conflict::<V>(archetype, chunk) {
+        Some(conflict) => {
+            tracing::warn!(
+                view = std::any::type_name::<V>(),
+                chunk = ?chunk.id(),
+                %conflict,
+                "Skipping chunk whose components are already borrowed"
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+/// An iterator over all chunks that match a given query.
+///
+/// Chunks are yielded from the back one archetype at a time: the query's chunkset and chunk
+/// filters are evaluated for every chunk of the last remaining archetype before any of them is
+/// yielded, so stateful filters such as `changed` may record chunks which are never reached if
+/// iteration stops early.
+pub struct ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
+where
+    V: for<'a> View<'a>,
+    FArch: Filter<ArchetypeFilterData<'data>>,
+    FChunkset: Filter<ChunksetFilterData<'data>>,
+    FChunk: Filter<ChunkFilterData<'data>>,
+{
+    _view: PhantomData<V>,
+    storage: &'data Storage,
+    arch_filter: PhantomData<&'filter FArch>,
+    chunkset_filter: &'filter FChunkset,
+    chunk_filter: &'filter FChunk,
+    changes: &'filter Arc<ChangeTracker>,
+    archetypes: MatchedArchetypes,
+    set_frontier: Option<(&'data ArchetypeData, Take<Enumerate<FChunkset::Iter>>)>,
+    chunk_frontier: Option<(
+        &'data ArchetypeData,
+        usize,
+        &'data [ComponentStorage],
+        Take<Enumerate<FChunk::Iter>>,
+    )>,
+    back: VecDeque<(&'data ArchetypeData, usize, usize)>,
+    include_empty: bool,
+    skip_conflicts: bool,
+    peek: bool,
+    #[cfg(feature = "prefetch")]
+    prefetch: Prefetch,
+}
+
+/// Determines if `item` matches `filter`, leaving the filter's state untouched if `peek`.
+fn filter_match<T: Copy, F: Filter<T>>(
+    filter: &F,
+    item: &<F::Iter as Iterator>::Item,
+    peek: bool,
+) -> bool {
+    if peek {
+        filter.peek_match(item).is_pass()
+    } else {
+        filter.is_match(item).is_pass()
+    }
+}
+
+impl<'data, 'filter, V, FArch, FChunkset, FChunk>
+    ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
+where
+    V: for<'a> View<'a>,
+    FArch: Filter<ArchetypeFilterData<'data>>,
+    FChunkset: Filter<ChunksetFilterData<'data>>,
+    FChunk: Filter<ChunkFilterData<'data>>,
+{
+    fn next_set(&mut self) -> Option<(&'data ArchetypeData, usize)> {
+        loop {
+            // if we are looping through an archetype, find the next set
+            if let Some((ref arch, ref mut chunks)) = self.set_frontier {
+                for (set_index, filter_data) in chunks {
+                    if filter_match(self.chunkset_filter, &filter_data, self.peek) {
+                        return Some((arch, set_index));
+                    }
+                }
+            }
+
+            // we have completed the current set, move on to the next matching archetype
+            self.set_frontier = None;
+            match self.archetypes.next() {
+                Some(arch_index) => {
+                    debug_assert!(arch_index < self.storage.archetypes().len());
+                    self.set_frontier = {
+                        let chunks = unsafe { self.storage.archetypes().get_unchecked(arch_index) };
+                        let data = ChunksetFilterData {
+                            archetype_data: chunks,
+                        };
+
+                        Some((
+                            chunks,
+                            self.chunkset_filter
+                                .collect(data)
+                                .enumerate()
+                                .take(chunks.len()),
+                        ))
+                    };
+                }
+                // there are no more sets
+                None => return None,
+            }
+        }
+    }
+
+    fn next_chunk(&mut self) -> Option<(&'data ArchetypeData, usize, usize)> {
+        loop {
+            // if we are looping through a set, then yield the next chunk
+            if let Some((arch, set_index, chunks, ref mut set)) = self.chunk_frontier {
+                for (chunk_index, filter_data) in set {
+                    // empty chunks are skipped before stateful filters can record them
+                    // conflicting chunks are skipped before stateful filters can record them
+                    if (self.include_empty || !chunks[chunk_index].is_empty())
+                        && !(self.skip_conflicts
+                            && skip_borrow_conflict::<V>(arch, &chunks[chunk_index]))
+                        && filter_match(self.chunk_filter, &filter_data, self.peek)
+                    {
+                        return Some((arch, set_index, chunk_index));
+                    }
+                }
+            }
+
+            // we have completed the set, find the next
+            if let Some((arch, set_index)) = self.next_set() {
+                let chunks = unsafe { arch.chunksets().get_unchecked(set_index) }.occupied();
+                self.chunk_frontier = Some((
+                    arch,
+                    set_index,
+                    chunks,
+                    self.chunk_filter
+                        .collect(ChunkFilterData { chunks })
+                        .enumerate()
+                        .take(chunks.len()),
+                ))
+            } else {
+                // the filters' iterators are not polled again once exhausted
+                self.chunk_frontier = None;
+                return None;
+            }
+        }
+    }
+
+    // Filters every chunk of an archetype taken from the back of the matched archetypes, so
+    // that its chunks can be yielded in reverse.
+    fn buffer_archetype(&mut self, arch_index: usize) {
+        debug_assert!(arch_index < self.storage.archetypes().len());
+        let arch = unsafe { self.storage.archetypes().get_unchecked(arch_index) };
+        let sets = self
+            .chunkset_filter
+            .collect(ChunksetFilterData {
+                archetype_data: arch,
+            })
+            .enumerate()
+            .take(arch.len());
+        for (set_index, filter_data) in sets {
+            if !filter_match(self.chunkset_filter, &filter_data, self.peek) {
+                continue;
+            }
+            let chunks = unsafe { arch.chunksets().get_unchecked(set_index) }.occupied();
+            let matched = self
+                .chunk_filter
+                .collect(ChunkFilterData { chunks })
+                .enumerate()
+                .take(chunks.len());
+            for (chunk_index, filter_data) in matched {
+                if (self.include_empty || !chunks[chunk_index].is_empty())
+                    && !(self.skip_conflicts
+                        && skip_borrow_conflict::<V>(arch, &chunks[chunk_index]))
+                    && filter_match(self.chunk_filter, &filter_data, self.peek)
+                {
+                    self.back.push_back((arch, set_index, chunk_index));
+                }
+            }
+        }
+    }
+}
+
+impl<'data, 'filter, V, FArch, FChunkset, FChunk> Iterator
+    for ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
+where
+    V: for<'a> View<'a>,
+    FArch: Filter<ArchetypeFilterData<'data>>,
+    FChunkset: Filter<ChunksetFilterData<'data>>,
+    FChunk: Filter<ChunkFilterData<'data>>,
+{
+    type Item = Chunk<'data, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((arch, set_index, chunk_index)) = self.next_chunk() {
+            #[cfg(feature = "prefetch")]
+            self.prefetch.chunk_yielded(arch, set_index, chunk_index);
+            return Some(Chunk::tracked(arch, set_index, chunk_index, self.changes));
+        }
+
+        // the front has caught up with the chunks buffered by `next_back`
+        let (arch, set_index, chunk_index) = self.back.pop_front()?;
+        Some(Chunk::tracked(arch, set_index, chunk_index, self.changes))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // the remaining chunks are only bounded once the last set is being iterated
+        let chunks = match self.chunk_frontier {
+            Some((_, _, _, ref chunks)) => chunks.size_hint().1,
+            None => Some(0),
+        };
+        let sets = match self.set_frontier {
+            Some((_, ref sets)) => sets.size_hint().1,
+            None => Some(0),
+        };
+        let buffered = self.back.len();
+        match (chunks, sets, self.archetypes.len()) {
+            (Some(chunks), Some(0), 0) => (buffered, Some(chunks + buffered)),
+            _ => (buffered, None),
+        }
+    }
+}
+
+impl<'data, 'filter, V, FArch, FChunkset, FChunk> DoubleEndedIterator
+    for ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
+where
+    V: for<'a> View<'a>,
+    FArch: Filter<ArchetypeFilterData<'data>>,
+    FChunkset: Filter<ChunksetFilterData<'data>>,
+    FChunk: Filter<ChunkFilterData<'data>>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((arch, set_index, chunk_index)) = self.back.pop_back() {
+                return Some(Chunk::tracked(arch, set_index, chunk_index, self.changes));
+            }
+
+            match self.archetypes.next_back() {
+                Some(arch_index) => self.buffer_archetype(arch_index),
+                None => {
+                    // the back has caught up with the archetype being iterated from the front
+                    while let Some(chunk) = self.next_chunk() {
+                        self.back.push_back(chunk);
+                    }
+                    if self.back.is_empty() {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'data, 'filter, V, FArch, FChunkset, FChunk> FusedIterator
+    for ChunkViewIter<'data, 'filter, V, FArch, FChunkset, FChunk>
+where
+    V: for<'a> View<'a>,
+    FArch: Filter<ArchetypeFilterData<'data>>,
+    FChunkset: Filter<ChunksetFilterData<'data>>,
+    FChunk: Filter<ChunkFilterData<'data>>,
+{
+}
+
+static PREFETCH_DISTANCE: AtomicUsize = AtomicUsize::new(1);
+
+/// Sets how many chunks ahead of the chunk being yielded by a query the components accessed by
+/// the query are prefetched. A distance of `0` disables prefetching. Defaults to `1`.
+///
+/// Prefetching only takes place when the `prefetch` feature is enabled.
+pub fn set_prefetch_distance(distance: usize) {
+    PREFETCH_DISTANCE.store(distance, Ordering::Relaxed);
+}
+
+/// Gets the distance set by `set_prefetch_distance`.
+pub fn prefetch_distance() -> usize { PREFETCH_DISTANCE.load(Ordering::Relaxed) }
+
+/// Prefetches the components accessed by a view from chunks ahead of those being iterated.
+#[cfg(feature = "prefetch")]
+struct Prefetch {
+    types: Vec<ComponentTypeId>,
+    distance: usize,
+}
+
+#[cfg(feature = "prefetch")]
+impl Prefetch {
+    fn new<V: for<'a> View<'a>>() -> Self {
+        let distance = prefetch_distance();
+        let mut types = Vec::new();
+        if distance > 0 {
+            types.extend(V::read_types());
+            types.extend(V::write_types());
+            types.sort();
+            types.dedup();
+        }
+        Self { types, distance }
+    }
+
+    #[inline]
+    fn chunk_yielded(&self, archetype: &ArchetypeData, set_index: usize, chunk_index: usize) {
+        if self.types.is_empty() {
+            return;
+        }
+
+        let chunks = unsafe { archetype.chunksets().get_unchecked(set_index) }.occupied();
+        if let Some(chunk) = chunks.get(chunk_index + self.distance) {
+            chunk.prefetch(&self.types);
+        }
+    }
+}
+
+// The remaining items of the chunks being iterated are a lower bound on the remaining items of
+// a query, and are exact once no chunks remain. Counting the items in the remaining chunks
+// would require walking them; `Query::count` does so without borrowing any components.
+fn frontier_size_hint<I: Iterator>(
+    front: Option<(usize, Option<usize>)>,
+    back: Option<(usize, Option<usize>)>,
+    chunks: &I,
+) -> (usize, Option<usize>) {
+    let (front_lower, front_upper) = front.unwrap_or((0, Some(0)));
+    let (back_lower, back_upper) = back.unwrap_or((0, Some(0)));
+    let lower = front_lower.saturating_add(back_lower);
+    let upper = match (front_upper, back_upper) {
+        (Some(front), Some(back)) => front.checked_add(back),
+        _ => None,
+    };
+    match chunks.size_hint() {
+        (_, Some(0)) => (lower, upper),
+        _ => (lower, None),
+    }
+}
+
+/// Records the length and component versions of the chunks an iterator is visiting, and
+/// panics if they change while the iterator is visiting them, such as when a chunk is modified
+/// through an unchecked path during iteration. Only present in debug builds.
+#[cfg(debug_assertions)]
+struct IterGuard<'data> {
+    view: &'static str,
+    types: Vec<ComponentTypeId>,
+    front: Option<GuardedChunk<'data>>,
+    back: Option<GuardedChunk<'data>>,
+}
+
+#[cfg(debug_assertions)]
+struct GuardedChunk<'data> {
+    desc: &'data ArchetypeDescription,
+    chunk: &'data ComponentStorage,
+    len: usize,
+    versions: Vec<(ComponentTypeId, crate::storage::Version)>,
+}
+
+#[cfg(debug_assertions)]
+impl<'data> GuardedChunk<'data> {
+    fn check(&self, view: &str) {
+        let id = self.chunk.id();
+        assert!(
+            self.chunk.len() == self.len,
+            "chunk {:?} was modified while being iterated by query {}: its length changed from \
+             {} to {}",
+            id,
+            view,
+            self.len,
+            self.chunk.len()
+        );
+        for (type_id, version) in &self.versions {
+            let current = self.chunk.components(*type_id).map(|c| c.version());
+            if current != Some(*version) {
+                let name = self
+                    .desc
+                    .components()
+                    .iter()
+                    .zip(self.desc.component_names())
+                    .find(|((t, _), _)| t == type_id)
+                    .map_or("<unknown>", |(_, name)| *name);
+                panic!(
+                    "chunk {:?} was modified while being iterated by query {}: the version of \
+                     {} changed from {:?} to {:?}",
+                    id, view, name, version, current
+                );
+            }
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl<'data> IterGuard<'data> {
+    fn new<V: for<'a> View<'a>>() -> Self {
+        let mut types = V::read_types();
+        types.extend(V::write_types());
+        types.sort();
+        types.dedup();
+        Self {
+            view: std::any::type_name::<V>(),
+            types,
+            front: None,
+            back: None,
+        }
+    }
+
+    fn guard(&self, chunk: &Chunk<'data, impl for<'a> View<'a>>) -> GuardedChunk<'data> {
+        let storage = chunk.storage();
+        GuardedChunk {
+            desc: chunk.archetype_description(),
+            chunk: storage,
+            len: storage.len(),
+            versions: self
+                .types
+                .iter()
+                .filter_map(|t| storage.components(*t).map(|c| (*t, c.version())))
+                .collect(),
+        }
+    }
+
+    /// Checks the chunk previously visited from the front, and starts guarding `chunk`, whose
+    /// data must already have been fetched.
+    fn enter_front(&mut self, chunk: &Chunk<'data, impl for<'a> View<'a>>) {
+        if let Some(previous) = &self.front {
+            previous.check(self.view);
+        }
+        self.front = Some(self.guard(chunk));
+    }
+
+    /// Checks the chunk previously visited from the back, and starts guarding `chunk`.
+    fn enter_back(&mut self, chunk: &Chunk<'data, impl for<'a> View<'a>>) {
+        if let Some(previous) = &self.back {
+            previous.check(self.view);
+        }
+        self.back = Some(self.guard(chunk));
+    }
+}
+
+#[cfg(debug_assertions)]
+impl<'data> Drop for IterGuard<'data> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        for chunk in self.front.iter().chain(self.back.iter()) {
+            chunk.check(self.view);
+        }
+    }
+}
+
+/// An iterator which iterates through all entity data in all chunks.
+///
+/// The iterator's `size_hint` only accounts for the chunk currently being iterated. Use
+/// `Query::count` to find the number of matching entities up front, e.g. to preallocate.
+pub struct ChunkDataIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    iter: I,
+    frontier: Option<<V as View<'data>>::Iter>,
+    back_frontier: Option<<V as View<'data>>::Iter>,
+    #[cfg(debug_assertions)]
+    guard: IterGuard<'data>,
+    _view: PhantomData<V>,
+}
+
+impl<'data, V, I> Iterator for ChunkDataIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    type Item = <<V as View<'data>>::Iter as Iterator>::Item;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut inner) = self.frontier {
+                if let elt @ Some(_) = inner.next() {
+                    return elt;
+                }
+                // the chunk's iterator is not polled again once exhausted
+                self.frontier = None;
+            }
+            match self.iter.next() {
+                Some(mut inner) => {
+                    self.frontier = Some(inner.iter());
+                    #[cfg(debug_assertions)]
+                    self.guard.enter_front(&inner);
+                }
+                None => return self.back_frontier.as_mut()?.next(),
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        frontier_size_hint(
+            self.frontier.as_ref().map(|f| f.size_hint()),
+            self.back_frontier.as_ref().map(|f| f.size_hint()),
+            &self.iter,
+        )
+    }
+
+    // drains each chunk with its own `fold`, so that `for_each` does not need to check which
+    // chunk it is in for every item
+    #[inline]
+    fn fold<Acc, G>(self, init: Acc, mut f: G) -> Acc
+    where
+        G: FnMut(Acc, Self::Item) -> Acc,
+    {
+        let mut acc = init;
+        if let Some(front) = self.frontier {
+            acc = front.fold(acc, &mut f);
+        }
+        #[cfg(debug_assertions)]
+        let mut guard = self.guard;
+        acc = self.iter.fold(acc, |acc, mut chunk| {
+            let items = chunk.iter();
+            #[cfg(debug_assertions)]
+            guard.enter_front(&chunk);
+            items.fold(acc, &mut f)
+        });
+        if let Some(back) = self.back_frontier {
+            acc = back.fold(acc, &mut f);
+        }
+        acc
+    }
+}
+
+impl<'data, V, I> DoubleEndedIterator for ChunkDataIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    <V as View<'data>>::Iter: DoubleEndedIterator,
+    I: DoubleEndedIterator<Item = Chunk<'data, V>>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut inner) = self.back_frontier {
+                if let elt @ Some(_) = inner.next_back() {
+                    return elt;
+                }
+                self.back_frontier = None;
+            }
+            match self.iter.next_back() {
+                Some(mut inner) => {
+                    self.back_frontier = Some(inner.iter());
+                    #[cfg(debug_assertions)]
+                    self.guard.enter_back(&inner);
+                }
+                None => return self.frontier.as_mut()?.next_back(),
+            }
+        }
+    }
+}
+
+impl<'data, V, I> FusedIterator for ChunkDataIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: FusedIterator<Item = Chunk<'data, V>>,
+{
+}
+
+impl<'data, V, I> ChunkDataIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    /// Pairs each item with the `ChunkBoundary` of its chunk if it is the first item of that
+    /// chunk, or else `None`, so that per-chunk state (such as a shared tag value) can be
+    /// updated once per chunk while iterating entity data as normal.
+    ///
+    /// Items which remain from a chunk that was already partially consumed are paired with
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Material(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((Material(1),), vec![(Position(0.0),), (Position(1.0),)]);
+    /// world.insert((Material(2),), vec![(Position(2.0),)]);
+    ///
+    /// let query = Read::<Position>::query();
+    /// let mut material = None;
+    /// for (boundary, pos) in query.iter(&mut world).with_chunk_boundaries() {
+    ///     if let Some(chunk) = boundary {
+    ///         material = chunk.tag::<Material>().copied();
+    ///     }
+    ///     println!("{:?} drawn with {:?}", *pos, material);
+    /// }
+    /// ```
+    pub fn with_chunk_boundaries(self) -> ChunkBoundaryIter<'data, V, I> {
+        ChunkBoundaryIter {
+            iter: self.iter,
+            frontier: self.frontier,
+            back_frontier: self.back_frontier,
+            boundary: None,
+        }
+    }
+}
+
+/// A description of a chunk, which does not give access to the chunk's component data and so
+/// can be held while that data is being iterated. See `ChunkDataIter::with_chunk_boundaries`.
+#[derive(Copy, Clone)]
+pub struct ChunkBoundary<'a> {
+    archetype: &'a ArchetypeData,
+    components: &'a ComponentStorage,
+    set: usize,
+}
+
+impl<'a> ChunkBoundary<'a> {
+    /// Gets the unique ID of the chunk.
+    #[inline]
+    pub fn id(&self) -> ChunkId { self.components.id() }
+
+    /// Gets the description of the component and tag types stored in the chunk's archetype.
+    #[inline]
+    pub fn archetype_description(&self) -> &'a ArchetypeDescription { self.archetype.description() }
+
+    /// Get a slice of all entities contained within the chunk.
+    #[inline]
+    pub fn entities(&self) -> &'a [Entity] { self.components.entities() }
+
+    /// Gets the number of entities in the chunk.
+    #[inline]
+    pub fn len(&self) -> usize { self.components.len() }
+
+    /// Determines if the chunk contains no entities.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.components.is_empty() }
+
+    /// Get a tag value.
+    pub fn tag<T: Tag>(&self) -> Option<&'a T> {
+        self.archetype
+            .tags()
+            .get(TagTypeId::of::<T>())
+            .map(|tags| unsafe { tags.data_slice::<T>() })
+            .and_then(|slice| slice.get(self.set))
+    }
+}
+
+impl<'a> std::fmt::Debug for ChunkBoundary<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ChunkBoundary")
+            .field("id", &self.id())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// An iterator which iterates through all entity data in all chunks, paired with the
+/// `ChunkBoundary` of the first item of each chunk. See `ChunkDataIter::with_chunk_boundaries`.
+pub struct ChunkBoundaryIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    iter: I,
+    frontier: Option<<V as View<'data>>::Iter>,
+    back_frontier: Option<<V as View<'data>>::Iter>,
+    boundary: Option<ChunkBoundary<'data>>,
+}
+
+impl<'data, V, I> Iterator for ChunkBoundaryIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    type Item = (
+        Option<ChunkBoundary<'data>>,
+        <<V as View<'data>>::Iter as Iterator>::Item,
+    );
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut inner) = self.frontier {
+                if let Some(elt) = inner.next() {
+                    return Some((self.boundary.take(), elt));
+                }
+                self.frontier = None;
+            }
+            match self.iter.next() {
+                Some(mut inner) => {
+                    // empty chunks never yield their boundary, as it is replaced by the next
+                    self.boundary = Some(inner.boundary());
+                    self.frontier = Some(inner.iter());
+                }
+                None => return self.back_frontier.as_mut()?.next().map(|elt| (None, elt)),
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        frontier_size_hint(
+            self.frontier.as_ref().map(|f| f.size_hint()),
+            self.back_frontier.as_ref().map(|f| f.size_hint()),
+            &self.iter,
+        )
+    }
+}
+
+impl<'data, V, I> FusedIterator for ChunkBoundaryIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: FusedIterator<Item = Chunk<'data, V>>,
+{
+}
+
+/// An iterator which iterates through all entity data in all chunks, zipped with entity ID.
+pub struct ChunkEntityIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    iter: I,
+    frontier: Option<ZipEntities<'data, V>>,
+    back_frontier: Option<ZipEntities<'data, V>>,
+    #[cfg(debug_assertions)]
+    guard: IterGuard<'data>,
+    _view: PhantomData<V>,
+}
+
+impl<'data, 'query, V, I> Iterator for ChunkEntityIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    type Item = (Entity, <<V as View<'data>>::Iter as Iterator>::Item);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut inner) = self.frontier {
+                if let elt @ Some(_) = inner.next() {
+                    return elt;
+                }
+            }
+            match self.iter.next() {
+                Some(mut inner) => {
+                    self.frontier = Some(inner.iter_entities());
+                    #[cfg(debug_assertions)]
+                    self.guard.enter_front(&inner);
+                }
+                None => return self.back_frontier.as_mut()?.next(),
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        frontier_size_hint(
+            self.frontier.as_ref().map(|f| f.size_hint()),
+            self.back_frontier.as_ref().map(|f| f.size_hint()),
+            &self.iter,
+        )
+    }
+
+    // drains each chunk with its own `fold`, so that `for_each` does not need to check which
+    // chunk it is in for every item
+    #[inline]
+    fn fold<Acc, G>(self, init: Acc, mut f: G) -> Acc
+    where
+        G: FnMut(Acc, Self::Item) -> Acc,
+    {
+        let mut acc = init;
+        if let Some(front) = self.frontier {
+            acc = front.fold(acc, &mut f);
+        }
+        #[cfg(debug_assertions)]
+        let mut guard = self.guard;
+        acc = self.iter.fold(acc, |acc, mut chunk| {
+            let items = chunk.iter_entities();
+            #[cfg(debug_assertions)]
+            guard.enter_front(&chunk);
+            items.fold(acc, &mut f)
+        });
+        if let Some(back) = self.back_frontier {
+            acc = back.fold(acc, &mut f);
+        }
+        acc
+    }
+}
+
+impl<'data, V, I> DoubleEndedIterator for ChunkEntityIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    <V as View<'data>>::Iter: DoubleEndedIterator,
+    I: DoubleEndedIterator<Item = Chunk<'data, V>>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut inner) = self.back_frontier {
+                if let elt @ Some(_) = inner.next_back() {
+                    return elt;
+                }
+                self.back_frontier = None;
+            }
+            match self.iter.next_back() {
+                Some(mut inner) => {
+                    self.back_frontier = Some(inner.iter_entities());
+                    #[cfg(debug_assertions)]
+                    self.guard.enter_back(&inner);
+                }
+                None => return self.frontier.as_mut()?.next_back(),
+            }
+        }
+    }
+}
+
+impl<'data, V, I> FusedIterator for ChunkEntityIter<'data, V, I>
+where
+    V: for<'a> View<'a>,
+    I: FusedIterator<Item = Chunk<'data, V>>,
+{
+}
+
+/// An iterator which yields the tag values of each chunk, along with the number of entities in
+/// the chunk. See `Query::iter_per_chunk`.
+pub struct ChunkTagIter<'data, V, I>
+where
+    V: for<'a> TagView<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    iter: I,
+}
+
+impl<'data, V, I> Iterator for ChunkTagIter<'data, V, I>
+where
+    V: for<'a> TagView<'a>,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    type Item = (<V as TagView<'data>>::Tags, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|chunk| (chunk.tags(), chunk.len()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) { self.iter.size_hint() }
+}
+
+impl<'data, V, I> DoubleEndedIterator for ChunkTagIter<'data, V, I>
+where
+    V: for<'a> TagView<'a>,
+    I: DoubleEndedIterator<Item = Chunk<'data, V>>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|chunk| (chunk.tags(), chunk.len()))
+    }
+}
+
+impl<'data, V, I> FusedIterator for ChunkTagIter<'data, V, I>
+where
+    V: for<'a> TagView<'a>,
+    I: FusedIterator<Item = Chunk<'data, V>>,
+{
+}
+
+/// Queries for entities within a `World`.
+///
+/// # Examples
+///
+/// Queries can be constructed from any `View` type, including tuples of `View`s.
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Model;
+/// // A query which matches any entity with a `Position` component
+/// let mut query = Read::<Position>::query();
+///
+/// // A query which matches any entity with both a `Position` and a `Velocity` component
+/// let mut query = <(Read<Position>, Read<Velocity>)>::query();
+/// ```
+///
+/// The view determines what data is accessed, and whether it is accessed mutably or not.
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Model;
+/// // A query which writes `Position`, reads `Velocity` and reads `Model`
+/// // Tags are read-only, and is distinguished from entity data reads with `Tagged<T>`.
+/// let mut query = <(Write<Position>, Read<Velocity>, Tagged<Model>)>::query();
+/// ```
+///
+/// By default, a query will filter its results to include only entities with the data
+/// types accessed by the view. However, additional filters can be specified if needed:
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Model;
+/// #[derive(Copy, Clone, Debug, PartialEq)]
+/// struct Static;
+///
+/// // A query which also requires that entities have the `Static` tag
+/// let mut query = <(Read<Position>, Tagged<Model>)>::query().filter(tag::<Static>());
+/// ```
+///
+/// Filters can be combined with bitwise operators:
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Model;
+/// #[derive(Copy, Clone, Debug, PartialEq)]
+/// struct Static;
+///
+/// // This query matches entities with positions and a model
+/// // But it also requires that the entity is not static, or has moved (even if static)
+/// let mut query = <(Read<Position>, Tagged<Model>)>::query()
+///     .filter(!tag::<Static>() | changed::<Position>());
+/// ```
+///
+/// Filters can be iterated through to pull data out of a `World`:
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Model;
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// // A query which writes `Position`, reads `Velocity` and reads `Model`
+/// // Tags are read-only, and is distinguished from entity data reads with `Tagged<T>`.
+/// let mut query = <(Write<Position>, Read<Velocity>, Tagged<Model>)>::query();
+///
+/// for (mut pos, vel, model) in query.iter(&mut world) {
+///     // `.iter` yields tuples of references to a single entity's data:
+///     // pos: &mut Position
+///     // vel: &Velocity
+///     // model: &Model
+/// }
+/// ```
+///
+/// The lower level `iter_chunks` function allows access to each underlying chunk of entity data.
+/// This allows you to run code for each tag value, or to retrieve a contiguous data slice.
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Model;
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let mut query = <(Write<Position>, Read<Velocity>, Tagged<Model>)>::query();
+///
+/// for chunk in query.iter_chunks(&mut world) {
+///     let model = chunk.tag::<Model>();
+///     let positions = chunk.components_mut::<Position>();
+///     let velocities = chunk.components::<Velocity>();
+/// }
+/// ```
+///
+/// The `ChunkView` yielded from `iter_chunks` allows access to all shared data in the chunk (queried for or not),
+/// but entity data slices can only be accessed if they were requested in the query's view. Attempting to access
+/// other data types, or attempting to write to components that were only requested via a `Read` will panic.
+///
+/// # Iteration order
+///
+/// Queries visit entities in a stable natural order: archetypes in the order in which they were
+/// created, then each archetype's chunk sets and chunks in order, then the entities within each
+/// chunk in storage order. Two worlds built by the same sequence of operations therefore iterate
+/// their entities in the same order, which makes iteration suitable for lockstep simulations.
+///
+/// The natural order changes as entities are removed, which swaps the last entity of a chunk
+/// into the vacated slot, and as `World::defrag` moves entities between chunks. Worlds whose
+/// archetypes may have been created in a different order should use `iter_entities_sorted`,
+/// which yields entities in order of their `Entity` IDs.
+///
+/// # Panic safety
+///
+/// Component borrows are held by guards inside the query's iterators and released when they are dropped,
+/// including while unwinding. A panic inside a `for_each` or `par_for_each` closure which is caught with
+/// `catch_unwind` therefore leaves the world usable. Borrows can only remain held if a guard is leaked, in
+/// which case `World::clear_poisoned_borrows` can be used to recover.
+///
+/// # Cloning
+///
+/// Queries can be cloned whenever their filter can, e.g. to build a query once and hand copies
+/// of it to several workers. Stateful filters such as `changed`, and `Changed<T>` view elements,
+/// do not carry their state over into clones: each clone starts without any recorded versions,
+/// so it reports every chunk as changed on its first run and then tracks changes independently
+/// of the original.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "F: Clone"))]
+pub struct Query<V: for<'a> View<'a>, F: EntityFilter> {
+    view: PhantomData<V>,
+    pub(crate) filter: F,
+    #[derivative(Clone(clone_with = "clone_changes"))]
+    changes: Arc<ChangeTracker>,
+    archetypes: ArchetypeCache,
+    pool: ExecutionPool,
+}
+
+// clones of a query start tracking changes afresh, as they do with their filter's state
+fn clone_changes(_: &Arc<ChangeTracker>) -> Arc<ChangeTracker> { Arc::new(ChangeTracker::new()) }
+
+/// The rayon thread pool on which a query performs its parallel work.
+#[derive(Clone, Default)]
+struct ExecutionPool {
+    #[cfg(feature = "par-iter")]
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+#[cfg(feature = "par-iter")]
+impl ExecutionPool {
+    /// Runs `op` within the query's thread pool, or on the current thread if none was given.
+    fn install<R, OP>(&self, op: OP) -> R
+    where
+        R: Send,
+        OP: FnOnce() -> R + Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// Caches the indices of the archetypes in a world which are matched by a query's archetype
+/// filter.
+///
+/// Archetypes are only ever appended to a world, and archetype filters only depend upon the
+/// layout of each archetype, so only archetypes created since the cache was last updated need
+/// to be checked.
+#[derive(Default)]
+struct ArchetypeCache {
+    cached: Mutex<CachedArchetypes>,
+}
+
+#[derive(Default)]
+struct CachedArchetypes {
+    storage: u64,
+    checked: usize,
+    matched: Arc<Vec<usize>>,
+}
+
+impl ArchetypeCache {
+    fn matching<'a, F>(&self, storage: &'a Storage, filter: &F) -> MatchedArchetypes
+    where
+        F: Filter<ArchetypeFilterData<'a>>,
+    {
+        let mut cached = self.cached.lock();
+        if cached.storage != storage.uid() {
+            *cached = CachedArchetypes {
+                storage: storage.uid(),
+                ..CachedArchetypes::default()
+            };
+        }
+
+        let count = storage.archetypes().len();
+        if cached.checked < count {
+            let checked = cached.checked;
+            let new = filter
+                .collect(ArchetypeFilterData {
+                    component_types: storage.component_types(),
+                    tag_types: storage.tag_types(),
+                })
+                .enumerate()
+                .skip(checked)
+                .filter(|(_, data)| filter.is_match(data).is_pass())
+                .map(|(index, _)| index);
+            Arc::make_mut(&mut cached.matched).extend(new);
+            cached.checked = count;
+        }
+
+        MatchedArchetypes {
+            end: cached.matched.len(),
+            indices: cached.matched.clone(),
+            next: 0,
+        }
+    }
+
+    /// Checks the archetypes created since the cache was last updated, as `matching` does,
+    /// but in parallel. Matched archetypes are recorded in the same order.
+    #[cfg(feature = "par-iter")]
+    fn par_update<'a, F>(&self, storage: &'a Storage, filter: &F)
+    where
+        F: Filter<ArchetypeFilterData<'a>>,
+        F::Iter: FissileIterator,
+    {
+        let mut cached = self.cached.lock();
+        if cached.storage != storage.uid() {
+            *cached = CachedArchetypes {
+                storage: storage.uid(),
+                ..CachedArchetypes::default()
+            };
+        }
+
+        let count = storage.archetypes().len();
+        if cached.checked < count {
+            let archetypes = FissileEnumerate::new(filter.collect(ArchetypeFilterData {
+                component_types: storage.component_types(),
+                tag_types: storage.tag_types(),
+            }));
+            let new = par_match_archetypes(filter, archetypes, 0, count, cached.checked);
+            Arc::make_mut(&mut cached.matched).extend(new);
+            cached.checked = count;
+        }
+    }
+}
+
+/// The number of archetypes below which `par_match_archetypes` stops splitting its work.
+#[cfg(feature = "par-iter")]
+const PAR_MATCH_MIN_ARCHETYPES: usize = 256;
+
+/// Finds the indices of the archetypes from `from` onwards which match `filter`, splitting
+/// `archetypes`, which covers `len` archetypes starting at index `start`, between tasks.
+#[cfg(feature = "par-iter")]
+fn par_match_archetypes<'a, F>(
+    filter: &F,
+    archetypes: FissileEnumerate<F::Iter>,
+    start: usize,
+    len: usize,
+    from: usize,
+) -> Vec<usize>
+where
+    F: Filter<ArchetypeFilterData<'a>>,
+    F::Iter: FissileIterator,
+{
+    if start + len <= from {
+        return Vec::new();
+    }
+
+    if len <= PAR_MATCH_MIN_ARCHETYPES {
+        return archetypes
+            .skip(from.saturating_sub(start))
+            .filter(|(_, data)| filter.is_match(data).is_pass())
+            .map(|(index, _)| index)
+            .collect();
+    }
+
+    let (left, right, left_len) = archetypes.split();
+    let (mut left, right) = rayon::join(
+        || par_match_archetypes(filter, left, start, left_len, from),
+        || par_match_archetypes(filter, right, start + left_len, len - left_len, from),
+    );
+    left.extend(right);
+    left
+}
+
+// a new query has not yet seen any world
+impl Clone for ArchetypeCache {
+    fn clone(&self) -> Self { Self::default() }
+}
+
+/// Iterates through the indices of the archetypes matched by a query.
+struct MatchedArchetypes {
+    indices: Arc<Vec<usize>>,
+    next: usize,
+    end: usize,
+}
+
+impl Iterator for MatchedArchetypes {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(self.indices[self.next - 1])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for MatchedArchetypes {
+    fn next_back(&mut self) -> Option<usize> {
+        if self.next == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.indices[self.end])
+    }
+}
+
+impl ExactSizeIterator for MatchedArchetypes {}
+
+impl FusedIterator for MatchedArchetypes {}
+
+/// What `Query::drain_entities` should do with an entity.
+pub enum EntityDecision {
+    /// Leaves the entity as it is.
+    Keep,
+    /// Deletes the entity.
+    Delete,
+    /// Records arbitrary commands, such as adding or removing components from the entity.
+    Custom(Box<dyn FnOnce(&CommandBuffer)>),
+}
+
+impl std::fmt::Debug for EntityDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EntityDecision::Keep => write!(f, "Keep"),
+            EntityDecision::Delete => write!(f, "Delete"),
+            EntityDecision::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Chooses up to `k` distinct indices into the concatenation of chunks with the given lengths,
+/// and maps each of them to a `(chunk, offset)` pair, in ascending order.
+#[cfg(feature = "rand")]
+fn sample_offsets<R: Rng + ?Sized>(
+    rng: &mut R,
+    lengths: impl Iterator<Item = usize>,
+    k: usize,
+) -> Vec<(usize, usize)> {
+    let lengths = lengths.collect::<Vec<_>>();
+    let total = lengths.iter().sum();
+    let mut indices = rand::seq::index::sample(rng, total, k.min(total)).into_vec();
+    indices.sort_unstable();
+
+    let mut chunk = 0;
+    let mut start = 0;
+    indices
+        .into_iter()
+        .map(|index| {
+            while index >= start + lengths[chunk] {
+                start += lengths[chunk];
+                chunk += 1;
+            }
+            (chunk, index - start)
+        })
+        .collect()
+}
+
+/// An error returned by `Query::single` when the query does not match exactly one entity.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SingleError {
+    /// No entities match the query.
+    NoEntities,
+    /// More than one entity matches the query.
+    MultipleEntities,
+}
+
+impl std::fmt::Display for SingleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SingleError::NoEntities => write!(f, "no entities match the query"),
+            SingleError::MultipleEntities => write!(f, "more than one entity matches the query"),
+        }
+    }
+}
+
+impl std::error::Error for SingleError {}
+
+/// A query paired with the world it is to be run against, which can be iterated with a `for`
+/// loop. See `Query::on`.
+pub struct QueryRef<'a, 'data, V: for<'v> View<'v>, F: EntityFilter> {
+    query: &'a Query<V, F>,
+    world: &'data World,
+}
+
+impl<'a, 'data, V, F> QueryRef<'a, 'data, V, F>
+where
+    V: for<'v> View<'v> + ReadOnly,
+    F: EntityFilter,
+{
+    /// Gets an iterator which also yields the `Entity` IDs of the matching entities.
+    pub fn entities(
+        self,
+    ) -> ChunkEntityIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    > {
+        self.query.iter_entities_immutable(self.world)
+    }
+}
+
+impl<'a, 'data, V, F> From<(&'a Query<V, F>, &'data World)> for QueryRef<'a, 'data, V, F>
+where
+    V: for<'v> View<'v>,
+    F: EntityFilter,
+{
+    fn from((query, world): (&'a Query<V, F>, &'data World)) -> Self { Self { query, world } }
+}
+
+impl<'a, 'data, V, F> IntoIterator for QueryRef<'a, 'data, V, F>
+where
+    V: for<'v> View<'v> + ReadOnly,
+    F: EntityFilter,
+{
+    type Item = <<V as View<'data>>::Iter as Iterator>::Item;
+    type IntoIter = ChunkDataIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    >;
+
+    fn into_iter(self) -> Self::IntoIter { self.query.iter_immutable(self.world) }
+}
+
+impl<V, F> Query<V, F>
+where
+    V: for<'a> View<'a>,
+    F: EntityFilter,
+{
+    /// Gets the query's filter, e.g. to inspect it with `EntityFilter::describe`.
+    pub fn filter_ref(&self) -> &F { &self.filter }
+
+    /// Gets a mutable reference to the query's filter.
+    pub fn filter_mut(&mut self) -> &mut F {
+        // the filter may be changed to match different archetypes
+        self.archetypes = ArchetypeCache::default();
+        &mut self.filter
+    }
+
+    /// Transforms each item of the query with the given function, returning a query-like
+    /// object which can be stored and iterated like the original query.
+    ///
+    /// The adapted query selects and borrows the same chunks as this query.
+    pub fn map<M, R>(self, map: M) -> AdaptedQuery<V, F, MapPipe<Identity, M>>
+    where
+        M: for<'a> Fn(<<V as View<'a>>::Iter as Iterator>::Item) -> R + Send + Sync,
+    {
+        AdaptedQuery {
+            query: self,
+            pipe: Identity,
+        }
+        .map(map)
+    }
+
+    /// Skips entities for which the given predicate returns `false`, returning a query-like
+    /// object which can be stored and iterated like the original query.
+    ///
+    /// Unlike a `Filter`, which selects whole chunks, the predicate is evaluated for each
+    /// entity as it is iterated. The adapted query selects and borrows the same chunks as this
+    /// query.
+    pub fn filter_entities<Pr>(self, predicate: Pr) -> AdaptedQuery<V, F, FilterPipe<Identity, Pr>>
+    where
+        Pr: for<'a, 'b> Fn(Entity, &'b <<V as View<'a>>::Iter as Iterator>::Item) -> bool
+            + Send
+            + Sync,
+    {
+        AdaptedQuery {
+            query: self,
+            pipe: Identity,
+        }
+        .filter_entities(predicate)
+    }
+
+    /// Adds an additional filter to the query.
+    pub fn filter<T: EntityFilter>(self, filter: T) -> Query<V, <F as std::ops::BitAnd<T>>::Output>
+    where
+        F: std::ops::BitAnd<T>,
+        <F as std::ops::BitAnd<T>>::Output: EntityFilter,
+    {
+        Query {
+            view: self.view,
+            filter: self.filter & filter,
+            changes: self.changes,
+            archetypes: ArchetypeCache::default(),
+            pool: self.pool,
+        }
+    }
+
+    #[cfg(feature = "par-iter")]
+    /// Runs the query's parallel work on the given rayon thread pool, rather than on the pool
+    /// the query is executed from.
+    ///
+    /// This applies to the parallel iterators returned by the query, such as `par_iter_chunks`
+    /// and `par_iter`, and to the `par_for_each` family, `par_fold` and `par_match`. The indexed
+    /// iterator returned by `par_iter_chunks_indexed` is an exception: it is driven by rayon's own
+    /// producers, so it runs on whichever pool it is consumed in. Use `ThreadPool::install` to
+    /// choose that pool. The `placed` variants run on their `PlacementPool` as before.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # use std::sync::Arc;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// # world.insert((), (0..10).map(|i| (Position(i as f32),)));
+    /// let simulation = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap());
+    ///
+    /// let query = Write::<Position>::query().with_thread_pool(simulation);
+    /// query.par_for_each(&mut world, |mut pos| {
+    ///     assert!(rayon::current_thread_index().is_some());
+    ///     pos.0 += 1.;
+    /// });
+    /// ```
+    pub fn with_thread_pool(mut self, pool: Arc<rayon::ThreadPool>) -> Self {
+        self.pool = ExecutionPool { pool: Some(pool) };
+        self
+    }
+
+    /// Creates a query with the same view which uses a different filter, but shares this
+    /// query's `Changed<T>` state.
+    pub(crate) fn with_filter<G: EntityFilter>(&self, filter: G) -> Query<V, G> {
+        Query {
+            view: PhantomData,
+            filter,
+            changes: self.changes.clone(),
+            archetypes: ArchetypeCache::default(),
+            pool: self.pool.clone(),
+        }
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query.
+    /// Does not perform static borrow checking.
+    ///
+    /// Chunks which contain no entities, such as those left behind when all of their entities
+    /// have been deleted, are skipped before the query's chunk filters are evaluated, so they
+    /// are never recorded by stateful filters such as `changed`. Use
+    /// `iter_chunks_including_empty_unchecked` to also visit them.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn iter_chunks_unchecked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+        let (arch_filter, chunkset_filter, chunk_filter) = self.filter.filters();
+        let storage = world.storage();
+        let archetypes = self.archetypes.matching(storage, arch_filter);
+        ChunkViewIter {
+            storage,
+            arch_filter: PhantomData,
+            chunkset_filter,
+            chunk_filter,
+            changes: &self.changes,
+            archetypes,
+            set_frontier: None,
+            chunk_frontier: None,
+            back: VecDeque::new(),
+            include_empty: false,
+            skip_conflicts: cfg!(any(debug_assertions, feature = "borrow-checks"))
+                && storage.borrow_conflict_policy().skips(),
+            peek: false,
+            #[cfg(feature = "prefetch")]
+            prefetch: Prefetch::new::<V>(),
+            _view: PhantomData,
+        }
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query.
+    pub fn iter_chunks_immutable<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.iter_chunks_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query.
+    pub fn iter_chunks<'a, 'data>(
+        &'a self,
+        world: &'data mut World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.iter_chunks_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query, including
+    /// chunks which contain no entities.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn iter_chunks_including_empty_unchecked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+        let mut iter = self.iter_chunks_unchecked(world);
+        iter.include_empty = true;
+        iter
+    }
+
+    /// Gets an iterator over the chunks that match the query, for use by callers which never
+    /// fetch the chunks' views. Chunks whose components are already borrowed are not skipped.
+    fn iter_chunks_unfetched<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+        // safe because the chunks' views are never fetched
+        let mut iter = unsafe { self.iter_chunks_unchecked(world) };
+        iter.skip_conflicts = false;
+        iter
+    }
+
+    /// Gets an iterator over the chunks that match the query, like `iter_chunks_unfetched`,
+    /// without updating the state of stateful filters such as `changed`.
+    fn iter_chunks_peeked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+        let mut iter = self.iter_chunks_unfetched(world);
+        iter.peek = true;
+        iter
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query, including
+    /// chunks which contain no entities.
+    pub fn iter_chunks_including_empty_immutable<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.iter_chunks_including_empty_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query, including
+    /// chunks which contain no entities.
+    pub fn iter_chunks_including_empty<'a, 'data>(
+        &'a self,
+        world: &'data mut World,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.iter_chunks_including_empty_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    ///
+    /// In debug builds, the iterator panics if a chunk's length or the version of one of the
+    /// view's components changes while the chunk is being iterated.
+    pub unsafe fn iter_entities_unchecked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkEntityIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    > {
+        ChunkEntityIter {
+            iter: self.iter_chunks_unchecked(world),
+            frontier: None,
+            back_frontier: None,
+            #[cfg(debug_assertions)]
+            guard: IterGuard::new::<V>(),
+            _view: PhantomData,
+        }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
+    pub fn iter_entities_immutable<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkEntityIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    >
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.iter_entities_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
+    pub fn iter_entities<'a, 'data>(
+        &'a self,
+        world: &'data mut World,
+    ) -> ChunkEntityIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    > {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.iter_entities_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, along
+    /// with the `Entity` IDs, in order of the entities' indices rather than the natural order.
+    /// Does not perform static borrow checking.
+    ///
+    /// Every matching entity's data is collected before the first is yielded, so the order
+    /// does not depend on how the world's storage is laid out.
+    ///
+    /// # Safety
+    ///
+    /// The normal borrowing restrictions apply for the duration of the iteration:
+    /// * Components borrowed with `Read` access must not be borrowed mutably elsewhere.
+    /// * Components borrowed with `Write` access must not be borrowed elsewhere at all.
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn iter_entities_sorted_unchecked<'data>(
+        &self,
+        world: &'data World,
+    ) -> std::vec::IntoIter<(Entity, <<V as View<'data>>::Iter as Iterator>::Item)> {
+        let mut items = Vec::new();
+        for mut chunk in self.iter_chunks_unchecked(world) {
+            items.extend(chunk.iter_entities());
+        }
+        items.sort_unstable_by_key(|(entity, _)| entity.index());
+        items.into_iter()
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, along
+    /// with the `Entity` IDs, in order of the entities' indices rather than the natural order.
+    pub fn iter_entities_sorted_immutable<'data>(
+        &self,
+        world: &'data World,
+    ) -> std::vec::IntoIter<(Entity, <<V as View<'data>>::Iter as Iterator>::Item)>
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.iter_entities_sorted_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, along
+    /// with the `Entity` IDs, in order of the entities' indices rather than the natural order.
+    ///
+    /// Every matching entity's data is collected before the first is yielded, so the order
+    /// does not depend on how the world's storage is laid out, such as the order in which its
+    /// archetypes were created. This allocates, unlike `iter_entities`.
+    pub fn iter_entities_sorted<'data>(
+        &self,
+        world: &'data mut World,
+    ) -> std::vec::IntoIter<(Entity, <<V as View<'data>>::Iter as Iterator>::Item)> {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.iter_entities_sorted_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, ordered
+    /// by the key which `key_fn` extracts from each item. Does not perform static borrow checking.
+    ///
+    /// Every matching entity's data is collected, and `key_fn` called once for each, before the
+    /// first item is yielded. Items with equal keys are yielded in their natural order.
+    ///
+    /// # Safety
+    ///
+    /// The normal borrowing restrictions apply for the duration of the iteration:
+    /// * Components borrowed with `Read` access must not be borrowed mutably elsewhere.
+    /// * Components borrowed with `Write` access must not be borrowed elsewhere at all.
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn iter_sorted_by_key_unchecked<'data, K, KF>(
+        &self,
+        world: &'data World,
+        key_fn: KF,
+    ) -> std::vec::IntoIter<<<V as View<'data>>::Iter as Iterator>::Item>
+    where
+        K: Ord,
+        KF: FnMut(&<<V as View<'data>>::Iter as Iterator>::Item) -> K,
+    {
+        let mut items = Vec::new();
+        for mut chunk in self.iter_chunks_unchecked(world) {
+            items.extend(chunk.iter());
+        }
+        items.sort_by_cached_key(key_fn);
+        items.into_iter()
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, ordered
+    /// by the key which `key_fn` extracts from each item.
+    pub fn iter_sorted_by_key_immutable<'data, K, KF>(
+        &self,
+        world: &'data World,
+        key_fn: KF,
+    ) -> std::vec::IntoIter<<<V as View<'data>>::Iter as Iterator>::Item>
+    where
+        V: ReadOnly,
+        K: Ord,
+        KF: FnMut(&<<V as View<'data>>::Iter as Iterator>::Item) -> K,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.iter_sorted_by_key_unchecked(world, key_fn) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, ordered
+    /// by the key which `key_fn` extracts from each item, such as a depth to render in.
+    ///
+    /// Every matching entity's data is collected, and `key_fn` called once for each, before the
+    /// first item is yielded. Items with equal keys are yielded in their natural order. Only the
+    /// order in which items are visited is changed, so views may write to their components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Depth(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Depth(3),), (Depth(1),), (Depth(2),)]);
+    ///
+    /// let query = Read::<Depth>::query();
+    /// let depths = query
+    ///     .iter_sorted_by_key(&mut world, |depth| depth.0)
+    ///     .map(|depth| depth.0)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(vec![1, 2, 3], depths);
+    /// ```
+    pub fn iter_sorted_by_key<'data, K, KF>(
+        &self,
+        world: &'data mut World,
+        key_fn: KF,
+    ) -> std::vec::IntoIter<<<V as View<'data>>::Iter as Iterator>::Item>
+    where
+        K: Ord,
+        KF: FnMut(&<<V as View<'data>>::Iter as Iterator>::Item) -> K,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.iter_sorted_by_key_unchecked(world, key_fn) }
+    }
+
+    /// Gets the view data of a single entity, if it is alive and matches the query.
+    /// Does not perform static borrow checking.
+    ///
+    /// The entity's chunk is fetched as it would be while iterating, so stateful filters such
+    /// as `changed` consider the chunk to have been visited.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn get_unchecked<'data>(
+        &self,
+        world: &'data World,
+        entity: Entity,
+    ) -> Option<<<V as View<'data>>::Iter as Iterator>::Item> {
+        if !world.is_alive(entity) {
+            return None;
+        }
+
+        let location = world.entity_allocator.get_location(entity.index())?;
+        let storage = world.storage();
+        let (arch_filter, chunkset_filter, chunk_filter) = self.filter.filters();
+
+        let arch_data = arch_filter
+            .collect(ArchetypeFilterData {
+                component_types: storage.component_types(),
+                tag_types: storage.tag_types(),
+            })
+            .nth(location.archetype().as_usize())?;
+        if !arch_filter.is_match(&arch_data).is_pass() {
+            return None;
+        }
+
+        let archetype = storage.archetype(location.archetype())?;
+        let set_data = chunkset_filter
+            .collect(ChunksetFilterData {
+                archetype_data: archetype,
+            })
+            .nth(location.set().as_usize())?;
+        if !chunkset_filter.is_match(&set_data).is_pass() {
+            return None;
+        }
+
+        let chunks = archetype.chunkset(location.set())?.occupied();
+        if cfg!(any(debug_assertions, feature = "borrow-checks"))
+            && storage.borrow_conflict_policy().skips()
+            && skip_borrow_conflict::<V>(archetype, chunks.get(location.chunk().as_usize())?)
+        {
+            return None;
+        }
+        let chunk_data = chunk_filter
+            .collect(ChunkFilterData { chunks })
+            .nth(location.chunk().as_usize())?;
+        if !chunk_filter.is_match(&chunk_data).is_pass() {
+            return None;
+        }
+
+        let (set, chunk) = (location.set().as_usize(), location.chunk().as_usize());
+        Chunk::<V>::tracked(archetype, set, chunk, &self.changes)
+            .iter()
+            .nth(location.component().as_usize())
+    }
+
+    /// Gets the view data of a single entity, if it is alive and matches the query.
+    pub fn get_immutable<'data>(
+        &self,
+        world: &'data World,
+        entity: Entity,
+    ) -> Option<<<V as View<'data>>::Iter as Iterator>::Item>
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.get_unchecked(world, entity) }
+    }
+
+    /// Gets the view data of a single entity, if it is alive and matches the query.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let entities = world
+    ///     .insert((), vec![(Position(0.), Health(1.)), (Position(2.), Health(3.))])
+    ///     .to_vec();
+    ///
+    /// let query = <(Read<Position>, Write<Health>)>::query();
+    /// if let Some((pos, mut health)) = query.get(&mut world, entities[1]) {
+    ///     health.0 -= pos.0;
+    /// }
+    /// assert_eq!(Health(1.), *world.get_component::<Health>(entities[1]).unwrap());
+    /// ```
+    pub fn get<'data>(
+        &self,
+        world: &'data mut World,
+        entity: Entity,
+    ) -> Option<<<V as View<'data>>::Iter as Iterator>::Item> {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.get_unchecked(world, entity) }
+    }
+
+    /// Pairs the query with a world, so that it can be iterated with a `for` loop.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Velocity(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Position(1.), Velocity(2.))]);
+    ///
+    /// let query = <(Read<Position>, Read<Velocity>)>::query();
+    /// for (pos, vel) in query.on(&world) {
+    ///     assert_eq!(Position(1.), *pos);
+    ///     assert_eq!(Velocity(2.), *vel);
+    /// }
+    /// for (entity, (pos, _)) in query.on(&world).entities() {
+    ///     assert_eq!(Some(*pos), world.get_component::<Position>(entity).map(|p| *p));
+    /// }
+    /// ```
+    pub fn on<'a, 'data>(&'a self, world: &'data World) -> QueryRef<'a, 'data, V, F> {
+        QueryRef { query: self, world }
+    }
+
+    /// Gets the view data of the first entity which matches the query, if any.
+    pub fn first<'data>(
+        &self,
+        world: &'data mut World,
+    ) -> Option<<<V as View<'data>>::Iter as Iterator>::Item> {
+        self.iter(world).next()
+    }
+
+    /// Gets the view data of the only entity which matches the query.
+    ///
+    /// Returns an error if no entities, or more than one entity, match the query. At most two
+    /// matching entities are visited.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # use legion::query::SingleError;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Camera(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let query = Read::<Camera>::query();
+    /// assert_eq!(Err(SingleError::NoEntities), query.single(&mut world).map(|c| *c));
+    ///
+    /// world.insert((), vec![(Camera(90.),)]);
+    /// assert_eq!(Ok(Camera(90.)), query.single(&mut world).map(|c| *c));
+    ///
+    /// world.insert((), vec![(Camera(60.),)]);
+    /// assert_eq!(Err(SingleError::MultipleEntities), query.single(&mut world).map(|c| *c));
+    /// ```
+    pub fn single<'data>(
+        &self,
+        world: &'data mut World,
+    ) -> Result<<<V as View<'data>>::Iter as Iterator>::Item, SingleError> {
+        let mut iter = self.iter(world);
+        let item = iter.next().ok_or(SingleError::NoEntities)?;
+        if iter.next().is_some() {
+            return Err(SingleError::MultipleEntities);
+        }
+        Ok(item)
+    }
+
+    /// Gets the view data and ID of the only entity which matches the query.
+    ///
+    /// Returns an error if no entities, or more than one entity, match the query.
+    pub fn single_entity<'data>(
+        &self,
+        world: &'data mut World,
+    ) -> Result<(Entity, <<V as View<'data>>::Iter as Iterator>::Item), SingleError> {
+        let mut iter = self.iter_entities(world);
+        let item = iter.next().ok_or(SingleError::NoEntities)?;
+        if iter.next().is_some() {
+            return Err(SingleError::MultipleEntities);
+        }
+        Ok(item)
+    }
+
+    /// Determines if the given entity is alive and matches the query's filter.
+    ///
+    /// No component data is fetched, and stateful filters such as `changed` are evaluated
+    /// without recording that the entity's chunk has been visited.
+    pub fn matches(&self, world: &World, entity: Entity) -> bool {
+        self.peek_entity(world, entity).unwrap_or(false)
+    }
+
+    fn peek_entity(&self, world: &World, entity: Entity) -> Option<bool> {
+        let location = world.entity_location(entity)?;
+        let storage = world.storage();
+        let (arch_filter, chunkset_filter, chunk_filter) = self.filter.filters();
+
+        let arch_data = arch_filter
+            .collect(ArchetypeFilterData {
+                component_types: storage.component_types(),
+                tag_types: storage.tag_types(),
+            })
+            .nth(location.archetype().as_usize())?;
+        if !arch_filter.peek_match(&arch_data).is_pass() {
+            return Some(false);
+        }
+
+        let archetype = storage.archetype(location.archetype())?;
+        let set_data = chunkset_filter
+            .collect(ChunksetFilterData {
+                archetype_data: archetype,
+            })
+            .nth(location.set().as_usize())?;
+        if !chunkset_filter.peek_match(&set_data).is_pass() {
+            return Some(false);
+        }
+
+        let chunks = archetype.chunkset(location.set())?.occupied();
+        let chunk_data = chunk_filter
+            .collect(ChunkFilterData { chunks })
+            .nth(location.chunk().as_usize())?;
+        Some(chunk_filter.peek_match(&chunk_data).is_pass())
+    }
+
+    /// Counts the entities which match the query.
+    ///
+    /// Unlike `iter(world).count()`, no component data is borrowed or visited; only the
+    /// length of each matching chunk is read. Like `matches`, counting does not update the
+    /// state of stateful filters such as `changed`.
+    pub fn count(&self, world: &World) -> usize {
+        self.iter_chunks_peeked(world)
+            .map(|chunk| chunk.len())
+            .sum()
+    }
+
+    /// Counts the chunks which match the query, excluding chunks which contain no entities.
+    ///
+    /// No component data is borrowed, and stateful filters are not updated.
+    pub fn chunk_count(&self, world: &World) -> usize { self.iter_chunks_peeked(world).count() }
+
+    #[cfg(feature = "par-iter")]
+    /// Matches the query's archetype filter against the world's archetypes in parallel.
+    ///
+    /// Queries remember which archetypes they matched, so each execution only checks the
+    /// archetypes created since the previous one. In worlds with many archetypes, the first
+    /// execution of a query can spend much of its time in this check; calling `par_match`
+    /// beforehand performs it across the rayon thread pool instead. The query then iterates as
+    /// usual, in the same order as it would have otherwise.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// # world.insert((), (0..10).map(|i| (Position(i as f32),)));
+    /// let query = Read::<Position>::query();
+    /// let count = query.par_match(&world).iter(&mut world).count();
+    /// # assert_eq!(10, count);
+    /// ```
+    pub fn par_match<'data>(&self, world: &'data World) -> &Self
+    where
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
+    {
+        let (arch_filter, _, _) = self.filter.filters();
+        let (archetypes, storage) = (&self.archetypes, world.storage());
+        self.pool
+            .install(|| archetypes.par_update(storage, arch_filter));
+        self
+    }
+
+    /// Iterates through the chunks which match a query made up only of `Tagged` elements,
+    /// yielding the tag values of each chunk along with the number of entities in the chunk.
+    ///
+    /// Unlike `iter`, which yields the tag values once for every entity, this visits each chunk
+    /// once. No component data is borrowed.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    /// # struct Material(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((Material(1),), (0..10).map(|i| (Position(i as f32),)));
+    /// world.insert((Material(2),), (0..5).map(|i| (Position(i as f32),)));
+    ///
+    /// let query = Tagged::<Material>::query();
+    /// let mut counts = query
+    ///     .iter_per_chunk(&world)
+    ///     .map(|(material, count)| (material.0, count))
+    ///     .collect::<Vec<_>>();
+    /// counts.sort();
+    /// assert_eq!(vec![(1, 10), (2, 5)], counts);
+    /// ```
+    ///
+    /// Views which access entity data cannot be iterated per chunk:
+    ///
+    /// ```compile_fail
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    /// # struct Material(u32);
+    /// # let universe = Universe::new();
+    /// # let world = universe.create_world();
+    /// let query = <(Read<Position>, Tagged<Material>)>::query();
+    /// for _ in query.iter_per_chunk(&world) {}
+    /// ```
+    pub fn iter_per_chunk<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkTagIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    >
+    where
+        V: for<'b> TagView<'b>,
+    {
+        ChunkTagIter {
+            iter: self.iter_chunks_unfetched(world),
+        }
+    }
+
+    /// Chooses up to `k` distinct entities which match the query, uniformly at random.
+    ///
+    /// If fewer than `k` entities match the query, all of them are returned. The sampled
+    /// entities are returned in the order in which they are stored.
+    ///
+    /// No component data is borrowed, and the entities of each chunk are not iterated; the
+    /// matching chunks are visited once to find their lengths, after which each sampled index
+    /// is mapped back to its chunk.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..100).map(|i| (Position(i as f32),)));
+    ///
+    /// let sampled = Read::<Position>::query().sample(&world, &mut rand::thread_rng(), 10);
+    /// assert_eq!(10, sampled.len());
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn sample<R: Rng + ?Sized>(&self, world: &World, rng: &mut R, k: usize) -> Vec<Entity> {
+        let chunks = self
+            .iter_chunks_unfetched(world)
+            .map(|chunk| chunk.entities())
+            .collect::<Vec<_>>();
+        sample_offsets(rng, chunks.iter().map(|entities| entities.len()), k)
+            .into_iter()
+            .map(|(chunk, offset)| chunks[chunk][offset])
+            .collect()
+    }
+
+    /// Chooses up to `k` distinct entities which match the query, uniformly at random, and
+    /// gets their view data.
+    ///
+    /// See `sample`.
+    #[cfg(feature = "rand")]
+    pub fn sample_immutable<'data, R: Rng + ?Sized>(
+        &self,
+        world: &'data World,
+        rng: &mut R,
+        k: usize,
+    ) -> Vec<(Entity, <<V as View<'data>>::Iter as Iterator>::Item)>
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        let mut chunks = unsafe { self.iter_chunks_unchecked(world) }.collect::<Vec<_>>();
+        let offsets = sample_offsets(rng, chunks.iter().map(|chunk| chunk.len()), k);
+        offsets
+            .into_iter()
+            .map(|(chunk, offset)| {
+                let chunk = &mut chunks[chunk];
+                let entity = chunk.entities()[offset];
+                (entity, chunk.iter().nth(offset).unwrap())
+            })
+            .collect()
+    }
+
+    /// Collects the IDs of all entities which match the query.
+    ///
+    /// No component data is borrowed, so the world may be freely modified with the returned
+    /// IDs, e.g. to delete the matching entities.
+    pub fn collect_entities(&self, world: &World) -> Vec<Entity> {
+        self.iter_chunks_unfetched(world)
+            .flat_map(|chunk| chunk.entities().iter().copied())
+            .collect()
+    }
+
+    /// Decides what to do with each entity which matches the query, and then applies the
+    /// decisions once iteration has completed.
+    ///
+    /// Like `collect_entities`, no component data is borrowed.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # use legion::query::EntityDecision;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let entities = world.insert((), vec![(Health(0.),), (Health(1.),)]).to_vec();
+    ///
+    /// let query = Read::<Health>::query();
+    /// query.drain_entities(&mut world, |entity| {
+    ///     if entity == entities[0] {
+    ///         EntityDecision::Delete
+    ///     } else {
+    ///         EntityDecision::Keep
+    ///     }
+    /// });
+    /// assert!(!world.is_alive(entities[0]));
+    /// assert!(world.is_alive(entities[1]));
+    /// ```
+    pub fn drain_entities<T>(&self, world: &mut World, mut f: T)
+    where
+        T: FnMut(Entity) -> EntityDecision,
+    {
+        let buffer = CommandBuffer::default();
+        for entity in self.collect_entities(world) {
+            match f(entity) {
+                EntityDecision::Keep => {}
+                EntityDecision::Delete => buffer.delete(entity),
+                EntityDecision::Custom(record) => record(&buffer),
+            }
+        }
+        buffer.write(world);
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    ///
+    /// In debug builds, the iterator panics if a chunk's length or the version of one of the
+    /// view's components changes while the chunk is being iterated.
+    pub unsafe fn iter_unchecked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkDataIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    > {
+        ChunkDataIter {
+            iter: self.iter_chunks_unchecked(world),
+            frontier: None,
+            back_frontier: None,
+            #[cfg(debug_assertions)]
+            guard: IterGuard::new::<V>(),
+            _view: PhantomData,
+        }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query.
+    pub fn iter_immutable<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkDataIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    >
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.iter_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query.
+    ///
+    /// As this borrows the world mutably, no other query can access the world while the
+    /// iterator is alive, which is checked at compile time. Use `iter_immutable` to run
+    /// multiple read-only queries at once, or `iter_unchecked` to share a world between queries
+    /// whose accesses are known not to conflict. Release builds skip the runtime borrow
+    /// checks made by every path.
+    pub fn iter<'a, 'data>(
+        &'a self,
+        world: &'data mut World,
+    ) -> ChunkDataIter<
+        'data,
+        V,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    > {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.iter_unchecked(world) }
+    }
+
+    /// Iterates through all entity data that matches the query.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn for_each_entities_unchecked<'a, 'data, T>(&'a self, world: &'data World, mut f: T)
+    where
+        T: FnMut((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
+    {
+        self.iter_entities_unchecked(world).for_each(&mut f);
+    }
+
+    /// Iterates through all entity data that matches the query.
+    pub fn for_each_entities_immutable<'a, 'data, T>(&'a self, world: &'data World, f: T)
+    where
+        T: FnMut((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.for_each_entities_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query.
+    pub fn for_each_entities<'a, 'data, T>(&'a self, world: &'data mut World, f: T)
+    where
+        T: FnMut((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.for_each_entities_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn for_each_unchecked<'a, 'data, T>(&'a self, world: &'data World, mut f: T)
+    where
+        T: FnMut(<<V as View<'data>>::Iter as Iterator>::Item),
+    {
+        self.iter_unchecked(world).for_each(&mut f);
+    }
+
+    /// Iterates through all entity data that matches the query.
+    pub fn for_each_immutable<'a, 'data, T>(&'a self, world: &'data World, f: T)
+    where
+        T: FnMut(<<V as View<'data>>::Iter as Iterator>::Item),
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.for_each_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query.
+    ///
+    /// As with `iter`, the world is borrowed mutably, so conflicting access is rejected at
+    /// compile time.
+    pub fn for_each<'a, 'data, T>(&'a self, world: &'data mut World, f: T)
+    where
+        T: FnMut(<<V as View<'data>>::Iter as Iterator>::Item),
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.for_each_unchecked(world, f) };
+    }
+
+    /// Iterates through all chunks that match the query.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn for_each_chunk_unchecked<'a, 'data, T>(&'a self, world: &'data World, mut f: T)
+    where
+        T: FnMut(Chunk<'data, V>),
+    {
+        self.iter_chunks_unchecked(world).for_each(&mut f);
+    }
+
+    /// Iterates through all chunks that match the query.
+    pub fn for_each_chunk_immutable<'a, 'data, T>(&'a self, world: &'data World, f: T)
+    where
+        T: FnMut(Chunk<'data, V>),
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.for_each_chunk_unchecked(world, f) };
+    }
+
+    /// Iterates through all chunks that match the query.
+    ///
+    /// Data which is shared by all entities in a chunk, such as tags, should be looked up once
+    /// per chunk rather than once per entity:
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    /// # struct Model(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((Model(1),), (0..3).map(|i| (Position(i as f32),)));
+    /// world.insert((Model(2),), (0..3).map(|i| (Position(i as f32),)));
+    ///
+    /// let mut drawn = Vec::new();
+    /// let query = <(Read<Position>, Tagged<Model>)>::query();
+    /// query.for_each_chunk(&mut world, |chunk| {
+    ///     let model = *chunk.tag::<Model>().unwrap();
+    ///     let positions = chunk.components::<Position>().unwrap();
+    ///     drawn.push((model, positions.len()));
+    /// });
+    /// drawn.sort_by_key(|(model, _)| model.0);
+    /// assert_eq!(vec![(Model(1), 3), (Model(2), 3)], drawn);
+    /// ```
+    pub fn for_each_chunk<'a, 'data, T>(&'a self, world: &'data mut World, f: T)
+    where
+        T: FnMut(Chunk<'data, V>),
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.for_each_chunk_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in batches of exactly `N` entities.
+    /// Entities left over at the end of each chunk are passed individually to `remainder`.
+    /// Batches never span multiple chunks.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components,
+    /// or if `N` is 0.
+    pub unsafe fn for_each_batched_unchecked<'a, 'data, const N: usize, T, R>(
+        &'a self,
+        world: &'data World,
+        mut full: T,
+        mut remainder: R,
+    ) where
+        V: BatchView<'data, N>,
+        T: FnMut(<<V as BatchView<'data, N>>::Batches as Iterator>::Item),
+        R: FnMut(<<V as BatchView<'data, N>>::Remainder as Iterator>::Item),
+    {
+        assert!(N > 0, "batch size must be greater than 0");
+        for chunk in self.iter_chunks_unchecked(world) {
+            let (_borrow, batches, rest) = V::fetch_batches(chunk.components);
+            batches.for_each(&mut full);
+            rest.for_each(&mut remainder);
+        }
+    }
+
+    /// Iterates through all entity data that matches the query in batches of exactly `N` entities.
+    /// Entities left over at the end of each chunk are passed individually to `remainder`.
+    /// Batches never span multiple chunks.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `N` is 0.
+    pub fn for_each_batched_immutable<'a, 'data, const N: usize, T, R>(
+        &'a self,
+        world: &'data World,
+        full: T,
+        remainder: R,
+    ) where
+        V: BatchView<'data, N> + ReadOnly,
+        T: FnMut(<<V as BatchView<'data, N>>::Batches as Iterator>::Item),
+        R: FnMut(<<V as BatchView<'data, N>>::Remainder as Iterator>::Item),
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.for_each_batched_unchecked::<N, T, R>(world, full, remainder) };
+    }
+
+    /// Iterates through all entity data that matches the query in batches of exactly `N` entities.
+    /// Entities left over at the end of each chunk are passed individually to `remainder`.
+    /// Batches never span multiple chunks.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `N` is 0.
+    pub fn for_each_batched<'a, 'data, const N: usize, T, R>(
+        &'a self,
+        world: &'data mut World,
+        full: T,
+        remainder: R,
+    ) where
+        V: BatchView<'data, N>,
+        T: FnMut(<<V as BatchView<'data, N>>::Batches as Iterator>::Item),
+        R: FnMut(<<V as BatchView<'data, N>>::Remainder as Iterator>::Item),
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.for_each_batched_unchecked::<N, T, R>(world, full, remainder) };
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query in parallel.
+    /// Does not perform static borrow checking.
+    ///
+    /// As with `iter_chunks_unchecked`, chunks which contain no entities are skipped.
+    ///
+    /// Unlike the `ChunkViewScoped` chunks passed to `par_for_each_chunk`, the yielded chunks
+    /// are not confined to the task which receives them, and may be collected or handed to other
+    /// tasks. Each fetch from a chunk, such as `Chunk::iter`, borrows the chunk's components
+    /// until its result is dropped, so fetching a chunk while another fetch of it is alive
+    /// conflicts; this is only detected when runtime borrow checks are enabled. See
+    /// `borrow::BorrowConflictPolicy`. Prefer `par_for_each_chunk` when chunks need not leave
+    /// their task.
+    ///
+    /// Without the `par-iter` feature, the returned iterator is an ordinary sequential
+    /// `Iterator`.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn par_iter_chunks_unchecked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewParIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    where
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+    {
+        let (arch_filter, chunkset_filter, chunk_filter) = self.filter.filters();
+        let storage = world.storage();
+        let archetypes = FissileEnumerate::new(arch_filter.collect(ArchetypeFilterData {
+            component_types: storage.component_types(),
+            tag_types: storage.tag_types(),
+        }));
+        ChunkViewParIter {
+            storage,
+            arch_filter,
+            chunkset_filter,
+            chunk_filter,
+            changes: &self.changes,
+            #[cfg(feature = "par-iter")]
+            pool: &self.pool,
+            skip_conflicts: cfg!(any(debug_assertions, feature = "borrow-checks"))
+                && storage.borrow_conflict_policy().skips(),
+            archetypes,
+            set_frontier: None,
+            chunk_frontier: None,
+            _view: PhantomData,
+        }
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query in parallel.
+    pub fn par_iter_chunks_immutable<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> ChunkViewParIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    where
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_iter_chunks_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query in parallel.
+    pub fn par_iter_chunks<'a, 'data>(
+        &'a self,
+        world: &'data mut World,
+    ) -> ChunkViewParIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    where
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_iter_chunks_unchecked(world) }
+    }
+
+    #[cfg(feature = "par-iter")]
+    /// Gets an indexed parallel iterator of all chunks that match the query, which can be
+    /// `zip`ped with other indexed iterators or `collect`ed in order. Does not perform static
+    /// borrow checking.
+    ///
+    /// The matching chunks are found up front and collected into a `Vec` before any work is
+    /// split, which costs one small allocation of a pointer-sized handle per chunk. Prefer
+    /// `par_iter_chunks_unchecked` when indexing is not needed.
+    ///
+    /// As with `par_iter_chunks_unchecked`, the yielded chunks may escape their task, and
+    /// conflicting fetches of the same chunk are only detected by runtime borrow checks.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn par_iter_chunks_indexed_unchecked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> rayon::vec::IntoIter<Chunk<'data, V>>
+    where
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+    {
+        // the chunks are found sequentially, so stateful filters see them in iteration order
+        Iterator::collect::<Vec<_>>(self.par_iter_chunks_unchecked(world)).into_par_iter()
+    }
+
+    #[cfg(feature = "par-iter")]
+    /// Gets an indexed parallel iterator of all chunks that match the query.
+    ///
+    /// See `par_iter_chunks_indexed_unchecked` for the cost of indexing.
+    pub fn par_iter_chunks_indexed_immutable<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> rayon::vec::IntoIter<Chunk<'data, V>>
+    where
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_iter_chunks_indexed_unchecked(world) }
+    }
+
+    #[cfg(feature = "par-iter")]
+    /// Gets an indexed parallel iterator of all chunks that match the query.
+    ///
+    /// See `par_iter_chunks_indexed_unchecked` for the cost of indexing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # use rayon::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..3000).map(|i| (Health(i),)));
+    ///
+    /// let query = Read::<Health>::query();
+    /// let mut totals = vec![0; query.chunk_count(&world)];
+    /// query
+    ///     .par_iter_chunks_indexed(&mut world)
+    ///     .zip(totals.par_iter_mut())
+    ///     .for_each(|(mut chunk, total)| *total = chunk.iter().map(|h| h.0).sum());
+    /// assert_eq!((0..3000).sum::<u32>(), totals.iter().sum());
+    /// ```
+    pub fn par_iter_chunks_indexed<'a, 'data>(
+        &'a self,
+        world: &'data mut World,
+    ) -> rayon::vec::IntoIter<Chunk<'data, V>>
+    where
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_iter_chunks_indexed_unchecked(world) }
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, which supports the
+    /// full set of rayon combinators. Does not perform static borrow checking.
+    ///
+    /// Work is split between chunks; each chunk's items are yielded by a single task, which
+    /// holds the chunk's component borrows for as long as it runs. See
+    /// `par_iter_split_unchecked` to also split the entities of each chunk between tasks.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_iter_unchecked<'a>(
+        &'a self,
+        world: &'a World,
+    ) -> impl ParallelIterator<Item = <<V as View<'a>>::Iter as Iterator>::Item> + 'a
+    where
+        <<V as View<'a>>::Iter as Iterator>::Item: Send,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        self.par_iter_chunks_unchecked(world)
+            .flat_map_iter(|mut chunk| chunk.iter())
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, which supports the
+    /// full set of rayon combinators.
+    #[cfg(feature = "par-iter")]
+    pub fn par_iter_immutable<'a>(
+        &'a self,
+        world: &'a World,
+    ) -> impl ParallelIterator<Item = <<V as View<'a>>::Iter as Iterator>::Item> + 'a
+    where
+        <<V as View<'a>>::Iter as Iterator>::Item: Send,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_iter_unchecked(world) }
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, which supports the
+    /// full set of rayon combinators.
+    ///
+    /// Work is split between chunks; each chunk's items are yielded by a single task, so
+    /// `Write` views never hand out the same component to two tasks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # use rayon::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..1000).map(|i| (Health(i),)));
+    ///
+    /// let query = Read::<Health>::query();
+    /// let total: u32 = query.par_iter(&mut world).map(|health| health.0).sum();
+    /// assert_eq!((0..1000).sum::<u32>(), total);
+    /// ```
+    #[cfg(feature = "par-iter")]
+    pub fn par_iter<'a>(
+        &'a self,
+        world: &'a mut World,
+    ) -> impl ParallelIterator<Item = <<V as View<'a>>::Iter as Iterator>::Item> + 'a
+    where
+        <<V as View<'a>>::Iter as Iterator>::Item: Send,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_iter_unchecked(world) }
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, which splits the
+    /// entities of each chunk between tasks as well as the chunks themselves. Does not perform
+    /// static borrow checking.
+    ///
+    /// Unlike `par_iter_unchecked`, which yields each chunk's items from a single task, this
+    /// keeps every thread busy even when only a few large chunks match. Only views implementing
+    /// `SplitView` (`Read`, `Write` and tuples of them) are supported. The components of every
+    /// matching chunk are borrowed when the iterator is created, and are released once it has
+    /// been driven.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_iter_split_unchecked<'a>(&'a self, world: &'a World) -> SplitParIter<'a, V>
+    where
+        V: SplitView<'a>,
+    {
+        // every chunk is borrowed once, up front, by this thread; tasks only receive the
+        // disjoint ranges split from those borrows
+        let mut borrows = Vec::new();
+        let mut chunks = Vec::new();
+        for chunk in self.iter_chunks_unchecked(world) {
+            let (borrow, parts) = V::fetch_parts(chunk.components);
+            borrows.push(borrow);
+            chunks.push((parts, chunk.len()));
+        }
+
+        SplitParIter {
+            borrows,
+            chunks,
+            pool: &self.pool,
+        }
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, which splits the
+    /// entities of each chunk between tasks. See `par_iter_split_unchecked`.
+    #[cfg(feature = "par-iter")]
+    pub fn par_iter_split_immutable<'a>(&'a self, world: &'a World) -> SplitParIter<'a, V>
+    where
+        V: SplitView<'a> + ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_iter_split_unchecked(world) }
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, which splits the
+    /// entities of each chunk between tasks. See `par_iter_split_unchecked`.
+    ///
+    /// Splitting a chunk splits each of its component slices at the same entity, so `Write`
+    /// views never hand out the same component to two tasks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # use rayon::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..1000).map(|i| (Health(i),)));
+    ///
+    /// let query = Write::<Health>::query();
+    /// query.par_iter_split(&mut world).for_each(|health| health.0 += 1);
+    /// let total: u32 = query.par_iter_split(&mut world).map(|health| health.0).sum();
+    /// assert_eq!((1..=1000).sum::<u32>(), total);
+    /// ```
+    #[cfg(feature = "par-iter")]
+    pub fn par_iter_split<'a>(&'a self, world: &'a mut World) -> SplitParIter<'a, V>
+    where
+        V: SplitView<'a>,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_iter_split_unchecked(world) }
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, along with the ID of
+    /// each entity. Does not perform static borrow checking.
+    ///
+    /// Work is split between chunks; each chunk's entities and items are yielded by a single task.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_iter_entities_unchecked<'a>(
+        &'a self,
+        world: &'a World,
+    ) -> impl ParallelIterator<Item = (Entity, <<V as View<'a>>::Iter as Iterator>::Item)> + 'a
+    where
+        <<V as View<'a>>::Iter as Iterator>::Item: Send,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        self.par_iter_chunks_unchecked(world)
+            .flat_map_iter(|mut chunk| chunk.iter_entities())
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, along with the ID of
+    /// each entity.
+    #[cfg(feature = "par-iter")]
+    pub fn par_iter_entities_immutable<'a>(
+        &'a self,
+        world: &'a World,
+    ) -> impl ParallelIterator<Item = (Entity, <<V as View<'a>>::Iter as Iterator>::Item)> + 'a
+    where
+        <<V as View<'a>>::Iter as Iterator>::Item: Send,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_iter_entities_unchecked(world) }
+    }
+
+    /// Gets a parallel iterator of all entity data that matches the query, along with the ID of
+    /// each entity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # use rayon::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..1000).map(|i| (Health(i),)));
+    ///
+    /// let query = Read::<Health>::query();
+    /// let dead = query
+    ///     .par_iter_entities(&mut world)
+    ///     .filter(|(_, health)| health.0 == 0)
+    ///     .map(|(entity, _)| entity)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(1, dead.len());
+    /// ```
+    #[cfg(feature = "par-iter")]
+    pub fn par_iter_entities<'a>(
+        &'a self,
+        world: &'a mut World,
+    ) -> impl ParallelIterator<Item = (Entity, <<V as View<'a>>::Iter as Iterator>::Item)> + 'a
+    where
+        <<V as View<'a>>::Iter as Iterator>::Item: Send,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_iter_entities_unchecked(world) }
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, along with the ID
+    /// of each entity. Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_for_each_entities_unchecked<'a, T>(&'a self, world: &'a World, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        // each chunk task zips the chunk's entities with its components, stopping at the
+        // shorter of the two as `iter_entities` does
+        let par_iter = self.par_iter_chunks_unchecked(world);
+        ParallelIterator::for_each(par_iter, |mut chunk| {
+            chunk.iter_entities().for_each(&f);
+        });
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, along with the ID
+    /// of each entity.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_entities_immutable<'a, T>(&'a self, world: &'a World, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_for_each_entities_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, along with the ID
+    /// of each entity.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_entities<'a, T>(&'a self, world: &'a mut World, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_for_each_entities_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in parallel.
+    /// Does not perform static borrow checking.
+    ///
+    /// Equivalent to `par_for_each_entities_unchecked`.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    #[deprecated(note = "renamed to `par_for_each_entities_unchecked`")]
+    pub unsafe fn par_entities_for_each_unchecked<'a, T>(&'a self, world: &'a World, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        self.par_for_each_entities_unchecked(world, f);
+    }
+
+    /// Iterates through all entity data that matches the query in parallel.
+    ///
+    /// Equivalent to `par_for_each_entities_immutable`.
+    #[cfg(feature = "par-iter")]
+    #[deprecated(note = "renamed to `par_for_each_entities_immutable`")]
+    pub fn par_entities_for_each_immutable<'a, T>(&'a self, world: &'a World, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        self.par_for_each_entities_immutable(world, f);
+    }
+
+    /// Iterates through all entity data that matches the query in parallel.
+    ///
+    /// Equivalent to `par_for_each_entities`.
+    #[cfg(feature = "par-iter")]
+    #[deprecated(note = "renamed to `par_for_each_entities`")]
+    pub fn par_entities_for_each<'a, T>(&'a self, world: &'a mut World, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        self.par_for_each_entities(world, f);
+    }
+
+    /// Iterates through all entity data that matches the query in parallel.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    ///
+    /// Without the `par-iter` feature, the entities are visited sequentially on the calling
+    /// thread.
+    pub unsafe fn par_for_each_unchecked<'a, T>(&'a self, world: &'a World, f: T)
+    where
+        T: Fn(<<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        let par_iter = self.par_iter_chunks_unchecked(world);
+        #[cfg(feature = "par-iter")]
+        ParallelIterator::for_each(par_iter, |mut chunk| {
+            for data in chunk.iter() {
+                f(data);
+            }
+        });
+        #[cfg(not(feature = "par-iter"))]
+        Iterator::for_each(par_iter, |mut chunk| {
+            for data in chunk.iter() {
+                f(data);
+            }
+        });
+    }
+
+    /// Iterates through all entity data that matches the query in parallel.
+    pub fn par_for_each_immutable<'a, T>(&'a self, world: &'a World, f: T)
+    where
+        T: Fn(<<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_for_each_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in parallel.
+    pub fn par_for_each<'a, T>(&'a self, world: &'a mut World, f: T)
+    where
+        T: Fn(<<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_for_each_unchecked(world, f) };
+    }
+
+    /// Folds all entity data that matches the query into a single value in parallel.
+    /// Does not perform static borrow checking.
+    ///
+    /// Each rayon task folds the items of the chunks it visits into its own accumulator, seeded
+    /// by `identity`, and the accumulators are then combined pairwise by `reduce`. This mirrors
+    /// `ParallelIterator::fold` followed by `ParallelIterator::reduce`, so `identity` may be
+    /// called any number of times and `reduce` must be associative.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_fold_unchecked<'a, A, I, T, R>(
+        &'a self,
+        world: &'a World,
+        identity: I,
+        fold: T,
+        reduce: R,
+    ) -> A
+    where
+        A: Send,
+        I: Fn() -> A + Send + Sync,
+        T: Fn(A, <<V as View<'a>>::Iter as Iterator>::Item) -> A + Send + Sync,
+        R: Fn(A, A) -> A + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        let par_iter = self.par_iter_chunks_unchecked(world);
+        ParallelIterator::fold(par_iter, &identity, |acc, mut chunk| {
+            chunk.iter().fold(acc, &fold)
+        })
+        .reduce(&identity, &reduce)
+    }
+
+    /// Folds all entity data that matches the query into a single value in parallel.
+    /// See `par_fold`.
+    #[cfg(feature = "par-iter")]
+    pub fn par_fold_immutable<'a, A, I, T, R>(
+        &'a self,
+        world: &'a World,
+        identity: I,
+        fold: T,
+        reduce: R,
+    ) -> A
+    where
+        A: Send,
+        I: Fn() -> A + Send + Sync,
+        T: Fn(A, <<V as View<'a>>::Iter as Iterator>::Item) -> A + Send + Sync,
+        R: Fn(A, A) -> A + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_fold_unchecked(world, identity, fold, reduce) }
+    }
+
+    /// Folds all entity data that matches the query into a single value in parallel, without
+    /// sharing an accumulator between threads.
+    ///
+    /// Each rayon task folds the items of the chunks it visits into its own accumulator, seeded
+    /// by `identity`, and the accumulators are then combined pairwise by `reduce`. This mirrors
+    /// `ParallelIterator::fold` followed by `ParallelIterator::reduce`, so `identity` may be
+    /// called any number of times and `reduce` must be associative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..1000).map(|i| (Health(i),)));
+    ///
+    /// let query = Read::<Health>::query();
+    /// let max = query.par_fold(
+    ///     &mut world,
+    ///     || 0,
+    ///     |max, health| max.max(health.0),
+    ///     |a, b| a.max(b),
+    /// );
+    /// assert_eq!(999, max);
+    /// ```
+    #[cfg(feature = "par-iter")]
+    pub fn par_fold<'a, A, I, T, R>(
+        &'a self,
+        world: &'a mut World,
+        identity: I,
+        fold: T,
+        reduce: R,
+    ) -> A
     where
-        F: std::ops::BitAnd<T>,
-        <F as std::ops::BitAnd<T>>::Output: EntityFilter,
+        A: Send,
+        I: Fn() -> A + Send + Sync,
+        T: Fn(A, <<V as View<'a>>::Iter as Iterator>::Item) -> A + Send + Sync,
+        R: Fn(A, A) -> A + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
     {
-        Query {
-            view: self.view,
-            filter: self.filter & filter,
-        }
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_fold_unchecked(world, identity, fold, reduce) }
     }
 
-    /// Gets an iterator which iterates through all chunks that match the query.
+    /// Iterates through all entity data that matches the query in parallel, along with the ID of
+    /// each entity, in tasks of at most `batch_size` entities.
     /// Does not perform static borrow checking.
     ///
+    /// See `par_for_each_batched`.
+    ///
     /// # Safety
     ///
     /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
     ///
     /// # Panics
     ///
-    /// This function may panic if other code is concurrently accessing the same components.
-    pub unsafe fn iter_chunks_unchecked<'a, 'data>(
+    /// This function may panic if other code is concurrently accessing the same components,
+    /// or if `batch_size` is 0.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_for_each_entities_batched_unchecked<'a, T>(
         &'a self,
-        world: &'data World,
-    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
-        let (arch_filter, chunkset_filter, chunk_filter) = self.filter.filters();
-        let storage = world.storage();
-        let archetypes = arch_filter
-            .collect(ArchetypeFilterData {
-                component_types: storage.component_types(),
-                tag_types: storage.tag_types(),
-            })
-            .enumerate();
-        ChunkViewIter {
-            storage,
-            arch_filter,
-            chunkset_filter,
-            chunk_filter,
-            archetypes,
-            set_frontier: None,
-            chunk_frontier: None,
-            _view: PhantomData,
+        world: &'a World,
+        batch_size: usize,
+        f: T,
+    ) where
+        V: SplitView<'a>,
+        T: Fn((Entity, <<V as SplitView<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+    {
+        assert!(batch_size > 0, "batch size must be greater than 0");
+
+        // every chunk is borrowed once, up front, by this thread; tasks only receive the
+        // disjoint ranges split from those borrows, so tasks which share a chunk never contend
+        // for its runtime borrows
+        let mut borrows = Vec::new();
+        let mut tasks = vec![Vec::new()];
+        let mut task_len = 0;
+        for chunk in self.iter_chunks_unchecked(world) {
+            let (borrow, mut parts) = V::fetch_parts(chunk.components);
+            borrows.push(borrow);
+
+            let mut entities = chunk.entities();
+            while !entities.is_empty() {
+                if task_len == batch_size {
+                    tasks.push(Vec::new());
+                    task_len = 0;
+                }
+
+                // split large chunks, and pack the remainder together with the following chunks
+                let mid = entities.len().min(batch_size - task_len);
+                let (head, tail) = entities.split_at(mid);
+                let (head_parts, tail_parts) = V::split_parts(parts, mid);
+                tasks.last_mut().unwrap().push((head, head_parts));
+                task_len += mid;
+                entities = tail;
+                parts = tail_parts;
+            }
         }
+
+        self.pool.install(|| {
+            tasks.into_par_iter().for_each(|task| {
+                for (entities, parts) in task {
+                    entities
+                        .iter()
+                        .copied()
+                        .zip(V::iter_parts(parts))
+                        .for_each(&f);
+                }
+            })
+        });
     }
 
-    /// Gets an iterator which iterates through all chunks that match the query.
-    pub fn iter_chunks_immutable<'a, 'data>(
+    /// Iterates through all entity data that matches the query in parallel, along with the ID of
+    /// each entity, in tasks of at most `batch_size` entities. See `par_for_each_batched`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `batch_size` is 0.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_entities_batched<'a, T>(
         &'a self,
-        world: &'data World,
-    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
-    where
-        V: ReadOnly,
+        world: &'a mut World,
+        batch_size: usize,
+        f: T,
+    ) where
+        V: SplitView<'a>,
+        T: Fn((Entity, <<V as SplitView<'a>>::Iter as Iterator>::Item)) + Send + Sync,
     {
-        // safe because the view can only read data immutably
-        unsafe { self.iter_chunks_unchecked(world) }
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_for_each_entities_batched_unchecked(world, batch_size, f) };
     }
 
-    /// Gets an iterator which iterates through all chunks that match the query.
-    pub fn iter_chunks<'a, 'data>(
+    /// Iterates through all entity data that matches the query in parallel, in tasks of at most
+    /// `batch_size` entities. Does not perform static borrow checking.
+    ///
+    /// See `par_for_each_batched`.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components,
+    /// or if `batch_size` is 0.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_for_each_batched_unchecked<'a, T>(
         &'a self,
-        world: &'data mut World,
-    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+        world: &'a World,
+        batch_size: usize,
+        f: T,
+    ) where
+        V: SplitView<'a>,
+        T: Fn(<<V as SplitView<'a>>::Iter as Iterator>::Item) + Send + Sync,
+    {
+        self.par_for_each_entities_batched_unchecked(world, batch_size, |(_, item)| f(item));
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, in tasks of at most
+    /// `batch_size` entities. See `par_for_each_batched`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `batch_size` is 0.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_batched_immutable<'a, T>(
+        &'a self,
+        world: &'a World,
+        batch_size: usize,
+        f: T,
+    ) where
+        V: SplitView<'a> + ReadOnly,
+        T: Fn(<<V as SplitView<'a>>::Iter as Iterator>::Item) + Send + Sync,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_for_each_batched_unchecked(world, batch_size, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, in tasks of at most
+    /// `batch_size` entities.
+    ///
+    /// Unlike `par_for_each`, which creates one task per chunk, work is divided by entity count:
+    /// chunks larger than `batch_size` are split between several tasks, and small chunks are
+    /// packed together into a single task. This keeps all threads busy when a few large chunks
+    /// hold most entities, and avoids scheduling overhead when there are many small chunks.
+    ///
+    /// Only views implementing `SplitView` (`Read`, `Write` and tuples of them) are supported,
+    /// and items are plain references rather than runtime borrow checked guards. Each chunk is
+    /// borrowed once for the whole call, rather than once per task.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `batch_size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Velocity(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..10_000).map(|_| (Position(0.0), Velocity(1.0))));
+    ///
+    /// let query = <(Write<Position>, Read<Velocity>)>::query();
+    /// query.par_for_each_batched(&mut world, 256, |(pos, vel)| {
+    ///     pos.0 += vel.0;
+    /// });
+    /// ```
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_batched<'a, T>(&'a self, world: &'a mut World, batch_size: usize, f: T)
+    where
+        V: SplitView<'a>,
+        T: Fn(<<V as SplitView<'a>>::Iter as Iterator>::Item) + Send + Sync,
+    {
         // safe because the &mut World ensures exclusivity
-        unsafe { self.iter_chunks_unchecked(world) }
+        unsafe { self.par_for_each_batched_unchecked(world, batch_size, f) };
     }
 
-    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
+    /// Iterates through all chunks that match the query in parallel.
     /// Does not perform static borrow checking.
     ///
     /// # Safety
@@ -950,53 +5116,53 @@ where
     /// # Panics
     ///
     /// This function may panic if other code is concurrently accessing the same components.
-    pub unsafe fn iter_entities_unchecked<'a, 'data>(
-        &'a self,
-        world: &'data World,
-    ) -> ChunkEntityIter<
-        'data,
-        V,
-        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
-    > {
-        ChunkEntityIter {
-            iter: self.iter_chunks_unchecked(world),
-            frontier: None,
-            _view: PhantomData,
-        }
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_for_each_chunk_unchecked<'a, T>(&'a self, world: &'a World, f: T)
+    where
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        let par_iter = self.par_iter_chunks_unchecked(world);
+        ParallelIterator::for_each(par_iter, |chunk| {
+            f(ChunkViewScoped::new(chunk));
+        });
     }
 
-    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
-    pub fn iter_entities_immutable<'a, 'data>(
-        &'a self,
-        world: &'data World,
-    ) -> ChunkEntityIter<
-        'data,
-        V,
-        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
-    >
+    /// Iterates through all chunks that match the query in parallel.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_chunk_immutable<'a, T>(&'a self, world: &'a World, f: T)
     where
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
         V: ReadOnly,
     {
         // safe because the view can only read data immutably
-        unsafe { self.iter_entities_unchecked(world) }
+        unsafe { self.par_for_each_chunk_unchecked(world, f) };
     }
 
-    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
-    pub fn iter_entities<'a, 'data>(
-        &'a self,
-        world: &'data mut World,
-    ) -> ChunkEntityIter<
-        'data,
-        V,
-        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
-    > {
+    /// Iterates through all chunks that match the query in parallel.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_chunk<'a, T>(&'a self, world: &'a mut World, f: T)
+    where
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
         // safe because the &mut World ensures exclusivity
-        unsafe { self.iter_entities_unchecked(world) }
+        unsafe { self.par_for_each_chunk_unchecked(world, f) };
     }
 
-    /// Gets an iterator which iterates through all entity data that matches the query.
+    /// Iterates through all chunks that match the query in parallel, processing each chunk on
+    /// the group of threads in `pool` which matches its placement hint.
     /// Does not perform static borrow checking.
     ///
+    /// See the `placement` module.
+    ///
     /// # Safety
     ///
     /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
@@ -1004,87 +5170,239 @@ where
     /// # Panics
     ///
     /// This function may panic if other code is concurrently accessing the same components.
-    pub unsafe fn iter_unchecked<'a, 'data>(
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_for_each_chunk_placed_unchecked<'a, T>(
         &'a self,
-        world: &'data World,
-    ) -> ChunkDataIter<
-        'data,
-        V,
-        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
-    > {
-        ChunkDataIter {
-            iter: self.iter_chunks_unchecked(world),
-            frontier: None,
-            _view: PhantomData,
-        }
+        world: &'a World,
+        pool: &PlacementPool,
+        f: T,
+    ) where
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
+    {
+        let chunks = self
+            .iter_chunks_unchecked(world)
+            .map(|chunk| (chunk.placement(), chunk));
+        pool.run(chunks, |chunk| f(ChunkViewScoped::new(chunk)));
     }
 
-    /// Gets an iterator which iterates through all entity data that matches the query.
-    pub fn iter_immutable<'a, 'data>(
+    /// Iterates through all chunks that match the query in parallel, processing each chunk on
+    /// the group of threads in `pool` which matches its placement hint.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_chunk_placed<'a, T>(
         &'a self,
-        world: &'data World,
-    ) -> ChunkDataIter<
-        'data,
-        V,
-        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
-    >
+        world: &'a mut World,
+        pool: &PlacementPool,
+        f: T,
+    ) where
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_for_each_chunk_placed_unchecked(world, pool, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, processing each
+    /// chunk on the group of threads in `pool` which matches its placement hint.
+    ///
+    /// See the `placement` module.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_placed<'a, T>(&'a self, world: &'a mut World, pool: &PlacementPool, f: T)
     where
-        V: ReadOnly,
+        T: Fn(<<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
     {
-        // safe because the view can only read data immutably
-        unsafe { self.iter_unchecked(world) }
+        let chunks = self
+            .iter_chunks(world)
+            .map(|chunk| (chunk.placement(), chunk));
+        pool.run(chunks, |mut chunk| chunk.iter().for_each(&f));
     }
+}
 
-    /// Gets an iterator which iterates through all entity data that matches the query.
-    pub fn iter<'a, 'data>(
-        &'a self,
-        world: &'data mut World,
-    ) -> ChunkDataIter<
-        'data,
-        V,
-        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
-    > {
-        // safe because the &mut World ensures exclusivity
-        unsafe { self.iter_unchecked(world) }
+/// Transforms or discards the items yielded for each entity by an `AdaptedQuery`.
+///
+/// See `Query::map` and `Query::filter_entities`.
+pub trait QueryPipe<'a, In>: Send + Sync {
+    /// The item produced by the pipe.
+    type Output;
+
+    /// Applies the pipe to the item of the given entity, returning `None` if the entity
+    /// should be skipped.
+    fn apply(&self, entity: Entity, item: In) -> Option<Self::Output>;
+}
+
+/// A pipe which passes each item through unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Identity;
+
+impl<'a, In> QueryPipe<'a, In> for Identity {
+    type Output = In;
+
+    #[inline]
+    fn apply(&self, _: Entity, item: In) -> Option<Self::Output> { Some(item) }
+}
+
+/// A pipe which transforms the output of an inner pipe with a function.
+#[derive(Debug, Clone)]
+pub struct MapPipe<P, M> {
+    inner: P,
+    map: M,
+}
+
+impl<'a, In, P, M, R> QueryPipe<'a, In> for MapPipe<P, M>
+where
+    P: QueryPipe<'a, In>,
+    M: Fn(P::Output) -> R + Send + Sync,
+{
+    type Output = R;
+
+    #[inline]
+    fn apply(&self, entity: Entity, item: In) -> Option<Self::Output> {
+        self.inner.apply(entity, item).map(&self.map)
+    }
+}
+
+/// A pipe which discards the outputs of an inner pipe for which a predicate returns `false`.
+#[derive(Debug, Clone)]
+pub struct FilterPipe<P, Pr> {
+    inner: P,
+    predicate: Pr,
+}
+
+impl<'a, In, P, Pr> QueryPipe<'a, In> for FilterPipe<P, Pr>
+where
+    P: QueryPipe<'a, In>,
+    Pr: Fn(Entity, &P::Output) -> bool + Send + Sync,
+{
+    type Output = P::Output;
+
+    #[inline]
+    fn apply(&self, entity: Entity, item: In) -> Option<Self::Output> {
+        self.inner
+            .apply(entity, item)
+            .filter(|item| (self.predicate)(entity, item))
     }
+}
+
+/// The item yielded by a pipe applied to the items of view `V`.
+pub type PipeOutput<'a, V, P> =
+    <P as QueryPipe<'a, <<V as View<'a>>::Iter as Iterator>::Item>>::Output;
+
+/// A query whose items are transformed or filtered per entity by a `QueryPipe`.
+///
+/// Created by `Query::map` and `Query::filter_entities`. The adapted query selects and
+/// borrows chunks exactly as the underlying query does; the pipe is applied lazily to each
+/// entity as it is iterated.
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// # world.insert((), vec![(Position(1.0), Velocity(2.0))]);
+/// let query = <(Read<Position>, Read<Velocity>)>::query()
+///     .map(|(pos, vel)| pos.0 + vel.0)
+///     .filter_entities(|_, next| *next > 0.0);
+///
+/// for next in query.iter(&mut world) {
+///     assert_eq!(3.0, next);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AdaptedQuery<V: for<'a> View<'a>, F: EntityFilter, P> {
+    query: Query<V, F>,
+    pipe: P,
+}
+
+/// An iterator which applies a `QueryPipe` to the items of an entity iterator.
+pub struct PipeIter<'a, 'p, I, P> {
+    iter: I,
+    pipe: &'p P,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a, 'p, I, In, P> Iterator for PipeIter<'a, 'p, I, P>
+where
+    I: Iterator<Item = (Entity, In)>,
+    P: QueryPipe<'a, In>,
+{
+    type Item = (Entity, P::Output);
 
-    /// Iterates through all entity data that matches the query.
-    /// Does not perform static borrow checking.
-    ///
-    /// # Safety
-    ///
-    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
-    ///
-    /// # Panics
-    ///
-    /// This function may panic if other code is concurrently accessing the same components.
-    pub unsafe fn for_each_entities_unchecked<'a, 'data, T>(&'a self, world: &'data World, mut f: T)
-    where
-        T: Fn((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
-    {
-        self.iter_entities_unchecked(world).for_each(&mut f);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let pipe = self.pipe;
+        self.iter
+            .by_ref()
+            .filter_map(|(entity, item)| pipe.apply(entity, item).map(|item| (entity, item)))
+            .next()
     }
+}
 
-    /// Iterates through all entity data that matches the query.
-    pub fn for_each_entities_immutable<'a, 'data, T>(&'a self, world: &'data World, f: T)
+impl<'a, 'p, I, In, P> FusedIterator for PipeIter<'a, 'p, I, P>
+where
+    I: FusedIterator<Item = (Entity, In)>,
+    P: QueryPipe<'a, In>,
+{
+}
+
+type AdaptedEntityIter<'a, 'data, V, F, P> = PipeIter<
+    'data,
+    'a,
+    ChunkEntityIter<
+        'data,
+        V,
+        ChunkViewIter<
+            'data,
+            'a,
+            V,
+            <F as EntityFilter>::ArchetypeFilter,
+            <F as EntityFilter>::ChunksetFilter,
+            <F as EntityFilter>::ChunkFilter,
+        >,
+    >,
+    P,
+>;
+
+impl<V, F, P> AdaptedQuery<V, F, P>
+where
+    V: for<'a> View<'a>,
+    F: EntityFilter,
+    P: for<'a> QueryPipe<'a, <<V as View<'a>>::Iter as Iterator>::Item>,
+{
+    /// Gets the underlying query, which describes the chunks and component access of the
+    /// adapted query.
+    pub fn query(&self) -> &Query<V, F> { &self.query }
+
+    /// Transforms each item of the query with the given function.
+    pub fn map<M, R>(self, map: M) -> AdaptedQuery<V, F, MapPipe<P, M>>
     where
-        T: Fn((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
-        V: ReadOnly,
+        M: for<'a> Fn(PipeOutput<'a, V, P>) -> R + Send + Sync,
     {
-        // safe because the view can only read data immutably
-        unsafe { self.for_each_entities_unchecked(world, f) };
+        AdaptedQuery {
+            query: self.query,
+            pipe: MapPipe {
+                inner: self.pipe,
+                map,
+            },
+        }
     }
 
-    /// Iterates through all entity data that matches the query.
-    pub fn for_each_entities<'a, 'data, T>(&'a self, world: &'data mut World, f: T)
+    /// Skips entities for which the given predicate returns `false`.
+    pub fn filter_entities<Pr>(self, predicate: Pr) -> AdaptedQuery<V, F, FilterPipe<P, Pr>>
     where
-        T: Fn((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
+        Pr: for<'a, 'b> Fn(Entity, &'b PipeOutput<'a, V, P>) -> bool + Send + Sync,
     {
-        // safe because the &mut World ensures exclusivity
-        unsafe { self.for_each_entities_unchecked(world, f) };
+        AdaptedQuery {
+            query: self.query,
+            pipe: FilterPipe {
+                inner: self.pipe,
+                predicate,
+            },
+        }
     }
 
-    /// Iterates through all entity data that matches the query.
+    /// Gets an iterator which iterates through the items of all matching entities, and also
+    /// yields the `Entity` IDs.
     /// Does not perform static borrow checking.
     ///
     /// # Safety
@@ -1094,34 +5412,44 @@ where
     /// # Panics
     ///
     /// This function may panic if other code is concurrently accessing the same components.
-    pub unsafe fn for_each_unchecked<'a, 'data, T>(&'a self, world: &'data World, mut f: T)
-    where
-        T: Fn(<<V as View<'data>>::Iter as Iterator>::Item),
-    {
-        self.iter_unchecked(world).for_each(&mut f);
+    ///
+    /// In debug builds, the iterator panics if a chunk's length or the version of one of the
+    /// view's components changes while the chunk is being iterated.
+    pub unsafe fn iter_entities_unchecked<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> AdaptedEntityIter<'a, 'data, V, F, P> {
+        PipeIter {
+            iter: self.query.iter_entities_unchecked(world),
+            pipe: &self.pipe,
+            _phantom: PhantomData,
+        }
     }
 
-    /// Iterates through all entity data that matches the query.
-    pub fn for_each_immutable<'a, 'data, T>(&'a self, world: &'data World, f: T)
+    /// Gets an iterator which iterates through the items of all matching entities, and also
+    /// yields the `Entity` IDs.
+    pub fn iter_entities_immutable<'a, 'data>(
+        &'a self,
+        world: &'data World,
+    ) -> AdaptedEntityIter<'a, 'data, V, F, P>
     where
-        T: Fn(<<V as View<'data>>::Iter as Iterator>::Item),
         V: ReadOnly,
     {
         // safe because the view can only read data immutably
-        unsafe { self.for_each_unchecked(world, f) };
+        unsafe { self.iter_entities_unchecked(world) }
     }
 
-    /// Iterates through all entity data that matches the query.
-    pub fn for_each<'a, 'data, T>(&'a self, world: &'data mut World, f: T)
-    where
-        T: Fn(<<V as View<'data>>::Iter as Iterator>::Item),
-    {
+    /// Gets an iterator which iterates through the items of all matching entities, and also
+    /// yields the `Entity` IDs.
+    pub fn iter_entities<'a, 'data>(
+        &'a self,
+        world: &'data mut World,
+    ) -> AdaptedEntityIter<'a, 'data, V, F, P> {
         // safe because the &mut World ensures exclusivity
-        unsafe { self.for_each_unchecked(world, f) };
+        unsafe { self.iter_entities_unchecked(world) }
     }
 
-    #[cfg(feature = "par-iter")]
-    /// Gets an iterator which iterates through all chunks that match the query in parallel.
+    /// Gets an iterator which iterates through the items of all matching entities.
     /// Does not perform static borrow checking.
     ///
     /// # Safety
@@ -1131,117 +5459,70 @@ where
     /// # Panics
     ///
     /// This function may panic if other code is concurrently accessing the same components.
-    pub unsafe fn par_iter_chunks_unchecked<'a, 'data>(
+    ///
+    /// In debug builds, the iterator panics if a chunk's length or the version of one of the
+    /// view's components changes while the chunk is being iterated.
+    pub unsafe fn iter_unchecked<'a, 'data>(
         &'a self,
         world: &'data World,
-    ) -> ChunkViewParIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    ) -> impl FusedIterator<Item = PipeOutput<'data, V, P>> + 'a
     where
-        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
-        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
-        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+        'data: 'a,
     {
-        let (arch_filter, chunkset_filter, chunk_filter) = self.filter.filters();
-        let storage = world.storage();
-        let archetypes = FissileEnumerate::new(arch_filter.collect(ArchetypeFilterData {
-            component_types: storage.component_types(),
-            tag_types: storage.tag_types(),
-        }));
-        ChunkViewParIter {
-            storage,
-            arch_filter,
-            chunkset_filter,
-            chunk_filter,
-            archetypes,
-            set_frontier: None,
-            chunk_frontier: None,
-            _view: PhantomData,
-        }
+        self.iter_entities_unchecked(world).map(|(_, item)| item)
     }
 
-    #[cfg(feature = "par-iter")]
-    /// Gets an iterator which iterates through all chunks that match the query in parallel.
-    pub fn par_iter_chunks_immutable<'a, 'data>(
+    /// Gets an iterator which iterates through the items of all matching entities.
+    pub fn iter_immutable<'a, 'data>(
         &'a self,
         world: &'data World,
-    ) -> ChunkViewParIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    ) -> impl FusedIterator<Item = PipeOutput<'data, V, P>> + 'a
     where
-        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
-        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
-        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+        'data: 'a,
         V: ReadOnly,
     {
         // safe because the view can only read data immutably
-        unsafe { self.par_iter_chunks_unchecked(world) }
+        unsafe { self.iter_unchecked(world) }
     }
 
-    #[cfg(feature = "par-iter")]
-    /// Gets an iterator which iterates through all chunks that match the query in parallel.
-    pub fn par_iter_chunks<'a, 'data>(
+    /// Gets an iterator which iterates through the items of all matching entities.
+    pub fn iter<'a, 'data>(
         &'a self,
         world: &'data mut World,
-    ) -> ChunkViewParIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>
+    ) -> impl FusedIterator<Item = PipeOutput<'data, V, P>> + 'a
     where
-        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'data>>>::Iter: FissileIterator,
-        <F::ChunksetFilter as Filter<ChunksetFilterData<'data>>>::Iter: FissileIterator,
-        <F::ChunkFilter as Filter<ChunkFilterData<'data>>>::Iter: FissileIterator,
+        'data: 'a,
     {
         // safe because the &mut World ensures exclusivity
-        unsafe { self.par_iter_chunks_unchecked(world) }
+        unsafe { self.iter_unchecked(world) }
     }
 
-    /// Iterates through all entity data that matches the query in parallel.
-    /// Does not perform static borrow checking.
-    ///
-    /// # Safety
-    ///
-    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
-    ///
-    /// # Panics
-    ///
-    /// This function may panic if other code is concurrently accessing the same components.
-    #[cfg(feature = "par-iter")]
-    pub unsafe fn par_entities_for_each_unchecked<'a, T>(&'a self, world: &'a World, f: T)
+    /// Iterates through the items of all matching entities.
+    pub fn for_each_immutable<'a, 'data, T>(&'a self, world: &'data World, f: T)
     where
-        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
-        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
-        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
-        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        T: FnMut(PipeOutput<'data, V, P>),
+        V: ReadOnly,
     {
-        self.par_for_each_chunk_unchecked(world, |mut chunk| {
-            for data in chunk.iter_entities() {
-                f(data);
-            }
-        });
+        self.iter_immutable(world).for_each(f);
     }
 
-    /// Iterates through all entity data that matches the query in parallel.
-    #[cfg(feature = "par-iter")]
-    pub fn par_entities_for_each_immutable<'a, T>(&'a self, world: &'a World, f: T)
+    /// Iterates through the items of all matching entities.
+    pub fn for_each<'a, 'data, T>(&'a self, world: &'data mut World, f: T)
     where
-        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
-        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
-        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
-        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
-        V: ReadOnly,
+        T: FnMut(PipeOutput<'data, V, P>),
     {
-        // safe because the view can only read data immutably
-        unsafe { self.par_entities_for_each_unchecked(world, f) };
+        self.iter(world).for_each(f);
     }
 
-    /// Iterates through all entity data that matches the query in parallel.
-    #[cfg(feature = "par-iter")]
-    pub fn par_entities_for_each<'a, T>(&'a self, world: &'a mut World, f: T)
+    /// Iterates through the items of all matching entities, along with their `Entity` IDs.
+    pub fn for_each_entities<'a, 'data, T>(&'a self, world: &'data mut World, f: T)
     where
-        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
-        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
-        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
-        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        T: FnMut((Entity, PipeOutput<'data, V, P>)),
     {
-        // safe because the &mut World ensures exclusivity
-        unsafe { self.par_entities_for_each_unchecked(world, f) };
+        self.iter_entities(world).for_each(f);
     }
 
-    /// Iterates through all entity data that matches the query in parallel.
+    /// Iterates through the items of all matching entities in parallel.
     /// Does not perform static borrow checking.
     ///
     /// # Safety
@@ -1252,101 +5533,103 @@ where
     ///
     /// This function may panic if other code is concurrently accessing the same components.
     #[cfg(feature = "par-iter")]
-    pub unsafe fn par_for_each_unchecked<'a, T>(&'a self, world: &'a World, f: T)
+    pub unsafe fn par_for_each_entities_unchecked<'a, T>(&'a self, world: &'a World, f: T)
     where
-        T: Fn(<<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        T: Fn((Entity, PipeOutput<'a, V, P>)) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
     {
-        self.par_for_each_chunk_unchecked(world, |mut chunk| {
-            for data in chunk.iter() {
-                f(data);
-            }
-        });
+        let pipe = &self.pipe;
+        self.query
+            .par_for_each_entities_unchecked(world, |(entity, item)| {
+                if let Some(item) = pipe.apply(entity, item) {
+                    f((entity, item));
+                }
+            });
     }
 
-    /// Iterates through all entity data that matches the query in parallel.
+    /// Iterates through the items of all matching entities in parallel.
     #[cfg(feature = "par-iter")]
-    pub fn par_for_each_immutable<'a, T>(&'a self, world: &'a World, f: T)
+    pub fn par_for_each_entities<'a, T>(&'a self, world: &'a mut World, f: T)
     where
-        T: Fn(<<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        T: Fn((Entity, PipeOutput<'a, V, P>)) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
-        V: ReadOnly,
     {
-        // safe because the view can only read data immutably
-        unsafe { self.par_for_each_unchecked(world, f) };
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_for_each_entities_unchecked(world, f) };
     }
 
-    /// Iterates through all entity data that matches the query in parallel.
+    /// Iterates through the items of all matching entities in parallel.
+    /// Does not perform static borrow checking.
+    ///
+    /// Equivalent to `par_for_each_entities_unchecked`.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
     #[cfg(feature = "par-iter")]
-    pub fn par_for_each<'a, T>(&'a self, world: &'a mut World, f: T)
+    #[deprecated(note = "renamed to `par_for_each_entities_unchecked`")]
+    pub unsafe fn par_entities_for_each_unchecked<'a, T>(&'a self, world: &'a World, f: T)
     where
-        T: Fn(<<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        T: Fn((Entity, PipeOutput<'a, V, P>)) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
     {
-        // safe because the &mut World ensures exclusivity
-        unsafe { self.par_for_each_unchecked(world, f) };
+        self.par_for_each_entities_unchecked(world, f);
     }
 
-    /// Iterates through all chunks that match the query in parallel.
-    /// Does not perform static borrow checking.
-    ///
-    /// # Safety
+    /// Iterates through the items of all matching entities in parallel.
     ///
-    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
-    ///
-    /// # Panics
-    ///
-    /// This function may panic if other code is concurrently accessing the same components.
+    /// Equivalent to `par_for_each_entities`.
     #[cfg(feature = "par-iter")]
-    pub unsafe fn par_for_each_chunk_unchecked<'a, T>(&'a self, world: &'a World, f: T)
+    #[deprecated(note = "renamed to `par_for_each_entities`")]
+    pub fn par_entities_for_each<'a, T>(&'a self, world: &'a mut World, f: T)
     where
-        T: Fn(Chunk<'a, V>) + Send + Sync,
+        T: Fn((Entity, PipeOutput<'a, V, P>)) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
     {
-        let par_iter = self.par_iter_chunks_unchecked(world);
-        ParallelIterator::for_each(par_iter, |chunk| {
-            f(chunk);
-        });
+        self.par_for_each_entities(world, f);
     }
 
-    /// Iterates through all chunks that match the query in parallel.
+    /// Iterates through the items of all matching entities in parallel.
     #[cfg(feature = "par-iter")]
-    pub fn par_for_each_chunk_immutable<'a, T>(&'a self, world: &'a World, f: T)
+    pub fn par_for_each_immutable<'a, T>(&'a self, world: &'a World, f: T)
     where
-        T: Fn(Chunk<'a, V>) + Send + Sync,
+        T: Fn(PipeOutput<'a, V, P>) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
         V: ReadOnly,
     {
         // safe because the view can only read data immutably
-        unsafe { self.par_for_each_chunk_unchecked(world, f) };
+        unsafe { self.par_for_each_entities_unchecked(world, |(_, item)| f(item)) };
     }
 
-    /// Iterates through all chunks that match the query in parallel.
+    /// Iterates through the items of all matching entities in parallel.
     #[cfg(feature = "par-iter")]
-    pub fn par_for_each_chunk<'a, T>(&'a self, world: &'a mut World, f: T)
+    pub fn par_for_each<'a, T>(&'a self, world: &'a mut World, f: T)
     where
-        T: Fn(Chunk<'a, V>) + Send + Sync,
+        T: Fn(PipeOutput<'a, V, P>) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
     {
         // safe because the &mut World ensures exclusivity
-        unsafe { self.par_for_each_chunk_unchecked(world, f) };
+        unsafe { self.par_for_each_entities_unchecked(world, |(_, item)| f(item)) };
     }
 }
 
 /// An iterator over all chunks that match a given query.
-#[cfg(feature = "par-iter")]
+///
+/// With the `par-iter` feature, this is a rayon `ParallelIterator` which splits work between
+/// archetypes, sets and chunks. Without it, only its sequential `Iterator` implementation is
+/// available.
 pub struct ChunkViewParIter<'data, 'filter, V, FArch, FChunkset, FChunk>
 where
     V: for<'a> View<'a>,
@@ -1362,6 +5645,10 @@ where
     arch_filter: &'filter FArch,
     chunkset_filter: &'filter FChunkset,
     chunk_filter: &'filter FChunk,
+    changes: &'filter Arc<ChangeTracker>,
+    #[cfg(feature = "par-iter")]
+    pool: &'filter ExecutionPool,
+    skip_conflicts: bool,
     archetypes: FissileEnumerate<FArch::Iter>,
     set_frontier: Option<(
         &'data ArchetypeData,
@@ -1376,7 +5663,6 @@ where
     )>,
 }
 
-#[cfg(feature = "par-iter")]
 impl<'data, 'filter, V, FArch, FChunkset, FChunk>
     ChunkViewParIter<'data, 'filter, V, FArch, FChunkset, FChunk>
 where
@@ -1402,11 +5688,13 @@ where
             }
 
             // we have completed the current set, find the next one
+            self.set_frontier = None;
             loop {
                 match self.archetypes.next() {
                     Some((arch_index, arch_data)) => {
                         if self.arch_filter.is_match(&arch_data).is_pass() {
                             // we have found another set
+                            debug_assert!(arch_index < self.storage.archetypes().len());
                             self.set_frontier = {
                                 let arch =
                                     unsafe { self.storage.archetypes().get_unchecked(arch_index) };
@@ -1431,7 +5719,6 @@ where
     }
 }
 
-#[cfg(feature = "par-iter")]
 impl<'data, 'filter, V, FArch, FChunkset, FChunk> Iterator
     for ChunkViewParIter<'data, 'filter, V, FArch, FChunkset, FChunk>
 where
@@ -1450,10 +5737,17 @@ where
             // if we are looping through a set, then yield the next chunk
             if let Some((ref arch, set_index, ref mut set, index_bound)) = self.chunk_frontier {
                 for (chunk_index, filter_data) in set {
-                    if chunk_index < index_bound
+                    if chunk_index >= index_bound {
+                        continue;
+                    }
+                    // empty and conflicting chunks are skipped before stateful filters can
+                    // record them
+                    let chunk = &arch.chunksets()[set_index][chunk_index];
+                    if !chunk.is_empty()
+                        && !(self.skip_conflicts && skip_borrow_conflict::<V>(arch, chunk))
                         && self.chunk_filter.is_match(&filter_data).is_pass()
                     {
-                        return Some(Chunk::new(arch, set_index, chunk_index));
+                        return Some(Chunk::tracked(arch, set_index, chunk_index, self.changes));
                     }
                 }
             }
@@ -1468,12 +5762,26 @@ where
                     chunks.len(),
                 ))
             } else {
+                self.chunk_frontier = None;
                 return None;
             }
         }
     }
 }
 
+impl<'data, 'filter, V, FArch, FChunkset, FChunk> FusedIterator
+    for ChunkViewParIter<'data, 'filter, V, FArch, FChunkset, FChunk>
+where
+    V: for<'a> View<'a>,
+    FArch: Filter<ArchetypeFilterData<'data>>,
+    FChunkset: Filter<ChunksetFilterData<'data>>,
+    FChunk: Filter<ChunkFilterData<'data>>,
+    FArch::Iter: FissileIterator + FusedIterator,
+    FChunkset::Iter: FissileIterator,
+    FChunk::Iter: FissileIterator,
+{
+}
+
 #[cfg(feature = "par-iter")]
 impl<'data, 'filter, V, FArch, FChunkset, FChunk> ParallelIterator
     for ChunkViewParIter<'data, 'filter, V, FArch, FChunkset, FChunk>
@@ -1492,7 +5800,8 @@ where
     where
         C: UnindexedConsumer<Self::Item>,
     {
-        bridge_unindexed(self, consumer)
+        let pool = self.pool;
+        pool.install(move || bridge_unindexed(self, consumer))
     }
 }
 
@@ -1517,6 +5826,9 @@ where
             arch_filter,
             chunkset_filter,
             chunk_filter,
+            changes,
+            pool,
+            skip_conflicts,
             archetypes,
             set_frontier,
             chunk_frontier,
@@ -1553,6 +5865,9 @@ where
             arch_filter,
             chunkset_filter,
             chunk_filter,
+            changes,
+            pool,
+            skip_conflicts,
             archetypes: right_archetypes,
             set_frontier: right_set,
             chunk_frontier: right_chunk,
@@ -1568,6 +5883,9 @@ where
                     arch_filter,
                     chunkset_filter,
                     chunk_filter,
+                    changes,
+                    pool,
+                    skip_conflicts,
                     archetypes: left_archetypes,
                     set_frontier: left_set,
                     chunk_frontier: left_chunk,
@@ -1583,3 +5901,90 @@ where
         folder.consume_iter(self)
     }
 }
+
+/// A parallel iterator over the data of the chunks matched by a query, which splits the
+/// entities of each chunk between tasks. See `Query::par_iter_split`.
+#[cfg(feature = "par-iter")]
+pub struct SplitParIter<'a, V: SplitView<'a>> {
+    // held until the iterator has been driven
+    borrows: Vec<V::Borrow>,
+    chunks: Vec<(V::Parts, usize)>,
+    pool: &'a ExecutionPool,
+}
+
+#[cfg(feature = "par-iter")]
+impl<'a, V> ParallelIterator for SplitParIter<'a, V>
+where
+    V: SplitView<'a>,
+    V::Borrow: Send,
+    <V::Iter as Iterator>::Item: Send,
+{
+    type Item = <V::Iter as Iterator>::Item;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let Self {
+            borrows,
+            chunks,
+            pool,
+        } = self;
+        let result =
+            pool.install(move || bridge_unindexed(SplitProducer::<V> { chunks }, consumer));
+        drop(borrows);
+        result
+    }
+}
+
+/// Splits the chunks of a `SplitParIter` between tasks, and then the entities of each chunk.
+#[cfg(feature = "par-iter")]
+struct SplitProducer<'a, V: SplitView<'a>> {
+    chunks: Vec<(V::Parts, usize)>,
+}
+
+#[cfg(feature = "par-iter")]
+impl<'a, V> UnindexedProducer for SplitProducer<'a, V>
+where
+    V: SplitView<'a>,
+    <V::Iter as Iterator>::Item: Send,
+{
+    type Item = <V::Iter as Iterator>::Item;
+
+    fn split(mut self) -> (Self, Option<Self>) {
+        match self.chunks.len() {
+            0 => (self, None),
+            1 if self.chunks[0].1 < 2 => (self, None),
+            1 => {
+                let (parts, len) = self.chunks.pop().unwrap();
+                let mid = len / 2;
+                let (left, right) = V::split_parts(parts, mid);
+                (
+                    Self {
+                        chunks: vec![(left, mid)],
+                    },
+                    Some(Self {
+                        chunks: vec![(right, len - mid)],
+                    }),
+                )
+            }
+            len => {
+                let right = self.chunks.split_off(len / 2);
+                (self, Some(Self { chunks: right }))
+            }
+        }
+    }
+
+    fn fold_with<F>(self, mut folder: F) -> F
+    where
+        F: Folder<Self::Item>,
+    {
+        for (parts, _) in self.chunks {
+            folder = folder.consume_iter(V::iter_parts(parts));
+            if folder.full() {
+                break;
+            }
+        }
+        folder
+    }
+}
@@ -1,11 +1,18 @@
 use crate::borrow::{AtomicRefCell, Ref, RefMut};
-use crate::query::{Read, Write};
+use crate::query::{Read, TryRead, TryWrite, Write};
 use downcast_rs::{impl_downcast, Downcast};
 use fxhash::FxHashMap;
+use parking_lot::Mutex;
 use std::{
     any::TypeId,
+    collections::VecDeque,
+    hash::{Hash, Hasher},
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 #[cfg(not(feature = "ffi"))]
@@ -188,6 +195,10 @@ impl Resources {
         self.storage.contains_key(&ResourceTypeId::of::<T>())
     }
 
+    pub(crate) fn contains_id(&self, type_id: ResourceTypeId) -> bool {
+        self.storage.contains_key(&type_id)
+    }
+
     /// Inserts the instance of `T` into the store. If the type already exists, it will be silently
     /// overwritten. If you would like to retain the instance of the resource that already exists,
     /// call `remove` first to retrieve it.
@@ -304,6 +315,170 @@ impl Resources {
     }
 }
 
+/// Identifies a reader of an `EventChannel`, and records which events it has read.
+///
+/// Dropping a reader unregisters it from the channel.
+#[derive(Debug)]
+pub struct ReaderId {
+    cursor: Arc<AtomicU64>,
+}
+
+/// A queue of events of type `T` which can be read independently by any number of readers.
+///
+/// Events are published into sharded buffers, so they can be published concurrently from
+/// parallel systems. Published events become visible to readers once the channel is flushed,
+/// which merges the buffers and releases any events which all readers have read. Each reader
+/// sees every event that was flushed after it was registered exactly once.
+///
+/// Events published with `publish_from` are tagged with a publisher ID, and are flushed ordered
+/// by publisher ID and then in the order each publisher published them. The flushed order
+/// therefore does not depend upon how publishers were scheduled across threads, provided each
+/// publisher ID is only used by one thread at a time (e.g. one ID per system).
+///
+/// Events published with `publish` are flushed after all events with a publisher ID. Events
+/// published from the same thread are flushed in the order they were published, but there is
+/// no ordering between events published concurrently from different threads.
+///
+/// ```
+/// # use legion::resource::EventChannel;
+/// #[derive(Debug, PartialEq)]
+/// struct Damage(u32);
+///
+/// let mut channel = EventChannel::new();
+/// let mut reader = channel.register_reader();
+///
+/// channel.publish(Damage(5));
+/// channel.publish_batch(vec![Damage(1), Damage(2)]);
+/// assert_eq!(0, channel.read(&mut reader).count());
+///
+/// channel.flush();
+/// assert_eq!(
+///     vec![&Damage(5), &Damage(1), &Damage(2)],
+///     channel.read(&mut reader).collect::<Vec<_>>()
+/// );
+/// assert_eq!(0, channel.read(&mut reader).count());
+/// ```
+pub struct EventChannel<T> {
+    events: VecDeque<T>,
+    offset: u64,
+    pending: Box<[Mutex<Vec<(u32, T)>>]>,
+    readers: Vec<Arc<AtomicU64>>,
+}
+
+impl<T> EventChannel<T> {
+    const SHARDS: usize = 16;
+    const ANONYMOUS: u32 = u32::MAX;
+
+    /// Creates a new empty channel.
+    pub fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+            offset: 0,
+            pending: (0..Self::SHARDS).map(|_| Mutex::new(Vec::new())).collect(),
+            readers: Vec::new(),
+        }
+    }
+
+    fn thread_shard(&self) -> &Mutex<Vec<(u32, T)>> {
+        let mut hasher = fxhash::FxHasher::default();
+        std::thread::current().id().hash(&mut hasher);
+        &self.pending[hasher.finish() as usize % Self::SHARDS]
+    }
+
+    fn publisher_shard(&self, publisher: u32) -> &Mutex<Vec<(u32, T)>> {
+        assert!(publisher != Self::ANONYMOUS, "publisher ID is reserved");
+        &self.pending[publisher as usize % Self::SHARDS]
+    }
+
+    /// Publishes an event, which will be visible to readers after the next `flush`.
+    pub fn publish(&self, event: T) { self.thread_shard().lock().push((Self::ANONYMOUS, event)); }
+
+    /// Publishes a sequence of events, which will be visible to readers after the next `flush`.
+    pub fn publish_batch<I: IntoIterator<Item = T>>(&self, events: I) {
+        self.thread_shard()
+            .lock()
+            .extend(events.into_iter().map(|event| (Self::ANONYMOUS, event)));
+    }
+
+    /// Publishes an event on behalf of the given publisher, which will be visible to readers
+    /// after the next `flush`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `publisher` is `u32::MAX`, which is reserved for events published with
+    /// `publish`.
+    pub fn publish_from(&self, publisher: u32, event: T) {
+        self.publisher_shard(publisher)
+            .lock()
+            .push((publisher, event));
+    }
+
+    /// Publishes a sequence of events on behalf of the given publisher, which will be visible to
+    /// readers after the next `flush`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `publisher` is `u32::MAX`, which is reserved for events published with
+    /// `publish`.
+    pub fn publish_batch_from<I: IntoIterator<Item = T>>(&self, publisher: u32, events: I) {
+        self.publisher_shard(publisher)
+            .lock()
+            .extend(events.into_iter().map(|event| (publisher, event)));
+    }
+
+    /// Registers a new reader, which will see all events flushed after this call.
+    pub fn register_reader(&mut self) -> ReaderId {
+        let cursor = Arc::new(AtomicU64::new(self.offset + self.events.len() as u64));
+        self.readers.push(cursor.clone());
+        ReaderId { cursor }
+    }
+
+    /// Reads all flushed events which the given reader has not yet read.
+    pub fn read(&self, reader: &mut ReaderId) -> impl Iterator<Item = &T> {
+        let end = self.offset + self.events.len() as u64;
+        let start = reader.cursor.swap(end, Ordering::Relaxed).max(self.offset);
+        self.events.range((start - self.offset) as usize..)
+    }
+
+    /// Makes all published events visible to readers, and releases all events which have been
+    /// read by every reader.
+    pub fn flush(&mut self) {
+        // unregister dropped readers
+        self.readers.retain(|cursor| Arc::strong_count(cursor) > 1);
+
+        let end = self.offset + self.events.len() as u64;
+        let read = self
+            .readers
+            .iter()
+            .map(|cursor| cursor.load(Ordering::Relaxed))
+            .min()
+            .unwrap_or(end);
+        let released = (read - self.offset) as usize;
+        self.events.drain(..released);
+        self.offset = read;
+
+        // each shard holds the events of its publishers in publish order, so a stable sort by
+        // publisher ID orders events by publisher and then by sequence
+        let mut pending = Vec::new();
+        for shard in self.pending.iter_mut() {
+            pending.append(shard.get_mut());
+        }
+        pending.sort_by_key(|(publisher, _)| *publisher);
+        self.events
+            .extend(pending.into_iter().map(|(_, event)| event));
+    }
+
+    /// Gets the number of flushed events which have not yet been released.
+    pub fn len(&self) -> usize { self.events.len() }
+
+    /// Determines if there are no flushed events which have not yet been released.
+    pub fn is_empty(&self) -> bool { self.events.is_empty() }
+}
+
+impl<T> Default for EventChannel<T> {
+    fn default() -> Self { Self::new() }
+}
+
 impl ResourceSet for () {
     type PreparedResources = ();
 
@@ -331,6 +506,23 @@ impl<T: Resource> ResourceSet for Write<T> {
     }
 }
 
+impl<T: Resource> ResourceSet for TryRead<T> {
+    type PreparedResources = Option<PreparedRead<T>>;
+
+    fn fetch(resources: &Resources) -> Self::PreparedResources {
+        let resource = resources.get::<T>()?;
+        Some(unsafe { PreparedRead::new(resource.deref() as *const T) })
+    }
+}
+impl<T: Resource> ResourceSet for TryWrite<T> {
+    type PreparedResources = Option<PreparedWrite<T>>;
+
+    fn fetch(resources: &Resources) -> Self::PreparedResources {
+        let mut resource = resources.get_mut::<T>()?;
+        Some(unsafe { PreparedWrite::new(resource.deref_mut() as *mut T) })
+    }
+}
+
 macro_rules! impl_resource_tuple {
     ( $( $ty: ident ),* ) => {
         #[allow(unused_parens, non_snake_case)]
@@ -405,4 +597,133 @@ mod tests {
         let owned = resources.remove::<TestTwo>();
         assert_eq!(owned.unwrap().value, "balls")
     }
+
+    #[test]
+    fn event_channel_readers_at_different_paces() {
+        let mut channel = EventChannel::new();
+        let mut fast = channel.register_reader();
+        let mut slow = channel.register_reader();
+
+        channel.publish_batch(0..3);
+        channel.flush();
+        assert_eq!(
+            vec![0, 1, 2],
+            channel.read(&mut fast).copied().collect::<Vec<_>>()
+        );
+
+        channel.publish_batch(3..5);
+        channel.flush();
+        assert_eq!(
+            vec![3, 4],
+            channel.read(&mut fast).copied().collect::<Vec<_>>()
+        );
+
+        // events are retained until the slow reader has read them
+        assert_eq!(5, channel.len());
+        assert_eq!(
+            vec![0, 1, 2, 3, 4],
+            channel.read(&mut slow).copied().collect::<Vec<_>>()
+        );
+
+        // a new reader only sees events flushed after it was registered
+        let mut late = channel.register_reader();
+        channel.publish(5);
+        channel.flush();
+        assert_eq!(1, channel.len());
+        assert_eq!(
+            vec![5],
+            channel.read(&mut late).copied().collect::<Vec<_>>()
+        );
+
+        // dropping a reader stops it holding events
+        drop(slow);
+        drop(late);
+        channel.read(&mut fast).count();
+        channel.flush();
+        assert!(channel.is_empty());
+    }
+
+    #[test]
+    fn event_channel_wraparound() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.register_reader();
+
+        let mut expected = 0;
+        for frame in 0..100 {
+            channel.publish_batch(frame * 7..(frame + 1) * 7);
+            channel.flush();
+
+            // read some frames partially late, so the buffer wraps at different points
+            if frame % 3 != 0 {
+                for event in channel.read(&mut reader) {
+                    assert_eq!(expected, *event);
+                    expected += 1;
+                }
+            }
+            assert!(channel.len() <= 21);
+        }
+        channel.read(&mut reader).for_each(|e| {
+            assert_eq!(expected, *e);
+            expected += 1;
+        });
+        assert_eq!(700, expected);
+    }
+
+    #[test]
+    fn event_channel_parallel_publish() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.register_reader();
+
+        let channel_ref = &channel;
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                scope.spawn(move || {
+                    for i in 0..250 {
+                        channel_ref.publish((thread, i));
+                    }
+                });
+            }
+        });
+        channel.flush();
+
+        let events = channel.read(&mut reader).copied().collect::<Vec<_>>();
+        assert_eq!(1000, events.len());
+        for thread in 0..4 {
+            // events from each publisher are flushed in publish order
+            let published = events.iter().filter(|(t, _)| *t == thread).map(|(_, i)| *i);
+            assert!(published.eq(0..250));
+        }
+    }
+
+    #[test]
+    fn event_channel_parallel_publish_is_deterministic() {
+        let publish = || {
+            let mut channel = EventChannel::new();
+            let mut reader = channel.register_reader();
+
+            let channel_ref = &channel;
+            std::thread::scope(|scope| {
+                for publisher in 0..8u32 {
+                    scope.spawn(move || {
+                        for i in 0..100 {
+                            if i % 10 == 0 {
+                                channel_ref.publish_batch_from(publisher, vec![(publisher, i); 2]);
+                            } else {
+                                channel_ref.publish_from(publisher, (publisher, i));
+                            }
+                        }
+                    });
+                }
+            });
+            channel.flush();
+            channel.read(&mut reader).copied().collect::<Vec<_>>()
+        };
+
+        let first = publish();
+        assert_eq!(880, first.len());
+        assert!(first.windows(2).all(|w| w[0] <= w[1]));
+        for _ in 0..20 {
+            assert_eq!(first, publish());
+        }
+    }
 }
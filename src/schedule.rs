@@ -59,6 +59,11 @@ pub trait Runnable {
     fn accesses_archetypes(&self) -> &ArchetypeAccess;
     fn run(&self, world: &World);
     fn command_buffer_mut(&self) -> RefMut<CommandBuffer>;
+
+    /// Determines if everything the system requires in order to run (such as resources
+    /// declared with `SystemBuilder::requires_resource`) is present in the world. Executors
+    /// skip systems whose requirements are not met.
+    fn requirements_met(&self, _world: &World) -> bool { true }
 }
 
 /// Executes a sequence of systems, potentially in parallel, and then commits their command buffers.
@@ -68,6 +73,9 @@ pub trait Runnable {
 /// or entities) are observed is maintained.
 pub struct Executor {
     systems: Vec<Box<dyn Schedulable>>,
+    sets: Vec<Option<usize>>,
+    #[cfg(feature = "par-schedule")]
+    planned: Vec<bool>,
     #[cfg(feature = "par-schedule")]
     static_dependants: Vec<Vec<usize>>,
     #[cfg(feature = "par-schedule")]
@@ -83,17 +91,39 @@ impl Executor {
     ///
     /// Systems are provided in the order in which side-effects (e.g. writes to resources or entities)
     /// are to be observed.
+    pub fn new(systems: Vec<Box<dyn Schedulable>>) -> Self {
+        let sets = vec![None; systems.len()];
+        Self::with_sets(systems, sets)
+    }
+
     #[cfg(not(feature = "par-schedule"))]
-    pub fn new(systems: Vec<Box<dyn Schedulable>>) -> Self { Self { systems } }
+    fn with_sets(systems: Vec<Box<dyn Schedulable>>, sets: Vec<Option<usize>>) -> Self {
+        Self { systems, sets }
+    }
 
-    /// Constructs a new executor for all systems to be run in a single stage.
-    ///
-    /// Systems are provided in the order in which side-effects (e.g. writes to resources or entities)
-    /// are to be observed.
+    #[cfg(feature = "par-schedule")]
+    fn with_sets(systems: Vec<Box<dyn Schedulable>>, sets: Vec<Option<usize>>) -> Self {
+        let mut executor = Executor {
+            systems,
+            sets,
+            planned: Vec::new(),
+            static_dependants: Vec::new(),
+            dynamic_dependants: Vec::new(),
+            static_dependency_counts: Vec::new(),
+            awaiting: Vec::new(),
+        };
+        executor.plan(&vec![true; executor.systems.len()]);
+        executor
+    }
+
+    /// Computes the dependencies between the enabled systems. Disabled systems are left out
+    /// entirely, so that they do not order the systems around them.
     #[cfg(feature = "par-schedule")]
     #[allow(clippy::cognitive_complexity)]
     // TODO: we should break this up
-    pub fn new(systems: Vec<Box<dyn Schedulable>>) -> Self {
+    fn plan(&mut self, enabled: &[bool]) {
+        let systems = &self.systems;
+        self.planned = enabled.to_vec();
         if systems.len() > 1 {
             let mut static_dependency_counts = Vec::with_capacity(systems.len());
 
@@ -119,6 +149,11 @@ impl Executor {
                 );
 
             for (i, system) in systems.iter().enumerate() {
+                if !enabled[i] {
+                    static_dependency_counts.push(AtomicUsize::new(0));
+                    continue;
+                }
+
                 let span = span!(
                     Level::TRACE,
                     "Building system dependencies",
@@ -203,21 +238,10 @@ impl Executor {
                 .iter()
                 .for_each(|_| awaiting.push(AtomicUsize::new(0)));
 
-            Executor {
-                awaiting,
-                static_dependants,
-                dynamic_dependants,
-                static_dependency_counts,
-                systems,
-            }
-        } else {
-            Executor {
-                awaiting: Vec::with_capacity(0),
-                static_dependants: Vec::with_capacity(0),
-                dynamic_dependants: Vec::with_capacity(0),
-                static_dependency_counts: Vec::with_capacity(0),
-                systems,
-            }
+            self.awaiting = awaiting;
+            self.static_dependants = static_dependants;
+            self.dynamic_dependants = dynamic_dependants;
+            self.static_dependency_counts = static_dependency_counts;
         }
     }
 
@@ -230,30 +254,50 @@ impl Executor {
         self.flush_command_buffers(world);
     }
 
-    /// Executes all systems sequentially.
-    ///
-    /// Only enabled with par-schedule is disabled
-    #[cfg(not(feature = "par-schedule"))]
-    pub fn run_systems(&mut self, world: &mut World) {
-        self.systems.iter_mut().for_each(|system| {
-            system.run(world);
-        });
-    }
-
     /// Executes all systems, potentially in parallel.
     ///
     /// Ordering is retained in so far as the order of observed resource and component
     /// accesses is maintained.
     ///
     /// Call from within `rayon::ThreadPool::install()` to execute within a specific thread pool.
-    #[cfg(feature = "par-schedule")]
     pub fn run_systems(&mut self, world: &mut World) {
+        let enabled = vec![true; self.systems.len()];
+        self.run_enabled(world, &enabled);
+    }
+
+    /// Executes the systems for which `enabled` is `true` sequentially.
+    ///
+    /// Only enabled with par-schedule is disabled
+    #[cfg(not(feature = "par-schedule"))]
+    fn run_enabled(&mut self, world: &mut World, enabled: &[bool]) {
+        self.systems
+            .iter_mut()
+            .zip(enabled)
+            .for_each(|(system, enabled)| {
+                if *enabled && system.requirements_met(world) {
+                    let _epoch = world.enter_write_epoch();
+                    system.run(world);
+                }
+            });
+    }
+
+    /// Executes the systems for which `enabled` is `true`, potentially in parallel. The
+    /// dependencies between systems are recomputed whenever the enabled systems change.
+    #[cfg(feature = "par-schedule")]
+    fn run_enabled(&mut self, world: &mut World, enabled: &[bool]) {
+        if self.planned.as_slice() != enabled {
+            self.plan(enabled);
+        }
+
         rayon::join(
             || {},
             || {
                 match self.systems.len() {
                     1 => {
-                        self.systems[0].run(world);
+                        if enabled[0] && self.systems[0].requirements_met(world) {
+                            let _epoch = world.enter_write_epoch();
+                            self.systems[0].run(world);
+                        }
                     }
                     _ => {
                         let systems = &mut self.systems;
@@ -261,7 +305,11 @@ impl Executor {
                         let awaiting = &mut self.awaiting;
 
                         // prepare all systems - archetype filters are pre-executed here
-                        systems.par_iter_mut().for_each(|sys| sys.prepare(world));
+                        systems
+                            .par_iter_mut()
+                            .zip(enabled)
+                            .filter(|(_, enabled)| **enabled)
+                            .for_each(|(sys, _)| sys.prepare(world));
 
                         // determine dynamic dependencies
                         izip!(
@@ -295,7 +343,7 @@ impl Executor {
 
                         // execute all systems with no outstanding dependencies
                         (0..systems.len())
-                            .filter(|i| awaiting[*i].load(Ordering::SeqCst) == 0)
+                            .filter(|i| enabled[*i] && awaiting[*i].load(Ordering::SeqCst) == 0)
                             .for_each(|i| {
                                 self.run_recursive(i, world);
                             });
@@ -307,15 +355,28 @@ impl Executor {
 
     /// Flushes the recorded command buffers for all systems.
     pub fn flush_command_buffers(&mut self, world: &mut World) {
+        // label the structural changes made by each system in entity histories
+        let label = world.records_entity_history();
         self.systems.iter().for_each(|system| {
+            if label {
+                world.push_op_label(system.name().to_string());
+            }
             system.command_buffer_mut().write(world);
+            if label {
+                world.pop_op_label();
+            }
         });
     }
 
     /// Recursively execute through the generated depedency cascade and exhaust it.
     #[cfg(feature = "par-schedule")]
     fn run_recursive(&self, i: usize, world: &World) {
-        self.systems[i].run(world);
+        // a skipped system still releases its dependants
+        if self.systems[i].requirements_met(world) {
+            // each system writes within its own epoch, on whichever thread it runs
+            let _epoch = world.enter_write_epoch();
+            self.systems[i].run(world);
+        }
 
         self.static_dependants[i].par_iter().for_each(|dep| {
             match self.awaiting[*dep].compare_exchange(
@@ -335,19 +396,98 @@ impl Executor {
     }
 }
 
+/// Whether the systems in a set should run, as decided by the set's run criteria.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShouldRun {
+    /// Run the set's systems once.
+    Yes,
+    /// Do not run the set's systems.
+    No,
+    /// Run the set's systems, and then evaluate the criteria again. This allows a set to run
+    /// several times in one execution of the schedule, such as to catch up a fixed timestep.
+    YesAndCheckAgain,
+}
+
+/// A named group of systems in a schedule, which can be enabled and disabled together.
+struct SystemSet {
+    name: String,
+    enabled: bool,
+    criteria: Option<Box<dyn FnMut(&World) -> ShouldRun>>,
+}
+
+impl SystemSet {
+    fn should_run(&mut self, world: &World) -> ShouldRun {
+        if !self.enabled {
+            return ShouldRun::No;
+        }
+        match &mut self.criteria {
+            Some(criteria) => criteria(world),
+            None => ShouldRun::Yes,
+        }
+    }
+}
+
 /// A factory for `Schedule`.
 pub struct Builder {
     steps: Vec<Step>,
     accumulator: Vec<Box<dyn Schedulable>>,
+    accumulator_sets: Vec<Option<usize>>,
+    sets: Vec<SystemSet>,
 }
 
 impl Builder {
     /// Adds a system to the schedule.
     pub fn add_system<T: Into<Box<dyn Schedulable>>>(mut self, system: T) -> Self {
         self.accumulator.push(system.into());
+        self.accumulator_sets.push(None);
+        self
+    }
+
+    /// Adds a system to the schedule as a member of the named set, creating the set if it does
+    /// not yet exist.
+    ///
+    /// The systems of a set are run together with the systems around them, but can be enabled
+    /// and disabled with `Schedule::set_enabled`, or controlled by run criteria given to
+    /// `set_run_criteria`.
+    pub fn add_system_to_set<T>(mut self, set: &str, system: T) -> Self
+    where
+        T: Into<Box<dyn Schedulable>>,
+    {
+        let set = self.set_index(set);
+        self.accumulator.push(system.into());
+        self.accumulator_sets.push(Some(set));
         self
     }
 
+    /// Sets the run criteria of the named set, creating the set if it does not yet exist.
+    ///
+    /// The criteria are evaluated each time a step containing systems of the set is about to
+    /// be executed. While they return `ShouldRun::YesAndCheckAgain`, the set's systems in that
+    /// step are run again, without the other systems of the step, until the criteria return
+    /// `Yes` or `No`.
+    pub fn set_run_criteria<F>(mut self, set: &str, criteria: F) -> Self
+    where
+        F: FnMut(&World) -> ShouldRun + 'static,
+    {
+        let set = self.set_index(set);
+        self.sets[set].criteria = Some(Box::new(criteria));
+        self
+    }
+
+    fn set_index(&mut self, name: &str) -> usize {
+        match self.sets.iter().position(|set| set.name == name) {
+            Some(index) => index,
+            None => {
+                self.sets.push(SystemSet {
+                    name: name.to_owned(),
+                    enabled: true,
+                    criteria: None,
+                });
+                self.sets.len() - 1
+            }
+        }
+    }
+
     /// Waits for executing systems to complete, and the flushes all outstanding system
     /// command buffers.
     pub fn flush(mut self) -> Self {
@@ -360,7 +500,8 @@ impl Builder {
         if !self.accumulator.is_empty() {
             let mut systems = Vec::new();
             std::mem::swap(&mut self.accumulator, &mut systems);
-            let executor = Executor::new(systems);
+            let sets = std::mem::take(&mut self.accumulator_sets);
+            let executor = Executor::with_sets(systems, sets);
             self.steps.push(Step::Systems(executor));
         }
     }
@@ -377,7 +518,12 @@ impl Builder {
     /// Adds a thread local system to the schedule. This system will be executed on the main thread.
     pub fn add_thread_local<S: Into<Box<dyn Runnable>>>(self, system: S) -> Self {
         let system = system.into();
-        self.add_thread_local_fn(move |world| system.run(world))
+        self.add_thread_local_fn(move |world| {
+            if system.requirements_met(world) {
+                let _epoch = world.enter_write_epoch();
+                system.run(world);
+            }
+        })
     }
 
     /// Finalizes the builder into a `Schedule`.
@@ -389,6 +535,8 @@ impl Default for Builder {
         Self {
             steps: Vec::new(),
             accumulator: Vec::new(),
+            accumulator_sets: Vec::new(),
+            sets: Vec::new(),
         }
     }
 }
@@ -424,19 +572,40 @@ pub enum Step {
 /// ```
 pub struct Schedule {
     steps: Vec<Step>,
+    sets: Vec<SystemSet>,
 }
 
 impl Schedule {
     /// Creates a new schedule builder.
     pub fn builder() -> Builder { Builder::default() }
 
+    /// Enables or disables the systems of the named set. Disabled systems are skipped, and do
+    /// not delay the systems which would otherwise have waited for them.
+    ///
+    /// Returns `false` if the schedule has no set with the given name.
+    pub fn set_enabled(&mut self, set: &str, enabled: bool) -> bool {
+        match self.sets.iter_mut().find(|s| s.name == set) {
+            Some(set) => {
+                set.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Determines if the named set exists and is enabled.
+    pub fn is_enabled(&self, set: &str) -> bool {
+        self.sets.iter().any(|s| s.name == set && s.enabled)
+    }
+
     /// Executes all of the steps in the schedule.
     pub fn execute(&mut self, world: &mut World) {
+        let sets = &mut self.sets;
         let mut waiting_flush: Vec<&mut Executor> = Vec::new();
         for step in &mut self.steps {
             match step {
                 Step::Systems(executor) => {
-                    executor.run_systems(world);
+                    Self::run_executor(executor, sets, world);
                     waiting_flush.push(executor);
                 }
                 Step::FlushCmdBuffers => waiting_flush
@@ -447,20 +616,62 @@ impl Schedule {
         }
     }
 
+    // Runs the systems of an executor whose sets' run criteria allow it, and then runs the
+    // systems of any sets which asked to be checked again until none remain.
+    fn run_executor(executor: &mut Executor, sets: &mut [SystemSet], world: &mut World) {
+        let mut first = true;
+        let mut decisions = vec![ShouldRun::No; sets.len()];
+        loop {
+            for (i, set) in sets.iter_mut().enumerate() {
+                let again = decisions[i] == ShouldRun::YesAndCheckAgain;
+                let present = executor.sets.contains(&Some(i));
+                decisions[i] = if present && (first || again) {
+                    set.should_run(world)
+                } else {
+                    ShouldRun::No
+                };
+            }
+
+            let enabled = executor
+                .sets
+                .iter()
+                .map(|set| match set {
+                    Some(set) => decisions[*set] != ShouldRun::No,
+                    None => first,
+                })
+                .collect::<Vec<_>>();
+            if enabled.contains(&true) {
+                executor.run_enabled(world, &enabled);
+            }
+
+            first = false;
+            if !decisions.contains(&ShouldRun::YesAndCheckAgain) {
+                break;
+            }
+        }
+    }
+
     /// Converts the schedule into a vector of steps.
     pub fn into_vec(self) -> Vec<Step> { self.steps }
 }
 
 impl From<Builder> for Schedule {
     fn from(builder: Builder) -> Self {
+        let builder = builder.flush();
         Self {
-            steps: builder.flush().steps,
+            steps: builder.steps,
+            sets: builder.sets,
         }
     }
 }
 
 impl From<Vec<Step>> for Schedule {
-    fn from(steps: Vec<Step>) -> Self { Self { steps } }
+    fn from(steps: Vec<Step>) -> Self {
+        Self {
+            steps,
+            sets: Vec::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -471,6 +682,7 @@ mod tests {
     use std::sync::{Arc, Mutex};
 
     #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn execute_in_order() {
         let universe = Universe::new();
         let mut world = universe.create_world();
@@ -509,6 +721,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn flush() {
         let universe = Universe::new();
         let mut world = universe.create_world();
@@ -534,4 +747,122 @@ mod tests {
 
         schedule.execute(&mut world);
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
+    fn run_criteria_fixed_timestep() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        #[derive(Default)]
+        struct Steps(usize);
+        #[derive(Default)]
+        struct Frames(usize);
+
+        world.resources.insert(Steps::default());
+        world.resources.insert(Frames::default());
+
+        let physics = SystemBuilder::new("physics")
+            .write_resource::<Steps>()
+            .build(|_, _, steps, _| steps.0 += 1);
+        let frame = SystemBuilder::new("frame")
+            .write_resource::<Frames>()
+            .build(|_, _, frames, _| frames.0 += 1);
+
+        // runs the set once for each pending step
+        let pending = Rc::new(Cell::new(0));
+        let criteria = {
+            let pending = pending.clone();
+            move |_: &World| match pending.get() {
+                0 => ShouldRun::No,
+                1 => {
+                    pending.set(0);
+                    ShouldRun::Yes
+                }
+                n => {
+                    pending.set(n - 1);
+                    ShouldRun::YesAndCheckAgain
+                }
+            }
+        };
+
+        let mut schedule = Schedule::builder()
+            .add_system_to_set("physics", physics)
+            .add_system(frame)
+            .set_run_criteria("physics", criteria)
+            .build();
+
+        for (step, expected) in vec![(3, 3), (0, 3), (1, 4), (2, 6)] {
+            pending.set(step);
+            schedule.execute(&mut world);
+            assert_eq!(expected, world.resources.get::<Steps>().unwrap().0);
+        }
+        assert_eq!(4, world.resources.get::<Frames>().unwrap().0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
+    fn disabled_set() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        #[derive(Default)]
+        struct Resource(usize);
+
+        world.resources.insert(Resource::default());
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let log_clone = log.clone();
+        let before = SystemBuilder::new("before")
+            .read_resource::<Resource>()
+            .build(move |_, _, _, _| log_clone.lock().unwrap().push("before"));
+        let debug_draw = SystemBuilder::new("debug_draw")
+            .write_resource::<Resource>()
+            .build(|_, _, resource, _| resource.0 += 1);
+        let log_clone = log.clone();
+        let after = SystemBuilder::new("after")
+            .read_resource::<Resource>()
+            .build(move |_, _, _, _| log_clone.lock().unwrap().push("after"));
+
+        let mut schedule = Schedule::builder()
+            .add_system(before)
+            .add_system_to_set("debug_draw", debug_draw)
+            .add_system(after)
+            .build();
+
+        schedule.execute(&mut world);
+        assert_eq!(1, world.resources.get::<Resource>().unwrap().0);
+
+        assert!(schedule.set_enabled("debug_draw", false));
+        assert!(!schedule.set_enabled("physics", false));
+        assert!(!schedule.is_enabled("debug_draw"));
+        schedule.execute(&mut world);
+        assert_eq!(1, world.resources.get::<Resource>().unwrap().0);
+        assert_eq!(4, log.lock().unwrap().len());
+
+        // without the writer between them, the readers no longer depend on each other
+        #[cfg(feature = "par-schedule")]
+        match &schedule.steps[0] {
+            Step::Systems(executor) => {
+                assert_eq!(vec![true, false, true], executor.planned);
+                assert!(executor.static_dependants.iter().all(|d| d.is_empty()));
+            }
+            _ => panic!("expected systems"),
+        }
+
+        schedule.set_enabled("debug_draw", true);
+        schedule.execute(&mut world);
+        assert_eq!(2, world.resources.get::<Resource>().unwrap().0);
+        #[cfg(feature = "par-schedule")]
+        match &schedule.steps[0] {
+            Step::Systems(executor) => {
+                assert_eq!(vec![1], executor.static_dependants[0]);
+                assert_eq!(vec![2], executor.static_dependants[1]);
+            }
+            _ => panic!("expected systems"),
+        }
+    }
 }
@@ -0,0 +1,107 @@
+//! Notes on which parts of the public API are expected to grow without breaking changes.
+//!
+//! The following types are marked `#[non_exhaustive]`, so that variants or fields may be added
+//! to them in minor releases:
+//!
+//! * Errors: `borrow::BorrowConflict`, `clone::CloneError`, `command::CommandError`,
+//!   `query::SingleError` and `world::InsertError`.
+//! * Events: `event::Event`.
+//! * Options: `world::WorldOptions` and `world::UniverseOptions`, which should be created with
+//!   `default` and then configured with their `with_*` methods or by assigning to their fields.
+//! * Statistics: `world::UniverseStats`, `world::MemoryStats`, `storage::ArchetypeStats`,
+//!   `filter::ChangedFilterStats` (with the `testing` feature) and
+//!   `entity::EntityAllocatorStats`.
+//! * Reports: `mirror::MirroredChunk`.
+//!
+//! Matches against these enums must include a wildcard arm, and these structs cannot be built
+//! with struct literals outside of legion. Code which relies on either does not compile:
+//!
+//! ```compile_fail
+//! # use legion::clone::CloneError;
+//! fn describe(err: CloneError) -> &'static str {
+//!     match err {
+//!         CloneError::EntityNotFound(_) => "not found",
+//!         CloneError::NotRegistered(_) => "not registered",
+//!     }
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! # use legion::command::CommandError;
+//! fn describe(err: CommandError) -> &'static str {
+//!     match err {
+//!         CommandError::EntityBlockFull => "full",
+//!     }
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! # use legion::query::SingleError;
+//! fn describe(err: SingleError) -> &'static str {
+//!     match err {
+//!         SingleError::NoEntities => "none",
+//!         SingleError::MultipleEntities => "many",
+//!     }
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! # use legion::event::Event;
+//! fn describe(event: Event) -> &'static str {
+//!     match event {
+//!         Event::ArchetypeCreated(_) => "archetype",
+//!         Event::ChunkCreated(_) => "chunk",
+//!         Event::EntityInserted(..) => "inserted",
+//!         Event::EntityRemoved(..) => "removed",
+//!         Event::EntityThresholdExceeded(..) => "threshold",
+//!     }
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! # use legion::world::WorldOptions;
+//! let options = WorldOptions {
+//!     write_epochs: true,
+//!     ..WorldOptions::default()
+//! };
+//! ```
+//!
+//! ```compile_fail
+//! # use legion::world::UniverseStats;
+//! fn blocks(stats: UniverseStats) -> usize {
+//!     let UniverseStats {
+//!         worlds_created,
+//!         entity_blocks_allocated,
+//!         entity_blocks_free,
+//!     } = stats;
+//!     entity_blocks_allocated + entity_blocks_free
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! # use legion::entity::EntityAllocatorStats;
+//! let stats = EntityAllocatorStats {
+//!     capacity: 0,
+//!     alive: 0,
+//!     free: 0,
+//!     retired: 0,
+//! };
+//! ```
+//!
+//! A wildcard arm, or a `..` rest pattern, compiles:
+//!
+//! ```
+//! # use legion::clone::CloneError;
+//! # use legion::world::UniverseStats;
+//! fn describe(err: CloneError) -> &'static str {
+//!     match err {
+//!         CloneError::EntityNotFound(_) => "not found",
+//!         _ => "other",
+//!     }
+//! }
+//!
+//! fn blocks(stats: UniverseStats) -> usize {
+//!     let UniverseStats { entity_blocks_allocated, .. } = stats;
+//!     entity_blocks_allocated
+//! }
+//! ```
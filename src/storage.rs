@@ -1,4 +1,7 @@
-use crate::borrow::{AtomicRefCell, Ref, RefMap, RefMapMut, RefMut};
+use crate::borrow::{
+    AtomicRefCell, BorrowConflict, BorrowConflictPolicy, Ref, RefMap, RefMapMut, RefMut,
+};
+use crate::clone::CloneImpl;
 use crate::entity::Entity;
 use crate::entity::EntityLocation;
 use crate::event::EventFilterWrapper;
@@ -11,16 +14,18 @@ use crate::filter::EntityFilter;
 use crate::filter::Filter;
 use crate::iterator::FissileZip;
 use crate::iterator::SliceVecIter;
+use crate::summary::{ComponentSummary, SummaryCache, SummaryDef};
 use crate::world::TagSet;
 use crate::world::WorldId;
 use derivative::Derivative;
 use fxhash::FxHashMap;
-use smallvec::Drain;
 use smallvec::SmallVec;
 use std::any::TypeId;
+use std::cell::Cell;
 use std::cell::UnsafeCell;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::marker::PhantomData;
 use std::mem::size_of;
 use std::ops::Deref;
 use std::ops::DerefMut;
@@ -28,18 +33,133 @@ use std::ops::RangeBounds;
 use std::ptr::NonNull;
 use std::slice::Iter;
 use std::slice::IterMut;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use tracing::trace;
+use tracing::{trace, warn};
 
 static VERSION_COUNTER: AtomicU64 = AtomicU64::new(0);
+static STORAGE_UID_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// Gets a well-aligned pointer for zero-sized or not-yet-allocated data, which is never
+/// dereferenced. Unlike an integer-to-pointer cast, the pointer has no provenance, so it cannot
+/// be mistaken for a pointer into an allocation.
+#[inline]
+fn dangling(align: usize) -> *mut u8 { std::ptr::null_mut::<u8>().wrapping_add(align) }
+
+fn next_version() -> Version {
+    loop {
+        // the counter wraps on overflow, skipping the reserved `Version::NONE`
+        let version = VERSION_COUNTER
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_add(1);
+        if version != 0 {
+            return Version(version);
+        }
+    }
+}
+
+/// A component data version, allocated from a single global counter each time component
+/// data is written.
+///
+/// `Ord` compares the raw counter values. Use `is_newer_than` to compare versions in a way
+/// which remains correct if the counter overflows, provided the two versions were allocated
+/// less than 2^63 writes apart.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde-1", serde(transparent))]
+pub struct Version(u64);
+
+impl Version {
+    /// The version of data which has never been written. It is older than all other versions.
+    pub const NONE: Version = Version(0);
+
+    pub(crate) fn from_bits(bits: u64) -> Self { Version(bits) }
+
+    pub(crate) fn to_bits(self) -> u64 { self.0 }
+
+    /// Determines if this is `Version::NONE`.
+    pub fn is_none(self) -> bool { self == Version::NONE }
+
+    /// Determines if this version was allocated after `other`, using the wrapping distance
+    /// between the two versions.
+    pub fn is_newer_than(self, other: Version) -> bool {
+        match (self.is_none(), other.is_none()) {
+            (true, _) => false,
+            (false, true) => true,
+            (false, false) => (self.0.wrapping_sub(other.0) as i64) > 0,
+        }
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result { write!(f, "v{}", self.0) }
+}
+
+/// Tracks a world's current write epoch. Within an epoch, only the first mutable borrow of
+/// each chunk's components increments their version.
+///
+/// Epochs are either begun for the whole world, or entered by a single thread with `enter`. An
+/// entered epoch takes precedence over the world's epoch on the thread which entered it.
+#[derive(Debug, Default)]
+pub(crate) struct WriteEpochs {
+    next: AtomicU64,
+    current: AtomicU64,
+}
+
+thread_local! {
+    /// The address of the `WriteEpochs` whose epoch was last entered on this thread, and the
+    /// ID of that epoch.
+    static ENTERED_EPOCH: Cell<(usize, u64)> = const { Cell::new((0, 0)) };
+}
+
+impl WriteEpochs {
+    /// Begins a new epoch, returning its ID. IDs are never `0`.
+    pub(crate) fn begin(&self) -> u64 {
+        let epoch = self.allocate();
+        self.current.store(epoch, Ordering::Relaxed);
+        epoch
+    }
+
+    /// Ends the current epoch. Until the next epoch begins, every mutable borrow increments
+    /// component versions.
+    pub(crate) fn end(&self) { self.current.store(0, Ordering::Relaxed); }
+
+    /// Enters a new epoch on the current thread, which lasts until the returned scope is
+    /// dropped. Other threads are unaffected.
+    pub(crate) fn enter(&self) -> WriteEpochScope {
+        let entered = (self.address(), self.allocate());
+        WriteEpochScope {
+            previous: ENTERED_EPOCH.with(|current| current.replace(entered)),
+            _thread: PhantomData,
+        }
+    }
+
+    fn allocate(&self) -> u64 { self.next.fetch_add(1, Ordering::Relaxed) + 1 }
+
+    fn address(&self) -> usize { self as *const Self as usize }
+
+    fn current(&self) -> u64 {
+        match ENTERED_EPOCH.with(Cell::get) {
+            (address, epoch) if address == self.address() => epoch,
+            _ => self.current.load(Ordering::Relaxed),
+        }
+    }
+}
 
-fn next_version() -> u64 {
-    VERSION_COUNTER
-        .fetch_add(1, Ordering::Relaxed)
-        .checked_add(1)
-        .unwrap()
+/// A write epoch entered on the current thread with `World::enter_write_epoch`. The thread
+/// returns to the epoch it was previously in when this is dropped.
+#[derive(Debug)]
+pub struct WriteEpochScope {
+    previous: (usize, u64),
+    // the scope belongs to the thread which entered it
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for WriteEpochScope {
+    fn drop(&mut self) { ENTERED_EPOCH.with(|current| current.set(self.previous)); }
 }
 
 #[cfg(not(feature = "ffi"))]
@@ -65,6 +185,16 @@ impl ComponentTypeId {
 
     /// Gets the component type ID that represents type `T`, also adds another identification number used for FFI.
     pub fn of_c_api<T: Component>(ty: u32) -> Self { Self(TypeId::of::<T>(), ty) }
+
+    /// Gets a 64-bit identifier for the component type, used to refer to it across the C API.
+    ///
+    /// The identifier is only stable within a single build.
+    pub fn ffi_id(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = fxhash::FxHasher64::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
 }
 
 #[cfg(not(feature = "ffi"))]
@@ -171,10 +301,23 @@ impl<T> SliceVec<T> {
 /// Stores all entity data for a `World`.
 pub struct Storage {
     world_id: WorldId,
+    uid: u64,
     component_types: ComponentTypes,
     tag_types: TagTypes,
     archetypes: Vec<ArchetypeData>,
     subscribers: Subscribers,
+    tracked_writes: Vec<ComponentTypeId>,
+    summaries: Vec<(ComponentTypeId, SummaryDef)>,
+    spawn_clock: Option<Arc<AtomicU64>>,
+    write_epochs: Option<Arc<WriteEpochs>>,
+    growth_factor: f32,
+    deny_oversized_components: bool,
+    entity_threshold: Option<usize>,
+    fingerprints: FxHashMap<ComponentTypeId, u64>,
+    placement_groups: u8,
+    borrow_conflict_policy: BorrowConflictPolicy,
+    #[cfg(feature = "numa")]
+    chunk_allocator: Option<Arc<dyn ChunkAllocator>>,
 }
 
 impl Storage {
@@ -182,11 +325,125 @@ impl Storage {
     pub fn new(world_id: WorldId) -> Self {
         Self {
             world_id,
+            uid: STORAGE_UID_COUNTER.fetch_add(1, Ordering::Relaxed),
             component_types: ComponentTypes::default(),
             tag_types: TagTypes::default(),
             archetypes: Vec::default(),
             subscribers: Subscribers::default(),
+            tracked_writes: Vec::default(),
+            summaries: Vec::default(),
+            spawn_clock: None,
+            write_epochs: None,
+            growth_factor: DEFAULT_GROWTH_FACTOR,
+            deny_oversized_components: false,
+            entity_threshold: None,
+            fingerprints: FxHashMap::default(),
+            placement_groups: 1,
+            borrow_conflict_policy: BorrowConflictPolicy::default(),
+            #[cfg(feature = "numa")]
+            chunk_allocator: None,
+        }
+    }
+
+    /// Gets an ID which is unique to this storage within the process, unlike its `WorldId`.
+    pub(crate) fn uid(&self) -> u64 { self.uid }
+
+    /// Sets the factor by which the chunk set and chunk vectors of all current and future
+    /// archetypes grow when full.
+    pub(crate) fn set_growth_factor(&mut self, factor: f32) {
+        self.growth_factor = factor;
+        for archetype in self.archetypes.iter_mut() {
+            archetype.growth_factor = factor;
+        }
+    }
+
+    /// Sets whether creating an archetype containing a component type which is larger than a
+    /// chunk panics, rather than emitting a warning.
+    pub(crate) fn deny_oversized_components(&mut self, deny: bool) {
+        self.deny_oversized_components = deny;
+    }
+
+    /// Sets the number of placement groups which the chunks of all future archetypes are
+    /// assigned to in turn.
+    pub(crate) fn set_placement_groups(&mut self, groups: u8) {
+        self.placement_groups = std::cmp::max(1, groups);
+    }
+
+    /// Sets the allocator used for the memory of chunks created from now on, in all current
+    /// and future archetypes.
+    #[cfg(feature = "numa")]
+    pub(crate) fn set_chunk_allocator(&mut self, allocator: Arc<dyn ChunkAllocator>) {
+        for archetype in self.archetypes.iter_mut() {
+            archetype.component_layout.allocator = Some(allocator.clone());
+        }
+        self.chunk_allocator = Some(allocator);
+    }
+
+    /// Gets the policy applied when component data cannot be borrowed.
+    pub(crate) fn borrow_conflict_policy(&self) -> BorrowConflictPolicy {
+        self.borrow_conflict_policy
+    }
+
+    /// Sets the policy applied when component data cannot be borrowed.
+    pub(crate) fn set_borrow_conflict_policy(&mut self, policy: BorrowConflictPolicy) {
+        self.borrow_conflict_policy = policy;
+    }
+
+    /// Sets the entity threshold of future archetypes whose descriptions do not set their own.
+    pub(crate) fn set_entity_threshold(&mut self, threshold: Option<usize>) {
+        self.entity_threshold = threshold;
+    }
+
+    /// Sets the layout fingerprint of the given component type in all current archetypes, and
+    /// in future archetypes whose descriptions do not set their own.
+    pub(crate) fn set_component_fingerprint(&mut self, type_id: ComponentTypeId, fingerprint: u64) {
+        self.fingerprints.insert(type_id, fingerprint);
+        for archetype in self.archetypes.iter_mut() {
+            archetype.set_component_fingerprint(type_id, fingerprint);
+        }
+    }
+
+    /// Enables per-entity write tracking for the given component type in all current
+    /// and future archetypes.
+    pub(crate) fn track_writes(&mut self, type_id: ComponentTypeId) {
+        if self.tracked_writes.contains(&type_id) {
+            return;
+        }
+
+        self.tracked_writes.push(type_id);
+        for archetype in self.archetypes.iter_mut() {
+            archetype.track_writes(type_id);
+        }
+    }
+
+    /// Enables per-chunk summaries of component type `T` in all current and future
+    /// archetypes, replacing any summary previously enabled for `T`.
+    pub(crate) fn enable_summary<T: Component, S: ComponentSummary<T>>(&mut self) {
+        let type_id = ComponentTypeId::of::<T>();
+        let def = SummaryDef::of::<T, S>();
+        self.summaries.retain(|(t, _)| *t != type_id);
+        self.summaries.push((type_id, def));
+        for archetype in self.archetypes.iter_mut() {
+            archetype.enable_summary(type_id, def);
+        }
+    }
+
+    /// Enables recording of the tick at which each entity was spawned in all current and
+    /// future archetypes, reading the current tick from `clock`.
+    pub(crate) fn record_spawn_ticks(&mut self, clock: Arc<AtomicU64>) {
+        for archetype in self.archetypes.iter_mut() {
+            archetype.record_spawn_ticks(clock.clone());
         }
+        self.spawn_clock = Some(clock);
+    }
+
+    /// Limits the version increments of all current and future archetypes to one per chunk
+    /// within each of the given write epochs.
+    pub(crate) fn record_write_epochs(&mut self, epochs: Arc<WriteEpochs>) {
+        for archetype in self.archetypes.iter_mut() {
+            archetype.record_write_epochs(epochs.clone());
+        }
+        self.write_epochs = Some(epochs);
     }
 
     pub(crate) fn subscribe<T: EntityFilter + Sync + 'static>(
@@ -208,8 +465,38 @@ impl Storage {
     /// achetype's data.
     pub(crate) fn alloc_archetype(
         &mut self,
-        desc: ArchetypeDescription,
-    ) -> (usize, &mut ArchetypeData) {
+        mut desc: ArchetypeDescription,
+    ) -> (ArchetypeIndex, &mut ArchetypeData) {
+        if desc.entity_threshold.is_none() {
+            desc.entity_threshold = self.entity_threshold;
+        }
+        for (type_id, meta) in desc.components.iter_mut() {
+            match self.fingerprints.get(type_id) {
+                Some(fingerprint) if meta.fingerprint == 0 => meta.fingerprint = *fingerprint,
+                _ => {}
+            }
+        }
+
+        for ((_, meta), name) in desc.components.iter().zip(desc.component_names.iter()) {
+            if meta.size <= MAX_CHUNK_SIZE {
+                continue;
+            }
+
+            if self.deny_oversized_components {
+                panic!(
+                    "component {} ({} bytes) is larger than a chunk ({} bytes)",
+                    name, meta.size, MAX_CHUNK_SIZE
+                );
+            }
+
+            warn!(
+                component = name,
+                size = meta.size,
+                chunk_size = MAX_CHUNK_SIZE,
+                "Component is larger than a chunk; each chunk will hold a single entity"
+            );
+        }
+
         let id = ArchetypeId(self.world_id, self.archetypes.len());
         let archetype = ArchetypeData::new(id, desc);
 
@@ -217,10 +504,16 @@ impl Storage {
 
         let index = self.archetypes.len() - 1;
         let archetype = &mut self.archetypes[index];
-        (index, archetype)
+        (ArchetypeIndex(index), archetype)
     }
 
     pub(crate) fn push(&mut self, mut archetype: ArchetypeData) {
+        archetype.growth_factor = self.growth_factor;
+        archetype.placement_groups = self.placement_groups;
+        #[cfg(feature = "numa")]
+        {
+            archetype.component_layout.allocator = self.chunk_allocator.clone();
+        }
         let desc = archetype.description();
         self.component_types
             .0
@@ -247,6 +540,22 @@ impl Storage {
         subscribers.send(Event::ArchetypeCreated(id));
         archetype.set_subscribers(subscribers);
 
+        for type_id in self.tracked_writes.iter() {
+            archetype.track_writes(*type_id);
+        }
+
+        for (type_id, def) in self.summaries.iter() {
+            archetype.enable_summary(*type_id, *def);
+        }
+
+        if let Some(clock) = &self.spawn_clock {
+            archetype.record_spawn_ticks(clock.clone());
+        }
+
+        if let Some(epochs) = &self.write_epochs {
+            archetype.record_write_epochs(epochs.clone());
+        }
+
         self.archetypes.push(archetype);
     }
 
@@ -266,6 +575,16 @@ impl Storage {
     /// Gets a mutable slice reference to all archetypes.
     pub fn archetypes_mut(&mut self) -> &mut [ArchetypeData] { &mut self.archetypes }
 
+    /// Gets the archetype at the given index.
+    pub fn archetype(&self, index: ArchetypeIndex) -> Option<&ArchetypeData> {
+        self.archetypes.get(index.0)
+    }
+
+    /// Gets the archetype at the given index mutably.
+    pub fn archetype_mut(&mut self, index: ArchetypeIndex) -> Option<&mut ArchetypeData> {
+        self.archetypes.get_mut(index.0)
+    }
+
     pub(crate) fn drain<R: RangeBounds<usize>>(
         &mut self,
         range: R,
@@ -316,19 +635,98 @@ pub struct ComponentMeta {
     size: usize,
     align: usize,
     drop_fn: Option<fn(*mut u8)>,
+    fingerprint: u64,
 }
 
 impl ComponentMeta {
     /// Gets the component meta of component type `T`.
+    ///
+    /// Types which do not need to be dropped have no drop function, so chunks of them are
+    /// freed without visiting each component.
     pub fn of<T: Component>() -> Self {
+        let drop_fn: Option<fn(*mut u8)> = if std::mem::needs_drop::<T>() {
+            Some(|ptr| unsafe { std::ptr::drop_in_place(ptr as *mut T) })
+        } else {
+            None
+        };
         ComponentMeta {
             size: size_of::<T>(),
             align: std::mem::align_of::<T>(),
-            drop_fn: Some(|ptr| unsafe { std::ptr::drop_in_place(ptr as *mut T) }),
+            drop_fn,
+            fingerprint: 0,
+        }
+    }
+
+    /// Determines if components of this type need to be dropped.
+    pub fn needs_drop(&self) -> bool { self.drop_fn.is_some() }
+
+    /// Gets the component meta of an externally defined component type, which is stored as
+    /// raw bytes with the given size and alignment and does not need to be dropped.
+    pub fn raw(size: usize, align: usize) -> Self {
+        ComponentMeta {
+            size,
+            align,
+            drop_fn: None,
+            fingerprint: 0,
+        }
+    }
+
+    /// Attaches a fingerprint of the type's internal layout (e.g. a hash of its field offsets),
+    /// which is compared along with its size and alignment when adopting component data from
+    /// another world. See also `World::set_component_fingerprint`.
+    pub fn with_fingerprint(mut self, fingerprint: u64) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// Gets the size of the component type.
+    pub fn size(&self) -> usize { self.size }
+
+    /// Gets the alignment of the component type.
+    pub fn align(&self) -> usize { self.align }
+
+    /// Gets the layout of the component type.
+    pub fn layout_fingerprint(&self) -> LayoutFingerprint {
+        LayoutFingerprint {
+            size: self.size,
+            align: self.align,
+            fingerprint: self.fingerprint,
         }
     }
 }
 
+/// Describes the memory layout of a component type.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LayoutFingerprint {
+    pub size: usize,
+    pub align: usize,
+    /// A user provided fingerprint of the type's internal layout, or `0` if none was given.
+    pub fingerprint: u64,
+}
+
+/// An error returned when two worlds disagree on the memory layout of a component type.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LayoutMismatch {
+    /// The name of the component type.
+    pub type_name: &'static str,
+    /// The layout of the component in the destination world.
+    pub expected: LayoutFingerprint,
+    /// The layout of the component in the source world.
+    pub found: LayoutFingerprint,
+}
+
+impl std::fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "component layout mismatch for {}: expected {:?}, found {:?}",
+            self.type_name, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for LayoutMismatch {}
+
 /// Describes the layout of an archetype, including what components
 /// and tags shall be attached to entities stored within an archetype.
 #[derive(Default, Clone)]
@@ -337,6 +735,7 @@ pub struct ArchetypeDescription {
     components: Vec<(ComponentTypeId, ComponentMeta)>,
     tag_names: Vec<&'static str>,
     component_names: Vec<&'static str>,
+    entity_threshold: Option<usize>,
 }
 
 impl ArchetypeDescription {
@@ -346,6 +745,25 @@ impl ArchetypeDescription {
     /// Gets a slice of the components in the description.
     pub fn components(&self) -> &[(ComponentTypeId, ComponentMeta)] { &self.components }
 
+    /// Gets the type names of the components in the description, in the same order as
+    /// `components`.
+    pub fn component_names(&self) -> &[&'static str] { &self.component_names }
+
+    /// Gets the type names of the tags in the description, in the same order as `tags`.
+    pub fn tag_names(&self) -> &[&'static str] { &self.tag_names }
+
+    /// Gets the number of entities above which the archetype emits
+    /// `Event::EntityThresholdExceeded`.
+    pub fn entity_threshold(&self) -> Option<usize> { self.entity_threshold }
+
+    /// Sets the number of entities above which the archetype emits
+    /// `Event::EntityThresholdExceeded`, overriding `WorldOptions::entity_threshold`.
+    ///
+    /// The threshold is only read when the archetype is created.
+    pub fn set_entity_threshold(&mut self, threshold: Option<usize>) {
+        self.entity_threshold = threshold;
+    }
+
     /// Adds a tag to the description.
     pub fn register_tag_raw(&mut self, type_id: TagTypeId, type_meta: TagMeta) {
         self.tags.push((type_id, type_meta));
@@ -364,6 +782,17 @@ impl ArchetypeDescription {
         self.component_names.push("<unknown>");
     }
 
+    /// Adds a component to the description, with the given type name.
+    pub(crate) fn register_component_named(
+        &mut self,
+        type_id: ComponentTypeId,
+        type_meta: ComponentMeta,
+        name: &'static str,
+    ) {
+        self.components.push((type_id, type_meta));
+        self.component_names.push(name);
+    }
+
     /// Adds a component to the description.
     pub fn register_component<T: Component>(&mut self) {
         self.components
@@ -390,6 +819,32 @@ impl<'a> Filter<ArchetypeFilterData<'a>> for ArchetypeDescription {
 }
 
 const MAX_CHUNK_SIZE: usize = 16 * 1024;
+
+#[cfg(feature = "prefetch")]
+const CACHE_LINE_SIZE: usize = 64;
+
+/// Prefetches the cache line containing `ptr` into all levels of the cache. This is a no-op on
+/// architectures without a stable prefetch intrinsic.
+#[cfg(feature = "prefetch")]
+#[inline(always)]
+unsafe fn prefetch_line(ptr: *const u8) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(ptr as *const i8);
+    }
+    #[cfg(all(target_arch = "x86", target_feature = "sse"))]
+    {
+        use std::arch::x86::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(ptr as *const i8);
+    }
+    #[cfg(not(any(
+        target_arch = "x86_64",
+        all(target_arch = "x86", target_feature = "sse")
+    )))]
+    let _ = ptr;
+}
+pub(crate) const DEFAULT_GROWTH_FACTOR: f32 = 2.0;
 const COMPONENT_STORAGE_ALIGNMENT: usize = 64;
 
 /// Unique ID of an archetype.
@@ -399,7 +854,7 @@ pub struct ArchetypeId(WorldId, usize);
 impl ArchetypeId {
     pub(crate) fn new(world_id: WorldId, index: usize) -> Self { ArchetypeId(world_id, index) }
 
-    fn index(self) -> usize { self.1 }
+    pub(crate) fn index(self) -> usize { self.1 }
 
     fn world(self) -> WorldId { self.0 }
 }
@@ -467,7 +922,7 @@ impl DynamicTagSet {
         unsafe {
             if meta.is_zero_sized() {
                 self.tags
-                    .push((type_id, meta, NonNull::new(meta.align as *mut u8).unwrap()));
+                    .push((type_id, meta, NonNull::new(dangling(meta.align)).unwrap()));
             } else {
                 let copy = std::alloc::alloc(meta.layout());
                 meta.clone(value.as_ptr(), copy);
@@ -548,6 +1003,10 @@ pub struct ArchetypeData {
     component_layout: ComponentStorageLayout,
     chunk_sets: Vec<Chunkset>,
     subscribers: Subscribers,
+    growth_factor: f32,
+    placement_groups: u8,
+    pinned_placement: Option<u8>,
+    next_placement: u8,
 }
 
 impl ArchetypeData {
@@ -580,6 +1039,7 @@ impl ArchetypeData {
             component_data_offsets.push((*type_id, data_capacity, *meta));
             data_capacity += meta.size * entity_capacity;
         }
+        let usage = Arc::new(ArchetypeUsage::new(desc.entity_threshold));
         let data_alignment =
             std::alloc::Layout::from_size_align(data_capacity, COMPONENT_STORAGE_ALIGNMENT)
                 .expect("invalid component data size/alignment");
@@ -592,9 +1052,20 @@ impl ArchetypeData {
                 capacity: entity_capacity,
                 alloc_layout: data_alignment,
                 data_layout: component_data_offsets,
+                tracked_writes: Vec::new(),
+                summaries: Vec::new(),
+                spawn_clock: None,
+                write_epochs: None,
+                usage,
+                #[cfg(feature = "numa")]
+                allocator: None,
             },
             chunk_sets: Vec::new(),
             subscribers: Subscribers::default(),
+            growth_factor: DEFAULT_GROWTH_FACTOR,
+            placement_groups: 1,
+            pinned_placement: None,
+            next_placement: 0,
         }
     }
 
@@ -677,11 +1148,147 @@ impl ArchetypeData {
         }
 
         self.tags.validate(self.chunk_sets.len());
+
+        // chunks merged in from another world may not have been tracking writes
+        for type_id in self.component_layout.tracked_writes.clone() {
+            self.track_writes(type_id);
+        }
+
+        // chunks merged in from another world may not have been summarized
+        for (type_id, def) in self.component_layout.summaries.clone() {
+            self.enable_summary(type_id, def);
+        }
+
+        // chunks merged in from another world may not have been recording spawn ticks
+        if let Some(clock) = self.component_layout.spawn_clock.clone() {
+            self.record_spawn_ticks(clock);
+        }
+
+        // chunks merged in from another world may not have been using write epochs
+        if let Some(epochs) = self.component_layout.write_epochs.clone() {
+            self.record_write_epochs(epochs);
+        }
+
+        // chunks merged in from another world are counted by their previous archetype
+        let usage = self.component_layout.usage.clone();
+        let (mut entities, mut chunks) = (0, 0);
+        for set in self.chunk_sets.iter_mut() {
+            for chunk in set.chunks.iter_mut() {
+                entities += chunk.len();
+                chunks += chunk.is_allocated() as usize;
+                chunk.usage = usage.clone();
+            }
+        }
+        usage.recount(entities, chunks);
+    }
+
+    /// Gets the number of entities and allocated chunks in the archetype, along with their
+    /// high-water marks.
+    pub fn stats(&self) -> ArchetypeStats {
+        let usage = &self.component_layout.usage;
+        ArchetypeStats {
+            archetype: self.id,
+            entities: usage.entities.load(Ordering::Relaxed),
+            chunks: usage.chunks.load(Ordering::Relaxed),
+            allocated_bytes: usage.chunks.load(Ordering::Relaxed)
+                * self.component_layout.alloc_layout.size(),
+            peak_entities: usage.peak_entities.load(Ordering::Relaxed),
+            peak_chunks: usage.peak_chunks.load(Ordering::Relaxed),
+            entity_threshold: usage.threshold,
+        }
+    }
+
+    /// Resets the archetype's high-water marks to its current usage, and re-arms its
+    /// threshold event.
+    pub(crate) fn reset_high_water_marks(&self) { self.component_layout.usage.reset(); }
+
+    /// Sets the layout fingerprint of the given component type, if the archetype contains it.
+    pub(crate) fn set_component_fingerprint(&mut self, type_id: ComponentTypeId, fingerprint: u64) {
+        for (_, meta) in self
+            .desc
+            .components
+            .iter_mut()
+            .filter(|(t, _)| *t == type_id)
+        {
+            meta.fingerprint = fingerprint;
+        }
+        for (_, _, meta) in self
+            .component_layout
+            .data_layout
+            .iter_mut()
+            .filter(|(t, _, _)| *t == type_id)
+        {
+            meta.fingerprint = fingerprint;
+        }
+    }
+
+    /// Enables per-entity write tracking for the given component type, if the archetype
+    /// contains it.
+    pub(crate) fn track_writes(&mut self, type_id: ComponentTypeId) {
+        if !self.desc.components.iter().any(|(t, _)| *t == type_id) {
+            return;
+        }
+
+        if !self.component_layout.tracked_writes.contains(&type_id) {
+            self.component_layout.tracked_writes.push(type_id);
+        }
+
+        for set in self.chunk_sets.iter_mut() {
+            for chunk in set.chunks.iter_mut() {
+                if let Some(components) = chunk.component_info.get_mut().get_mut(type_id) {
+                    components.track_writes();
+                }
+            }
+        }
+    }
+
+    /// Enables per-chunk summaries of the given component type, if the archetype contains it.
+    pub(crate) fn enable_summary(&mut self, type_id: ComponentTypeId, def: SummaryDef) {
+        if !self.desc.components.iter().any(|(t, _)| *t == type_id) {
+            return;
+        }
+
+        let summaries = &mut self.component_layout.summaries;
+        summaries.retain(|(t, _)| *t != type_id);
+        summaries.push((type_id, def));
+
+        for set in self.chunk_sets.iter_mut() {
+            for chunk in set.chunks.iter_mut() {
+                if let Some(components) = chunk.component_info.get_mut().get_mut(type_id) {
+                    components.summary = Some(SummaryCache::new(def));
+                }
+            }
+        }
+    }
+
+    /// Enables recording of the tick at which each entity was spawned, reading the current
+    /// tick from `clock`.
+    pub(crate) fn record_spawn_ticks(&mut self, clock: Arc<AtomicU64>) {
+        for set in self.chunk_sets.iter_mut() {
+            for chunk in set.chunks.iter_mut() {
+                chunk.record_spawn_ticks(clock.clone());
+            }
+        }
+        self.component_layout.spawn_clock = Some(clock);
+    }
+
+    /// Limits version increments to one per chunk within each of the given write epochs.
+    pub(crate) fn record_write_epochs(&mut self, epochs: Arc<WriteEpochs>) {
+        for set in self.chunk_sets.iter_mut() {
+            for chunk in set.chunks.iter_mut() {
+                for (_, components) in chunk.component_info.get_mut().iter_mut() {
+                    components.write_epochs = Some(epochs.clone());
+                    // epochs recorded by another world's chunks may collide with these ones
+                    *components.write_epoch.get_mut() = 0;
+                }
+            }
+        }
+        self.component_layout.write_epochs = Some(epochs);
     }
 
     pub(crate) fn enumerate_entities<'a>(
         &'a self,
-        archetype_index: usize,
+        archetype_index: ArchetypeIndex,
     ) -> impl Iterator<Item = (Entity, EntityLocation)> + 'a {
         self.chunk_sets
             .iter()
@@ -700,9 +1307,9 @@ impl ArchetypeData {
                                     *entity,
                                     EntityLocation::new(
                                         archetype_index,
-                                        set_index,
-                                        chunk_index,
-                                        entity_index,
+                                        SetIndex(set_index),
+                                        LocalChunkIndex(chunk_index),
+                                        SlotIndex(entity_index),
                                     ),
                                 )
                             })
@@ -712,6 +1319,7 @@ impl ArchetypeData {
 
     fn push<F: FnMut(&mut Tags)>(&mut self, set: Chunkset, mut initialize: F) {
         initialize(&mut self.tags);
+        grow_for_push(&mut self.chunk_sets, self.growth_factor);
         self.chunk_sets.push(set);
 
         let index = self.chunk_sets.len() - 1;
@@ -727,40 +1335,157 @@ impl ArchetypeData {
     /// Allocates a new chunk set. Returns the index of the new set.
     ///
     /// `initialize` is expected to push the new chunkset's tag values onto the tags collection.
-    pub(crate) fn alloc_chunk_set<F: FnMut(&mut Tags)>(&mut self, initialize: F) -> usize {
+    pub(crate) fn alloc_chunk_set<F: FnMut(&mut Tags)>(&mut self, initialize: F) -> SetIndex {
         self.push(Chunkset::default(), initialize);
-        self.chunk_sets.len() - 1
+        SetIndex(self.chunk_sets.len() - 1)
     }
 
     /// Finds a chunk with space free for at least one entity, creating one if needed.
-    pub(crate) fn get_free_chunk(&mut self, set_index: usize) -> usize {
-        let count = {
-            let chunks = &mut self.chunk_sets[set_index];
-            let len = chunks.len();
+    pub(crate) fn get_free_chunk(&mut self, set_index: SetIndex) -> LocalChunkIndex {
+        {
+            let chunks = &mut self.chunk_sets[set_index.0];
             for (i, chunk) in chunks.iter_mut().enumerate() {
                 if !chunk.is_full() {
-                    return i;
+                    return LocalChunkIndex(i);
                 }
             }
-            len
-        };
+        }
 
-        let chunk = self
-            .component_layout
-            .alloc_storage(ChunkId(self.id, set_index, count));
-        unsafe { self.chunk_sets.get_unchecked_mut(set_index).push(chunk) };
+        self.alloc_chunk(set_index)
+    }
+
+    /// Clones the entity at `index` within a chunk, as `entity`, into a chunk of the same set
+    /// with free space.
+    ///
+    /// Returns the index of the target chunk and of the clone within it.
+    ///
+    /// # Safety
+    ///
+    /// `cloner` must be able to clone every component type in the archetype.
+    pub(crate) unsafe fn clone_entity(
+        &mut self,
+        set_index: SetIndex,
+        chunk_index: LocalChunkIndex,
+        index: SlotIndex,
+        entity: Entity,
+        cloner: &CloneImpl,
+        remap: &dyn Fn(Entity) -> Entity,
+    ) -> (LocalChunkIndex, SlotIndex) {
+        let target_index = self.get_free_chunk(set_index);
+        let chunks = self.chunk_sets[set_index.0].chunks.as_mut_ptr();
+
+        // the target may be the source chunk, so read the source pointers up front; they
+        // remain valid as the target is only appended to
+        let source = &*chunks.add(chunk_index.0);
+        let source_id = source.id;
+        let sources = self
+            .desc
+            .components
+            .iter()
+            .map(|(type_id, _)| {
+                let (ptr, size, _) = source.components(*type_id).unwrap().data_raw();
+                (*type_id, (*ptr).add(size * index.0) as *const u8)
+            })
+            .collect::<Vec<_>>();
+
+        let target = &mut *chunks.add(target_index.0);
+        trace!(index = index.0, source = ?source_id, destination = ?target.id, "Cloning entity");
+
+        let mut writer = target.writer();
+        let (entities, components) = writer.get();
+        for (type_id, src) in sources {
+            let accessor = (&mut *components.get()).get_mut(type_id).unwrap();
+            let mut component_writer = accessor.writer();
+            cloner.clone(type_id, src, component_writer.spare_ptr(), remap);
+            component_writer.commit_raw(1);
+        }
+        entities.push(entity);
+        let component_index = SlotIndex(entities.len() - 1);
+
+        (target_index, component_index)
+    }
+
+    /// Reserves capacity for at least `additional` more chunk sets.
+    pub(crate) fn reserve_chunk_sets(&mut self, additional: usize) {
+        self.chunk_sets.reserve(additional);
+        for (_, tags) in self.tags.0.iter_mut() {
+            tags.reserve(additional);
+        }
+    }
+
+    /// Ensures the given chunk set contains at least `count` chunks, creating empty chunks
+    /// as needed.
+    ///
+    /// Chunk memory is only allocated when entities are first written into each chunk.
+    pub(crate) fn reserve_chunks(&mut self, set_index: SetIndex, count: usize) {
+        let chunks = &mut self.chunk_sets[set_index.0].chunks;
+        chunks.reserve(count.saturating_sub(chunks.len()));
+        while self.chunk_sets[set_index.0].len() < count {
+            self.alloc_chunk(set_index);
+        }
+    }
+
+    /// Releases trailing empty chunks and any excess capacity in the chunk vectors.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        for set in self.chunk_sets.iter_mut() {
+            let occupied = set.occupied().len();
+            set.chunks.truncate(occupied);
+            set.chunks.shrink_to_fit();
+        }
+        self.chunk_sets.shrink_to_fit();
+    }
+
+    /// Gets the placement hint given to all chunks created in this archetype, if it has been
+    /// pinned to one with `World::set_archetype_placement`.
+    pub fn placement(&self) -> Option<u8> { self.pinned_placement }
+
+    pub(crate) fn set_placement(&mut self, placement: Option<u8>) {
+        self.pinned_placement = placement;
+
+        // chunks without memory can still be moved
+        if let Some(placement) = placement {
+            for set in self.chunk_sets.iter_mut() {
+                for chunk in set.chunks.iter_mut().filter(|chunk| !chunk.is_allocated()) {
+                    chunk.placement = placement;
+                }
+            }
+        }
+    }
+
+    /// Gets the placement hint of the next chunk, cycling through the placement groups unless
+    /// the archetype has been pinned to one.
+    fn next_placement(&mut self) -> u8 {
+        if let Some(placement) = self.pinned_placement {
+            return placement;
+        }
+        let placement = self.next_placement % self.placement_groups;
+        self.next_placement = (placement + 1) % self.placement_groups;
+        placement
+    }
+
+    /// Appends a new empty chunk to the given chunk set. Returns the index of the new chunk.
+    fn alloc_chunk(&mut self, set_index: SetIndex) -> LocalChunkIndex {
+        let count = self.chunk_sets[set_index.0].len();
+        let placement = self.next_placement();
+        let chunk = self.component_layout.alloc_storage(
+            ChunkId(self.id, set_index, LocalChunkIndex(count)),
+            placement,
+        );
+        let set = unsafe { self.chunk_sets.get_unchecked_mut(set_index.0) };
+        grow_for_push(&mut set.chunks, self.growth_factor);
+        set.push(chunk);
 
         trace!(
             world = self.id.world().index(),
             archetype = self.id.index(),
-            chunkset = set_index,
+            chunkset = set_index.0,
             chunk = count,
             components = ?self.desc.component_names,
             tags = ?self.desc.tag_names,
             "Created chunk"
         );
 
-        count
+        LocalChunkIndex(count)
     }
 
     /// Gets the number of chunk sets stored within this archetype.
@@ -778,6 +1503,14 @@ impl ArchetypeData {
     /// Gets a mutable slice of chunksets.
     pub fn chunksets_mut(&mut self) -> &mut [Chunkset] { &mut self.chunk_sets }
 
+    /// Gets the chunkset at the given index.
+    pub fn chunkset(&self, index: SetIndex) -> Option<&Chunkset> { self.chunk_sets.get(index.0) }
+
+    /// Gets the chunkset at the given index mutably.
+    pub fn chunkset_mut(&mut self, index: SetIndex) -> Option<&mut Chunkset> {
+        self.chunk_sets.get_mut(index.0)
+    }
+
     /// Gets a description of the component types in the archetype.
     pub fn description(&self) -> &ArchetypeDescription { &self.desc }
 
@@ -791,10 +1524,13 @@ impl ArchetypeData {
             archetype = self.id().index(),
             "Defragmenting archetype"
         );
-        let arch_index = self.id.index();
+        let arch_index = ArchetypeIndex(self.id.index());
         for (i, chunkset) in self.chunk_sets.iter_mut().enumerate() {
             let complete = chunkset.defrag(budget, |e, chunk, component| {
-                on_moved(e, EntityLocation::new(arch_index, i, chunk, component));
+                on_moved(
+                    e,
+                    EntityLocation::new(arch_index, SetIndex(i), chunk, component),
+                );
             });
             if !complete {
                 return false;
@@ -807,11 +1543,27 @@ impl ArchetypeData {
 
 fn align_up(addr: usize, align: usize) -> usize { (addr + (align - 1)) & align.wrapping_neg() }
 
+/// Grows a full vec by `factor` of its current capacity, such that the next push does not
+/// reallocate.
+fn grow_for_push<T>(vec: &mut Vec<T>, factor: f32) {
+    if vec.len() == vec.capacity() {
+        let additional = (vec.capacity() as f32 * (factor - 1.0)).ceil() as usize;
+        vec.reserve_exact(std::cmp::max(1, additional));
+    }
+}
+
 /// Describes the data layout for a chunk.
 pub struct ComponentStorageLayout {
     capacity: usize,
     alloc_layout: std::alloc::Layout,
     data_layout: Vec<(ComponentTypeId, usize, ComponentMeta)>,
+    tracked_writes: Vec<ComponentTypeId>,
+    summaries: Vec<(ComponentTypeId, SummaryDef)>,
+    spawn_clock: Option<Arc<AtomicU64>>,
+    write_epochs: Option<Arc<WriteEpochs>>,
+    usage: Arc<ArchetypeUsage>,
+    #[cfg(feature = "numa")]
+    allocator: Option<Arc<dyn ChunkAllocator>>,
 }
 
 impl ComponentStorageLayout {
@@ -821,7 +1573,7 @@ impl ComponentStorageLayout {
     /// The components in each chunk.
     pub fn components(&self) -> &[(ComponentTypeId, usize, ComponentMeta)] { &self.data_layout }
 
-    fn alloc_storage(&self, id: ChunkId) -> ComponentStorage {
+    fn alloc_storage(&self, id: ChunkId, placement: u8) -> ComponentStorage {
         let storage_info = self
             .data_layout
             .iter()
@@ -829,12 +1581,24 @@ impl ComponentStorageLayout {
                 (
                     *ty,
                     ComponentResourceSet {
-                        ptr: AtomicRefCell::new(meta.align as *mut u8),
+                        ptr: AtomicRefCell::new(dangling(meta.align)),
                         capacity: self.capacity,
                         count: UnsafeCell::new(0),
                         element_size: meta.size,
                         drop_fn: meta.drop_fn,
-                        version: UnsafeCell::new(0),
+                        version: UnsafeCell::new(Version::NONE),
+                        dirty: if self.tracked_writes.contains(ty) {
+                            Some(DirtySlots::new(self.capacity))
+                        } else {
+                            None
+                        },
+                        summary: self
+                            .summaries
+                            .iter()
+                            .find(|(t, _)| t == ty)
+                            .map(|(_, def)| SummaryCache::new(*def)),
+                        write_epochs: self.write_epochs.clone(),
+                        write_epoch: UnsafeCell::new(0),
                     },
                 )
             })
@@ -842,6 +1606,7 @@ impl ComponentStorageLayout {
 
         ComponentStorage {
             id,
+            dense_index: ChunkIndex::alloc(),
             capacity: self.capacity,
             entities: Vec::with_capacity(self.capacity),
             component_offsets: self
@@ -852,42 +1617,169 @@ impl ComponentStorageLayout {
             component_layout: self.alloc_layout,
             component_info: UnsafeCell::new(Components::new(storage_info)),
             component_data: None,
+            spawn_ticks: self
+                .spawn_clock
+                .as_ref()
+                .map(|clock| SpawnTicks::new(clock.clone(), self.capacity)),
+            usage: self.usage.clone(),
             subscribers: Subscribers::default(),
+            placement,
+            #[cfg(feature = "numa")]
+            allocator: self.allocator.clone(),
         }
     }
 }
 
-/// Contains chunks with the same layout and tag values.
-#[derive(Default)]
-pub struct Chunkset {
-    chunks: Vec<ComponentStorage>,
-    subscribers: Subscribers,
-}
+/// Allocates the memory of chunks, such as from the memory of the NUMA node whose threads
+/// process each placement group. See `World::set_chunk_allocator` and
+/// `ComponentStorage::placement`.
+///
+/// # Safety
+///
+/// Implementations must return memory which satisfies the requested layout, as
+/// `std::alloc::GlobalAlloc` does.
+#[cfg(feature = "numa")]
+pub unsafe trait ChunkAllocator: Send + Sync {
+    /// Allocates memory for a chunk with the given placement hint, or returns null on failure.
+    ///
+    /// # Safety
+    ///
+    /// `layout` has a non-zero size.
+    unsafe fn alloc(&self, layout: std::alloc::Layout, placement: u8) -> *mut u8;
 
-impl Deref for Chunkset {
-    type Target = [ComponentStorage];
+    /// Frees memory returned by `alloc`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` was returned by `alloc` on this allocator, with the same `layout` and `placement`.
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout, placement: u8);
+}
 
-    fn deref(&self) -> &Self::Target { self.chunks.as_slice() }
+/// The number of entities and allocated chunks in an archetype, along with their high-water
+/// marks since the world was created or since `World::reset_high_water_marks` was last called.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ArchetypeStats {
+    /// The archetype the statistics describe.
+    pub archetype: ArchetypeId,
+    /// The number of entities in the archetype.
+    pub entities: usize,
+    /// The number of chunks with allocated memory in the archetype.
+    pub chunks: usize,
+    /// The number of bytes of component memory allocated by the archetype's chunks.
+    pub allocated_bytes: usize,
+    /// The greatest number of entities simultaneously stored in the archetype.
+    pub peak_entities: usize,
+    /// The greatest number of chunks simultaneously allocated by the archetype.
+    pub peak_chunks: usize,
+    /// The number of entities above which the archetype emits
+    /// `Event::EntityThresholdExceeded`.
+    pub entity_threshold: Option<usize>,
 }
 
-impl DerefMut for Chunkset {
-    fn deref_mut(&mut self) -> &mut Self::Target { self.chunks.as_mut_slice() }
+/// Counts the entities and allocated chunks of an archetype. Shared by all of the archetype's
+/// chunks, which update it as entities are inserted and removed.
+struct ArchetypeUsage {
+    entities: AtomicUsize,
+    chunks: AtomicUsize,
+    peak_entities: AtomicUsize,
+    peak_chunks: AtomicUsize,
+    threshold: Option<usize>,
+    armed: AtomicBool,
 }
 
-impl Chunkset {
-    pub(crate) fn new() -> Self {
+impl ArchetypeUsage {
+    fn new(threshold: Option<usize>) -> Self {
         Self {
-            chunks: Vec::new(),
-            subscribers: Subscribers::default(),
+            entities: AtomicUsize::new(0),
+            chunks: AtomicUsize::new(0),
+            peak_entities: AtomicUsize::new(0),
+            peak_chunks: AtomicUsize::new(0),
+            threshold,
+            armed: AtomicBool::new(true),
         }
     }
 
-    /// Pushes a new chunk into the set.
-    pub fn push(&mut self, chunk: ComponentStorage) {
-        let id = chunk.id();
-        self.chunks.push(chunk);
+    /// Records added entities. Returns the new entity count if it has exceeded the threshold
+    /// for the first time since it was last at or below it, or since the last reset.
+    fn add_entities(&self, count: usize) -> Option<usize> {
+        let entities = self.entities.fetch_add(count, Ordering::Relaxed) + count;
+        self.peak_entities.fetch_max(entities, Ordering::Relaxed);
+        let exceeded = matches!(self.threshold, Some(threshold) if entities > threshold);
+        if exceeded && self.armed.swap(false, Ordering::Relaxed) {
+            Some(entities)
+        } else {
+            None
+        }
+    }
 
-        let index = self.chunks.len() - 1;
+    /// Records removed entities, re-arming the threshold if the count has dropped back to it.
+    fn remove_entities(&self, count: usize) {
+        let entities = self.entities.fetch_sub(count, Ordering::Relaxed) - count;
+        self.rearm(entities);
+    }
+
+    fn rearm(&self, entities: usize) {
+        if matches!(self.threshold, Some(threshold) if entities <= threshold) {
+            self.armed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn add_chunk(&self) {
+        let chunks = self.chunks.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak_chunks.fetch_max(chunks, Ordering::Relaxed);
+    }
+
+    fn remove_chunk(&self) { self.chunks.fetch_sub(1, Ordering::Relaxed); }
+
+    fn recount(&self, entities: usize, chunks: usize) {
+        self.entities.store(entities, Ordering::Relaxed);
+        self.chunks.store(chunks, Ordering::Relaxed);
+        self.peak_entities.fetch_max(entities, Ordering::Relaxed);
+        self.peak_chunks.fetch_max(chunks, Ordering::Relaxed);
+        self.rearm(entities);
+    }
+
+    fn reset(&self) {
+        let entities = self.entities.load(Ordering::Relaxed);
+        let chunks = self.chunks.load(Ordering::Relaxed);
+        self.peak_entities.store(entities, Ordering::Relaxed);
+        self.peak_chunks.store(chunks, Ordering::Relaxed);
+        self.armed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Contains chunks with the same layout and tag values.
+#[derive(Default)]
+pub struct Chunkset {
+    chunks: Vec<ComponentStorage>,
+    subscribers: Subscribers,
+}
+
+impl Deref for Chunkset {
+    type Target = [ComponentStorage];
+
+    fn deref(&self) -> &Self::Target { self.chunks.as_slice() }
+}
+
+impl DerefMut for Chunkset {
+    fn deref_mut(&mut self) -> &mut Self::Target { self.chunks.as_mut_slice() }
+}
+
+impl Chunkset {
+    pub(crate) fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    /// Pushes a new chunk into the set.
+    pub fn push(&mut self, chunk: ComponentStorage) {
+        let id = chunk.id();
+        self.chunks.push(chunk);
+
+        let index = self.chunks.len() - 1;
         let filter = ChunkFilterData {
             chunks: &self.chunks,
         };
@@ -930,6 +1822,16 @@ impl Chunkset {
         self.chunks.drain(range)
     }
 
+    /// Gets the chunk at the given index.
+    pub fn chunk(&self, index: LocalChunkIndex) -> Option<&ComponentStorage> {
+        self.chunks.get(index.0)
+    }
+
+    /// Gets the chunk at the given index mutably.
+    pub fn chunk_mut(&mut self, index: LocalChunkIndex) -> Option<&mut ComponentStorage> {
+        self.chunks.get_mut(index.0)
+    }
+
     /// Gets a slice reference to occupied chunks.
     pub fn occupied(&self) -> &[ComponentStorage] {
         let mut len = self.chunks.len();
@@ -968,7 +1870,7 @@ impl Chunkset {
     /// new component index.
     ///
     /// Returns whether or not the chunkset has been fully defragmented.
-    fn defrag<F: FnMut(Entity, usize, usize)>(
+    fn defrag<F: FnMut(Entity, LocalChunkIndex, SlotIndex)>(
         &mut self,
         budget: &mut usize,
         mut on_moved: F,
@@ -1018,7 +1920,11 @@ impl Chunkset {
                 assert!(swapped.is_none());
 
                 // notify move
-                on_moved(*target.entities.last().unwrap(), first, target.len() - 1);
+                on_moved(
+                    *target.entities.last().unwrap(),
+                    LocalChunkIndex(first),
+                    SlotIndex(target.len() - 1),
+                );
 
                 // exit if we cant move any more
                 if target.is_full() || source.is_empty() {
@@ -1029,66 +1935,250 @@ impl Chunkset {
     }
 }
 
-/// Unique ID of a chunk.
+/// The index of an archetype within a world's storage.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Debug)]
+pub struct ArchetypeIndex(usize);
+
+impl ArchetypeIndex {
+    pub(crate) fn new(index: usize) -> Self { ArchetypeIndex(index) }
+
+    /// Gets the index as a `usize`, e.g. to index `Storage::archetypes`.
+    pub fn as_usize(self) -> usize { self.0 }
+}
+
+/// The index of a chunkset within its archetype.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Debug)]
+pub struct SetIndex(usize);
+
+impl SetIndex {
+    pub(crate) fn new(index: usize) -> Self { SetIndex(index) }
+
+    /// Gets the index as a `usize`, e.g. to index `ArchetypeData::chunksets`.
+    pub fn as_usize(self) -> usize { self.0 }
+}
+
+/// The index of a chunk within its chunkset.
+///
+/// Unlike a chunk's dense `ChunkIndex`, which identifies the chunk for as long as it is alive,
+/// this is the chunk's position within the chunkset.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Debug)]
+pub struct LocalChunkIndex(usize);
+
+impl LocalChunkIndex {
+    pub(crate) fn new(index: usize) -> Self { LocalChunkIndex(index) }
+
+    /// Gets the index as a `usize`, e.g. to index `Chunkset::occupied`.
+    pub fn as_usize(self) -> usize { self.0 }
+}
+
+/// The index of an entity's components within its chunk.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Debug)]
+pub struct SlotIndex(usize);
+
+impl SlotIndex {
+    pub(crate) fn new(index: usize) -> Self { SlotIndex(index) }
+
+    /// Gets the index as a `usize`, e.g. to index `ComponentStorage::entities`.
+    pub fn as_usize(self) -> usize { self.0 }
+}
+
+/// ID of a chunk, unique among the chunks which currently exist.
+///
+/// A chunk keeps its ID for as long as it exists in its world, however its entities change.
+/// Once a chunk has been freed, such as by `World::defrag`, a chunk later allocated in the same
+/// chunkset may reuse its ID. State which must not carry over to such a chunk should be keyed
+/// by the chunk's `ChunkIndex` instead, whose generation distinguishes each reuse.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
-pub struct ChunkId(ArchetypeId, usize, usize);
+pub struct ChunkId(ArchetypeId, SetIndex, LocalChunkIndex);
 
 impl ChunkId {
-    pub(crate) fn new(archetype: ArchetypeId, set: usize, index: usize) -> Self {
+    pub(crate) fn new(archetype: ArchetypeId, set: SetIndex, index: LocalChunkIndex) -> Self {
         ChunkId(archetype, set, index)
     }
 
+    /// Gets the ID of the chunk's archetype.
     pub fn archetype_id(&self) -> ArchetypeId { self.0 }
 
-    pub(crate) fn set(&self) -> usize { self.1 }
+    /// Gets the index of the chunk's chunkset within its archetype.
+    pub fn set(&self) -> SetIndex { self.1 }
+
+    /// Gets the index of the chunk within its chunkset.
+    pub fn index(&self) -> LocalChunkIndex { self.2 }
+}
+
+/// A dense index identifying a live chunk.
+///
+/// Indices are unique among all live chunks in the process and are recycled when chunks are
+/// released, with a generation which distinguishes each reuse of an index. This allows per-chunk
+/// state to be stored in a `Vec` rather than a hash map; see `ChunkStateMap` and
+/// `ChunkVersionMap`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ChunkIndex {
+    index: u32,
+    generation: u32,
+}
+
+struct ChunkIndexAllocator {
+    generations: Vec<u32>,
+    free: Vec<u32>,
+}
+
+static CHUNK_INDICES: std::sync::Mutex<ChunkIndexAllocator> =
+    std::sync::Mutex::new(ChunkIndexAllocator {
+        generations: Vec::new(),
+        free: Vec::new(),
+    });
 
-    pub(crate) fn index(&self) -> usize { self.2 }
+impl ChunkIndex {
+    pub(crate) fn new(index: u32, generation: u32) -> Self { ChunkIndex { index, generation } }
+
+    /// Gets the index of the chunk.
+    pub fn index(&self) -> usize { self.index as usize }
+
+    /// Gets the generation of the index.
+    pub fn generation(&self) -> u32 { self.generation }
+
+    pub(crate) fn alloc() -> Self {
+        let mut indices = CHUNK_INDICES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = indices.free.pop() {
+            ChunkIndex::new(index, indices.generations[index as usize])
+        } else {
+            let index = indices.generations.len() as u32;
+            indices.generations.push(0);
+            ChunkIndex::new(index, 0)
+        }
+    }
+
+    /// Determines if the index has not been released since it was allocated.
+    pub(crate) fn is_alive(self) -> bool {
+        let indices = CHUNK_INDICES.lock().unwrap_or_else(|e| e.into_inner());
+        // releasing an index advances its generation
+        indices.generations.get(self.index as usize) == Some(&self.generation)
+    }
+
+    pub(crate) fn free(self) {
+        let mut indices = CHUNK_INDICES.lock().unwrap_or_else(|e| e.into_inner());
+        let generation = &mut indices.generations[self.index as usize];
+        *generation = generation.wrapping_add(1);
+        indices.free.push(self.index);
+    }
 }
 
 /// A set of component slices located on a chunk.
-pub struct Components(SmallVec<[(ComponentTypeId, ComponentResourceSet); 5]>);
+pub struct Components {
+    data: SmallVec<[(ComponentTypeId, ComponentResourceSet); 5]>,
+    // indices into `data` in the order in which the archetype declares its component types
+    declared: SmallVec<[usize; 5]>,
+}
 
 impl Components {
-    pub(crate) fn new(mut data: SmallVec<[(ComponentTypeId, ComponentResourceSet); 5]>) -> Self {
-        data.sort_by_key(|(t, _)| *t);
-        Self(data)
+    pub(crate) fn new(data: SmallVec<[(ComponentTypeId, ComponentResourceSet); 5]>) -> Self {
+        let mut data = data.into_iter().enumerate().collect::<SmallVec<[_; 5]>>();
+        data.sort_by_key(|(_, (t, _))| *t);
+        let mut declared = SmallVec::from_elem(0, data.len());
+        for (sorted, (index, _)) in data.iter().enumerate() {
+            declared[*index] = sorted;
+        }
+        Self {
+            data: data.into_iter().map(|(_, component)| component).collect(),
+            declared,
+        }
     }
 
     /// Gets a component slice accessor for the specified component type.
     #[inline]
     pub fn get(&self, type_id: ComponentTypeId) -> Option<&ComponentResourceSet> {
-        self.0
+        self.data
             .binary_search_by_key(&type_id, |(t, _)| *t)
             .ok()
-            .map(|i| unsafe { &self.0.get_unchecked(i).1 })
+            .map(|i| unsafe { &self.data.get_unchecked(i).1 })
     }
 
     /// Gets a mutable component slice accessor for the specified component type.
     #[inline]
     pub fn get_mut(&mut self, type_id: ComponentTypeId) -> Option<&mut ComponentResourceSet> {
-        self.0
+        self.data
             .binary_search_by_key(&type_id, |(t, _)| *t)
             .ok()
-            .map(move |i| unsafe { &mut self.0.get_unchecked_mut(i).1 })
+            .map(move |i| unsafe { &mut self.data.get_unchecked_mut(i).1 })
     }
 
-    fn iter(&mut self) -> Iter<(ComponentTypeId, ComponentResourceSet)> { self.0.iter() }
-
-    fn iter_mut(&mut self) -> IterMut<(ComponentTypeId, ComponentResourceSet)> { self.0.iter_mut() }
+    fn iter(&self) -> Iter<(ComponentTypeId, ComponentResourceSet)> { self.data.iter() }
 
-    fn drain(&mut self) -> Drain<(ComponentTypeId, ComponentResourceSet)> { self.0.drain() }
+    fn iter_mut(&mut self) -> IterMut<(ComponentTypeId, ComponentResourceSet)> {
+        self.data.iter_mut()
+    }
 }
 
 /// Stores a chunk of entities and their component data of a specific data layout.
 pub struct ComponentStorage {
     id: ChunkId,
+    dense_index: ChunkIndex,
     capacity: usize,
     entities: Vec<Entity>,
     component_layout: std::alloc::Layout,
     component_offsets: FxHashMap<ComponentTypeId, usize>,
     component_info: UnsafeCell<Components>,
     component_data: Option<NonNull<u8>>,
+    spawn_ticks: Option<SpawnTicks>,
+    usage: Arc<ArchetypeUsage>,
     subscribers: Subscribers,
+    placement: u8,
+    #[cfg(feature = "numa")]
+    allocator: Option<Arc<dyn ChunkAllocator>>,
+}
+
+/// Records the world tick at which each entity in a chunk was spawned, along with the
+/// range of ticks in the chunk.
+struct SpawnTicks {
+    clock: Arc<AtomicU64>,
+    ticks: Vec<u64>,
+    min: u64,
+    max: u64,
+}
+
+impl SpawnTicks {
+    fn new(clock: Arc<AtomicU64>, capacity: usize) -> Self {
+        Self {
+            clock,
+            ticks: Vec::with_capacity(capacity),
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    fn now(&self) -> u64 { self.clock.load(Ordering::Relaxed) }
+
+    fn push(&mut self, tick: u64) {
+        self.ticks.push(tick);
+        self.min = self.min.min(tick);
+        self.max = self.max.max(tick);
+    }
+
+    /// Records the current tick for any entities which do not yet have a spawn tick.
+    fn fill(&mut self, len: usize) {
+        let now = self.now();
+        while self.ticks.len() < len {
+            self.push(now);
+        }
+    }
+
+    fn swap_remove(&mut self, index: usize) -> u64 {
+        let removed = self.ticks.swap_remove(index);
+        if removed == self.min || removed == self.max {
+            self.min = self.ticks.iter().copied().min().unwrap_or(u64::MAX);
+            self.max = self.ticks.iter().copied().max().unwrap_or(0);
+        }
+        removed
+    }
+
+    fn range(&self) -> Option<(u64, u64)> {
+        if self.ticks.is_empty() {
+            None
+        } else {
+            Some((self.min, self.max))
+        }
+    }
 }
 
 pub struct StorageWriter<'a> {
@@ -1104,12 +2194,23 @@ impl<'a> StorageWriter<'a> {
 
 impl<'a> Drop for StorageWriter<'a> {
     fn drop(&mut self) {
+        self.storage.validate();
+        let len = self.storage.entities.len();
+        if let Some(spawn_ticks) = &mut self.storage.spawn_ticks {
+            spawn_ticks.fill(len);
+        }
+
         self.storage.update_count_gauge();
         for entity in self.storage.entities.iter().skip(self.initial_count) {
             self.storage
                 .subscribers
                 .send(Event::EntityInserted(*entity, self.storage.id()));
         }
+
+        let added = len.saturating_sub(self.initial_count);
+        if added > 0 {
+            self.storage.record_added(added);
+        }
     }
 }
 
@@ -1117,6 +2218,17 @@ impl ComponentStorage {
     /// Gets the unique ID of the chunk.
     pub fn id(&self) -> ChunkId { self.id }
 
+    /// Gets the placement hint of the chunk, which identifies the group of threads which should
+    /// process it, and the memory it was allocated from if a `ChunkAllocator` was set.
+    ///
+    /// Chunks are assigned to each of the world's `WorldOptions::placement_groups` in turn,
+    /// unless their archetype has been pinned with `World::set_archetype_placement`.
+    pub fn placement(&self) -> u8 { self.placement }
+
+    /// Gets the dense index of the chunk, which can be used to associate state with the chunk
+    /// in a `ChunkStateMap`.
+    pub fn dense_index(&self) -> ChunkIndex { self.dense_index }
+
     /// Gets the number of entities stored in the chunk.
     pub fn len(&self) -> usize { self.entities.len() }
 
@@ -1143,20 +2255,97 @@ impl ComponentStorage {
     /// Gets a slice reference containing the IDs of all entities stored in the chunk.
     pub fn entities(&self) -> &[Entity] { self.entities.as_slice() }
 
+    // every component array holds exactly one element for each entity in the chunk
+    fn validate(&self) {
+        for (type_id, components) in unsafe { &*self.component_info.get() }.iter() {
+            debug_assert_eq!(
+                self.entities.len(),
+                components.len(),
+                "chunk entity and {:?} component counts disagree",
+                type_id
+            );
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn truncate_entities(&mut self, len: usize) { self.entities.truncate(len); }
+
+    /// Gets the world tick at which each entity in the chunk was spawned, in the same order
+    /// as `entities`.
+    ///
+    /// Returns `None` if the world was not created with `WorldOptions::record_spawn_tick`.
+    pub fn spawn_ticks(&self) -> Option<&[u64]> {
+        self.spawn_ticks.as_ref().map(|s| s.ticks.as_slice())
+    }
+
+    /// Gets the earliest and latest spawn ticks of the entities in the chunk.
+    ///
+    /// Returns `None` if the chunk is empty or spawn ticks are not being recorded.
+    pub fn spawn_tick_range(&self) -> Option<(u64, u64)> {
+        self.spawn_ticks.as_ref().and_then(|s| s.range())
+    }
+
+    /// Starts recording spawn ticks, if the chunk is not already doing so. Entities already
+    /// in the chunk are recorded as spawned at the current tick.
+    fn record_spawn_ticks(&mut self, clock: Arc<AtomicU64>) {
+        let (len, capacity) = (self.len(), self.capacity);
+        let spawn_ticks = self
+            .spawn_ticks
+            .get_or_insert_with(|| SpawnTicks::new(clock.clone(), capacity));
+        spawn_ticks.clock = clock;
+        spawn_ticks.fill(len);
+    }
+
+    /// Releases any leaked borrows of the chunk's component slices.
+    ///
+    /// Returns the number of component slices which were recorded as borrowed.
+    pub(crate) fn clear_poisoned_borrows(&mut self) -> usize {
+        self.component_info
+            .get_mut()
+            .iter_mut()
+            .map(|(_, components)| components.ptr.reset_borrows())
+            .filter(|borrowed| *borrowed)
+            .count()
+    }
+
     /// Gets a component accessor for the specified component type.
     pub fn components(&self, component_type: ComponentTypeId) -> Option<&ComponentResourceSet> {
         unsafe { &*self.component_info.get() }.get(component_type)
     }
 
+    /// Gets the summary of the chunk's `T` components, recomputing it if they have been
+    /// modified since it was last requested.
+    ///
+    /// Returns `None` if the chunk is empty, does not contain `T`, or if `T` is not being
+    /// summarized by `S`. See `World::enable_summary`.
+    ///
+    /// # Panics
+    ///
+    /// Recomputing the summary borrows the `T` components, and so will panic if they are
+    /// currently borrowed mutably.
+    pub fn summary<T: Component, S: ComponentSummary<T>>(&self) -> Option<Arc<S>> {
+        let components = self.components(ComponentTypeId::of::<T>())?;
+        let cache = components.summary.as_ref()?;
+        unsafe { cache.get::<S>(components) }
+    }
+
     /// Removes an entity from the chunk by swapping it with the last entry.
     ///
     /// Returns the ID of the entity which was swapped into the removed entity's position.
     pub fn swap_remove(&mut self, index: usize, drop: bool) -> Option<Entity> {
         let removed = self.entities.swap_remove(index);
-        for (_, component) in unsafe { &mut *self.component_info.get() }.iter_mut() {
-            component.writer().swap_remove(index, drop);
+        // drop the removed components in the same order as when the chunk itself is dropped
+        let components = unsafe { &mut *self.component_info.get() };
+        for &i in components.declared.iter() {
+            components.data[i].1.writer().swap_remove(index, drop);
+        }
+
+        if let Some(spawn_ticks) = &mut self.spawn_ticks {
+            spawn_ticks.swap_remove(index);
         }
 
+        self.validate();
+        self.usage.remove_entities(1);
         self.subscribers
             .send(Event::EntityRemoved(removed, self.id()));
         self.update_count_gauge();
@@ -1177,6 +2366,22 @@ impl ComponentStorage {
     ///
     /// Returns the ID of the entity which was swapped into the removed entity's position.
     pub fn move_entity(&mut self, target: &mut ComponentStorage, index: usize) -> Option<Entity> {
+        self.move_entity_taking(target, index, &[])
+    }
+
+    /// Moves an entity from this chunk into a target chunk, moving all compatable components
+    /// into the target chunk. Components which are not present in the target chunk are moved
+    /// out to the given pointers if their type is listed in `take`, or are otherwise dropped.
+    ///
+    /// # Safety
+    ///
+    /// Each pointer in `take` must be valid for writes of a value of its component type.
+    pub(crate) fn move_entity_taking(
+        &mut self,
+        target: &mut ComponentStorage,
+        index: usize,
+        take: &[(ComponentTypeId, NonNull<u8>)],
+    ) -> Option<Entity> {
         debug_assert!(index < self.len());
         debug_assert!(!target.is_full());
         if !target.is_allocated() {
@@ -1188,6 +2393,15 @@ impl ComponentStorage {
         let entity = unsafe { *self.entities.get_unchecked(index) };
         target.entities.push(entity);
 
+        // the entity keeps its spawn tick as it moves between chunks
+        if let Some(target_ticks) = &mut target.spawn_ticks {
+            let tick = match &self.spawn_ticks {
+                Some(spawn_ticks) => spawn_ticks.ticks[index],
+                None => target_ticks.now(),
+            };
+            target_ticks.push(tick);
+        }
+
         let self_components = unsafe { &mut *self.component_info.get() };
         let target_components = unsafe { &mut *target.component_info.get() };
 
@@ -1201,6 +2415,13 @@ impl ComponentStorage {
                         .writer()
                         .push_raw(NonNull::new_unchecked(component), 1);
                 }
+            } else if let Some((_, out)) = take.iter().find(|(t, _)| t == comp_type) {
+                // move the component out to the caller
+                let (ptr, element_size, _) = accessor.data_raw();
+                unsafe {
+                    let component = ptr.add(element_size * index);
+                    std::ptr::copy_nonoverlapping(component, out.as_ptr(), element_size);
+                }
             } else {
                 // drop the component rather than move it
                 unsafe { accessor.writer().drop_in_place(index) };
@@ -1214,10 +2435,28 @@ impl ComponentStorage {
             .subscribers
             .send(Event::EntityInserted(entity, target.id()));
         target.update_count_gauge();
+        target.record_added(1);
 
         removed
     }
 
+    // counts added entities towards the archetype's usage, notifying subscribers if this
+    // pushes the archetype over its entity threshold
+    fn record_added(&mut self, count: usize) {
+        if let Some(entities) = self.usage.add_entities(count) {
+            warn!(
+                world = self.id.archetype_id().world().index(),
+                archetype = self.id.archetype_id().index(),
+                entities,
+                threshold = self.usage.threshold,
+                "Archetype exceeded its entity threshold"
+            );
+            let archetype = self.id.archetype_id();
+            self.subscribers
+                .send(Event::EntityThresholdExceeded(archetype, entities));
+        }
+    }
+
     /// Gets mutable references to the internal data of the chunk.
     pub fn writer(&mut self) -> StorageWriter {
         if !self.is_allocated() {
@@ -1238,8 +2477,8 @@ impl ComponentStorage {
         trace!(
             world = self.id.archetype_id().world().index(),
             archetype = self.id.archetype_id().index(),
-            chunkset = self.id.set(),
-            chunk = self.id.index(),
+            chunkset = self.id.set().0,
+            chunk = self.id.index().0,
             layout = ?self.component_layout,
             "Freeing chunk memory"
         );
@@ -1252,9 +2491,10 @@ impl ComponentStorage {
         // free component memory
         unsafe {
             let ptr = self.component_data.take().unwrap();
-            std::alloc::dealloc(ptr.as_ptr(), self.component_layout);
+            self.dealloc_data(ptr);
         }
 
+        self.usage.remove_chunk();
         self.update_mem_gauge();
     }
 
@@ -1264,8 +2504,8 @@ impl ComponentStorage {
         trace!(
             world = self.id.archetype_id().world().index(),
             archetype = self.id.archetype_id().index(),
-            chunkset = self.id.set(),
-            chunk = self.id.index(),
+            chunkset = self.id.set().0,
+            chunk = self.id.index().0,
             layout = ?self.component_layout,
             "Allocating chunk memory"
         );
@@ -1273,7 +2513,7 @@ impl ComponentStorage {
 
         unsafe {
             // allocating backing store
-            let ptr = std::alloc::alloc(self.component_layout);
+            let ptr = self.alloc_data();
             self.component_data = Some(NonNull::new_unchecked(ptr));
 
             // update accessor pointers
@@ -1283,9 +2523,70 @@ impl ComponentStorage {
             }
         }
 
+        self.usage.add_chunk();
         self.update_mem_gauge();
     }
 
+    unsafe fn alloc_data(&self) -> *mut u8 {
+        // chunks which only hold zero-sized components must not allocate
+        if self.component_layout.size() == 0 {
+            return dangling(self.component_layout.align());
+        }
+
+        #[cfg(feature = "numa")]
+        {
+            if let Some(allocator) = &self.allocator {
+                let ptr = allocator.alloc(self.component_layout, self.placement);
+                if ptr.is_null() {
+                    std::alloc::handle_alloc_error(self.component_layout);
+                }
+                return ptr;
+            }
+        }
+        let ptr = std::alloc::alloc(self.component_layout);
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(self.component_layout);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc_data(&self, ptr: NonNull<u8>) {
+        if self.component_layout.size() == 0 {
+            return;
+        }
+
+        #[cfg(feature = "numa")]
+        {
+            if let Some(allocator) = &self.allocator {
+                allocator.dealloc(ptr.as_ptr(), self.component_layout, self.placement);
+                return;
+            }
+        }
+        std::alloc::dealloc(ptr.as_ptr(), self.component_layout);
+    }
+
+    /// Hints to the CPU that the given component slices of the chunk are about to be read.
+    #[cfg(feature = "prefetch")]
+    pub(crate) fn prefetch(&self, types: &[ComponentTypeId]) {
+        let data = match self.component_data {
+            Some(data) => data,
+            None => return,
+        };
+
+        let components = unsafe { &*self.component_info.get() };
+        for type_id in types {
+            if let (Some(offset), Some(accessor)) = (
+                self.component_offsets.get(type_id),
+                components.get(*type_id),
+            ) {
+                let bytes = accessor.element_size * self.len();
+                for line in (0..bytes).step_by(CACHE_LINE_SIZE) {
+                    unsafe { prefetch_line(data.as_ptr().add(offset + line)) };
+                }
+            }
+        }
+    }
+
     fn update_mem_gauge(&self) {
         #[cfg(feature = "metrics")]
         {
@@ -1295,8 +2596,8 @@ impl ComponentStorage {
                 if self.is_allocated() { self.component_layout.size().try_into().unwrap() } else { 0 },
                 "world" => self.id.archetype_id().world().index().to_string(),
                 "archetype" => self.id.archetype_id().index().to_string(),
-                "chunkset" => self.id.set().to_string(),
-                "chunk" => self.id.index().to_string()
+                "chunkset" => self.id.set().0.to_string(),
+                "chunk" => self.id.index().0.to_string()
             );
         }
     }
@@ -1310,8 +2611,8 @@ impl ComponentStorage {
                 self.len().try_into().unwrap(),
                 "world" => self.id.archetype_id().world().index().to_string(),
                 "archetype" => self.id.archetype_id().index().to_string(),
-                "chunkset" => self.id.set().to_string(),
-                "chunk" => self.id.index().to_string()
+                "chunkset" => self.id.set().0.to_string(),
+                "chunk" => self.id.index().0.to_string()
             );
         }
     }
@@ -1323,9 +2624,15 @@ unsafe impl Send for ComponentStorage {}
 
 impl Drop for ComponentStorage {
     fn drop(&mut self) {
+        self.dense_index.free();
+
         if let Some(ptr) = self.component_data {
-            // run the drop functions of all components
-            for (_, info) in unsafe { &mut *self.component_info.get() }.drain() {
+            // run the drop functions of all components, in the order in which the archetype
+            // declares their types and then in slot order; types without drop functions are
+            // skipped entirely
+            let components = unsafe { &mut *self.component_info.get() };
+            for &index in components.declared.iter() {
+                let info = &mut components.data[index].1;
                 if let Some(drop_fn) = info.drop_fn {
                     let ptr = info.ptr.get_mut();
                     for i in 0..self.len() {
@@ -1338,7 +2645,49 @@ impl Drop for ComponentStorage {
 
             // free the chunk's memory
             unsafe {
-                std::alloc::dealloc(ptr.as_ptr(), self.component_layout);
+                self.dealloc_data(ptr);
+            }
+
+            self.usage.remove_chunk();
+            self.usage.remove_entities(self.len());
+        }
+    }
+}
+
+/// Records which slots in a component slice have been written.
+pub(crate) struct DirtySlots(Box<[AtomicU64]>);
+
+impl DirtySlots {
+    fn new(capacity: usize) -> Self {
+        Self(
+            (0..capacity.div_ceil(64))
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+        )
+    }
+
+    fn set(&self, index: usize) {
+        self.0[index / 64].fetch_or(1 << (index % 64), Ordering::Relaxed);
+    }
+
+    fn take(&self, index: usize) -> bool {
+        let bit = 1 << (index % 64);
+        self.0[index / 64].fetch_and(!bit, Ordering::Relaxed) & bit != 0
+    }
+
+    fn set_range(&self, start: usize, end: usize) {
+        for index in start..end {
+            self.set(index);
+        }
+    }
+
+    fn drain<F: FnMut(usize)>(&self, mut f: F) {
+        for (i, word) in self.0.iter().enumerate() {
+            let mut bits = word.swap(0, Ordering::Relaxed);
+            while bits != 0 {
+                let bit = bits.trailing_zeros() as usize;
+                f(i * 64 + bit);
+                bits &= bits - 1;
             }
         }
     }
@@ -1352,12 +2701,31 @@ pub struct ComponentResourceSet {
     count: UnsafeCell<usize>,
     capacity: usize,
     drop_fn: Option<fn(*mut u8)>,
-    version: UnsafeCell<u64>,
+    version: UnsafeCell<Version>,
+    dirty: Option<DirtySlots>,
+    summary: Option<SummaryCache>,
+    write_epochs: Option<Arc<WriteEpochs>>,
+    write_epoch: UnsafeCell<u64>,
 }
 
 impl ComponentResourceSet {
     /// Gets the version of the component slice.
-    pub fn version(&self) -> u64 { unsafe { (*self.version.get()) } }
+    pub fn version(&self) -> Version { unsafe { *self.version.get() } }
+
+    /// Gets the number of components in the slice.
+    pub fn len(&self) -> usize { unsafe { *self.count.get() } }
+
+    /// Determines if the slice is empty.
+    pub fn is_empty(&self) -> bool { self.len() < 1 }
+
+    /// Gets the reason the slice cannot currently be borrowed, mutably if `mutable`, if any.
+    pub(crate) fn borrow_conflict(&self, mutable: bool) -> Option<&'static str> {
+        if mutable {
+            self.ptr.try_get_mut().err()
+        } else {
+            self.ptr.try_get().err()
+        }
+    }
 
     /// Gets a raw pointer to the start of the component slice.
     ///
@@ -1390,12 +2758,34 @@ impl ComponentResourceSet {
         // this version increment is not thread safe
         // - but the pointer `get_mut` ensures exclusive access at runtime
         let ptr = self.ptr.get_mut();
-        unsafe {
-            *self.version.get() = next_version();
-        };
+        unsafe { self.bump_version() };
         (ptr, self.element_size, unsafe { *self.count.get() })
     }
 
+    /// Increments the version, unless it has already been incremented within the current
+    /// write epoch.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that the component slice is exclusively borrowed.
+    unsafe fn bump_version(&self) {
+        if let Some(epochs) = &self.write_epochs {
+            let epoch = epochs.current();
+            if epoch != 0 {
+                if *self.write_epoch.get() == epoch {
+                    // the version no longer identifies the contents of the slice
+                    if let Some(summary) = &self.summary {
+                        summary.invalidate();
+                    }
+                    return;
+                }
+                *self.write_epoch.get() = epoch;
+            }
+        }
+
+        *self.version.get() = next_version();
+    }
+
     /// Gets a shared reference to the slice of components.
     ///
     /// # Safety
@@ -1409,8 +2799,53 @@ impl ComponentResourceSet {
         ptr.map_into(|ptr| std::slice::from_raw_parts(*ptr as *const _ as *const T, count))
     }
 
+    /// Gets a shared reference to the slice of components, or an error if the slice is
+    /// already borrowed mutably. Conflicts are reported regardless of the world's
+    /// `BorrowConflictPolicy`.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that `T` is representative of the component data actually stored.
+    pub unsafe fn try_data_slice<T>(&self) -> Result<RefMap<'_, &[T]>, BorrowConflict> {
+        let ptr = self.ptr.try_get().map_err(|reason| BorrowConflict {
+            component: std::any::type_name::<T>(),
+            mutable: false,
+            reason,
+        })?;
+        let count = *self.count.get();
+        Ok(ptr.map_into(|ptr| std::slice::from_raw_parts(*ptr as *const _ as *const T, count)))
+    }
+
+    /// Gets a mutable reference to the slice of components, or an error if the slice is
+    /// already borrowed. Conflicts are reported regardless of the world's
+    /// `BorrowConflictPolicy`.
+    ///
+    /// If write tracking is enabled and the slice is borrowed, every component in the slice is
+    /// marked as written.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that `T` is representative of the component data actually stored.
+    // the slice is only handed out while the exclusive borrow of `ptr` is held by the guard
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn try_data_slice_mut<T>(&self) -> Result<RefMapMut<'_, &mut [T]>, BorrowConflict> {
+        let ptr = self.ptr.try_get_mut().map_err(|reason| BorrowConflict {
+            component: std::any::type_name::<T>(),
+            mutable: true,
+            reason,
+        })?;
+        self.bump_version();
+        let count = *self.count.get();
+        if let Some(dirty) = &self.dirty {
+            dirty.set_range(0, count);
+        }
+        Ok(ptr.map_into(|ptr| std::slice::from_raw_parts_mut(*ptr as *mut _ as *mut T, count)))
+    }
+
     /// Gets a mutable reference to the slice of components.
     ///
+    /// If write tracking is enabled, every component in the slice is marked as written.
+    ///
     /// # Safety
     ///
     /// Ensure that `T` is representative of the component data actually stored.
@@ -1424,11 +2859,99 @@ impl ComponentResourceSet {
     /// It will happen in 50000 years if you do 10000 mutations a millisecond.
     pub unsafe fn data_slice_mut<T>(&self) -> RefMapMut<&mut [T]> {
         let (ptr, _size, count) = self.data_raw_mut();
+        if let Some(dirty) = &self.dirty {
+            dirty.set_range(0, count);
+        }
         ptr.map_into(|ptr| std::slice::from_raw_parts_mut(*ptr as *mut _ as *mut T, count))
     }
 
+    /// Gets a mutable reference to a single component.
+    ///
+    /// Unlike `data_slice_mut`, only the borrowed component is marked as written.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that `T` is representative of the component data actually stored.
+    ///
+    /// Access to the component data within the slice is runtime borrow checked.
+    /// This call will panic if borrowing rules are broken.
+    pub unsafe fn data_element_mut<T>(&self, index: usize) -> Option<RefMut<'_, T>> {
+        let (ptr, _size, count) = self.data_raw_mut();
+        if index >= count {
+            return None;
+        }
+
+        if let Some(dirty) = &self.dirty {
+            dirty.set(index);
+        }
+
+        let (borrow, ptr) = ptr.deconstruct();
+        Some(RefMut::new(borrow, &mut *(*ptr as *mut T).add(index)))
+    }
+
+    /// Gets a mutable reference to a single component, or an error if the slice is already
+    /// borrowed. Conflicts are reported regardless of the world's `BorrowConflictPolicy`.
+    ///
+    /// Unlike `try_data_slice_mut`, only the borrowed component is marked as written.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that `T` is representative of the component data actually stored.
+    pub unsafe fn try_data_element_mut<T>(
+        &self,
+        index: usize,
+    ) -> Result<Option<RefMut<'_, T>>, BorrowConflict> {
+        let ptr = self.ptr.try_get_mut().map_err(|reason| BorrowConflict {
+            component: std::any::type_name::<T>(),
+            mutable: true,
+            reason,
+        })?;
+        if index >= *self.count.get() {
+            return Ok(None);
+        }
+
+        self.bump_version();
+        if let Some(dirty) = &self.dirty {
+            dirty.set(index);
+        }
+
+        let (borrow, ptr) = ptr.deconstruct();
+        Ok(Some(RefMut::new(borrow, &mut *(*ptr as *mut T).add(index))))
+    }
+
+    /// Determines if writes to individual components in this slice are being tracked.
+    pub fn is_tracking_writes(&self) -> bool { self.dirty.is_some() }
+
+    pub(crate) fn track_writes(&mut self) {
+        if self.dirty.is_none() {
+            self.dirty = Some(DirtySlots::new(self.capacity));
+        }
+    }
+
+    /// Calls `f` with the index of every component written since the last drain, and clears
+    /// the written flags.
+    pub(crate) fn drain_dirty<F: FnMut(usize)>(&self, f: F) {
+        if let Some(dirty) = &self.dirty {
+            dirty.drain(f);
+        }
+    }
+
     /// Creates a writer for pushing components into or removing from the vec.
     pub fn writer(&mut self) -> ComponentWriter { ComponentWriter::new(self) }
+
+    /// Creates a writer through a shared reference, so that writers for several component
+    /// types in the same chunk can be held at once.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold exclusive access to the chunk, such as through its `StorageWriter`,
+    /// for as long as the writer is alive. The writer's borrow of the slice is only checked in
+    /// debug builds, and it modifies the slice's length through a shared reference, so any
+    /// other access to the slice (including its length) while the writer is alive is undefined
+    /// behaviour.
+    pub(crate) unsafe fn writer_unchecked(&self) -> ComponentWriter<'_> {
+        ComponentWriter::new(self)
+    }
 }
 
 impl Debug for ComponentResourceSet {
@@ -1478,10 +3001,29 @@ impl<'a> ComponentWriter<'a> {
         debug_assert!((*self.accessor.count.get() + count) <= self.accessor.capacity);
         std::ptr::copy_nonoverlapping(
             components.as_ptr(),
-            self.ptr
-                .add(*self.accessor.count.get() * self.accessor.element_size),
+            self.spare_ptr(),
             count * self.accessor.element_size,
         );
+        self.commit_raw(count);
+    }
+
+    /// Gets a pointer to the first unused slot at the end of the vec.
+    ///
+    /// Components may be written directly into the unused capacity, and then made part
+    /// of the vec with `commit_raw`.
+    pub(crate) unsafe fn spare_ptr(&mut self) -> *mut u8 {
+        self.ptr
+            .add(*self.accessor.count.get() * self.accessor.element_size)
+    }
+
+    /// Appends `count` components which have already been written into the unused
+    /// capacity at the end of the vec.
+    pub(crate) unsafe fn commit_raw(&mut self, count: usize) {
+        debug_assert!((*self.accessor.count.get() + count) <= self.accessor.capacity);
+        if let Some(dirty) = &self.accessor.dirty {
+            let start = *self.accessor.count.get();
+            dirty.set_range(start, start + count);
+        }
         *self.accessor.count.get() += count;
         *self.accessor.version.get() = next_version();
     }
@@ -1519,6 +3061,15 @@ impl<'a> ComponentWriter<'a> {
                 std::ptr::copy_nonoverlapping(swap_target, to_remove, size);
             }
 
+            if let Some(dirty) = &self.accessor.dirty {
+                // the written flag follows the component that was swapped into place
+                let moved = dirty.take(count - 1);
+                dirty.take(index);
+                if moved && index < count - 1 {
+                    dirty.set(index);
+                }
+            }
+
             *self.accessor.count.get() -= 1;
         }
     }
@@ -1560,7 +3111,7 @@ impl TagStorage {
                         .unwrap();
                 NonNull::new_unchecked(std::alloc::alloc(layout))
             } else {
-                NonNull::new_unchecked(element.align as *mut u8)
+                NonNull::new_unchecked(dangling(element.align))
             }
         };
 
@@ -1595,7 +3146,7 @@ impl TagStorage {
         let ptr = if self.element.size > 0 {
             self.ptr.as_ptr().add(self.len * self.element.size)
         } else {
-            self.element.align as *mut u8
+            dangling(self.element.align)
         };
 
         self.len += 1;
@@ -1669,7 +3220,16 @@ impl TagStorage {
         std::slice::from_raw_parts(self.ptr.as_ptr() as *const T, self.len)
     }
 
-    fn grow(&mut self) {
+    fn grow(&mut self) { self.grow_to(2 * self.capacity); }
+
+    /// Reserves capacity for at least `additional` more tags.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        if self.element.size != 0 && self.len + additional > self.capacity {
+            self.grow_to(self.len + additional);
+        }
+    }
+
+    fn grow_to(&mut self, new_cap: usize) {
         assert!(self.element.size != 0, "capacity overflow");
         unsafe {
             let (new_cap, ptr) = {
@@ -1678,7 +3238,6 @@ impl TagStorage {
                     self.element.align,
                 )
                 .unwrap();
-                let new_cap = 2 * self.capacity;
                 let ptr =
                     std::alloc::realloc(self.ptr.as_ptr(), layout, new_cap * self.element.size);
 
@@ -1739,6 +3298,89 @@ mod test {
     #[derive(Copy, Clone, PartialEq, Debug)]
     struct ZeroSize;
 
+    #[test]
+    pub fn version_ordering() {
+        let a = Version::from_bits(5);
+        let b = Version::from_bits(6);
+        assert!(b.is_newer_than(a));
+        assert!(!a.is_newer_than(b));
+        assert!(!a.is_newer_than(a));
+        assert!(a.is_newer_than(Version::NONE));
+        assert!(!Version::NONE.is_newer_than(a));
+        assert!(a < b);
+    }
+
+    #[test]
+    pub fn version_wrapping_comparison() {
+        let before = Version::from_bits(u64::MAX);
+        let after = Version::from_bits(1);
+        assert!(after.is_newer_than(before));
+        assert!(!before.is_newer_than(after));
+
+        // ordering is decided by the shortest wrapping distance
+        let far = Version::from_bits(1 << 62);
+        assert!(far.is_newer_than(after));
+        assert!(!far.is_newer_than(Version::from_bits((1 << 63) + (1 << 62))));
+    }
+
+    #[test]
+    pub fn write_bumps_version() {
+        let set = ComponentResourceSet {
+            ptr: AtomicRefCell::new(dangling(std::mem::align_of::<u32>())),
+            capacity: 0,
+            count: UnsafeCell::new(0),
+            element_size: 4,
+            drop_fn: None,
+            version: UnsafeCell::new(Version::NONE),
+            dirty: None,
+            summary: None,
+            write_epochs: None,
+            write_epoch: UnsafeCell::new(0),
+        };
+        unsafe { set.data_slice_mut::<u32>() };
+        let version = set.version();
+        assert!(version.is_newer_than(Version::NONE));
+
+        unsafe { set.data_slice_mut::<u32>() };
+        assert!(set.version().is_newer_than(version));
+    }
+
+    #[test]
+    pub fn write_epoch_bumps_version_once() {
+        let epochs = Arc::new(WriteEpochs::default());
+        let set = ComponentResourceSet {
+            ptr: AtomicRefCell::new(dangling(std::mem::align_of::<u32>())),
+            capacity: 0,
+            count: UnsafeCell::new(0),
+            element_size: 4,
+            drop_fn: None,
+            version: UnsafeCell::new(Version::NONE),
+            dirty: None,
+            summary: None,
+            write_epochs: Some(epochs.clone()),
+            write_epoch: UnsafeCell::new(0),
+        };
+
+        epochs.begin();
+        unsafe { set.data_slice_mut::<u32>() };
+        let version = set.version();
+        unsafe { set.data_slice_mut::<u32>() };
+        assert_eq!(version, set.version());
+
+        epochs.begin();
+        unsafe { set.data_slice_mut::<u32>() };
+        assert!(set.version().is_newer_than(version));
+        let version = set.version();
+
+        // outside of an epoch, every borrow bumps the version
+        epochs.end();
+        unsafe { set.data_slice_mut::<u32>() };
+        assert!(set.version().is_newer_than(version));
+        let version = set.version();
+        unsafe { set.data_slice_mut::<u32>() };
+        assert!(set.version().is_newer_than(version));
+    }
+
     #[test]
     pub fn create() {
         let _ = tracing_subscriber::fmt::try_init();
@@ -1756,10 +3398,9 @@ mod test {
 
         let chunk_index = data.get_free_chunk(set);
         let components = data
-            .chunksets_mut()
-            .get_mut(set)
+            .chunkset_mut(set)
             .unwrap()
-            .get_mut(chunk_index)
+            .chunk_mut(chunk_index)
             .unwrap();
         let mut writer = components.writer();
         let (chunk_entities, chunk_components) = writer.get();
@@ -1774,6 +3415,52 @@ mod test {
         }
     }
 
+    #[test]
+    pub fn description_entity_threshold() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut archetypes = Storage::new(WorldId::default());
+        archetypes.set_entity_threshold(Some(10));
+
+        let mut desc = ArchetypeDescription::default();
+        desc.register_component::<isize>();
+        let (_, data) = archetypes.alloc_archetype(desc);
+        assert_eq!(Some(10), data.stats().entity_threshold);
+
+        // a threshold in the description overrides the storage's
+        let mut desc = ArchetypeDescription::default();
+        desc.register_component::<usize>();
+        desc.set_entity_threshold(Some(1));
+        let (_, data) = archetypes.alloc_archetype(desc);
+        assert_eq!(Some(1), data.stats().entity_threshold);
+        assert_eq!(Some(1), data.description().entity_threshold());
+
+        let set = data.alloc_chunk_set(|_| {});
+        let chunk_index = data.get_free_chunk(set);
+        let chunk = data
+            .chunkset_mut(set)
+            .unwrap()
+            .chunk_mut(chunk_index)
+            .unwrap();
+        let mut writer = chunk.writer();
+        let (chunk_entities, chunk_components) = writer.get();
+        chunk_entities.push(Entity::new(1, Wrapping(0)));
+        chunk_entities.push(Entity::new(2, Wrapping(0)));
+        unsafe {
+            (&mut *chunk_components.get())
+                .get_mut(ComponentTypeId::of::<usize>())
+                .unwrap()
+                .writer()
+                .push(&[1usize, 2usize]);
+        }
+        drop(writer);
+
+        let stats = data.stats();
+        assert_eq!(2, stats.entities);
+        assert_eq!(1, stats.chunks);
+        assert_eq!(1, stats.peak_chunks);
+    }
+
     #[test]
     pub fn create_lazy_allocated() {
         let _ = tracing_subscriber::fmt::try_init();
@@ -1791,10 +3478,9 @@ mod test {
 
         let chunk_index = data.get_free_chunk(set);
         let chunk = data
-            .chunksets_mut()
-            .get_mut(set)
+            .chunkset_mut(set)
             .unwrap()
-            .get_mut(chunk_index)
+            .chunk_mut(chunk_index)
             .unwrap();
 
         assert!(!chunk.is_allocated());
@@ -1821,10 +3507,9 @@ mod test {
 
         let chunk_index = data.get_free_chunk(set);
         let chunk = data
-            .chunksets_mut()
-            .get_mut(set)
+            .chunkset_mut(set)
             .unwrap()
-            .get_mut(chunk_index)
+            .chunk_mut(chunk_index)
             .unwrap();
 
         assert!(!chunk.is_allocated());
@@ -1865,10 +3550,9 @@ mod test {
         let set = data.alloc_chunk_set(|_| {});
         let chunk_index = data.get_free_chunk(set);
         let components = data
-            .chunksets_mut()
-            .get_mut(set)
+            .chunkset_mut(set)
             .unwrap()
-            .get_mut(chunk_index)
+            .chunk_mut(chunk_index)
             .unwrap();
 
         let entities = [
@@ -1996,10 +3680,9 @@ mod test {
 
         let chunk_index = data.get_free_chunk(set);
         let components = data
-            .chunksets_mut()
-            .get_mut(set)
+            .chunkset_mut(set)
             .unwrap()
-            .get_mut(chunk_index)
+            .chunk_mut(chunk_index)
             .unwrap();
         let mut writer = components.writer();
         let (chunk_entities, chunk_components) = writer.get();
@@ -2031,10 +3714,9 @@ mod test {
 
         let chunk_index = data.get_free_chunk(set);
         let components = data
-            .chunksets_mut()
-            .get_mut(set)
+            .chunkset_mut(set)
             .unwrap()
-            .get_mut(chunk_index)
+            .chunk_mut(chunk_index)
             .unwrap();
         let mut writer = components.writer();
         let (chunk_entities, chunk_components) = writer.get();
@@ -0,0 +1,151 @@
+//! Per-chunk summaries of component data, which filters can use to skip whole chunks.
+//!
+//! Summaries are enabled per component type with `World::enable_summary`, and are computed
+//! lazily: a chunk's summary is only recomputed when it is requested after the chunk's
+//! components have been modified.
+
+use crate::storage::Component;
+use crate::storage::ComponentResourceSet;
+use crate::storage::Version;
+use parking_lot::Mutex;
+use std::any::{Any, TypeId};
+use std::ops::{Bound, RangeBounds};
+use std::sync::Arc;
+
+/// A summary of the components of type `T` stored in a single chunk.
+///
+/// Implement this to maintain e.g. a bounding box over position components. A summary must
+/// describe every component in the chunk, so that filters may rely upon it to prove that a
+/// chunk contains no matching entities.
+pub trait ComponentSummary<T: Component>: Send + Sync + Sized + 'static {
+    /// Summarizes the components in a chunk. `components` is never empty.
+    fn summarize(components: &[T]) -> Self;
+}
+
+/// Summarizes a chunk by the smallest and largest component within it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinMaxSummary<T> {
+    pub min: T,
+    pub max: T,
+}
+
+impl<T> MinMaxSummary<T>
+where
+    T: PartialOrd,
+{
+    /// Determines if any component within the summarized range may lie within `range`.
+    pub fn overlaps<R: RangeBounds<T>>(&self, range: &R) -> bool {
+        let above_start = match range.start_bound() {
+            Bound::Included(start) => self.max >= *start,
+            Bound::Excluded(start) => self.max > *start,
+            Bound::Unbounded => true,
+        };
+        let below_end = match range.end_bound() {
+            Bound::Included(end) => self.min <= *end,
+            Bound::Excluded(end) => self.min < *end,
+            Bound::Unbounded => true,
+        };
+        above_start && below_end
+    }
+}
+
+impl<T> ComponentSummary<T> for MinMaxSummary<T>
+where
+    T: Component + PartialOrd + Clone,
+{
+    fn summarize(components: &[T]) -> Self {
+        let mut min = &components[0];
+        let mut max = &components[0];
+        for component in &components[1..] {
+            if component < min {
+                min = component;
+            }
+            if component > max {
+                max = component;
+            }
+        }
+
+        MinMaxSummary {
+            min: min.clone(),
+            max: max.clone(),
+        }
+    }
+}
+
+type SummarizeFn = unsafe fn(&ComponentResourceSet) -> Arc<dyn Any + Send + Sync>;
+
+/// Describes how to summarize a component type within each chunk.
+#[derive(Clone, Copy)]
+pub(crate) struct SummaryDef {
+    summary_type: TypeId,
+    summarize: SummarizeFn,
+}
+
+impl SummaryDef {
+    pub(crate) fn of<T: Component, S: ComponentSummary<T>>() -> Self {
+        unsafe fn summarize<T: Component, S: ComponentSummary<T>>(
+            components: &ComponentResourceSet,
+        ) -> Arc<dyn Any + Send + Sync> {
+            Arc::new(S::summarize(&components.data_slice::<T>()))
+        }
+
+        SummaryDef {
+            summary_type: TypeId::of::<S>(),
+            summarize: summarize::<T, S>,
+        }
+    }
+}
+
+/// Caches the summary of a chunk's components, keyed by the version and count of the
+/// components when it was computed.
+pub(crate) struct SummaryCache {
+    def: SummaryDef,
+    cached: Mutex<Option<(Version, usize, Arc<dyn Any + Send + Sync>)>>,
+}
+
+impl SummaryCache {
+    pub(crate) fn new(def: SummaryDef) -> Self {
+        SummaryCache {
+            def,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Gets the summary of `components`, recomputing it if they have been modified since it
+    /// was last computed.
+    ///
+    /// Returns `None` if the components are empty or are summarized by another type.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that `components` is the component slice this cache was created for.
+    pub(crate) unsafe fn get<S: Any + Send + Sync>(
+        &self,
+        components: &ComponentResourceSet,
+    ) -> Option<Arc<S>> {
+        if self.def.summary_type != TypeId::of::<S>() {
+            return None;
+        }
+
+        let (version, count) = (components.version(), components.len());
+        if count == 0 {
+            return None;
+        }
+
+        // removals do not advance the version, but always change the count
+        let mut cached = self.cached.lock();
+        let summary = match &*cached {
+            Some((v, c, summary)) if *v == version && *c == count => summary.clone(),
+            _ => {
+                let summary = (self.def.summarize)(components);
+                *cached = Some((version, count, summary.clone()));
+                summary
+            }
+        };
+
+        summary.downcast::<S>().ok()
+    }
+
+    /// Discards the cached summary, so that it is recomputed when next requested.
+    pub(crate) fn invalidate(&self) { *self.cached.lock() = None; }
+}
@@ -3,8 +3,13 @@ use crate::command::CommandBuffer;
 use crate::cons::{ConsAppend, ConsFlatten};
 use crate::entity::Entity;
 use crate::filter::EntityFilter;
+use crate::filter::{ArchetypeFilterData, ChunkFilterData, ChunksetFilterData, Filter};
+use crate::iterator::FissileIterator;
 use crate::query::ReadOnly;
-use crate::query::{ChunkDataIter, ChunkEntityIter, ChunkViewIter, Query, Read, View, Write};
+use crate::query::{
+    Chunk, ChunkDataIter, ChunkEntityIter, ChunkViewIter, Query, Read, TryRead, TryWrite, View,
+    Write,
+};
 use crate::resource::{Resource, ResourceSet, ResourceTypeId};
 use crate::schedule::ArchetypeAccess;
 use crate::schedule::{Runnable, Schedulable};
@@ -19,13 +24,7 @@ use std::marker::PhantomData;
 use tracing::{debug, info, span, Level};
 
 #[cfg(feature = "par-iter")]
-use crate::filter::{ArchetypeFilterData, ChunkFilterData, ChunksetFilterData, Filter};
-
-#[cfg(feature = "par-iter")]
-use crate::iterator::FissileIterator;
-
-#[cfg(feature = "par-iter")]
-use crate::query::Chunk;
+use crate::query::ChunkViewScoped;
 
 /// Structure used by `SystemAccess` for describing access to the provided `T`
 #[derive(Derivative, Debug, Clone)]
@@ -300,7 +299,7 @@ where
     #[inline]
     pub unsafe fn for_each_unchecked<'a, 'data, T>(&'a self, world: &SubWorld, f: T)
     where
-        T: Fn(<<V as View<'data>>::Iter as Iterator>::Item),
+        T: FnMut(<<V as View<'data>>::Iter as Iterator>::Item),
     {
         self.query.get().for_each_unchecked(&*world.world, f)
     }
@@ -309,7 +308,7 @@ where
     #[inline]
     pub fn for_each_immutable<'a, 'data, T>(&'a self, world: &SubWorld, f: T)
     where
-        T: Fn(<<V as View<'data>>::Iter as Iterator>::Item),
+        T: FnMut(<<V as View<'data>>::Iter as Iterator>::Item),
         V: ReadOnly,
     {
         // safe because the view can only read data immutably
@@ -320,12 +319,51 @@ where
     #[inline]
     pub fn for_each<'a, 'data, T>(&'a self, world: &mut SubWorld, f: T)
     where
-        T: Fn(<<V as View<'data>>::Iter as Iterator>::Item),
+        T: FnMut(<<V as View<'data>>::Iter as Iterator>::Item),
     {
         // safe because the &mut SubWorld ensures exclusivity
         unsafe { self.for_each_unchecked(world, f) }
     }
 
+    /// Iterates through all chunks that match the query.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[inline]
+    pub unsafe fn for_each_chunk_unchecked<'a, 'data, T>(&'a self, world: &SubWorld, f: T)
+    where
+        T: FnMut(Chunk<'data, V>),
+    {
+        self.query.get().for_each_chunk_unchecked(&*world.world, f)
+    }
+
+    /// Iterates through all chunks that match the query.
+    #[inline]
+    pub fn for_each_chunk_immutable<'a, 'data, T>(&'a self, world: &SubWorld, f: T)
+    where
+        T: FnMut(Chunk<'data, V>),
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.for_each_chunk_unchecked(world, f) }
+    }
+
+    /// Iterates through all chunks that match the query.
+    #[inline]
+    pub fn for_each_chunk<'a, 'data, T>(&'a self, world: &mut SubWorld, f: T)
+    where
+        T: FnMut(Chunk<'data, V>),
+    {
+        // safe because the &mut SubWorld ensures exclusivity
+        unsafe { self.for_each_chunk_unchecked(world, f) }
+    }
+
     /// Iterates through all entity data that matches the query.
     /// Does not perform static borrow checking.
     ///
@@ -340,7 +378,7 @@ where
     #[inline]
     pub unsafe fn for_each_entities_unchecked<'a, 'data, T>(&'a self, world: &SubWorld, f: T)
     where
-        T: Fn((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
+        T: FnMut((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
     {
         self.query
             .get()
@@ -352,7 +390,7 @@ where
     #[inline]
     pub fn for_each_entities_immutable<'a, 'data, T>(&'a self, world: &SubWorld, f: T)
     where
-        T: Fn((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
+        T: FnMut((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
         V: ReadOnly,
     {
         // safe because the view can only read data immutably
@@ -364,7 +402,7 @@ where
     #[inline]
     pub fn for_each_entities<'a, 'data, T>(&'a self, world: &mut SubWorld, f: T)
     where
-        T: Fn((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
+        T: FnMut((Entity, <<V as View<'data>>::Iter as Iterator>::Item)),
     {
         // safe because the &mut SubWorld ensures exclusivity
         unsafe { self.for_each_entities_unchecked(world, f) }
@@ -382,7 +420,7 @@ where
     /// This function may panic if other code is concurrently accessing the same components.
     #[cfg(feature = "par-iter")]
     #[inline]
-    pub unsafe fn par_entities_for_each_unchecked<'a, T>(&'a self, world: &SubWorld, f: T)
+    pub unsafe fn par_for_each_entities_unchecked<'a, T>(&'a self, world: &SubWorld, f: T)
     where
         T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
@@ -391,13 +429,13 @@ where
     {
         self.query
             .get()
-            .par_entities_for_each_unchecked(&*world.world, f)
+            .par_for_each_entities_unchecked(&*world.world, f)
     }
 
     /// Iterates through all entities that matches the query in parallel by chunk.
     #[cfg(feature = "par-iter")]
     #[inline]
-    pub fn par_entities_for_each_immutable<'a, T>(&'a self, world: &SubWorld, f: T)
+    pub fn par_for_each_entities_immutable<'a, T>(&'a self, world: &SubWorld, f: T)
     where
         T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
@@ -406,13 +444,13 @@ where
         V: ReadOnly,
     {
         // safe because the view can only read data immutably
-        unsafe { self.par_entities_for_each_unchecked(world, f) }
+        unsafe { self.par_for_each_entities_unchecked(world, f) }
     }
 
     /// Iterates through all entities that matches the query in parallel by chunk.
     #[cfg(feature = "par-iter")]
     #[inline]
-    pub fn par_entities_for_each<'a, T>(&'a self, world: &mut SubWorld, f: T)
+    pub fn par_for_each_entities<'a, T>(&'a self, world: &mut SubWorld, f: T)
     where
         T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
@@ -420,10 +458,10 @@ where
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
     {
         // safe because the &mut SubWorld ensures exclusivity
-        unsafe { self.par_entities_for_each_unchecked(world, f) }
+        unsafe { self.par_for_each_entities_unchecked(world, f) }
     }
 
-    /// Iterates through all entity data that matches the query in parallel.
+    /// Iterates through all entities that matches the query in parallel by chunk.
     /// Does not perform static borrow checking.
     ///
     /// # Safety
@@ -434,6 +472,57 @@ where
     ///
     /// This function may panic if other code is concurrently accessing the same components.
     #[cfg(feature = "par-iter")]
+    #[deprecated(note = "renamed to `par_for_each_entities_unchecked`")]
+    #[inline]
+    pub unsafe fn par_entities_for_each_unchecked<'a, T>(&'a self, world: &SubWorld, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        self.par_for_each_entities_unchecked(world, f)
+    }
+
+    /// Iterates through all entities that matches the query in parallel by chunk.
+    #[cfg(feature = "par-iter")]
+    #[deprecated(note = "renamed to `par_for_each_entities_immutable`")]
+    #[inline]
+    pub fn par_entities_for_each_immutable<'a, T>(&'a self, world: &SubWorld, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+    {
+        self.par_for_each_entities_immutable(world, f)
+    }
+
+    /// Iterates through all entities that matches the query in parallel by chunk.
+    #[cfg(feature = "par-iter")]
+    #[deprecated(note = "renamed to `par_for_each_entities`")]
+    #[inline]
+    pub fn par_entities_for_each<'a, T>(&'a self, world: &mut SubWorld, f: T)
+    where
+        T: Fn((Entity, <<V as View<'a>>::Iter as Iterator>::Item)) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+    {
+        self.par_for_each_entities(world, f)
+    }
+
+    /// Iterates through all entity data that matches the query in parallel.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// Incorrectly accessing components that are already borrowed elsewhere is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
     #[inline]
     pub unsafe fn par_for_each_unchecked<'a, T>(&'a self, world: &SubWorld, f: T)
     where
@@ -446,7 +535,6 @@ where
     }
 
     /// Iterates through all entity data that matches the query in parallel.
-    #[cfg(feature = "par-iter")]
     #[inline]
     pub fn par_for_each_immutable<'a, T>(&'a self, world: &SubWorld, f: T)
     where
@@ -461,7 +549,6 @@ where
     }
 
     /// Iterates through all entity data that matches the query in parallel.
-    #[cfg(feature = "par-iter")]
     #[inline]
     pub fn par_for_each<'a, T>(&'a self, world: &mut SubWorld, f: T)
     where
@@ -488,7 +575,7 @@ where
     #[inline]
     pub unsafe fn par_for_each_chunk_unchecked<'a, T>(&'a self, world: &SubWorld, f: T)
     where
-        T: Fn(Chunk<'a, V>) + Send + Sync,
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
@@ -503,7 +590,7 @@ where
     #[inline]
     pub fn par_for_each_chunk_immutable<'a, T>(&'a self, world: &SubWorld, f: T)
     where
-        T: Fn(Chunk<'a, V>) + Send + Sync,
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
@@ -518,7 +605,7 @@ where
     #[inline]
     pub fn par_for_each_chunk<'a, T>(&'a self, world: &mut SubWorld, f: T)
     where
-        T: Fn(Chunk<'a, V>) + Send + Sync,
+        T: for<'task> Fn(ChunkViewScoped<'task, V>) + Send + Sync,
         <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
         <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
@@ -654,7 +741,7 @@ impl SubWorld {
             if let Some(archetypes) = self.archetypes {
                 if let Some(location) = (*self.world).entity_allocator.get_location(entity.index())
                 {
-                    return (*archetypes).contains(location.archetype());
+                    return (*archetypes).contains(location.archetype().as_usize());
                 }
             }
         }
@@ -815,6 +902,9 @@ where
     // query types, which would make allocations every single request
     access: SystemAccess,
 
+    // The system is skipped unless all of these resources are present
+    required_resources: Vec<ResourceTypeId>,
+
     // We pre-allocate a command buffer for ourself. Writes are self-draining so we never have to rellocate.
     command_buffer: AtomicRefCell<CommandBuffer>,
 }
@@ -850,6 +940,12 @@ where
 
     fn command_buffer_mut(&self) -> RefMut<CommandBuffer> { self.command_buffer.get_mut() }
 
+    fn requirements_met(&self, world: &World) -> bool {
+        self.required_resources
+            .iter()
+            .all(|type_id| world.resources.contains_id(*type_id))
+    }
+
     fn run(&self, world: &World) {
         let span = span!(Level::INFO, "System", system = %self.name);
         let _guard = span.enter();
@@ -956,6 +1052,7 @@ pub struct SystemBuilder<Q = (), R = ()> {
     resources: R,
 
     resource_access: Access<ResourceTypeId>,
+    required_resources: Vec<ResourceTypeId>,
     component_access: Access<ComponentTypeId>,
     access_all_archetypes: bool,
 }
@@ -971,6 +1068,7 @@ impl SystemBuilder<(), ()> {
             queries: (),
             resources: (),
             resource_access: Access::default(),
+            required_resources: Vec::new(),
             component_access: Access::default(),
             access_all_archetypes: false,
         }
@@ -1004,6 +1102,7 @@ where
             queries: ConsAppend::append(self.queries, query),
             resources: self.resources,
             resource_access: self.resource_access,
+            required_resources: self.required_resources,
             component_access: self.component_access,
             access_all_archetypes: self.access_all_archetypes,
         }
@@ -1013,6 +1112,9 @@ where
     ///
     /// This will inform the dispatcher to not allow any writes access to this resource while
     /// this system is running. Parralel reads still occur during execution.
+    ///
+    /// The system will panic when run if the resource is not present in the world. See also
+    /// `try_read_resource` and `requires_resource`.
     pub fn read_resource<T>(mut self) -> SystemBuilder<Q, <R as ConsAppend<Read<T>>>::Output>
     where
         T: 'static + Resource,
@@ -1026,6 +1128,30 @@ where
             queries: self.queries,
             resources: ConsAppend::append(self.resources, Read::<T>::default()),
             resource_access: self.resource_access,
+            required_resources: self.required_resources,
+            component_access: self.component_access,
+            access_all_archetypes: self.access_all_archetypes,
+        }
+    }
+
+    /// Flag this resource type as being read by this system, if it is present.
+    ///
+    /// The system is given an `Option` which is `None` when the resource is not present in
+    /// the world. Access is scheduled as with `read_resource`.
+    pub fn try_read_resource<T>(mut self) -> SystemBuilder<Q, <R as ConsAppend<TryRead<T>>>::Output>
+    where
+        T: 'static + Resource,
+        R: ConsAppend<TryRead<T>>,
+        <R as ConsAppend<TryRead<T>>>::Output: ConsFlatten,
+    {
+        self.resource_access.reads.push(ResourceTypeId::of::<T>());
+
+        SystemBuilder {
+            name: self.name,
+            queries: self.queries,
+            resources: ConsAppend::append(self.resources, TryRead::<T>::default()),
+            resource_access: self.resource_access,
+            required_resources: self.required_resources,
             component_access: self.component_access,
             access_all_archetypes: self.access_all_archetypes,
         }
@@ -1035,6 +1161,9 @@ where
     ///
     /// This will inform the dispatcher to not allow any parallel access to this resource while
     /// this system is running.
+    ///
+    /// The system will panic when run if the resource is not present in the world. See also
+    /// `try_write_resource` and `requires_resource`.
     pub fn write_resource<T>(mut self) -> SystemBuilder<Q, <R as ConsAppend<Write<T>>>::Output>
     where
         T: 'static + Resource,
@@ -1048,11 +1177,46 @@ where
             queries: self.queries,
             resources: ConsAppend::append(self.resources, Write::<T>::default()),
             resource_access: self.resource_access,
+            required_resources: self.required_resources,
+            component_access: self.component_access,
+            access_all_archetypes: self.access_all_archetypes,
+        }
+    }
+
+    /// Flag this resource type as being written by this system, if it is present.
+    ///
+    /// The system is given an `Option` which is `None` when the resource is not present in
+    /// the world. Access is scheduled as with `write_resource`.
+    pub fn try_write_resource<T>(
+        mut self,
+    ) -> SystemBuilder<Q, <R as ConsAppend<TryWrite<T>>>::Output>
+    where
+        T: 'static + Resource,
+        R: ConsAppend<TryWrite<T>>,
+        <R as ConsAppend<TryWrite<T>>>::Output: ConsFlatten,
+    {
+        self.resource_access.writes.push(ResourceTypeId::of::<T>());
+
+        SystemBuilder {
+            name: self.name,
+            queries: self.queries,
+            resources: ConsAppend::append(self.resources, TryWrite::<T>::default()),
+            resource_access: self.resource_access,
+            required_resources: self.required_resources,
             component_access: self.component_access,
             access_all_archetypes: self.access_all_archetypes,
         }
     }
 
+    /// Requires that a resource is present in the world for this system to run.
+    ///
+    /// Executors skip the system entirely when the resource is missing. This does not give the
+    /// system access to the resource; declare access with e.g. `read_resource` as well.
+    pub fn requires_resource<T: Resource>(mut self) -> Self {
+        self.required_resources.push(ResourceTypeId::of::<T>());
+        self
+    }
+
     /// This performs a soft resource block on the component for writing. The dispatcher will
     /// generally handle dispatching read and writes on components based on archetype, allowing
     /// for more granular access and more parallelization of systems.
@@ -1131,6 +1295,7 @@ where
                 components: self.component_access,
                 tags: Access::default(),
             },
+            required_resources: self.required_resources,
             command_buffer: AtomicRefCell::new(CommandBuffer::default()),
         })
     }
@@ -1166,6 +1331,7 @@ where
                 components: self.component_access,
                 tags: Access::default(),
             },
+            required_resources: self.required_resources,
             command_buffer: AtomicRefCell::new(CommandBuffer::default()),
         })
     }
@@ -1201,6 +1367,7 @@ mod tests {
     struct TestCompThree(f32, f32, f32);
 
     #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn builder_schedule_execute() {
         let _ = tracing_subscriber::fmt::try_init();
 
@@ -1296,6 +1463,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn builder_create_and_execute() {
         let _ = tracing_subscriber::fmt::try_init();
 
@@ -1337,6 +1505,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn fnmut_stateful_system_test() {
         let _ = tracing_subscriber::fmt::try_init();
 
@@ -1371,6 +1540,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn system_mutate_archetype() {
         let _ = tracing_subscriber::fmt::try_init();
 
@@ -1419,6 +1589,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn system_mutate_archetype_buffer() {
         let _ = tracing_subscriber::fmt::try_init();
 
@@ -1469,6 +1640,7 @@ mod tests {
 
     #[test]
     #[cfg(feature = "par-schedule")]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn par_res_write() {
         use std::sync::atomic::{AtomicUsize, Ordering};
         let _ = tracing_subscriber::fmt::try_init();
@@ -1537,6 +1709,7 @@ mod tests {
 
     #[test]
     #[cfg(feature = "par-schedule")]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn par_res_readwrite() {
         use std::sync::atomic::{AtomicUsize, Ordering};
         let _ = tracing_subscriber::fmt::try_init();
@@ -1595,6 +1768,7 @@ mod tests {
     #[test]
     #[cfg(feature = "par-schedule")]
     #[allow(clippy::float_cmp)]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
     fn par_comp_readwrite() {
         let _ = tracing_subscriber::fmt::try_init();
 
@@ -1724,4 +1898,75 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
+    fn try_read_resource_optional() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let system_seen = seen.clone();
+        let system = SystemBuilder::<()>::new("TestSystem")
+            .try_read_resource::<TestResource>()
+            .try_write_resource::<TestResourceTwo>()
+            .build(move |_, _, (read, write), _| {
+                if let Some(write) = write {
+                    write.0 += 1;
+                }
+                system_seen.lock().unwrap().push(read.as_ref().map(|r| r.0));
+            });
+
+        let mut schedule = Schedule::builder().add_system(system).build();
+        schedule.execute(&mut world);
+
+        world.resources.insert(TestResource(123));
+        world.resources.insert(TestResourceTwo(0));
+        schedule.execute(&mut world);
+
+        assert_eq!(*seen.lock().unwrap(), vec![None, Some(123)]);
+        assert_eq!(world.resources.get::<TestResourceTwo>().unwrap().0, 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
+    fn requires_resource_skips() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let system_one_runs = runs.clone();
+        let system_one = SystemBuilder::<()>::new("TestSystem1")
+            .requires_resource::<TestResource>()
+            .read_resource::<TestResource>()
+            .build(move |_, _, resource, _| {
+                system_one_runs.lock().unwrap().push(resource.0);
+            });
+        let system_two_runs = runs.clone();
+        let system_two = SystemBuilder::<()>::new("TestSystem2")
+            .write_resource::<TestResourceTwo>()
+            .build(move |_, _, resource, _| {
+                resource.0 += 1;
+                system_two_runs.lock().unwrap().push(resource.0);
+            });
+
+        let mut schedule = Schedule::builder()
+            .add_system(system_one)
+            .add_system(system_two)
+            .build();
+
+        world.resources.insert(TestResourceTwo(0));
+        schedule.execute(&mut world);
+        assert_eq!(*runs.lock().unwrap(), vec![1]);
+
+        world.resources.insert(TestResource(123));
+        schedule.execute(&mut world);
+        let mut runs = runs.lock().unwrap().clone();
+        runs.sort();
+        assert_eq!(runs, vec![1, 2, 123]);
+    }
 }
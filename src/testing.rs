@@ -0,0 +1,522 @@
+//! Utilities for comparing whole worlds in tests, e.g. against golden fixtures.
+//!
+//! Comparisons ignore how entities happen to be laid out in storage (archetype creation order,
+//! chunk packing) and instead compare per-entity liveness, the set of component and tag types
+//! attached to each entity, and the values of any types registered in a `CompareRegistry`.
+//!
+//! Entities are matched across worlds by their `Entity` ID. Standalone worlds created with
+//! `World::new` allocate IDs deterministically, so a world built by a system under test can be
+//! compared against a fixture world built by the same sequence of insertions.
+//!
+//! ```rust
+//! # use legion::prelude::*;
+//! # use legion::testing::CompareRegistry;
+//! # use legion::assert_worlds_equal;
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! struct Position(f32);
+//!
+//! let mut expected = World::new();
+//! expected.insert((), vec![(Position(1.0),)]);
+//!
+//! let mut actual = World::new();
+//! actual.insert((), vec![(Position(1.0001),)]);
+//!
+//! let mut registry = CompareRegistry::default();
+//! registry.register_with::<Position, _>(|a, b| (a.0 - b.0).abs() < 0.001);
+//!
+//! assert_worlds_equal!(&expected, &actual, &registry);
+//! ```
+use crate::entity::Entity;
+use crate::storage::Component;
+use crate::storage::ComponentTypeId;
+use crate::storage::Tag;
+use crate::storage::TagTypeId;
+use crate::world::World;
+use fxhash::FxHashMap;
+use std::fmt::Debug;
+use std::fmt::Display;
+
+type CompareFn = Box<dyn Fn(&World, &World, Entity) -> Option<String> + Send + Sync>;
+
+struct Comparer {
+    name: &'static str,
+    compare: CompareFn,
+}
+
+/// Maps component and tag types to the functions used to compare their values.
+///
+/// Types which are not registered are still checked for presence, but their values are ignored.
+#[derive(Default)]
+pub struct CompareRegistry {
+    components: FxHashMap<ComponentTypeId, Comparer>,
+    tags: FxHashMap<TagTypeId, Comparer>,
+}
+
+impl CompareRegistry {
+    /// Compares values of component type `T` with `PartialEq`.
+    pub fn register<T: Component + PartialEq + Debug>(&mut self) -> &mut Self {
+        self.register_with::<T, _>(|a, b| a == b)
+    }
+
+    /// Compares values of component type `T` with the given function, which returns `true`
+    /// when the two values should be considered equal.
+    pub fn register_with<T, F>(&mut self, eq: F) -> &mut Self
+    where
+        T: Component + Debug,
+        F: Fn(&T, &T) -> bool + Send + Sync + 'static,
+    {
+        let compare = move |a: &World, b: &World, entity: Entity| match (
+            a.get_component::<T>(entity),
+            b.get_component::<T>(entity),
+        ) {
+            (Some(x), Some(y)) if !eq(&x, &y) => Some(format!("{:?} != {:?}", *x, *y)),
+            _ => None,
+        };
+
+        self.components.insert(
+            ComponentTypeId::of::<T>(),
+            Comparer {
+                name: std::any::type_name::<T>(),
+                compare: Box::new(compare),
+            },
+        );
+        self
+    }
+
+    /// Compares values of tag type `T` with `PartialEq`.
+    pub fn register_tag<T: Tag + Debug>(&mut self) -> &mut Self {
+        let compare = |a: &World, b: &World, entity: Entity| match (
+            a.get_tag::<T>(entity),
+            b.get_tag::<T>(entity),
+        ) {
+            (Some(x), Some(y)) if x != y => Some(format!("{:?} != {:?}", x, y)),
+            _ => None,
+        };
+
+        self.tags.insert(
+            TagTypeId::of::<T>(),
+            Comparer {
+                name: std::any::type_name::<T>(),
+                compare: Box::new(compare),
+            },
+        );
+        self
+    }
+
+    fn component_name(&self, type_id: ComponentTypeId) -> String {
+        self.components
+            .get(&type_id)
+            .map(|c| c.name.to_owned())
+            .unwrap_or_else(|| format!("{:?}", type_id))
+    }
+
+    fn tag_name(&self, type_id: TagTypeId) -> String {
+        self.tags
+            .get(&type_id)
+            .map(|c| c.name.to_owned())
+            .unwrap_or_else(|| format!("{:?}", type_id))
+    }
+}
+
+/// A single difference found between two worlds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// The entity that differs.
+    pub entity: Entity,
+    /// The name of the component or tag type that differs, if the difference is not in the
+    /// entity's liveness.
+    pub data_type: Option<String>,
+    /// A rendered description of the difference.
+    pub description: String,
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.data_type {
+            Some(ref data_type) => {
+                write!(f, "{} [{}]: {}", self.entity, data_type, self.description)
+            }
+            None => write!(f, "{}: {}", self.entity, self.description),
+        }
+    }
+}
+
+/// Lists every entity in the world along with the index of the archetype it is stored in.
+fn entity_archetypes(world: &World) -> FxHashMap<Entity, usize> {
+    let mut entities = FxHashMap::default();
+    for (index, archetype) in world.storage().archetypes().iter().enumerate() {
+        for chunkset in archetype.chunksets() {
+            for chunk in chunkset.occupied() {
+                entities.extend(chunk.entities().iter().map(|e| (*e, index)));
+            }
+        }
+    }
+
+    entities
+}
+
+/// Compares the entities stored in two worlds.
+///
+/// Returns `Ok(())` if both worlds contain the same entities with the same component and tag
+/// types, and all registered types have equal values. Otherwise, all differences are returned.
+pub fn worlds_equal(
+    a: &World,
+    b: &World,
+    registry: &CompareRegistry,
+) -> Result<(), Vec<Difference>> {
+    let entities_a = entity_archetypes(a);
+    let entities_b = entity_archetypes(b);
+
+    let mut differences = Vec::new();
+
+    for (entity, arch_a) in entities_a.iter() {
+        let arch_b = match entities_b.get(entity) {
+            Some(arch_b) => arch_b,
+            None => {
+                differences.push(Difference {
+                    entity: *entity,
+                    data_type: None,
+                    description: "entity missing from right world".to_owned(),
+                });
+                continue;
+            }
+        };
+
+        let desc_a = a.storage().archetypes()[*arch_a].description();
+        let desc_b = b.storage().archetypes()[*arch_b].description();
+
+        for (type_id, _) in desc_a.components() {
+            if !desc_b.components().iter().any(|(t, _)| t == type_id) {
+                differences.push(Difference {
+                    entity: *entity,
+                    data_type: Some(registry.component_name(*type_id)),
+                    description: "component missing from right world".to_owned(),
+                });
+            } else if let Some(comparer) = registry.components.get(type_id) {
+                if let Some(description) = (comparer.compare)(a, b, *entity) {
+                    differences.push(Difference {
+                        entity: *entity,
+                        data_type: Some(comparer.name.to_owned()),
+                        description,
+                    });
+                }
+            }
+        }
+
+        for (type_id, _) in desc_b.components() {
+            if !desc_a.components().iter().any(|(t, _)| t == type_id) {
+                differences.push(Difference {
+                    entity: *entity,
+                    data_type: Some(registry.component_name(*type_id)),
+                    description: "component missing from left world".to_owned(),
+                });
+            }
+        }
+
+        for (type_id, _) in desc_a.tags() {
+            if !desc_b.tags().iter().any(|(t, _)| t == type_id) {
+                differences.push(Difference {
+                    entity: *entity,
+                    data_type: Some(registry.tag_name(*type_id)),
+                    description: "tag missing from right world".to_owned(),
+                });
+            } else if let Some(comparer) = registry.tags.get(type_id) {
+                if let Some(description) = (comparer.compare)(a, b, *entity) {
+                    differences.push(Difference {
+                        entity: *entity,
+                        data_type: Some(comparer.name.to_owned()),
+                        description,
+                    });
+                }
+            }
+        }
+
+        for (type_id, _) in desc_b.tags() {
+            if !desc_a.tags().iter().any(|(t, _)| t == type_id) {
+                differences.push(Difference {
+                    entity: *entity,
+                    data_type: Some(registry.tag_name(*type_id)),
+                    description: "tag missing from left world".to_owned(),
+                });
+            }
+        }
+    }
+
+    for entity in entities_b.keys() {
+        if !entities_a.contains_key(entity) {
+            differences.push(Difference {
+                entity: *entity,
+                data_type: None,
+                description: "entity missing from left world".to_owned(),
+            });
+        }
+    }
+
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(differences)
+    }
+}
+
+/// Checks the internal bookkeeping of a world for inconsistencies, such as after a stress test.
+///
+/// Verifies that every stored entity is alive and located where it is stored, that no entity
+/// is stored twice, that each chunk holds as many of each component as it has entities, that
+/// each archetype has one tag value per chunk set, and that the world's memory statistics agree
+/// with its contents. Returns a description of each inconsistency found.
+pub fn check_consistency(world: &World) -> Result<(), Vec<String>> {
+    let mut problems = Vec::new();
+    let mut seen = FxHashMap::default();
+    let mut total = 0;
+
+    for (a, archetype) in world.storage().archetypes().iter().enumerate() {
+        let desc = archetype.description();
+        for (type_id, _) in desc.tags() {
+            let values = archetype.tags().get(*type_id).map_or(0, |tags| tags.len());
+            if values != archetype.len() {
+                problems.push(format!(
+                    "archetype {} has {} {:?} tag values for {} chunk sets",
+                    a,
+                    values,
+                    type_id,
+                    archetype.len()
+                ));
+            }
+        }
+
+        let mut entities = 0;
+        for (s, chunkset) in archetype.chunksets().iter().enumerate() {
+            for (c, chunk) in chunkset.iter().enumerate() {
+                if chunk.len() > chunk.capacity() {
+                    problems.push(format!("chunk {:?} is over capacity", chunk.id()));
+                }
+
+                for (type_id, _) in desc.components() {
+                    let count = chunk.components(*type_id).map_or(0, |c| c.len());
+                    if count != chunk.len() {
+                        problems.push(format!(
+                            "chunk {:?} has {} entities but {} {:?} components",
+                            chunk.id(),
+                            chunk.len(),
+                            count,
+                            type_id
+                        ));
+                    }
+                }
+
+                for (i, entity) in chunk.entities().iter().enumerate() {
+                    if let Some(previous) = seen.insert(*entity, chunk.id()) {
+                        problems.push(format!(
+                            "{} is stored in both {:?} and {:?}",
+                            world.entity_debug(*entity),
+                            previous,
+                            chunk.id()
+                        ));
+                    }
+
+                    if !world.is_alive(*entity) {
+                        problems.push(format!(
+                            "{} is stored but not alive",
+                            world.entity_debug(*entity)
+                        ));
+                        continue;
+                    }
+
+                    match world.entity_allocator.get_location(entity.index()) {
+                        Some(location)
+                            if location.archetype().as_usize() == a
+                                && location.set().as_usize() == s
+                                && location.chunk().as_usize() == c
+                                && location.component().as_usize() == i => {}
+                        location => problems.push(format!(
+                            "{} is stored at ({}, {}, {}, {}) but located at {:?}",
+                            world.entity_debug(*entity),
+                            a,
+                            s,
+                            c,
+                            i,
+                            location
+                        )),
+                    }
+                }
+
+                entities += chunk.len();
+            }
+        }
+
+        let stats = archetype.stats();
+        if stats.entities != entities {
+            problems.push(format!(
+                "archetype {} stores {} entities but its statistics count {}",
+                a, entities, stats.entities
+            ));
+        }
+        total += entities;
+    }
+
+    let stats = world.memory_stats();
+    if stats.entities != total {
+        problems.push(format!(
+            "world stores {} entities but its statistics count {}",
+            total, stats.entities
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Asserts that two worlds are equal according to `legion::testing::worlds_equal`, panicking
+/// with a list of all differences otherwise.
+#[macro_export]
+macro_rules! assert_worlds_equal {
+    ($a:expr, $b:expr, $registry:expr) => {
+        if let Err(differences) = $crate::testing::worlds_equal($a, $b, $registry) {
+            let mut message = String::new();
+            for difference in &differences {
+                message.push_str(&format!("\n  {}", difference));
+            }
+            panic!(
+                "assertion failed: worlds are not equal ({} differences):{}",
+                differences.len(),
+                message
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity::DebugLabels;
+    use crate::prelude::*;
+    use crate::testing::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Pos(f32, f32);
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Health(u32);
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Model(u32);
+
+    fn registry() -> CompareRegistry {
+        let mut registry = CompareRegistry::default();
+        registry
+            .register::<Health>()
+            .register_with::<Pos, _>(|a, b| (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01)
+            .register_tag::<Model>();
+        registry
+    }
+
+    fn create(health: u32, x: f32) -> World {
+        let mut world = World::new();
+        world.insert(
+            (Model(1),),
+            vec![(Pos(1., 2.), Health(5)), (Pos(x, 4.), Health(health))],
+        );
+        world.insert((), vec![(Pos(5., 6.),)]);
+        world
+    }
+
+    #[test]
+    fn equal_worlds() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        assert_worlds_equal!(&create(6, 3.), &create(6, 3.), &registry());
+    }
+
+    #[test]
+    fn equal_ignores_layout() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let a = create(6, 3.);
+        let mut b = World::new();
+        let entities = b
+            .insert((), vec![(Pos(0., 0.),), (Pos(0., 0.),), (Pos(5., 6.),)])
+            .to_vec();
+        b.add_tag(entities[0], Model(1));
+        b.add_component(entities[0], Health(5));
+        b.add_tag(entities[1], Model(1));
+        b.add_component(entities[1], Health(6));
+        *b.get_component_mut::<Pos>(entities[0]).unwrap() = Pos(1., 2.);
+        *b.get_component_mut::<Pos>(entities[1]).unwrap() = Pos(3., 4.);
+
+        assert_worlds_equal!(&a, &b, &registry());
+    }
+
+    #[test]
+    fn detects_value_difference() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let differences = worlds_equal(&create(6, 3.), &create(7, 3.), &registry()).unwrap_err();
+        assert_eq!(1, differences.len());
+        assert_eq!(
+            Some(std::any::type_name::<Health>().to_owned()),
+            differences[0].data_type
+        );
+        assert_eq!("Health(6) != Health(7)", differences[0].description);
+    }
+
+    #[test]
+    fn float_tolerance() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        assert!(worlds_equal(&create(6, 3.), &create(6, 3.001), &registry()).is_ok());
+        assert!(worlds_equal(&create(6, 3.), &create(6, 3.1), &registry()).is_err());
+    }
+
+    #[test]
+    fn detects_missing_entity() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let a = create(6, 3.);
+        let mut b = create(6, 3.);
+        let entity = *b.insert((), vec![(Health(1),)]).first().unwrap();
+
+        let differences = worlds_equal(&a, &b, &registry()).unwrap_err();
+        assert_eq!(1, differences.len());
+        assert_eq!(entity, differences[0].entity);
+        assert_eq!(None, differences[0].data_type);
+    }
+
+    #[test]
+    fn consistency() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create(6, 3.);
+        let entities = world.insert((), (0..5000).map(|i| (Health(i),))).to_vec();
+        for entity in entities.iter().step_by(3) {
+            world.delete(*entity);
+        }
+        world.add_component(entities[1], Pos(0., 0.));
+        world.defrag(None);
+        assert_eq!(Ok(()), check_consistency(&world));
+
+        // a chunk holding fewer entities than components is reported
+        world.storage_mut().archetypes_mut()[0].chunksets_mut()[0][0].truncate_entities(1);
+        let problems = check_consistency(&world).unwrap_err();
+        assert!(problems.iter().any(|p| p.contains("components")));
+        assert!(problems.iter().any(|p| p.contains("statistics")));
+
+        // problems with an entity include its debug label
+        if DebugLabels::ENABLED {
+            let mut world = create(6, 3.);
+            let entity = world.insert((), vec![(Health(0),)])[0];
+            world.set_debug_label(entity, "orphan");
+            world.entity_allocator.delete_entity(entity);
+            let problems = check_consistency(&world).unwrap_err();
+            assert!(problems
+                .iter()
+                .any(|p| p.contains("\"orphan\" is stored but not alive")));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "worlds are not equal")]
+    fn assert_panics() {
+        assert_worlds_equal!(&create(6, 3.), &create(7, 3.), &registry());
+    }
+}
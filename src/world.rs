@@ -1,92 +1,341 @@
+use crate::borrow::BorrowConflict;
+use crate::borrow::BorrowConflictPolicy;
 use crate::borrow::Ref;
 use crate::borrow::RefMut;
+use crate::clone::{CloneError, CloneImpl};
 use crate::entity::BlockAllocator;
+use crate::entity::DebugLabels;
 use crate::entity::Entity;
 use crate::entity::EntityAllocator;
+use crate::entity::EntityAllocatorStats;
+use crate::entity::EntityDebug;
+use crate::entity::EntityHistories;
+use crate::entity::EntityHistoryEntry;
 use crate::entity::EntityLocation;
+use crate::entity::HistoryOp;
 use crate::event::Event;
 use crate::filter::ArchetypeFilterData;
 use crate::filter::ChunksetFilterData;
+use crate::filter::DynFilter;
 use crate::filter::EntityFilter;
 use crate::filter::Filter;
 use crate::iterator::SliceVecIter;
+use crate::partition::WorldPartition;
+use crate::pod;
+use crate::pod::{ChunkFormatError, PodRegistry};
 use crate::resource::Resources;
 use crate::storage::ArchetypeData;
 use crate::storage::ArchetypeDescription;
+use crate::storage::ArchetypeId;
+use crate::storage::ArchetypeIndex;
+use crate::storage::ArchetypeStats;
+#[cfg(feature = "numa")]
+use crate::storage::ChunkAllocator;
 use crate::storage::Component;
 use crate::storage::ComponentMeta;
+use crate::storage::ComponentResourceSet;
 use crate::storage::ComponentStorage;
 use crate::storage::ComponentTypeId;
+use crate::storage::Components;
+use crate::storage::LayoutMismatch;
+use crate::storage::SetIndex;
+use crate::storage::SlotIndex;
 use crate::storage::Storage;
 use crate::storage::Tag;
 use crate::storage::TagMeta;
 use crate::storage::TagTypeId;
 use crate::storage::Tags;
+use crate::storage::WriteEpochScope;
+use crate::storage::WriteEpochs;
+use crate::storage::DEFAULT_GROWTH_FACTOR;
+use crate::summary::ComponentSummary;
 use crate::tuple::TupleEq;
+use fxhash::FxHashMap;
 use parking_lot::Mutex;
+use std::any::Any;
+use std::borrow::Cow;
 use std::cell::UnsafeCell;
+use std::collections::VecDeque;
 use std::iter::Enumerate;
 use std::iter::Peekable;
 use std::iter::Repeat;
 use std::iter::Take;
 use std::marker::PhantomData;
+use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::ptr::NonNull;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use tracing::{info, span, trace, Level};
+use tracing::{info, span, trace, warn, Level};
 
 /// The `Universe` is a factory for creating `World`s.
 ///
 /// Entities inserted into worlds created within the same universe are guarenteed to have
 /// unique `Entity` IDs, even across worlds.
-#[derive(Debug)]
+///
+/// A `Universe` is a cheap handle to shared state; clones refer to the same universe and
+/// may be sent to other threads to create worlds concurrently. Each world holds a handle
+/// back to the universe it was created in, so the universe lives at least as long as its
+/// worlds.
+#[derive(Debug, Clone, Default)]
 pub struct Universe {
+    inner: Arc<UniverseInner>,
+}
+
+#[derive(Debug)]
+struct UniverseInner {
     allocator: Arc<Mutex<BlockAllocator>>,
     world_count: AtomicUsize,
+    options: UniverseOptions,
+}
+
+impl UniverseInner {
+    fn new(options: UniverseOptions) -> Self {
+        Self {
+            world_count: AtomicUsize::from(0),
+            allocator: Arc::new(Mutex::new(BlockAllocator::new(
+                options.deterministic_entities,
+            ))),
+            options,
+        }
+    }
+}
+
+impl Default for UniverseInner {
+    fn default() -> Self { Self::new(UniverseOptions::default()) }
+}
+
+/// Options used to configure a `Universe`.
+///
+/// As with `WorldOptions`, options must be created with `default` and then modified.
+///
+/// ```
+/// # use legion::world::{Universe, UniverseOptions};
+/// let options = UniverseOptions::default().with_deterministic_entities(true);
+/// let universe = Universe::with_options(options);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UniverseOptions {
+    /// Makes `Entity` ID allocation reproducible, e.g. for replay tests. Defaults to `false`.
+    ///
+    /// Worlds allocate IDs in blocks from a counter shared by the universe. Blocks returned
+    /// by dropped worlds are normally handed out again, so the IDs allocated after a world
+    /// is dropped depend upon when it was dropped, possibly on another thread. When this is
+    /// set, returned blocks are discarded and every block is taken from the counter in order.
+    ///
+    /// IDs are then determined entirely by the sequence of operations performed on each
+    /// world, provided that worlds in the same universe first allocate from each new block
+    /// in a consistent order, such as by being populated from one thread. The order in
+    /// which each operation assigns IDs is documented on `World::insert`,
+    /// `World::clone_entity_batch` and `CommandBuffer::write`.
+    pub deterministic_entities: bool,
+}
+
+impl UniverseOptions {
+    /// Sets `deterministic_entities`.
+    pub fn with_deterministic_entities(mut self, deterministic_entities: bool) -> Self {
+        self.deterministic_entities = deterministic_entities;
+        self
+    }
+}
+
+/// Statistics about the entities and chunk memory of a `World`. See `World::memory_stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemoryStats {
+    /// The number of entities in the world.
+    pub entities: usize,
+    /// The number of chunks with allocated memory in the world.
+    pub chunks: usize,
+    /// The number of bytes of component memory allocated by the world's chunks.
+    pub allocated_bytes: usize,
+    /// Statistics for each archetype, in archetype order.
+    pub archetypes: Vec<ArchetypeStats>,
+}
+
+/// Statistics about the state shared by all worlds in a `Universe`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UniverseStats {
+    /// The number of worlds created in the universe.
+    pub worlds_created: usize,
+    /// The number of entity ID blocks which have been allocated from the universe.
+    pub entity_blocks_allocated: usize,
+    /// The number of entity ID blocks which have been returned by dropped worlds and are
+    /// available for reuse.
+    pub entity_blocks_free: usize,
 }
 
 impl Universe {
     /// Creates a new `Universe`.
     pub fn new() -> Self { Self::default() }
 
+    /// Creates a new `Universe`, configured with the given options.
+    pub fn with_options(options: UniverseOptions) -> Self {
+        Self {
+            inner: Arc::new(UniverseInner::new(options)),
+        }
+    }
+
+    /// Gets the options the universe was created with.
+    pub fn options(&self) -> UniverseOptions { self.inner.options }
+
+    /// Determines if two handles refer to the same universe.
+    pub fn ptr_eq(&self, other: &Universe) -> bool { Arc::ptr_eq(&self.inner, &other.inner) }
+
+    /// Gets statistics about the state shared by worlds in this universe.
+    pub fn stats(&self) -> UniverseStats {
+        let allocator = self.inner.allocator.lock();
+        UniverseStats {
+            worlds_created: self.inner.world_count.load(Ordering::SeqCst),
+            entity_blocks_allocated: allocator.allocated_blocks(),
+            entity_blocks_free: allocator.free_blocks(),
+        }
+    }
+
     /// Creates a new `World` within this `Universe`.
     ///
     /// Entities inserted into worlds created within the same universe are guarenteed to have
     /// unique `Entity` IDs, even across worlds. See also `World::new`.
-    pub fn create_world(&self) -> World {
-        let id = self.world_count.fetch_add(1, Ordering::SeqCst);
-        let world =
-            World::new_in_universe(WorldId(id), EntityAllocator::new(self.allocator.clone()));
+    pub fn create_world(&self) -> World { self.create_world_with_options(WorldOptions::default()) }
+
+    /// Creates a new `World` within this `Universe`, configured with the given options.
+    pub fn create_world_with_options(&self, options: WorldOptions) -> World {
+        let id = self.inner.world_count.fetch_add(1, Ordering::SeqCst);
+        let world = World::new_in_universe(WorldId(id), self.clone(), options);
 
         info!(world = world.id().0, "Created world");
         world
     }
 }
 
-impl Default for Universe {
+#[derive(Default, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct WorldId(usize);
+
+impl WorldId {
+    pub fn index(self) -> usize { self.0 }
+}
+
+/// Options used to configure a `World`.
+///
+/// New options may be added in minor releases, so options must be created with `default` and
+/// then modified, either by assigning to their fields or with the `with_*` methods.
+///
+/// ```
+/// # use legion::prelude::*;
+/// # use legion::world::WorldOptions;
+/// let options = WorldOptions::default()
+///     .with_record_spawn_tick(true)
+///     .with_write_epochs(true);
+/// let world = Universe::new().create_world_with_options(options);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct WorldOptions {
+    /// The factor by which each archetype's chunk set and chunk vectors grow when they
+    /// are full. Defaults to `2.0`.
+    ///
+    /// Smaller factors waste less memory, while larger factors cause fewer reallocations
+    /// when many chunks are created one at a time. See also `World::reserve_chunks`.
+    pub chunk_growth_factor: f32,
+    /// Records the world tick at which each entity was spawned. Defaults to `false`.
+    ///
+    /// This costs 8 bytes of memory per entity. See also `World::spawn_tick` and
+    /// `filter::filter_fns::spawned_before`.
+    pub record_spawn_tick: bool,
+    /// Limits the version of each chunk's components to one increment per write epoch.
+    /// Defaults to `false`.
+    ///
+    /// Without epochs, every mutable borrow of a chunk's components increments their version.
+    /// See `World::begin_write_epoch`.
+    pub write_epochs: bool,
+    /// Panics when an entity is inserted with a component type which is larger than a chunk
+    /// (16 KiB). Defaults to `false`.
+    ///
+    /// Otherwise, such components are allowed with a warning, and each chunk of their
+    /// archetypes holds a single entity. Oversized components are never moved out of the chunk
+    /// into separate storage, as views borrow each chunk's components as a contiguous slice.
+    pub deny_oversized_components: bool,
+    /// The number of entities above which each archetype emits
+    /// `Event::EntityThresholdExceeded`. Defaults to `None`.
+    ///
+    /// Archetypes whose descriptions set their own threshold use that instead. See
+    /// `ArchetypeDescription::set_entity_threshold` and `World::memory_stats`.
+    pub entity_threshold: Option<usize>,
+    /// The number of placement groups which chunks are assigned to in turn. Defaults to `1`.
+    ///
+    /// Placement hints allow parallel iteration to process each chunk on a group of threads
+    /// near the memory the chunk was allocated from, such as on machines with several NUMA
+    /// nodes. See `ComponentStorage::placement` and the `placement` module.
+    pub placement_groups: u8,
+}
+
+impl Default for WorldOptions {
     fn default() -> Self {
         Self {
-            world_count: AtomicUsize::from(0),
-            allocator: Arc::new(Mutex::new(BlockAllocator::new())),
+            chunk_growth_factor: DEFAULT_GROWTH_FACTOR,
+            record_spawn_tick: false,
+            write_epochs: false,
+            deny_oversized_components: false,
+            entity_threshold: None,
+            placement_groups: 1,
         }
     }
 }
 
-#[derive(Default, Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub struct WorldId(usize);
+impl WorldOptions {
+    /// Sets `chunk_growth_factor`.
+    pub fn with_chunk_growth_factor(mut self, chunk_growth_factor: f32) -> Self {
+        self.chunk_growth_factor = chunk_growth_factor;
+        self
+    }
 
-impl WorldId {
-    pub fn index(self) -> usize { self.0 }
+    /// Sets `record_spawn_tick`.
+    pub fn with_record_spawn_tick(mut self, record_spawn_tick: bool) -> Self {
+        self.record_spawn_tick = record_spawn_tick;
+        self
+    }
+
+    /// Sets `write_epochs`.
+    pub fn with_write_epochs(mut self, write_epochs: bool) -> Self {
+        self.write_epochs = write_epochs;
+        self
+    }
+
+    /// Sets `deny_oversized_components`.
+    pub fn with_deny_oversized_components(mut self, deny_oversized_components: bool) -> Self {
+        self.deny_oversized_components = deny_oversized_components;
+        self
+    }
+
+    /// Sets `entity_threshold`.
+    pub fn with_entity_threshold(mut self, entity_threshold: Option<usize>) -> Self {
+        self.entity_threshold = entity_threshold;
+        self
+    }
+
+    /// Sets `placement_groups`.
+    pub fn with_placement_groups(mut self, placement_groups: u8) -> Self {
+        self.placement_groups = placement_groups;
+        self
+    }
 }
 
 /// Contains queryable collections of data associated with `Entity`s.
 pub struct World {
     id: WorldId,
+    universe: Universe,
     storage: UnsafeCell<Storage>,
     pub(crate) entity_allocator: EntityAllocator,
     defrag_progress: usize,
+    location_epoch: u64,
+    tick: Arc<AtomicU64>,
+    write_epochs: Arc<WriteEpochs>,
+    debug_labels: DebugLabels,
+    history: EntityHistories,
     pub resources: Resources,
 }
 
@@ -99,19 +348,42 @@ impl World {
     ///
     /// `Entity` IDs in such a world will only be unique within that world. See also
     /// `Universe::create_world`.
-    pub fn new() -> Self {
-        Self::new_in_universe(
-            WorldId(0),
-            EntityAllocator::new(Arc::new(Mutex::new(BlockAllocator::new()))),
-        )
+    pub fn new() -> Self { Self::with_options(WorldOptions::default()) }
+
+    /// Create a new `World` independent of any `Universe`, configured with the given options.
+    pub fn with_options(options: WorldOptions) -> Self {
+        Universe::new().create_world_with_options(options)
     }
 
-    fn new_in_universe(id: WorldId, allocator: EntityAllocator) -> Self {
+    fn new_in_universe(id: WorldId, universe: Universe, options: WorldOptions) -> Self {
+        let allocator = EntityAllocator::new(universe.inner.allocator.clone());
+        let mut storage = Storage::new(id);
+        storage.set_growth_factor(options.chunk_growth_factor);
+        storage.deny_oversized_components(options.deny_oversized_components);
+        storage.set_entity_threshold(options.entity_threshold);
+        storage.set_placement_groups(options.placement_groups);
+
+        let tick = Arc::new(AtomicU64::new(0));
+        if options.record_spawn_tick {
+            storage.record_spawn_ticks(tick.clone());
+        }
+
+        let write_epochs = Arc::new(WriteEpochs::default());
+        if options.write_epochs {
+            storage.record_write_epochs(write_epochs.clone());
+        }
+
         Self {
             id,
-            storage: UnsafeCell::new(Storage::new(id)),
+            universe,
+            storage: UnsafeCell::new(storage),
             entity_allocator: allocator,
             defrag_progress: 0,
+            location_epoch: 0,
+            tick,
+            write_epochs,
+            debug_labels: DebugLabels::default(),
+            history: EntityHistories::default(),
             resources: Resources::default(),
         }
     }
@@ -152,8 +424,52 @@ impl World {
     /// Gets the unique ID of this world within its universe.
     pub fn id(&self) -> WorldId { self.id }
 
+    /// Gets the universe this world was created in.
+    ///
+    /// Worlds created with `World::new` belong to their own private universe.
+    pub fn universe(&self) -> &Universe { &self.universe }
+
+    /// Gets the current world tick. The tick starts at `0` and is only changed by
+    /// `advance_tick`.
+    pub fn tick(&self) -> u64 { self.tick.load(Ordering::Relaxed) }
+
+    /// Increments the world tick, returning the new tick.
+    ///
+    /// Entities inserted into a world created with `WorldOptions::record_spawn_tick` record
+    /// the tick at which they were spawned.
+    pub fn advance_tick(&mut self) -> u64 { self.tick.fetch_add(1, Ordering::Relaxed) + 1 }
+
+    /// Begins a new write epoch, returning its ID.
+    ///
+    /// In a world created with `WorldOptions::write_epochs`, only the first mutable borrow of
+    /// each chunk's components within an epoch increments their version, so that consumers
+    /// of `changed` filters and `Version` views observe a single change per epoch. See also
+    /// `enter_write_epoch`, which the schedule uses to run each system within its own epoch.
+    pub fn begin_write_epoch(&self) -> u64 { self.write_epochs.begin() }
+
+    /// Enters a new write epoch on the current thread, until the returned scope is dropped.
+    ///
+    /// Mutable borrows made by the current thread use this epoch instead of the one begun with
+    /// `begin_write_epoch`, while other threads are unaffected. This allows systems running in
+    /// parallel to each write within their own epoch. Borrows made on other threads on behalf
+    /// of the current one, such as by `par_for_each`, are not part of the epoch.
+    pub fn enter_write_epoch(&self) -> WriteEpochScope { self.write_epochs.enter() }
+
+    /// Ends the current write epoch. Until the next epoch begins, every mutable borrow of a
+    /// chunk's components increments their version.
+    pub fn end_write_epoch(&self) { self.write_epochs.end() }
+
     /// Inserts new entities into the world.
     ///
+    /// Each tag and component type may only appear once in `tags` and in the component tuples.
+    ///
+    /// Entities are allocated IDs in the order in which `components` yields them, and the
+    /// returned IDs are in that order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a tag or component type is given more than once. See `try_insert`.
+    ///
     /// # Examples
     ///
     /// Inserting entity tuples:
@@ -176,7 +492,33 @@ impl World {
     /// ];
     /// world.insert(tags, data);
     /// ```
-    pub fn insert<T, C>(&mut self, mut tags: T, components: C) -> &[Entity]
+    pub fn insert<T, C>(&mut self, tags: T, components: C) -> &[Entity]
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        C: IntoComponentSource,
+    {
+        match self.try_insert(tags, components) {
+            Ok(entities) => entities,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Inserts new entities into the world, or returns an error if a tag or component type is
+    /// given more than once.
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # use legion::world::InsertError;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    /// # struct Team(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let result = world.try_insert((Team(1), Team(2)), vec![(Position(0.0),)]);
+    /// assert!(matches!(result, Err(InsertError::DuplicateTag(_))));
+    /// ```
+    pub fn try_insert<T, C>(&mut self, mut tags: T, components: C) -> Result<&[Entity], InsertError>
     where
         T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
         C: IntoComponentSource,
@@ -184,8 +526,15 @@ impl World {
         let span = span!(Level::TRACE, "Inserting entities", world = self.id().0);
         let _guard = span.enter();
 
-        // find or create archetype
         let mut components = components.into();
+        if let Some(name) = tags.duplicate_type() {
+            return Err(InsertError::DuplicateTag(name));
+        }
+        if let Some(name) = components.duplicate_type() {
+            return Err(InsertError::DuplicateComponent(name));
+        }
+
+        // find or create archetype
         let archetype_index = self.find_or_create_archetype(&mut tags, &mut components);
 
         // find or create chunk set
@@ -199,14 +548,14 @@ impl World {
             let archetype = unsafe {
                 (&mut *self.storage.get())
                     .archetypes_mut()
-                    .get_unchecked_mut(archetype_index)
+                    .get_unchecked_mut(archetype_index.as_usize())
             };
             let chunk_index = archetype.get_free_chunk(chunk_set_index);
             let chunk = unsafe {
                 archetype
                     .chunksets_mut()
-                    .get_unchecked_mut(chunk_set_index)
-                    .get_unchecked_mut(chunk_index)
+                    .get_unchecked_mut(chunk_set_index.as_usize())
+                    .get_unchecked_mut(chunk_index.as_usize())
             };
 
             // insert as many components as we can into the chunk
@@ -216,17 +565,147 @@ impl World {
             let start = chunk.len() - allocated;
             let added = chunk.entities().iter().enumerate().skip(start);
             for (i, e) in added {
-                let location =
-                    EntityLocation::new(archetype_index, chunk_set_index, chunk_index, i);
+                let location = EntityLocation::new(
+                    archetype_index,
+                    chunk_set_index,
+                    chunk_index,
+                    SlotIndex::new(i),
+                );
                 self.entity_allocator.set_location(e.index(), location);
             }
         }
 
+        let tick = self.tick();
         let entities = self.entity_allocator.allocation_buffer();
+        self.history
+            .record(entities.iter().copied(), HistoryOp::Inserted, tick);
 
         trace!(count = entities.len(), "Inserted entities");
 
-        entities
+        Ok(entities)
+    }
+
+    /// Pre-sizes the storage for entities with component types `C` and each of the given sets
+    /// of tag values, such that later insertions do not need to grow the archetype's chunk
+    /// vectors.
+    ///
+    /// Each chunk set is filled with at least `chunks_per_set` (initially empty) chunks. Chunk
+    /// memory is still only allocated once entities are written into each chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Model(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.reserve_chunks::<_, (Position,), _>((0..100).map(|i| (Model(i),)), 1);
+    ///
+    /// for i in 0..100 {
+    ///     world.insert((Model(i),), vec![(Position(0.0),)]);
+    /// }
+    /// ```
+    pub fn reserve_chunks<T, C, I>(&mut self, tags: I, chunks_per_set: usize)
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        I: IntoIterator<Item = T>,
+        ComponentTupleSet<C, std::iter::Empty<C>>: ComponentLayout,
+    {
+        let mut tags = tags.into_iter().peekable();
+        let mut components = ComponentTupleSet {
+            iter: std::iter::empty().peekable(),
+            filter: ComponentTupleFilter::<C> {
+                _phantom: PhantomData,
+            },
+        };
+
+        let archetype_index = match tags.peek_mut() {
+            Some(first) => self.find_or_create_archetype(first, &mut components),
+            None => return,
+        };
+
+        let (additional, _) = tags.size_hint();
+        self.storage_mut().archetypes_mut()[archetype_index.as_usize()]
+            .reserve_chunk_sets(additional);
+
+        for mut tags in tags {
+            let set_index = self.find_or_create_chunk(archetype_index, &mut tags);
+            self.storage_mut().archetypes_mut()[archetype_index.as_usize()]
+                .reserve_chunks(set_index, chunks_per_set);
+        }
+    }
+
+    /// Inserts `count` entities into the world, creating the components of each entity by
+    /// calling `f` with the entity's index.
+    ///
+    /// This is equivalent to inserting `(0..count).map(f)`, but as the number of entities
+    /// is known up front, each component is written directly into its destination chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Velocity(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let entities = world
+    ///     .insert_from_fn((), 1000, |i| (Position(i as f32), Velocity(0.0)))
+    ///     .to_vec();
+    ///
+    /// assert_eq!(Position(10.0), *world.get_component::<Position>(entities[10]).unwrap());
+    /// ```
+    pub fn insert_from_fn<T, C, F>(&mut self, tags: T, count: usize, f: F) -> &[Entity]
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        F: FnMut(usize) -> C,
+        ComponentFnSource<C, F>: ComponentSource,
+    {
+        self.insert(tags, ComponentFnSource::new(count, f))
+    }
+
+    /// Inserts new entities into the world, and gives each a debug label created by calling
+    /// `label` with the entity's index within the batch. See `set_debug_label`.
+    ///
+    /// `label` is not called unless debug labels are enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # use legion::entity::DebugLabels;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let data = vec![(Position(0.0),), (Position(1.0),)];
+    /// let entities = world
+    ///     .insert_labeled((), data, |i| format!("enemy {}", i))
+    ///     .to_vec();
+    ///
+    /// if DebugLabels::ENABLED {
+    ///     assert_eq!(Some("enemy 1"), world.debug_label(entities[1]));
+    /// }
+    /// ```
+    pub fn insert_labeled<T, C, F, L>(&mut self, tags: T, components: C, mut label: F) -> &[Entity]
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        C: IntoComponentSource,
+        F: FnMut(usize) -> L,
+        L: Into<Cow<'static, str>>,
+    {
+        self.insert(tags, components);
+        if DebugLabels::ENABLED {
+            for (i, entity) in self.entity_allocator.allocation_buffer().iter().enumerate() {
+                self.debug_labels.set(*entity, label(i).into());
+            }
+        }
+        self.entity_allocator.allocation_buffer()
     }
 
     pub(crate) fn insert_buffered<T, C>(&mut self, entity: Entity, tags: T, components: C)
@@ -238,6 +717,119 @@ impl World {
         unimplemented!()
     }
 
+    /// Clones an entity within the world, returning the ID of the clone.
+    ///
+    /// The clone has the same tag values as the original, and a clone of each of its
+    /// components, made by `cloner`. See `clone_entity_batch`.
+    pub fn clone_entity(
+        &mut self,
+        entity: Entity,
+        cloner: &CloneImpl,
+    ) -> Result<Entity, CloneError> {
+        self.clone_entity_batch(&[entity], cloner)
+            .map(|clones| clones[0])
+    }
+
+    /// Clones entities within the world, returning the IDs of the clones in the same order.
+    /// Clones are allocated IDs in that order, too.
+    ///
+    /// Components registered with `CloneImpl::register_remap` have their references to any of
+    /// the cloned entities rewritten to refer to the corresponding clone.
+    ///
+    /// An entity may be given more than once, to clone it several times. The clones made from
+    /// each entity's first occurrence refer to each other, as do those made from each entity's
+    /// second occurrence, and so on, so that `[a, b, a, b]` clones `a` and `b` into two
+    /// independent copies. References to an entity which occurs fewer times than the clone's
+    /// own entity refer to that entity's last clone.
+    ///
+    /// No entities are cloned if any entity is not alive, or has a component type which has
+    /// not been registered with `cloner`.
+    pub fn clone_entity_batch(
+        &mut self,
+        entities: &[Entity],
+        cloner: &CloneImpl,
+    ) -> Result<Vec<Entity>, CloneError> {
+        let span = span!(Level::TRACE, "Cloning entities", world = self.id().0);
+        let _guard = span.enter();
+
+        let mut sources = Vec::with_capacity(entities.len());
+        for (i, entity) in entities.iter().enumerate() {
+            let location = self
+                .entity_allocator
+                .get_location(entity.index())
+                .filter(|_| self.is_alive(*entity))
+                .ok_or(CloneError::EntityNotFound(*entity))?;
+            let archetype = &self.storage().archetypes()[location.archetype().as_usize()];
+            cloner.validate(archetype.description())?;
+            sources.push((location, i));
+        }
+
+        self.entity_allocator.clear_allocation_buffer();
+        let clones = entities
+            .iter()
+            .map(|_| self.entity_allocator.create_entity())
+            .collect::<Vec<_>>();
+        // the clones of each entity, in order of occurrence
+        let mut map = FxHashMap::<Entity, Vec<Entity>>::default();
+        let occurrences = entities
+            .iter()
+            .zip(clones.iter())
+            .map(|(entity, clone)| {
+                let entity_clones = map.entry(*entity).or_default();
+                entity_clones.push(*clone);
+                entity_clones.len() - 1
+            })
+            .collect::<Vec<_>>();
+
+        // clone entities chunk by chunk; clones are only appended to chunks, so the
+        // locations of the originals remain valid
+        sources.sort_by_key(|(location, _)| {
+            (
+                location.archetype(),
+                location.set(),
+                location.chunk(),
+                location.component(),
+            )
+        });
+        for (location, i) in sources {
+            // references resolve to the clones made from the same occurrence
+            let occurrence = occurrences[i];
+            let remap = |entity: Entity| match map.get(&entity) {
+                Some(entity_clones) => entity_clones[occurrence.min(entity_clones.len() - 1)],
+                None => entity,
+            };
+            let archetype = self
+                .storage_mut()
+                .archetype_mut(location.archetype())
+                .unwrap();
+            let (chunk, component) = unsafe {
+                archetype.clone_entity(
+                    location.set(),
+                    location.chunk(),
+                    location.component(),
+                    clones[i],
+                    cloner,
+                    &remap,
+                )
+            };
+            let location =
+                EntityLocation::new(location.archetype(), location.set(), chunk, component);
+            self.entity_allocator
+                .set_location(clones[i].index(), location);
+        }
+
+        let tick = self.tick();
+        for (entity, clone) in entities.iter().zip(clones.iter()) {
+            self.debug_labels.copy(*entity, *clone);
+            self.history
+                .record(Some(*clone), HistoryOp::Cloned(*entity), tick);
+        }
+
+        trace!(count = clones.len(), "Cloned entities");
+
+        Ok(clones)
+    }
+
     /// Removes the given `Entity` from the `World`.
     ///
     /// Returns `true` if the entity was deleted; else `false`.
@@ -247,20 +839,24 @@ impl World {
             let chunk = self
                 .storage_mut()
                 .archetypes_mut()
-                .get_mut(location.archetype())
+                .get_mut(location.archetype().as_usize())
                 .unwrap()
                 .chunksets_mut()
-                .get_mut(location.set())
+                .get_mut(location.set().as_usize())
                 .unwrap()
-                .get_mut(location.chunk())
+                .get_mut(location.chunk().as_usize())
                 .unwrap();
 
             // swap remove with last entity in chunk
-            if let Some(swapped) = chunk.swap_remove(location.component(), true) {
+            if let Some(swapped) = chunk.swap_remove(location.component().as_usize(), true) {
                 // record swapped entity's new location
                 self.entity_allocator
                     .set_location(swapped.index(), location);
             }
+            self.location_epoch += 1;
+            self.debug_labels.remove(entity);
+            let tick = self.tick();
+            self.history.record(Some(entity), HistoryOp::Deleted, tick);
 
             trace!(world = self.id().0, ?entity, "Deleted entity");
 
@@ -277,13 +873,12 @@ impl World {
         remove_components: &[ComponentTypeId],
         add_tags: &[(TagTypeId, TagMeta, NonNull<u8>)],
         remove_tags: &[TagTypeId],
-    ) -> (usize, usize) {
+    ) -> (ArchetypeIndex, SetIndex) {
         let archetype = {
             let result = {
                 let source_archetype = self
                     .storage()
-                    .archetypes()
-                    .get(source_location.archetype())
+                    .archetype(source_location.archetype())
                     .unwrap();
 
                 // find target chunk
@@ -296,7 +891,7 @@ impl World {
                 let mut tag_layout = DynamicTagLayout {
                     storage: self.storage(),
                     archetype: source_location.archetype(),
-                    chunk: source_location.chunk(),
+                    set: source_location.set(),
                     existing: source_archetype.description().tags(),
                     add: add_tags,
                     remove: remove_tags,
@@ -331,10 +926,11 @@ impl World {
         // slow path: create new chunk
         let source_archetype = self
             .storage()
-            .archetypes()
-            .get(source_location.archetype())
+            .archetype(source_location.archetype())
             .unwrap();
-        let mut tags = source_archetype.tags().tag_set(source_location.chunk());
+        let mut tags = source_archetype
+            .tags()
+            .tag_set(source_location.set().as_usize());
         for type_id in remove_tags.iter() {
             tags.remove(*type_id);
         }
@@ -354,6 +950,7 @@ impl World {
         remove_components: &[ComponentTypeId],
         add_tags: &[(TagTypeId, TagMeta, NonNull<u8>)],
         remove_tags: &[TagTypeId],
+        take_components: &[(ComponentTypeId, NonNull<u8>)],
     ) -> &mut ComponentStorage {
         let location = self
             .entity_allocator
@@ -377,42 +974,46 @@ impl World {
         // fetch entity's chunk
         let current_chunk = unsafe { &mut *self.storage.get() }
             .archetypes_mut()
-            .get_mut(location.archetype())
+            .get_mut(location.archetype().as_usize())
             .unwrap()
             .chunksets_mut()
-            .get_mut(location.set())
+            .get_mut(location.set().as_usize())
             .unwrap()
-            .get_mut(location.chunk())
+            .get_mut(location.chunk().as_usize())
             .unwrap();
 
         // fetch target chunk
         let archetype = unsafe { &mut *self.storage.get() }
-            .archetypes_mut()
-            .get_mut(target_arch_index)
+            .archetype_mut(target_arch_index)
             .unwrap();
         let target_chunk_index = archetype.get_free_chunk(target_chunkset_index);
         let target_chunk = unsafe {
             archetype
                 .chunksets_mut()
-                .get_unchecked_mut(target_chunkset_index)
-                .get_unchecked_mut(target_chunk_index)
+                .get_unchecked_mut(target_chunkset_index.as_usize())
+                .get_unchecked_mut(target_chunk_index.as_usize())
         };
 
         // move existing data over into new chunk
-        if let Some(swapped) = current_chunk.move_entity(target_chunk, location.component()) {
+        if let Some(swapped) = current_chunk.move_entity_taking(
+            target_chunk,
+            location.component().as_usize(),
+            take_components,
+        ) {
             // update location of any entity that was moved into the previous location
             self.entity_allocator
                 .set_location(swapped.index(), location);
         }
 
         // record the entity's new location
+        self.location_epoch += 1;
         self.entity_allocator.set_location(
             entity.index(),
             EntityLocation::new(
                 target_arch_index,
                 target_chunkset_index,
                 target_chunk_index,
-                target_chunk.len() - 1,
+                SlotIndex::new(target_chunk.len() - 1),
             ),
         );
 
@@ -434,6 +1035,11 @@ impl World {
             "Adding component to entity"
         );
 
+        let name = std::any::type_name::<T>();
+        let tick = self.tick();
+        self.history
+            .record(Some(entity), HistoryOp::ComponentAdded(name), tick);
+
         // move the entity into a suitable chunk
         let target_chunk = self.move_entity(
             entity,
@@ -441,6 +1047,7 @@ impl World {
             &[],
             &[],
             &[],
+            &[],
         );
 
         // push new component into chunk
@@ -469,15 +1076,63 @@ impl World {
             );
 
             // move the entity into a suitable chunk
-            self.move_entity(entity, &[], &[ComponentTypeId::of::<T>()], &[], &[]);
+            self.move_entity(entity, &[], &[ComponentTypeId::of::<T>()], &[], &[], &[]);
+
+            let name = std::any::type_name::<T>();
+            let tick = self.tick();
+            self.history
+                .record(Some(entity), HistoryOp::ComponentRemoved(name), tick);
         }
     }
 
+    /// Removes a component from an entity, returning its value.
+    ///
+    /// Returns `None` if the entity does not have the component.
+    pub fn take_component<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        self.get_component::<T>(entity)?;
+
+        trace!(
+            world = self.id().0,
+            ?entity,
+            component = std::any::type_name::<T>(),
+            "Taking component from entity"
+        );
+
+        // move the entity into a suitable chunk, moving the component out rather than dropping it
+        let mut component = MaybeUninit::<T>::uninit();
+        let out = NonNull::new(component.as_mut_ptr() as *mut u8).unwrap();
+        self.move_entity(
+            entity,
+            &[],
+            &[ComponentTypeId::of::<T>()],
+            &[],
+            &[],
+            &[(ComponentTypeId::of::<T>(), out)],
+        );
+
+        let name = std::any::type_name::<T>();
+        let tick = self.tick();
+        self.history
+            .record(Some(entity), HistoryOp::ComponentRemoved(name), tick);
+
+        Some(unsafe { component.assume_init() })
+    }
+
+    /// Replaces the value of a component on an entity, returning the previous value.
+    ///
+    /// Unlike `add_component`, this never changes the entity's archetype. Returns `None`, and
+    /// drops `component`, if the entity does not have the component.
+    pub fn replace_component<T: Component>(&mut self, entity: Entity, component: T) -> Option<T> {
+        self.get_component_mut::<T>(entity)
+            .map(|mut current| std::mem::replace(&mut *current, component))
+    }
+
     /// Adds a tag to an entity, or sets its value if the tag is
     /// already present.
     pub fn add_tag<T: Tag>(&mut self, entity: Entity, tag: T) {
-        if self.get_tag::<T>(entity).is_some() {
-            self.remove_tag::<T>(entity);
+        let replaced = self.get_tag::<T>(entity).is_some();
+        if replaced {
+            self.move_entity(entity, &[], &[], &[], &[TagTypeId::of::<T>()], &[]);
         }
 
         trace!(
@@ -498,7 +1153,17 @@ impl World {
                 NonNull::new(&tag as *const _ as *mut u8).unwrap(),
             )],
             &[],
+            &[],
         );
+
+        let name = std::any::type_name::<T>();
+        let kind = if replaced {
+            HistoryOp::TagChanged(name)
+        } else {
+            HistoryOp::TagAdded(name)
+        };
+        let tick = self.tick();
+        self.history.record(Some(entity), kind, tick);
     }
 
     /// Removes a tag from an entity.
@@ -512,7 +1177,12 @@ impl World {
             );
 
             // move the entity into a suitable chunk
-            self.move_entity(entity, &[], &[], &[], &[TagTypeId::of::<T>()]);
+            self.move_entity(entity, &[], &[], &[], &[TagTypeId::of::<T>()], &[]);
+
+            let name = std::any::type_name::<T>();
+            let tick = self.tick();
+            self.history
+                .record(Some(entity), HistoryOp::TagRemoved(name), tick);
         }
     }
 
@@ -520,28 +1190,37 @@ impl World {
     ///
     /// Returns `Some(data)` if the entity was found and contains the specified data.
     /// Otherwise `None` is returned.
+    ///
+    /// If the component is already borrowed mutably, the world's `BorrowConflictPolicy`
+    /// determines whether this panics or returns `None`.
     pub fn get_component<T: Component>(&self, entity: Entity) -> Option<Ref<T>> {
-        if !self.is_alive(entity) {
-            return None;
-        }
-
-        let location = self.entity_allocator.get_location(entity.index())?;
-        let archetype = self.storage().archetypes().get(location.archetype())?;
-        let chunk = archetype
-            .chunksets()
-            .get(location.set())?
-            .get(location.chunk())?;
-        let (slice_borrow, slice) = unsafe {
-            chunk
-                .components(ComponentTypeId::of::<T>())?
-                .data_slice::<T>()
-                .deconstruct()
-        };
-        let component = slice.get(location.component())?;
+        let (components, index) = self.find_components::<T>(entity)?;
+        self.check_borrow::<T>(entity, components, false).ok()?;
+        let (slice_borrow, slice) = unsafe { components.data_slice::<T>().deconstruct() };
+        let component = slice.get(index)?;
 
         Some(Ref::new(slice_borrow, component))
     }
 
+    /// Borrows component data for the given entity, or returns an error if the component is
+    /// already borrowed mutably. Conflicts are returned regardless of the world's
+    /// `BorrowConflictPolicy`.
+    ///
+    /// Returns `Ok(None)` if the entity was not found or does not contain the specified data.
+    pub fn try_get_component<T: Component>(
+        &self,
+        entity: Entity,
+    ) -> Result<Option<Ref<'_, T>>, BorrowConflict> {
+        let (components, index) = match self.find_components::<T>(entity) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let (slice_borrow, slice) = unsafe { components.try_data_slice::<T>()?.deconstruct() };
+        Ok(slice
+            .get(index)
+            .map(|component| Ref::new(slice_borrow, component)))
+    }
+
     /// Mutably borrows entity data for the given entity.
     ///
     /// Returns `Some(data)` if the entity was found and contains the specified data.
@@ -558,23 +1237,89 @@ impl World {
         &self,
         entity: Entity,
     ) -> Option<RefMut<T>> {
-        if !self.is_alive(entity) {
+        let (components, index) = self.find_components::<T>(entity)?;
+        self.check_borrow::<T>(entity, components, true).ok()?;
+        components.data_element_mut::<T>(index)
+    }
+
+    /// Mutably borrows component data for the given entity, or returns an error if the
+    /// component is already borrowed. Conflicts are returned regardless of the world's
+    /// `BorrowConflictPolicy`.
+    ///
+    /// Returns `Ok(None)` if the entity was not found or does not contain the specified data.
+    ///
+    /// # Safety
+    ///
+    /// Accessing a component which is already being concurrently accessed elsewhere is undefined behavior.
+    pub unsafe fn try_get_component_mut_unchecked<T: Component>(
+        &self,
+        entity: Entity,
+    ) -> Result<Option<RefMut<'_, T>>, BorrowConflict> {
+        match self.find_components::<T>(entity) {
+            Some((components, index)) => components.try_data_element_mut::<T>(index),
+            None => Ok(None),
+        }
+    }
+
+    /// Finds the component slice holding the `T` component of `entity`, and the entity's
+    /// index within it.
+    fn find_components<T: Component>(
+        &self,
+        entity: Entity,
+    ) -> Option<(&ComponentResourceSet, usize)> {
+        if !self.is_alive(entity) {
             return None;
         }
 
         let location = self.entity_allocator.get_location(entity.index())?;
-        let archetype = self.storage().archetypes().get(location.archetype())?;
+        let archetype = self.storage().archetype(location.archetype())?;
         let chunk = archetype
-            .chunksets()
-            .get(location.set())?
-            .get(location.chunk())?;
-        let (slice_borrow, slice) = chunk
-            .components(ComponentTypeId::of::<T>())?
-            .data_slice_mut::<T>()
-            .deconstruct();
-        let component = slice.get_mut(location.component())?;
+            .chunkset(location.set())?
+            .chunk(location.chunk())?;
+        let components = chunk.components(ComponentTypeId::of::<T>())?;
+        Some((components, location.component().as_usize()))
+    }
 
-        Some(RefMut::new(slice_borrow, component))
+    /// Applies the world's `BorrowConflictPolicy` if `entity`'s `T` components cannot be
+    /// borrowed. Returns the conflict if the policy skips it; otherwise panics with a
+    /// description of `entity`, so that borrow conflicts name the entity involved when debug
+    /// labels are enabled.
+    fn check_borrow<T: Component>(
+        &self,
+        entity: Entity,
+        components: &ComponentResourceSet,
+        mutable: bool,
+    ) -> Result<(), BorrowConflict> {
+        let skips = self.borrow_conflict_policy().skips();
+        if !skips && !DebugLabels::ENABLED {
+            return Ok(());
+        }
+        let reason = match components.borrow_conflict(mutable) {
+            Some(reason) => reason,
+            None => return Ok(()),
+        };
+
+        let component = std::any::type_name::<T>();
+        if !skips {
+            panic!(
+                "cannot borrow {} of entity {}: {}",
+                component,
+                self.entity_debug(entity),
+                reason
+            );
+        }
+
+        let conflict = BorrowConflict {
+            component,
+            mutable,
+            reason,
+        };
+        warn!(
+            entity = %self.entity_debug(entity),
+            %conflict,
+            "Skipping access to a component which is already borrowed"
+        );
+        Err(conflict)
     }
 
     /// Mutably borrows entity data for the given entity.
@@ -586,10 +1331,40 @@ impl World {
         unsafe { self.get_component_mut_unchecked(entity) }
     }
 
-    /// Mutably borrows entity data for the given entity.
+    /// Mutably borrows component data for the given entity, or returns an error if the
+    /// component is already borrowed. See `try_get_component_mut_unchecked`.
+    pub fn try_get_component_mut<T: Component>(
+        &mut self,
+        entity: Entity,
+    ) -> Result<Option<RefMut<'_, T>>, BorrowConflict> {
+        // safe because the &mut self ensures exclusivity
+        unsafe { self.try_get_component_mut_unchecked(entity) }
+    }
+
+    /// Gets the policy applied when component data cannot be borrowed because it is already
+    /// borrowed elsewhere.
+    pub fn borrow_conflict_policy(&self) -> BorrowConflictPolicy {
+        self.storage().borrow_conflict_policy()
+    }
+
+    /// Sets the policy applied when component data cannot be borrowed because it is already
+    /// borrowed elsewhere, such as by a query which is still being iterated.
     ///
-    /// Returns `Some(data)` if the entity was found and contains the specified data.
-    /// Otherwise `None` is returned.
+    /// Under a skipping policy, `get_component` and friends return `None` for conflicting
+    /// components, and queries skip the chunks whose components they cannot borrow. Skipped
+    /// chunks are not recorded by stateful filters such as `changed`, so they are visited as
+    /// normal once the conflicting borrow has been released. See `BorrowConflictPolicy`.
+    pub fn set_borrow_conflict_policy(&mut self, policy: BorrowConflictPolicy) {
+        self.storage_mut().set_borrow_conflict_policy(policy);
+    }
+
+    /// Gets a view of the world which only contains the entities tagged with `value`.
+    ///
+    /// Queries executed through the partition only match the partition's entities, and
+    /// entities inserted through it are tagged with `value`. See `WorldPartition`.
+    pub fn partition<T: Tag>(&mut self, value: T) -> WorldPartition<'_, T> {
+        WorldPartition::new(self, value)
+    }
 
     /// Gets tag data for the given entity.
     ///
@@ -601,15 +1376,365 @@ impl World {
         }
 
         let location = self.entity_allocator.get_location(entity.index())?;
-        let archetype = self.storage().archetypes().get(location.archetype())?;
+        let archetype = self.storage().archetype(location.archetype())?;
         let tags = archetype.tags().get(TagTypeId::of::<T>())?;
 
-        unsafe { tags.data_slice::<T>().get(location.set()) }
+        unsafe { tags.data_slice::<T>().get(location.set().as_usize()) }
+    }
+
+    /// Sets the fingerprint of the internal layout of component type `T` (e.g. a hash of its
+    /// field offsets), which `try_merge` compares along with the type's size and alignment.
+    ///
+    /// Components inserted into the world otherwise have a fingerprint of `0`. The fingerprint
+    /// applies to all current archetypes, and to future archetypes whose descriptions do not
+    /// set their own with `ComponentMeta::with_fingerprint`. See also
+    /// `PodRegistry::register_with_fingerprint`.
+    pub fn set_component_fingerprint<T: Component>(&mut self, fingerprint: u64) {
+        self.storage_mut()
+            .set_component_fingerprint(ComponentTypeId::of::<T>(), fingerprint);
+    }
+
+    /// Enables per-entity write tracking for component type `T`.
+    ///
+    /// Once enabled, each `T` that is mutably borrowed or newly added to an entity is flagged
+    /// as written until it is collected by `drain_dirty`. Mutable borrows of whole chunk slices,
+    /// such as by queries containing `Write<T>`, flag every entity in the chunk.
+    pub fn track_writes<T: Component>(&mut self) {
+        self.storage_mut().track_writes(ComponentTypeId::of::<T>());
+    }
+
+    /// Enables per-chunk summaries of component type `T`, computed by `S`.
+    ///
+    /// Summaries are recomputed lazily, when requested after a chunk's `T` components have
+    /// been modified, and are used by filters such as `component_range` to skip chunks.
+    /// Enabling a summary for `T` replaces any summary previously enabled for it.
+    pub fn enable_summary<T: Component, S: ComponentSummary<T>>(&mut self) {
+        self.storage_mut().enable_summary::<T, S>();
+    }
+
+    /// Yields every entity whose `T` component has been written since the last call, and
+    /// clears their written flags.
+    ///
+    /// Draining is destructive, so each tracked component type supports a single consumer.
+    /// Yields nothing if `track_writes::<T>` has not been called.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if any other code is currently borrowing `T` mutably.
+    pub fn drain_dirty<T: Component>(&self) -> impl Iterator<Item = Entity> {
+        let type_id = ComponentTypeId::of::<T>();
+        let mut dirty = Vec::new();
+        for archetype in self.storage().archetypes() {
+            for chunkset in archetype.chunksets() {
+                for chunk in chunkset.occupied() {
+                    if let Some(components) = chunk.components(type_id) {
+                        // hold a shared borrow so that no writes can be in progress
+                        let _borrow = components.data_raw();
+                        let entities = chunk.entities();
+                        components.drain_dirty(|i| dirty.push(entities[i]));
+                    }
+                }
+            }
+        }
+
+        dirty.into_iter()
     }
 
     /// Determines if the given `Entity` is alive within this `World`.
     pub fn is_alive(&self, entity: Entity) -> bool { self.entity_allocator.is_alive(entity) }
 
+    /// Attaches a human-readable label to an entity, which is included wherever legion describes
+    /// the entity, such as in `entity_debug` and in borrow conflict panics.
+    ///
+    /// Labels follow their entity into clones and merged worlds, and are discarded when the
+    /// entity is deleted. They are only recorded in debug builds, or when the `debug-labels`
+    /// feature is enabled; otherwise this does nothing. Dead entities are not labelled.
+    pub fn set_debug_label<L: Into<Cow<'static, str>>>(&mut self, entity: Entity, label: L) {
+        if DebugLabels::ENABLED && self.is_alive(entity) {
+            self.debug_labels.set(entity, label.into());
+        }
+    }
+
+    /// Gets the debug label of an entity. See `set_debug_label`.
+    pub fn debug_label(&self, entity: Entity) -> Option<&str> { self.debug_labels.get(entity) }
+
+    /// Gets the debug labels of all entities in the world.
+    pub fn debug_labels(&self) -> &DebugLabels { &self.debug_labels }
+
+    /// Formats an entity along with its debug label, e.g. `3#0 "player"`.
+    pub fn entity_debug(&self, entity: Entity) -> EntityDebug<'_> {
+        EntityDebug::new(entity, &self.debug_labels)
+    }
+
+    /// Starts recording the structural operations applied to each entity, such as components
+    /// being added or removed, retaining the last `capacity` operations per entity. A
+    /// `capacity` of `0` stops recording and discards all recorded history.
+    ///
+    /// Histories are retained after their entity is deleted, until the entity's index is reused
+    /// by a new entity or recording is stopped. The total retained is therefore bounded by the
+    /// number of entity indices in use, rather than growing with every entity ever deleted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Frozen;
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.set_entity_history(16);
+    /// let entity = world.insert((), vec![(Position(0.0), Frozen)])[0];
+    ///
+    /// world.push_op_label("thaw system");
+    /// world.remove_component::<Frozen>(entity);
+    /// world.pop_op_label();
+    ///
+    /// let last = world.entity_history(entity).pop().unwrap();
+    /// assert_eq!(Some("thaw system"), last.label.as_deref());
+    /// ```
+    pub fn set_entity_history(&mut self, capacity: usize) { self.history.set_capacity(capacity); }
+
+    /// Determines if structural operations are being recorded. See `set_entity_history`.
+    pub fn records_entity_history(&self) -> bool { self.history.is_enabled() }
+
+    /// Gets the recorded structural operations applied to an entity, oldest first. See
+    /// `set_entity_history`.
+    pub fn entity_history(&self, entity: Entity) -> Vec<EntityHistoryEntry> {
+        self.history.get(entity)
+    }
+
+    /// Pushes a label which is attached to the structural operations recorded in entity
+    /// histories until it is popped with `pop_op_label`. Schedules push the name of each system
+    /// while applying its command buffer.
+    pub fn push_op_label<L: Into<Cow<'static, str>>>(&mut self, label: L) {
+        let label: Cow<'static, str> = label.into();
+        self.history.push_label(label.as_ref().into());
+    }
+
+    /// Pops the label most recently pushed with `push_op_label`.
+    pub fn pop_op_label(&mut self) { self.history.pop_label(); }
+
+    /// Gets where the given entity's components are stored, if it is alive.
+    ///
+    /// The location remains valid for as long as `location_epoch` is unchanged.
+    pub fn entity_location(&self, entity: Entity) -> Option<EntityLocation> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        self.entity_allocator.get_location(entity.index())
+    }
+
+    /// Gets a counter which is incremented whenever entities may have moved within the
+    /// world's storage, invalidating the locations returned by `entity_location`.
+    ///
+    /// The counter is incremented by:
+    ///
+    /// * deleting an entity, which moves the last entity in its chunk into its place
+    /// * adding or removing a component or tag, which moves the entity to another chunk and
+    ///   the last entity in its previous chunk into its place
+    /// * `defrag`, if it moves any entities
+    /// * `merge`
+    ///
+    /// Inserting or cloning entities only appends to chunks, and so does not move existing
+    /// entities. Neither does writing component data or adding a component which the entity
+    /// already has.
+    pub fn location_epoch(&self) -> u64 { self.location_epoch }
+
+    /// Gets a counter which is incremented whenever an archetype is created in the world.
+    ///
+    /// Archetypes are never removed from a world, so queries only need to check which of the
+    /// world's archetypes they match when this has changed.
+    pub fn archetype_generation(&self) -> u64 { self.storage().archetypes().len() as u64 }
+
+    /// Borrows entity data for the given entity, using a location previously returned by
+    /// `entity_location` if `epoch` is still the current `location_epoch`.
+    ///
+    /// Falls back to looking up the entity's location if the cached location is stale.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if the component was mutably borrowed elsewhere.
+    pub fn get_component_at<T: Component>(
+        &self,
+        entity: Entity,
+        location: EntityLocation,
+        epoch: u64,
+    ) -> Option<Ref<'_, T>> {
+        if epoch != self.location_epoch {
+            return self.get_component(entity);
+        }
+
+        let chunk = self
+            .storage()
+            .archetype(location.archetype())?
+            .chunkset(location.set())?
+            .chunk(location.chunk())?;
+        let index = location.component().as_usize();
+        if chunk.entities().get(index) != Some(&entity) {
+            return self.get_component(entity);
+        }
+
+        let (slice_borrow, slice) = unsafe {
+            chunk
+                .components(ComponentTypeId::of::<T>())?
+                .data_slice::<T>()
+                .deconstruct()
+        };
+        Some(Ref::new(slice_borrow, &slice[index]))
+    }
+
+    /// Gets the world tick at which the given entity was spawned.
+    ///
+    /// Returns `None` if the entity is not alive, or if the world was not created with
+    /// `WorldOptions::record_spawn_tick`. An entity keeps its spawn tick when components or
+    /// tags are added to or removed from it.
+    pub fn spawn_tick(&self, entity: Entity) -> Option<u64> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let location = self.entity_allocator.get_location(entity.index())?;
+        let chunk = self
+            .storage()
+            .archetype(location.archetype())?
+            .chunkset(location.set())?
+            .chunk(location.chunk())?;
+        chunk
+            .spawn_ticks()?
+            .get(location.component().as_usize())
+            .copied()
+    }
+
+    /// Gets statistics about the entity slots allocated by this `World`.
+    pub fn allocator_stats(&self) -> EntityAllocatorStats { self.entity_allocator.stats() }
+
+    /// Gets the number of entities and allocated chunks in each archetype, along with their
+    /// high-water marks since the world was created or since `reset_high_water_marks` was
+    /// last called.
+    ///
+    /// The counts are maintained as entities are inserted and removed, so this is cheap
+    /// enough to call every frame.
+    pub fn memory_stats(&self) -> MemoryStats {
+        let archetypes = self
+            .storage()
+            .archetypes()
+            .iter()
+            .map(|arch| arch.stats())
+            .collect::<Vec<_>>();
+        MemoryStats {
+            entities: archetypes.iter().map(|stats| stats.entities).sum(),
+            chunks: archetypes.iter().map(|stats| stats.chunks).sum(),
+            allocated_bytes: archetypes.iter().map(|stats| stats.allocated_bytes).sum(),
+            archetypes,
+        }
+    }
+
+    /// Resets the high-water marks of every archetype to its current usage.
+    ///
+    /// Archetypes which are still above their entity threshold emit
+    /// `Event::EntityThresholdExceeded` again on their next insertion.
+    pub fn reset_high_water_marks(&mut self) {
+        for archetype in self.storage().archetypes() {
+            archetype.reset_high_water_marks();
+        }
+    }
+
+    /// Pins the chunks of an archetype to a placement group, or returns them to being assigned
+    /// to each group in turn if `placement` is `None`.
+    ///
+    /// Chunks whose memory is allocated keep their placement, while new and empty chunks are
+    /// moved to the pinned group. Returns `false` if the archetype was not found.
+    /// See `WorldOptions::placement_groups`.
+    pub fn set_archetype_placement(
+        &mut self,
+        archetype: ArchetypeId,
+        placement: Option<u8>,
+    ) -> bool {
+        match self
+            .storage_mut()
+            .archetypes_mut()
+            .iter_mut()
+            .find(|data| data.id() == archetype)
+        {
+            Some(data) => {
+                data.set_placement(placement);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the allocator used for the memory of chunks, which is given the placement hint of
+    /// each chunk it allocates, e.g. to allocate from the matching NUMA node.
+    ///
+    /// Chunks which are already allocated are freed by the allocator which allocated them.
+    #[cfg(feature = "numa")]
+    pub fn set_chunk_allocator(&mut self, allocator: Arc<dyn ChunkAllocator>) {
+        self.storage_mut().set_chunk_allocator(allocator);
+    }
+
+    /// Deletes all entities in the world and returns any retired entity slots to use.
+    ///
+    /// Slots are retired when their version counter is exhausted, so that a stale `Entity`
+    /// handle can never alias a new entity. Calling this function declares that no handles
+    /// to entities from this world remain in use; any handle obtained before the call may
+    /// be considered alive again if it is used afterwards.
+    ///
+    /// Returns the number of slots which were returned to use.
+    pub fn invalidate_all_handles(&mut self) -> usize {
+        self.delete_all(&crate::filter::filter_fns::any());
+        self.entity_allocator.unretire()
+    }
+
+    /// Gets the IDs of all entities which match the given filter.
+    pub fn entities_matching(&self, filter: &dyn DynFilter) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        filter.for_each_chunk(self.storage(), &mut |chunk| {
+            entities.extend_from_slice(chunk.entities())
+        });
+        entities
+    }
+
+    /// Deletes all entities which match the given filter.
+    ///
+    /// Returns the number of entities deleted.
+    pub fn delete_all(&mut self, filter: &dyn DynFilter) -> usize {
+        let entities = self.entities_matching(filter);
+        for entity in entities.iter() {
+            self.delete(*entity);
+        }
+        entities.len()
+    }
+
+    /// Releases all component borrows which are still recorded as held.
+    ///
+    /// Borrows taken by queries and component accessors are released when their guards are
+    /// dropped, including while unwinding from a panic, so this is only needed if a guard has
+    /// been leaked (e.g. via `mem::forget`), after which every access to the affected components
+    /// would otherwise panic. As this function requires exclusive access to the world, no valid
+    /// borrows can exist while it runs.
+    ///
+    /// Returns the number of component slices which were recorded as borrowed.
+    pub fn clear_poisoned_borrows(&mut self) -> usize {
+        let mut cleared = 0;
+        for archetype in self.storage_mut().archetypes_mut() {
+            for set in archetype.chunksets_mut() {
+                for chunk in set.iter_mut() {
+                    cleared += chunk.clear_poisoned_borrows();
+                }
+            }
+        }
+
+        if cleared > 0 {
+            warn!(count = cleared, "Cleared poisoned component borrows");
+        }
+
+        cleared
+    }
+
     /// Iteratively defragments the world's internal memory.
     ///
     /// This compacts entities into fewer more continuous chunks.
@@ -629,13 +1754,18 @@ impl World {
         let archetypes = unsafe { &mut *self.storage.get() }.archetypes_mut();
         let mut budget = budget.unwrap_or(std::usize::MAX);
         let start = self.defrag_progress;
+        let mut moved = false;
         while self.defrag_progress < archetypes.len() {
             // defragment the next archetype
             let complete =
                 (&mut archetypes[self.defrag_progress]).defrag(&mut budget, |e, location| {
                     self.entity_allocator.set_location(e.index(), location);
+                    moved = true;
                 });
             if complete {
+                // release chunks which were emptied by the defrag
+                archetypes[self.defrag_progress].shrink_to_fit();
+
                 // increment the index, looping it once we get to the end
                 self.defrag_progress = (self.defrag_progress + 1) % archetypes.len();
             }
@@ -645,14 +1775,120 @@ impl World {
                 break;
             }
         }
+
+        if moved {
+            self.location_epoch += 1;
+        }
     }
 
+    /// Moves all entities from another world into this world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two worlds disagree on the layout of a component type. See `try_merge`.
     pub fn merge(&mut self, world: World) {
+        if let Err((_, err)) = self.try_merge(world) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Moves all entities from another world into this world.
+    ///
+    /// Component data is adopted without being copied per-entity, so both worlds must agree on
+    /// the size, alignment and fingerprint of each component type they share. If any differ,
+    /// neither world is modified and the source world is returned alongside the error.
+    // the source world is taken by value, so handing it back is no larger than accepting it
+    #[allow(clippy::result_large_err)]
+    pub fn try_merge(&mut self, world: World) -> Result<(), (World, LayoutMismatch)> {
+        let mut layouts = FxHashMap::default();
+        for archetype in self.storage().archetypes() {
+            for (type_id, meta) in archetype.description().components() {
+                layouts.insert(*type_id, meta.layout_fingerprint());
+            }
+        }
+
+        for archetype in world.storage().archetypes() {
+            let desc = archetype.description();
+            for ((type_id, meta), name) in desc.components().iter().zip(desc.component_names()) {
+                match layouts.get(type_id) {
+                    Some(expected) if *expected != meta.layout_fingerprint() => {
+                        let err = LayoutMismatch {
+                            type_name: name,
+                            expected: *expected,
+                            found: meta.layout_fingerprint(),
+                        };
+                        return Err((world, err));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.merge_unchecked(world);
+        Ok(())
+    }
+
+    /// Writes the components of every entity in the world to a buffer in the binary chunk
+    /// format described in the `pod` module, one record per chunk.
+    ///
+    /// Fails if the world contains a component type which is not in `registry`, or any
+    /// entities with tags.
+    pub fn write_chunks_to_bytes(
+        &self,
+        registry: &PodRegistry,
+    ) -> Result<Vec<u8>, ChunkFormatError> {
+        pod::write_chunks(self.storage(), registry)
+    }
+
+    /// Inserts new entities from a buffer in the binary chunk format described in the `pod`
+    /// module, such as one written by `write_chunks_to_bytes`.
+    ///
+    /// The whole buffer is validated against `registry` before any entities are inserted, after
+    /// which each component array is copied into newly allocated chunks as a whole. Returns the
+    /// new entities, in the order in which they are stored in the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion::prelude::*;
+    /// # use legion::pod::{ChunkFormatError, PodRegistry};
+    /// let mut registry = PodRegistry::new();
+    /// registry.register::<u32>("u32");
+    ///
+    /// let mut world = Universe::new().create_world();
+    /// let result = world.load_chunks_from_bytes(b"not a chunk buffer", &registry);
+    /// assert_eq!(Err(ChunkFormatError::BadMagic), result);
+    /// ```
+    pub fn load_chunks_from_bytes(
+        &mut self,
+        bytes: &[u8],
+        registry: &PodRegistry,
+    ) -> Result<Vec<Entity>, ChunkFormatError> {
+        let chunks = pod::read_chunks(bytes, registry)?;
+        let mut entities = Vec::with_capacity(chunks.iter().map(|chunk| chunk.len()).sum());
+        for chunk in chunks {
+            let inserted = self.try_insert((), chunk).map_err(|err| match err {
+                InsertError::DuplicateComponent(name) => {
+                    ChunkFormatError::DuplicateComponent(name.to_owned())
+                }
+                InsertError::DuplicateTag(name) => ChunkFormatError::UnsupportedTag(name),
+            })?;
+            entities.extend_from_slice(inserted);
+        }
+
+        Ok(entities)
+    }
+
+    fn merge_unchecked(&mut self, mut world: World) {
         let span =
             span!(Level::INFO, "Merging worlds", source = world.id().0, destination = ?self.id());
         let _guard = span.enter();
 
         self.entity_allocator.merge(world.entity_allocator);
+        self.debug_labels
+            .merge(std::mem::take(&mut world.debug_labels));
+        self.history.merge(std::mem::take(&mut world.history));
+        self.location_epoch += 1;
 
         for archetype in unsafe { &mut *world.storage.get() }.drain(..) {
             let target_archetype = {
@@ -670,23 +1906,25 @@ impl World {
                         .get_mut(arch_index)
                         .unwrap()
                         .merge(archetype);
-                    arch_index
+                    ArchetypeIndex::new(arch_index)
                 } else {
                     // archetype does not already exist, append
                     self.storage_mut().push(archetype);
-                    self.storage_mut().archetypes().len() - 1
+                    ArchetypeIndex::new(self.storage_mut().archetypes().len() - 1)
                 }
             };
 
             // update entity locations
-            let archetype = &unsafe { &*self.storage.get() }.archetypes()[target_archetype];
+            let archetype = unsafe { &*self.storage.get() }
+                .archetype(target_archetype)
+                .unwrap();
             for (entity, location) in archetype.enumerate_entities(target_archetype) {
                 self.entity_allocator.set_location(entity.index(), location);
             }
         }
     }
 
-    fn find_archetype<T, C>(&self, tags: &mut T, components: &mut C) -> Option<usize>
+    fn find_archetype<T, C>(&self, tags: &mut T, components: &mut C) -> Option<ArchetypeIndex>
     where
         T: for<'a> Filter<ArchetypeFilterData<'a>>,
         C: for<'a> Filter<ArchetypeFilterData<'a>>,
@@ -703,11 +1941,11 @@ impl World {
             .enumerate()
             .take(self.storage().archetypes().len())
             .filter(|(_, (a, b))| *a && *b)
-            .map(|(i, _)| i)
+            .map(|(i, _)| ArchetypeIndex::new(i))
             .next()
     }
 
-    fn create_archetype<T, C>(&mut self, tags: &T, components: &C) -> usize
+    fn create_archetype<T, C>(&mut self, tags: &T, components: &C) -> ArchetypeIndex
     where
         T: TagLayout,
         C: ComponentLayout,
@@ -720,7 +1958,7 @@ impl World {
         index
     }
 
-    fn find_or_create_archetype<T, C>(&mut self, tags: &mut T, components: &mut C) -> usize
+    fn find_or_create_archetype<T, C>(&mut self, tags: &mut T, components: &mut C) -> ArchetypeIndex
     where
         T: TagLayout,
         C: ComponentLayout,
@@ -732,12 +1970,16 @@ impl World {
         }
     }
 
-    fn find_chunk_set<T>(&self, archetype: usize, tags: &mut T) -> Option<usize>
+    fn find_chunk_set<T>(&self, archetype: ArchetypeIndex, tags: &mut T) -> Option<SetIndex>
     where
         T: for<'a> Filter<ChunksetFilterData<'a>>,
     {
         // fetch the archetype, we can already assume that the archetype index is valid
-        let archetype_data = unsafe { self.storage().archetypes().get_unchecked(archetype) };
+        let archetype_data = unsafe {
+            self.storage()
+                .archetypes()
+                .get_unchecked(archetype.as_usize())
+        };
 
         // find a chunk with the correct tags
         let chunk_filter_data = ChunksetFilterData {
@@ -745,25 +1987,25 @@ impl World {
         };
 
         if let Some(i) = tags.matches(chunk_filter_data).matching_indices().next() {
-            return Some(i);
+            return Some(SetIndex::new(i));
         }
 
         None
     }
 
-    fn create_chunk_set<T>(&mut self, archetype: usize, tags: &T) -> usize
+    fn create_chunk_set<T>(&mut self, archetype: ArchetypeIndex, tags: &T) -> SetIndex
     where
         T: TagSet,
     {
         let archetype_data = unsafe {
             self.storage_mut()
                 .archetypes_mut()
-                .get_unchecked_mut(archetype)
+                .get_unchecked_mut(archetype.as_usize())
         };
         archetype_data.alloc_chunk_set(|chunk_tags| tags.write_tags(chunk_tags))
     }
 
-    fn find_or_create_chunk<T>(&mut self, archetype: usize, tags: &mut T) -> usize
+    fn find_or_create_chunk<T>(&mut self, archetype: ArchetypeIndex, tags: &mut T) -> SetIndex
     where
         T: TagSet + for<'a> Filter<ChunksetFilterData<'a>>,
     {
@@ -779,6 +2021,38 @@ impl Default for World {
     fn default() -> Self { Self::new() }
 }
 
+/// An error returned by `World::try_insert`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum InsertError {
+    /// The named tag type was given more than once.
+    DuplicateTag(&'static str),
+    /// The named component type was given more than once.
+    DuplicateComponent(&'static str),
+}
+
+impl std::fmt::Display for InsertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InsertError::DuplicateTag(name) => write!(f, "duplicate tag type {}", name),
+            InsertError::DuplicateComponent(name) => {
+                write!(f, "duplicate component type {}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+/// Finds the name of the first type which appears more than once in `types`.
+fn find_duplicate<T: PartialEq>(types: &[(T, &'static str)]) -> Option<&'static str> {
+    types
+        .iter()
+        .enumerate()
+        .find(|(i, (id, _))| types[..*i].iter().any(|(other, _)| other == id))
+        .map(|(_, (_, name))| *name)
+}
+
 /// Describes the types of a set of components attached to an entity.
 pub trait ComponentLayout: Sized {
     /// A filter type which filters archetypes to an exact match with this layout.
@@ -789,6 +2063,19 @@ pub trait ComponentLayout: Sized {
 
     /// Modifies an archetype description to include the components described by this layout.
     fn tailor_archetype(&self, archetype: &mut ArchetypeDescription);
+
+    /// Gets the name of a component type which appears more than once in this layout, if any.
+    fn duplicate_type(&self) -> Option<&'static str> {
+        let mut description = ArchetypeDescription::default();
+        self.tailor_archetype(&mut description);
+        let types = description
+            .components()
+            .iter()
+            .map(|(id, _)| *id)
+            .zip(description.component_names().iter().copied())
+            .collect::<Vec<_>>();
+        find_duplicate(&types)
+    }
 }
 
 /// Describes the types of a set of tags attached to an entity.
@@ -801,6 +2088,19 @@ pub trait TagLayout: Sized {
 
     /// Modifies an archetype description to include the tags described by this layout.
     fn tailor_archetype(&self, archetype: &mut ArchetypeDescription);
+
+    /// Gets the name of a tag type which appears more than once in this layout, if any.
+    fn duplicate_type(&self) -> Option<&'static str> {
+        let mut description = ArchetypeDescription::default();
+        self.tailor_archetype(&mut description);
+        let types = description
+            .tags()
+            .iter()
+            .map(|(id, _)| *id)
+            .zip(description.tag_names().iter().copied())
+            .collect::<Vec<_>>();
+        find_duplicate(&types)
+    }
 }
 
 /// A set of tag values to be attached to an entity.
@@ -872,6 +2172,48 @@ pub struct ComponentTupleFilter<T> {
     _phantom: PhantomData<T>,
 }
 
+/// A `ComponentSource` which creates a known number of entities by calling a function with the
+/// index of each entity.
+///
+/// Each component returned by the function is written directly into its slot in the destination
+/// chunk.
+pub struct ComponentFnSource<T, F>
+where
+    F: FnMut(usize) -> T,
+{
+    f: F,
+    index: usize,
+    count: usize,
+    filter: ComponentTupleFilter<T>,
+}
+
+impl<T, F> ComponentFnSource<T, F>
+where
+    F: FnMut(usize) -> T,
+{
+    /// Creates a new component source which will create `count` entities.
+    pub fn new(count: usize, f: F) -> Self {
+        Self {
+            f,
+            index: 0,
+            count,
+            filter: ComponentTupleFilter {
+                _phantom: PhantomData,
+            },
+        }
+    }
+}
+
+impl<T, F> IntoComponentSource for ComponentFnSource<T, F>
+where
+    F: FnMut(usize) -> T,
+    ComponentFnSource<T, F>: ComponentSource,
+{
+    type Source = Self;
+
+    fn into(self) -> Self::Source { self }
+}
+
 mod tuple_impls {
     use super::*;
     use crate::iterator::SliceVecIter;
@@ -887,6 +2229,79 @@ mod tuple_impls {
         ( $( $ty: ident => $id: ident ),* ) => {
             impl_data_tuple!(@TAG_SET $( $ty => $id ),*);
             impl_data_tuple!(@COMPONENT_SOURCE $( $ty => $id ),*);
+            impl_data_tuple!(@FN_SOURCE $( $ty => $id ),*);
+        };
+        ( @FN_SOURCE $( $ty: ident => $id: ident ),* ) => {
+            impl<UWU, $( $ty ),*> ComponentLayout for ComponentFnSource<($( $ty, )*), UWU>
+            where
+                UWU: FnMut(usize) -> ($( $ty, )*),
+                $( $ty: Component ),*
+            {
+                type Filter = ComponentTupleFilter<($( $ty, )*)>;
+
+                fn get_filter(&mut self) -> &mut Self::Filter {
+                    &mut self.filter
+                }
+
+                fn tailor_archetype(&self, archetype: &mut ArchetypeDescription) {
+                    #![allow(unused_variables)]
+                    $(
+                        archetype.register_component::<$ty>();
+                    )*
+                }
+
+                fn duplicate_type(&self) -> Option<&'static str> {
+                    find_duplicate::<ComponentTypeId>(&[
+                        $( (ComponentTypeId::of::<$ty>(), std::any::type_name::<$ty>()) ),*
+                    ])
+                }
+            }
+
+            impl<UWU, $( $ty ),*> ComponentSource for ComponentFnSource<($( $ty, )*), UWU>
+            where
+                UWU: FnMut(usize) -> ($( $ty, )*),
+                $( $ty: Component ),*
+            {
+                fn is_empty(&mut self) -> bool {
+                    self.index == self.count
+                }
+
+                fn write(&mut self, allocator: &mut EntityAllocator, chunk: &mut ComponentStorage) -> usize {
+                    #![allow(unused_variables)]
+                    #![allow(unused_unsafe)]
+                    #![allow(non_snake_case)]
+                    let count = std::cmp::min(chunk.capacity() - chunk.len(), self.count - self.index);
+                    let mut writer = chunk.writer();
+                    let (entities, components) = writer.get();
+
+                    unsafe {
+                        $(
+                            let mut $ty = (&*components.get()).get(ComponentTypeId::of::<$ty>()).unwrap().writer_unchecked();
+                        )*
+
+                        // write each component straight into the unused capacity of its vec;
+                        // nothing is committed until all components have been written, so a
+                        // panic in `f` only leaks the values written so far
+                        for i in 0..count {
+                            let ($( $id, )*) = (self.f)(self.index + i);
+                            $(
+                                std::ptr::write(($ty.spare_ptr() as *mut $ty).add(i), $id);
+                            )*
+                        }
+
+                        $(
+                            $ty.commit_raw(count);
+                        )*
+                    }
+
+                    for _ in 0..count {
+                        entities.push(allocator.create_entity());
+                    }
+
+                    self.index += count;
+                    count
+                }
+            }
         };
         ( @COMPONENT_SOURCE $( $ty: ident => $id: ident ),* ) => {
             impl<UWU, $( $ty ),*> ComponentLayout for ComponentTupleSet<($( $ty, )*), UWU>
@@ -906,6 +2321,12 @@ mod tuple_impls {
                         archetype.register_component::<$ty>();
                     )*
                 }
+
+                fn duplicate_type(&self) -> Option<&'static str> {
+                    find_duplicate::<ComponentTypeId>(&[
+                        $( (ComponentTypeId::of::<$ty>(), std::any::type_name::<$ty>()) ),*
+                    ])
+                }
             }
 
             impl<UWU, $( $ty ),*> ComponentSource for ComponentTupleSet<($( $ty, )*), UWU>
@@ -928,7 +2349,7 @@ mod tuple_impls {
 
                     unsafe {
                         $(
-                            let mut $ty = (&mut *components.get()).get_mut(ComponentTypeId::of::<$ty>()).unwrap().writer();
+                            let mut $ty = (&*components.get()).get(ComponentTypeId::of::<$ty>()).unwrap().writer_unchecked();
                         )*
 
                         while let Some(($( $id, )*)) = { if count == space { None } else { self.iter.next() } } {
@@ -1002,6 +2423,12 @@ mod tuple_impls {
                         archetype.register_tag::<$ty>();
                     )*
                 }
+
+                fn duplicate_type(&self) -> Option<&'static str> {
+                    find_duplicate::<TagTypeId>(&[
+                        $( (TagTypeId::of::<$ty>(), std::any::type_name::<$ty>()) ),*
+                    ])
+                }
             }
 
             impl<'a, $( $ty ),*> Filter<ArchetypeFilterData<'a>> for ($( $ty, )*)
@@ -1145,117 +2572,461 @@ impl<'a, 'b> Filter<ArchetypeFilterData<'b>> for DynamicComponentLayout<'a> {
                 }),
         )
     }
-}
+}
+
+struct DynamicTagLayout<'a> {
+    storage: &'a Storage,
+    archetype: ArchetypeIndex,
+    set: SetIndex,
+    existing: &'a [(TagTypeId, TagMeta)],
+    add: &'a [(TagTypeId, TagMeta, NonNull<u8>)],
+    remove: &'a [TagTypeId],
+}
+
+unsafe impl<'a> Send for DynamicTagLayout<'a> {}
+
+unsafe impl<'a> Sync for DynamicTagLayout<'a> {}
+
+impl<'a> TagLayout for DynamicTagLayout<'a> {
+    type Filter = Self;
+
+    fn get_filter(&mut self) -> &mut Self::Filter { self }
+
+    fn tailor_archetype(&self, archetype: &mut ArchetypeDescription) {
+        // copy tags from existing archetype into new
+        // except for those in `remove`
+        let tags = self
+            .existing
+            .iter()
+            .filter(|(t, _)| !self.remove.contains(t));
+
+        for (tag_type, meta) in tags {
+            archetype.register_tag_raw(*tag_type, *meta);
+        }
+
+        // append tag from `add`
+        for (tag_type, meta, _) in self.add.iter() {
+            archetype.register_tag_raw(*tag_type, *meta);
+        }
+    }
+}
+
+impl<'a, 'b> Filter<ArchetypeFilterData<'b>> for DynamicTagLayout<'a> {
+    type Iter = SliceVecIter<'b, TagTypeId>;
+
+    fn collect(&self, source: ArchetypeFilterData<'b>) -> Self::Iter { source.tag_types.iter() }
+
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        Some(
+            item.len() == (self.existing.len() + self.add.len() - self.remove.len())
+                && item.iter().all(|t| {
+                    // all types are not in remove
+                    !self.remove.contains(t)
+                    // any are either in existing or add
+                        && (self.existing.iter().any(|(x, _)| x == t)
+                            || self.add.iter().any(|(x, _, _)| x == t))
+                }),
+        )
+    }
+}
+
+impl<'a, 'b> Filter<ChunksetFilterData<'b>> for DynamicTagLayout<'a> {
+    type Iter = Take<Enumerate<Repeat<&'b ArchetypeData>>>;
+
+    fn collect(&self, source: ChunksetFilterData<'b>) -> Self::Iter {
+        std::iter::repeat(source.archetype_data)
+            .enumerate()
+            .take(source.archetype_data.len())
+    }
+
+    fn is_match(&self, (chunk_index, arch): &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        for (type_id, meta) in self.existing {
+            if self.remove.contains(type_id) {
+                continue;
+            }
+
+            unsafe {
+                // find the value of the tag in the source chunk
+                let (slice_ptr, element_size, _) = self
+                    .storage
+                    .archetype(self.archetype)
+                    .unwrap()
+                    .tags()
+                    .get(*type_id)
+                    .unwrap()
+                    .data_raw();
+                let current = slice_ptr.as_ptr().add(self.set.as_usize() * element_size);
+
+                // find the value of the tag in the candidate chunk
+                let (slice_ptr, element_size, _) = arch.tags().get(*type_id).unwrap().data_raw();
+                let candidate = slice_ptr.as_ptr().add(chunk_index * element_size);
+
+                if !meta.equals(current, candidate) {
+                    return Some(false);
+                }
+            }
+        }
+
+        for (type_id, meta, ptr) in self.add {
+            unsafe {
+                let (slice_ptr, element_size, _) = arch.tags().get(*type_id).unwrap().data_raw();
+                let candidate = slice_ptr.as_ptr().add(chunk_index * element_size);
+
+                if !meta.equals(ptr.as_ptr(), candidate) {
+                    return Some(false);
+                }
+            }
+        }
+
+        Some(true)
+    }
+}
+
+/// Builds a single entity from a set of components and tags which is only known at runtime.
+///
+/// Components and tags are accumulated type-erased, so the layout of the entity may depend on
+/// runtime conditions (see `with_if`). `build` then performs a single insertion into the
+/// archetype matching the final layout.
+///
+/// Bulk spawning of many entities with a statically known layout should still prefer
+/// `World::insert`, which writes components without per-entity boxing.
+///
+/// ```rust
+/// # use legion::prelude::*;
+/// # use legion::world::EntityBuilder;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity(f32);
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Model(u32);
+/// # let mut world = World::new();
+/// # let moving = true;
+/// let entity = EntityBuilder::new()
+///     .with(Position(0.0))
+///     .with_if(moving, Velocity(1.0))
+///     .with_tag(Model(5))
+///     .build(&mut world);
+///
+/// assert_eq!(Some(Velocity(1.0)), world.get_component::<Velocity>(entity).map(|v| *v));
+/// ```
+#[derive(Default)]
+pub struct EntityBuilder {
+    tags: Vec<Box<dyn BuilderTag>>,
+    components: Vec<Box<dyn BuilderComponent>>,
+}
+
+impl EntityBuilder {
+    /// Creates a new, empty entity builder.
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds a component to the entity, replacing any existing component of the same type.
+    pub fn with<T: Component>(mut self, value: T) -> Self {
+        let type_id = ComponentTypeId::of::<T>();
+        self.components.retain(|c| c.component_type() != type_id);
+        self.components.push(Box::new(ComponentValue(value)));
+        self
+    }
+
+    /// Adds a component to the entity if `condition` is `true`.
+    pub fn with_if<T: Component>(self, condition: bool, value: T) -> Self {
+        if condition {
+            self.with(value)
+        } else {
+            self
+        }
+    }
+
+    /// Adds a tag to the entity, replacing any existing tag of the same type.
+    pub fn with_tag<T: Tag>(mut self, value: T) -> Self {
+        let type_id = TagTypeId::of::<T>();
+        self.tags.retain(|t| t.tag_type() != type_id);
+        self.tags.push(Box::new(TagValue(value)));
+        self
+    }
+
+    /// Inserts the entity into the world.
+    pub fn build(self, world: &mut World) -> Entity {
+        world.insert(
+            BuilderTagSet(self.tags),
+            BuilderComponentSource::new(vec![self.components]),
+        )[0]
+    }
+
+    /// Inserts `count` entities into the world, each created by the builder which `build`
+    /// returns for the entity's index.
+    ///
+    /// Consecutive entities which end up with the same layout and tag values are inserted
+    /// together.
+    ///
+    /// ```rust
+    /// # use legion::prelude::*;
+    /// # use legion::world::EntityBuilder;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Model(u32);
+    /// # let mut world = World::new();
+    /// let entities = EntityBuilder::build_batch(&mut world, 10, |i| {
+    ///     EntityBuilder::new()
+    ///         .with(Position(i as f32))
+    ///         .with_tag(Model(5))
+    /// });
+    /// assert_eq!(10, entities.len());
+    /// ```
+    pub fn build_batch<F>(world: &mut World, count: usize, mut build: F) -> Vec<Entity>
+    where
+        F: FnMut(usize) -> EntityBuilder,
+    {
+        let mut entities = Vec::with_capacity(count);
+        let mut tags: Option<Vec<Box<dyn BuilderTag>>> = None;
+        let mut batch: Vec<Vec<Box<dyn BuilderComponent>>> = Vec::new();
+
+        for i in 0..count {
+            let builder = build(i);
+
+            let same_layout = match (&tags, batch.first()) {
+                (Some(tags), Some(first)) => {
+                    same_tags(tags, &builder.tags) && same_components(first, &builder.components)
+                }
+                _ => true,
+            };
+
+            if !same_layout {
+                let batch = std::mem::take(&mut batch);
+                let tags = BuilderTagSet(tags.take().unwrap());
+                entities.extend_from_slice(world.insert(tags, BuilderComponentSource::new(batch)));
+            }
+
+            if tags.is_none() {
+                tags = Some(builder.tags);
+            }
+            batch.push(builder.components);
+        }
+
+        if let Some(tags) = tags {
+            let tags = BuilderTagSet(tags);
+            entities.extend_from_slice(world.insert(tags, BuilderComponentSource::new(batch)));
+        }
+
+        entities
+    }
+}
+
+fn same_tags(a: &[Box<dyn BuilderTag>], b: &[Box<dyn BuilderTag>]) -> bool {
+    a.len() == b.len() && a.iter().all(|x| b.iter().any(|y| x.equals(y.as_ref())))
+}
+
+fn same_components(a: &[Box<dyn BuilderComponent>], b: &[Box<dyn BuilderComponent>]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|x| b.iter().any(|y| x.component_type() == y.component_type()))
+}
+
+trait BuilderComponent: Send + Sync {
+    fn component_type(&self) -> ComponentTypeId;
+
+    fn register(&self, archetype: &mut ArchetypeDescription);
+
+    /// # Safety
+    ///
+    /// The component storage must contain this component type and have spare capacity.
+    unsafe fn write(self: Box<Self>, components: &mut Components);
+}
+
+struct ComponentValue<T>(T);
+
+impl<T: Component> BuilderComponent for ComponentValue<T> {
+    fn component_type(&self) -> ComponentTypeId { ComponentTypeId::of::<T>() }
+
+    fn register(&self, archetype: &mut ArchetypeDescription) {
+        archetype.register_component::<T>();
+    }
+
+    unsafe fn write(self: Box<Self>, components: &mut Components) {
+        let slice = [self.0];
+        components
+            .get_mut(ComponentTypeId::of::<T>())
+            .unwrap()
+            .writer()
+            .push(&slice);
+        std::mem::forget(slice);
+    }
+}
+
+trait BuilderTag: Send + Sync {
+    fn tag_type(&self) -> TagTypeId;
+
+    fn register(&self, archetype: &mut ArchetypeDescription);
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn equals(&self, other: &dyn BuilderTag) -> bool;
+
+    fn write(&self, tags: &mut Tags);
+
+    fn matches(&self, archetype: &ArchetypeData, set: usize) -> bool;
+}
+
+struct TagValue<T>(T);
+
+impl<T: Tag> BuilderTag for TagValue<T> {
+    fn tag_type(&self) -> TagTypeId { TagTypeId::of::<T>() }
+
+    fn register(&self, archetype: &mut ArchetypeDescription) { archetype.register_tag::<T>(); }
+
+    fn as_any(&self) -> &dyn Any { &self.0 }
+
+    fn equals(&self, other: &dyn BuilderTag) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(&self.0)
+    }
 
-struct DynamicTagLayout<'a> {
-    storage: &'a Storage,
-    archetype: usize,
-    chunk: usize,
-    existing: &'a [(TagTypeId, TagMeta)],
-    add: &'a [(TagTypeId, TagMeta, NonNull<u8>)],
-    remove: &'a [TagTypeId],
+    fn write(&self, tags: &mut Tags) {
+        unsafe {
+            tags.get_mut(TagTypeId::of::<T>())
+                .unwrap()
+                .push(self.0.clone())
+        };
+    }
+
+    fn matches(&self, archetype: &ArchetypeData, set: usize) -> bool {
+        unsafe {
+            archetype
+                .tags()
+                .get(TagTypeId::of::<T>())
+                .unwrap()
+                .data_slice::<T>()[set]
+                == self.0
+        }
+    }
 }
 
-unsafe impl<'a> Send for DynamicTagLayout<'a> {}
+struct BuilderTagSet(Vec<Box<dyn BuilderTag>>);
 
-unsafe impl<'a> Sync for DynamicTagLayout<'a> {}
+impl TagSet for BuilderTagSet {
+    fn write_tags(&self, tags: &mut Tags) {
+        for tag in &self.0 {
+            tag.write(tags);
+        }
+    }
+}
 
-impl<'a> TagLayout for DynamicTagLayout<'a> {
+impl TagLayout for BuilderTagSet {
     type Filter = Self;
 
     fn get_filter(&mut self) -> &mut Self::Filter { self }
 
     fn tailor_archetype(&self, archetype: &mut ArchetypeDescription) {
-        // copy tags from existing archetype into new
-        // except for those in `remove`
-        let tags = self
-            .existing
-            .iter()
-            .filter(|(t, _)| !self.remove.contains(t));
-
-        for (tag_type, meta) in tags {
-            archetype.register_tag_raw(*tag_type, *meta);
-        }
-
-        // append tag from `add`
-        for (tag_type, meta, _) in self.add.iter() {
-            archetype.register_tag_raw(*tag_type, *meta);
+        for tag in &self.0 {
+            tag.register(archetype);
         }
     }
 }
 
-impl<'a, 'b> Filter<ArchetypeFilterData<'b>> for DynamicTagLayout<'a> {
-    type Iter = SliceVecIter<'b, TagTypeId>;
+impl<'a> Filter<ArchetypeFilterData<'a>> for BuilderTagSet {
+    type Iter = SliceVecIter<'a, TagTypeId>;
 
-    fn collect(&self, source: ArchetypeFilterData<'b>) -> Self::Iter { source.tag_types.iter() }
+    fn collect(&self, source: ArchetypeFilterData<'a>) -> Self::Iter { source.tag_types.iter() }
 
     fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
-        Some(
-            item.len() == (self.existing.len() + self.add.len() - self.remove.len())
-                && item.iter().all(|t| {
-                    // all types are not in remove
-                    !self.remove.contains(t)
-                    // any are either in existing or add
-                        && (self.existing.iter().any(|(x, _)| x == t)
-                            || self.add.iter().any(|(x, _, _)| x == t))
-                }),
-        )
+        Some(item.len() == self.0.len() && self.0.iter().all(|t| item.contains(&t.tag_type())))
     }
 }
 
-impl<'a, 'b> Filter<ChunksetFilterData<'b>> for DynamicTagLayout<'a> {
-    type Iter = Take<Enumerate<Repeat<&'b ArchetypeData>>>;
+impl<'a> Filter<ChunksetFilterData<'a>> for BuilderTagSet {
+    type Iter = Take<Enumerate<Repeat<&'a ArchetypeData>>>;
 
-    fn collect(&self, source: ChunksetFilterData<'b>) -> Self::Iter {
+    fn collect(&self, source: ChunksetFilterData<'a>) -> Self::Iter {
         std::iter::repeat(source.archetype_data)
             .enumerate()
             .take(source.archetype_data.len())
     }
 
-    fn is_match(&self, (chunk_index, arch): &<Self::Iter as Iterator>::Item) -> Option<bool> {
-        for (type_id, meta) in self.existing {
-            if self.remove.contains(type_id) {
-                continue;
-            }
+    fn is_match(&self, (set, arch): &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        Some(self.0.iter().all(|t| t.matches(arch, *set)))
+    }
+}
 
-            unsafe {
-                // find the value of the tag in the source chunk
-                let (slice_ptr, element_size, _) = self
-                    .storage
-                    .archetypes()
-                    .get(self.archetype)
-                    .unwrap()
-                    .tags()
-                    .get(*type_id)
-                    .unwrap()
-                    .data_raw();
-                let current = slice_ptr.as_ptr().add(self.chunk * element_size);
+struct BuilderComponentFilter(Vec<ComponentTypeId>);
 
-                // find the value of the tag in the candidate chunk
-                let (slice_ptr, element_size, _) = arch.tags().get(*type_id).unwrap().data_raw();
-                let candidate = slice_ptr.as_ptr().add(chunk_index * element_size);
+impl<'a> Filter<ArchetypeFilterData<'a>> for BuilderComponentFilter {
+    type Iter = SliceVecIter<'a, ComponentTypeId>;
 
-                if !meta.equals(current, candidate) {
-                    return Some(false);
-                }
+    fn collect(&self, source: ArchetypeFilterData<'a>) -> Self::Iter {
+        source.component_types.iter()
+    }
+
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        Some(item.len() == self.0.len() && self.0.iter().all(|t| item.contains(t)))
+    }
+}
+
+/// Writes the components of a set of builders which all share the same layout.
+struct BuilderComponentSource {
+    entities: VecDeque<Vec<Box<dyn BuilderComponent>>>,
+    filter: BuilderComponentFilter,
+}
+
+impl BuilderComponentSource {
+    fn new(entities: Vec<Vec<Box<dyn BuilderComponent>>>) -> Self {
+        let types = entities
+            .first()
+            .map(|c| c.iter().map(|c| c.component_type()).collect())
+            .unwrap_or_default();
+
+        Self {
+            entities: entities.into(),
+            filter: BuilderComponentFilter(types),
+        }
+    }
+}
+
+impl ComponentLayout for BuilderComponentSource {
+    type Filter = BuilderComponentFilter;
+
+    fn get_filter(&mut self) -> &mut Self::Filter { &mut self.filter }
+
+    fn tailor_archetype(&self, archetype: &mut ArchetypeDescription) {
+        if let Some(components) = self.entities.front() {
+            for component in components {
+                component.register(archetype);
             }
         }
+    }
+}
 
-        for (type_id, meta, ptr) in self.add {
-            unsafe {
-                let (slice_ptr, element_size, _) = arch.tags().get(*type_id).unwrap().data_raw();
-                let candidate = slice_ptr.as_ptr().add(chunk_index * element_size);
+impl ComponentSource for BuilderComponentSource {
+    fn is_empty(&mut self) -> bool { self.entities.is_empty() }
 
-                if !meta.equals(ptr.as_ptr(), candidate) {
-                    return Some(false);
-                }
+    fn write(&mut self, allocator: &mut EntityAllocator, chunk: &mut ComponentStorage) -> usize {
+        let space = chunk.capacity() - chunk.len();
+        let mut writer = chunk.writer();
+        let (entities, components) = writer.get();
+        let mut count = 0;
+
+        while count < space {
+            let values = match self.entities.pop_front() {
+                Some(values) => values,
+                None => break,
+            };
+
+            entities.push(allocator.create_entity());
+            for value in values {
+                unsafe { value.write(&mut *components.get()) };
             }
+            count += 1;
         }
 
-        Some(true)
+        count
     }
 }
 
+impl IntoComponentSource for BuilderComponentSource {
+    type Source = Self;
+
+    fn into(self) -> Self::Source { self }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1643,4 +3414,439 @@ mod tests {
         assert_eq!(*b.get_component::<Pos>(entity_b).unwrap(), Pos(7., 8., 9.));
         assert_eq!(*b.get_component::<Pos>(entity_a).unwrap(), Pos(1., 2., 3.));
     }
+
+    #[test]
+    fn merge_layout_mismatch() {
+        use crate::storage::ComponentMeta;
+
+        let universe = Universe::new();
+        let mut a = universe.create_world();
+        let mut b = universe.create_world();
+
+        let entity_b = b.insert((), vec![(Pos(7., 8., 9.),)])[0];
+
+        // an archetype whose `Pos` was registered with a different size
+        let mut desc = ArchetypeDescription::default();
+        desc.register_component_raw(ComponentTypeId::of::<Pos>(), ComponentMeta::raw(8, 4));
+        a.storage_mut().alloc_archetype(desc);
+        let entity_a = a.insert((), vec![(Rot(0.1, 0.2, 0.3),)])[0];
+
+        let (a, err) = b.try_merge(a).unwrap_err();
+        assert_eq!("<unknown>", err.type_name);
+        assert_eq!(std::mem::size_of::<Pos>(), err.expected.size);
+        assert_eq!(8, err.found.size);
+
+        // the destination is left untouched
+        assert_eq!(1, b.storage().archetypes().len());
+        assert_eq!(*b.get_component::<Pos>(entity_b).unwrap(), Pos(7., 8., 9.));
+
+        // the source is handed back intact
+        assert_eq!(2, a.storage().archetypes().len());
+        assert_eq!(
+            *a.get_component::<Rot>(entity_a).unwrap(),
+            Rot(0.1, 0.2, 0.3)
+        );
+        assert!(!b.is_alive(entity_a));
+
+        // a differing fingerprint is also rejected
+        let mut c = universe.create_world();
+        let mut desc = ArchetypeDescription::default();
+        desc.register_component_raw(
+            ComponentTypeId::of::<Pos>(),
+            ComponentMeta::of::<Pos>().with_fingerprint(1),
+        );
+        c.storage_mut().alloc_archetype(desc);
+        assert!(b.try_merge(c).is_err());
+    }
+
+    #[test]
+    fn try_merge_component_fingerprints() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let universe = Universe::new();
+        let mut a = universe.create_world();
+        let mut b = universe.create_world();
+        a.insert((), vec![(Pos(1., 2., 3.),)]);
+        b.insert((), vec![(Pos(4., 5., 6.),)]);
+
+        // existing archetypes take the fingerprint
+        a.set_component_fingerprint::<Pos>(7);
+        let (mut b, err) = a.try_merge(b).unwrap_err();
+        assert_eq!(7, err.expected.fingerprint);
+        assert_eq!(0, err.found.fingerprint);
+
+        // as do archetypes created afterwards
+        b.set_component_fingerprint::<Pos>(7);
+        let entity = b.insert((), vec![(Pos(7., 8., 9.), Rot(0., 0., 0.))])[0];
+        a.try_merge(b).map_err(|(_, err)| err).unwrap();
+        assert_eq!(*a.get_component::<Pos>(entity).unwrap(), Pos(7., 8., 9.));
+    }
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort_by_key(|e| e.index());
+        entities
+    }
+
+    #[test]
+    fn track_writes_precision() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+        world.track_writes::<Pos>();
+
+        let entities = world
+            .insert(
+                (),
+                (0..10).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+            )
+            .to_vec();
+
+        // insertion marks new slots as written
+        assert_eq!(
+            sorted(entities.clone()),
+            sorted(world.drain_dirty::<Pos>().collect())
+        );
+        assert_eq!(0, world.drain_dirty::<Pos>().count());
+
+        world.get_component_mut::<Pos>(entities[3]).unwrap().0 = 10.;
+        world.get_component_mut::<Pos>(entities[7]).unwrap().0 = 10.;
+        world.get_component_mut::<Rot>(entities[5]).unwrap().0 = 10.;
+
+        assert_eq!(
+            vec![entities[3], entities[7]],
+            sorted(world.drain_dirty::<Pos>().collect())
+        );
+        assert_eq!(0, world.drain_dirty::<Pos>().count());
+
+        // untracked components never report writes
+        assert_eq!(0, world.drain_dirty::<Rot>().count());
+    }
+
+    #[test]
+    fn track_writes_structural() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+        let entities = world
+            .insert((), (0..4).map(|i| (Pos(i as f32, 0., 0.),)))
+            .to_vec();
+
+        // enabling tracking does not flag existing data
+        world.track_writes::<Pos>();
+        assert_eq!(0, world.drain_dirty::<Pos>().count());
+
+        world.get_component_mut::<Pos>(entities[3]).unwrap().0 = 10.;
+        // deleting swaps the last entity into the removed slot, which must keep its flag
+        world.delete(entities[0]);
+        assert_eq!(
+            vec![entities[3]],
+            world.drain_dirty::<Pos>().collect::<Vec<_>>()
+        );
+
+        // moving to a new archetype flags the entity
+        world.add_component(entities[1], Rot(0., 0., 0.));
+        assert_eq!(
+            vec![entities[1]],
+            world.drain_dirty::<Pos>().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Pos(1., 0., 0.),
+            *world.get_component::<Pos>(entities[1]).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "par-iter")]
+    #[cfg_attr(miri, ignore = "runs on the rayon thread pool, see tests/miri.rs")]
+    fn track_writes_par_for_each() {
+        use crate::query::{IntoQuery, Write};
+
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+        world.track_writes::<Pos>();
+
+        let written = world
+            .insert((Model(0),), (0..100).map(|i| (Pos(i as f32, 0., 0.),)))
+            .to_vec();
+        world.insert((Model(1),), (0..100).map(|i| (Pos(i as f32, 0., 0.),)));
+        world.drain_dirty::<Pos>().for_each(drop);
+
+        let query = Write::<Pos>::query().filter(crate::filter::filter_fns::tag_value(&Model(0)));
+        query.par_for_each(&mut world, |mut pos| pos.1 = 1.);
+
+        assert_eq!(
+            sorted(written),
+            sorted(world.drain_dirty::<Pos>().collect())
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        all(miri, feature = "par-iter"),
+        ignore = "runs on the rayon thread pool, see tests/miri.rs"
+    )]
+    fn par_for_each_with_and_without_par_iter() {
+        use crate::query::{IntoQuery, Read, Write};
+        use std::sync::Mutex;
+
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+        world.insert((Model(0),), (0..100).map(|i| (Pos(i as f32, 0., 0.),)));
+        world.insert((Model(1),), (100..200).map(|i| (Pos(i as f32, 0., 0.),)));
+
+        Write::<Pos>::query().par_for_each(&mut world, |mut pos| pos.1 = 1.);
+
+        let query = Read::<Pos>::query();
+        let visited = Mutex::new(Vec::new());
+        query.par_for_each_immutable(&world, |pos| visited.lock().unwrap().push(pos.0 as usize));
+        #[allow(unused_mut)]
+        let mut visited = visited.into_inner().unwrap();
+        let expected = query
+            .iter_immutable(&world)
+            .map(|pos| {
+                assert_eq!(1., pos.1);
+                pos.0 as usize
+            })
+            .collect::<Vec<_>>();
+
+        // the sequential fallback visits entities in the same order as `iter`
+        #[cfg(feature = "par-iter")]
+        visited.sort_unstable();
+        assert_eq!(expected, visited);
+
+        let chunks = query.par_iter_chunks_immutable(&world);
+        assert_eq!(query.chunk_count(&world), chunks.count());
+    }
+
+    fn location(world: &World, entity: Entity) -> (ArchetypeIndex, SetIndex) {
+        let location = world.entity_allocator.get_location(entity.index()).unwrap();
+        (location.archetype(), location.set())
+    }
+
+    #[test]
+    fn builder_conditional_archetype() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+
+        let build = |world: &mut World, moving: bool| {
+            EntityBuilder::new()
+                .with(Pos(1., 2., 3.))
+                .with_if(moving, Rot(0.1, 0.2, 0.3))
+                .with_tag(Model(5))
+                .build(world)
+        };
+
+        let still = build(&mut world, false);
+        let moving = build(&mut world, true);
+        let moving2 = build(&mut world, true);
+
+        assert_ne!(location(&world, still).0, location(&world, moving).0);
+        assert_eq!(location(&world, moving), location(&world, moving2));
+        assert!(world.get_component::<Rot>(still).is_none());
+        assert_eq!(
+            Rot(0.1, 0.2, 0.3),
+            *world.get_component::<Rot>(moving).unwrap()
+        );
+        assert_eq!(Model(5), *world.get_tag::<Model>(still).unwrap());
+    }
+
+    #[test]
+    fn builder_matches_tuple_insert() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+
+        let inserted = world.insert((Model(5),), vec![(Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3))])[0];
+        let built = EntityBuilder::new()
+            .with(Rot(0.4, 0.5, 0.6))
+            .with(Pos(4., 5., 6.))
+            .with_tag(Model(5))
+            .build(&mut world);
+        let other_tag = EntityBuilder::new()
+            .with(Pos(4., 5., 6.))
+            .with(Rot(0.4, 0.5, 0.6))
+            .with_tag(Model(6))
+            .build(&mut world);
+
+        assert_eq!(location(&world, inserted), location(&world, built));
+        assert_eq!(location(&world, inserted).0, location(&world, other_tag).0);
+        assert_ne!(location(&world, inserted).1, location(&world, other_tag).1);
+        assert_eq!(Pos(4., 5., 6.), *world.get_component::<Pos>(built).unwrap());
+        assert_eq!(
+            Rot(0.4, 0.5, 0.6),
+            *world.get_component::<Rot>(built).unwrap()
+        );
+    }
+
+    #[test]
+    fn builder_non_clone_component() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        #[derive(Debug, PartialEq)]
+        struct Unique(Box<u32>);
+
+        let mut world = create();
+        let entity = EntityBuilder::new()
+            .with(Unique(Box::new(5)))
+            .with_if(true, Pos(1., 2., 3.))
+            .build(&mut world);
+
+        assert_eq!(
+            Unique(Box::new(5)),
+            *world.get_component::<Unique>(entity).unwrap()
+        );
+        assert_eq!(
+            Pos(1., 2., 3.),
+            *world.get_component::<Pos>(entity).unwrap()
+        );
+    }
+
+    #[test]
+    fn builder_batch() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+
+        let entities = EntityBuilder::build_batch(&mut world, 6, |i| {
+            EntityBuilder::new()
+                .with(Pos(i as f32, 0., 0.))
+                .with_if(i >= 4, Rot(0., 0., 0.))
+                .with_tag(Model(5))
+        });
+
+        assert_eq!(6, entities.len());
+        for (i, e) in entities.iter().enumerate() {
+            assert_eq!(
+                Pos(i as f32, 0., 0.),
+                *world.get_component::<Pos>(*e).unwrap()
+            );
+            assert_eq!(i >= 4, world.get_component::<Rot>(*e).is_some());
+        }
+        assert_eq!(location(&world, entities[0]), location(&world, entities[3]));
+        assert_ne!(location(&world, entities[0]), location(&world, entities[4]));
+    }
+
+    #[test]
+    fn invalidate_all_handles_unretires_slots() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+        let entity = world.entity_allocator.set_version(entity, u32::MAX - 1);
+        assert!(world.is_alive(entity));
+
+        world.delete(entity);
+        assert_eq!(1, world.allocator_stats().retired);
+
+        let other = world.insert((), vec![(Pos(4., 5., 6.),)])[0];
+        assert_eq!(1, world.invalidate_all_handles());
+        assert!(!world.is_alive(other));
+
+        let stats = world.allocator_stats();
+        assert_eq!(0, stats.retired);
+        assert_eq!(0, stats.alive);
+        assert_eq!(stats.capacity, stats.free);
+    }
+
+    #[test]
+    fn defrag_releases_reserved_chunks() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut world = create();
+
+        world.reserve_chunks::<_, (Pos,), _>(vec![(Model(0),)], 8);
+        let entities = world
+            .insert((Model(0),), (0..10).map(|_| (Pos(0., 0., 0.),)))
+            .to_vec();
+        assert_eq!(8, world.storage().archetypes()[0].chunksets()[0].len());
+
+        world.defrag(None);
+
+        assert_eq!(1, world.storage().archetypes()[0].chunksets()[0].len());
+        for entity in entities {
+            assert_eq!(
+                Pos(0., 0., 0.),
+                *world.get_component::<Pos>(entity).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn iter_entities_clamps_to_entity_count() {
+        use crate::query::{IntoQuery, Read};
+        use std::sync::Mutex;
+
+        struct Capture(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = {
+            let log = log.clone();
+            tracing_subscriber::fmt::Subscriber::builder()
+                .with_writer(move || Capture(log.clone()))
+                .finish()
+        };
+
+        let mut world = create();
+        let entities = world
+            .insert((), (0..4).map(|i| (Pos(i as f32, 0., 0.),)))
+            .to_vec();
+
+        // leave the chunk's component array longer than its entity array
+        world.storage_mut().archetypes_mut()[0].chunksets_mut()[0][0].truncate_entities(2);
+
+        let query = Read::<Pos>::query();
+        let (forward, mut backward) = tracing::subscriber::with_default(subscriber, || {
+            let forward = query
+                .iter_entities_immutable(&world)
+                .map(|(e, pos)| (e, *pos))
+                .collect::<Vec<_>>();
+            let backward = query
+                .iter_entities_immutable(&world)
+                .rev()
+                .map(|(e, pos)| (e, *pos))
+                .collect::<Vec<_>>();
+            (forward, backward)
+        });
+
+        let expected = vec![
+            (entities[0], Pos(0., 0., 0.)),
+            (entities[1], Pos(1., 0., 0.)),
+        ];
+        assert_eq!(expected, forward);
+        backward.reverse();
+        assert_eq!(expected, backward);
+
+        // nth and ranges stop at the last entity as well
+        let mut chunk = query.iter_chunks_immutable(&world).next().unwrap();
+        assert_eq!(
+            Some(entities[1]),
+            chunk.iter_entities().nth(1).map(|(e, _)| e)
+        );
+        assert!(chunk.iter_entities().nth(2).is_none());
+        let range = chunk
+            .iter_entities_range(1..4)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![entities[1]], range);
+
+        #[cfg(debug_assertions)]
+        {
+            let log = String::from_utf8(log.lock().unwrap().clone()).unwrap();
+            assert!(log.contains("Chunk entity and component counts disagree"));
+        }
+    }
 }
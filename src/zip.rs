@@ -90,6 +90,15 @@ macro_rules! impl_zip_iter {
                 Some(($($B,)*))
             }
 
+            fn nth(&mut self, n: usize) -> Option<Self::Item>
+            {
+                let ($(ref mut $B,)*) = self.t;
+                $(
+                    let $B = $B.nth(n)?;
+                )*
+                Some(($($B,)*))
+            }
+
             #[allow(clippy::let_and_return)]
             fn size_hint(&self) -> (usize, Option<usize>)
             {
@@ -108,6 +117,32 @@ macro_rules! impl_zip_iter {
                 $B: ExactSizeIterator,
             )*
         { }
+
+        // like iter::Zip, longer iterators are first trimmed to the length of the shortest
+        #[allow(non_snake_case)]
+        impl<$($B),*> DoubleEndedIterator for Zip<($($B,)*)> where
+            $(
+                $B: DoubleEndedIterator + ExactSizeIterator,
+            )*
+        {
+            fn next_back(&mut self) -> Option<Self::Item>
+            {
+                let ($(ref mut $B,)*) = self.t;
+                let len = usize::MAX;
+                $(
+                    let len = ::std::cmp::min($B.len(), len);
+                )*
+                $(
+                    while $B.len() > len {
+                        $B.next_back();
+                    }
+                )*
+                $(
+                    let $B = $B.next_back()?;
+                )*
+                Some(($($B,)*))
+            }
+        }
     );
 }
 
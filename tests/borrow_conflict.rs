@@ -0,0 +1,183 @@
+// runtime borrow checks are only performed with debug assertions or the borrow-checks feature
+#![cfg(any(debug_assertions, feature = "borrow-checks"))]
+
+use legion::borrow::{BorrowConflict, BorrowConflictPolicy};
+use legion::prelude::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+fn world() -> (World, Entity, Entity) {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let held = world.insert((Model(0),), (0..10).map(|i| (Pos(i as f32), Vel(1.))))[0];
+    let free = world.insert((Model(1),), (0..10).map(|i| (Pos(i as f32), Vel(1.))))[0];
+    (world, held, free)
+}
+
+#[test]
+fn panic_policy_panics() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let (world, held, _) = world();
+    assert_eq!(BorrowConflictPolicy::Panic, world.borrow_conflict_policy());
+
+    let writer = Write::<Pos>::query().filter(tag_value(&Model(0)));
+    let mut chunk = unsafe { writer.iter_chunks_unchecked(&world) }
+        .next()
+        .unwrap();
+    let _borrow = chunk.iter();
+
+    let get = catch_unwind(AssertUnwindSafe(|| {
+        world.get_component::<Pos>(held).is_some()
+    }));
+    assert!(get.is_err());
+    let iter = catch_unwind(AssertUnwindSafe(|| {
+        Read::<Pos>::query().iter_immutable(&world).count()
+    }));
+    assert!(iter.is_err());
+
+    // fallible accessors report the conflict under every policy
+    let err = world.try_get_component::<Pos>(held).err().unwrap();
+    assert_eq!(std::any::type_name::<Pos>(), err.component);
+    assert!(!err.mutable);
+}
+
+#[test]
+fn return_error_policy_skips() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let (mut world, held, free) = world();
+    world.set_borrow_conflict_policy(BorrowConflictPolicy::ReturnError);
+
+    let changed = Read::<Pos>::query().filter(changed::<Pos>());
+    let writer = Write::<Pos>::query().filter(tag_value(&Model(0)));
+    let mut chunk = unsafe { writer.iter_chunks_unchecked(&world) }
+        .next()
+        .unwrap();
+    let borrow = chunk.iter();
+
+    // component accessors
+    assert!(world.get_component::<Pos>(held).is_none());
+    assert_eq!(
+        Some(Pos(0.)),
+        world.get_component::<Pos>(free).map(|pos| *pos)
+    );
+    assert!(world.get_component::<Vel>(held).is_some());
+    match world.try_get_component::<Pos>(held) {
+        Err(BorrowConflict {
+            component, mutable, ..
+        }) => {
+            assert_eq!(std::any::type_name::<Pos>(), component);
+            assert!(!mutable);
+        }
+        _ => panic!("expected a borrow conflict"),
+    }
+    let vel = unsafe { world.try_get_component_mut_unchecked::<Vel>(held) };
+    assert_eq!(Ok(true), vel.map(|vel| vel.is_some()));
+
+    // queries skip the conflicting chunk, but still visit chunks they do not need to borrow
+    assert_eq!(10, Read::<Pos>::query().iter_immutable(&world).count());
+    assert_eq!(
+        20,
+        unsafe { Write::<Vel>::query().iter_unchecked(&world) }.count()
+    );
+    assert_eq!(
+        10,
+        unsafe { Write::<Pos>::query().iter_unchecked(&world) }.count()
+    );
+    assert_eq!(20, Read::<Pos>::query().count(&world));
+    assert!(Read::<Pos>::query().get_immutable(&world, held).is_none());
+    assert!(Read::<Pos>::query().get_immutable(&world, free).is_some());
+    let models = changed
+        .iter_chunks_immutable(&world)
+        .map(|chunk| chunk.tag::<Model>().unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![1], models);
+
+    // once released, the skipped chunk is visited normally on the next frame
+    drop(borrow);
+    drop(chunk);
+    assert!(world.get_component::<Pos>(held).is_some());
+    assert!(world.try_get_component::<Pos>(held).unwrap().is_some());
+    let models = changed
+        .iter_chunks_immutable(&world)
+        .map(|chunk| chunk.tag::<Model>().unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![0], models);
+    assert_eq!(20, Read::<Pos>::query().iter_immutable(&world).count());
+}
+
+#[test]
+fn panic_in_debug_policy_follows_build() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let (mut world, held, free) = world();
+    world.set_borrow_conflict_policy(BorrowConflictPolicy::PanicInDebugSkipInRelease);
+    assert_eq!(
+        !cfg!(debug_assertions),
+        world.borrow_conflict_policy().skips()
+    );
+
+    let writer = Write::<Pos>::query().filter(tag_value(&Model(0)));
+    let mut chunk = unsafe { writer.iter_chunks_unchecked(&world) }
+        .next()
+        .unwrap();
+    let borrow = chunk.iter();
+
+    let get = catch_unwind(AssertUnwindSafe(|| {
+        world.get_component::<Pos>(held).is_some()
+    }));
+    let iter = catch_unwind(AssertUnwindSafe(|| {
+        Read::<Pos>::query().iter_immutable(&world).count()
+    }));
+    if cfg!(debug_assertions) {
+        assert!(get.is_err());
+        assert!(iter.is_err());
+    } else {
+        assert!(!get.unwrap());
+        assert_eq!(10, iter.unwrap());
+    }
+    assert!(world.get_component::<Pos>(free).is_some());
+
+    drop(borrow);
+    drop(chunk);
+    assert_eq!(20, Read::<Pos>::query().iter_immutable(&world).count());
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn return_error_policy_skips_par() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let (mut world, _, _) = world();
+    world.set_borrow_conflict_policy(BorrowConflictPolicy::ReturnError);
+
+    let reader = Read::<Pos>::query().filter(tag_value(&Model(1)));
+    let mut chunk = unsafe { reader.iter_chunks_unchecked(&world) }
+        .next()
+        .unwrap();
+    let borrow = chunk.iter();
+
+    let count = AtomicUsize::new(0);
+    let query = Write::<Pos>::query();
+    unsafe {
+        query.par_for_each_unchecked(&world, |_| {
+            count.fetch_add(1, Ordering::SeqCst);
+        })
+    };
+    assert_eq!(10, count.load(Ordering::SeqCst));
+
+    drop(borrow);
+    drop(chunk);
+    query.par_for_each(&mut world, |mut pos| pos.0 = -1.);
+    assert!(Read::<Pos>::query()
+        .iter(&mut world)
+        .all(|pos| pos.0 == -1.));
+}
@@ -0,0 +1,94 @@
+use legion::prelude::*;
+use legion::world::WorldOptions;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts allocations made by the current thread, so that tests running in parallel
+/// do not disturb each other's counts.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) { System.dealloc(ptr, layout) }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let start = ALLOCATIONS.with(|count| count.get());
+    f();
+    ALLOCATIONS.with(|count| count.get()) - start
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32, f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+const SETS: u32 = 500;
+
+// inserts entities one at a time, each into a new chunk set
+fn workload(world: &mut World) {
+    for i in 0..SETS {
+        world.insert((Model(i),), vec![(Pos(0., 0., 0.),)]);
+    }
+}
+
+#[test]
+fn reserve_chunks_reduces_allocations() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+
+    let mut world = universe.create_world();
+    // create the archetype up front so that only chunk growth is measured
+    world.insert((Model(SETS),), vec![(Pos(0., 0., 0.),)]);
+    let unreserved = count_allocations(|| workload(&mut world));
+
+    let mut world = universe.create_world();
+    world.insert((Model(SETS),), vec![(Pos(0., 0., 0.),)]);
+    world.reserve_chunks::<_, (Pos,), _>((0..SETS).map(|i| (Model(i),)), 1);
+    let reserved = count_allocations(|| workload(&mut world));
+
+    assert!(
+        reserved * 2 < unreserved,
+        "reserved: {}, unreserved: {}",
+        reserved,
+        unreserved
+    );
+
+    let query = Read::<Pos>::query();
+    assert_eq!(SETS as usize + 1, query.iter_immutable(&world).count());
+}
+
+#[test]
+fn growth_factor_reduces_reallocations() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+
+    let mut options = WorldOptions::default();
+    options.chunk_growth_factor = 1.1;
+    let mut world = universe.create_world_with_options(options);
+    let slow = count_allocations(|| workload(&mut world));
+
+    options.chunk_growth_factor = 4.0;
+    let mut world = universe.create_world_with_options(options);
+    let fast = count_allocations(|| workload(&mut world));
+
+    assert!(fast < slow, "fast: {}, slow: {}", fast, slow);
+}
@@ -0,0 +1,200 @@
+use legion::clone::{CloneError, CloneImpl, EntityRemap};
+use legion::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+#[derive(Clone, Debug, PartialEq)]
+struct Name(String);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Parent(Entity);
+
+impl EntityRemap for Parent {
+    fn remap_entities(&mut self, remap: &dyn Fn(Entity) -> Entity) { self.0 = remap(self.0); }
+}
+
+fn cloner() -> CloneImpl {
+    let mut cloner = CloneImpl::new();
+    cloner
+        .register::<Pos>()
+        .register::<Vel>()
+        .register::<Name>()
+        .register_remap::<Parent>();
+    cloner
+}
+
+#[test]
+fn clone_entity_copies_components_and_tags() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let entity = world.insert((Model(5),), vec![(Pos(1.), Name("original".to_string()))])[0];
+
+    let clone = world.clone_entity(entity, &cloner()).unwrap();
+    assert_ne!(entity, clone);
+    assert!(world.is_alive(clone));
+    assert_eq!(Pos(1.), *world.get_component::<Pos>(clone).unwrap());
+    assert_eq!(Some(&Model(5)), world.get_tag::<Model>(clone));
+
+    // the clone is independent of the original
+    world
+        .get_component_mut::<Name>(clone)
+        .unwrap()
+        .0
+        .push_str(" clone");
+    *world.get_component_mut::<Pos>(clone).unwrap() = Pos(2.);
+    assert_eq!("original", world.get_component::<Name>(entity).unwrap().0);
+    assert_eq!(
+        "original clone",
+        world.get_component::<Name>(clone).unwrap().0
+    );
+    assert_eq!(Pos(1.), *world.get_component::<Pos>(entity).unwrap());
+
+    assert_eq!(2, Read::<Pos>::query().iter(&mut world).count());
+    world.delete(entity);
+    assert_eq!(Pos(2.), *world.get_component::<Pos>(clone).unwrap());
+}
+
+#[test]
+fn clone_entity_remaps_self_references() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let root = world.insert((), vec![(Pos(0.),)])[0];
+    let entity = world.insert((), vec![(Pos(1.),)])[0];
+    world.add_component(entity, Parent(entity));
+    let child = world.insert((), vec![(Pos(2.), Parent(root))])[0];
+
+    let clone = world.clone_entity(entity, &cloner()).unwrap();
+    assert_eq!(
+        Parent(clone),
+        *world.get_component::<Parent>(clone).unwrap()
+    );
+    assert_eq!(
+        Parent(entity),
+        *world.get_component::<Parent>(entity).unwrap()
+    );
+
+    // references to entities which are not cloned are left alone
+    let child_clone = world.clone_entity(child, &cloner()).unwrap();
+    assert_eq!(
+        Parent(root),
+        *world.get_component::<Parent>(child_clone).unwrap()
+    );
+}
+
+#[test]
+fn clone_entity_batch_across_archetypes() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let mut entities = world
+        .insert((Model(0),), (0..300).map(|i| (Pos(i as f32),)))
+        .to_vec();
+    let parent = world.insert((), vec![(Pos(-1.), Vel(0.))])[0];
+    entities.push(parent);
+    entities.extend_from_slice(
+        world.insert((Model(1),), (0..3).map(|i| (Vel(i as f32), Parent(parent)))),
+    );
+
+    // clone in an order which does not follow the storage layout
+    entities.reverse();
+    let clones = world.clone_entity_batch(&entities, &cloner()).unwrap();
+    assert_eq!(entities.len(), clones.len());
+
+    for (entity, clone) in entities.iter().zip(clones.iter()) {
+        assert_ne!(entity, clone);
+        assert_eq!(
+            world.get_tag::<Model>(*entity),
+            world.get_tag::<Model>(*clone)
+        );
+        assert_eq!(
+            world.get_component::<Pos>(*entity).map(|c| *c),
+            world.get_component::<Pos>(*clone).map(|c| *c)
+        );
+        assert_eq!(
+            world.get_component::<Vel>(*entity).map(|c| *c),
+            world.get_component::<Vel>(*clone).map(|c| *c)
+        );
+        if let Some(p) = world.get_component::<Parent>(*entity) {
+            assert_eq!(Parent(parent), *p);
+            let cloned_parent = clones[entities.iter().position(|e| *e == parent).unwrap()];
+            assert_eq!(
+                Parent(cloned_parent),
+                *world.get_component::<Parent>(*clone).unwrap()
+            );
+        }
+    }
+
+    assert_eq!(2 * 301, Read::<Pos>::query().iter(&mut world).count());
+    assert_eq!(2 * 4, Read::<Vel>::query().iter(&mut world).count());
+}
+
+#[test]
+fn clone_entity_batch_repeated_entities() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let root = world.insert((), vec![(Pos(0.),)])[0];
+    let child = world.insert((), vec![(Pos(1.), Parent(root))])[0];
+    let other = world.insert((), vec![(Pos(2.), Parent(root))])[0];
+
+    // each occurrence clones an independent copy of the hierarchy
+    let clones = world
+        .clone_entity_batch(&[root, child, root, child, other, other, other], &cloner())
+        .unwrap();
+    assert_eq!(7, clones.len());
+    assert_eq!(
+        Parent(clones[0]),
+        *world.get_component::<Parent>(clones[1]).unwrap()
+    );
+    assert_eq!(
+        Parent(clones[2]),
+        *world.get_component::<Parent>(clones[3]).unwrap()
+    );
+
+    assert_eq!(
+        Parent(clones[0]),
+        *world.get_component::<Parent>(clones[4]).unwrap()
+    );
+    assert_eq!(
+        Parent(clones[2]),
+        *world.get_component::<Parent>(clones[5]).unwrap()
+    );
+
+    // occurrences beyond those of the referenced entity refer to its last clone
+    assert_eq!(
+        Parent(clones[2]),
+        *world.get_component::<Parent>(clones[6]).unwrap()
+    );
+    assert_eq!(Parent(root), *world.get_component::<Parent>(child).unwrap());
+    assert_eq!(10, Read::<Pos>::query().iter(&mut world).count());
+}
+
+#[test]
+fn clone_entity_errors() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let entities = world.insert((), vec![(Pos(0.),), (Pos(1.),)]).to_vec();
+    let unregistered = world.insert((), vec![(Pos(0.), 5usize)])[0];
+
+    let err = world
+        .clone_entity_batch(&[entities[0], unregistered], &cloner())
+        .unwrap_err();
+    assert_eq!(CloneError::NotRegistered("usize"), err);
+    assert!(err.to_string().contains("usize"));
+
+    world.delete(entities[1]);
+    assert_eq!(
+        Err(CloneError::EntityNotFound(entities[1])),
+        world.clone_entity(entities[1], &cloner())
+    );
+
+    // failed batches do not clone any entities
+    assert_eq!(2, Read::<Pos>::query().iter(&mut world).count());
+}
@@ -0,0 +1,98 @@
+use legion::entity::DebugLabels;
+use legion::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+
+#[test]
+#[cfg(any(debug_assertions, feature = "debug-labels"))]
+fn labels_follow_entities() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert_labeled((), (0..3).map(|i| (Pos(i as f32),)), |i| {
+            format!("enemy {}", i)
+        })
+        .to_vec();
+    let player = world.insert((), vec![(Pos(0.),)])[0];
+    world.set_debug_label(player, "player");
+
+    assert_eq!(Some("enemy 2"), world.debug_label(entities[2]));
+    assert_eq!(
+        format!("{} \"player\"", player),
+        world.entity_debug(player).to_string()
+    );
+    assert_eq!(
+        format!("{:?}", world.entity_debug(player)),
+        world.entity_debug(player).to_string()
+    );
+    assert_eq!(4, world.debug_labels().len());
+
+    // entities without a label are formatted on their own
+    let unlabelled = world.insert((), vec![(Pos(0.),)])[0];
+    assert_eq!(None, world.debug_label(unlabelled));
+    assert_eq!(
+        unlabelled.to_string(),
+        world.entity_debug(unlabelled).to_string()
+    );
+
+    // clones are given the label of their original
+    let mut cloner = legion::clone::CloneImpl::new();
+    cloner.register::<Pos>();
+    let clone = world.clone_entity(player, &cloner).unwrap();
+    assert_eq!(Some("player"), world.debug_label(clone));
+
+    // labels are discarded along with their entity, and dead entities cannot be labelled
+    world.delete(entities[0]);
+    assert_eq!(None, world.debug_label(entities[0]));
+    world.set_debug_label(entities[0], "ghost");
+    assert_eq!(None, world.debug_label(entities[0]));
+
+    // labels are moved along with merged entities
+    let mut other = universe.create_world();
+    let merged = other.insert((), vec![(Pos(0.),)])[0];
+    other.set_debug_label(merged, String::from("merged"));
+    world.merge(other);
+    assert_eq!(Some("merged"), world.debug_label(merged));
+    assert_eq!(Some("player"), world.debug_label(player));
+}
+
+#[test]
+#[cfg(all(
+    any(debug_assertions, feature = "debug-labels"),
+    any(debug_assertions, feature = "borrow-checks")
+))]
+#[should_panic(expected = "\"player\"")]
+fn borrow_conflicts_name_the_entity() {
+    let mut world = Universe::new().create_world();
+    let player = world.insert((), vec![(Pos(0.),)])[0];
+    world.set_debug_label(player, "player");
+
+    let query = Write::<Pos>::query();
+    let mut iter = unsafe { query.iter_unchecked(&world) };
+    let _pos = iter.next();
+    world.get_component::<Pos>(player);
+}
+
+#[test]
+#[cfg(not(any(debug_assertions, feature = "debug-labels")))]
+fn labels_are_disabled() {
+    assert!(!DebugLabels::ENABLED);
+    assert_eq!(0, std::mem::size_of::<DebugLabels>());
+
+    let mut world = Universe::new().create_world();
+    let entity = world.insert_labeled((), vec![(Pos(0.),)], |_| -> String { unreachable!() })[0];
+    world.set_debug_label(entity, "player");
+    assert_eq!(None, world.debug_label(entity));
+    assert_eq!(entity.to_string(), world.entity_debug(entity).to_string());
+}
+
+#[test]
+fn labels_enabled_matches_build() {
+    assert_eq!(
+        cfg!(any(debug_assertions, feature = "debug-labels")),
+        DebugLabels::ENABLED
+    );
+}
@@ -0,0 +1,124 @@
+use legion::prelude::*;
+use legion::storage::ComponentMeta;
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<(&'static str, u32)>>>;
+
+struct A(u32, Log);
+struct B(u32, Log);
+
+impl Drop for A {
+    fn drop(&mut self) { self.1.lock().unwrap().push(("A", self.0)); }
+}
+
+impl Drop for B {
+    fn drop(&mut self) { self.1.lock().unwrap().push(("B", self.0)); }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+
+fn take(log: &Log) -> Vec<(&'static str, u32)> { std::mem::take(&mut *log.lock().unwrap()) }
+
+#[test]
+fn component_meta_needs_drop() {
+    assert!(!ComponentMeta::of::<Pos>().needs_drop());
+    assert!(!ComponentMeta::of::<[u64; 4]>().needs_drop());
+    assert!(ComponentMeta::of::<String>().needs_drop());
+    assert!(ComponentMeta::of::<A>().needs_drop());
+}
+
+#[test]
+fn world_drop_order() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let log = Log::default();
+    let mut world = Universe::new().create_world();
+    world.insert(
+        (),
+        (0..3).map(|i| (A(i, log.clone()), Pos(0.), B(i, log.clone()))),
+    );
+    assert!(take(&log).is_empty());
+    drop(world);
+
+    // component types in the order the archetype declares them, then slots in ascending order
+    let expected = vec![("A", 0), ("A", 1), ("A", 2), ("B", 0), ("B", 1), ("B", 2)];
+    assert_eq!(expected, take(&log));
+
+    let mut world = Universe::new().create_world();
+    world.insert((), (0..2).map(|i| (B(i, log.clone()), A(i, log.clone()))));
+    drop(world);
+    assert_eq!(vec![("B", 0), ("B", 1), ("A", 0), ("A", 1)], take(&log));
+}
+
+#[test]
+fn delete_drops_only_removed_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let log = Log::default();
+    let mut world = Universe::new().create_world();
+    let entities = world
+        .insert((), (0..5).map(|i| (A(i, log.clone()), B(i, log.clone()))))
+        .to_vec();
+
+    // the last entity is swapped into the removed slot without being dropped
+    world.delete(entities[1]);
+    assert_eq!(vec![("A", 1), ("B", 1)], take(&log));
+    world.delete(entities[4]);
+    assert_eq!(vec![("A", 4), ("B", 4)], take(&log));
+
+    drop(world);
+    let expected = vec![("A", 0), ("A", 3), ("A", 2), ("B", 0), ("B", 3), ("B", 2)];
+    assert_eq!(expected, take(&log));
+}
+
+#[test]
+fn emptied_chunks_drop_each_component_once() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let log = Log::default();
+    let mut world = Universe::new().create_world();
+    let entities = world
+        .insert((), (0..3000).map(|i| (A(i, log.clone()), Pos(0.))))
+        .to_vec();
+    let chunks = Read::<Pos>::query().chunk_count(&world);
+    assert!(chunks > 1);
+
+    // emptying every chunk frees them without dropping anything further
+    for entity in entities {
+        world.delete(entity);
+    }
+    assert_eq!(0, Read::<Pos>::query().chunk_count(&world));
+    let mut dropped = take(&log);
+    assert_eq!(3000, dropped.len());
+    dropped.sort();
+    dropped.dedup();
+    assert_eq!(3000, dropped.len());
+
+    drop(world);
+    assert!(take(&log).is_empty());
+}
+
+#[test]
+fn delete_all_and_moves_drop_each_component_once() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let log = Log::default();
+    let mut world = Universe::new().create_world();
+    let entities = world
+        .insert((), (0..10).map(|i| (A(i, log.clone()),)))
+        .to_vec();
+
+    // moving an entity to another archetype moves its components without dropping them
+    world.add_component(entities[0], B(0, log.clone()));
+    world.remove_component::<B>(entities[0]);
+    assert_eq!(vec![("B", 0)], take(&log));
+
+    assert_eq!(10, world.delete_all(&component::<A>()));
+    let mut dropped = take(&log);
+    dropped.sort();
+    assert_eq!((0..10).map(|i| ("A", i)).collect::<Vec<_>>(), dropped);
+
+    drop(world);
+    assert!(take(&log).is_empty());
+}
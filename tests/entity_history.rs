@@ -0,0 +1,174 @@
+use legion::clone::CloneImpl;
+use legion::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32, f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+fn cloner() -> CloneImpl {
+    let mut cloner = CloneImpl::new();
+    cloner.register::<Pos>().register::<Vel>();
+    cloner
+}
+
+fn render(world: &World, entity: Entity) -> String {
+    world
+        .entity_history(entity)
+        .iter()
+        .map(|entry| entry.to_string().replace("entity_history::", ""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn entity_history_records_structural_ops() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.set_entity_history(16);
+    assert!(world.records_entity_history());
+
+    world.push_op_label("spawner");
+    let entities = world
+        .insert((Model(0),), vec![(Pos(0., 0.),), (Pos(1., 1.),)])
+        .to_vec();
+    world.pop_op_label();
+
+    world.advance_tick();
+    world.add_component(entities[0], Vel(1., 1.));
+    // replacing a component's value is not a structural change
+    world.add_component(entities[0], Vel(2., 2.));
+    world.add_tag(entities[0], Model(1));
+    world.remove_component::<Vel>(entities[0]);
+    world.advance_tick();
+    world.remove_tag::<Model>(entities[0]);
+    world.add_tag(entities[0], Model(2));
+    world.delete(entities[0]);
+
+    let expected = [
+        "op 0 at tick 0 by \"spawner\": inserted",
+        "op 1 at tick 1: added component Vel",
+        "op 2 at tick 1: changed tag Model",
+        "op 3 at tick 1: removed component Vel",
+        "op 4 at tick 2: removed tag Model",
+        "op 5 at tick 2: added tag Model",
+        "op 6 at tick 2: deleted",
+    ];
+    assert_eq!(expected.join("\n"), render(&world, entities[0]));
+    assert_eq!(expected[0], render(&world, entities[1]));
+
+    // entities inserted by the same call share an operation
+    let history = world.entity_history(entities[1]);
+    assert_eq!(0, history[0].op);
+    assert_eq!(Some("spawner"), history[0].label.as_deref());
+}
+
+#[test]
+fn entity_history_retains_most_recent_ops() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.set_entity_history(2);
+
+    let entity = world.insert((), vec![(Pos(0., 0.),)])[0];
+    world.add_component(entity, Vel(0., 0.));
+    world.remove_component::<Vel>(entity);
+    assert_eq!(
+        "op 1 at tick 0: added component Vel\nop 2 at tick 0: removed component Vel",
+        render(&world, entity)
+    );
+
+    world.set_entity_history(1);
+    assert_eq!(
+        "op 2 at tick 0: removed component Vel",
+        render(&world, entity)
+    );
+
+    // disabling recording discards all history
+    world.set_entity_history(0);
+    assert!(world.entity_history(entity).is_empty());
+    world.add_component(entity, Vel(0., 0.));
+    assert!(world.entity_history(entity).is_empty());
+}
+
+#[test]
+fn entity_history_dropped_when_index_reused() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.set_entity_history(4);
+
+    let old = world.insert((), vec![(Pos(0., 0.),)])[0];
+    world.delete(old);
+    assert_eq!(
+        "op 0 at tick 0: inserted\nop 1 at tick 0: deleted",
+        render(&world, old)
+    );
+
+    // a new entity which reuses the index replaces the deleted entity's history
+    let new = world.insert((), vec![(Pos(1., 1.),)])[0];
+    assert_eq!(old.to_bits() as u32, new.to_bits() as u32);
+    assert!(world.entity_history(old).is_empty());
+    assert_eq!("op 2 at tick 0: inserted", render(&world, new));
+}
+
+#[test]
+fn entity_history_disabled_by_default() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    assert!(!world.records_entity_history());
+
+    let entity = world.insert((), vec![(Pos(0., 0.),)])[0];
+    world.add_component(entity, Vel(0., 0.));
+    let clone = world.clone_entity(entity, &cloner()).unwrap();
+    world.delete(entity);
+    assert!(world.entity_history(entity).is_empty());
+    assert!(world.entity_history(clone).is_empty());
+}
+
+#[test]
+fn entity_history_records_clones() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.set_entity_history(4);
+
+    let entity = world.insert((), vec![(Pos(0., 0.),)])[0];
+    let clone = world.clone_entity(entity, &cloner()).unwrap();
+    assert_eq!(
+        format!("op 1 at tick 0: cloned from {}", entity),
+        render(&world, clone)
+    );
+}
+
+#[test]
+fn entity_history_labels_command_buffer_ops_with_system_name() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.set_entity_history(4);
+    let entity = world.insert((), vec![(Pos(0., 0.),)])[0];
+
+    let system = SystemBuilder::new("accelerate")
+        .with_query(Read::<Pos>::query())
+        .build(move |cmd, _, _, _| {
+            cmd.add_component(entity, Vel(1., 0.));
+        });
+    let mut schedule = Schedule::builder().add_system(system).build();
+    schedule.execute(&mut world);
+
+    assert_eq!(
+        "op 0 at tick 0: inserted\nop 1 at tick 0 by \"accelerate\": added component Vel",
+        render(&world, entity)
+    );
+}
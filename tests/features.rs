@@ -0,0 +1,87 @@
+//! Smoke tests for each optional feature, so that running the test suite under a given feature
+//! combination exercises the APIs that combination enables.
+
+use legion::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32, f32);
+
+fn world() -> (World, Entity) {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+    (world, entity)
+}
+
+#[test]
+fn core() {
+    let (mut world, entity) = world();
+    let query = Read::<Pos>::query();
+    assert_eq!(
+        vec![Pos(1., 2., 3.)],
+        query.iter(&mut world).map(|p| *p).collect::<Vec<_>>()
+    );
+    assert!(world.delete(entity));
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn par_iter() {
+    let (mut world, _) = world();
+    let query = Write::<Pos>::query();
+    query.par_for_each(&mut world, |mut pos| pos.0 = 5.);
+    assert_eq!(5., query.iter(&mut world).next().unwrap().0);
+}
+
+#[test]
+#[cfg(feature = "par-schedule")]
+fn par_schedule() {
+    let (mut world, _) = world();
+    let system = SystemBuilder::new("system")
+        .with_query(Write::<Pos>::query())
+        .build(|_, world, _, query| {
+            for mut pos in query.iter(world) {
+                pos.1 = 5.;
+            }
+        });
+
+    let mut schedule = Schedule::builder().add_system(system).build();
+    schedule.execute(&mut world);
+    assert_eq!(5., Read::<Pos>::query().iter(&mut world).next().unwrap().1);
+}
+
+#[test]
+#[cfg(feature = "events")]
+fn events() {
+    let (mut world, _) = world();
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    world.subscribe(sender, component::<Pos>());
+    world.insert((), vec![(Pos(4., 5., 6.),)]);
+    assert!(receiver
+        .try_iter()
+        .any(|e| matches!(e, Event::EntityInserted(..))));
+}
+
+#[test]
+#[cfg(feature = "ffi")]
+fn ffi() {
+    use legion::c_api::*;
+
+    let (mut world, entity) = world();
+    let handle = lgn_world_get_rust_component(
+        (&mut world).into(),
+        lgn_rust_component_id::<Pos>(),
+        entity.into(),
+    );
+    let ptr = lgn_component_ptr(handle);
+    assert_eq!(Pos(1., 2., 3.), unsafe { *(ptr as *const Pos) });
+    lgn_component_release(handle);
+}
+
+#[test]
+#[cfg(feature = "serde-1")]
+fn serde() {
+    let (_, entity) = world();
+    let json = serde_json::to_string(&entity).unwrap();
+    assert_eq!(entity, serde_json::from_str::<Entity>(&json).unwrap());
+}
@@ -0,0 +1,183 @@
+use legion::event::Event;
+use legion::prelude::*;
+use legion::storage::ArchetypeId;
+use legion::world::WorldOptions;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+
+fn thresholds(receiver: &crossbeam_channel::Receiver<Event>) -> Vec<(ArchetypeId, usize)> {
+    receiver
+        .try_iter()
+        .filter_map(|event| match event {
+            Event::EntityThresholdExceeded(archetype, count) => Some((archetype, count)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn memory_stats_track_high_water_marks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let entities = world
+        .insert((), (0..5000).map(|i| (Pos(i as f32),)))
+        .to_vec();
+    world.insert((), vec![(Pos(0.), Vel(0.))]);
+
+    let stats = world.memory_stats();
+    assert_eq!(5001, stats.entities);
+    assert_eq!(2, stats.archetypes.len());
+    let pos = stats.archetypes[0];
+    assert_eq!(5000, pos.entities);
+    assert_eq!(5000, pos.peak_entities);
+    assert!(pos.chunks > 1);
+    assert_eq!(pos.chunks, pos.peak_chunks);
+    assert_eq!(pos.chunks + 1, stats.chunks);
+    assert_eq!(
+        stats.allocated_bytes,
+        stats
+            .archetypes
+            .iter()
+            .map(|a| a.allocated_bytes)
+            .sum::<usize>()
+    );
+    let peak_chunks = pos.chunks;
+
+    // deleting entities lowers the counts but keeps the peaks
+    for entity in &entities[1000..] {
+        world.delete(*entity);
+    }
+    let pos = world.memory_stats().archetypes[0];
+    assert_eq!(1000, pos.entities);
+    assert_eq!(5000, pos.peak_entities);
+    assert!(pos.chunks < peak_chunks);
+    assert_eq!(peak_chunks, pos.peak_chunks);
+
+    // moving entities between archetypes counts towards both
+    world.add_component(entities[0], Vel(1.));
+    let stats = world.memory_stats();
+    assert_eq!(999, stats.archetypes[0].entities);
+    assert_eq!(2, stats.archetypes[1].entities);
+    assert_eq!(2, stats.archetypes[1].peak_entities);
+
+    // resetting lowers the peaks to the current counts
+    world.reset_high_water_marks();
+    let pos = world.memory_stats().archetypes[0];
+    assert_eq!(999, pos.peak_entities);
+    assert_eq!(pos.chunks, pos.peak_chunks);
+
+    world.insert((), (0..10).map(|i| (Pos(i as f32),)));
+    assert_eq!(1009, world.memory_stats().archetypes[0].peak_entities);
+
+    world.delete_all(&any());
+    let stats = world.memory_stats();
+    assert_eq!(0, stats.entities);
+    assert_eq!(0, stats.chunks);
+    assert_eq!(0, stats.allocated_bytes);
+    assert_eq!(1009, stats.archetypes[0].peak_entities);
+}
+
+#[test]
+fn memory_stats_after_merge() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut a = universe.create_world();
+    let mut b = universe.create_world();
+    a.insert((), (0..10).map(|i| (Pos(i as f32),)));
+    b.insert((), (0..20).map(|i| (Pos(i as f32),)));
+    b.insert((), (0..3).map(|i| (Vel(i as f32),)));
+
+    a.merge(b);
+    let stats = a.memory_stats();
+    assert_eq!(33, stats.entities);
+    assert_eq!(30, stats.archetypes[0].entities);
+    assert_eq!(30, stats.archetypes[0].peak_entities);
+    assert_eq!(3, stats.archetypes[1].entities);
+}
+
+#[test]
+fn entity_threshold_fires_once_per_crossing() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let options = WorldOptions::default().with_entity_threshold(Some(100));
+    let mut world = Universe::new().create_world_with_options(options);
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    world.subscribe(sender, any());
+
+    let entities = world
+        .insert((), (0..100).map(|i| (Pos(i as f32),)))
+        .to_vec();
+    assert!(thresholds(&receiver).is_empty());
+    assert_eq!(
+        Some(100),
+        world.memory_stats().archetypes[0].entity_threshold
+    );
+
+    world.insert((), vec![(Pos(0.),), (Pos(0.),)]);
+    let id = world.memory_stats().archetypes[0].archetype;
+    assert_eq!(vec![(id, 102)], thresholds(&receiver));
+
+    // further insertions, and dropping while still above the threshold, stay silent
+    world.insert((), vec![(Pos(0.),)]);
+    for entity in &entities[..2] {
+        world.delete(*entity);
+    }
+    world.insert((), vec![(Pos(0.),)]);
+    assert!(thresholds(&receiver).is_empty());
+
+    // dropping to the threshold re-arms the event for the next crossing
+    for entity in &entities[2..5] {
+        world.delete(*entity);
+    }
+    assert_eq!(99, world.memory_stats().archetypes[0].entities);
+    world.insert((), vec![(Pos(0.),)]);
+    assert!(thresholds(&receiver).is_empty());
+    world.insert((), (0..14).map(|i| (Pos(i as f32),)));
+    assert_eq!(vec![(id, 114)], thresholds(&receiver));
+    world.insert((), vec![(Pos(0.),)]);
+    assert!(thresholds(&receiver).is_empty());
+
+    // other archetypes are tracked separately
+    world.insert((), (0..101).map(|i| (Vel(i as f32),)));
+    let vel = world.memory_stats().archetypes[1].archetype;
+    assert_eq!(vec![(vel, 101)], thresholds(&receiver));
+
+    // resetting re-arms the event
+    world.reset_high_water_marks();
+    assert!(thresholds(&receiver).is_empty());
+    world.insert((), vec![(Pos(0.),)]);
+    assert_eq!(vec![(id, 116)], thresholds(&receiver));
+}
+
+#[test]
+fn entity_threshold_moves_and_disabled() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let options = WorldOptions::default().with_entity_threshold(Some(2));
+    let mut world = Universe::new().create_world_with_options(options);
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    world.subscribe(sender, any());
+
+    let entities = world.insert((), (0..4).map(|i| (Pos(i as f32),))).to_vec();
+    assert_eq!(1, thresholds(&receiver).len());
+
+    // moving entities into another archetype crosses its threshold
+    for entity in &entities[..3] {
+        world.add_component(*entity, Vel(0.));
+    }
+    let moved = world.memory_stats().archetypes[1].archetype;
+    assert_eq!(vec![(moved, 3)], thresholds(&receiver));
+
+    // worlds without a threshold never emit the event
+    let mut world = Universe::new().create_world();
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    world.subscribe(sender, any());
+    world.insert((), (0..5000).map(|i| (Pos(i as f32),)));
+    assert!(thresholds(&receiver).is_empty());
+    assert_eq!(None, world.memory_stats().archetypes[0].entity_threshold);
+}
@@ -0,0 +1,228 @@
+//! A small test profile which exercises the unsafe storage and query core, sized so that it
+//! runs in reasonable time under miri:
+//!
+//! ```text
+//! MIRIFLAGS="-Zmiri-disable-isolation -Zmiri-strict-provenance" \
+//!     cargo +nightly miri test --lib --test miri
+//! ```
+//!
+//! Isolation is disabled because `tracing-subscriber` reads the realtime clock to timestamp the
+//! events logged by some tests.
+//!
+//! Entity counts are reduced under `cfg(miri)`, and a large component keeps chunks small so
+//! that a few dozen entities still span several chunks.
+//!
+//! Intentionally excluded from the profile:
+//!
+//! * Library tests which run on the rayon thread pool (the `schedule` and `system` tests which
+//!   execute schedules, and `world::tests::track_writes_par_for_each`) are ignored under miri.
+//!   rayon's work-stealing deque uses crossbeam-epoch, which itself violates stacked borrows,
+//!   so miri aborts inside the dependency before reaching any of legion's code.
+//! * The `c_api` tests are only built with the `ffi` feature, which the profile leaves off. The
+//!   C API is meant to be called across an FFI boundary, which miri cannot interpret.
+//! * The other integration tests, and doctests, are written for throughput with thousands of
+//!   entities and many `par_*` calls, and would take hours to interpret. The storage and query
+//!   paths they rely on are covered here instead.
+use legion::prelude::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[cfg(miri)]
+const COUNT: usize = 40;
+#[cfg(not(miri))]
+const COUNT: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32, f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32, f32, f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Big([u64; 64]);
+#[derive(Clone, Debug, PartialEq)]
+struct Name(String);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+fn world() -> (World, Vec<Entity>) {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert(
+            (Model(0),),
+            (0..COUNT).map(|i| {
+                let f = i as f32;
+                (
+                    Pos(f, f, f),
+                    Vel(1., 0., 0.),
+                    Big([i as u64; 64]),
+                    Name(i.to_string()),
+                )
+            }),
+        )
+        .to_vec();
+    (world, entities)
+}
+
+#[test]
+fn miri_insert_and_get() {
+    let (world, entities) = world();
+    assert!(Read::<Pos>::query().iter_chunks_immutable(&world).count() > 1);
+
+    for (i, entity) in entities.iter().enumerate() {
+        let f = i as f32;
+        assert_eq!(Pos(f, f, f), *world.get_component::<Pos>(*entity).unwrap());
+        assert_eq!(i as u64, world.get_component::<Big>(*entity).unwrap().0[63]);
+        assert_eq!(
+            i.to_string(),
+            world.get_component::<Name>(*entity).unwrap().0
+        );
+        assert_eq!(Some(&Model(0)), world.get_tag::<Model>(*entity));
+    }
+}
+
+#[test]
+fn miri_query_read_write() {
+    let (mut world, _) = world();
+
+    let query = <(Write<Pos>, Read<Vel>)>::query();
+    for (mut pos, vel) in query.iter(&mut world) {
+        pos.0 += vel.0;
+    }
+
+    let query = <(Read<Pos>, Write<Name>)>::query();
+    for (pos, mut name) in query.iter(&mut world) {
+        name.0.push_str(&format!("@{}", pos.0));
+    }
+
+    for (i, (pos, name)) in <(Read<Pos>, Read<Name>)>::query()
+        .iter(&mut world)
+        .enumerate()
+    {
+        assert_eq!(i as f32 + 1., pos.0);
+        assert_eq!(format!("{}@{}", i, i + 1), name.0);
+    }
+
+    let query = <(Write<Pos>, Read<Vel>)>::query();
+    let (mut full, mut remainder) = (0, 0);
+    query.for_each_batched::<8, _, _>(
+        &mut world,
+        |(pos, vel)| {
+            for (pos, vel) in pos.iter_mut().zip(vel.iter()) {
+                pos.1 += vel.0;
+            }
+            full += 8;
+        },
+        |(pos, vel)| {
+            pos.1 += vel.0;
+            remainder += 1;
+        },
+    );
+    assert_eq!(COUNT, full + remainder);
+    for pos in Read::<Pos>::query().iter(&mut world) {
+        assert_eq!(pos.0, pos.1);
+    }
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "borrow-checks"))]
+fn miri_borrow_conflicts() {
+    let (world, _) = world();
+
+    let query = Read::<Pos>::query();
+    let mut reading = unsafe { query.iter_unchecked(&world) };
+    assert!(reading.next().is_some());
+
+    // a second reader is allowed, but a writer panics
+    let readers = unsafe { Read::<Pos>::query().iter_unchecked(&world) }.count();
+    assert_eq!(COUNT, readers);
+    let conflict = catch_unwind(AssertUnwindSafe(|| {
+        unsafe { Write::<Pos>::query().iter_unchecked(&world) }.count()
+    }));
+    assert!(conflict.is_err());
+    drop(reading);
+
+    // a writer blocks every other access to the same component type
+    let query = Write::<Pos>::query();
+    let mut writing = unsafe { query.iter_unchecked(&world) };
+    assert!(writing.next().is_some());
+    let conflict = catch_unwind(AssertUnwindSafe(|| {
+        unsafe { Read::<Pos>::query().iter_unchecked(&world) }.count()
+    }));
+    assert!(conflict.is_err());
+    let unrelated = unsafe { Write::<Vel>::query().iter_unchecked(&world) }.count();
+    assert_eq!(COUNT, unrelated);
+    drop(writing);
+
+    assert_eq!(
+        COUNT,
+        unsafe { Write::<Pos>::query().iter_unchecked(&world) }.count()
+    );
+}
+
+#[test]
+fn miri_entity_iteration() {
+    let (mut world, entities) = world();
+
+    let query = Read::<Big>::query();
+    let visited = query
+        .iter_entities(&mut world)
+        .map(|(entity, big)| {
+            assert_eq!(
+                entities.iter().position(|e| *e == entity),
+                Some(big.0[0] as usize)
+            );
+            entity
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(entities, visited);
+
+    for mut chunk in query.iter_chunks(&mut world) {
+        let ids = chunk.entities();
+        for (entity, big) in chunk.iter_entities() {
+            assert!(ids.contains(&entity));
+            assert_eq!(big.0[0], big.0[63]);
+        }
+    }
+}
+
+#[test]
+fn miri_structural_changes() {
+    let (mut world, entities) = world();
+
+    // deleting swaps the last entity of a chunk into the hole
+    for entity in entities.iter().step_by(3) {
+        assert!(world.delete(*entity));
+    }
+    // moving entities between archetypes copies and drops component data
+    for entity in entities.iter().skip(1).step_by(3) {
+        world.remove_component::<Name>(*entity);
+        world.add_tag(*entity, Model(1));
+    }
+    for entity in entities.iter().skip(2).step_by(3) {
+        world.add_component(*entity, Vel(2., 0., 0.));
+    }
+
+    for (i, entity) in entities.iter().enumerate() {
+        let big = world.get_component::<Big>(*entity).map(|big| big.0[0]);
+        match i % 3 {
+            0 => assert!(!world.is_alive(*entity)),
+            1 => {
+                assert_eq!(Some(i as u64), big);
+                assert!(world.get_component::<Name>(*entity).is_none());
+                assert_eq!(Some(&Model(1)), world.get_tag::<Model>(*entity));
+            }
+            _ => {
+                assert_eq!(Some(i as u64), big);
+                assert_eq!(
+                    i.to_string(),
+                    world.get_component::<Name>(*entity).unwrap().0
+                );
+                assert_eq!(2., world.get_component::<Vel>(*entity).unwrap().0);
+            }
+        }
+    }
+
+    let names = Read::<Name>::query().iter(&mut world).count();
+    assert_eq!(entities.len() - (COUNT + 2) / 3 - (COUNT + 1) / 3, names);
+    assert_eq!(entities.len() - (COUNT + 2) / 3, world.delete_all(&any()));
+    assert_eq!(0, Read::<Pos>::query().iter(&mut world).count());
+}
@@ -0,0 +1,170 @@
+use legion::mirror::{ComponentMirror, MirrorTarget};
+use legion::pod::Pod;
+use legion::prelude::*;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+struct Instance(u32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+unsafe impl Pod for Instance {}
+
+#[derive(Default)]
+struct MockTarget {
+    data: Vec<u8>,
+    writes: Vec<Range<usize>>,
+    dirty: Vec<Range<usize>>,
+}
+
+impl MockTarget {
+    fn instance(&self, offset: usize) -> u32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.data[offset..offset + 4]);
+        u32::from_ne_bytes(bytes)
+    }
+}
+
+impl MirrorTarget for MockTarget {
+    fn ensure_capacity(&mut self, bytes: usize) {
+        if self.data.len() < bytes {
+            self.data.resize(bytes, 0);
+        }
+    }
+
+    fn write(&mut self, offset: usize, bytes: &[u8]) {
+        self.data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        self.writes.push(offset..offset + bytes.len());
+    }
+
+    fn mark_range_dirty(&mut self, range: Range<usize>) { self.dirty.push(range); }
+}
+
+fn take_writes(mirror: &mut ComponentMirror<Instance, MockTarget>) -> Vec<Range<usize>> {
+    let target = mirror.target_mut();
+    assert_eq!(target.writes, target.dirty);
+    target.dirty.clear();
+    std::mem::replace(&mut target.writes, Vec::new())
+}
+
+// Checks that the mirrored components of every chunk match the world.
+fn assert_mirrored(world: &World, mirror: &ComponentMirror<Instance, MockTarget>) {
+    let query = Read::<Instance>::query();
+    let mut expected = query.iter_immutable(world).map(|i| i.0).collect::<Vec<_>>();
+    let mut mirrored = Vec::new();
+    for (_, chunk) in mirror.chunks() {
+        assert!(chunk.offset + chunk.len * 4 <= chunk.offset + chunk.capacity);
+        assert!(chunk.offset + chunk.capacity <= mirror.len());
+        for i in 0..chunk.len {
+            mirrored.push(mirror.target().instance(chunk.offset + i * 4));
+        }
+    }
+    expected.sort();
+    mirrored.sort();
+    assert_eq!(expected, mirrored);
+}
+
+#[test]
+fn mirror_writes_changed_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    world.insert((Model(0),), (0..10).map(|i| (Instance(i),)));
+    world.insert((Model(1),), (10..15).map(|i| (Instance(i),)));
+    world.insert((), vec![(Vel(0.),)]);
+
+    let query = Read::<Instance>::query();
+    let mut mirror = ComponentMirror::new(MockTarget::default());
+    mirror.update(&world, &query);
+    assert_eq!(2, mirror.chunks().count());
+    assert_eq!(2, take_writes(&mut mirror).len());
+    assert_mirrored(&world, &mirror);
+
+    // unchanged chunks are not written again
+    mirror.update(&world, &query);
+    assert!(take_writes(&mut mirror).is_empty());
+
+    // only the modified chunk is written
+    let modify = Write::<Instance>::query().filter(tag_value(&Model(1)));
+    for mut instance in modify.iter(&mut world) {
+        instance.0 += 100;
+    }
+    mirror.update(&world, &query);
+    let writes = take_writes(&mut mirror);
+    assert_eq!(1, writes.len());
+    assert_eq!(5 * 4, writes[0].len());
+    assert_mirrored(&world, &mirror);
+
+    // reading components does not cause writes
+    for _ in <(Tagged<Model>, Read<Instance>)>::query().iter(&mut world) {}
+    mirror.update(&world, &query);
+    assert!(take_writes(&mut mirror).is_empty());
+}
+
+#[test]
+fn mirror_offsets_survive_chunk_churn() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let first = world
+        .insert((Model(0),), (0..4).map(|i| (Instance(i),)))
+        .to_vec();
+    world.insert((Model(1),), (4..8).map(|i| (Instance(i),)));
+
+    let query = Read::<Instance>::query();
+    let mut mirror = ComponentMirror::new(MockTarget::default());
+    mirror.update(&world, &query);
+    let offsets = mirror.chunks().collect::<Vec<_>>();
+    let len = mirror.len();
+    take_writes(&mut mirror);
+
+    // adding entities to an existing chunk keeps its region
+    let added = world.insert((Model(1),), vec![(Instance(8),)])[0];
+    mirror.update(&world, &query);
+    assert_eq!(1, take_writes(&mut mirror).len());
+    for (id, chunk) in &offsets {
+        assert_eq!(chunk.offset, mirror.chunk(*id).unwrap().offset);
+    }
+    assert_mirrored(&world, &mirror);
+
+    // removing entities shrinks the region's length
+    world.delete(added);
+    world.delete(first[3]);
+    mirror.update(&world, &query);
+    assert_eq!(2, take_writes(&mut mirror).len());
+    assert_mirrored(&world, &mirror);
+
+    // emptied chunks are released, and their regions reused by new chunks
+    for entity in &first[..3] {
+        world.delete(*entity);
+    }
+    mirror.update(&world, &query);
+    assert_eq!(1, mirror.chunks().count());
+    assert_mirrored(&world, &mirror);
+
+    world.insert((Model(2),), (20..22).map(|i| (Instance(i),)));
+    mirror.update(&world, &query);
+    assert_eq!(2, mirror.chunks().count());
+    assert_eq!(len, mirror.len());
+    assert_mirrored(&world, &mirror);
+    let mut offsets = mirror
+        .chunks()
+        .map(|(_, chunk)| chunk.offset)
+        .collect::<Vec<_>>();
+    offsets.sort();
+    offsets.dedup();
+    assert_eq!(2, offsets.len());
+}
+
+#[test]
+#[should_panic(expected = "not readable")]
+fn mirror_requires_readable_component() {
+    let mut world = Universe::new().create_world();
+    world.insert((), vec![(Instance(0), Vel(0.))]);
+
+    let mut mirror = ComponentMirror::<Instance, _>::new(MockTarget::default());
+    mirror.update(&world, &Read::<Vel>::query());
+}
@@ -0,0 +1,111 @@
+use legion::prelude::*;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MatchId(u32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Model(u32);
+
+#[test]
+fn partitions_are_isolated() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let outside = world.insert((), vec![(Pos(0.), Vel(0.))])[0];
+    let other_tags = world.insert((Model(1),), vec![(Pos(0.), Vel(0.))])[0];
+
+    let first = world
+        .partition(MatchId(1))
+        .insert((0..3).map(|i| (Pos(i as f32), Vel(1.))))
+        .to_vec();
+    let second = world
+        .partition(MatchId(2))
+        .insert((0..5).map(|i| (Pos(i as f32), Vel(2.))))
+        .to_vec();
+
+    // inserts attach the partition's tag
+    for entity in &first {
+        assert_eq!(Some(&MatchId(1)), world.get_tag::<MatchId>(*entity));
+    }
+    for entity in &second {
+        assert_eq!(Some(&MatchId(2)), world.get_tag::<MatchId>(*entity));
+    }
+
+    let query = <(Write<Pos>, Read<Vel>)>::query();
+    for (value, entities) in vec![(1, &first), (2, &second)] {
+        let mut partition = world.partition(MatchId(value));
+        assert_eq!(entities.len(), partition.len());
+        assert_eq!(entities.len(), partition.query(&query).count());
+        assert_eq!(
+            entities.iter().copied().collect::<HashSet<_>>(),
+            partition.iter_entities().collect::<HashSet<_>>()
+        );
+        assert_eq!(
+            entities.iter().copied().collect::<HashSet<_>>(),
+            partition
+                .query(&query)
+                .iter_entities()
+                .map(|(e, _)| e)
+                .collect::<HashSet<_>>()
+        );
+        assert!(!partition.contains(outside));
+        assert!(!partition.contains(other_tags));
+
+        partition
+            .query(&query)
+            .for_each(|(mut pos, vel)| pos.0 += vel.0 * 10.);
+    }
+
+    // writes through a partition only touch its own entities
+    for (i, entity) in first.iter().enumerate() {
+        assert_eq!(
+            Pos(i as f32 + 10.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+    }
+    for (i, entity) in second.iter().enumerate() {
+        assert_eq!(
+            Pos(i as f32 + 20.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+    }
+    assert_eq!(Pos(0.), *world.get_component::<Pos>(outside).unwrap());
+    assert_eq!(Pos(0.), *world.get_component::<Pos>(other_tags).unwrap());
+
+    // a partition without entities matches nothing
+    let mut empty = world.partition(MatchId(3));
+    assert!(empty.is_empty());
+    assert_eq!(0, empty.query(&query).iter().count());
+    assert_eq!(0, empty.iter_entities().count());
+}
+
+#[test]
+fn partition_keeps_query_state() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let first = world.partition(MatchId(1)).insert(vec![(Pos(1.),)])[0];
+    world.partition(MatchId(2)).insert(vec![(Pos(2.),)]);
+
+    let query = Read::<Pos>::query().filter(changed::<Pos>());
+    assert_eq!(1, world.partition(MatchId(1)).query(&query).iter().count());
+    assert_eq!(0, world.partition(MatchId(1)).query(&query).iter().count());
+
+    // the query's own filter state is shared by every partition it runs through
+    assert_eq!(1, world.partition(MatchId(2)).query(&query).iter().count());
+    assert_eq!(0, world.partition(MatchId(2)).query(&query).iter().count());
+
+    *world.get_component_mut::<Pos>(first).unwrap() = Pos(5.);
+    assert_eq!(0, world.partition(MatchId(2)).query(&query).iter().count());
+    let mut partition = world.partition(MatchId(1));
+    let changed = partition
+        .query(&query)
+        .iter()
+        .map(|pos| *pos)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![Pos(5.)], changed);
+}
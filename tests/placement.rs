@@ -0,0 +1,190 @@
+#![cfg(feature = "par-iter")]
+
+use legion::placement::PlacementPool;
+use legion::prelude::*;
+use legion::world::WorldOptions;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+
+fn thread_group() -> Option<usize> {
+    let thread = std::thread::current();
+    let name = thread.name()?.strip_prefix("legion-placement-")?;
+    name.split('-').next()?.parse().ok()
+}
+
+#[test]
+fn chunks_are_placed_in_turn() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let options = WorldOptions::default().with_placement_groups(3);
+    let mut world = Universe::new().create_world_with_options(options);
+    world.insert((), (0..10000).map(|i| (Pos(i as f32),)));
+    world.insert((), (0..5000).map(|i| (Pos(i as f32), Vel(0.))));
+
+    let placements = |world: &mut World| {
+        Read::<Pos>::query()
+            .iter_chunks(world)
+            .map(|chunk| (chunk.id().archetype_id(), chunk.placement()))
+            .collect::<Vec<_>>()
+    };
+
+    // each archetype cycles through the groups from the first
+    let stats = world.memory_stats();
+    let (pos, pos_vel) = (stats.archetypes[0].archetype, stats.archetypes[1].archetype);
+    let all = placements(&mut world);
+    for archetype in &[pos, pos_vel] {
+        let chunks = all.iter().filter(|(a, _)| a == archetype).map(|(_, p)| *p);
+        assert!(chunks.enumerate().all(|(i, p)| p as usize == i % 3));
+    }
+    assert!(all.iter().filter(|(a, _)| *a == pos).count() > 3);
+
+    // pinned archetypes place new chunks in their group
+    assert!(world.set_archetype_placement(pos_vel, Some(2)));
+    world.insert((), (0..5000).map(|i| (Pos(i as f32), Vel(0.))));
+    let pinned = placements(&mut world)
+        .into_iter()
+        .filter(|(a, _)| *a == pos_vel)
+        .map(|(_, p)| p)
+        .collect::<Vec<_>>();
+    assert!(pinned.len() > all.iter().filter(|(a, _)| *a == pos_vel).count());
+    let existing = all.iter().filter(|(a, _)| *a == pos_vel).count();
+    assert!(pinned[existing..].iter().all(|p| *p == 2));
+
+    // archetypes of other worlds are not found
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let mut other = universe.create_world();
+    other.insert((), vec![(Pos(0.),)]);
+    let foreign = other.memory_stats().archetypes[0].archetype;
+    assert!(!world.set_archetype_placement(foreign, None));
+
+    // worlds default to a single group
+    world.insert((), (0..10000).map(|i| (Pos(i as f32),)));
+    assert!(placements(&mut world).iter().all(|(_, p)| *p == 0));
+}
+
+#[test]
+fn chunks_are_processed_by_their_group() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let options = WorldOptions::default().with_placement_groups(4);
+    let mut world = Universe::new().create_world_with_options(options);
+    world.insert((), (0..20000).map(|i| (Pos(i as f32),)));
+
+    let pool = PlacementPool::new(2, 2).unwrap();
+    assert_eq!(2, pool.groups());
+    assert_eq!(1, pool.group_of(3));
+
+    let visited = Mutex::new(Vec::new());
+    let query = Write::<Pos>::query();
+    query.par_for_each_chunk_placed(&mut world, &pool, |mut chunk| {
+        for mut pos in chunk.iter() {
+            pos.0 += 1.;
+        }
+        visited
+            .lock()
+            .unwrap()
+            .push((chunk.id(), chunk.placement(), thread_group()));
+    });
+
+    let visited = visited.into_inner().unwrap();
+    assert_eq!(query.chunk_count(&world), visited.len());
+    for (_, placement, group) in &visited {
+        assert_eq!(Some(*placement as usize % 2), *group);
+    }
+    assert!(visited.iter().any(|(_, p, _)| *p == 3));
+    let mut ids = visited.iter().map(|(id, _, _)| *id).collect::<Vec<_>>();
+    ids.sort_by_key(|id| format!("{:?}", id));
+    ids.dedup();
+    assert_eq!(visited.len(), ids.len());
+
+    let sum = Read::<Pos>::query()
+        .iter(&mut world)
+        .map(|pos| pos.0)
+        .sum::<f32>();
+    assert_eq!((1..=20000).map(|i| i as f32).sum::<f32>(), sum);
+}
+
+#[test]
+fn entities_of_pinned_archetypes_are_processed_by_their_group() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let options = WorldOptions::default().with_placement_groups(2);
+    let mut world = Universe::new().create_world_with_options(options);
+    world.insert((), vec![(Pos(0.), Vel(0.))]);
+    world.delete_all(&any());
+
+    // the emptied chunk is moved along with new chunks
+    let archetype = world.memory_stats().archetypes[0].archetype;
+    world.set_archetype_placement(archetype, Some(1));
+    world.insert((), (0..5000).map(|i| (Pos(i as f32), Vel(0.))));
+
+    let pool = PlacementPool::new(2, 1).unwrap();
+    let groups = Mutex::new(Vec::new());
+    <(Read<Pos>, Write<Vel>)>::query().par_for_each_placed(&mut world, &pool, |(pos, mut vel)| {
+        vel.0 = pos.0;
+        groups.lock().unwrap().push(thread_group());
+    });
+
+    let groups = groups.into_inner().unwrap();
+    assert_eq!(5000, groups.len());
+    assert!(groups.iter().all(|group| *group == Some(1)));
+    assert!(Read::<Vel>::query()
+        .iter(&mut world)
+        .enumerate()
+        .all(|(i, vel)| vel.0 == i as f32));
+}
+
+#[cfg(feature = "numa")]
+#[test]
+fn chunk_allocator_receives_placements() {
+    use legion::storage::ChunkAllocator;
+    use std::alloc::Layout;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Counting {
+        allocs: Mutex<Vec<u8>>,
+        deallocs: Mutex<Vec<u8>>,
+    }
+
+    unsafe impl ChunkAllocator for Counting {
+        unsafe fn alloc(&self, layout: Layout, placement: u8) -> *mut u8 {
+            self.allocs.lock().unwrap().push(placement);
+            std::alloc::alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout, placement: u8) {
+            self.deallocs.lock().unwrap().push(placement);
+            std::alloc::dealloc(ptr, layout)
+        }
+    }
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let allocator = Arc::new(Counting::default());
+    let options = WorldOptions::default().with_placement_groups(2);
+    let mut world = Universe::new().create_world_with_options(options);
+    world.set_chunk_allocator(allocator.clone());
+    let entities = world
+        .insert((), (0..10000).map(|i| (Pos(i as f32),)))
+        .to_vec();
+    let chunks = Read::<Pos>::query().chunk_count(&world);
+    let expected = (0..chunks).map(|i| (i % 2) as u8).collect::<Vec<_>>();
+    assert_eq!(expected, *allocator.allocs.lock().unwrap());
+
+    // emptied chunks are returned to the allocator, along with the rest when the world drops
+    for entity in entities {
+        world.delete(entity);
+    }
+    drop(world);
+    let mut allocs = allocator.allocs.lock().unwrap().clone();
+    let mut deallocs = allocator.deallocs.lock().unwrap().clone();
+    allocs.sort_unstable();
+    deallocs.sort_unstable();
+    assert_eq!(allocs, deallocs);
+}
@@ -0,0 +1,211 @@
+use legion::pod::{ChunkFormatError, Pod, PodRegistry};
+use legion::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+struct Pos(f32, f32, f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+struct Vel(f32, f32, f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Id(u64);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Flag(u8);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+unsafe impl Pod for Pos {}
+unsafe impl Pod for Vel {}
+unsafe impl Pod for Id {}
+unsafe impl Pod for Flag {}
+
+fn registry() -> PodRegistry {
+    let mut registry = PodRegistry::new();
+    registry
+        .register::<Pos>("pos")
+        .register::<Vel>("vel")
+        .register::<Id>("id")
+        .register::<Flag>("flag");
+    registry
+}
+
+fn populated(universe: &Universe) -> World {
+    let mut world = universe.create_world();
+    world.insert(
+        (),
+        (0..3000).map(|i| (Pos(i as f32, 0., 0.), Vel(0., i as f32, 0.))),
+    );
+    world.insert((), (0..1000).map(|i| (Pos(i as f32, 1., 0.),)));
+    world.insert((), (0..500).map(|i| (Flag(i as u8), Id(i))));
+    world
+}
+
+type Row = (Option<Pos>, Option<Vel>, Option<Id>, Option<Flag>);
+
+fn row(world: &World, entity: Entity) -> Row {
+    (
+        world.get_component::<Pos>(entity).map(|c| *c),
+        world.get_component::<Vel>(entity).map(|c| *c),
+        world.get_component::<Id>(entity).map(|c| *c),
+        world.get_component::<Flag>(entity).map(|c| *c),
+    )
+}
+
+#[test]
+fn pod_chunks_round_trip() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = populated(&universe);
+    let registry = registry();
+    let bytes = world.write_chunks_to_bytes(&registry).unwrap();
+
+    // entities are loaded in the order in which the source world stores them
+    let mut source = Vec::new();
+    for query_entities in vec![
+        Read::<Pos>::query()
+            .iter_entities(&mut world)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>(),
+        Read::<Id>::query()
+            .iter_entities(&mut world)
+            .map(|(e, _)| e)
+            .collect(),
+    ] {
+        source.extend(query_entities);
+    }
+
+    let mut loaded = universe.create_world();
+    let entities = loaded.load_chunks_from_bytes(&bytes, &registry).unwrap();
+    assert_eq!(4500, entities.len());
+    assert_eq!(
+        source.iter().map(|e| row(&world, *e)).collect::<Vec<_>>(),
+        entities
+            .iter()
+            .map(|e| row(&loaded, *e))
+            .collect::<Vec<_>>()
+    );
+
+    // the loaded world writes an identical buffer
+    assert_eq!(bytes, loaded.write_chunks_to_bytes(&registry).unwrap());
+
+    // loading again appends to the existing archetypes
+    let again = loaded.load_chunks_from_bytes(&bytes, &registry).unwrap();
+    assert_eq!(4500, again.len());
+    assert_eq!(8000, Read::<Pos>::query().iter(&mut loaded).count());
+    assert_eq!(3, loaded.archetype_generation());
+    assert_eq!(row(&loaded, entities[10]), row(&loaded, again[10]));
+}
+
+#[test]
+fn pod_chunks_reject_invalid_buffers() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let world = populated(&universe);
+    let registry = registry();
+    let bytes = world.write_chunks_to_bytes(&registry).unwrap();
+    let mut loaded = universe.create_world();
+
+    let load = |loaded: &mut World, bytes: &[u8], registry: &PodRegistry| {
+        loaded
+            .load_chunks_from_bytes(bytes, registry)
+            .map(|e| e.len())
+    };
+
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(
+        Err(ChunkFormatError::BadMagic),
+        load(&mut loaded, &magic, &registry)
+    );
+
+    let mut version = bytes.clone();
+    version[8] = 2;
+    assert_eq!(
+        Err(ChunkFormatError::UnsupportedVersion(2)),
+        load(&mut loaded, &version, &registry)
+    );
+
+    let mut order = bytes.clone();
+    order[12] ^= 1;
+    assert_eq!(
+        Err(ChunkFormatError::ByteOrderMismatch),
+        load(&mut loaded, &order, &registry)
+    );
+
+    assert_eq!(
+        Err(ChunkFormatError::Truncated),
+        load(&mut loaded, &bytes[..bytes.len() - 1], &registry)
+    );
+
+    let mut partial = PodRegistry::new();
+    partial.register::<Pos>("pos");
+    assert_eq!(
+        Err(ChunkFormatError::UnknownComponent("vel".to_owned())),
+        load(&mut loaded, &bytes, &partial)
+    );
+
+    let mut fingerprinted = PodRegistry::new();
+    fingerprinted
+        .register_with_fingerprint::<Pos>("pos", 7)
+        .register::<Vel>("vel")
+        .register::<Id>("id")
+        .register::<Flag>("flag");
+    match load(&mut loaded, &bytes, &fingerprinted) {
+        Err(ChunkFormatError::LayoutMismatch {
+            name,
+            expected,
+            found,
+        }) => {
+            assert_eq!("pos", name);
+            assert_eq!(7, expected.fingerprint);
+            assert_eq!(0, found.fingerprint);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // a type listed twice is rejected, even if no chunk refers to it twice
+    let mut duplicate = bytes.clone();
+    let name = duplicate.windows(3).position(|w| w == b"vel").unwrap();
+    duplicate[name..name + 3].copy_from_slice(b"pos");
+    assert_eq!(
+        Err(ChunkFormatError::DuplicateComponent("pos".to_owned())),
+        load(&mut loaded, &duplicate, &registry)
+    );
+
+    // nothing is inserted from a buffer which fails validation part way through
+    assert_eq!(0, Read::<Pos>::query().iter(&mut loaded).count());
+    assert_eq!(0, loaded.archetype_generation());
+}
+
+#[test]
+fn pod_chunks_write_errors() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = populated(&universe);
+
+    let mut partial = PodRegistry::new();
+    partial.register::<Pos>("pos");
+    match world.write_chunks_to_bytes(&partial) {
+        Err(ChunkFormatError::UnregisteredComponent(name)) => assert!(name.ends_with("Vel")),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    world.insert((Model(0),), vec![(Pos(0., 0., 0.),)]);
+    match world.write_chunks_to_bytes(&registry()) {
+        Err(ChunkFormatError::UnsupportedTag(name)) => assert!(name.ends_with("Model")),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // empty archetypes are skipped
+    let entity = Read::<Pos>::query()
+        .filter(tag::<Model>())
+        .iter_entities(&mut world)
+        .next()
+        .unwrap()
+        .0;
+    world.delete(entity);
+    assert!(world.write_chunks_to_bytes(&registry()).is_ok());
+}
@@ -1,7 +1,10 @@
 #![allow(clippy::map_clone)]
 
+use legion::borrow::Ref;
 use legion::prelude::*;
-use std::collections::HashMap;
+use legion::query::{Chunk, ChunkBoundary, QueryRef, SingleError, View};
+use std::collections::{HashMap, HashSet};
+use std::iter::FusedIterator;
 
 #[cfg(feature = "par-iter")]
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -74,6 +77,37 @@ fn query_try_read_entity_data() {
     );
 }
 
+#[test]
+fn query_try_read_in_tuple() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), Some((Pos(1., 2., 3.),)));
+    world.insert((), Some((Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6))));
+    world.insert((), Some((Rot(0.7, 0.8, 0.9),)));
+
+    assert!(<(Read<Pos>, TryRead<Rot>) as View>::reads::<Rot>());
+    assert!(!<(Read<Pos>, TryRead<Rot>) as View>::writes::<Rot>());
+
+    let query = <(Read<Pos>, TryRead<Rot>)>::query();
+    let mut results = query
+        .iter(&mut world)
+        .map(|(pos, rot)| (pos.0, rot.map(|r| r.0)))
+        .collect::<Vec<_>>();
+    results.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    assert_eq!(vec![(1., None), (4., Some(0.4))], results);
+
+    let mut missing = 0;
+    for chunk in query.iter_chunks(&mut world) {
+        match chunk.components::<Rot>() {
+            Some(rots) => assert_eq!(chunk.entities().len(), rots.len()),
+            None => missing += 1,
+        }
+    }
+    assert_eq!(1, missing);
+}
+
 #[test]
 fn query_try_write_entity_data() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -93,6 +127,56 @@ fn query_try_write_entity_data() {
     );
 }
 
+#[test]
+fn query_try_write_mixed_archetypes() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let without = world
+        .insert((), vec![(Pos(1., 0., 0.),), (Pos(2., 0., 0.),)])
+        .to_vec();
+    let with = world
+        .insert(
+            (),
+            vec![
+                (Pos(3., 0., 0.), Rot(0., 0., 0.)),
+                (Pos(4., 0., 0.), Rot(0., 0., 0.)),
+            ],
+        )
+        .to_vec();
+
+    assert!(<(Read<Pos>, TryWrite<Rot>) as View>::writes::<Rot>());
+
+    let query = <(Read<Pos>, TryWrite<Rot>)>::query();
+    let mut visited = 0;
+    for (pos, rot) in query.iter(&mut world) {
+        visited += 1;
+        if let Some(mut rot) = rot {
+            rot.0 = pos.0 * 10.;
+        }
+    }
+    assert_eq!(4, visited);
+
+    assert_eq!(
+        Some(Rot(30., 0., 0.)),
+        world.get_component::<Rot>(with[0]).map(|x| *x)
+    );
+    assert_eq!(
+        Some(Rot(40., 0., 0.)),
+        world.get_component::<Rot>(with[1]).map(|x| *x)
+    );
+    for entity in without {
+        assert!(world.get_component::<Rot>(entity).is_none());
+        assert!(world.get_component::<Pos>(entity).is_some());
+    }
+
+    // only the chunk containing `Rot` has been written
+    let changed = Read::<Pos>::query().filter(changed::<Rot>());
+    assert_eq!(2, changed.iter(&mut world).count());
+    assert_eq!(0, changed.iter(&mut world).count());
+}
+
 #[test]
 fn query_cached_read_entity_data() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -159,6 +243,219 @@ fn query_read_entity_data_par() {
     assert_eq!(components.len(), count.load(Ordering::SeqCst));
 }
 
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_write_entity_data_par_foreach_chunk() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for model in 0..4 {
+        world.insert(
+            (Model(model),),
+            (0..1000).map(|i| (Pos(i as f32, 0., 0.), Vel(1., 2., 3.))),
+        );
+    }
+
+    let changed = <(Tagged<Model>, Changed<Pos>)>::query();
+    let changed_models = |world: &mut World| {
+        let mut models = changed
+            .iter(world)
+            .filter(|(_, changed)| *changed)
+            .map(|(model, _)| model.0)
+            .collect::<Vec<_>>();
+        models.sort();
+        models.dedup();
+        models
+    };
+    assert_eq!(vec![0, 1, 2, 3], changed_models(&mut world));
+
+    // writes through the chunk's view and its component slices
+    let query =
+        <(Write<Pos>, Read<Vel>)>::query().filter(tag_value(&Model(1)) | tag_value(&Model(2)));
+    query.par_for_each_chunk(&mut world, |mut chunk| {
+        for (mut pos, vel) in chunk.iter() {
+            pos.1 += vel.1;
+        }
+        for pos in chunk.components_mut::<Pos>().unwrap().iter_mut() {
+            pos.2 += 1.;
+        }
+    });
+
+    // only the written chunks' versions were bumped
+    assert_eq!(vec![1, 2], changed_models(&mut world));
+    assert!(changed_models(&mut world).is_empty());
+
+    let query = <(Read<Pos>, Tagged<Model>)>::query();
+    for (pos, model) in query.iter(&mut world) {
+        let written = model.0 == 1 || model.0 == 2;
+        assert_eq!(if written { (2., 1.) } else { (0., 0.) }, (pos.1, pos.2));
+    }
+
+    // `par_for_each` bumps versions in the same way
+    let query = Write::<Pos>::query().filter(tag_value(&Model(3)));
+    query.par_for_each(&mut world, |mut pos| pos.2 = 5.);
+    assert_eq!(vec![3], changed_models(&mut world));
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_iter_chunks_indexed_zip() {
+    use rayon::prelude::*;
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for model in 0..3 {
+        world.insert((Model(model),), (0..1500).map(|i| (Pos(i as f32, 0., 0.),)));
+    }
+
+    let query = Read::<Pos>::query().filter(tag_value(&Model(0)) | tag_value(&Model(2)));
+    let sequential = query
+        .iter_chunks_immutable(&world)
+        .map(|chunk| (chunk.id(), chunk.len()))
+        .collect::<Vec<_>>();
+    assert!(sequential.len() > 2);
+
+    let chunks = query.par_iter_chunks_indexed(&mut world);
+    assert_eq!(sequential.len(), chunks.len());
+
+    // write a summary of each chunk into its slot of a pre-allocated buffer
+    let mut summaries = vec![(None, 0, 0.); sequential.len()];
+    chunks
+        .zip(summaries.par_iter_mut())
+        .for_each(|(mut chunk, summary)| {
+            let sum = chunk.iter().map(|pos| pos.0).sum::<f32>();
+            *summary = (Some(chunk.id()), chunk.len(), sum);
+        });
+
+    for ((id, len), (summary_id, summary_len, sum)) in sequential.iter().zip(summaries.iter()) {
+        assert_eq!(Some(*id), *summary_id);
+        assert_eq!(len, summary_len);
+        assert!(*sum > 0.);
+    }
+    let total = summaries.iter().map(|(_, len, _)| len).sum::<usize>();
+    assert_eq!(3000, total);
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_match_many_archetypes() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Marker<const N: usize>;
+
+    // add a subset of ten marker components to each entity, giving up to 1024 archetypes
+    fn insert(world: &mut World, bits: std::ops::Range<usize>) {
+        for bits in bits {
+            let entity = world.insert((), vec![(Pos(bits as f32, 0., 0.),)])[0];
+            macro_rules! mark {
+                ($($n:literal),*) => {
+                    $(if bits & (1 << $n) != 0 {
+                        world.add_component(entity, Marker::<$n>);
+                    })*
+                };
+            }
+            mark!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9);
+        }
+    }
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    insert(&mut world, 0..600);
+
+    let sequential = |world: &World| {
+        Read::<Pos>::query()
+            .filter(component::<Marker<3>>() | component::<Marker<8>>())
+            .iter_immutable(world)
+            .map(|pos| pos.0)
+            .collect::<Vec<_>>()
+    };
+
+    let query = Read::<Pos>::query().filter(component::<Marker<3>>() | component::<Marker<8>>());
+    let expected = sequential(&world);
+    assert!(!expected.is_empty());
+    let matched = query
+        .par_match(&world)
+        .iter_immutable(&world)
+        .map(|pos| pos.0)
+        .collect::<Vec<_>>();
+    assert_eq!(expected, matched);
+
+    // only the archetypes created since are matched again, and are appended in order
+    insert(&mut world, 600..1024);
+    let expected = sequential(&world);
+    let matched = query
+        .par_match(&world)
+        .iter_immutable(&world)
+        .map(|pos| pos.0)
+        .collect::<Vec<_>>();
+    assert_eq!(expected, matched);
+    let matched = query
+        .iter_immutable(&world)
+        .map(|pos| pos.0)
+        .collect::<Vec<_>>();
+    assert_eq!(expected, matched);
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_with_thread_pool() {
+    use rayon::prelude::*;
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pool = std::sync::Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .thread_name(|i| format!("simulation-{}", i))
+            .build()
+            .unwrap(),
+    );
+    let in_pool = || {
+        rayon::current_thread_index().is_some()
+            && std::thread::current()
+                .name()
+                .map_or(false, |name| name.starts_with("simulation-"))
+    };
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for model in 0..3 {
+        world.insert((Model(model),), (0..1500).map(|i| (Pos(i as f32, 0., 0.),)));
+    }
+
+    let query = Write::<Pos>::query().with_thread_pool(pool.clone());
+    query.par_for_each(&mut world, |mut pos| {
+        assert!(in_pool());
+        pos.1 = 1.;
+    });
+    query.par_for_each_batched(&mut world, 100, |pos| {
+        assert!(in_pool());
+        pos.2 = 1.;
+    });
+    assert!(query.par_iter_chunks(&mut world).all(|_| in_pool()));
+
+    // the pool is carried over when the query is filtered further
+    let query = Read::<Pos>::query()
+        .with_thread_pool(pool)
+        .filter(tag_value(&Model(1)));
+    let total = query.par_match(&world).par_fold_immutable(
+        &world,
+        || 0,
+        |total, pos| {
+            assert!(in_pool());
+            assert_eq!((1., 1.), (pos.1, pos.2));
+            total + 1
+        },
+        |a, b| a + b,
+    );
+    assert_eq!(1500, total);
+
+    // without a pool, the query runs on the global pool
+    Read::<Pos>::query().par_for_each_immutable(&world, |_| assert!(!in_pool()));
+}
+
 #[test]
 #[cfg(feature = "par-iter")]
 fn query_read_entity_data_par_foreach() {
@@ -190,6 +487,319 @@ fn query_read_entity_data_par_foreach() {
     assert_eq!(components.len(), count.load(Ordering::SeqCst));
 }
 
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_iter() {
+    use rayon::prelude::*;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert(
+        (Model(1),),
+        (0..5000).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((Model(2),), (0..3000).map(|i| (Pos(i as f32, 1., 0.),)));
+
+    let query = Read::<Pos>::query();
+    let sequential = query.iter(&mut world).map(|pos| pos.0 as f64).sum::<f64>();
+    let parallel = query
+        .par_iter(&mut world)
+        .map(|pos| pos.0 as f64)
+        .sum::<f64>();
+    assert_eq!(sequential, parallel);
+
+    // rayon combinators, including over tags
+    let query = <(Read<Pos>, Tagged<Model>)>::query();
+    let tagged = query
+        .par_iter_immutable(&world)
+        .filter(|(_, model)| **model == Model(2))
+        .map(|(pos, _)| pos.1)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![1.; 3000], tagged);
+
+    // writes through the parallel iterator reach every entity once
+    let query = Write::<Pos>::query();
+    query.par_iter(&mut world).for_each(|mut pos| pos.2 += 1.);
+    assert!(Read::<Pos>::query().iter(&mut world).all(|pos| pos.2 == 1.));
+    let max = Read::<Pos>::query()
+        .par_iter(&mut world)
+        .map(|pos| pos.0 as usize)
+        .reduce(|| 0, std::cmp::max);
+    assert_eq!(4999, max);
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_iter_split() {
+    use rayon::prelude::*;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert(
+        (Model(1),),
+        (0..5000).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((Model(2),), (0..3000).map(|i| (Pos(i as f32, 1., 0.),)));
+
+    let query = Read::<Pos>::query();
+    let sequential = query.iter(&mut world).map(|pos| pos.0 as f64).sum::<f64>();
+    let parallel = query
+        .par_iter_split(&mut world)
+        .map(|pos| pos.0 as f64)
+        .sum::<f64>();
+    assert_eq!(sequential, parallel);
+
+    // the entities of a single chunk are split between tasks
+    let mut single = universe.create_world();
+    single.insert((), (0..200).map(|i| (Pos(i as f32, 0., 0.),)));
+    let query = Read::<Pos>::query();
+    assert_eq!(1, query.chunk_count(&single));
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    let splits = Mutex::new(HashSet::new());
+    pool.install(|| {
+        query
+            .par_iter_split_immutable(&single)
+            .fold(|| 0, |count, _| count + 1)
+            .for_each(|count| {
+                splits.lock().unwrap().insert(count);
+            })
+    });
+    assert!(splits
+        .into_inner()
+        .unwrap()
+        .iter()
+        .all(|count| *count < 200));
+
+    // writes through split slices reach every entity once
+    let query = <(Write<Pos>, Read<Rot>)>::query();
+    query
+        .par_iter_split(&mut world)
+        .for_each(|(pos, _)| pos.2 += 1.);
+    let query = Write::<Pos>::query();
+    query.par_iter_split(&mut world).for_each(|pos| pos.2 += 1.);
+    let counts = Read::<Pos>::query()
+        .iter(&mut world)
+        .map(|pos| pos.2 as usize)
+        .collect::<Vec<_>>();
+    assert_eq!(5000, counts.iter().filter(|c| **c == 2).count());
+    assert_eq!(3000, counts.iter().filter(|c| **c == 1).count());
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_iter_entities() {
+    use rayon::prelude::*;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert(
+        (Model(1),),
+        (0..5000).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((Model(2),), (0..3000).map(|i| (Pos(i as f32, 1., 0.),)));
+
+    let query = Read::<Pos>::query();
+    let mut expected = query
+        .iter_entities(&mut world)
+        .map(|(entity, pos)| (entity, *pos))
+        .collect::<Vec<_>>();
+    expected.sort_by_key(|(entity, _)| entity.to_bits());
+
+    let sorted = |mut pairs: Vec<(Entity, Pos)>| {
+        pairs.sort_by_key(|(entity, _)| entity.to_bits());
+        pairs
+    };
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    query.par_for_each_entities(&mut world, |(entity, pos)| {
+        sender.send((entity, *pos)).unwrap();
+    });
+    assert_eq!(expected, sorted(receiver.try_iter().collect()));
+
+    let collected = query
+        .par_iter_entities(&mut world)
+        .map(|(entity, pos)| (entity, *pos))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, sorted(collected));
+
+    // writes through the parallel iterator are attributed to the right entities
+    let query = Write::<Pos>::query();
+    query
+        .par_iter_entities(&mut world)
+        .for_each(|(entity, mut pos)| pos.2 = entity.to_bits() as f32);
+    for (entity, pos) in Read::<Pos>::query().iter_entities(&mut world) {
+        assert_eq!(entity.to_bits() as f32, pos.2);
+    }
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_for_each_batched() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    // one large archetype split across several chunks, and many single entity chunks
+    world.insert(
+        (Model(0),),
+        (0..5000).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    for model in 1..50 {
+        world.insert(
+            (Model(model),),
+            vec![(Pos(model as f32, 0., 0.), Rot(0., 0., 0.))],
+        );
+    }
+    let count = Read::<Pos>::query().iter(&mut world).count();
+
+    for batch_size in &[1, 7, 64, 1000, 100_000] {
+        let query = <(Write<Rot>, Read<Pos>)>::query();
+        let visited = AtomicUsize::new(0);
+        query.par_for_each_batched(&mut world, *batch_size, |(rot, pos)| {
+            rot.0 += pos.0;
+            visited.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(count, visited.load(Ordering::SeqCst));
+    }
+
+    // each entity was visited exactly once per call, and saw its own data
+    for (pos, rot) in <(Read<Pos>, Read<Rot>)>::query().iter(&mut world) {
+        assert_eq!(pos.0 * 5., rot.0);
+    }
+
+    let query = Write::<Pos>::query();
+    query.par_for_each_entities_batched(&mut world, 100, |(entity, pos)| {
+        pos.2 = entity.to_bits() as f32;
+    });
+    for (entity, pos) in Read::<Pos>::query().iter_entities(&mut world) {
+        assert_eq!(entity.to_bits() as f32, pos.2);
+    }
+
+    let sum = AtomicUsize::new(0);
+    Read::<Pos>::query().par_for_each_batched_immutable(&world, 13, |pos| {
+        sum.fetch_add(pos.0 as usize, Ordering::SeqCst);
+    });
+    let expected = (0..5000).sum::<usize>() + (1..50).sum::<usize>();
+    assert_eq!(expected, sum.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+#[should_panic(expected = "batch size must be greater than 0")]
+fn query_par_for_each_batched_zero_batch_size() {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), vec![(Pos(0., 0., 0.),)]);
+    Read::<Pos>::query().par_for_each_batched(&mut world, 0, |_| {});
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_fold_bounding_box() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for model in 0..4 {
+        world.insert(
+            (Model(model),),
+            (0..25_000).map(|i| {
+                let i = (i * 7919 + model * 104_729) % 100_000;
+                (Pos(
+                    i as f32 - 50_000.,
+                    (i % 313) as f32,
+                    -((i % 97) as f32),
+                ),)
+            }),
+        );
+    }
+
+    type Aabb = ([f32; 3], [f32; 3]);
+    let empty = || -> Aabb { ([f32::MAX; 3], [f32::MIN; 3]) };
+    let grow = |(mut min, mut max): Aabb, pos: &Pos| -> Aabb {
+        for (i, v) in [pos.0, pos.1, pos.2].iter().enumerate() {
+            min[i] = min[i].min(*v);
+            max[i] = max[i].max(*v);
+        }
+        (min, max)
+    };
+    let union = |(mut min, mut max): Aabb, (other_min, other_max): Aabb| -> Aabb {
+        for i in 0..3 {
+            min[i] = min[i].min(other_min[i]);
+            max[i] = max[i].max(other_max[i]);
+        }
+        (min, max)
+    };
+
+    let query = Read::<Pos>::query();
+    let expected = query
+        .iter(&mut world)
+        .fold(empty(), |aabb, pos| grow(aabb, &pos));
+    assert_eq!(100_000, query.iter(&mut world).count());
+
+    let aabb = query.par_fold(&mut world, empty, |aabb, pos| grow(aabb, &pos), union);
+    assert_eq!(expected, aabb);
+    assert_eq!(
+        aabb,
+        query.par_fold_immutable(&world, empty, |aabb, pos| grow(aabb, &pos), union)
+    );
+
+    // folding no entities yields the identity
+    let query = Read::<Pos>::query().filter(tag_value(&Model(4)));
+    assert_eq!(
+        empty(),
+        query.par_fold(&mut world, empty, |aabb, pos| grow(aabb, &pos), union)
+    );
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_read_shared_data_par_foreach() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let components = vec![
+        (Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3)),
+        (Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6)),
+    ];
+
+    world.insert((Static, Model(5)), components.clone());
+    world.insert((Static, Model(6)), components.clone());
+
+    let query = <(Read<Pos>, Tagged<Model>)>::query();
+
+    let mut expected = query
+        .iter(&mut world)
+        .map(|(pos, model)| (*pos, *model))
+        .collect::<Vec<_>>();
+
+    let results = std::sync::Mutex::new(Vec::new());
+    query.par_for_each(&mut world, |(pos, model)| {
+        results.lock().unwrap().push((*pos, *model));
+    });
+
+    let mut results = results.into_inner().unwrap();
+    let key = |(pos, model): &(Pos, Model)| (model.0, pos.0 as u32);
+    expected.sort_by_key(key);
+    results.sort_by_key(key);
+
+    assert_eq!(components.len() * 2, results.len());
+    assert_eq!(expected, results);
+}
+
 #[test]
 fn query_read_entity_data_tuple() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -461,6 +1071,56 @@ fn query_on_changed_no_changes() {
     assert_eq!(0, count);
 }
 
+#[test]
+fn query_on_changed_tracks_each_chunk() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let a = world.insert((Model(1),), vec![(Pos(1., 2., 3.),)])[0];
+    let b = world.insert((Model(2),), vec![(Pos(4., 5., 6.),)])[0];
+
+    let query = Read::<Pos>::query().filter(changed::<Pos>());
+    assert_eq!(2, query.iter(&mut world).count());
+    assert_eq!(0, query.iter(&mut world).count());
+
+    // write the later chunk first, so the earlier chunk has the older version
+    world.get_component_mut::<Pos>(b).unwrap().0 = 0.;
+    world.get_component_mut::<Pos>(a).unwrap().0 = 0.;
+    assert_eq!(2, query.iter(&mut world).count());
+}
+
+#[test]
+fn query_on_changed_clone_starts_afresh() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let a = world.insert((Model(1),), vec![(Pos(1., 2., 3.),)])[0];
+    world.insert((Model(2),), vec![(Pos(4., 5., 6.),)]);
+
+    let query = Read::<Pos>::query().filter(changed::<Pos>());
+    let clone = query.clone();
+
+    // each query sees everything as changed on its first run
+    assert_eq!(2, query.iter(&mut world).count());
+    assert_eq!(0, query.iter(&mut world).count());
+    assert_eq!(2, clone.iter(&mut world).count());
+    assert_eq!(0, clone.iter(&mut world).count());
+
+    // clones of a query which has already run do not inherit its versions
+    let late = query.clone();
+    assert_eq!(2, late.iter(&mut world).count());
+
+    world.get_component_mut::<Pos>(a).unwrap().0 = 0.;
+    assert_eq!(1, query.iter(&mut world).count());
+    assert_eq!(1, clone.iter(&mut world).count());
+    assert_eq!(1, late.iter(&mut world).count());
+    assert_eq!(0, query.iter(&mut world).count());
+}
+
 #[test]
 fn query_on_changed_self_changes() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -587,3 +1247,2010 @@ fn query_iter_chunks_tag() {
         }
     }
 }
+
+#[test]
+fn query_for_each_batched() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let a = world
+        .insert(
+            (Model(0),),
+            (0..10).map(|i| (Pos(i as f32, 0., 0.), Vel(1., 2., 3.))),
+        )
+        .to_vec();
+    let b = world
+        .insert(
+            (Model(1),),
+            (0..5).map(|i| (Pos(i as f32, 0., 0.), Vel(1., 2., 3.))),
+        )
+        .to_vec();
+
+    let mut batches = 0;
+    let mut remainder = 0;
+    let query = <(Write<Pos>, Read<Vel>)>::query();
+    query.for_each_batched::<4, _, _>(
+        &mut world,
+        |(pos, vel): (&mut [Pos; 4], &[Vel; 4])| {
+            for i in 0..4 {
+                pos[i].1 += vel[i].1;
+            }
+            batches += 1;
+        },
+        |(pos, vel)| {
+            pos.1 += vel.1;
+            remainder += 1;
+        },
+    );
+
+    // batches never span chunks; 10 = 2 * 4 + 2 and 5 = 1 * 4 + 1
+    assert_eq!(3, batches);
+    assert_eq!(3, remainder);
+
+    for (i, e) in a.iter().chain(b.iter()).enumerate() {
+        let expected = if i < a.len() { i } else { i - a.len() };
+        assert_eq!(
+            Pos(expected as f32, 2., 0.),
+            *world.get_component::<Pos>(*e).unwrap()
+        );
+    }
+
+    let mut seen = Vec::new();
+    let mut rest = Vec::new();
+    Read::<Pos>::query().for_each_batched_immutable::<3, _, _>(
+        &world,
+        |pos: &[Pos; 3]| seen.extend(pos.iter().map(|p| p.0 as u32)),
+        |pos| rest.push(pos.0 as u32),
+    );
+    // 10 = 3 * 3 + 1 and 5 = 1 * 3 + 2
+    assert_eq!(3, rest.len());
+    seen.extend(rest);
+    seen.sort();
+    assert_eq!(vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 6, 7, 8, 9], seen);
+}
+
+#[test]
+fn query_write_panic_releases_borrows() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let query = Write::<Pos>::query();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        query.for_each(&mut world, |mut pos| {
+            pos.1 = 1.;
+            if pos.0 > 4. {
+                panic!("panic while holding a component borrow");
+            }
+        });
+    }));
+    assert!(result.is_err());
+
+    // the chunk can be borrowed again, both mutably and immutably
+    for mut pos in query.iter(&mut world) {
+        pos.2 = 2.;
+    }
+    let read = Read::<Pos>::query();
+    assert_eq!(10, read.iter(&mut world).filter(|pos| pos.2 == 2.).count());
+    assert_eq!(0, world.clear_poisoned_borrows());
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_write_panic_releases_borrows_par() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    for i in 0..8 {
+        world.insert((Model(i),), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+    }
+
+    let query = Write::<Pos>::query();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        query.par_for_each(&mut world, |pos| {
+            if pos.0 > 4. {
+                panic!("panic while holding a component borrow");
+            }
+        });
+    }));
+    assert!(result.is_err());
+
+    let count = AtomicUsize::new(0);
+    query.par_for_each(&mut world, |mut pos| {
+        pos.1 = 1.;
+        count.fetch_add(1, Ordering::SeqCst);
+    });
+    assert_eq!(80, count.load(Ordering::SeqCst));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn query_clear_poisoned_borrows() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entity = world.insert((), vec![(Pos(0., 0., 0.),)])[0];
+
+    // leaking a guard leaves the borrow held
+    std::mem::forget(world.get_component_mut::<Pos>(entity).unwrap());
+
+    let query = Read::<Pos>::query();
+    let result = catch_unwind(AssertUnwindSafe(|| query.iter(&mut world).count()));
+    assert!(result.is_err());
+
+    assert_eq!(1, world.clear_poisoned_borrows());
+    assert_eq!(1, query.iter(&mut world).count());
+}
+
+#[derive(Debug, PartialEq)]
+struct Extent(f32, f32);
+
+impl legion::query::Compute for Extent {
+    type Source = (Read<Pos>, Read<Scale>);
+
+    fn compute<'a>((pos, scale): (Ref<'a, Pos>, Ref<'a, Scale>)) -> Self {
+        Extent(pos.0 - scale.0, pos.0 + scale.0)
+    }
+}
+
+#[test]
+fn query_computed() {
+    use legion::query::Computed;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert(
+        (),
+        (0..4).map(|i| {
+            (
+                Pos(i as f32, 0., 0.),
+                Scale(1., 1., 1.),
+                Vel(i as f32, 0., 0.),
+            )
+        }),
+    );
+    // missing a source component, so not matched
+    world.insert((), vec![(Pos(9., 0., 0.), Vel(9., 0., 0.))]);
+
+    let query = <(Read<Vel>, Computed<Extent>)>::query();
+    let mut count = 0;
+    for (vel, extent) in query.iter(&mut world) {
+        assert_eq!(Extent(vel.0 - 1., vel.0 + 1.), extent);
+        count += 1;
+    }
+    assert_eq!(4, count);
+}
+
+#[test]
+#[should_panic(expected = "invalid view")]
+fn query_computed_conflicting_write() {
+    use legion::query::Computed;
+
+    let _ = <(Write<Pos>, Computed<Extent>)>::query();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn query_computed_borrows_sources() {
+    use legion::query::Computed;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entity = world.insert((), vec![(Pos(1., 0., 0.), Scale(1., 1., 1.))])[0];
+
+    let query = Computed::<Extent>::query();
+    let mut iter = unsafe { query.iter_unchecked(&world) };
+    assert_eq!(Some(Extent(0., 2.)), iter.next());
+
+    // the source chunk is still borrowed while the iterator is alive
+    let result = catch_unwind(AssertUnwindSafe(|| unsafe {
+        world.get_component_mut_unchecked::<Pos>(entity).is_some()
+    }));
+    assert!(result.is_err());
+    drop(iter);
+
+    *world.get_component_mut::<Pos>(entity).unwrap() = Pos(5., 0., 0.);
+    assert_eq!(
+        vec![Extent(4., 6.)],
+        query.iter(&mut world).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn query_describe_filter() {
+    use legion::filter::{EntityFilter, FilterDescription};
+
+    let query = Read::<Pos>::query().filter(!tag::<Static>() | changed::<Rot>());
+    let description = query.filter_ref().describe();
+    assert_eq!(
+        FilterDescription::And(vec![
+            FilterDescription::And(vec![
+                FilterDescription::Component("query_api::Pos"),
+                FilterDescription::Or(vec![
+                    FilterDescription::Not(Box::new(FilterDescription::Tag("query_api::Static"))),
+                    FilterDescription::Component("query_api::Rot"),
+                ]),
+            ]),
+            FilterDescription::Changed("query_api::Rot"),
+        ]),
+        description
+    );
+    assert_eq!(
+        "((component<query_api::Pos> & (!tag<query_api::Static> | component<query_api::Rot>)) \
+         & changed<query_api::Rot>)",
+        description.to_string()
+    );
+    assert!(format!("{:?}", query.filter_ref())
+        .contains("ComponentChangedFilter { type: \"query_api::Rot\", entries: 0 }"));
+}
+
+#[test]
+fn query_map_filter_entities() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert(
+            (),
+            (0..10).map(|i| (Pos(i as f32, 0., 0.), Rot(1., 0., 0.))),
+        )
+        .to_vec();
+    world.insert((), vec![(Pos(100., 0., 0.),)]);
+
+    let query = <(Read<Pos>, Read<Rot>)>::query()
+        .map(|(pos, rot)| pos.0 + rot.0)
+        .filter_entities(|_, sum| *sum > 5.)
+        .map(|sum| sum * 2.);
+
+    let mut results = query.iter(&mut world).collect::<Vec<_>>();
+    results.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(vec![12., 14., 16., 18., 20.], results);
+
+    // the adapted query can be reused, and yields entity IDs
+    let found = query
+        .iter_entities(&mut world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(&entities[5..], &found[..]);
+
+    let by_entity = Read::<Pos>::query().filter_entities(|e, _| e == entities[3]);
+    let mut visited = 0;
+    by_entity.for_each(&mut world, |pos| {
+        assert_eq!(3., pos.0);
+        visited += 1;
+    });
+    assert_eq!(1, visited);
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_map_filter_entities_par() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for i in 0..4 {
+        world.insert((Model(i),), (0..1000).map(|i| (Pos(i as f32, 0., 0.),)));
+    }
+
+    let query = Read::<Pos>::query()
+        .filter_entities(|_, pos| pos.0 as usize % 3 == 0)
+        .map(|pos| pos.0 as usize);
+
+    let sequential: usize = query.iter(&mut world).sum();
+    let parallel = AtomicUsize::new(0);
+    query.par_for_each(&mut world, |x| {
+        parallel.fetch_add(x, Ordering::Relaxed);
+    });
+    assert_eq!(sequential, parallel.load(Ordering::Relaxed));
+}
+
+#[test]
+fn query_filter_entities_borrows_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let changed = Read::<Pos>::query().filter(changed::<Pos>());
+    assert_eq!(10, changed.iter(&mut world).count());
+
+    // the predicate rejects every entity, but the chunk is still borrowed for writing
+    let query = Write::<Pos>::query().filter_entities(|_, _| false);
+    assert_eq!(0, query.iter(&mut world).count());
+    assert_eq!(10, changed.iter(&mut world).count());
+}
+
+#[test]
+fn query_exclude() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let moving = world
+        .insert((), vec![(Pos(1., 0., 0.),), (Pos(2., 0., 0.),)])
+        .to_vec();
+    let frozen = world
+        .insert(
+            (),
+            vec![(Pos(3., 0., 0.), Static), (Pos(4., 0., 0.), Static)],
+        )
+        .to_vec();
+    world.insert((), vec![(Rot(0., 0., 0.),)]);
+
+    let query = <(Write<Pos>, Exclude<Static>)>::query();
+    for (mut pos, ()) in query.iter(&mut world) {
+        pos.0 += 10.;
+    }
+
+    for entity in moving {
+        assert!(world.get_component::<Pos>(entity).unwrap().0 > 10.);
+    }
+    for entity in frozen {
+        assert!(world.get_component::<Pos>(entity).unwrap().0 < 10.);
+    }
+
+    assert!(!<(Write<Pos>, Exclude<Static>) as View>::reads::<Static>());
+    assert_eq!(3, Exclude::<Static>::query().iter(&mut world).count());
+}
+
+#[test]
+#[should_panic(expected = "invalid view")]
+fn query_exclude_accessed_type() { <(Read<Pos>, Exclude<Pos>)>::query(); }
+
+#[test]
+fn query_has() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let plain = world
+        .insert((), vec![(Pos(1., 0., 0.),), (Pos(2., 0., 0.),)])
+        .to_vec();
+    let selected = world
+        .insert(
+            (),
+            vec![(Pos(3., 0., 0.), Static), (Pos(4., 0., 0.), Static)],
+        )
+        .to_vec();
+    world.insert((), vec![(Rot(0., 0., 0.),)]);
+
+    let query = <(Read<Pos>, Has<Static>)>::query();
+    let mut count = 0;
+    for (entity, (pos, has)) in query.iter_entities(&mut world) {
+        assert_eq!(selected.contains(&entity), has);
+        assert_eq!(plain.contains(&entity), !has);
+        assert_eq!(has, pos.0 > 2.);
+        count += 1;
+    }
+    assert_eq!(4, count);
+
+    // does not borrow the component, so it can be combined with access to it
+    assert!(!<Has<Static> as View>::reads::<Static>());
+    assert!(!<Has<Static> as View>::writes::<Static>());
+    let query = <(Write<Pos>, Has<Pos>)>::query();
+    assert!(query.iter(&mut world).all(|(_, has)| has));
+    assert_eq!(5, Has::<Static>::query().iter(&mut world).count());
+}
+
+#[test]
+fn query_entities_in_view() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))));
+    world.insert((Static,), (0..5).map(|i| (Pos(i as f32, 1., 0.),)));
+
+    let expected = Read::<Pos>::query()
+        .iter_entities(&mut world)
+        .map(|(e, pos)| (e, *pos))
+        .collect::<Vec<_>>();
+    let query = <(Read<Pos>, Entities)>::query();
+    let actual = query
+        .iter(&mut world)
+        .map(|(pos, e)| (e, *pos))
+        .collect::<Vec<_>>();
+    assert_eq!(10, actual.len());
+    assert_eq!(expected, actual);
+
+    let query = <(Entities, Write<Pos>, Read<Rot>)>::query();
+    let mut written = Vec::new();
+    for (entity, mut pos, _) in query.iter(&mut world) {
+        pos.2 = 1.;
+        written.push(entity);
+    }
+    for (entity, pos) in Read::<Pos>::query().iter_entities(&mut world) {
+        assert_eq!(written.contains(&entity), pos.2 > 0.);
+    }
+    assert_eq!(5, written.len());
+
+    assert!(<(Entities, Write<Pos>) as View>::validate());
+    assert!(!<Entities as View>::reads::<Pos>());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Wide<const I: usize>(usize);
+
+#[test]
+fn query_wide_view_and_filter() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert(
+        (),
+        (0..10).map(|i| {
+            (
+                Wide::<0>(i),
+                Wide::<1>(i + 1),
+                Wide::<2>(i + 2),
+                Wide::<3>(i + 3),
+                Wide::<4>(i + 4),
+                Wide::<5>(i + 5),
+                Wide::<6>(i + 6),
+                Wide::<7>(i + 7),
+                Wide::<8>(i + 8),
+                Wide::<9>(i + 9),
+            )
+        }),
+    );
+    world.insert((), (0..5).map(|i| (Wide::<0>(i), Wide::<10>(i))));
+
+    let query = <(
+        Entities,
+        Read<Wide<0>>,
+        Read<Wide<1>>,
+        Read<Wide<2>>,
+        Read<Wide<3>>,
+        Read<Wide<4>>,
+        Read<Wide<5>>,
+        Read<Wide<6>>,
+        Read<Wide<7>>,
+        Read<Wide<8>>,
+        Write<Wide<9>>,
+        Has<Wide<10>>,
+        TryRead<Wide<11>>,
+    )>::query();
+    let mut count = 0;
+    for (_, w0, w1, w2, w3, w4, w5, w6, w7, w8, mut w9, has, missing) in query.iter(&mut world) {
+        let i = w0.0;
+        assert_eq!(
+            [i + 1, i + 2, i + 3, i + 4, i + 5, i + 6, i + 7, i + 8],
+            [w1.0, w2.0, w3.0, w4.0, w5.0, w6.0, w7.0, w8.0]
+        );
+        assert_eq!(i + 9, w9.0);
+        assert!(!has);
+        assert!(missing.is_none());
+        w9.0 = i;
+        count += 1;
+    }
+    assert_eq!(10, count);
+    assert!(Read::<Wide<9>>::query()
+        .iter(&mut world)
+        .all(|w9| w9.0 < 10));
+
+    let filter = component::<Wide<0>>()
+        & component::<Wide<1>>()
+        & component::<Wide<2>>()
+        & component::<Wide<3>>()
+        & component::<Wide<4>>()
+        & component::<Wide<5>>()
+        & component::<Wide<6>>()
+        & component::<Wide<7>>()
+        & component::<Wide<8>>()
+        & component::<Wide<9>>()
+        & !component::<Wide<10>>()
+        & !component::<Wide<11>>();
+    let query = Read::<Wide<0>>::query().filter(filter);
+    assert_eq!(10, query.iter(&mut world).count());
+
+    let filter = component::<Wide<11>>()
+        | component::<Wide<12>>()
+        | component::<Wide<13>>()
+        | component::<Wide<14>>()
+        | component::<Wide<15>>()
+        | component::<Wide<16>>()
+        | component::<Wide<17>>()
+        | component::<Wide<18>>()
+        | component::<Wide<19>>()
+        | component::<Wide<10>>();
+    let query = Read::<Wide<0>>::query().filter(filter);
+    assert_eq!(5, query.iter(&mut world).count());
+}
+
+#[test]
+fn query_prefetch_distance() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for i in 0..8 {
+        world.insert(
+            (Model(i),),
+            (0..100).map(|j| (Pos(j as f32, 0., 0.), Rot(0., 0., 0.))),
+        );
+    }
+
+    let query = <(Read<Pos>, Write<Rot>)>::query();
+    let mut results = Vec::new();
+    for distance in &[0, 1, 3, 100] {
+        legion::query::set_prefetch_distance(*distance);
+        assert_eq!(*distance, legion::query::prefetch_distance());
+        for (pos, mut rot) in query.iter(&mut world) {
+            rot.0 += pos.0;
+        }
+        results.push(
+            Read::<Rot>::query()
+                .iter(&mut world)
+                .map(|rot| rot.0)
+                .collect::<Vec<_>>(),
+        );
+    }
+    legion::query::set_prefetch_distance(1);
+
+    assert_eq!(800, results[0].len());
+    for (i, result) in results.iter().enumerate() {
+        let expected = results[0]
+            .iter()
+            .map(|r| r * (i + 1) as f32)
+            .collect::<Vec<_>>();
+        assert_eq!(&expected, result);
+    }
+}
+
+#[test]
+fn query_changed_in_view() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert(
+        (Model(0),),
+        (0..5).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert(
+        (Model(1),),
+        (0..5).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((Model(2),), (0..5).map(|i| (Rot(i as f32, 0., 0.),)));
+
+    let query = <(Tagged<Model>, Read<Pos>, Changed<Pos>)>::query();
+    let changed = |world: &mut World| {
+        let mut changed = query
+            .iter(world)
+            .map(|(model, _, changed)| (model.0, changed))
+            .collect::<Vec<_>>();
+        changed.sort();
+        changed.dedup();
+        changed
+    };
+
+    // chunks without `Pos` are not matched, and everything is new on the first pass
+    assert_eq!(vec![(0, true), (1, true)], changed(&mut world));
+    assert_eq!(vec![(0, false), (1, false)], changed(&mut world));
+
+    // writes are tracked per chunk
+    let write = Write::<Pos>::query().filter(tag_value(&Model(1)));
+    for mut pos in write.iter(&mut world) {
+        pos.1 = 1.;
+    }
+    assert_eq!(vec![(0, false), (1, true)], changed(&mut world));
+    assert_eq!(vec![(0, false), (1, false)], changed(&mut world));
+
+    // clones start afresh and track changes independently
+    let other = query.clone();
+    world.insert((Model(0),), vec![(Pos(5., 0., 0.), Rot(0., 0., 0.))]);
+    assert_eq!(11, other.iter(&mut world).filter(|(_, _, c)| *c).count());
+    assert_eq!(0, other.iter(&mut world).filter(|(_, _, c)| *c).count());
+    assert_eq!(vec![(0, true), (1, false)], changed(&mut world));
+}
+
+#[test]
+fn query_version_in_view() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))));
+    world.insert((), (0..5).map(|i| (Rot(i as f32, 0., 0.),)));
+
+    let query = <(Read<Pos>, legion::query::ChunkVersion<Pos>)>::query();
+    let versions = |world: &mut World| {
+        let versions = query.iter(world).map(|(_, v)| v).collect::<Vec<_>>();
+        assert_eq!(5, versions.len());
+        assert!(versions.iter().all(|v| *v == versions[0]));
+        versions[0]
+    };
+
+    let initial = versions(&mut world);
+    assert!(!initial.is_none());
+
+    for _ in Read::<Pos>::query().iter(&mut world) {}
+    assert_eq!(initial, versions(&mut world));
+
+    for mut pos in Write::<Pos>::query().iter(&mut world) {
+        pos.0 += 1.;
+    }
+    let written = versions(&mut world);
+    assert!(written.is_newer_than(initial));
+    assert_eq!(written, versions(&mut world));
+}
+
+#[test]
+fn query_collect_entities_then_mutate() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let expected = world
+        .insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))))
+        .to_vec();
+    world.insert((), (0..5).map(|i| (Rot(i as f32, 0., 0.),)));
+
+    let query = <(Read<Pos>, Write<Rot>)>::query();
+    let entities = query.collect_entities(&world);
+    assert_eq!(expected, entities);
+
+    // no component borrows are held, so the world can be modified
+    for entity in &entities[..3] {
+        world.delete(*entity);
+    }
+    world.add_component(entities[3], Scale(1., 1., 1.));
+
+    let remaining = query.collect_entities(&world);
+    assert_eq!(2, remaining.len());
+    assert!(remaining.contains(&entities[3]) && remaining.contains(&entities[4]));
+    assert_eq!(7, Read::<Rot>::query().iter(&mut world).count());
+}
+
+#[test]
+fn query_drain_entities() {
+    use legion::query::EntityDecision;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert((), (0..6).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+
+    let query = Read::<Pos>::query();
+    let mut visited = 0;
+    query.drain_entities(&mut world, |entity| {
+        visited += 1;
+        match entities.iter().position(|e| *e == entity).unwrap() % 3 {
+            0 => EntityDecision::Keep,
+            1 => EntityDecision::Delete,
+            _ => EntityDecision::Custom(Box::new(move |cmd| {
+                cmd.add_component(entity, Scale(2., 2., 2.))
+            })),
+        }
+    });
+    assert_eq!(6, visited);
+
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(i % 3 != 1, world.is_alive(*entity));
+        assert_eq!(i % 3 == 2, world.get_component::<Scale>(*entity).is_some());
+    }
+}
+
+type TransformBundle = (Read<Pos>, Read<Rot>);
+
+#[test]
+fn query_nested_view() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert(
+        (),
+        (0..5).map(|i| {
+            (
+                Pos(i as f32, 0., 0.),
+                Rot(i as f32, 1., 0.),
+                Scale(0., 0., 0.),
+            )
+        }),
+    );
+    world.insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))));
+
+    let query = <(TransformBundle, Write<Scale>)>::query();
+    let mut count = 0;
+    for ((pos, rot), mut scale) in query.iter(&mut world) {
+        assert_eq!(pos.0, rot.0);
+        scale.0 = pos.0 + rot.1;
+        count += 1;
+    }
+    assert_eq!(5, count);
+
+    let query = <(Read<Scale>, (Read<Pos>, (Read<Rot>,)))>::query();
+    for (scale, (pos, (rot,))) in query.iter(&mut world) {
+        assert_eq!(scale.0, pos.0 + rot.1);
+    }
+
+    // duplicates are detected across nesting levels
+    assert!(<(TransformBundle, Write<Scale>) as View>::validate());
+    assert!(!<((Read<Pos>,), Write<Pos>) as View>::validate());
+    assert!(!<((Read<Pos>,), Read<Pos>) as View>::validate());
+    assert!(!<(Read<Rot>, (Read<Scale>, (Write<Rot>,))) as View>::validate());
+}
+
+#[test]
+fn query_unit_view() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let expected = world
+        .insert((Static,), (0..5).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    world.insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let query = <()>::query().filter(tag::<Static>());
+    let entities = query
+        .iter_entities_immutable(&world)
+        .map(|(entity, ())| entity)
+        .collect::<Vec<_>>();
+    assert_eq!(expected, entities);
+    assert_eq!(10, <()>::query().iter(&mut world).count());
+
+    // no component borrows are taken, so components may be written while iterating
+    let write = Write::<Pos>::query();
+    for (entity, ()) in query.iter_entities_immutable(&world) {
+        for mut pos in unsafe { write.iter_unchecked(&world) } {
+            pos.1 = 1.;
+        }
+        assert!(expected.contains(&entity));
+    }
+
+    // `Entities` yields just the IDs
+    let query = Entities::query().filter(tag::<Static>());
+    assert_eq!(expected, query.iter(&mut world).collect::<Vec<_>>());
+}
+
+#[test]
+fn query_component_set_filters() {
+    use legion::filter::EntityFilter;
+    use legion::storage::ComponentTypeId;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let exact_match = world.insert((), vec![(Pos(0., 0., 0.), Rot(0., 0., 0.))])[0];
+    let tagged = world.insert((Static,), vec![(Rot(0., 0., 0.), Pos(0., 0., 0.))])[0];
+    let extra = world.insert(
+        (),
+        vec![(Pos(0., 0., 0.), Rot(0., 0., 0.), Scale(0., 0., 0.))],
+    )[0];
+    let missing = world.insert((), vec![(Pos(0., 0., 0.),)])[0];
+    let other = world.insert((), vec![(Pos(0., 0., 0.), Scale(0., 0., 0.))])[0];
+
+    let matching = |filter| {
+        let mut entities = <()>::query().filter(filter).collect_entities(&world);
+        entities.sort_by_key(|e| e.to_string());
+        entities
+    };
+    let sorted = |mut entities: Vec<Entity>| {
+        entities.sort_by_key(|e| e.to_string());
+        entities
+    };
+
+    // component order does not matter, and tags are not considered
+    assert_eq!(
+        sorted(vec![exact_match, tagged]),
+        matching(exact::<(Rot, Pos)>())
+    );
+    let types = [ComponentTypeId::of::<Pos>(), ComponentTypeId::of::<Rot>()];
+    assert_eq!(
+        sorted(vec![exact_match, tagged]),
+        matching(exact_components(&types))
+    );
+    assert_eq!(
+        sorted(vec![exact_match, tagged, extra]),
+        matching(superset_of(&types))
+    );
+    assert_eq!(
+        sorted(vec![exact_match, tagged, missing]),
+        matching(subset_of(&types))
+    );
+    let query = <()>::query().filter(!exact::<(Pos, Rot)>());
+    assert_eq!(
+        sorted(vec![extra, missing, other]),
+        sorted(query.collect_entities(&world))
+    );
+
+    // composes with other filters
+    let query = <()>::query().filter(exact::<(Pos, Rot)>() & tag::<Static>());
+    assert_eq!(vec![tagged], query.collect_entities(&world));
+    assert_eq!(
+        "(exact_components(2) & tag<query_api::Static>)",
+        query.filter_ref().describe().to_string()
+    );
+}
+
+#[test]
+fn query_get() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert(
+            (),
+            (0..300).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+        )
+        .to_vec();
+    let tagged = world.insert((Static,), vec![(Pos(-1., 0., 0.), Rot(0., 0., 0.))])[0];
+    let no_rot = world.insert((), vec![(Pos(-2., 0., 0.),)])[0];
+    let dead = world.insert((), vec![(Pos(-3., 0., 0.), Rot(0., 0., 0.))])[0];
+    world.delete(dead);
+
+    let query = <(Read<Pos>, Write<Rot>)>::query();
+    for (i, entity) in entities.iter().enumerate() {
+        let (pos, mut rot) = query.get(&mut world, *entity).unwrap();
+        assert_eq!(i as f32, pos.0);
+        rot.0 = pos.0 * 2.;
+    }
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(
+            i as f32 * 2.,
+            world.get_component::<Rot>(*entity).unwrap().0
+        );
+    }
+    assert_eq!(-1., query.get(&mut world, tagged).unwrap().0 .0);
+    assert!(query.get(&mut world, no_rot).is_none());
+    assert!(query.get(&mut world, dead).is_none());
+
+    let query = Read::<Pos>::query().filter(!tag::<Static>());
+    assert!(query.get_immutable(&world, tagged).is_none());
+    assert_eq!(-2., query.get_immutable(&world, no_rot).unwrap().0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn query_get_borrow_checked() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entity = world.insert((), vec![(Pos(0., 0., 0.),)])[0];
+
+    let pos = Read::<Pos>::query().get_immutable(&world, entity).unwrap();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+        Write::<Pos>::query()
+            .get_unchecked(&world, entity)
+            .map(|_| ())
+    }));
+    assert!(result.is_err());
+    drop(pos);
+    assert!(unsafe { Write::<Pos>::query().get_unchecked(&world, entity) }.is_some());
+}
+
+#[test]
+fn query_matches() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert(
+            (Model(1),),
+            vec![
+                (Pos(0., 0., 0.), Rot(0., 0., 0.)),
+                (Pos(1., 1., 1.), Rot(1., 1., 1.)),
+            ],
+        )
+        .to_vec();
+    let other = world.insert((Model(2),), vec![(Pos(2., 2., 2.),)])[0];
+
+    let query = <(Read<Pos>, Read<Rot>)>::query();
+    assert!(query.matches(&world, entities[0]));
+    assert!(!query.matches(&world, other));
+
+    let query = Read::<Pos>::query().filter(tag_value(&Model(2)));
+    assert!(!query.matches(&world, entities[0]));
+    assert!(query.matches(&world, other));
+
+    world.delete(other);
+    assert!(!query.matches(&world, other));
+
+    // matching does not mark the chunk as seen by the changed filter
+    let query = Read::<Pos>::query().filter(changed::<Pos>());
+    assert!(query.matches(&world, entities[1]));
+    assert!(query.matches(&world, entities[1]));
+    assert_eq!(2, query.iter(&mut world).count());
+    assert!(!query.matches(&world, entities[1]));
+}
+
+#[test]
+fn query_count() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert(
+        (Model(1),),
+        (0..3).map(|_| (Pos(0., 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert(
+        (Model(2),),
+        (0..5).map(|_| (Pos(0., 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((), (0..7).map(|_| (Pos(0., 0., 0.),)));
+
+    let query = Read::<Pos>::query();
+    assert_eq!(15, query.count(&world));
+    assert_eq!(3, query.chunk_count(&world));
+
+    // rejected at the archetype level
+    let query = <(Read<Pos>, Read<Rot>)>::query();
+    assert_eq!(8, query.count(&world));
+    assert_eq!(2, query.chunk_count(&world));
+
+    // rejected at the chunkset level
+    let query = Read::<Pos>::query().filter(tag_value(&Model(2)));
+    assert_eq!(5, query.count(&world));
+
+    // rejected at the chunk level, without consuming the changes
+    let query = Read::<Pos>::query().filter(changed::<Rot>());
+    assert_eq!(8, query.count(&world));
+    assert_eq!(2, query.chunk_count(&world));
+    assert_eq!(8, query.count(&world));
+    assert_eq!(8, query.iter_immutable(&world).count());
+    assert_eq!(0, query.count(&world));
+    assert_eq!(0, query.chunk_count(&world));
+
+    // counting does not borrow component data
+    let query = Write::<Pos>::query();
+    let _pos = unsafe { query.iter_unchecked(&world) }.next().unwrap();
+    assert_eq!(15, query.count(&world));
+}
+
+#[test]
+fn query_single() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let query = Write::<Pos>::query().filter(tag::<Static>());
+    assert!(query.first(&mut world).is_none());
+    assert_eq!(
+        Err(SingleError::NoEntities),
+        query.single(&mut world).map(|_| ())
+    );
+    assert_eq!(
+        Err(SingleError::NoEntities),
+        query.single_entity(&mut world).map(|_| ())
+    );
+
+    world.insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+    let player = world.insert((Static,), vec![(Pos(1., 2., 3.),)])[0];
+
+    let (entity, mut pos) = query.single_entity(&mut world).unwrap();
+    assert_eq!(player, entity);
+    pos.0 = 5.;
+    drop(pos);
+    assert_eq!(Pos(5., 2., 3.), *query.single(&mut world).unwrap());
+    assert_eq!(Pos(5., 2., 3.), *query.first(&mut world).unwrap());
+
+    // a second match in another chunk is detected
+    world.insert((Static, Model(1)), vec![(Pos(0., 0., 0.),)]);
+    assert_eq!(
+        Err(SingleError::MultipleEntities),
+        query.single(&mut world).map(|_| ())
+    );
+    assert_eq!(
+        Err(SingleError::MultipleEntities),
+        query.single_entity(&mut world).map(|_| ())
+    );
+    assert!(query.first(&mut world).is_some());
+    assert_eq!(
+        "more than one entity matches the query",
+        SingleError::MultipleEntities.to_string()
+    );
+}
+
+#[test]
+fn query_for_each_mut_closure() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), (1..=4).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Static,), (5..=6).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let mut sum = 0.;
+    Read::<Pos>::query().for_each(&mut world, |pos| sum += pos.0);
+    assert_eq!(21., sum);
+
+    let mut seen = Vec::new();
+    Read::<Pos>::query().for_each_entities_immutable(&world, |(entity, _)| seen.push(entity));
+    assert_eq!(6, seen.len());
+}
+
+#[test]
+fn query_for_each_chunk() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1),), (0..3).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(2),), (0..5).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let mut sizes = Vec::new();
+    let query = <(Write<Pos>, Tagged<Model>)>::query();
+    query.for_each_chunk(&mut world, |chunk| {
+        let model = *chunk.tag::<Model>().unwrap();
+        let mut positions = chunk.components_mut::<Pos>().unwrap();
+        for pos in positions.iter_mut() {
+            pos.1 = model.0 as f32;
+        }
+        sizes.push((model.0, positions.len()));
+    });
+    sizes.sort();
+    assert_eq!(vec![(1, 3), (2, 5)], sizes);
+
+    let mut total = 0.;
+    Read::<Pos>::query().for_each_chunk_immutable(&world, |mut chunk| {
+        total += chunk.iter().map(|pos| pos.1).sum::<f32>();
+    });
+    assert_eq!(13., total);
+}
+
+#[test]
+fn query_on_world() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert(
+            (Model(1),),
+            (0..3).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+        )
+        .to_vec();
+    world.insert((), vec![(Pos(9., 0., 0.),)]);
+
+    let query = <(Read<Pos>, Read<Rot>)>::query();
+    let mut sum = 0.;
+    for (pos, _) in query.on(&world) {
+        sum += pos.0;
+    }
+    assert_eq!(3., sum);
+
+    let mut seen = query
+        .on(&world)
+        .entities()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    seen.sort_by_key(|entity| entities.iter().position(|e| e == entity));
+    assert_eq!(entities, seen);
+
+    let with_entities = <(Entities, Read<Pos>)>::query();
+    assert_eq!(
+        4,
+        QueryRef::from((&with_entities, &world)).into_iter().count()
+    );
+}
+
+#[test]
+fn query_archetypes_created_between_executions() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), vec![(Pos(1., 0., 0.),)]);
+
+    let query = Read::<Pos>::query().filter(!component::<Scale>());
+    assert_eq!(1, query.iter(&mut world).count());
+
+    // new matching and non-matching archetypes
+    let generation = world.archetype_generation();
+    world.insert((), vec![(Pos(2., 0., 0.), Rot(0., 0., 0.))]);
+    world.insert((), vec![(Pos(3., 0., 0.), Scale(0., 0., 0.))]);
+    world.insert((), vec![(Rot(0., 0., 0.),)]);
+    assert_eq!(generation + 3, world.archetype_generation());
+    assert_eq!(2, query.iter(&mut world).count());
+
+    // entities moved into a new archetype
+    let entity = world.insert((), vec![(Rot(0., 0., 0.),)])[0];
+    world.add_component(entity, Pos(4., 0., 0.));
+    world.add_tag(entity, Model(1));
+    let mut values = query.iter(&mut world).map(|p| p.0).collect::<Vec<_>>();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(vec![1., 2., 4.], values);
+
+    // the same query can be used with another world
+    let mut other = World::new();
+    other.insert((), vec![(Pos(5., 0., 0.), Scale(0., 0., 0.))]);
+    assert_eq!(0, query.iter(&mut other).count());
+    other.insert((Static,), vec![(Pos(6., 0., 0.),)]);
+    assert_eq!(1, query.iter(&mut other).count());
+    assert_eq!(3, query.iter(&mut world).count());
+
+    // changing the filter invalidates the cache
+    let mut query = Read::<Pos>::query();
+    assert_eq!(4, query.iter(&mut world).count());
+    let _ = query.filter_mut();
+    assert_eq!(2, query.iter(&mut other).count());
+}
+
+fn exhaust<I: FusedIterator>(mut iter: I, expected: usize) {
+    for _ in 0..expected {
+        assert!(iter.next().is_some());
+    }
+    for _ in 0..10 {
+        assert!(iter.next().is_none());
+    }
+}
+
+#[test]
+fn query_iterators_are_fused() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1),), (0..3).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(2),), (0..2000).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((), vec![(Pos(0., 0., 0.), Rot(0., 0., 0.))]);
+    world.insert((), vec![(Rot(0., 0., 0.),)]);
+
+    let query = Read::<Pos>::query();
+    let chunks = query.iter_chunks_immutable(&world).count();
+    assert!(chunks > 3);
+    exhaust(query.iter_chunks_immutable(&world), chunks);
+    exhaust(query.iter_immutable(&world), 2004);
+    exhaust(query.iter_entities_immutable(&world), 2004);
+    exhaust(query.iter(&mut world), 2004);
+    for mut chunk in query.iter_chunks_immutable(&world) {
+        let len = chunk.len();
+        exhaust(chunk.iter_entities(), len);
+    }
+
+    let tagged = <(Read<Pos>, Tagged<Model>)>::query().filter(tag_value(&Model(1)));
+    exhaust(tagged.iter_immutable(&world), 3);
+    let mapped = query
+        .clone()
+        .map(|pos| pos.0)
+        .filter_entities(|_, x| *x < 1.);
+    exhaust(mapped.iter_immutable(&world), 3);
+
+    // stateful filters see no further changes once the iterator has been exhausted
+    let changed = Read::<Pos>::query().filter(changed::<Pos>());
+    exhaust(changed.iter_immutable(&world), 2004);
+    exhaust(changed.iter_immutable(&world), 0);
+
+    let empty = Read::<Scale>::query();
+    exhaust(empty.iter_immutable(&world), 0);
+    exhaust(empty.iter_chunks_immutable(&world), 0);
+}
+
+// checks the iterator's size hint against the number of items it actually yields at each step
+fn assert_hints<I: Iterator>(mut iter: I, expected: usize) {
+    for remaining in (0..=expected).rev() {
+        let (lower, upper) = iter.size_hint();
+        assert!(lower <= remaining);
+        assert!(upper.map_or(true, |upper| upper >= remaining));
+        if remaining > 0 {
+            assert!(iter.next().is_some());
+        } else {
+            assert!(iter.next().is_none());
+        }
+    }
+    assert_eq!((0, Some(0)), iter.size_hint());
+}
+
+#[test]
+fn query_iterator_size_hints() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1),), (0..3).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(2),), (0..2000).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((), vec![(Pos(0., 0., 0.), Rot(0., 0., 0.))]);
+    world.insert((), vec![(Rot(0., 0., 0.),)]);
+
+    let query = Read::<Pos>::query();
+    assert!(query.chunk_count(&world) > 3);
+    assert_hints(query.iter_immutable(&world), 2004);
+    assert_hints(query.iter_entities_immutable(&world), 2004);
+    assert_hints(query.iter(&mut world), 2004);
+    assert_hints(
+        query.iter_chunks_immutable(&world),
+        query.chunk_count(&world),
+    );
+
+    // the first chunk's entities are known as soon as iteration begins
+    let mut iter = query.iter_immutable(&world);
+    let first = query.iter_chunks_immutable(&world).next().unwrap().len();
+    iter.next();
+    assert_eq!(first - 1, iter.size_hint().0);
+
+    for mut chunk in query.iter_chunks_immutable(&world) {
+        let len = chunk.len();
+        assert_eq!(len, chunk.iter_entities().len());
+    }
+
+    let tagged = Read::<Pos>::query().filter(tag_value(&Model(1)));
+    assert_hints(tagged.iter_immutable(&world), 3);
+    let empty = Read::<Scale>::query();
+    assert_hints(empty.iter_immutable(&world), 0);
+}
+
+#[test]
+fn query_iterators_reverse() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1),), (0..3).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(2),), (0..2000).map(|i| (Pos(i as f32, 1., 0.),)));
+    world.insert((), vec![(Pos(0., 2., 0.), Rot(0., 0., 0.))]);
+    world.insert((), vec![(Rot(0., 0., 0.),)]);
+
+    let query = Read::<Pos>::query();
+    assert!(query.chunk_count(&world) > 3);
+    let forward = query
+        .iter_immutable(&world)
+        .map(|pos| *pos)
+        .collect::<Vec<_>>();
+    let mut reversed = query
+        .iter_immutable(&world)
+        .rev()
+        .map(|pos| *pos)
+        .collect::<Vec<_>>();
+    reversed.reverse();
+    assert_eq!(forward, reversed);
+
+    let forward = query
+        .iter_entities_immutable(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    let mut reversed = query
+        .iter_entities_immutable(&world)
+        .rev()
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    reversed.reverse();
+    assert_eq!(forward, reversed);
+
+    let chunks = |iter: &mut dyn Iterator<Item = Chunk<'_, Read<Pos>>>| {
+        iter.map(|mut chunk| chunk.iter_entities().map(|(e, _)| e).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+    let forward = chunks(&mut query.iter_chunks_immutable(&world));
+    let mut reversed = chunks(&mut query.iter_chunks_immutable(&world).rev());
+    reversed.reverse();
+    assert_eq!(forward, reversed);
+
+    let tagged = <(Read<Pos>, Tagged<Model>)>::query();
+    let forward = tagged
+        .iter_immutable(&world)
+        .map(|(pos, model)| (*pos, *model))
+        .collect::<Vec<_>>();
+    let mut reversed = tagged
+        .iter_immutable(&world)
+        .rev()
+        .map(|(pos, model)| (*pos, *model))
+        .collect::<Vec<_>>();
+    reversed.reverse();
+    assert_eq!(forward, reversed);
+
+    // iterating from both ends yields every item once, wherever the two ends meet
+    let entities = query
+        .iter_entities_immutable(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    for split in vec![0, 1, 3, 1000, 2004] {
+        let mut iter = query.iter_entities_immutable(&world);
+        let mut front = iter
+            .by_ref()
+            .take(split)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        let mut back = iter.by_ref().rev().map(|(e, _)| e).collect::<Vec<_>>();
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+        back.reverse();
+        front.extend(back);
+        assert_eq!(entities, front);
+
+        let mut iter = query.iter_entities_immutable(&world);
+        let mut back = iter
+            .by_ref()
+            .rev()
+            .take(split)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        let mut front = iter.by_ref().map(|(e, _)| e).collect::<Vec<_>>();
+        back.reverse();
+        front.extend(back);
+        assert_eq!(entities, front);
+    }
+
+    let mut iter = query.iter_chunks_immutable(&world);
+    let last = iter.next_back().unwrap().len();
+    assert_eq!(2004, last + iter.map(|chunk| chunk.len()).sum::<usize>());
+}
+
+#[test]
+fn query_iterators_fold() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1),), (0..3).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(2),), (0..2000).map(|i| (Pos(i as f32, 1., 0.),)));
+    world.insert((), vec![(Pos(0., 2., 0.), Rot(0., 0., 0.))]);
+
+    let query = Read::<Pos>::query();
+    let external = query
+        .iter_entities_immutable(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    let mut internal = Vec::new();
+    query.for_each_entities_immutable(&world, |(e, _)| internal.push(e));
+    assert_eq!(external, internal);
+
+    let mut positions = Vec::new();
+    query.for_each_immutable(&world, |pos| positions.push(*pos));
+    assert_eq!(
+        query
+            .iter_immutable(&world)
+            .map(|pos| *pos)
+            .collect::<Vec<_>>(),
+        positions
+    );
+
+    // folding a partially consumed iterator resumes within the chunks being iterated at each end
+    for split in vec![0, 1, 3, 1000] {
+        let mut iter = query.iter_entities_immutable(&world);
+        let mut folded = iter
+            .by_ref()
+            .take(split)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        let mut back = iter
+            .by_ref()
+            .rev()
+            .take(split)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        folded = iter.fold(folded, |mut acc, (e, _)| {
+            acc.push(e);
+            acc
+        });
+        back.reverse();
+        folded.extend(back);
+        assert_eq!(external, folded);
+
+        let mut iter = query.iter_immutable(&world);
+        iter.by_ref().take(split).count();
+        iter.by_ref().rev().take(split).count();
+        assert_eq!(2004 - split * 2, iter.fold(0, |n, _| n + 1));
+    }
+}
+
+#[test]
+fn query_filter_entity_count() {
+    use legion::filter::EntityFilter;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for (i, n) in vec![1, 10, 63, 64, 65, 3000].into_iter().enumerate() {
+        world.insert((Model(i as u32),), (0..n).map(|_| (Pos(0., 0., 0.),)));
+    }
+    let deleted = world.insert((Model(6),), (0..100).map(|_| (Pos(0., 0., 0.),)))[..40].to_vec();
+    for entity in deleted {
+        world.delete(entity);
+    }
+
+    let all = Read::<Pos>::query();
+    let gpu = Read::<Pos>::query().filter(min_entities(64));
+    let cpu = Read::<Pos>::query().filter(max_entities(63));
+
+    for chunk in gpu.iter_chunks_immutable(&world) {
+        assert!(chunk.len() >= 64);
+    }
+    for chunk in cpu.iter_chunks_immutable(&world) {
+        assert!(chunk.len() <= 63);
+    }
+
+    // every chunk goes down exactly one path
+    assert_eq!(
+        all.chunk_count(&world),
+        gpu.chunk_count(&world) + cpu.chunk_count(&world)
+    );
+    let gpu_entities = gpu.collect_entities(&world);
+    let cpu_entities = cpu.collect_entities(&world);
+    assert_eq!(
+        all.collect_entities(&world).len(),
+        gpu_entities.len() + cpu_entities.len()
+    );
+    let mut split = gpu_entities.into_iter().collect::<HashSet<_>>();
+    split.extend(cpu_entities);
+    assert_eq!(
+        all.collect_entities(&world)
+            .into_iter()
+            .collect::<HashSet<_>>(),
+        split
+    );
+
+    fn lens<F: EntityFilter>(query: &Query<Read<Pos>, F>, world: &World) -> Vec<usize> {
+        let mut lens = query
+            .iter_chunks_immutable(world)
+            .map(|chunk| chunk.len())
+            .collect::<Vec<_>>();
+        lens.sort();
+        lens
+    }
+    assert_eq!(vec![1, 10, 60, 63], lens(&cpu, &world));
+    assert!(lens(&gpu, &world).starts_with(&[64, 65]));
+
+    let between = Read::<Pos>::query().filter(min_entities(10) & max_entities(63));
+    assert_eq!(vec![10, 60, 63], lens(&between, &world));
+    let outside = Read::<Pos>::query().filter(!min_entities(10) | min_entities(65));
+    for n in lens(&outside, &world) {
+        assert!(!(10..65).contains(&n));
+    }
+
+    assert_eq!("min_entities(64)", min_entities(64).describe().to_string());
+    assert_eq!("max_entities(63)", max_entities(63).describe().to_string());
+}
+
+#[test]
+fn query_skips_empty_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), (0..3000).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let query = Read::<Pos>::query();
+    let chunks = query.chunk_count(&world);
+    assert!(chunks > 2);
+
+    // empty the first chunk, leaving it in place in front of the others
+    let first = query
+        .iter_chunks_immutable(&world)
+        .next()
+        .unwrap()
+        .entities()
+        .to_vec();
+    for entity in first.iter() {
+        world.delete(*entity);
+    }
+
+    assert_eq!(chunks - 1, query.chunk_count(&world));
+    for chunk in query.iter_chunks(&mut world) {
+        assert!(chunk.len() > 0);
+    }
+    for chunk in query.iter_chunks_immutable(&world).rev() {
+        assert!(chunk.len() > 0);
+    }
+    assert_eq!(3000 - first.len(), query.iter_immutable(&world).count());
+
+    let including_empty = query
+        .iter_chunks_including_empty_immutable(&world)
+        .map(|chunk| chunk.len())
+        .collect::<Vec<_>>();
+    assert_eq!(chunks, including_empty.len());
+    assert_eq!(0, including_empty[0]);
+    let empty = Read::<Pos>::query().filter(max_entities(0));
+    assert_eq!(0, empty.chunk_count(&world));
+    assert_eq!(
+        1,
+        empty.iter_chunks_including_empty_immutable(&world).count()
+    );
+
+    #[cfg(feature = "par-iter")]
+    {
+        use rayon::iter::ParallelIterator;
+        let par_chunks = ParallelIterator::count(query.par_iter_chunks(&mut world));
+        assert_eq!(chunks - 1, par_chunks);
+    }
+
+    // the empty chunk is not recorded by the changed filter, so it is reported once refilled
+    let changed = Read::<Pos>::query().filter(changed::<Pos>());
+    assert_eq!(chunks - 1, changed.iter_chunks_immutable(&world).count());
+    assert!(format!("{:?}", changed.filter_ref()).contains(&format!("entries: {}", chunks - 1)));
+    assert_eq!(0, changed.chunk_count(&world));
+
+    world.insert((), (0..first.len()).map(|i| (Pos(i as f32, 1., 0.),)));
+    let refilled = changed
+        .iter_chunks_immutable(&world)
+        .map(|chunk| chunk.len())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![first.len()], refilled);
+}
+
+#[test]
+fn query_iter_per_chunk() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1),), (0..3000).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(2),), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert(
+        (Static, Model(2)),
+        (0..20).map(|i| (Pos(i as f32, 0., 0.),)),
+    );
+
+    // one item per chunk, which together account for every entity
+    let query = Tagged::<Model>::query();
+    let chunks = query.iter_per_chunk(&world).collect::<Vec<_>>();
+    assert_eq!(query.chunk_count(&world), chunks.len());
+    assert!(chunks.len() > 3);
+    let mut counts = HashMap::<u32, usize>::new();
+    for (model, count) in chunks.iter() {
+        *counts.entry(model.0).or_default() += count;
+    }
+    assert_eq!(Some(&3000), counts.get(&1));
+    assert_eq!(Some(&30), counts.get(&2));
+    assert_eq!(3030, query.iter_immutable(&world).count());
+
+    let reversed = query.iter_per_chunk(&world).rev().collect::<Vec<_>>();
+    assert_eq!(chunks.iter().rev().cloned().collect::<Vec<_>>(), reversed);
+
+    // tuples of tags, and filters, are supported
+    let query = <(Tagged<Static>, Tagged<Model>)>::query();
+    let chunks = query
+        .iter_per_chunk(&world)
+        .map(|((_, model), count)| (model.0, count))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![(2, 20)], chunks);
+
+    let query = Tagged::<Model>::query().filter(tag_value(&Model(2)));
+    let total = query
+        .iter_per_chunk(&world)
+        .map(|(_, count)| count)
+        .sum::<usize>();
+    assert_eq!(30, total);
+}
+
+#[test]
+fn query_chunk_ids() {
+    use legion::storage::{ChunkId, ComponentTypeId, TagTypeId};
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1),), (0..3000).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(2),), vec![(Pos(0., 0., 0.), Rot(0., 0., 0.))]);
+
+    let ids = |world: &World| {
+        Read::<Pos>::query()
+            .iter_chunks_immutable(world)
+            .map(|chunk| (chunk.id(), chunk.entities().to_vec()))
+            .collect::<HashMap<_, _>>()
+    };
+    let first = ids(&world);
+    assert!(first.len() > 2);
+
+    // another query sees the same chunks under the same IDs
+    let rotations = <(Read<Rot>, Tagged<Model>)>::query();
+    for chunk in rotations.iter_chunks_immutable(&world) {
+        assert_eq!(Some(&chunk.entities().to_vec()), first.get(&chunk.id()));
+        let description = chunk.archetype_description();
+        assert!(description
+            .components()
+            .iter()
+            .any(|(t, _)| *t == ComponentTypeId::of::<Rot>()));
+        assert_eq!(TagTypeId::of::<Model>(), description.tags()[0].0);
+    }
+
+    // a chunk's ID is kept while its entities change
+    let (chunk_id, entities): (&ChunkId, _) = first
+        .iter()
+        .find(|(_, entities)| entities.len() > 2)
+        .unwrap();
+    let chunk_id = *chunk_id;
+    let removed = entities[..2].to_vec();
+    for entity in removed.iter() {
+        world.delete(*entity);
+    }
+    let second = ids(&world);
+    assert_eq!(first.len(), second.len());
+    assert!(!second[&chunk_id].contains(&removed[0]));
+    assert_eq!(first[&chunk_id].len() - 2, second[&chunk_id].len());
+
+    // chunks of the same archetype share its ID
+    let archetypes = second
+        .keys()
+        .map(|id| id.archetype_id())
+        .collect::<HashSet<_>>();
+    assert_eq!(2, archetypes.len());
+}
+
+#[test]
+fn chunk_iter_entities_nth_and_range() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert(
+            (),
+            (0..300).map(|i| (Pos(i as f32, 0., 0.), Vel(0., i as f32, 0.))),
+        )
+        .to_vec();
+
+    let query = <(Write<Pos>, TryRead<Rot>, Read<Vel>)>::query();
+    let mut chunk = query.iter_chunks(&mut world).next().unwrap();
+    assert_eq!(300, chunk.iter_entities().len());
+
+    let mut iter = chunk.iter_entities();
+    let (entity, (pos, rot, vel)) = iter.nth(128).unwrap();
+    assert_eq!(entities[128], entity);
+    assert_eq!(Pos(128., 0., 0.), *pos);
+    assert!(rot.is_none());
+    assert_eq!(Vel(0., 128., 0.), *vel);
+    assert_eq!(171, iter.len());
+    drop((pos, rot, vel));
+    assert_eq!(entities[129], iter.next().unwrap().0);
+    assert!(iter.nth(170).is_none());
+    assert!(iter.next().is_none());
+    drop(iter);
+
+    let skipped = chunk
+        .iter_entities()
+        .skip(290)
+        .map(|(e, (pos, _, _))| (e, pos.0))
+        .collect::<Vec<_>>();
+    let expected = (290..300)
+        .map(|i| (entities[i], i as f32))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, skipped);
+
+    let range = |chunk: &mut Chunk<_>, range| {
+        chunk
+            .iter_entities_range(range)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(entities[128..256].to_vec(), range(&mut chunk, 128..256));
+    assert_eq!(entities[256..].to_vec(), range(&mut chunk, 256..512));
+    assert!(range(&mut chunk, 400..512).is_empty());
+    assert!(range(&mut chunk, 10..5).is_empty());
+
+    let mut reversed = chunk
+        .iter_entities_range(10..20)
+        .rev()
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    reversed.reverse();
+    assert_eq!(entities[10..20].to_vec(), reversed);
+    assert_eq!(
+        entities[..3].to_vec(),
+        chunk
+            .iter_entities_range(..=2)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn query_iter_entities_sorted() {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    // every world allocates the same entities, but moves them into archetypes in a shuffled
+    // order, so that the archetypes are created in a different order
+    let build = |seed: u64| {
+        let mut world = World::new();
+        let entities = world
+            .insert((), (0..200).map(|i| (Pos(i as f32, 0., 0.),)))
+            .to_vec();
+        let mut order = entities.iter().copied().enumerate().collect::<Vec<_>>();
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+        for (i, entity) in order {
+            match i % 4 {
+                0 => world.add_component(entity, Rot(0., 0., 0.)),
+                1 => world.add_component(entity, Vel(0., 0., 0.)),
+                2 => world.add_tag(entity, Model(i as u32 % 3)),
+                _ => {}
+            }
+        }
+        world
+    };
+
+    let query = Read::<Pos>::query();
+    let natural = |world: &World| {
+        query
+            .iter_entities_immutable(world)
+            .map(|(e, pos)| (e, *pos))
+            .collect::<Vec<_>>()
+    };
+    let sorted = |world: &World| {
+        query
+            .iter_entities_sorted_immutable(world)
+            .map(|(e, pos)| (e, *pos))
+            .collect::<Vec<_>>()
+    };
+
+    // identical worlds iterate in the same natural order
+    let (a, b, c) = (build(1), build(1), build(2));
+    assert_eq!(natural(&a), natural(&b));
+    assert_ne!(natural(&a), natural(&c));
+
+    // sorted iteration is independent of the archetype creation order
+    assert_eq!(200, sorted(&a).len());
+    assert_eq!(sorted(&a), sorted(&b));
+    assert_eq!(sorted(&a), sorted(&c));
+    let positions = sorted(&c)
+        .into_iter()
+        .map(|(_, pos)| pos.0)
+        .collect::<Vec<_>>();
+    assert_eq!((0..200).map(|i| i as f32).collect::<Vec<_>>(), positions);
+
+    // sorted iteration can write
+    let mut c = c;
+    for (i, (_, mut pos)) in Write::<Pos>::query()
+        .iter_entities_sorted(&mut c)
+        .enumerate()
+    {
+        pos.1 = i as f32;
+    }
+    assert!(sorted(&c).iter().all(|(_, pos)| pos.0 == pos.1));
+}
+
+#[test]
+fn query_iter_sorted_by_key() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Depth(f32);
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Order(usize);
+
+    // non-negative floats are ordered by their bit patterns
+    let key = |depth: &Depth| depth.0.to_bits();
+
+    let mut world = Universe::new().create_world();
+    let depth = |i: usize| Depth(((i * 7919) % 101) as f32 / 4.);
+    world.insert((), (0..100).map(|i| (depth(i), Order(0))));
+    world.insert(
+        (),
+        (100..200).map(|i| (depth(i), Order(0), Pos(0., 0., 0.))),
+    );
+    world.insert(
+        (Model(1),),
+        (200..250).map(|i| (depth(i), Order(0), Rot(0., 0., 0.))),
+    );
+    world.insert((Model(2),), (250..300).map(|i| (depth(i), Order(0))));
+
+    let depths = Read::<Depth>::query()
+        .iter_sorted_by_key_immutable(&world, |depth| key(depth))
+        .map(|depth| depth.0)
+        .collect::<Vec<_>>();
+    assert_eq!(300, depths.len());
+    assert!(depths.windows(2).all(|w| w[0] <= w[1]));
+
+    // writable views are visited in order, without moving their components
+    let query = <(Read<Depth>, Write<Order>)>::query();
+    for (i, (_, mut order)) in query
+        .iter_sorted_by_key(&mut world, |(depth, _)| key(depth))
+        .enumerate()
+    {
+        order.0 = i;
+    }
+    let mut rows = Read::<Depth>::query()
+        .iter_entities(&mut world)
+        .map(|(entity, depth)| (entity, *depth))
+        .collect::<Vec<_>>();
+    let natural = rows.clone();
+    rows.sort_by_key(|(_, depth)| key(depth));
+    for (i, (entity, _)) in rows.into_iter().enumerate() {
+        assert_eq!(Order(i), *world.get_component::<Order>(entity).unwrap());
+    }
+    let after = Read::<Depth>::query()
+        .iter_entities(&mut world)
+        .map(|(entity, depth)| (entity, *depth))
+        .collect::<Vec<_>>();
+    assert_eq!(natural, after);
+}
+
+#[test]
+fn query_iter_with_chunk_boundaries() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    for model in 0..3 {
+        world.insert(
+            (Model(model),),
+            (0..2000).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+        );
+    }
+    world.insert((Model(3),), vec![(Pos(0., 0., 0.),)]);
+
+    let query = <(Read<Pos>, Tagged<Model>)>::query();
+    let chunks = query.chunk_count(&world);
+    assert!(chunks > 4);
+
+    // each chunk's boundary comes with its first item, and describes the following items
+    let mut boundaries = Vec::new();
+    let mut current: Option<ChunkBoundary> = None;
+    let mut remaining = 0;
+    for (boundary, (_, model)) in query.iter(&mut world).with_chunk_boundaries() {
+        if let Some(boundary) = boundary {
+            assert_eq!(0, remaining);
+            remaining = boundary.len();
+            boundaries.push(boundary.id());
+            current = Some(boundary);
+        }
+        assert_eq!(Some(model), current.unwrap().tag::<Model>());
+        remaining -= 1;
+    }
+    assert_eq!(0, remaining);
+    assert_eq!(chunks, boundaries.len());
+    let ids = query
+        .iter_chunks(&mut world)
+        .map(|chunk| chunk.id())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, boundaries);
+
+    // items remaining from a partially consumed chunk have no boundary
+    let mut iter = query.iter(&mut world);
+    iter.next();
+    let mut iter = iter.with_chunk_boundaries();
+    assert!(iter.next().unwrap().0.is_none());
+    assert_eq!(
+        chunks - 1,
+        iter.filter(|(boundary, _)| boundary.is_some()).count()
+    );
+
+    // writable views can be iterated while holding boundaries
+    let query = Write::<Pos>::query();
+    for (boundary, mut pos) in query.iter(&mut world).with_chunk_boundaries() {
+        if let Some(boundary) = boundary {
+            pos.1 = boundary.len() as f32;
+        }
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "was modified while being iterated by query legion::query::Tagged")]
+fn query_iter_detects_chunk_modified_during_iteration() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let entities = world
+        .insert((Model(0),), (0..10).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+
+    // a tag-only view holds no component borrows which would catch the modification
+    let query = Tagged::<Model>::query();
+    let world_ptr: *const World = &world;
+    let world_mut: *mut World = &mut world;
+    let mut iter = unsafe { query.iter_entities_unchecked(&*world_ptr) };
+    iter.next();
+
+    // removes an entity from the chunk being iterated behind the iterator's back
+    unsafe { (*world_mut).delete(entities[9]) };
+
+    for _ in iter {}
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn query_iter_allows_chunk_modified_after_iteration() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let entities = world
+        .insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+
+    let query = Read::<Pos>::query();
+    for _ in query.iter(&mut world) {}
+    for _ in query.iter_entities(&mut world) {}
+    world.delete(entities[0]);
+    assert_eq!(9, query.iter(&mut world).count());
+}
+
+#[test]
+fn query_without() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(0),), vec![(Pos(1., 0., 0.),)]);
+    world.insert((Model(1),), vec![(Pos(2., 0., 0.),)]);
+    world.insert((Model(1),), vec![(Pos(3., 0., 0.), Rot(0., 0., 0.))]);
+    world.insert((Model(1), Static), vec![(Pos(4., 0., 0.),)]);
+    world.insert((Static,), vec![(Pos(5., 0., 0.), Rot(0., 0., 0.))]);
+
+    fn values<F: legion::filter::EntityFilter>(
+        query: Query<Read<Pos>, F>,
+        world: &World,
+    ) -> Vec<f32> {
+        let mut values = query
+            .iter_immutable(world)
+            .map(|pos| pos.0)
+            .collect::<Vec<_>>();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        values
+    }
+
+    let query = Read::<Pos>::query();
+    assert_eq!(
+        vec![1., 2., 4.],
+        values(query.clone().filter(without::<Rot>()), &world)
+    );
+    assert_eq!(
+        values(query.clone().filter(!component::<Rot>()), &world),
+        values(query.clone().filter(without::<Rot>()), &world)
+    );
+    assert_eq!(
+        vec![1., 2., 3.],
+        values(query.clone().filter(without_tag::<Static>()), &world)
+    );
+
+    // composes with other filters
+    assert_eq!(
+        vec![2., 4.],
+        values(
+            query
+                .clone()
+                .filter(without::<Rot>() & tag_value(&Model(1))),
+            &world
+        )
+    );
+    assert_eq!(
+        vec![1., 2., 4., 5.],
+        values(
+            query.clone().filter(without::<Rot>() | tag::<Static>()),
+            &world
+        )
+    );
+    assert_eq!(
+        vec![1., 2.],
+        values(
+            query
+                .clone()
+                .filter(without::<Rot>() & without_tag::<Static>()),
+            &world
+        )
+    );
+    assert_eq!(
+        vec![3., 5.],
+        values(query.filter(!without::<Rot>()), &world)
+    );
+}
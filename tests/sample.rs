@@ -0,0 +1,98 @@
+#![cfg(feature = "rand")]
+
+use legion::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+#[test]
+fn sample_is_uniform() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    // matching entities are spread over several chunks of differing sizes
+    let mut world = Universe::new().create_world();
+    let mut entities = Vec::new();
+    entities.extend_from_slice(world.insert((), (0..10).map(|i| (Pos(i as f32),))));
+    for model in 0..5 {
+        let batch = world.insert((Model(model),), (0..100).map(|i| (Pos(i as f32),)));
+        entities.extend_from_slice(batch);
+    }
+    entities.extend_from_slice(world.insert((), (0..90).map(|i| (Pos(i as f32), Vel(0.)))));
+    world.insert((), (0..50).map(|i| (Vel(i as f32),)));
+
+    let query = Read::<Pos>::query();
+    assert_eq!(7, query.chunk_count(&world));
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut hits = HashMap::<Entity, usize>::new();
+    for _ in 0..2000 {
+        let sampled = query.sample(&world, &mut rng, 60);
+        assert_eq!(60, sampled.len());
+        assert_eq!(60, sampled.iter().collect::<HashSet<_>>().len());
+        for entity in sampled {
+            *hits.entry(entity).or_default() += 1;
+        }
+    }
+
+    // each entity is expected to be sampled 200 times, with a standard deviation of ~13.4
+    assert_eq!(entities.len(), hits.len());
+    for entity in &entities {
+        let count = hits[entity];
+        assert!(
+            count > 130 && count < 270,
+            "{} sampled {} times",
+            entity,
+            count
+        );
+    }
+}
+
+#[test]
+fn sample_more_than_matching() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let mut rng = StdRng::seed_from_u64(0);
+    let query = Read::<Pos>::query();
+    assert!(query.sample(&world, &mut rng, 5).is_empty());
+
+    let mut entities = world.insert((), vec![(Pos(0.),), (Pos(1.),)]).to_vec();
+    entities.extend_from_slice(world.insert((Model(1),), vec![(Pos(2.),)]));
+    world.insert((), vec![(Vel(0.),)]);
+
+    let sampled = query.sample(&world, &mut rng, 10);
+    assert_eq!(3, sampled.len());
+    assert_eq!(
+        entities.iter().collect::<HashSet<_>>(),
+        sampled.iter().collect::<HashSet<_>>()
+    );
+    assert!(query.sample(&world, &mut rng, 0).is_empty());
+
+    // filtered out entities are never sampled
+    let query = Read::<Pos>::query().filter(tag_value(&Model(1)));
+    assert_eq!(vec![entities[2]], query.sample(&world, &mut rng, 10));
+}
+
+#[test]
+fn sample_immutable() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    world.insert((), (0..300).map(|i| (Pos(i as f32), Vel(i as f32 * 2.))));
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let sampled = <(Read<Pos>, Read<Vel>)>::query().sample_immutable(&world, &mut rng, 20);
+    assert_eq!(20, sampled.len());
+    for (entity, (pos, vel)) in sampled {
+        assert_eq!(*pos, *world.get_component::<Pos>(entity).unwrap());
+        assert_eq!(vel.0, pos.0 * 2.);
+    }
+}
@@ -0,0 +1,173 @@
+use legion::collections::SecondaryMap;
+use legion::prelude::*;
+use legion::storage::ChunkIndex;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32, f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+#[test]
+fn secondary_map_rejects_stale_entities() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let old = world.insert((), vec![(Pos(1., 1.),)])[0];
+
+    let mut map = SecondaryMap::new();
+    assert_eq!(None, map.insert(old, 1));
+    assert_eq!(Some(1), map.insert(old, 2));
+    assert_eq!(Some(&2), map.get(old));
+
+    // the new entity reuses the deleted entity's index with a new version
+    world.delete(old);
+    let new = world.insert((), vec![(Pos(2., 2.),)])[0];
+    assert_eq!(old.to_bits() as u32, new.to_bits() as u32);
+    assert_ne!(old, new);
+
+    assert_eq!(None, map.get(new));
+    assert_eq!(None, map.get_mut(new));
+    assert_eq!(None, map.remove(new));
+    assert!(!map.contains_key(new));
+
+    // storing a value for the new entity replaces the stale value
+    assert_eq!(None, map.insert(new, 3));
+    assert_eq!(None, map.get(old));
+    assert_eq!(Some(&3), map.get(new));
+    assert_eq!(1, map.len());
+
+    // storing a value for the stale entity leaves the new entity's value in place
+    assert_eq!(None, map.insert(old, 4));
+    assert_eq!(None, map.get(old));
+    assert_eq!(Some(&3), map.get(new));
+    assert_eq!(1, map.len());
+
+    assert_eq!(Some(3), map.remove(new));
+    assert!(map.is_empty());
+}
+
+#[test]
+fn secondary_map_maintain_prunes_deleted_entities() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let mut map = SecondaryMap::with_events(&mut world);
+
+    let entities = world
+        .insert((Model(0),), (0..10).map(|i| (Pos(i as f32, 0.),)))
+        .to_vec();
+    for (i, entity) in entities.iter().enumerate() {
+        map.insert(*entity, i);
+    }
+
+    // entities moved between chunks are kept
+    world.add_component(entities[1], Vel(0., 0.));
+    world.add_tag(entities[2], Model(1));
+    world.delete(entities[3]);
+    world.delete(entities[4]);
+
+    assert_eq!(2, map.maintain(&world));
+    assert_eq!(8, map.len());
+    assert_eq!(Some(&1), map.get(entities[1]));
+    assert_eq!(Some(&2), map.get(entities[2]));
+    assert_eq!(None, map.get(entities[3]));
+    assert_eq!(0, map.maintain(&world));
+
+    // maps which were not subscribed to events are pruned with `auto_gc`
+    let mut unsubscribed = SecondaryMap::new();
+    for entity in &entities {
+        unsubscribed.insert(*entity, ());
+    }
+    assert_eq!(0, unsubscribed.maintain(&world));
+    assert_eq!(2, unsubscribed.auto_gc(&world));
+    assert_eq!(8, unsubscribed.len());
+}
+
+#[test]
+fn secondary_map_iterates_live_entries() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert((), (0..5).map(|i| (Pos(i as f32, 0.),)))
+        .to_vec();
+
+    let mut map = SecondaryMap::new();
+    for (i, entity) in entities.iter().enumerate() {
+        map.insert(*entity, i);
+    }
+    map.remove(entities[0]);
+    world.delete(entities[1]);
+    map.auto_gc(&world);
+
+    for (_, value) in map.iter_mut() {
+        *value *= 10;
+    }
+
+    let mut entries = map.iter().map(|(e, v)| (e, *v)).collect::<Vec<_>>();
+    entries.sort_by_key(|(_, v)| *v);
+    let expected = entities[2..]
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (*e, (i + 2) * 10))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, entries);
+
+    assert_eq!(1, map.retain(|entity, _| entity != entities[2]));
+    assert_eq!(2, map.len());
+}
+
+#[test]
+fn secondary_map_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    for model in 0..3 {
+        world.insert((Model(model),), vec![(Pos(0., 0.),)]);
+    }
+
+    let query = Read::<Pos>::query();
+    let chunks = query
+        .iter_chunks(&mut world)
+        .map(|chunk| chunk.dense_index())
+        .collect::<Vec<ChunkIndex>>();
+    assert_eq!(3, chunks.len());
+
+    let mut map = SecondaryMap::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        map.insert(*chunk, i);
+    }
+    assert_eq!(Some(&1), map.get(chunks[1]));
+
+    let other = universe.create_world();
+    assert_eq!(0, map.auto_gc(&other));
+
+    // chunk indices are released along with their world
+    drop(world);
+    assert_eq!(3, map.auto_gc(&other));
+    assert!(map.is_empty());
+    assert_eq!(None, map.get(chunks[0]));
+}
+
+#[test]
+fn secondary_map_generation_wraparound() {
+    use legion::collections::SecondaryKey;
+
+    let old = ChunkIndex::from_parts(0, u32::MAX);
+    let new = ChunkIndex::from_parts(0, 0);
+
+    let mut map = SecondaryMap::new();
+    map.insert(old, 1);
+
+    // the generation after `u32::MAX` is newer, not stale
+    assert_eq!(None, map.insert(new, 2));
+    assert_eq!(Some(&2), map.get(new));
+    assert_eq!(None, map.insert(old, 3));
+    assert_eq!(Some(&2), map.get(new));
+}
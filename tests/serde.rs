@@ -0,0 +1,66 @@
+#![cfg(feature = "serde-1")]
+
+use legion::entity::EntityAllocatorStats;
+use legion::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32, f32);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Message {
+    target: Entity,
+    #[serde(with = "legion::entity::serde_entity_map")]
+    scores: HashMap<Entity, u32>,
+}
+
+#[test]
+fn entity_round_trip() {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+
+    let message = Message {
+        target: entities[1],
+        scores: entities
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (*e, i as u32))
+            .collect(),
+    };
+
+    let json = serde_json::to_string(&message).unwrap();
+    let decoded: Message = serde_json::from_str(&json).unwrap();
+    assert_eq!(message, decoded);
+    assert!(world.is_alive(decoded.target));
+}
+
+#[test]
+fn entity_representation_is_stable() {
+    // version in the upper 32 bits, index in the lower 32 bits
+    let entity = Entity::from_bits((3 << 32) | 7);
+    assert_eq!("7#3", entity.to_string());
+    assert_eq!("12884901895", serde_json::to_string(&entity).unwrap());
+    assert_eq!(
+        entity,
+        serde_json::from_str::<Entity>("12884901895").unwrap()
+    );
+}
+
+#[test]
+fn allocator_stats_round_trip() {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), vec![(Pos(0., 0., 0.),)]);
+
+    let stats = world.allocator_stats();
+    let json = serde_json::to_string(&stats).unwrap();
+    assert_eq!(
+        stats,
+        serde_json::from_str::<EntityAllocatorStats>(&json).unwrap()
+    );
+}
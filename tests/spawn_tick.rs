@@ -0,0 +1,134 @@
+use legion::prelude::*;
+use legion::world::WorldOptions;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+fn create_world() -> World {
+    let mut options = WorldOptions::default();
+    options.record_spawn_tick = true;
+    Universe::new().create_world_with_options(options)
+}
+
+#[test]
+fn spawn_tick_per_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = create_world();
+    assert_eq!(0, world.tick());
+
+    let first = world.insert((), vec![(Pos(0.),), (Pos(1.),)]).to_vec();
+    assert_eq!(1, world.advance_tick());
+    assert_eq!(2, world.advance_tick());
+    let second = world.insert((), vec![(Pos(2.),)]).to_vec();
+
+    for entity in first.iter() {
+        assert_eq!(Some(0), world.spawn_tick(*entity));
+    }
+    assert_eq!(Some(2), world.spawn_tick(second[0]));
+
+    // entities keep their spawn tick when moved between archetypes
+    world.advance_tick();
+    world.add_component(first[0], Vel(0.));
+    world.delete(first[1]);
+    assert_eq!(Some(0), world.spawn_tick(first[0]));
+    assert_eq!(Some(2), world.spawn_tick(second[0]));
+    assert_eq!(None, world.spawn_tick(first[1]));
+
+    let query = Read::<Pos>::query();
+    let mut ticks = Vec::new();
+    for chunk in query.iter_chunks(&mut world) {
+        assert_eq!(chunk.entities().len(), chunk.spawn_ticks().len());
+        ticks.extend_from_slice(chunk.spawn_ticks());
+    }
+    ticks.sort();
+    assert_eq!(vec![0, 2], ticks);
+}
+
+#[test]
+fn spawned_before_skips_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = create_world();
+    for tick in 0..4 {
+        world.insert((Model(tick),), (0..10).map(|i| (Pos(i as f32),)));
+        world.advance_tick();
+    }
+
+    let query = Read::<Pos>::query().filter(spawned_before(2));
+    let mut chunks = 0;
+    for chunk in query.iter_chunks(&mut world) {
+        chunks += 1;
+        assert!(chunk.spawn_ticks().iter().all(|tick| *tick < 2));
+    }
+    assert_eq!(2, chunks);
+
+    assert_eq!(
+        0,
+        Read::<Pos>::query()
+            .filter(spawned_before(0))
+            .iter(&mut world)
+            .count()
+    );
+    assert_eq!(
+        40,
+        Read::<Pos>::query()
+            .filter(spawned_before(4))
+            .iter(&mut world)
+            .count()
+    );
+}
+
+#[test]
+fn spawned_before_matches_mixed_chunk() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = create_world();
+    world.insert((), vec![(Pos(0.),)]);
+    world.advance_tick();
+    world.advance_tick();
+    world.insert((), vec![(Pos(1.),)]);
+
+    // the chunk is matched, as it contains an entity spawned before the tick
+    let query = Read::<Pos>::query().filter(spawned_before(1));
+    let old = query
+        .iter_chunks(&mut world)
+        .flat_map(|chunk| {
+            chunk
+                .entities()
+                .iter()
+                .zip(chunk.spawn_ticks().iter())
+                .filter(|(_, tick)| **tick < 1)
+                .map(|(e, _)| *e)
+                .collect::<Vec<_>>()
+        })
+        .count();
+    assert_eq!(2, query.iter(&mut world).count());
+    assert_eq!(1, old);
+}
+
+#[test]
+fn spawn_tick_disabled() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    world.advance_tick();
+    let entity = world.insert((), vec![(Pos(0.),)])[0];
+
+    assert_eq!(1, world.tick());
+    assert_eq!(None, world.spawn_tick(entity));
+    assert_eq!(
+        0,
+        Read::<Pos>::query()
+            .filter(spawned_before(5))
+            .iter(&mut world)
+            .count()
+    );
+    for chunk in Read::<Pos>::query().iter_chunks(&mut world) {
+        assert!(chunk.spawn_ticks().is_empty());
+    }
+}
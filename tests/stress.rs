@@ -0,0 +1,157 @@
+use legion::command::CommandBuffer;
+use legion::prelude::*;
+use legion::testing::check_consistency;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+// Each component carries a sequence number, which is incremented by every mutation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Source(u64);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Derived {
+    seq: u64,
+    source: u64,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Group(u32);
+
+const ITERATIONS: usize = 100;
+
+fn seed() -> u64 {
+    std::env::var("LEGION_STRESS_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(0x5eed)
+}
+
+// decides from an entity and iteration alone whether a closure queues an insert, so that the
+// same entities are inserted however the work is split between threads
+fn spawns(entity: Entity, iteration: usize) -> bool {
+    let mut hasher = DefaultHasher::new();
+    (entity, iteration).hash(&mut hasher);
+    hasher.finish() % 97 == 0
+}
+
+/// Runs a parallel write query with a `changed` filter, which queues inserts from within its
+/// closure, alongside a downstream consumer which watches both of the components it touches
+/// through an `Or` of `changed` filters, and checks that the consumer observes every mutation
+/// exactly once.
+fn run(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut world = Universe::new().create_world();
+    for group in 0..4 {
+        let count = rng.gen_range(100, 1500);
+        world.insert(
+            (Group(group),),
+            (0..count).map(|_| (Source(0), Derived { seq: 0, source: 0 })),
+        );
+    }
+
+    let initial = world.memory_stats().entities;
+
+    let propagate = <(Read<Source>, Write<Derived>)>::query().filter(changed::<Source>());
+    let consumer =
+        <(Read<Source>, Read<Derived>)>::query().filter(changed::<Source>() | changed::<Derived>());
+    let all = <(Read<Source>, Read<Derived>)>::query();
+    let buffer = CommandBuffer::default();
+
+    // the latest sequence numbers of each entity observed by the consumer
+    let mut observed = HashMap::<Entity, (u64, u64)>::new();
+    let mut spawned = 0;
+
+    for iteration in 0..ITERATIONS {
+        // mutate a random subset of sources
+        let entities = all
+            .iter_entities(&mut world)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        for _ in 0..rng.gen_range(0, 64) {
+            let entity = entities[rng.gen_range(0, entities.len())];
+            world.get_component_mut::<Source>(entity).unwrap().0 += 1;
+        }
+
+        // propagate changed sources in parallel, queueing inserts from the closures
+        let queued = std::sync::atomic::AtomicUsize::new(0);
+        propagate.par_for_each_entities(&mut world, |(entity, (source, mut derived))| {
+            derived.seq += 1;
+            derived.source = source.0;
+            if spawns(entity, iteration) {
+                queued.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                buffer.insert(
+                    (Group(4),),
+                    vec![(Source(0), Derived { seq: 0, source: 0 })],
+                );
+            }
+        });
+        spawned += queued.into_inner();
+
+        // periodically flush the queued inserts and compact storage
+        let flushed = iteration % 10 == 9;
+        if flushed {
+            buffer.write(&mut world);
+            world.defrag(Some(rng.gen_range(1, 1000)));
+        }
+
+        // every mutation is propagated, and observed by the consumer exactly once
+        let mut delivered = 0;
+        for mut chunk in consumer.iter_chunks(&mut world) {
+            let mut fresh = false;
+            for (entity, (source, derived)) in chunk.iter_entities() {
+                let seqs = (source.0, derived.seq);
+                fresh |= observed.insert(entity, seqs) != Some(seqs);
+            }
+            // chunks may be re-delivered after entities are moved between them
+            assert!(
+                fresh || flushed,
+                "seed {}, iteration {}: chunk {:?} was delivered without any new mutations",
+                seed,
+                iteration,
+                chunk.id()
+            );
+            delivered += 1;
+        }
+
+        for (entity, (source, derived)) in all.iter_entities(&mut world) {
+            assert_eq!(
+                source.0, derived.source,
+                "seed {}: {} was not propagated",
+                seed, entity
+            );
+            assert_eq!(
+                Some(&(source.0, derived.seq)),
+                observed.get(&entity),
+                "seed {}, iteration {}: mutation of {} was not observed",
+                seed,
+                iteration,
+                entity
+            );
+        }
+
+        // both halves of the consumer's filter check every chunk it considers
+        let filter = &consumer.filter_ref().chunk_filter.filters.2.filters;
+        let (source, derived) = (filter.0.stats(), filter.1.stats());
+        assert_eq!(
+            source.checked, derived.checked,
+            "seed {}: the consumer's changed filters checked different chunks",
+            seed
+        );
+        assert!(source.matched + derived.matched >= delivered);
+    }
+
+    buffer.write(&mut world);
+    assert_eq!(Ok(()), check_consistency(&world));
+    assert_eq!(initial + spawned, world.memory_stats().entities);
+}
+
+#[test]
+fn parallel_changed_writes_with_queued_inserts() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let seed = seed();
+    for offset in 0..2 {
+        run(seed.wrapping_add(offset));
+    }
+}
@@ -0,0 +1,160 @@
+use legion::prelude::*;
+use legion::summary::{ComponentSummary, MinMaxSummary};
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+struct Health(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Team(u32);
+
+#[derive(Debug, PartialEq)]
+struct Bounds {
+    min: (f32, f32),
+    max: (f32, f32),
+}
+
+impl ComponentSummary<Pos> for Bounds {
+    fn summarize(components: &[Pos]) -> Self {
+        let mut bounds = Bounds {
+            min: (components[0].0, components[0].1),
+            max: (components[0].0, components[0].1),
+        };
+        for pos in components {
+            bounds.min = (bounds.min.0.min(pos.0), bounds.min.1.min(pos.1));
+            bounds.max = (bounds.max.0.max(pos.0), bounds.max.1.max(pos.1));
+        }
+        bounds
+    }
+}
+
+fn min_max(world: &mut World) -> Vec<(f32, f32)> {
+    let mut ranges = Read::<Health>::query()
+        .iter_chunks(world)
+        .filter_map(|chunk| chunk.summary::<Health, MinMaxSummary<Health>>())
+        .map(|summary| (summary.min.0, summary.max.0))
+        .collect::<Vec<_>>();
+    ranges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ranges
+}
+
+#[test]
+fn summary_tracks_mutations() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    world.enable_summary::<Health, MinMaxSummary<Health>>();
+
+    let entities = world
+        .insert((), (1..=5).map(|i| (Health(i as f32),)))
+        .to_vec();
+    assert_eq!(vec![(1., 5.)], min_max(&mut world));
+
+    for mut health in Write::<Health>::query().iter(&mut world) {
+        health.0 *= 2.;
+    }
+    assert_eq!(vec![(2., 10.)], min_max(&mut world));
+
+    world.delete(entities[4]);
+    assert_eq!(vec![(2., 8.)], min_max(&mut world));
+
+    world.insert((), vec![(Health(-1.),)]);
+    assert_eq!(vec![(-1., 8.)], min_max(&mut world));
+
+    *world.get_component_mut::<Health>(entities[1]).unwrap() = Health(20.);
+    assert_eq!(vec![(-1., 20.)], min_max(&mut world));
+}
+
+#[test]
+fn summary_tracks_mutations_within_write_epoch() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut options = legion::world::WorldOptions::default();
+    options.write_epochs = true;
+    let mut world = Universe::new().create_world_with_options(options);
+    world.enable_summary::<Health, MinMaxSummary<Health>>();
+    world.insert((), (1..=5).map(|i| (Health(i as f32),)));
+
+    // the second write does not advance the version, but still invalidates the summary
+    world.begin_write_epoch();
+    for mut health in Write::<Health>::query().iter(&mut world) {
+        health.0 *= 2.;
+    }
+    assert_eq!(vec![(2., 10.)], min_max(&mut world));
+    for mut health in Write::<Health>::query().iter(&mut world) {
+        health.0 += 10.;
+    }
+    assert_eq!(vec![(12., 20.)], min_max(&mut world));
+    let query = Read::<Health>::query().filter(component_range(Health(15.)..));
+    assert_eq!(1, query.iter_chunks(&mut world).count());
+}
+
+#[test]
+fn component_range_skips_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    for team in 0..4 {
+        let base = team as f32 * 10.;
+        world.insert((Team(team),), (0..10).map(|i| (Health(base + i as f32),)));
+    }
+
+    // without a summary, every chunk containing the component matches
+    let query = Read::<Health>::query().filter(component_range(Health(12.)..Health(15.)));
+    assert_eq!(4, query.iter_chunks(&mut world).count());
+    assert_eq!(40, query.iter(&mut world).count());
+
+    world.enable_summary::<Health, MinMaxSummary<Health>>();
+    let mut chunks = query.iter_chunks(&mut world);
+    let chunk = chunks.next().unwrap();
+    assert_eq!(Some(&Team(1)), chunk.tag::<Team>());
+    assert!(chunks.next().is_none());
+    let matching = query
+        .iter(&mut world)
+        .filter(|h| h.0 >= 12. && h.0 < 15.)
+        .count();
+    assert_eq!(3, matching);
+
+    // bounds are respected at the edges of each chunk
+    let query = Read::<Health>::query().filter(component_range(Health(9.)..Health(10.)));
+    assert_eq!(1, query.iter_chunks(&mut world).count());
+    let query = Read::<Health>::query().filter(component_range(Health(9.)..=Health(10.)));
+    assert_eq!(2, query.iter_chunks(&mut world).count());
+    let query = Read::<Health>::query().filter(component_range(Health(35.)..));
+    assert_eq!(1, query.iter_chunks(&mut world).count());
+
+    // chunks are reconsidered once their components are modified
+    for (team, mut health) in <(Tagged<Team>, Write<Health>)>::query().iter(&mut world) {
+        if team.0 == 3 {
+            health.0 -= 20.;
+        }
+    }
+    let query = Read::<Health>::query().filter(component_range(Health(12.)..Health(15.)));
+    assert_eq!(2, query.iter_chunks(&mut world).count());
+}
+
+#[test]
+fn custom_summary() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    world.enable_summary::<Pos, Bounds>();
+    world.insert(
+        (),
+        vec![(Pos(1., 5.), Health(1.)), (Pos(-3., 2.), Health(2.))],
+    );
+
+    for chunk in Read::<Pos>::query().iter_chunks(&mut world) {
+        let bounds = chunk.summary::<Pos, Bounds>().unwrap();
+        assert_eq!(
+            Bounds {
+                min: (-3., 2.),
+                max: (1., 5.)
+            },
+            *bounds
+        );
+
+        // other summary types and components are not maintained
+        assert!(chunk.summary::<Health, MinMaxSummary<Health>>().is_none());
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "index")]
+
+use legion::index::*;
+use legion::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32, f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32, f32);
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Model(u32);
+
+impl GridPosition for Pos {
+    fn grid_position(&self) -> (f32, f32) { (self.0, self.1) }
+}
+
+fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+    entities.sort_by_key(|e| e.to_string());
+    entities
+}
+
+#[test]
+fn index_initial_population() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let entities = world
+        .insert((), vec![(Pos(1., 1.),), (Pos(15., 1.),), (Pos(25., 25.),)])
+        .to_vec();
+
+    let mut index = SyncedIndex::<Pos, _>::new(&mut world, UniformGrid::new(10.));
+    assert_eq!(3, index.update(&world));
+    assert_eq!(3, index.backend().len());
+
+    assert_eq!(
+        sorted(vec![entities[0], entities[1]]),
+        sorted(index.query_region(&Rect::new((0., 0.), (20., 20.))))
+    );
+
+    // nothing has changed, so nothing should be re-read
+    assert_eq!(0, index.update(&world));
+}
+
+#[test]
+fn index_tracks_movement() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let mut index = SyncedIndex::<Pos, _>::new(&mut world, UniformGrid::new(10.));
+
+    let moving = world
+        .insert((), (0..10).map(|i| (Pos(i as f32, 0.), Vel(10., 0.))))
+        .to_vec();
+    let fixed = world
+        .insert((Model(1),), (0..10).map(|i| (Pos(i as f32, 0.),)))
+        .to_vec();
+
+    assert_eq!(20, index.update(&world));
+    assert_eq!(20, index.backend().cell_moves());
+
+    let query = <(Write<Pos>, Read<Vel>)>::query();
+    for (mut pos, vel) in query.iter(&mut world) {
+        pos.0 += vel.0;
+        pos.1 += vel.1;
+    }
+
+    // only the chunk containing the moving entities was written
+    assert_eq!(10, index.update(&world));
+    assert_eq!(30, index.backend().cell_moves());
+
+    assert_eq!(
+        sorted(fixed.clone()),
+        sorted(index.query_region(&Rect::new((0., 0.), (9.5, 1.))))
+    );
+    assert_eq!(
+        sorted(moving.clone()),
+        sorted(index.query_region(&Rect::new((10., 0.), (20., 1.))))
+    );
+}
+
+#[test]
+fn index_tracks_structural_changes() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let mut index = SyncedIndex::<Pos, _>::new(&mut world, UniformGrid::new(10.));
+
+    let entities = world
+        .insert((), vec![(Pos(1., 1.),), (Pos(2., 2.),), (Pos(3., 3.),)])
+        .to_vec();
+    index.update(&world);
+
+    // relocating an entity into a new archetype keeps it in the index
+    world.add_component(entities[0], Vel(0., 0.));
+    // removing the indexed component removes it from the index
+    world.remove_component::<Pos>(entities[1]);
+    // deleting removes it from the index
+    world.delete(entities[2]);
+
+    index.update(&world);
+
+    assert_eq!(1, index.backend().len());
+    assert_eq!(
+        vec![entities[0]],
+        index.query_region(&Rect::new((0., 0.), (10., 10.)))
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn index_defers_borrowed_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    let mut index = SyncedIndex::<Pos, _>::new(&mut world, UniformGrid::new(10.));
+    let entity = world.insert((), vec![(Pos(1., 1.),)])[0];
+    world.insert((Model(1),), vec![(Pos(2., 2.),)]);
+
+    // a chunk which is being written to is skipped, rather than read while it changes
+    let query = Write::<Pos>::query().filter(!tag::<Model>());
+    let mut chunk = unsafe { query.iter_chunks_unchecked(&world) }
+        .next()
+        .unwrap();
+    let mut borrow = chunk.iter();
+    assert_eq!(1, index.update(&world));
+    assert_eq!(1, index.query_region(&Rect::new((0., 0.), (5., 5.))).len());
+
+    borrow.next().unwrap().0 = 25.;
+    drop(borrow);
+    drop(chunk);
+
+    // the skipped chunk is read by the next update
+    assert_eq!(1, index.update(&world));
+    assert_eq!(
+        vec![entity],
+        index.query_region(&Rect::new((20., 0.), (30., 5.)))
+    );
+    assert_eq!(0, index.update(&world));
+}
@@ -0,0 +1,176 @@
+use legion::prelude::*;
+use legion::world::UniverseOptions;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Seq(u32);
+
+#[test]
+fn create_worlds_concurrently() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let handles = (0..8)
+        .map(|_| {
+            let universe = universe.clone();
+            std::thread::spawn(move || {
+                let mut world = universe.create_world();
+                let entities = world
+                    .insert((), (0..3000).map(|i| (Pos(i as f32),)))
+                    .to_vec();
+                assert!(world.universe().ptr_eq(&universe));
+                (world, entities)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let results = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .collect::<Vec<_>>();
+
+    let mut ids = HashSet::new();
+    let mut world_ids = HashSet::new();
+    for (world, entities) in results.iter() {
+        world_ids.insert(world.id());
+        for entity in entities {
+            assert!(ids.insert(*entity), "{} allocated by two worlds", entity);
+        }
+    }
+    assert_eq!(8, world_ids.len());
+    assert_eq!(8 * 3000, ids.len());
+
+    let stats = universe.stats();
+    assert_eq!(8, stats.worlds_created);
+    assert_eq!(8 * 3, stats.entity_blocks_allocated);
+    assert_eq!(0, stats.entity_blocks_free);
+}
+
+#[test]
+fn entity_blocks_shared_between_handles() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let other = universe.clone();
+    assert!(universe.ptr_eq(&other));
+    assert!(!universe.ptr_eq(&Universe::new()));
+
+    let mut world = universe.create_world();
+    world.insert((), vec![(Pos(0.),)]);
+    drop(world);
+
+    // blocks released by one handle's world are reused by another's
+    let stats = other.stats();
+    assert_eq!(1, stats.entity_blocks_allocated);
+    assert_eq!(1, stats.entity_blocks_free);
+
+    let mut world = other.create_world();
+    world.insert((), vec![(Pos(0.),)]);
+    let stats = universe.stats();
+    assert_eq!(2, stats.worlds_created);
+    assert_eq!(1, stats.entity_blocks_allocated);
+    assert_eq!(0, stats.entity_blocks_free);
+}
+
+#[test]
+fn standalone_world_has_own_universe() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let a = World::new();
+    let b = World::new();
+    assert!(!a.universe().ptr_eq(b.universe()));
+    assert_eq!(1, a.universe().stats().worlds_created);
+}
+
+fn index(entity: Entity) -> u32 { entity.to_bits() as u32 }
+
+// a scripted scenario, returning every entity ID allocated by it in the order it was allocated
+fn scripted_scenario(universe: &Universe) -> Vec<Entity> {
+    let mut ids = Vec::new();
+
+    let mut world = universe.create_world();
+    ids.extend_from_slice(world.insert((), (0..10).map(|i| (Pos(i as f32),))));
+
+    // a world dropped on another thread returns its blocks to the universe
+    let mut scratch = universe.create_world();
+    ids.extend_from_slice(scratch.insert((), (0..2000).map(|i| (Pos(i as f32),))));
+    std::thread::spawn(move || drop(scratch)).join().unwrap();
+
+    for entity in ids[2..5].to_vec() {
+        world.delete(entity);
+    }
+    ids.extend_from_slice(world.insert((), (0..1500).map(|i| (Pos(i as f32),))));
+
+    // inserts queued by systems which run in parallel are assigned IDs when flushed
+    let systems = (0..4)
+        .map(|n| {
+            SystemBuilder::new(format!("spawn {}", n)).build(move |cmd, _, _, _| {
+                cmd.insert((), (0..50).map(move |i| (Seq(n * 100 + i),)))
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut schedule = systems
+        .into_iter()
+        .fold(Schedule::builder(), |builder, system| {
+            builder.add_system(system)
+        })
+        .build();
+    schedule.execute(&mut world);
+
+    let mut queued = Read::<Seq>::query()
+        .iter_entities(&mut world)
+        .map(|(entity, seq)| (seq.0, entity))
+        .collect::<Vec<_>>();
+    queued.sort_by_key(|(seq, _)| *seq);
+    ids.extend(queued.into_iter().map(|(_, entity)| entity));
+
+    let mut other = universe.create_world();
+    ids.extend_from_slice(other.insert((), (0..10).map(|i| (Pos(i as f32),))));
+    ids
+}
+
+#[test]
+fn deterministic_entities_replay() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let options = UniverseOptions::default().with_deterministic_entities(true);
+    let first = Universe::with_options(options);
+    let second = Universe::with_options(options);
+    assert!(second.options().deterministic_entities);
+
+    let ids = scripted_scenario(&first);
+    assert_eq!(10 + 2000 + 1500 + 200 + 10, ids.len());
+    assert_eq!(ids, scripted_scenario(&second));
+
+    // blocks returned by dropped worlds are not reused
+    let stats = first.stats();
+    assert_eq!(0, stats.entity_blocks_free);
+    assert_eq!(2 + 2 + 1, stats.entity_blocks_allocated);
+    let entity = first.create_world().insert((), vec![(Pos(0.),)])[0];
+    assert!(ids.iter().all(|id| index(*id) != index(entity)));
+}
+
+#[test]
+fn queued_inserts_assigned_ids_in_order() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = Universe::new().create_world();
+    let cmd = CommandBuffer::default();
+    cmd.insert((), vec![(Seq(0),), (Seq(1),)]);
+    cmd.insert((), vec![(Seq(2),)]);
+    cmd.write(&mut world);
+
+    let inserted = world.insert((), vec![(Seq(3),)])[0];
+    let mut ids = Read::<Seq>::query()
+        .iter_entities(&mut world)
+        .map(|(entity, seq)| (seq.0, index(entity)))
+        .collect::<Vec<_>>();
+    ids.sort_by_key(|(seq, _)| *seq);
+    let expected = (0..4).map(|i| index(inserted) - 3 + i).collect::<Vec<_>>();
+    assert_eq!(
+        expected,
+        ids.into_iter().map(|(_, index)| index).collect::<Vec<_>>()
+    );
+}
@@ -1,4 +1,5 @@
 use legion::prelude::*;
+use legion::world::{ComponentFnSource, InsertError};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct Pos(f32, f32, f32);
@@ -29,6 +30,105 @@ fn insert() {
     assert_eq!(2, entities.len());
 }
 
+#[test]
+fn insert_from_fn() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut expected = universe.create_world();
+    let mut world = universe.create_world();
+
+    let components = |i: usize| (Pos(i as f32, 0., 0.), Rot(0., i as f32, 0.));
+
+    // spans several chunks
+    let count = 5000;
+    let expected_entities = expected
+        .insert((Model(5),), (0..count).map(components))
+        .to_vec();
+    let entities = world
+        .insert_from_fn((Model(5),), count, components)
+        .to_vec();
+
+    assert_eq!(count, entities.len());
+
+    let query = <(Read<Pos>, Read<Rot>, Tagged<Model>)>::query();
+    let expected_data = query
+        .iter_immutable(&expected)
+        .map(|(pos, rot, model)| (*pos, *rot, *model))
+        .collect::<Vec<_>>();
+    let data = query
+        .iter_immutable(&world)
+        .map(|(pos, rot, model)| (*pos, *rot, *model))
+        .collect::<Vec<_>>();
+    assert_eq!(expected_data, data);
+
+    for (i, (a, b)) in expected_entities.iter().zip(entities.iter()).enumerate() {
+        assert_eq!(
+            *expected.get_component::<Pos>(*a).unwrap(),
+            *world.get_component::<Pos>(*b).unwrap()
+        );
+        assert_eq!(
+            Rot(0., i as f32, 0.),
+            *world.get_component::<Rot>(*b).unwrap()
+        );
+    }
+}
+
+#[test]
+fn insert_from_fn_empty() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world.insert_from_fn((), 0, |i| (Pos(i as f32, 0., 0.),));
+    assert!(entities.is_empty());
+}
+
+#[test]
+fn try_insert_duplicate_types() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1), Static), vec![(Pos(0., 0., 0.),)]);
+
+    let err = world
+        .try_insert((Model(1), Model(2)), vec![(Pos(0., 0., 0.),)])
+        .unwrap_err();
+    assert!(matches!(err, InsertError::DuplicateTag(name) if name.ends_with("Model")));
+
+    let err = world
+        .try_insert(
+            (),
+            vec![(Pos(0., 0., 0.), Rot(0., 0., 0.), Pos(1., 0., 0.))],
+        )
+        .unwrap_err();
+    assert!(matches!(err, InsertError::DuplicateComponent(name) if name.ends_with("Pos")));
+    assert!(err.to_string().contains("Pos"));
+
+    let source = ComponentFnSource::new(3, |_| (Rot(0., 0., 0.), Rot(1., 0., 0.)));
+    let err = world.try_insert((), source).unwrap_err();
+    assert!(matches!(err, InsertError::DuplicateComponent(name) if name.ends_with("Rot")));
+
+    // nothing was inserted by the failed calls
+    assert_eq!(1, Read::<Pos>::query().iter(&mut world).count());
+    assert_eq!(0, Read::<Rot>::query().iter(&mut world).count());
+
+    let entities = world
+        .try_insert((Model(3),), vec![(Pos(0., 0., 0.),)])
+        .unwrap();
+    assert_eq!(1, entities.len());
+}
+
+#[test]
+#[should_panic(expected = "duplicate tag type")]
+fn insert_duplicate_tag_panics() {
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((Model(1), Static, Model(2)), vec![(Pos(0., 0., 0.),)]);
+}
+
 #[test]
 fn get_component() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -297,6 +397,87 @@ fn mutate_remove_component() {
     assert_eq!(2, query_with_rot.iter(&mut world).count());
 }
 
+#[derive(Debug)]
+struct Counted(u32, std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+impl Drop for Counted {
+    fn drop(&mut self) { self.1.fetch_add(1, std::sync::atomic::Ordering::SeqCst); }
+}
+
+#[test]
+fn take_component() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let entities = world
+        .insert(
+            (),
+            (0..3).map(|i| (Pos(i as f32, 0., 0.), Counted(i, drops.clone()))),
+        )
+        .to_vec();
+
+    let taken = world.take_component::<Counted>(entities[1]).unwrap();
+    assert_eq!(1, taken.0);
+    assert_eq!(0, drops.load(Ordering::SeqCst));
+    assert!(world.get_component::<Counted>(entities[1]).is_none());
+    assert_eq!(
+        Pos(1., 0., 0.),
+        *world.get_component::<Pos>(entities[1]).unwrap()
+    );
+    assert!(world.take_component::<Counted>(entities[1]).is_none());
+
+    drop(taken);
+    assert_eq!(1, drops.load(Ordering::SeqCst));
+
+    // the remaining entities are unaffected
+    assert_eq!(0, world.get_component::<Counted>(entities[0]).unwrap().0);
+    assert_eq!(2, world.get_component::<Counted>(entities[2]).unwrap().0);
+
+    drop(world);
+    assert_eq!(3, drops.load(Ordering::SeqCst));
+}
+
+#[test]
+fn replace_component() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let entity = world.insert((), vec![(Pos(0., 0., 0.), Counted(1, drops.clone()))])[0];
+
+    let query = Read::<Counted>::query().filter(changed::<Counted>());
+    assert_eq!(1, query.iter(&mut world).count());
+
+    let old = world
+        .replace_component(entity, Counted(2, drops.clone()))
+        .unwrap();
+    assert_eq!(1, old.0);
+    assert_eq!(2, world.get_component::<Counted>(entity).unwrap().0);
+    assert_eq!(1, query.iter(&mut world).count());
+    assert_eq!(0, drops.load(Ordering::SeqCst));
+    drop(old);
+    assert_eq!(1, drops.load(Ordering::SeqCst));
+
+    // the new value is dropped if the entity does not have the component
+    world.remove_component::<Counted>(entity);
+    assert_eq!(2, drops.load(Ordering::SeqCst));
+    assert!(world
+        .replace_component(entity, Counted(3, drops.clone()))
+        .is_none());
+    assert_eq!(3, drops.load(Ordering::SeqCst));
+}
+
 #[test]
 fn mutate_add_tag() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -416,3 +597,245 @@ fn mutate_change_tag() {
 
     assert_eq!(2, query_model_5.iter(&mut world).count());
 }
+
+#[test]
+fn mutate_add_component_beyond_first_chunk_keeps_tags() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    // fill several chunks in the first chunkset, then create a second chunkset
+    let entities = world
+        .insert((Model(5),), (0..5000).map(|_| (Pos(1., 2., 3.),)))
+        .to_vec();
+    world.insert((Model(3),), vec![(Pos(4., 5., 6.),)]);
+
+    let entity = *entities.last().unwrap();
+    assert!(world.entity_location(entity).unwrap().chunk().as_usize() > 0);
+
+    world.add_component(entity, Rot(0.1, 0.2, 0.3));
+    assert_eq!(Some(&Model(5)), world.get_tag::<Model>(entity));
+}
+
+#[test]
+fn entity_location() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((Model(1),), vec![(Pos(0., 0., 0.),)]);
+    let entities = world
+        .insert((Model(2),), vec![(Pos(1., 2., 3.),), (Pos(4., 5., 6.),)])
+        .to_vec();
+
+    let location = world.entity_location(entities[1]).unwrap();
+    assert_eq!(1, location.set().as_usize());
+    assert_eq!(0, location.chunk().as_usize());
+    assert_eq!(1, location.component().as_usize());
+
+    assert_eq!(
+        location.archetype(),
+        world.entity_location(entities[0]).unwrap().archetype()
+    );
+
+    let query = Read::<Pos>::query().filter(tag_value(&Model(2)));
+    let chunk = query.iter_chunks(&mut world).next().unwrap();
+    assert_eq!(
+        entities[1],
+        chunk.entities()[location.component().as_usize()]
+    );
+
+    world.delete(entities[1]);
+    assert!(world.entity_location(entities[1]).is_none());
+}
+
+#[test]
+fn dyn_filter_matches_static_filter() {
+    use legion::filter::DynFilter;
+    use std::collections::HashSet;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert(
+        (Model(1),),
+        (0..10).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert(
+        (Model(2),),
+        (0..10).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((Model(1),), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    // a filter tree assembled at runtime, as an editor would construct it
+    fn build_filter(model: &Model, require_rot: bool) -> Box<dyn DynFilter + '_> {
+        if require_rot {
+            Box::new(component::<Rot>() & tag_value(model))
+        } else {
+            Box::new(tag_value(model))
+        }
+    }
+
+    let model = Model(1);
+    let filter = build_filter(&model, true);
+
+    let query = Read::<Pos>::query().filter(component::<Rot>() & tag_value(&model));
+    let expected = query
+        .iter_entities_immutable(&world)
+        .map(|(e, _)| e)
+        .collect::<HashSet<_>>();
+
+    let matching = world.entities_matching(filter.as_ref());
+    assert_eq!(expected.len(), matching.len());
+    assert_eq!(expected, matching.into_iter().collect::<HashSet<_>>());
+
+    assert_eq!(10, world.delete_all(filter.as_ref()));
+    for entity in expected {
+        assert!(!world.is_alive(entity));
+    }
+    assert!(world.entities_matching(filter.as_ref()).is_empty());
+    assert_eq!(20, world.entities_matching(&component::<Pos>()).len());
+}
+
+#[test]
+fn location_epoch() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((Model(1),), (0..4).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    let mut epoch = world.location_epoch();
+    let mut bumped = |world: &World| {
+        let previous = std::mem::replace(&mut epoch, world.location_epoch());
+        previous != epoch
+    };
+
+    // appending to chunks and writing data does not move entities
+    world.insert((Model(1),), vec![(Pos(9., 0., 0.),)]);
+    assert!(!bumped(&world));
+    world.add_component(entities[0], Pos(5., 0., 0.));
+    assert!(!bumped(&world));
+
+    world.delete(entities[1]);
+    assert!(bumped(&world));
+    world.add_component(entities[2], Rot(0., 0., 0.));
+    assert!(bumped(&world));
+    world.remove_component::<Rot>(entities[2]);
+    assert!(bumped(&world));
+    world.add_tag(entities[3], Static);
+    assert!(bumped(&world));
+    world.remove_tag::<Static>(entities[3]);
+    assert!(bumped(&world));
+
+    // defrag bumps the epoch only if it moves entities
+    world.defrag(None);
+    assert!(!bumped(&world));
+    let many = world
+        .insert((Model(2),), (0..5000).map(|_| (Pos(0., 0., 0.),)))
+        .to_vec();
+    assert!(!bumped(&world));
+    for entity in many.iter().take(2000) {
+        world.delete(*entity);
+    }
+    assert!(bumped(&world));
+    world.defrag(None);
+    assert!(bumped(&world));
+
+    world.merge(universe.create_world());
+    assert!(bumped(&world));
+}
+
+#[test]
+fn get_component_at() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    let epoch = world.location_epoch();
+    let cached = entities
+        .iter()
+        .map(|e| (*e, world.entity_location(*e).unwrap()))
+        .collect::<Vec<_>>();
+
+    for (i, (entity, location)) in cached.iter().enumerate() {
+        let pos = world
+            .get_component_at::<Pos>(*entity, *location, epoch)
+            .unwrap();
+        assert_eq!(Pos(i as f32, 0., 0.), *pos);
+    }
+
+    // deleting the first entity moves the last into its slot
+    world.delete(entities[0]);
+    assert_ne!(epoch, world.location_epoch());
+    let (entity, location) = cached[2];
+    assert_eq!(
+        Pos(2., 0., 0.),
+        *world
+            .get_component_at::<Pos>(entity, location, epoch)
+            .unwrap()
+    );
+    let (entity, location) = cached[0];
+    assert!(world
+        .get_component_at::<Pos>(entity, location, epoch)
+        .is_none());
+
+    // a location belonging to another entity is not trusted
+    let location = world.entity_location(entities[1]).unwrap();
+    let epoch = world.location_epoch();
+    assert_eq!(
+        Pos(2., 0., 0.),
+        *world
+            .get_component_at::<Pos>(entities[2], location, epoch)
+            .unwrap()
+    );
+}
+
+#[derive(Clone, Copy)]
+struct Huge([u8; 32 * 1024]);
+
+#[test]
+fn oversized_component() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert(
+            (),
+            (0..3u8).map(|i| (Huge([i; 32 * 1024]), Pos(i as f32, 0., 0.))),
+        )
+        .to_vec();
+
+    let query = <(Read<Huge>, Read<Pos>)>::query();
+    assert_eq!(3, query.chunk_count(&world));
+    for (huge, pos) in query.iter(&mut world) {
+        assert!(huge.0.iter().all(|b| *b as f32 == pos.0));
+    }
+
+    world.delete(entities[1]);
+    assert_eq!(2, query.count(&world));
+    assert_eq!(2, world.get_component::<Huge>(entities[2]).unwrap().0[100]);
+}
+
+#[test]
+#[should_panic(expected = "larger than a chunk")]
+fn oversized_component_denied() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut options = legion::world::WorldOptions::default();
+    options.deny_oversized_components = true;
+    let mut world = Universe::new().create_world_with_options(options);
+    world.insert((), vec![(Huge([0; 32 * 1024]),)]);
+}
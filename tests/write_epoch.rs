@@ -0,0 +1,273 @@
+use legion::prelude::*;
+use legion::query::ChunkVersion;
+use legion::world::WorldOptions;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pos(f32);
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vel(f32);
+
+fn create_world(write_epochs: bool) -> World {
+    let mut options = WorldOptions::default();
+    options.write_epochs = write_epochs;
+    Universe::new().create_world_with_options(options)
+}
+
+fn version(world: &mut World) -> legion::storage::Version {
+    let (_, version) = <(Read<Pos>, ChunkVersion<Pos>)>::query()
+        .iter(world)
+        .next()
+        .unwrap();
+    version
+}
+
+fn write(world: &mut World) {
+    for mut pos in Write::<Pos>::query().iter(world) {
+        pos.0 += 1.;
+    }
+}
+
+#[test]
+fn write_epoch_bumps_once() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = create_world(true);
+    let entity = world.insert((), vec![(Pos(0.),)])[0];
+    let initial = version(&mut world);
+
+    world.begin_write_epoch();
+    write(&mut world);
+    let first = version(&mut world);
+    assert!(first.is_newer_than(initial));
+    write(&mut world);
+    *world.get_component_mut::<Pos>(entity).unwrap() = Pos(5.);
+    assert_eq!(first, version(&mut world));
+
+    world.begin_write_epoch();
+    write(&mut world);
+    let second = version(&mut world);
+    assert!(second.is_newer_than(first));
+    write(&mut world);
+    assert_eq!(second, version(&mut world));
+
+    // every write bumps the version outside of an epoch
+    world.end_write_epoch();
+    write(&mut world);
+    let third = version(&mut world);
+    assert!(third.is_newer_than(second));
+    write(&mut world);
+    assert!(version(&mut world).is_newer_than(third));
+}
+
+#[test]
+fn write_epoch_disabled_by_default() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = create_world(false);
+    world.insert((), vec![(Pos(0.),)]);
+
+    world.begin_write_epoch();
+    write(&mut world);
+    let first = version(&mut world);
+    write(&mut world);
+    assert!(version(&mut world).is_newer_than(first));
+}
+
+#[test]
+fn write_epoch_per_system() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = create_world(true);
+    world.insert((), vec![(Pos(0.), Vel(1.))]);
+
+    let versions = Arc::new(Mutex::new(Vec::new()));
+    let recorded = versions.clone();
+    let integrate = SystemBuilder::new("integrate")
+        .with_query(<(Write<Pos>, Read<Vel>)>::query())
+        .with_query(<(Read<Pos>, ChunkVersion<Pos>)>::query())
+        .build(move |_, world, _, (write, read)| {
+            for _ in 0..2 {
+                for (mut pos, vel) in write.iter(world) {
+                    pos.0 += vel.0;
+                }
+                for (_, version) in read.iter_immutable(world) {
+                    recorded.lock().unwrap().push(version);
+                }
+            }
+        });
+    let mut schedule = Schedule::builder().add_system(integrate).build();
+
+    schedule.execute(&mut world);
+    schedule.execute(&mut world);
+
+    let versions = versions.lock().unwrap();
+    assert_eq!(4, versions.len());
+    assert_eq!(versions[0], versions[1]);
+    assert_eq!(versions[2], versions[3]);
+    assert!(versions[2].is_newer_than(versions[1]));
+    assert_eq!(
+        Pos(4.),
+        Read::<Pos>::query()
+            .iter(&mut world)
+            .next()
+            .map(|p| *p)
+            .unwrap()
+    );
+
+    // the schedule ends its epochs, so writes outside of systems are not missed
+    write(&mut world);
+    assert!(version(&mut world).is_newer_than(versions[3]));
+}
+
+#[test]
+fn write_epoch_changes_after_reader_are_not_lost() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    // the number of changed chunks seen by the reader; writing it orders the systems
+    #[derive(Default)]
+    struct Seen(usize);
+
+    let mut world = create_world(true);
+    world.insert((), vec![(Pos(0.),)]);
+    world.resources.insert(Seen::default());
+
+    // writers only write on the first run
+    let writer = |name: &'static str| {
+        let mut first = true;
+        SystemBuilder::new(name)
+            .write_resource::<Seen>()
+            .with_query(Write::<Pos>::query())
+            .build(move |_, world, _, query| {
+                if std::mem::take(&mut first) {
+                    for mut pos in query.iter(world) {
+                        pos.0 += 1.;
+                    }
+                }
+            })
+    };
+    let reader = SystemBuilder::new("reader")
+        .write_resource::<Seen>()
+        .with_query(Read::<Pos>::query().filter(changed::<Pos>()))
+        .build(move |_, world, seen, query| {
+            seen.0 = query.iter(world).count();
+        });
+    let mut schedule = Schedule::builder()
+        .add_system(writer("first"))
+        .add_system(reader)
+        .add_system(writer("second"))
+        .build();
+
+    // the reader runs between the writers, so it sees the second write on the next run
+    let mut seen = Vec::new();
+    for _ in 0..3 {
+        schedule.execute(&mut world);
+        seen.push(world.resources.get::<Seen>().unwrap().0);
+    }
+    assert_eq!(vec![1, 1, 0], seen);
+}
+
+#[test]
+#[cfg(feature = "par-schedule")]
+fn write_epoch_per_parallel_system() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut world = create_world(true);
+    world.insert((), vec![(Pos(0.),)]);
+    world.insert((), vec![(Vel(0.),)]);
+
+    fn writer<T: legion::storage::Component>(
+        name: &'static str,
+        bump: fn(&mut T),
+        recorded: Arc<Mutex<Vec<legion::storage::Version>>>,
+    ) -> Box<dyn Schedulable> {
+        SystemBuilder::new(name)
+            .with_query(Write::<T>::query())
+            .with_query(<(Read<T>, ChunkVersion<T>)>::query())
+            .build(move |_, world, _, (write, read)| {
+                for _ in 0..3 {
+                    for mut value in write.iter(world) {
+                        bump(&mut value);
+                    }
+                    for (_, version) in read.iter_immutable(world) {
+                        recorded.lock().unwrap().push(version);
+                    }
+                }
+            })
+    }
+
+    // `pos` and `vel` may run in parallel; `pos_again` waits for `pos`
+    let pos = Arc::new(Mutex::new(Vec::new()));
+    let vel = Arc::new(Mutex::new(Vec::new()));
+    let mut schedule = Schedule::builder()
+        .add_system(writer::<Pos>("pos", |p| p.0 += 1., pos.clone()))
+        .add_system(writer::<Vel>("vel", |v| v.0 += 1., vel.clone()))
+        .add_system(writer::<Pos>("pos_again", |p| p.0 += 1., pos.clone()))
+        .build();
+
+    let mut last = None;
+    for _ in 0..10 {
+        schedule.execute(&mut world);
+
+        // every write within a system shares the system's version
+        let pos = std::mem::take(&mut *pos.lock().unwrap());
+        let vel = std::mem::take(&mut *vel.lock().unwrap());
+        assert_eq!(6, pos.len());
+        assert_eq!(3, vel.len());
+        assert!(pos[..3].iter().all(|v| *v == pos[0]));
+        assert!(pos[3..].iter().all(|v| *v == pos[3]));
+        assert!(vel.iter().all(|v| *v == vel[0]));
+
+        // and each system is a distinct change
+        assert!(pos[3].is_newer_than(pos[0]));
+        if let Some((p, v)) = last {
+            assert!(pos[0].is_newer_than(p));
+            assert!(vel[0].is_newer_than(v));
+        }
+        last = Some((pos[3], vel[0]));
+    }
+    assert_eq!(
+        Pos(60.),
+        Read::<Pos>::query()
+            .iter(&mut world)
+            .next()
+            .map(|p| *p)
+            .unwrap()
+    );
+}
+
+#[test]
+fn write_epoch_reset_on_merge() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut options = WorldOptions::default();
+    options.write_epochs = true;
+    let mut world = universe.create_world_with_options(options);
+    let mut other = universe.create_world_with_options(options);
+    world.insert((), vec![(Pos(0.),)]);
+    other.insert((), vec![(Pos(0.),)]);
+
+    // both worlds' first epochs share an ID
+    other.begin_write_epoch();
+    write(&mut other);
+    other.end_write_epoch();
+    world.begin_write_epoch();
+    world.merge(other);
+
+    // the merged chunk was not written within this world's epoch
+    let versions = <(Read<Pos>, ChunkVersion<Pos>)>::query()
+        .iter(&mut world)
+        .map(|(_, version)| version)
+        .collect::<Vec<_>>();
+    write(&mut world);
+    let written = <(Read<Pos>, ChunkVersion<Pos>)>::query()
+        .iter(&mut world)
+        .map(|(_, version)| version)
+        .collect::<Vec<_>>();
+    assert_eq!(2, written.len());
+    assert!(written
+        .iter()
+        .zip(&versions)
+        .all(|(w, v)| w.is_newer_than(*v)));
+}